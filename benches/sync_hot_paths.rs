@@ -0,0 +1,79 @@
+use criterion::{criterion_group, criterion_main, BenchmarkId, Criterion, Throughput};
+use std::hint::black_box;
+use tether::bench::{generate_synthetic_repo, synthetic_content, synthetic_state};
+
+/// Typical dotfile, large config, and oversized generated file
+const SIZES: [usize; 3] = [4 * 1024, 64 * 1024, 1024 * 1024];
+
+fn hashing(c: &mut Criterion) {
+    let mut group = c.benchmark_group("sha256");
+    for size in SIZES {
+        let data = synthetic_content(size, size);
+        group.throughput(Throughput::Bytes(size as u64));
+        group.bench_with_input(BenchmarkId::from_parameter(size), &data, |b, data| {
+            b.iter(|| tether::sha256_hex(black_box(data)))
+        });
+    }
+    group.finish();
+}
+
+fn encryption(c: &mut Criterion) {
+    let key = tether::security::generate_key();
+    let mut group = c.benchmark_group("aes_gcm");
+    for size in SIZES {
+        let data = synthetic_content(size, size);
+        let encrypted = tether::security::encrypt(&data, &key).unwrap();
+        group.throughput(Throughput::Bytes(size as u64));
+        group.bench_with_input(BenchmarkId::new("encrypt", size), &data, |b, data| {
+            b.iter(|| tether::security::encrypt(black_box(data), &key).unwrap())
+        });
+        group.bench_with_input(BenchmarkId::new("decrypt", size), &encrypted, |b, enc| {
+            b.iter(|| tether::security::decrypt(black_box(enc), &key).unwrap())
+        });
+    }
+    group.finish();
+}
+
+fn directory_scan(c: &mut Criterion) {
+    let mut group = c.benchmark_group("walkdir");
+    for files in [1_000, 10_000] {
+        let tmp = tempfile::TempDir::new().unwrap();
+        generate_synthetic_repo(tmp.path(), files, 256).unwrap();
+        group.throughput(Throughput::Elements(files as u64));
+        group.bench_with_input(BenchmarkId::from_parameter(files), &tmp, |b, tmp| {
+            b.iter(|| {
+                walkdir::WalkDir::new(tmp.path())
+                    .into_iter()
+                    .filter_map(|e| e.ok())
+                    .filter(|e| e.file_type().is_file())
+                    .count()
+            })
+        });
+    }
+    group.finish();
+}
+
+fn state_serialization(c: &mut Criterion) {
+    let mut group = c.benchmark_group("sync_state");
+    for files in [500, 5_000, 50_000] {
+        let state = synthetic_state(files);
+        let json = serde_json::to_string_pretty(&state).unwrap();
+        group.throughput(Throughput::Elements(files as u64));
+        group.bench_with_input(BenchmarkId::new("serialize", files), &state, |b, state| {
+            b.iter(|| serde_json::to_string_pretty(black_box(state)).unwrap())
+        });
+        group.bench_with_input(BenchmarkId::new("deserialize", files), &json, |b, json| {
+            b.iter(|| serde_json::from_str::<tether::sync::SyncState>(black_box(json)).unwrap())
+        });
+    }
+    group.finish();
+}
+
+criterion_group!(
+    benches,
+    hashing,
+    encryption,
+    directory_scan,
+    state_serialization
+);
+criterion_main!(benches);
@@ -0,0 +1,37 @@
+//! tether's sync engine for embedding in other tools: config, syncing, package
+//! managers and encryption, without the command line or dashboard.
+//!
+//! This is [`tether::api`], the part of tether that follows semver; see there for
+//! what it covers and an example.
+
+pub use tether::api::*;
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// What embedders compile against; a change here is a breaking release
+    #[test]
+    fn test_public_api() {
+        assert!(!VERSION.is_empty());
+
+        let key = generate_key();
+        let ciphertext = encrypt(b"export EDITOR=vim", &key).unwrap();
+        assert_eq!(decrypt(&ciphertext, &key).unwrap(), b"export EDITOR=vim");
+
+        assert!(set_context(Some("not a name")).is_err());
+        set_context(Some("work")).unwrap();
+        assert!(sync_path().unwrap().ends_with(".tether-work/sync"));
+        set_context(None).unwrap();
+        assert!(sync_path().unwrap().ends_with(".tether/sync"));
+
+        assert!(!Config::default().has_personal_repo());
+        let _ = (
+            sync,
+            daemon_status,
+            decrypt_file,
+            set_quiet,
+            SyncMode::PullOnly,
+        );
+    }
+}
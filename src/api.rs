@@ -0,0 +1,88 @@
+//! The API for embedding tether in other tools, also published as the `tether-core`
+//! crate. Everything here follows semver: items are only added or deprecated within a
+//! major version, and serialized types only gain fields. The rest of the crate is
+//! tether's own internals and can change in any release.
+//!
+//! Build with `default-features = false` to leave out the command line and dashboard
+//! (clap, ratatui). Everything works on the current user's tether setup, as the
+//! `tether` command does: `~/.tether`, or the context named by [`set_context`].
+//!
+//! ```no_run
+//! # async fn run() -> anyhow::Result<()> {
+//! use tether::api::{self, SyncMode};
+//!
+//! api::set_quiet(true);
+//! let config = api::Config::load()?;
+//! if config.has_personal_repo() {
+//!     api::sync(SyncMode::Full).await?;
+//! }
+//! let report = api::DriftReport::build(&config, &api::SyncState::load()?, &api::sync_path()?)?;
+//! println!("{}", report.summary());
+//! # Ok(())
+//! # }
+//! ```
+
+use anyhow::Result;
+use std::path::PathBuf;
+
+pub use crate::config::{
+    Config, DotfileEntry, FeaturesConfig, LogFormat, MachineRole, NotificationEvent,
+    NotificationsConfig, PowerPolicy, ProfileConfig, Severity, SyncConfig,
+};
+pub use crate::daemon::ipc::{ContextStatus, DaemonStatus};
+pub use crate::packages::{
+    BrewManager, BunManager, GemManager, NpmManager, PackageInfo, PackageManager, PnpmManager,
+    UvManager,
+};
+pub use crate::security::{
+    decrypt, encrypt, generate_key, scan_for_secrets, SecretFinding, SecretType,
+};
+pub use crate::sync::report::{DriftReport, MachineDrift};
+pub use crate::sync::{
+    FileState, GitBackend, MachineDiff, MachineState, PackageChanges, SyncMode, SyncState,
+};
+
+/// The version of tether this API comes from
+pub const VERSION: &str = env!("CARGO_PKG_VERSION");
+
+/// Work on the tether context `name` (its own config, sync repo and daemon), or the
+/// default one with `None`. Applies to the whole process, like `--context`.
+pub fn set_context(name: Option<&str>) -> Result<()> {
+    match name {
+        Some(name) if !crate::config::is_valid_context_name(name) => anyhow::bail!(
+            "Invalid context name '{}': use letters, digits, '-' and '_'",
+            name
+        ),
+        Some(name) => std::env::set_var(crate::config::CONTEXT_ENV, name),
+        None => std::env::remove_var(crate::config::CONTEXT_ENV),
+    }
+    Ok(())
+}
+
+/// Print nothing but warnings and errors: no progress or status lines
+pub fn set_quiet(quiet: bool) {
+    crate::cli::Progress::set_quiet(quiet);
+}
+
+/// Where the sync repo is checked out
+pub fn sync_path() -> Result<PathBuf> {
+    crate::sync::SyncEngine::sync_path()
+}
+
+/// Sync dotfiles and packages as `tether sync` does, without ever reading stdin.
+/// Conflicts it can't resolve on its own are left for `tether resolve`, as the daemon
+/// does; a locked key or files changed outside a sync stop it with an error.
+pub async fn sync(mode: SyncMode) -> Result<()> {
+    crate::cli::commands::sync::run(false, false, false, false, mode).await
+}
+
+/// What the daemon serving the current context is doing, or `None` when it isn't
+/// running
+pub fn daemon_status() -> Option<DaemonStatus> {
+    crate::daemon::ipc::query()
+}
+
+/// Decrypt with the current context's key, unlocking it from the keychain if needed
+pub fn decrypt_file(content: &[u8]) -> Result<Vec<u8>> {
+    decrypt(content, &crate::security::get_encryption_key()?)
+}
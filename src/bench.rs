@@ -0,0 +1,289 @@
+//! Synthetic workloads for profiling sync hot paths.
+//!
+//! Shared by the criterion benches in `benches/` and the hidden `tether bench` command,
+//! so both measure the same operations: hashing, encryption, directory scans and
+//! state serialization. `tether bench --startup` also times command startup.
+
+use crate::sync::state::{FileState, SyncState};
+use anyhow::Result;
+use chrono::Utc;
+use rayon::prelude::*;
+use std::collections::{BTreeMap, HashMap, HashSet};
+use std::path::Path;
+use std::time::{Duration, Instant};
+
+/// Files per synthetic config directory (mirrors typical `~/.config/<app>/` fan-out)
+const FILES_PER_DIR: usize = 25;
+
+/// Deterministic dotfile-like text so runs are comparable across machines.
+pub fn synthetic_content(seed: usize, size: usize) -> Vec<u8> {
+    let mut out = Vec::with_capacity(size);
+    let mut n = seed as u64 ^ 0x9e37_79b9_7f4a_7c15;
+    while out.len() < size {
+        // xorshift keeps lines varied without pulling in a rng dependency
+        n ^= n << 13;
+        n ^= n >> 7;
+        n ^= n << 17;
+        let line = format!("export TETHER_BENCH_{}=\"{:016x}\"\n", n % 1000, n);
+        out.extend_from_slice(line.as_bytes());
+    }
+    out.truncate(size);
+    out
+}
+
+/// Populate `root` with `files` synthetic config files of `file_size` bytes each,
+/// laid out like a synced `~/.config` tree. Returns total bytes written.
+pub fn generate_synthetic_repo(root: &Path, files: usize, file_size: usize) -> Result<u64> {
+    let mut total = 0u64;
+    for i in 0..files {
+        let dir = root
+            .join("profiles/dev/config")
+            .join(format!("app{}", i / FILES_PER_DIR));
+        std::fs::create_dir_all(&dir)?;
+        let content = synthetic_content(i, file_size);
+        std::fs::write(
+            dir.join(format!("file{}.conf", i % FILES_PER_DIR)),
+            &content,
+        )?;
+        total += content.len() as u64;
+    }
+    Ok(total)
+}
+
+/// Build a `SyncState` tracking `files` entries, for serialization benchmarks.
+pub fn synthetic_state(files: usize) -> SyncState {
+    let now = Utc::now();
+    let files = (0..files)
+        .map(|i| {
+            (
+                format!("~/.config/app{}/file{}.conf", i / FILES_PER_DIR, i),
+                FileState {
+                    hash: crate::sha256_hex(i.to_string().as_bytes()),
+                    last_modified: now,
+                    synced: true,
+                    disk: None,
+                    repo: None,
+                },
+            )
+        })
+        .collect();
+    SyncState {
+        machine_id: "bench".to_string(),
+        last_sync: now,
+        files,
+        packages: HashMap::new(),
+        last_upgrade: None,
+        last_upgrade_with_updates: None,
+        deferred_casks: Vec::new(),
+        deferred_casks_hash: None,
+        pending_installs: BTreeMap::new(),
+        dismissed_imports: HashSet::new(),
+        deleted_files: HashMap::new(),
+        repo_visibility: None,
+        last_report: None,
+        repo_sizes: Vec::new(),
+    }
+}
+
+/// Timing for one hot path
+pub struct BenchResult {
+    pub name: &'static str,
+    pub items: usize,
+    pub bytes: u64,
+    pub elapsed: Duration,
+}
+
+impl BenchResult {
+    /// Throughput in MiB/s (0 when nothing was measured)
+    pub fn mib_per_sec(&self) -> f64 {
+        let secs = self.elapsed.as_secs_f64();
+        if secs == 0.0 || self.bytes == 0 {
+            return 0.0;
+        }
+        self.bytes as f64 / (1024.0 * 1024.0) / secs
+    }
+}
+
+/// Run every hot path once against the files under `root` and the given state.
+/// Encryption uses a throwaway key so profiling never touches the user's real key.
+pub fn run_suite(root: &Path, state: &SyncState) -> Result<Vec<BenchResult>> {
+    let mut results = Vec::new();
+
+    let start = Instant::now();
+    let paths: Vec<_> = walkdir::WalkDir::new(root)
+        .follow_links(false)
+        .into_iter()
+        .filter_map(|e| e.ok())
+        .filter(|e| e.file_type().is_file())
+        .filter(|e| !e.path().components().any(|c| c.as_os_str() == ".git"))
+        .map(|e| e.into_path())
+        .collect();
+    results.push(BenchResult {
+        name: "walkdir scan",
+        items: paths.len(),
+        bytes: 0,
+        elapsed: start.elapsed(),
+    });
+
+    let contents: Vec<Vec<u8>> = paths.iter().filter_map(|p| std::fs::read(p).ok()).collect();
+    let bytes: u64 = contents.iter().map(|c| c.len() as u64).sum();
+
+    let start = Instant::now();
+    for content in &contents {
+        std::hint::black_box(crate::sha256_hex(content));
+    }
+    results.push(BenchResult {
+        name: "sha256 hashing",
+        items: contents.len(),
+        bytes,
+        elapsed: start.elapsed(),
+    });
+
+    let key = crate::security::generate_key();
+    let start = Instant::now();
+    let mut encrypted = Vec::with_capacity(contents.len());
+    for content in &contents {
+        encrypted.push(crate::security::encrypt(content, &key)?);
+    }
+    results.push(BenchResult {
+        name: "encryption",
+        items: contents.len(),
+        bytes,
+        elapsed: start.elapsed(),
+    });
+
+    let start = Instant::now();
+    for data in &encrypted {
+        std::hint::black_box(crate::security::decrypt(data, &key)?);
+    }
+    results.push(BenchResult {
+        name: "decryption",
+        items: encrypted.len(),
+        bytes,
+        elapsed: start.elapsed(),
+    });
+
+    // Same work spread across the worker pool, as sync does for synced dirs
+    let start = Instant::now();
+    contents
+        .par_iter()
+        .map(|content| crate::security::encrypt(content, &key))
+        .collect::<Result<Vec<_>>>()?;
+    results.push(BenchResult {
+        name: "encryption (parallel)",
+        items: contents.len(),
+        bytes,
+        elapsed: start.elapsed(),
+    });
+
+    let start = Instant::now();
+    encrypted
+        .par_iter()
+        .map(|data| crate::security::decrypt(data, &key))
+        .collect::<Result<Vec<_>>>()?;
+    results.push(BenchResult {
+        name: "decryption (parallel)",
+        items: encrypted.len(),
+        bytes,
+        elapsed: start.elapsed(),
+    });
+
+    let start = Instant::now();
+    let json = serde_json::to_string_pretty(state)?;
+    let _: SyncState = serde_json::from_str(&json)?;
+    results.push(BenchResult {
+        name: "state round-trip",
+        items: state.files.len(),
+        bytes: json.len() as u64,
+        elapsed: start.elapsed(),
+    });
+
+    Ok(results)
+}
+
+/// Runs per startup phase; each phase reports its mean
+pub const STARTUP_RUNS: u32 = 20;
+
+fn time_phase(
+    name: &'static str,
+    bytes: u64,
+    mut phase: impl FnMut() -> Result<usize>,
+) -> Result<BenchResult> {
+    let mut items = 0;
+    let start = Instant::now();
+    for _ in 0..STARTUP_RUNS {
+        items = phase()?;
+    }
+    Ok(BenchResult {
+        name,
+        items,
+        bytes,
+        elapsed: start.elapsed() / STARTUP_RUNS,
+    })
+}
+
+/// Time what read-only commands like `tether status` do before printing anything:
+/// loading config and state, opening the sync repo, counting unpushed commits and
+/// asking the daemon for its status. Uses this machine's real files.
+pub fn run_startup_suite() -> Result<Vec<BenchResult>> {
+    let file_size = |path: &Path| std::fs::metadata(path).map(|m| m.len()).unwrap_or(0);
+    let config_path = crate::config::Config::config_path()?;
+    let state_path = SyncState::state_path()?;
+    let sync_path = crate::sync::SyncEngine::sync_path()?;
+
+    let mut results = vec![
+        time_phase("config load", file_size(&config_path), || {
+            Ok(crate::config::Config::load()?.profiles.len())
+        })?,
+        time_phase("state load", file_size(&state_path), || {
+            Ok(SyncState::load()?.files.len())
+        })?,
+        time_phase("conflicts load", 0, || {
+            Ok(crate::sync::ConflictState::load()?.conflicts.len())
+        })?,
+        time_phase("repo open", 0, || {
+            crate::sync::GitBackend::open(&sync_path)?;
+            Ok(1)
+        })?,
+        time_phase("unpushed commits", 0, || {
+            crate::sync::GitBackend::open(&sync_path)?.unpushed_commits()
+        })?,
+        time_phase("daemon query", 0, || {
+            Ok(crate::daemon::ipc::query().map_or(0, |s| s.contexts.len()))
+        })?,
+    ];
+    let total = results.iter().map(|r| r.elapsed).sum();
+    results.push(BenchResult {
+        name: "total",
+        items: 0,
+        bytes: 0,
+        elapsed: total,
+    });
+    Ok(results)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::TempDir;
+
+    #[test]
+    fn test_synthetic_content_is_deterministic() {
+        assert_eq!(synthetic_content(7, 512), synthetic_content(7, 512));
+        assert_ne!(synthetic_content(7, 512), synthetic_content(8, 512));
+        assert_eq!(synthetic_content(1, 100).len(), 100);
+    }
+
+    #[test]
+    fn test_run_suite_on_synthetic_repo() {
+        let tmp = TempDir::new().unwrap();
+        let bytes = generate_synthetic_repo(tmp.path(), 30, 256).unwrap();
+        assert_eq!(bytes, 30 * 256);
+
+        let results = run_suite(tmp.path(), &synthetic_state(30)).unwrap();
+        let scan = results.iter().find(|r| r.name == "walkdir scan").unwrap();
+        assert_eq!(scan.items, 30);
+        let hashing = results.iter().find(|r| r.name == "sha256 hashing").unwrap();
+        assert_eq!(hashing.bytes, bytes);
+    }
+}
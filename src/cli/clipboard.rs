@@ -0,0 +1,100 @@
+//! Putting secrets on the system clipboard instead of the terminal, using the
+//! platform's clipboard tools (pbcopy, wl-copy, xclip or xsel).
+
+use anyhow::Result;
+use std::io::{Read, Write};
+use std::process::{Command, Stdio};
+use std::time::Duration;
+
+/// Copy and paste commands for this system
+fn tools() -> Result<(Command, Command)> {
+    let pair = |copy: &[&str], paste: &[&str]| {
+        let mut copy_cmd = Command::new(copy[0]);
+        copy_cmd.args(&copy[1..]);
+        let mut paste_cmd = Command::new(paste[0]);
+        paste_cmd.args(&paste[1..]);
+        (copy_cmd, paste_cmd)
+    };
+    if cfg!(target_os = "macos") {
+        return Ok(pair(&["pbcopy"], &["pbpaste"]));
+    }
+    if std::env::var_os("WAYLAND_DISPLAY").is_some() && which::which("wl-copy").is_ok() {
+        return Ok(pair(&["wl-copy"], &["wl-paste", "--no-newline"]));
+    }
+    if which::which("xclip").is_ok() {
+        return Ok(pair(
+            &["xclip", "-selection", "clipboard"],
+            &["xclip", "-selection", "clipboard", "-o"],
+        ));
+    }
+    if which::which("xsel").is_ok() {
+        return Ok(pair(
+            &["xsel", "--clipboard", "--input"],
+            &["xsel", "--clipboard", "--output"],
+        ));
+    }
+    anyhow::bail!("No clipboard tool found; install wl-clipboard, xclip or xsel")
+}
+
+fn copy(text: &str) -> Result<()> {
+    let (mut command, _) = tools()?;
+    // xclip and wl-copy keep serving the selection from a forked child; don't wait on
+    // the pipes it inherits
+    let mut child = command
+        .stdin(Stdio::piped())
+        .stdout(Stdio::null())
+        .stderr(Stdio::null())
+        .spawn()?;
+    if let Some(mut stdin) = child.stdin.take() {
+        stdin.write_all(text.as_bytes())?;
+    }
+    if !child.wait()?.success() {
+        anyhow::bail!("Could not copy to the clipboard");
+    }
+    Ok(())
+}
+
+fn paste() -> Result<String> {
+    let (_, mut command) = tools()?;
+    let output = command.stderr(Stdio::null()).output()?;
+    if !output.status.success() {
+        anyhow::bail!("Could not read the clipboard");
+    }
+    Ok(String::from_utf8_lossy(&output.stdout).into_owned())
+}
+
+/// Copy `secret` and, when `clear_after` is non-zero, start a background process that
+/// empties the clipboard after that many seconds unless something else was copied
+/// in the meantime.
+pub fn copy_secret(secret: &str, clear_after: u64) -> Result<()> {
+    copy(secret)?;
+    if clear_after == 0 {
+        return Ok(());
+    }
+    // The secret's hash goes over stdin, not argv, where `ps` would show it
+    let mut child = Command::new(std::env::current_exe()?)
+        .args(["clear-clipboard", "--after", &clear_after.to_string()])
+        .stdin(Stdio::piped())
+        .stdout(Stdio::null())
+        .stderr(Stdio::null())
+        .spawn()?;
+    if let Some(mut stdin) = child.stdin.take() {
+        stdin.write_all(crate::sha256_hex(secret.as_bytes()).as_bytes())?;
+    }
+    Ok(())
+}
+
+/// The background half of [`copy_secret`]: reads the secret's hash from stdin, waits,
+/// and clears the clipboard if it still holds that secret.
+pub fn clear_after(seconds: u64) -> Result<()> {
+    let mut hash = String::new();
+    std::io::stdin().read_to_string(&mut hash)?;
+    std::thread::sleep(Duration::from_secs(seconds));
+    let current = paste()?;
+    if crate::sha256_hex(current.as_bytes()) == hash.trim()
+        || crate::sha256_hex(current.trim_end_matches('\n').as_bytes()) == hash.trim()
+    {
+        copy("")?;
+    }
+    Ok(())
+}
@@ -0,0 +1,1400 @@
+//! Command-line arguments and dispatching them to the commands, behind the `cli`
+//! feature so the library builds without clap.
+
+use super::*;
+use crate::daemon::ipc::DaemonRequest;
+use anyhow::Result;
+use clap::{Parser, Subcommand};
+
+#[derive(Parser)]
+#[command(name = "tether")]
+#[command(about = "Sync your dev environment across machines", long_about = None)]
+#[command(version)]
+pub struct Cli {
+    /// Skip confirmation prompts (non-interactive mode)
+    #[arg(short = 'y', long, global = true)]
+    pub yes: bool,
+
+    /// Use an isolated tether context (own config dir, sync repo and daemon);
+    /// defaults to $TETHER_CONTEXT
+    #[arg(long, global = true, value_name = "NAME")]
+    pub context: Option<String>,
+
+    /// Plain output for scripts: no spinners or progress lines (`secrets get` prints
+    /// only the value). Spinners become plain lines whenever stderr isn't a terminal.
+    #[arg(short, long, global = true)]
+    pub quiet: bool,
+
+    #[command(subcommand)]
+    pub command: Option<Commands>,
+}
+
+#[derive(Subcommand)]
+pub enum Commands {
+    #[cfg(feature = "dashboard")]
+    /// Interactive dashboard
+    Dashboard,
+
+    /// Initialize Tether on this machine
+    Init {
+        /// Git repository URL
+        #[arg(long)]
+        repo: Option<String>,
+
+        /// Don't start the daemon automatically
+        #[arg(long)]
+        no_daemon: bool,
+
+        /// Team-only mode: skip personal dotfiles/packages, only use team sync
+        #[arg(long)]
+        team_only: bool,
+
+        /// Join using a pairing QR code from 'tether machines pair': scan it with the
+        /// camera, or pass the pasted text or a screenshot of it
+        #[arg(long, value_name = "PAYLOAD", num_args = 0..=1, default_missing_value = "", conflicts_with_all = ["repo", "team_only"])]
+        from_qr: Option<String>,
+    },
+
+    /// Apply dotfiles in a devcontainer or Codespace without setting up tether
+    Bootstrap {
+        /// Sync repo URL
+        #[arg(required_unless_present = "issue_identity")]
+        repo: Option<String>,
+        /// Shallow-clone, apply dotfiles and leave nothing else behind (no config,
+        /// state or daemon). Encrypted repos are decrypted with $TETHER_KEY.
+        #[arg(long, requires = "repo")]
+        ephemeral: bool,
+        /// Profile whose dotfiles to apply (default: dev)
+        #[arg(long)]
+        profile: Option<String>,
+        /// Also install packages from the synced manifests
+        #[arg(long)]
+        packages: bool,
+        /// Create an age identity that can decrypt the sync repo, to use as $TETHER_KEY
+        #[arg(long, conflicts_with_all = ["repo", "ephemeral", "profile", "packages"])]
+        issue_identity: bool,
+    },
+
+    /// Manually trigger a sync
+    Sync {
+        /// Show what would be synced without doing it
+        #[arg(long)]
+        dry_run: bool,
+
+        /// Skip conflict prompts
+        #[arg(long)]
+        force: bool,
+
+        /// Re-prompt for previously dismissed file imports
+        #[arg(long)]
+        rediscover: bool,
+
+        /// Sync only this file (e.g. .zshrc or ~/.config/nvim/init.lua)
+        #[arg(long)]
+        file: Option<String>,
+
+        /// Apply remote changes but never write to the sync repo or push
+        #[arg(long, conflicts_with = "push_only")]
+        pull_only: bool,
+
+        /// Push local changes but never apply remote changes locally
+        #[arg(long)]
+        push_only: bool,
+    },
+
+    /// Show current sync status
+    Status,
+
+    /// Check whether synced dotfiles are behind the remote; exits 1 if any are
+    Check {
+        /// Only check this file (e.g. .zshrc)
+        file: Option<String>,
+    },
+
+    /// Show differences between machines
+    Diff {
+        /// Compare with specific machine
+        #[arg(long)]
+        machine: Option<String>,
+    },
+
+    /// Control the background daemon
+    Daemon {
+        #[command(subcommand)]
+        action: DaemonAction,
+    },
+
+    /// Manage machines in sync network
+    Machines {
+        #[command(subcommand)]
+        action: MachineAction,
+    },
+
+    /// Manage ignore patterns
+    Ignore {
+        #[command(subcommand)]
+        action: IgnoreAction,
+    },
+
+    /// Manage configuration
+    Config {
+        #[command(subcommand)]
+        action: ConfigAction,
+    },
+
+    #[cfg(feature = "team")]
+    /// Manage team sync
+    Team {
+        #[command(subcommand)]
+        action: TeamAction,
+    },
+
+    /// Resolve file conflicts
+    Resolve {
+        /// Specific file to resolve (resolves all if not specified)
+        file: Option<String>,
+        /// Show secret values in diffs instead of masking them
+        #[arg(long)]
+        reveal: bool,
+    },
+
+    /// List and resolve conflicts deferred by the daemon or earlier syncs
+    Conflicts {
+        #[command(subcommand)]
+        action: Option<ConflictsAction>,
+    },
+
+    /// Unlock encryption key with passphrase
+    Unlock {
+        /// Keep the key in the login keychain (Secret Service on Linux) so it unlocks
+        /// with your login
+        #[arg(long)]
+        keychain: bool,
+    },
+
+    /// Clear cached encryption key (and remove it from the login keychain or keyring)
+    Lock,
+
+    /// Manage the passphrase protecting the encryption key
+    Passphrase {
+        #[command(subcommand)]
+        action: PassphraseAction,
+    },
+
+    /// Manage the encryption key itself
+    Key {
+        #[command(subcommand)]
+        action: KeyAction,
+    },
+
+    /// Upgrade all installed packages
+    Upgrade,
+
+    /// List and manage installed packages
+    Packages {
+        /// List packages without interactive selection
+        #[arg(long)]
+        list: bool,
+        #[command(subcommand)]
+        action: Option<PackagesAction>,
+    },
+
+    /// Snapshot exact package versions and the dotfiles commit into a named lock
+    Freeze {
+        /// Lock name (e.g. q3-baseline)
+        name: String,
+    },
+
+    /// Reproduce a frozen environment on this machine (lists locks if no name)
+    Thaw {
+        /// Lock name
+        name: Option<String>,
+        /// Show what would change without changing anything
+        #[arg(long)]
+        dry_run: bool,
+    },
+
+    /// Check the sync repo for corruption, or with --against, this machine against a
+    /// lock; exits 1 on any problem, 2 if it can't check
+    Verify {
+        /// Lock name, as created by `tether freeze`
+        #[arg(long)]
+        against: Option<String>,
+    },
+
+    /// Restore files from backup
+    Restore {
+        #[command(subcommand)]
+        action: RestoreAction,
+    },
+
+    /// Personal secrets (API tokens, ...) synced encrypted through your sync repo
+    Secrets {
+        #[command(subcommand)]
+        action: PersonalSecretsAction,
+    },
+
+    /// Manage age identity for team secrets
+    Identity {
+        #[command(subcommand)]
+        action: IdentityAction,
+    },
+
+    #[cfg(feature = "team")]
+    /// Manage collaborator-based project secret sharing
+    Collab {
+        #[command(subcommand)]
+        action: CollabAction,
+    },
+
+    /// Browse everything in the sync repo: show, history, restore, untrack
+    Browse {
+        /// Print a plain listing instead of the interactive browser
+        #[arg(long)]
+        list: bool,
+    },
+
+    /// Show file change history from sync repo
+    History {
+        /// Dotfile path (e.g., .zshrc)
+        file: String,
+        /// Maximum number of entries to show
+        #[arg(short, long, default_value = "20")]
+        limit: usize,
+    },
+
+    /// Run a command with the current project's secrets as environment variables
+    Run {
+        /// List the variables that would be injected (names and sources only)
+        #[arg(long)]
+        list: bool,
+        /// Command and arguments (after --)
+        #[arg(
+            trailing_var_arg = true,
+            allow_hyphen_values = true,
+            required_unless_present = "list"
+        )]
+        command: Vec<String>,
+    },
+
+    /// Search decrypted dotfiles, directory files and project configs in the sync repo
+    Grep {
+        /// Regular expression to search for
+        pattern: String,
+        /// Match case-insensitively
+        #[arg(short, long)]
+        ignore_case: bool,
+        /// Also search past versions, including files no longer synced
+        #[arg(long)]
+        history: bool,
+    },
+
+    /// Print the decrypted repo copy of a file to stdout
+    Show {
+        /// File path (e.g., .zshrc or .config/nvim/init.lua), optionally @<commit>
+        path: String,
+    },
+
+    /// Copy a profile's dotfiles and synced directories to a host without tether, over SSH
+    PushTo {
+        /// SSH destination (host, user@host or a Host alias from ~/.ssh/config)
+        host: String,
+        /// Profile to render (defaults to this machine's)
+        #[arg(long)]
+        profile: Option<String>,
+        /// List what would be copied and removed without copying
+        #[arg(long)]
+        dry_run: bool,
+    },
+
+    /// Generate setup for tools that run tether for you
+    Integrate {
+        #[command(subcommand)]
+        action: IntegrateAction,
+    },
+
+    /// Review the local log of sensitive operations
+    Audit {
+        #[command(subcommand)]
+        action: AuditAction,
+    },
+
+    /// Delete sync repo files no machine's config references anymore
+    Prune {
+        /// List orphaned files without deleting them
+        #[arg(long)]
+        dry_run: bool,
+    },
+
+    /// Try config changes in a throwaway HOME with its own local sync repo, made from
+    /// this machine's config and dotfiles without secrets
+    Sandbox {
+        /// Keep the sandbox directory after the shell exits
+        #[arg(long)]
+        keep: bool,
+    },
+
+    /// Maintain the sync repository
+    Repo {
+        #[command(subcommand)]
+        action: RepoAction,
+    },
+
+    /// Print shell completions, or check that your shell has them and tether on PATH
+    Completions {
+        #[command(subcommand)]
+        action: CompletionsAction,
+    },
+
+    /// Complete a partly typed command line for the completion scripts
+    #[command(name = "__complete", hide = true)]
+    Complete {
+        /// Position of the word being completed
+        #[arg(long)]
+        index: usize,
+        #[arg(last = true)]
+        words: Vec<String>,
+    },
+
+    /// Clear the clipboard after a delay if it still holds a copied secret
+    #[command(hide = true)]
+    ClearClipboard {
+        #[arg(long)]
+        after: u64,
+    },
+
+    /// Hold an unlocked identity for `security.session_minutes`
+    #[command(hide = true)]
+    IdentityAgent {
+        /// Seconds until the session ends
+        #[arg(long)]
+        ttl: u64,
+    },
+
+    /// Profile sync hot paths (hashing, encryption, scans, state I/O) or startup
+    #[command(hide = true)]
+    Bench {
+        /// Use a generated repo instead of this machine's sync repo
+        #[arg(long)]
+        synthetic: bool,
+        /// Number of files in the synthetic repo
+        #[arg(long, default_value = "1000")]
+        files: usize,
+        /// Size in bytes of each synthetic file
+        #[arg(long, default_value = "4096")]
+        size: usize,
+        /// Time command startup (config, state and repo loading) instead
+        #[arg(long, conflicts_with = "synthetic")]
+        startup: bool,
+    },
+}
+
+#[derive(Subcommand)]
+pub enum PassphraseAction {
+    /// Protect the encryption key with a new passphrase (nothing is re-encrypted)
+    Rotate,
+}
+
+#[derive(Subcommand)]
+pub enum KeyAction {
+    /// Generate a new encryption key and re-encrypt the sync repo with it
+    Rotate,
+}
+
+#[derive(Subcommand)]
+pub enum IntegrateAction {
+    /// Print a devcontainer.json snippet that bootstraps your dotfiles in every container
+    Devcontainer {
+        /// Profile to apply (defaults to this machine's)
+        #[arg(long)]
+        profile: Option<String>,
+        /// Also install the profile's packages
+        #[arg(long)]
+        packages: bool,
+        /// Write a local devcontainer feature to this directory instead (e.g. .devcontainer/tether)
+        #[arg(long, value_name = "DIR")]
+        feature: Option<std::path::PathBuf>,
+    },
+}
+
+#[derive(Subcommand)]
+pub enum AuditAction {
+    /// List logged syncs, restores, secret reads, unlocks and recipient changes,
+    /// and check the log hasn't been tampered with
+    Show {
+        /// Only the most recent N entries
+        #[arg(long, short = 'n')]
+        limit: Option<usize>,
+    },
+}
+
+#[derive(Subcommand)]
+pub enum RepoAction {
+    /// Squash old history and garbage-collect old encrypted blobs (force-pushes)
+    Prune {
+        /// How much history to keep (e.g. 90d, 12w, 6m, 1y)
+        #[arg(long, default_value = "90d")]
+        keep: String,
+    },
+    /// Pack and compress the local sync repo's objects (nothing is pushed)
+    Compact,
+}
+
+#[derive(Subcommand)]
+pub enum CompletionsAction {
+    /// Print the bash completion script
+    Bash,
+    /// Print the zsh completion script
+    Zsh,
+    /// Print the fish completion script
+    Fish,
+    /// Check that tether is on PATH and completions are loaded in your shell
+    Doctor {
+        /// Add the missing lines to your shell's startup file
+        #[arg(long)]
+        fix: bool,
+    },
+}
+
+#[derive(Subcommand)]
+pub enum DaemonAction {
+    /// Start the daemon
+    Start {
+        /// Also sync every other context on this machine, each on its own schedule
+        #[arg(long)]
+        all_contexts: bool,
+    },
+    /// Stop the daemon
+    Stop,
+    /// Restart the daemon
+    Restart {
+        /// Also sync every other context on this machine, each on its own schedule
+        #[arg(long)]
+        all_contexts: bool,
+    },
+    /// View daemon logs
+    Logs {
+        /// Keep printing new lines as the daemon writes them
+        #[arg(short, long)]
+        follow: bool,
+        /// Only lines since then: a duration back (`30m`, `2h`, `1d`) or a date/time
+        /// (`2024-05-01`, `2024-05-01T09:00:00Z`); includes rotated logs
+        #[arg(long, value_name = "WHEN")]
+        since: Option<String>,
+        /// Only panics and syncs the watchdog stopped, with their backtraces; includes
+        /// rotated logs
+        #[arg(long, conflicts_with = "follow")]
+        crashes: bool,
+    },
+    /// Show what the running daemon is doing
+    Status,
+    /// Ask the running daemon to sync right away
+    SyncNow,
+    /// Skip scheduled syncs until resumed (or the daemon restarts)
+    Pause {
+        /// Resume on its own after this long: `90s`, `30m`, `2h` or `1d`
+        #[arg(long = "for", value_name = "DURATION")]
+        duration: Option<String>,
+    },
+    /// Resume scheduled syncs
+    Resume,
+    /// Make the running daemon re-read its config (sync interval, watchers)
+    ReloadConfig,
+    /// Install launchd service (auto-start on login)
+    Install {
+        /// Also sync every other context on this machine, each on its own schedule
+        #[arg(long)]
+        all_contexts: bool,
+    },
+    /// Uninstall launchd service
+    Uninstall,
+    /// Internal daemon runner
+    #[command(hide = true)]
+    Run {
+        #[arg(long)]
+        all_contexts: bool,
+    },
+}
+
+#[derive(Subcommand)]
+pub enum MachineAction {
+    /// List all machines
+    List,
+    /// Rename this machine
+    Rename { old: String, new: String },
+    /// Remove a machine from sync
+    Remove { name: String },
+    /// Compare two machines' synced files and packages
+    Diff {
+        /// Machine to compare from
+        from: String,
+        /// Machine to compare to
+        to: String,
+        /// Print the comparison as JSON
+        #[arg(long)]
+        json: bool,
+    },
+    /// Summarize drift across machines: who's behind on sync, package differences,
+    /// conflicts and security warnings
+    Report {
+        /// Print the report as JSON
+        #[arg(long)]
+        json: bool,
+        /// Also deliver it as configured in [report] (notification, file, webhook)
+        #[arg(long)]
+        send: bool,
+    },
+    /// Show a QR code that sets up a new machine with `tether init --from-qr`
+    Pair {
+        /// Profile to assign to the new machine (defaults to this machine's)
+        #[arg(long)]
+        profile: Option<String>,
+        /// Print the pairing payload and code as JSON
+        #[arg(long)]
+        json: bool,
+    },
+    /// Manage machine profile assignment
+    Profile {
+        #[command(subcommand)]
+        action: MachineProfileAction,
+    },
+}
+
+#[derive(Subcommand)]
+pub enum MachineProfileAction {
+    /// Assign a profile to this machine
+    Set {
+        /// Profile name (must exist in config)
+        profile: String,
+    },
+    /// Remove profile assignment from this machine
+    Unset,
+    /// Create a new profile (interactive wizard)
+    Create {
+        /// Profile name
+        name: String,
+        /// Inherit dotfiles, dirs and packages from another profile
+        #[arg(long)]
+        extends: Option<String>,
+    },
+    /// Edit an existing profile
+    Edit {
+        /// Profile name
+        name: String,
+    },
+    /// List all profiles
+    List,
+    /// Show a profile's dotfiles, dirs and packages
+    Show {
+        /// Profile name
+        name: String,
+        /// Include everything inherited through `extends`
+        #[arg(long)]
+        resolved: bool,
+    },
+}
+
+#[derive(Subcommand)]
+pub enum IgnoreAction {
+    /// Add secret scanning ignore pattern
+    Add {
+        /// Text of flagged lines to ignore
+        #[arg(required_unless_present_any = ["path", "fingerprint"])]
+        pattern: Option<String>,
+        /// Only ignore findings in this file (all of them without a pattern)
+        #[arg(long)]
+        path: Option<String>,
+        /// Ignore a single finding, by the fingerprint the scan printed
+        #[arg(long, conflicts_with_all = ["pattern", "path"])]
+        fingerprint: Option<String>,
+    },
+    /// List secret scanning ignore patterns
+    List,
+    /// Remove secret scanning ignore pattern
+    Remove {
+        #[arg(required_unless_present_any = ["path", "fingerprint"])]
+        pattern: Option<String>,
+        #[arg(long)]
+        path: Option<String>,
+        #[arg(long, conflicts_with_all = ["pattern", "path"])]
+        fingerprint: Option<String>,
+    },
+    /// Ignore a dotfile on this machine (won't be overwritten during sync)
+    Dotfile { file: String },
+    /// Ignore a project config on this machine
+    Project {
+        /// Project identifier (repo name or path)
+        project: String,
+        /// Config file path relative to project root
+        path: String,
+    },
+    /// List files ignored on this machine
+    SyncList,
+    /// Unignore a file on this machine
+    SyncRemove {
+        /// File to unignore (dotfile name or "project:path")
+        file: String,
+    },
+}
+
+#[derive(Subcommand)]
+pub enum ConfigAction {
+    /// Get config value
+    Get { key: String },
+    /// Set config value
+    Set { key: String, value: String },
+    /// Open config in editor
+    Edit,
+    /// Interactive UI for managing files, folders, and patterns
+    Dotfiles,
+    /// Manage feature toggles
+    Features {
+        #[command(subcommand)]
+        action: Option<FeaturesAction>,
+    },
+}
+
+#[derive(Subcommand)]
+pub enum FeaturesAction {
+    /// Enable a feature
+    Enable {
+        /// Feature name
+        feature: String,
+    },
+    /// Disable a feature
+    Disable {
+        /// Feature name
+        feature: String,
+    },
+}
+
+#[derive(Subcommand)]
+pub enum ConflictsAction {
+    /// Show pending conflicts with diffs (default)
+    List {
+        /// Show secret values in diffs instead of masking them
+        #[arg(long)]
+        reveal: bool,
+    },
+    /// Resolve pending conflicts interactively
+    Resolve {
+        /// Specific file to resolve (resolves all if not specified)
+        file: Option<String>,
+        /// Show secret values in diffs instead of masking them
+        #[arg(long)]
+        reveal: bool,
+    },
+}
+
+#[derive(Subcommand)]
+pub enum PackagesAction {
+    /// Search package managers for a tool, then optionally install it
+    Search {
+        /// Package name or search term
+        query: String,
+    },
+    /// Install packages other machines added that are waiting for approval
+    /// (packages.approve_installs); picks interactively without names
+    Approve {
+        /// Packages to approve (all of them with --yes)
+        packages: Vec<String>,
+    },
+    /// Keep packages waiting for approval off this machine
+    Reject {
+        /// Packages to reject (all of them with --yes)
+        packages: Vec<String>,
+    },
+}
+
+#[derive(Subcommand)]
+pub enum RestoreAction {
+    /// List available backups
+    List,
+    /// Restore a file from backup (interactive if no args)
+    File {
+        /// Backup timestamp (e.g., 2024-01-15T10-30-00)
+        #[arg(long)]
+        from: Option<String>,
+        /// File to restore (e.g., dotfiles/.zshrc)
+        file: Option<String>,
+    },
+    /// Restore a dotfile from git history
+    Git {
+        /// Dotfile path (e.g., .zshrc)
+        file: String,
+        /// Commit hash (interactive picker if omitted)
+        #[arg(long)]
+        commit: Option<String>,
+    },
+}
+
+#[derive(Subcommand)]
+pub enum IdentityAction {
+    /// Generate a new age identity
+    Init {
+        /// Keep the identity on a YubiKey (needs age-plugin-yubikey)
+        #[arg(long)]
+        yubikey: bool,
+    },
+    /// Show your public key
+    Show,
+    /// Unlock identity with passphrase (YubiKey identities need no unlocking)
+    Unlock,
+    /// Lock identity (clear cached key and end any session)
+    Lock,
+    /// Reset identity (generate new, destroys old)
+    Reset {
+        /// Keep the new identity on a YubiKey (needs age-plugin-yubikey)
+        #[arg(long)]
+        yubikey: bool,
+    },
+}
+
+#[derive(Subcommand)]
+pub enum CollabAction {
+    /// Initialize a new collab for the current project
+    Init {
+        /// Project path (defaults to current directory)
+        #[arg(long)]
+        project: Option<String>,
+    },
+    /// Join an existing collab
+    Join {
+        /// Collab sync repo URL
+        url: String,
+    },
+    /// Add a secret file to the collab
+    Add {
+        /// File to add (e.g., .env)
+        file: String,
+        /// Project path (defaults to current directory)
+        #[arg(long)]
+        project: Option<String>,
+    },
+    /// Refresh collaborators from GitHub and re-encrypt secrets
+    Refresh {
+        /// Project path (defaults to current directory)
+        #[arg(long)]
+        project: Option<String>,
+    },
+    /// List all collabs
+    List,
+    /// Add another project to an existing collab
+    AddProject {
+        /// Project path to add
+        project: String,
+    },
+    /// Remove a collab
+    Remove {
+        /// Collab name (interactive if not specified)
+        name: Option<String>,
+    },
+}
+
+#[derive(Subcommand)]
+pub enum TeamAction {
+    /// Interactive team setup wizard
+    Setup,
+    /// Add team sync repository
+    Add {
+        /// Team repository URL
+        url: String,
+        /// Custom team name (defaults to org/owner from URL)
+        #[arg(long)]
+        name: Option<String>,
+        /// Skip auto-injection of source lines
+        #[arg(long)]
+        no_auto_inject: bool,
+    },
+    /// Switch active team
+    Switch {
+        /// Team name to switch to
+        name: String,
+    },
+    /// List all teams
+    List,
+    /// Remove team sync
+    Remove {
+        /// Team name to remove (defaults to active team)
+        name: Option<String>,
+    },
+    /// Enable team sync
+    Enable,
+    /// Disable team sync
+    Disable,
+    /// Show team sync status
+    Status,
+    /// Manage allowed organizations for team repos
+    Orgs {
+        #[command(subcommand)]
+        action: OrgAction,
+    },
+    /// Manage team secrets (encrypted with age)
+    Secrets {
+        #[command(subcommand)]
+        action: SecretsAction,
+    },
+    /// Manage team files and sync preferences
+    Files {
+        #[command(subcommand)]
+        action: FilesAction,
+    },
+    /// Manage team project secrets
+    Projects {
+        #[command(subcommand)]
+        action: ProjectsAction,
+    },
+}
+
+#[derive(Subcommand)]
+pub enum OrgAction {
+    /// Add allowed organization
+    Add {
+        /// GitHub organization name
+        org: String,
+    },
+    /// List allowed organizations
+    List,
+    /// Remove allowed organization
+    Remove {
+        /// GitHub organization name
+        org: String,
+    },
+}
+
+#[derive(Subcommand)]
+pub enum SecretsAction {
+    /// Add a recipient's public key to the team
+    AddRecipient {
+        /// age or SSH (ssh-ed25519/ssh-rsa) public key, or path to .pub file
+        #[arg(required_unless_present = "github")]
+        key: Option<String>,
+        /// Add every supported SSH key of this GitHub user instead
+        #[arg(long, conflicts_with = "key")]
+        github: Option<String>,
+        /// Name for this recipient (defaults to the GitHub user or your username)
+        #[arg(long)]
+        name: Option<String>,
+    },
+    /// List team recipients
+    ListRecipients,
+    /// Remove a recipient from the team
+    RemoveRecipient {
+        /// Recipient name
+        name: String,
+    },
+    /// Add or update a secret
+    Set {
+        /// Secret name (e.g., "GITHUB_TOKEN")
+        name: String,
+        /// Secret value (prompts if not provided)
+        #[arg(long)]
+        value: Option<String>,
+    },
+    /// Get a secret value
+    Get {
+        /// Secret name
+        name: String,
+        /// Put the value on the clipboard instead of printing it
+        #[arg(long)]
+        copy: bool,
+        /// Seconds before a copied value is cleared from the clipboard (0 keeps it)
+        #[arg(long, value_name = "SECS", default_value = "30", requires = "copy")]
+        clear_after: u64,
+    },
+    /// List all secrets
+    List,
+    /// Remove a secret
+    Remove {
+        /// Secret name
+        name: String,
+    },
+}
+
+#[derive(Subcommand)]
+pub enum PersonalSecretsAction {
+    /// Add or update a secret (reads piped input, else prompts, unless --value)
+    Set {
+        /// Secret name (e.g., "GITHUB_TOKEN")
+        name: String,
+        /// Secret value
+        #[arg(long)]
+        value: Option<String>,
+    },
+    /// Print a secret value
+    Get {
+        /// Secret name, or a provider reference such as "bw:GitHub#password"
+        name: String,
+        /// Put the value on the clipboard instead of printing it
+        #[arg(long)]
+        copy: bool,
+        /// Seconds before a copied value is cleared from the clipboard (0 keeps it)
+        #[arg(long, value_name = "SECS", default_value = "30", requires = "copy")]
+        clear_after: u64,
+    },
+    /// List secret names
+    List,
+    /// Remove a secret
+    Remove {
+        /// Secret name
+        name: String,
+    },
+}
+
+#[derive(Subcommand)]
+pub enum FilesAction {
+    /// List synced team files
+    List,
+    /// Show local patterns (files never synced)
+    LocalPatterns,
+    /// Reset file to team version (clobber local changes)
+    Reset {
+        /// File to reset
+        file: Option<String>,
+        /// Reset all files
+        #[arg(long)]
+        all: bool,
+    },
+    /// Promote local file to team repository
+    Promote {
+        /// File to promote
+        file: String,
+    },
+    /// Mark file as personal (skip team sync)
+    Ignore {
+        /// File to ignore
+        file: String,
+    },
+    /// Unmark file as personal (resume team sync)
+    Unignore {
+        /// File to unignore
+        file: String,
+    },
+    /// Show diff between local and team version
+    Diff {
+        /// File to diff (all if not specified)
+        file: Option<String>,
+        /// Show secret values instead of masking them
+        #[arg(long)]
+        reveal: bool,
+    },
+}
+
+#[derive(Subcommand)]
+pub enum ProjectsAction {
+    /// Add a project secret to the team repo
+    Add {
+        /// File to add (e.g., .env)
+        file: String,
+        /// Project path (defaults to current directory)
+        #[arg(long)]
+        project: Option<String>,
+    },
+    /// List team project secrets
+    List,
+    /// Remove a project secret
+    Remove {
+        /// File to remove
+        file: String,
+        /// Project (normalized URL like github.com/org/repo)
+        #[arg(long)]
+        project: Option<String>,
+    },
+    /// Remove personal project secrets that are now team-owned
+    PurgePersonal {
+        /// Also purge from git history
+        #[arg(long)]
+        history: bool,
+    },
+    /// Migrate personal project secrets to team repo
+    Migrate,
+}
+
+impl Cli {
+    pub async fn run(&self) -> Result<()> {
+        if let Some(context) = &self.context {
+            if !crate::config::is_valid_context_name(context) {
+                anyhow::bail!(
+                    "Invalid context name '{}': use letters, digits, '-' and '_'",
+                    context
+                );
+            }
+            // Through the environment so spawned syncs and daemons stay in it
+            std::env::set_var(crate::config::CONTEXT_ENV, context);
+        }
+        crate::config::current_context()?;
+        crate::cli::Progress::set_quiet(self.quiet);
+
+        match &self.command {
+            #[cfg(feature = "dashboard")]
+            None if !crate::config::server_mode() => {
+                tokio::task::spawn_blocking(crate::dashboard::run).await?
+            }
+            #[cfg(feature = "dashboard")]
+            Some(Commands::Dashboard) => tokio::task::spawn_blocking(crate::dashboard::run).await?,
+            // Minimal builds and servers have no dashboard to fall back to
+            None => {
+                <Self as clap::CommandFactory>::command().print_help()?;
+                Ok(())
+            }
+            Some(cmd) => {
+                let start = std::time::Instant::now();
+                let result = self.run_command(cmd).await;
+                log::debug!("Command finished in {:.1?}", start.elapsed());
+                result
+            }
+        }
+    }
+
+    async fn run_command(&self, command: &Commands) -> Result<()> {
+        match command {
+            #[cfg(feature = "dashboard")]
+            Commands::Dashboard => unreachable!(),
+            Commands::Init {
+                repo,
+                no_daemon,
+                team_only,
+                from_qr,
+            } => init::run(repo.as_deref(), *no_daemon, *team_only, from_qr.as_deref()).await,
+            Commands::Bootstrap {
+                repo,
+                ephemeral,
+                profile,
+                packages,
+                issue_identity,
+            } => match repo {
+                _ if *issue_identity => bootstrap::issue_identity().await,
+                Some(repo) if *ephemeral => {
+                    bootstrap::ephemeral(repo, profile.as_deref(), *packages).await
+                }
+                _ => anyhow::bail!(
+                    "Only --ephemeral bootstraps are supported; run 'tether init' to set up this machine"
+                ),
+            },
+            Commands::Sync {
+                dry_run,
+                force,
+                rediscover,
+                file,
+                pull_only,
+                push_only,
+            } => {
+                let mode = if *pull_only {
+                    crate::sync::SyncMode::PullOnly
+                } else if *push_only {
+                    crate::sync::SyncMode::PushOnly
+                } else {
+                    crate::sync::SyncMode::Full
+                };
+                match file {
+                    Some(file) => sync::run_file(file, *dry_run, mode).await,
+                    None => sync::run(*dry_run, *force, *rediscover, true, mode).await,
+                }
+            }
+            Commands::Status => status::run().await,
+            Commands::Check { file } => {
+                if !check::run(file.as_deref()).await? {
+                    std::process::exit(1);
+                }
+                Ok(())
+            }
+            Commands::Diff { machine } => diff::run(machine.as_deref()).await,
+            Commands::Daemon { action } => match action {
+                DaemonAction::Start { all_contexts } => daemon::start(*all_contexts).await,
+                DaemonAction::Stop => daemon::stop().await,
+                DaemonAction::Restart { all_contexts } => daemon::restart(*all_contexts).await,
+                DaemonAction::Logs {
+                    follow,
+                    since,
+                    crashes,
+                } => daemon::logs(*follow, since.as_deref(), *crashes).await,
+                DaemonAction::Status => daemon::control(DaemonRequest::Status),
+                DaemonAction::SyncNow => daemon::control(DaemonRequest::SyncNow),
+                DaemonAction::Pause { duration } => daemon::pause(duration.as_deref()),
+                DaemonAction::Resume => daemon::control(DaemonRequest::Resume),
+                DaemonAction::ReloadConfig => daemon::control(DaemonRequest::ReloadConfig),
+                DaemonAction::Install { all_contexts } => daemon::install(*all_contexts).await,
+                DaemonAction::Uninstall => daemon::uninstall().await,
+                DaemonAction::Run { all_contexts } => daemon::run_daemon(*all_contexts).await,
+            },
+            Commands::Machines { action } => match action {
+                MachineAction::List => machines::list().await,
+                MachineAction::Rename { old, new } => machines::rename(old, new).await,
+                MachineAction::Remove { name } => machines::remove(name).await,
+                MachineAction::Diff { from, to, json } => machines::diff(from, to, *json).await,
+                MachineAction::Report { json, send } => machines::report(*json, *send).await,
+                MachineAction::Pair { profile, json } => {
+                    machines::pair(profile.as_deref(), *json).await
+                }
+                MachineAction::Profile { action } => match action {
+                    MachineProfileAction::Set { profile } => machines::profile_set(profile).await,
+                    MachineProfileAction::Unset => machines::profile_unset().await,
+                    MachineProfileAction::Create { name, extends } => {
+                        machines::profile_create(name, extends.as_deref()).await
+                    }
+                    MachineProfileAction::Edit { name } => machines::profile_edit(name).await,
+                    MachineProfileAction::List => machines::profile_list().await,
+                    MachineProfileAction::Show { name, resolved } => {
+                        machines::profile_show(name, *resolved).await
+                    }
+                },
+            },
+            Commands::Ignore { action } => match action {
+                IgnoreAction::Add {
+                    pattern,
+                    path,
+                    fingerprint,
+                } => {
+                    ignore::add(
+                        pattern.as_deref(),
+                        path.as_deref(),
+                        fingerprint.as_deref(),
+                    )
+                    .await
+                }
+                IgnoreAction::List => ignore::list().await,
+                IgnoreAction::Remove {
+                    pattern,
+                    path,
+                    fingerprint,
+                } => {
+                    ignore::remove(
+                        pattern.as_deref(),
+                        path.as_deref(),
+                        fingerprint.as_deref(),
+                    )
+                    .await
+                }
+                IgnoreAction::Dotfile { file } => ignore::ignore_dotfile(file).await,
+                IgnoreAction::Project { project, path } => {
+                    ignore::ignore_project(project, path).await
+                }
+                IgnoreAction::SyncList => ignore::sync_list().await,
+                IgnoreAction::SyncRemove { file } => ignore::sync_remove(file).await,
+            },
+            Commands::Config { action } => match action {
+                ConfigAction::Get { key } => config::get(key).await,
+                ConfigAction::Set { key, value } => config::set(key, value).await,
+                ConfigAction::Edit => config::edit().await,
+                ConfigAction::Dotfiles => config::dotfiles().await,
+                ConfigAction::Features { action } => match action {
+                    None => config::features_list().await,
+                    Some(FeaturesAction::Enable { feature }) => {
+                        config::features_enable(feature).await
+                    }
+                    Some(FeaturesAction::Disable { feature }) => {
+                        config::features_disable(feature).await
+                    }
+                },
+            },
+            #[cfg(feature = "team")]
+            Commands::Team { action } => match action {
+                TeamAction::Setup => team::setup().await,
+                TeamAction::Add {
+                    url,
+                    name,
+                    no_auto_inject,
+                } => team::add(url, name.as_deref(), *no_auto_inject).await,
+                TeamAction::Switch { name } => team::switch(name).await,
+                TeamAction::List => team::list().await,
+                TeamAction::Remove { name } => team::remove(name.as_deref()).await,
+                TeamAction::Enable => team::enable().await,
+                TeamAction::Disable => team::disable().await,
+                TeamAction::Status => team::status().await,
+                TeamAction::Orgs { action } => match action {
+                    OrgAction::Add { org } => team::orgs_add(org, self.yes).await,
+                    OrgAction::List => team::orgs_list().await,
+                    OrgAction::Remove { org } => team::orgs_remove(org).await,
+                },
+                TeamAction::Secrets { action } => match action {
+                    SecretsAction::AddRecipient { key, github, name } => {
+                        team::secrets_add_recipient(
+                            key.as_deref(),
+                            github.as_deref(),
+                            name.as_deref(),
+                        )
+                        .await
+                    }
+                    SecretsAction::ListRecipients => team::secrets_list_recipients().await,
+                    SecretsAction::RemoveRecipient { name } => {
+                        team::secrets_remove_recipient(name).await
+                    }
+                    SecretsAction::Set { name, value } => {
+                        team::secrets_set(name, value.as_deref()).await
+                    }
+                    SecretsAction::Get {
+                        name,
+                        copy,
+                        clear_after,
+                    } => team::secrets_get(name, copy.then_some(*clear_after), self.quiet).await,
+                    SecretsAction::List => team::secrets_list().await,
+                    SecretsAction::Remove { name } => team::secrets_remove(name).await,
+                },
+                TeamAction::Files { action } => match action {
+                    FilesAction::List => team::files_list().await,
+                    FilesAction::LocalPatterns => team::files_local_patterns().await,
+                    FilesAction::Reset { file, all } => {
+                        team::files_reset(file.as_deref(), *all).await
+                    }
+                    FilesAction::Promote { file } => team::files_promote(file).await,
+                    FilesAction::Ignore { file } => team::files_ignore(file).await,
+                    FilesAction::Unignore { file } => team::files_unignore(file).await,
+                    FilesAction::Diff { file, reveal } => {
+                        team::files_diff(file.as_deref(), *reveal).await
+                    }
+                },
+                TeamAction::Projects { action } => match action {
+                    ProjectsAction::Add { file, project } => {
+                        team::projects_add(file, project.as_deref()).await
+                    }
+                    ProjectsAction::List => team::projects_list().await,
+                    ProjectsAction::Remove { file, project } => {
+                        team::projects_remove(file, project.as_deref()).await
+                    }
+                    ProjectsAction::PurgePersonal { history } => {
+                        team::projects_purge_personal(*history, self.yes).await
+                    }
+                    ProjectsAction::Migrate => team::projects_migrate(self.yes).await,
+                },
+            },
+            Commands::Resolve { file, reveal } => resolve::run(file.as_deref(), *reveal).await,
+            Commands::Conflicts { action } => match action {
+                Some(ConflictsAction::Resolve { file, reveal }) => {
+                    resolve::run(file.as_deref(), *reveal).await
+                }
+                Some(ConflictsAction::List { reveal }) => resolve::list(*reveal).await,
+                None => resolve::list(false).await,
+            },
+            Commands::Unlock { keychain } => unlock::run(*keychain).await,
+            Commands::Lock => unlock::lock().await,
+            Commands::Passphrase { action } => match action {
+                PassphraseAction::Rotate => unlock::rotate_passphrase().await,
+            },
+            Commands::Key { action } => match action {
+                KeyAction::Rotate => unlock::rotate_key().await,
+            },
+            Commands::Upgrade => upgrade::run().await,
+            Commands::Packages { list, action } => match action {
+                Some(PackagesAction::Search { query }) => packages::search(query).await,
+                Some(PackagesAction::Approve { packages }) => {
+                    packages::approve(packages, self.yes).await
+                }
+                Some(PackagesAction::Reject { packages }) => {
+                    packages::reject(packages, self.yes).await
+                }
+                None => packages::run(*list, self.yes).await,
+            },
+            Commands::Freeze { name } => freeze::run(name, self.yes).await,
+            Commands::Thaw { name, dry_run } => {
+                freeze::thaw(name.as_deref(), *dry_run, self.yes).await
+            }
+            Commands::Verify { against } => {
+                let result = match against {
+                    Some(name) => freeze::verify(name).await,
+                    None => repo::verify().await,
+                };
+                match result {
+                    Ok(true) => Ok(()),
+                    Ok(false) => std::process::exit(1),
+                    Err(e) => {
+                        crate::cli::Output::error(&format!("Could not verify: {:#}", e));
+                        std::process::exit(2)
+                    }
+                }
+            }
+            Commands::Restore { action } => match action {
+                RestoreAction::List => restore::list_cmd().await,
+                RestoreAction::File { from, file } => {
+                    restore::run(from.as_deref(), file.as_deref()).await
+                }
+                RestoreAction::Git { file, commit } => {
+                    restore::git_restore(file, commit.as_deref()).await
+                }
+            },
+            Commands::Secrets { action } => match action {
+                PersonalSecretsAction::Set { name, value } => {
+                    secrets::set(name, value.as_deref()).await
+                }
+                PersonalSecretsAction::Get {
+                    name,
+                    copy,
+                    clear_after,
+                } => secrets::get(name, copy.then_some(*clear_after), self.quiet).await,
+                PersonalSecretsAction::List => secrets::list().await,
+                PersonalSecretsAction::Remove { name } => secrets::remove(name).await,
+            },
+            Commands::Identity { action } => match action {
+                IdentityAction::Init { yubikey } => identity::init(*yubikey).await,
+                IdentityAction::Show => identity::show().await,
+                IdentityAction::Unlock => identity::unlock().await,
+                IdentityAction::Lock => identity::lock().await,
+                IdentityAction::Reset { yubikey } => identity::reset(*yubikey).await,
+            },
+            Commands::Browse { list } => browse::run(*list).await,
+            Commands::History { file, limit } => history::run(file, *limit).await,
+            Commands::Show { path } => show::run(path).await,
+            Commands::Grep {
+                pattern,
+                ignore_case,
+                history,
+            } => match grep::run(pattern, *ignore_case, *history).await {
+                Ok(true) => Ok(()),
+                Ok(false) => std::process::exit(1),
+                Err(e) => Err(e),
+            },
+            Commands::Run { list, command } => run::run(command, *list).await,
+            Commands::PushTo {
+                host,
+                profile,
+                dry_run,
+            } => push_to::run(host, profile.as_deref(), *dry_run).await,
+            Commands::Integrate { action } => match action {
+                IntegrateAction::Devcontainer {
+                    profile,
+                    packages,
+                    feature,
+                } => integrate::devcontainer(profile.as_deref(), *packages, feature.as_deref()).await,
+            },
+            Commands::Audit { action } => match action {
+                AuditAction::Show { limit } => audit::show(*limit).await,
+            },
+            Commands::Prune { dry_run } => prune::run(*dry_run, self.yes).await,
+            Commands::Sandbox { keep } => sandbox::run(*keep).await,
+            Commands::Repo { action } => match action {
+                RepoAction::Prune { keep } => repo::prune(keep, self.yes).await,
+                RepoAction::Compact => repo::compact().await,
+            },
+            Commands::Completions { action } => match action {
+                CompletionsAction::Bash => completions::print(crate::cli::shell_rc::Shell::Bash),
+                CompletionsAction::Zsh => completions::print(crate::cli::shell_rc::Shell::Zsh),
+                CompletionsAction::Fish => completions::print(crate::cli::shell_rc::Shell::Fish),
+                CompletionsAction::Doctor { fix } => completions::doctor(*fix),
+            },
+            Commands::Complete { index, words } => completions::complete(*index, words),
+            Commands::ClearClipboard { after } => crate::cli::clipboard::clear_after(*after),
+            Commands::IdentityAgent { ttl } => crate::security::agent::serve(*ttl),
+            Commands::Bench {
+                synthetic,
+                files,
+                size,
+                startup,
+            } => {
+                if *startup {
+                    bench::startup().await
+                } else {
+                    bench::run(*synthetic, *files, *size).await
+                }
+            }
+            #[cfg(feature = "team")]
+            Commands::Collab { action } => match action {
+                CollabAction::Init { project } => collab::init(project.as_deref()).await,
+                CollabAction::Join { url } => collab::join(url).await,
+                CollabAction::Add { file, project } => collab::add(file, project.as_deref()).await,
+                CollabAction::Refresh { project } => collab::refresh(project.as_deref()).await,
+                CollabAction::List => collab::list().await,
+                CollabAction::AddProject { project } => collab::add_project(project).await,
+                CollabAction::Remove { name } => collab::remove(name.as_deref()).await,
+            },
+        }
+    }
+}
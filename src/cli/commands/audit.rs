@@ -0,0 +1,39 @@
+use crate::cli::Output;
+use crate::security::audit;
+use anyhow::Result;
+use chrono::Local;
+
+/// Print the audit log, oldest first, after checking its hash chain
+pub async fn show(limit: Option<usize>) -> Result<()> {
+    let path = audit::log_path()?;
+    let entries = audit::read(&path)?;
+    if entries.is_empty() {
+        Output::info("Nothing has been logged yet");
+        return Ok(());
+    }
+
+    let skip = limit.map_or(0, |n| entries.len().saturating_sub(n));
+    for entry in &entries[skip..] {
+        println!(
+            "  {:>4}  {}  {}  {:<17}  {}",
+            entry.seq,
+            entry.at.with_timezone(&Local).format("%Y-%m-%d %H:%M:%S"),
+            entry.machine,
+            entry.event,
+            entry.detail
+        );
+    }
+
+    println!();
+    match audit::verify(&entries) {
+        None => Output::success(&format!("{} entries, hash chain intact", entries.len())),
+        Some(i) => {
+            Output::error(&format!(
+                "The log was modified: entry {} doesn't match the one before it",
+                i + 1
+            ));
+            anyhow::bail!("Audit log failed verification ({})", path.display());
+        }
+    }
+    Ok(())
+}
@@ -0,0 +1,82 @@
+use crate::bench::{
+    generate_synthetic_repo, run_startup_suite, run_suite, synthetic_state, BenchResult,
+    STARTUP_RUNS,
+};
+use crate::cli::Output;
+use crate::sync::{SyncEngine, SyncState};
+use anyhow::Result;
+use comfy_table::{Attribute, Cell, Color};
+
+/// Profile sync hot paths against this machine's sync repo, or a synthetic one.
+pub async fn run(synthetic: bool, files: usize, size: usize) -> Result<()> {
+    let sync_path = SyncEngine::sync_path()?;
+    let use_real = !synthetic && sync_path.exists();
+
+    // Keep the temp dir alive until the suite finishes
+    let tmp;
+    let (root, state) = if use_real {
+        Output::info(&format!("Profiling sync repo at {}", sync_path.display()));
+        (sync_path, SyncState::load()?)
+    } else {
+        tmp = tempfile::TempDir::new()?;
+        Output::info(&format!(
+            "Profiling synthetic repo ({} files x {} bytes)",
+            files, size
+        ));
+        generate_synthetic_repo(tmp.path(), files, size)?;
+        (tmp.path().to_path_buf(), synthetic_state(files))
+    };
+
+    let results = run_suite(&root, &state)?;
+    print_results("Hot path", &results);
+    Ok(())
+}
+
+/// Time the work read-only commands do before printing, to find slow startup
+pub async fn startup() -> Result<()> {
+    Output::info(&format!(
+        "Profiling command startup (mean of {} runs)",
+        STARTUP_RUNS
+    ));
+    let results = run_startup_suite()?;
+    print_results("Startup phase", &results);
+    Ok(())
+}
+
+fn print_results(label: &str, results: &[BenchResult]) {
+    let mut table = Output::table_full();
+    table.set_header(vec![
+        Cell::new(label)
+            .add_attribute(Attribute::Bold)
+            .fg(Color::Cyan),
+        Cell::new("Items")
+            .add_attribute(Attribute::Bold)
+            .fg(Color::Cyan),
+        Cell::new("Bytes")
+            .add_attribute(Attribute::Bold)
+            .fg(Color::Cyan),
+        Cell::new("Time")
+            .add_attribute(Attribute::Bold)
+            .fg(Color::Cyan),
+        Cell::new("MiB/s")
+            .add_attribute(Attribute::Bold)
+            .fg(Color::Cyan),
+    ]);
+    for r in results {
+        let throughput = r.mib_per_sec();
+        table.add_row(vec![
+            Cell::new(r.name),
+            Cell::new(r.items),
+            Cell::new(r.bytes),
+            Cell::new(format!("{:.2?}", r.elapsed)),
+            Cell::new(if throughput > 0.0 {
+                format!("{:.1}", throughput)
+            } else {
+                "-".to_string()
+            }),
+        ]);
+    }
+
+    println!();
+    println!("{table}");
+}
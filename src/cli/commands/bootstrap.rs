@@ -0,0 +1,242 @@
+use super::push_to::{is_executable, make_executable, read_stored, render, Rendered};
+use crate::cli::Output;
+use crate::config::Config;
+use crate::sync::{GitBackend, SyncEngine};
+use age::secrecy::ExposeSecret;
+use anyhow::{Context, Result};
+use std::path::Path;
+
+/// Environment variable holding what decrypts the sync repo in a container
+pub(super) const KEY_ENV: &str = "TETHER_KEY";
+
+/// Machine ID ephemeral runs use for profile and package manager lookups
+const EPHEMERAL_MACHINE: &str = "ephemeral";
+
+/// Decrypt the repo key with `TETHER_KEY`: an age identity from
+/// `tether bootstrap --issue-identity`, or else the passphrase
+fn key_from_env(sync_path: &Path, value: &str) -> Result<Vec<u8>> {
+    let value = value.trim();
+    if value.starts_with("AGE-SECRET-KEY-") {
+        let identity: age::x25519::Identity = value
+            .parse()
+            .map_err(|e| anyhow::anyhow!("Invalid age identity in {}: {}", KEY_ENV, e))?;
+        let recipient = identity.to_public().to_string();
+        let path = sync_path.join(crate::security::hardware::wrapped_key_path(&recipient));
+        let wrapped = std::fs::read(&path).with_context(|| {
+            format!(
+                "The key isn't wrapped for this identity ({} not found)",
+                path.display()
+            )
+        })?;
+        return crate::security::decrypt_with_identity(&wrapped, &identity.into());
+    }
+    crate::security::decrypt_key_file(
+        &sync_path.join(crate::security::keychain::ENCRYPTED_KEY_FILENAME),
+        value,
+    )
+}
+
+/// Whether the repo stores dotfiles or directory files encrypted. Secrets always are,
+/// but bootstrap doesn't apply them.
+fn has_encrypted_files(sync_path: &Path) -> bool {
+    ["profiles", "configs"].iter().any(|dir| {
+        walkdir::WalkDir::new(sync_path.join(dir))
+            .into_iter()
+            .filter_map(|e| e.ok())
+            .any(|e| e.file_type().is_file() && e.path().extension() == Some("enc".as_ref()))
+    })
+}
+
+/// The tether config synced in the repo, if there is one
+fn synced_config(sync_path: &Path, key: Option<&[u8]>) -> Result<Option<Config>> {
+    let path = sync_path.join("configs/tether/config.toml.enc");
+    let Some(key) = key.filter(|_| path.exists()) else {
+        return Ok(None);
+    };
+    let content = read_stored(sync_path, &path, Some(key))?;
+    let config = toml::from_str(std::str::from_utf8(&content)?)
+        .context("Could not parse the synced config")?;
+    Ok(Some(config))
+}
+
+/// Without a synced config, everything stored for `profile` and in `profiles/shared`
+/// (profile files win), plus synced directory files
+fn render_stored(sync_path: &Path, profile: &str, key: Option<&[u8]>) -> Result<Rendered> {
+    let mut files = Rendered::new();
+    let roots = [
+        ("profiles/shared".to_string(), true),
+        (format!("profiles/{}", profile), true),
+        ("configs".to_string(), false),
+    ];
+    for (root, dotfiles) in roots {
+        let root = sync_path.join(root);
+        for entry in walkdir::WalkDir::new(&root)
+            .follow_links(false)
+            .sort_by_file_name()
+            .into_iter()
+            .filter_map(|e| e.ok())
+            .filter(|e| e.file_type().is_file())
+        {
+            let stored = entry.path().strip_prefix(&root)?.to_string_lossy();
+            if stored.starts_with("tether/") {
+                continue;
+            }
+            let file = match (key.is_some(), stored.strip_suffix(".enc")) {
+                (true, Some(name)) if dotfiles => format!(".{}", name),
+                (true, Some(name)) => name.to_string(),
+                (true, None) => continue,
+                (false, _) => stored.to_string(),
+            };
+            if !crate::config::is_safe_dotfile_path(&file) {
+                continue;
+            }
+            let content = read_stored(sync_path, entry.path(), key)?;
+            files.insert(file, (content, is_executable(entry.path())));
+        }
+    }
+    Ok(files)
+}
+
+/// Write rendered files into `home`, returning how many there were
+fn apply(home: &Path, files: &Rendered) -> Result<usize> {
+    for (file, (content, executable)) in files {
+        let path = home.join(file);
+        if let Some(parent) = path.parent() {
+            std::fs::create_dir_all(parent)?;
+        }
+        crate::security::write_owner_only(&path, content)?;
+        if *executable {
+            make_executable(&path)?;
+        }
+    }
+    Ok(files.len())
+}
+
+/// Apply a profile's dotfiles from a shallow clone of `repo`, for devcontainers and
+/// Codespaces. Nothing else is set up: no config, no state, no daemon, and packages
+/// only with `packages`. Encrypted repos are decrypted with `TETHER_KEY`.
+pub async fn ephemeral(repo: &str, profile: Option<&str>, packages: bool) -> Result<()> {
+    let start = std::time::Instant::now();
+    let checkout = tempfile::TempDir::new()?;
+    let sync_path = checkout.path().join("sync");
+    GitBackend::clone_shallow(repo, &sync_path)?;
+
+    let key = if has_encrypted_files(&sync_path) {
+        let value = std::env::var(KEY_ENV).map_err(|_| {
+            anyhow::anyhow!(
+                "The sync repo is encrypted: set {} to an identity from 'tether bootstrap --issue-identity' (or your passphrase)",
+                KEY_ENV
+            )
+        })?;
+        Some(key_from_env(&sync_path, &value)?)
+    } else {
+        None
+    };
+
+    let config = synced_config(&sync_path, key.as_deref())?;
+    let profile = profile.unwrap_or(crate::config::DEFAULT_PROFILE);
+    let files = match &config {
+        Some(config) => render(config, &sync_path, profile, key.as_deref())?,
+        None => render_stored(&sync_path, profile, key.as_deref())?,
+    };
+    if files.is_empty() {
+        Output::warning(&format!(
+            "Profile '{}' has no files in the sync repo",
+            profile
+        ));
+    }
+    let applied = apply(&crate::home_dir()?, &files)?;
+
+    if packages {
+        let mut config = config.unwrap_or_default();
+        // --packages is the approval
+        config.packages.approve_installs = false;
+        config
+            .machine_profiles
+            .insert(EPHEMERAL_MACHINE.to_string(), profile.to_string());
+        let mut state = crate::sync::SyncState::load()?;
+        let machine_state = crate::sync::MachineState::new(EPHEMERAL_MACHINE);
+        crate::sync::import_packages(&config, &sync_path, &mut state, &machine_state, false, &[])
+            .await?;
+    }
+
+    Output::success(&format!(
+        "Applied {} file(s) from profile '{}' in {:.1?}",
+        applied,
+        profile,
+        start.elapsed()
+    ));
+    Ok(())
+}
+
+/// Create an age identity that unlocks the repo key, for `TETHER_KEY` in containers.
+/// Only the wrapped key is committed; the identity is printed once.
+pub async fn issue_identity() -> Result<()> {
+    let config = Config::load()?;
+    if !config.security.encrypt_dotfiles {
+        anyhow::bail!(
+            "The sync repo isn't encrypted, so bootstrap needs no {}",
+            KEY_ENV
+        );
+    }
+    let key = crate::security::get_encryption_key()?;
+    let sync_path = SyncEngine::sync_path()?;
+    let identity = age::x25519::Identity::generate();
+    let recipient = identity.to_public().to_string();
+
+    super::secrets::commit_change(&config, &sync_path, "Add bootstrap identity", || {
+        crate::security::hardware::wrap_key(&sync_path, &key, &recipient).map(|_| ())
+    })?;
+
+    Output::success("Created an identity that can decrypt the sync repo:");
+    println!("{}", identity.to_string().expose_secret());
+    Output::info(&format!(
+        "Store it as the {} secret of your devcontainer or Codespaces",
+        KEY_ENV
+    ));
+    Output::dim("  Anyone holding it can decrypt your dotfiles; 'tether key rotate' revokes it");
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::TempDir;
+
+    #[test]
+    fn test_key_from_env_identity() {
+        let temp = TempDir::new().unwrap();
+        let root = temp.path();
+        let key = crate::security::generate_key();
+
+        let identity = age::x25519::Identity::generate();
+        let recipient = identity.to_public().to_string();
+        crate::security::hardware::wrap_key(root, &key, &recipient).unwrap();
+        let secret = identity.to_string();
+        assert_eq!(key_from_env(root, secret.expose_secret()).unwrap(), key);
+
+        let other = age::x25519::Identity::generate().to_string();
+        assert!(key_from_env(root, other.expose_secret()).is_err());
+    }
+
+    #[test]
+    fn test_render_stored_without_config() {
+        let temp = TempDir::new().unwrap();
+        let root = temp.path();
+        let write = |rel: &str, content: &str| {
+            let path = root.join(rel);
+            std::fs::create_dir_all(path.parent().unwrap()).unwrap();
+            std::fs::write(path, content).unwrap();
+        };
+        write("profiles/shared/.gitconfig", "[user]");
+        write("profiles/shared/.zshrc", "shared");
+        write("profiles/dev/.zshrc", "dev");
+        write("profiles/work/.vimrc", "set nu");
+        write("configs/.config/htop/htoprc", "fields=0");
+
+        let files = render_stored(root, "dev", None).unwrap();
+        let paths: Vec<&str> = files.keys().map(String::as_str).collect();
+        assert_eq!(paths, vec![".config/htop/htoprc", ".gitconfig", ".zshrc"]);
+        assert_eq!(files[".zshrc"].0, b"dev");
+    }
+}
@@ -0,0 +1,293 @@
+use crate::cli::{Output, Prompt};
+use crate::config::Config;
+use crate::sync::{GitBackend, SyncEngine};
+use anyhow::Result;
+use std::path::Path;
+
+/// What a sync repo file holds, which decides the actions it offers
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub(super) enum Kind {
+    Dotfile,
+    DirFile,
+    Project,
+    Secret,
+    Lock,
+    Machine,
+    Other,
+}
+
+impl Kind {
+    fn label(self) -> &'static str {
+        match self {
+            Kind::Dotfile => "dotfile",
+            Kind::DirFile => "dir",
+            Kind::Project => "project",
+            Kind::Secret => "secret",
+            Kind::Lock => "lock",
+            Kind::Machine => "machine",
+            Kind::Other => "other",
+        }
+    }
+
+    fn section(self) -> &'static str {
+        match self {
+            Kind::Dotfile => "Dotfiles",
+            Kind::DirFile => "Synced directories",
+            Kind::Project => "Project configs",
+            Kind::Secret => "Secrets",
+            Kind::Lock => "Locks",
+            Kind::Machine => "Machines",
+            Kind::Other => "Other",
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub(super) struct RepoEntry {
+    pub(super) repo_path: String,
+    pub(super) kind: Kind,
+    /// Home-relative path the file is applied to, if it has one
+    pub(super) home_path: Option<String>,
+    /// Profile directory of a dotfile (`shared` or a profile name)
+    pub(super) profile: Option<String>,
+}
+
+/// Stored dotfile name -> dotfile path (encrypted names drop the leading dot)
+fn dotfile_name(name: &str) -> String {
+    match name.strip_suffix(".enc") {
+        Some(n) => format!(".{}", n),
+        None => name.to_string(),
+    }
+}
+
+/// Describe a tracked repo file; `None` for bookkeeping (chunks, integrity manifest,
+/// format marker) that has nothing to browse
+pub(super) fn classify(repo_path: &str) -> Option<RepoEntry> {
+    let (top, rest) = repo_path.split_once('/').unwrap_or((repo_path, ""));
+    let mut entry = RepoEntry {
+        repo_path: repo_path.to_string(),
+        kind: Kind::Other,
+        home_path: None,
+        profile: None,
+    };
+    match top {
+        crate::sync::chunks::CHUNKS_DIR | "format_version" | "integrity.json" => return None,
+        "profiles" => {
+            let (profile, name) = rest.split_once('/')?;
+            entry.kind = Kind::Dotfile;
+            entry.home_path = Some(dotfile_name(name));
+            entry.profile = Some(profile.to_string());
+        }
+        "dotfiles" => {
+            entry.kind = Kind::Dotfile;
+            entry.home_path = Some(dotfile_name(rest));
+        }
+        "configs" if !rest.starts_with("tether/") => {
+            entry.kind = Kind::DirFile;
+            entry.home_path = Some(rest.strip_suffix(".enc").unwrap_or(rest).to_string());
+        }
+        "projects" => entry.kind = Kind::Project,
+        crate::sync::personal_secrets::SECRETS_DIR => entry.kind = Kind::Secret,
+        crate::sync::freeze::LOCKS_DIR => entry.kind = Kind::Lock,
+        "machines" => entry.kind = Kind::Machine,
+        _ => {}
+    }
+    Some(entry)
+}
+
+impl RepoEntry {
+    pub(super) fn display(&self) -> String {
+        match (&self.home_path, &self.profile) {
+            (Some(home), Some(profile)) => format!("~/{}  ({})", home, profile),
+            (Some(home), None) => format!("~/{}", home),
+            _ => self.repo_path.clone(),
+        }
+    }
+}
+
+/// Browse everything stored in the sync repo, with per-file actions. Prints a plain
+/// listing instead with `list` or when stdin isn't a terminal.
+pub async fn run(list: bool) -> Result<()> {
+    let sync_path = SyncEngine::sync_path()?;
+    if !sync_path.exists() {
+        anyhow::bail!("Sync repository not found. Run 'tether init' first.");
+    }
+    let git = GitBackend::open(&sync_path)?;
+    let mut entries: Vec<RepoEntry> = git
+        .list_tracked_files(".")?
+        .iter()
+        .filter_map(|p| classify(p))
+        .collect();
+    entries.sort_by_key(|e| (e.kind, e.display()));
+
+    if entries.is_empty() {
+        Output::info("The sync repo is empty");
+        return Ok(());
+    }
+    if list || !std::io::IsTerminal::is_terminal(&std::io::stdin()) {
+        print_listing(&entries);
+        return Ok(());
+    }
+
+    let rows: Vec<String> = entries
+        .iter()
+        .map(|e| format!("{:<8} {}", e.kind.label(), e.display()))
+        .collect();
+    let mut cursor = 0;
+    loop {
+        let mut options: Vec<&str> = rows.iter().map(|s| s.as_str()).collect();
+        options.push("Quit");
+        // Esc quits too
+        let Ok(choice) = Prompt::select("Sync repo (type to filter)", options, cursor) else {
+            break;
+        };
+        if choice == rows.len() {
+            break;
+        }
+        cursor = choice;
+        entry_menu(&sync_path, &git, &entries[choice])?;
+    }
+    Ok(())
+}
+
+fn print_listing(entries: &[RepoEntry]) {
+    let mut current = None;
+    for entry in entries {
+        if current != Some(entry.kind) {
+            current = Some(entry.kind);
+            let count = entries.iter().filter(|e| e.kind == entry.kind).count();
+            Output::section(&format!("{} ({})", entry.kind.section(), count));
+        }
+        Output::list_item(&entry.display());
+    }
+    println!();
+}
+
+fn entry_menu(sync_path: &Path, git: &GitBackend, entry: &RepoEntry) -> Result<()> {
+    let mut actions = vec!["Show content", "History"];
+    if entry.home_path.is_some() {
+        actions.push("Restore a past version");
+    }
+    if entry.kind == Kind::Dotfile {
+        actions.push("Untrack");
+    }
+    actions.push("Back");
+
+    loop {
+        let Ok(choice) = Prompt::select(&entry.display(), actions.clone(), 0) else {
+            return Ok(());
+        };
+        let result = match actions[choice] {
+            "Show content" => show(sync_path, entry),
+            "History" => {
+                super::history::print_log(&entry.display(), &git.file_log(&entry.repo_path, 20)?);
+                Ok(())
+            }
+            "Restore a past version" => super::restore::restore_from_history(
+                &entry.repo_path,
+                entry.home_path.as_deref().unwrap_or_default(),
+                None,
+            ),
+            "Untrack" => untrack(entry.home_path.as_deref().unwrap_or_default()),
+            _ => return Ok(()),
+        };
+        if let Err(e) = result {
+            Output::error(&e.to_string());
+        }
+    }
+}
+
+fn show(sync_path: &Path, entry: &RepoEntry) -> Result<()> {
+    let mut content = std::fs::read(sync_path.join(&entry.repo_path))?;
+    if entry.repo_path.ends_with(".enc") {
+        let key = crate::security::get_encryption_key()?;
+        let plaintext = crate::security::decrypt(&content, &key)?;
+        content = crate::sync::chunks::read_from_repo(
+            sync_path,
+            &sync_path.join(&entry.repo_path),
+            plaintext,
+            &key,
+        )?;
+    }
+
+    Output::section(&entry.display());
+    if crate::sync::is_binary(&content) {
+        Output::dim(&format!("  {} bytes of binary data", content.len()));
+    } else {
+        for line in String::from_utf8_lossy(&content).lines() {
+            println!("  {}", line);
+        }
+    }
+    println!();
+    Ok(())
+}
+
+/// Stop syncing a dotfile: drop it from the global list and every profile. The local
+/// copy stays; the repo copy goes with the next `tether prune`, once no machine uses it.
+fn untrack(file: &str) -> Result<()> {
+    if !Prompt::confirm(
+        &format!("Stop syncing ~/{}? The local copy is kept.", file),
+        false,
+    )? {
+        return Ok(());
+    }
+
+    let mut config = Config::load()?;
+    let before = config.dotfiles.files.len()
+        + config
+            .profiles
+            .values()
+            .map(|p| p.dotfiles.len())
+            .sum::<usize>();
+    config.dotfiles.files.retain(|e| e.path() != file);
+    for profile in config.profiles.values_mut() {
+        profile.dotfiles.retain(|e| e.path() != file);
+    }
+    let after = config.dotfiles.files.len()
+        + config
+            .profiles
+            .values()
+            .map(|p| p.dotfiles.len())
+            .sum::<usize>();
+
+    if before == after {
+        Output::warning(&format!(
+            "~/{} isn't listed in the config (a glob pattern may match it)",
+            file
+        ));
+        return Ok(());
+    }
+    config.save()?;
+    Output::success(&format!("~/{} is no longer synced", file));
+    Output::dim("  `tether prune` deletes the repo copy once no machine syncs it");
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_classify_repo_paths() {
+        let zshrc = classify("profiles/shared/zshrc.enc").unwrap();
+        assert_eq!(zshrc.kind, Kind::Dotfile);
+        assert_eq!(zshrc.home_path.as_deref(), Some(".zshrc"));
+        assert_eq!(zshrc.display(), "~/.zshrc  (shared)");
+
+        let plain = classify("profiles/dev/.bashrc").unwrap();
+        assert_eq!(plain.home_path.as_deref(), Some(".bashrc"));
+
+        let dir_file = classify("configs/.config/nvim/init.lua.enc").unwrap();
+        assert_eq!(dir_file.kind, Kind::DirFile);
+        assert_eq!(dir_file.home_path.as_deref(), Some(".config/nvim/init.lua"));
+
+        let tether_config = classify("configs/tether/config.toml.enc").unwrap();
+        assert_eq!(tether_config.kind, Kind::Other);
+        assert!(tether_config.home_path.is_none());
+
+        assert_eq!(classify("secrets/TOKEN.enc").unwrap().kind, Kind::Secret);
+        assert_eq!(classify("locks/base.json").unwrap().kind, Kind::Lock);
+        assert!(classify("chunks/ab/abcdef.enc").is_none());
+        assert!(classify("integrity.json").is_none());
+    }
+}
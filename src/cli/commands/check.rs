@@ -0,0 +1,51 @@
+use crate::cli::{Output, Progress};
+use crate::config::Config;
+use crate::sync::stale::{find_stale, Staleness};
+use crate::sync::{GitBackend, SyncEngine, SyncState};
+use anyhow::Result;
+
+/// Report synced dotfiles that are behind the remote. Returns whether everything
+/// (or `file`) is up to date.
+pub async fn run(file: Option<&str>) -> Result<bool> {
+    let config = Config::load()?;
+    let state = SyncState::load()?;
+    let home = crate::home_dir()?;
+    let git = GitBackend::open(&SyncEngine::sync_path()?)?;
+
+    let only = file.map(|f| super::sync::home_relative(f, &home));
+    if let Some(rel) = &only {
+        if !crate::config::is_safe_dotfile_path(rel) {
+            anyhow::bail!("Unsafe file path: {}", rel);
+        }
+    }
+
+    let pb = Progress::spinner("Checking remote...");
+    let fetched = git.fetch();
+    pb.finish_and_clear();
+    if let Err(e) = fetched {
+        Output::warning(&format!("Could not reach remote, using last fetch: {}", e));
+    }
+
+    let stale = find_stale(&config, &state, &git, &home, only.as_deref())?;
+    if stale.is_empty() {
+        match &only {
+            Some(rel) => Output::success(&format!("~/{} is up to date", rel)),
+            None => Output::success("All synced dotfiles are up to date"),
+        }
+        return Ok(true);
+    }
+
+    for file in &stale {
+        match file.staleness {
+            Staleness::Behind => Output::warning(&format!(
+                "~/{} changed on another machine - run 'tether sync' before editing",
+                file.file
+            )),
+            Staleness::Diverged => Output::error(&format!(
+                "~/{} changed here and on another machine - the next sync will conflict",
+                file.file
+            )),
+        }
+    }
+    Ok(false)
+}
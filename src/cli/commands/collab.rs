@@ -226,7 +226,11 @@ pub async fn join(url: &str) -> Result<()> {
     std::fs::create_dir_all(collab_dir.parent().unwrap())?;
 
     let pb = Progress::spinner("Cloning collab repository...");
-    GitBackend::clone(url, &collab_dir)?;
+    GitBackend::clone_with_progress(
+        url,
+        &collab_dir,
+        Some(Progress::transfer(&pb, "Cloning collab repository...")),
+    )?;
     Progress::finish_success(&pb, "Repository cloned");
 
     // Read metadata to get projects
@@ -302,7 +306,7 @@ pub async fn join(url: &str) -> Result<()> {
         Ok(key) => key,
         Err(_) => {
             Output::info("Creating age identity...");
-            crate::cli::commands::identity::init().await?;
+            crate::cli::commands::identity::init(false).await?;
             crate::security::get_public_key()?
         }
     };
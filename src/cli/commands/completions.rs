@@ -0,0 +1,238 @@
+//! Shell completions and `tether completions doctor`.
+//!
+//! The completion scripts are thin: they pass the words typed so far to the hidden
+//! `tether __complete` command, which walks the clap command tree, so completions
+//! follow the installed binary and never need regenerating after an upgrade.
+
+use crate::cli::shell_rc::{self, Shell};
+use crate::cli::Output;
+use anyhow::Result;
+use clap::CommandFactory;
+use std::path::Path;
+
+const BASH_SCRIPT: &str = r#"_tether() {
+    local IFS=$'\n'
+    COMPREPLY=($(tether __complete --index "$COMP_CWORD" -- "${COMP_WORDS[@]}" 2>/dev/null))
+}
+complete -o default -F _tether tether
+"#;
+
+const ZSH_SCRIPT: &str = r#"_tether() {
+    local -a candidates
+    candidates=("${(@f)$(tether __complete --index $((CURRENT - 1)) -- "${words[@]}" 2>/dev/null)}")
+    if [[ -n "${candidates[1]}" ]]; then
+        compadd -a candidates
+    else
+        _files
+    fi
+}
+compdef _tether tether
+"#;
+
+const FISH_SCRIPT: &str = r#"complete -c tether -f -a '(tether __complete --index (count (commandline -opc)) -- (commandline -opc) (commandline -ct))'
+"#;
+
+pub fn script(shell: Shell) -> &'static str {
+    match shell {
+        Shell::Bash => BASH_SCRIPT,
+        Shell::Zsh => ZSH_SCRIPT,
+        Shell::Fish => FISH_SCRIPT,
+    }
+}
+
+/// The rc file line that loads completions
+fn completion_line(shell: Shell) -> String {
+    match shell {
+        Shell::Fish => "tether completions fish | source".to_string(),
+        _ => format!("eval \"$(tether completions {})\"", shell.name()),
+    }
+}
+
+/// The rc file line that puts `dir` on PATH, relative to `$HOME` when it's inside it
+fn path_line(shell: Shell, dir: &Path, home: &Path) -> String {
+    let dir = match dir.strip_prefix(home) {
+        Ok(relative) => format!("$HOME/{}", relative.display()),
+        Err(_) => dir.display().to_string(),
+    };
+    match shell {
+        Shell::Fish => format!("fish_add_path \"{}\"", dir),
+        _ => format!("export PATH=\"{}:$PATH\"", dir),
+    }
+}
+
+/// Completion candidates for `words[index]`: subcommands, or long flags when the word
+/// starts with `-`. Empty after a flag that takes a value, so the shell falls back to
+/// file names.
+pub fn candidates(index: usize, words: &[String]) -> Vec<String> {
+    let mut root = crate::cli::Cli::command();
+    // Propagates global flags to subcommands
+    root.build();
+    let current = words.get(index).map(String::as_str).unwrap_or("");
+
+    let mut command = &root;
+    let mut expects_value = false;
+    for word in words.iter().take(index).skip(1) {
+        if std::mem::take(&mut expects_value) {
+            continue;
+        }
+        if let Some(long) = word.strip_prefix("--") {
+            expects_value = !long.contains('=')
+                && command
+                    .get_arguments()
+                    .any(|arg| arg.get_long() == Some(long) && arg.get_action().takes_values());
+        } else if let Some(short) = word.strip_prefix('-') {
+            let mut chars = short.chars();
+            if let (Some(short), None) = (chars.next(), chars.next()) {
+                expects_value = command
+                    .get_arguments()
+                    .any(|arg| arg.get_short() == Some(short) && arg.get_action().takes_values());
+            }
+        } else if let Some(subcommand) = command.find_subcommand(word) {
+            command = subcommand;
+        }
+    }
+    if expects_value {
+        return Vec::new();
+    }
+
+    let mut candidates: Vec<String> = if current.starts_with('-') {
+        command
+            .get_arguments()
+            .filter(|arg| !arg.is_hide_set())
+            .filter_map(|arg| arg.get_long().map(|long| format!("--{}", long)))
+            .collect()
+    } else {
+        command
+            .get_subcommands()
+            .filter(|subcommand| !subcommand.is_hide_set())
+            .map(|subcommand| subcommand.get_name().to_string())
+            .collect()
+    };
+    candidates.retain(|candidate| candidate.starts_with(current));
+    candidates.sort();
+    candidates.dedup();
+    candidates
+}
+
+pub fn complete(index: usize, words: &[String]) -> Result<()> {
+    for candidate in candidates(index, words) {
+        println!("{}", candidate);
+    }
+    Ok(())
+}
+
+pub fn print(shell: Shell) -> Result<()> {
+    print!("{}", script(shell));
+    Ok(())
+}
+
+/// Check that tether is on PATH and completions are loaded in the user's shell, and
+/// with `fix`, add what's missing to its startup file.
+pub fn doctor(fix: bool) -> Result<()> {
+    let Some(shell) = Shell::detect() else {
+        Output::warning(
+            "Couldn't tell your shell from $SHELL; add the output of 'tether completions <bash|zsh|fish>' to its startup file",
+        );
+        return Ok(());
+    };
+    let home = crate::home_dir()?;
+    let rc_file = shell.rc_file(&home);
+    let rc_display = rc_file
+        .strip_prefix(&home)
+        .map(|relative| format!("~/{}", relative.display()))
+        .unwrap_or_else(|_| rc_file.display().to_string());
+    let rc_content = std::fs::read_to_string(&rc_file).unwrap_or_default();
+
+    Output::section("Shell Integration");
+    Output::key_value("Shell", shell.name());
+    Output::key_value("Startup file", &rc_display);
+    println!();
+
+    let mut missing = Vec::new();
+
+    let exe = std::env::current_exe()?.canonicalize()?;
+    let on_path = which::which("tether")
+        .ok()
+        .and_then(|found| found.canonicalize().ok())
+        .is_some_and(|found| found == exe);
+    if on_path {
+        Output::success("tether is on PATH");
+    } else if let Some(dir) = exe.parent() {
+        let line = path_line(shell, dir, &home);
+        if rc_content.contains(&line) {
+            Output::warning(&format!(
+                "{} is added to PATH in {}, but not in this shell; open a new one",
+                dir.display(),
+                rc_display
+            ));
+        } else {
+            Output::warning(&format!(
+                "{} isn't on PATH, so new shells won't find this tether",
+                dir.display()
+            ));
+            missing.push(line);
+        }
+    }
+
+    let completions = completion_line(shell);
+    if rc_content.contains("tether completions") {
+        Output::success("Completions are loaded");
+    } else {
+        Output::warning(&format!("Completions aren't loaded in {}", rc_display));
+        missing.push(completions);
+    }
+
+    if missing.is_empty() {
+        return Ok(());
+    }
+    if !fix {
+        println!();
+        Output::info(&format!(
+            "Run 'tether completions doctor --fix' to add to {}:",
+            rc_display
+        ));
+        for line in &missing {
+            Output::dim(&format!("  {}", line));
+        }
+        return Ok(());
+    }
+    for line in &missing {
+        if shell_rc::append_line(&rc_file, line)? {
+            Output::success(&format!("Added to {}: {}", rc_display, line));
+        }
+    }
+    Output::info(&format!(
+        "Open a new shell or run 'source {}' to pick it up",
+        rc_display
+    ));
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn words(line: &str) -> Vec<String> {
+        line.split(' ').map(String::from).collect()
+    }
+
+    #[test]
+    fn test_candidates() {
+        let top = candidates(1, &words("tether st"));
+        assert_eq!(top, vec!["status".to_string()]);
+        assert!(!candidates(1, &words("tether ")).contains(&"__complete".to_string()));
+
+        let doctor = candidates(2, &words("tether completions d"));
+        assert_eq!(doctor, vec!["doctor".to_string()]);
+        let flags = candidates(3, &words("tether completions doctor --"));
+        assert!(flags.contains(&"--fix".to_string()));
+        assert!(flags.contains(&"--yes".to_string()));
+
+        // A flag's value is left to the shell
+        assert!(candidates(2, &words("tether --context w")).is_empty());
+        assert_eq!(
+            candidates(3, &words("tether --context work sta")),
+            vec!["status".to_string()]
+        );
+    }
+}
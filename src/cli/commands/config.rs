@@ -76,7 +76,19 @@ pub async fn set(key: &str, value: &str) -> Result<()> {
     };
 
     // Parse the value string into appropriate TOML type
-    let new_value: toml::Value = if value == "true" {
+    let new_value: toml::Value = if key == "sync.interval" {
+        let Some(secs) = crate::config::SyncConfig::parse_interval(value) else {
+            Output::error(&format!(
+                "Invalid sync interval '{}': use seconds or a number with s, m or h (e.g. 10m)",
+                value
+            ));
+            return Ok(());
+        };
+        if secs < 60 {
+            Output::warning("The daemon syncs at most once a minute; using 1m");
+        }
+        toml::Value::String(value.trim().to_string())
+    } else if value == "true" {
         toml::Value::Boolean(true)
     } else if value == "false" {
         toml::Value::Boolean(false)
@@ -86,8 +98,8 @@ pub async fn set(key: &str, value: &str) -> Result<()> {
         toml::Value::Float(f)
     } else if value.starts_with('[') && value.ends_with(']') {
         // Array value - parse as TOML
-        match toml::from_str(value) {
-            Ok(v) => v,
+        match toml::from_str::<toml::Table>(&format!("value = {}", value)) {
+            Ok(mut t) => t.remove("value").unwrap_or(toml::Value::Array(Vec::new())),
             Err(e) => {
                 Output::error(&format!("Failed to parse array: {}", e));
                 return Ok(());
@@ -103,6 +115,7 @@ pub async fn set(key: &str, value: &str) -> Result<()> {
     let config_toml = toml::to_string_pretty(&toml_value)?;
     config = toml::from_str(&config_toml)?;
     config.save()?;
+    crate::daemon::ipc::reload_config();
 
     Output::success(&format!("Set {} = {}", key, value));
     Ok(())
@@ -133,6 +146,7 @@ pub async fn edit() -> Result<()> {
         match Config::load() {
             Ok(_) => {
                 Output::success("Config updated successfully");
+                crate::daemon::ipc::reload_config();
             }
             Err(e) => {
                 Output::error(&format!("Config validation failed: {}", e));
@@ -157,7 +171,7 @@ pub async fn dotfiles() -> Result<()> {
         println!();
         Output::subheader("Home Directory (~/)");
         render_dotfile_table("Files", &config.dotfiles.files);
-        render_entry_table("Folders", &config.dotfiles.dirs);
+        render_dotfile_table("Folders", &config.dotfiles.dirs);
 
         // Section 2: Project configs
         println!();
@@ -168,7 +182,7 @@ pub async fn dotfiles() -> Result<()> {
         };
         Output::subheader(&format!("Project Configs ({})", status));
         render_entry_table("Search Paths", &config.project_configs.search_paths);
-        render_entry_table("File Patterns", &config.project_configs.patterns);
+        render_dotfile_table("File Patterns", &config.project_configs.patterns);
 
         let options = vec![
             "Dotfiles",
@@ -191,7 +205,7 @@ pub async fn dotfiles() -> Result<()> {
                 "file path (e.g., .zshrc)",
                 &mut config.dotfiles.files,
             )?),
-            1 => Some(manage_entry_list(
+            1 => Some(manage_dotfile_list(
                 "Dotfile Folders",
                 "folder path (e.g., .config/nvim)",
                 &mut config.dotfiles.dirs,
@@ -201,7 +215,7 @@ pub async fn dotfiles() -> Result<()> {
                 "path (e.g., ~/Projects)",
                 &mut config.project_configs.search_paths,
             )?),
-            3 => Some(manage_entry_list(
+            3 => Some(manage_dotfile_list(
                 "Project File Patterns",
                 "pattern (e.g., .env.local)",
                 &mut config.project_configs.patterns,
@@ -420,7 +434,7 @@ fn manage_dotfile_list(
 
                 let paths: Vec<String> = entries.iter().map(|e| e.path().to_string()).collect();
                 let selection =
-                    InquireSelect::new("Select file to toggle create_if_missing", paths)
+                    InquireSelect::new("Select entry to toggle create_if_missing", paths)
                         .prompt()?;
 
                 if let Some(entry) = entries.iter_mut().find(|e| e.path() == selection) {
@@ -499,6 +513,7 @@ pub async fn features_enable(feature: &str) -> Result<()> {
     match set_feature(&mut config.features, feature, true) {
         Ok(()) => {
             config.save()?;
+            crate::daemon::ipc::reload_config();
             Output::success(&format!("Enabled {}", feature));
             show_feature_guidance(feature, true);
         }
@@ -515,6 +530,7 @@ pub async fn features_disable(feature: &str) -> Result<()> {
     match set_feature(&mut config.features, feature, false) {
         Ok(()) => {
             config.save()?;
+            crate::daemon::ipc::reload_config();
             Output::success(&format!("Disabled {}", feature));
         }
         Err(e) => Output::error(&e.to_string()),
@@ -1,6 +1,7 @@
 use crate::cli::Output;
 use crate::config::Config;
-use crate::daemon::DaemonServer;
+use crate::daemon::ipc::{self, DaemonRequest};
+use crate::daemon::pid::{self, PidState};
 use anyhow::Result;
 use std::fs::{self, OpenOptions};
 use std::io;
@@ -25,17 +26,34 @@ impl DaemonPaths {
     }
 }
 
-pub async fn start() -> Result<()> {
+/// Contexts a daemon started here serves: this one, plus with `all` every other
+/// context set up on this machine
+fn served_contexts(all: bool) -> Result<Vec<Option<String>>> {
+    let current = crate::config::current_context()?;
+    let mut contexts = vec![current.clone()];
+    if all {
+        let home = crate::home_dir()?;
+        contexts.extend(
+            crate::config::discover_contexts(&home)
+                .into_iter()
+                .filter(|c| *c != current),
+        );
+    }
+    Ok(contexts)
+}
+
+pub async fn start(all_contexts: bool) -> Result<()> {
     let paths = DaemonPaths::new()?;
     fs::create_dir_all(&paths.dir)?;
 
-    if let Some(pid) = read_daemon_pid()? {
-        if is_process_running(pid) {
-            Output::info(&format!("Daemon already running (PID {pid})"));
-            return Ok(());
-        } else {
-            let _ = cleanup_pid_file(Some(pid));
-        }
+    if let Some(pid) = pid::check()?.running() {
+        Output::info(&format!("Daemon already running (PID {pid})"));
+        return Ok(());
+    }
+    if let Some(stale) = pid::clean_stale()? {
+        Output::info(&format!(
+            "Cleaned up after a daemon that didn't exit cleanly (PID {stale})"
+        ));
     }
 
     let exe = std::env::current_exe()?;
@@ -49,9 +67,12 @@ pub async fn start() -> Result<()> {
         .append(true)
         .open(&paths.log)?;
 
-    let child = Command::new(exe)
-        .arg("daemon")
-        .arg("run")
+    let mut command = Command::new(exe);
+    command.arg("daemon").arg("run");
+    if all_contexts {
+        command.arg("--all-contexts");
+    }
+    let child = command
         .stdin(Stdio::null())
         .stdout(Stdio::from(stdout))
         .stderr(Stdio::from(stderr))
@@ -60,25 +81,33 @@ pub async fn start() -> Result<()> {
     let pid = child.id();
     fs::write(&paths.pid, pid.to_string())?;
     Output::success(&format!("Daemon started (PID {pid})"));
+    if all_contexts {
+        let contexts = served_contexts(true)?;
+        let names: Vec<&str> = contexts
+            .iter()
+            .map(|c| c.as_deref().unwrap_or("default"))
+            .collect();
+        Output::info(&format!("Syncing contexts: {}", names.join(", ")));
+    }
     Ok(())
 }
 
 pub async fn stop() -> Result<()> {
     let paths = DaemonPaths::new()?;
-    let pid = match read_daemon_pid()? {
-        Some(pid) => pid,
-        None => {
+    // Never signal a process that only inherited a dead daemon's pid
+    let pid = match pid::check()? {
+        PidState::Running(pid) => pid,
+        PidState::Stale { .. } => {
+            pid::clean_stale()?;
+            Output::info("Daemon is not running (removed a stale PID file)");
+            return Ok(());
+        }
+        PidState::Missing => {
             Output::info("Daemon is not running");
             return Ok(());
         }
     };
 
-    if !is_process_running(pid) {
-        Output::info("Daemon is not running");
-        let _ = cleanup_pid_file(Some(pid));
-        return Ok(());
-    }
-
     let signal_result = unsafe { libc::kill(pid as libc::pid_t, libc::SIGTERM) };
     if signal_result != 0 {
         let err = io::Error::last_os_error();
@@ -89,20 +118,20 @@ pub async fn stop() -> Result<()> {
 
     // Graceful: wait up to 10 seconds
     for _ in 0..50 {
-        if !is_process_running(pid) {
+        if !pid::is_process_running(pid) {
             break;
         }
         sleep(Duration::from_millis(200)).await;
     }
 
     // Force kill if still running
-    if is_process_running(pid) {
+    if pid::is_process_running(pid) {
         log::debug!("Daemon did not exit gracefully, sending SIGKILL");
         unsafe { libc::kill(pid as libc::pid_t, libc::SIGKILL) };
 
         // Wait for forced termination
         for _ in 0..10 {
-            if !is_process_running(pid) {
+            if !pid::is_process_running(pid) {
                 break;
             }
             sleep(Duration::from_millis(200)).await;
@@ -110,115 +139,261 @@ pub async fn stop() -> Result<()> {
     }
 
     // Final check
-    if is_process_running(pid) {
+    if pid::is_process_running(pid) {
         return Err(anyhow::anyhow!(
             "Daemon did not exit after SIGKILL. Check logs: {}",
             paths.log.display()
         ));
     }
 
-    cleanup_pid_file(Some(pid))?;
+    pid::remove(&paths.pid, Some(pid))?;
     Output::success("Daemon stopped");
     Ok(())
 }
 
-pub async fn restart() -> Result<()> {
+pub async fn restart(all_contexts: bool) -> Result<()> {
+    // Keep serving every context if the running daemon does
+    let all_contexts = all_contexts || ipc::query().is_some_and(|status| status.contexts.len() > 1);
     Output::info("Restarting daemon...");
     stop().await?;
     sleep(Duration::from_millis(500)).await;
-    start().await
+    start(all_contexts).await
 }
 
-pub async fn logs() -> Result<()> {
+/// Print the end of the daemon log, or everything since `since`, then with `follow`
+/// keep printing what the daemon writes
+pub async fn logs(follow: bool, since: Option<&str>, crashes: bool) -> Result<()> {
     let log_path = DaemonPaths::new()?.log;
-    if !log_path.exists() {
+    let since = since
+        .map(|since| parse_since(since, chrono::Utc::now()))
+        .transpose()?;
+    if !log_path.exists() && !follow {
         Output::info("No daemon logs yet");
         return Ok(());
     }
 
     Output::info(&format!("Showing daemon logs ({})", log_path.display()));
-    let content = fs::read_to_string(&log_path)?;
-    let lines: Vec<&str> = content.lines().collect();
-    let start = lines.len().saturating_sub(50);
-
-    for line in &lines[start..] {
-        println!("{line}");
+    let content = fs::read_to_string(&log_path).unwrap_or_default();
+    if since.is_some() || crashes {
+        let mut all = Vec::new();
+        for rotated in crate::daemon::logs::rotated(&log_path) {
+            all.extend(fs::read_to_string(rotated)?.lines().map(String::from));
+        }
+        all.extend(content.lines().map(String::from));
+        let mut lines = match since {
+            Some(since) => crate::daemon::logs::lines_since(&all, since),
+            None => all.iter().map(String::as_str).collect(),
+        };
+        if crashes {
+            lines = crate::daemon::logs::crash_lines(&lines);
+            if lines.is_empty() {
+                Output::success("No crashes logged");
+            }
+        }
+        for line in lines {
+            println!("{line}");
+        }
+    } else {
+        let lines: Vec<&str> = content.lines().collect();
+        for line in &lines[lines.len().saturating_sub(50)..] {
+            println!("{line}");
+        }
     }
 
+    if follow {
+        follow_log(&log_path, content.len() as u64).await?;
+    }
     Ok(())
 }
 
-pub async fn run_daemon() -> Result<()> {
-    let mut server = DaemonServer::new();
-    let pid = std::process::id();
-    log::info!("Daemon process starting (PID {pid})");
-
-    // Write PID file so dashboard/CLI can detect the running daemon
-    if let Ok(paths) = DaemonPaths::new() {
-        let _ = fs::write(&paths.pid, pid.to_string());
+/// Print lines as they're added to the log after `pos`, until interrupted. The daemon
+/// truncates the log when it rotates it, so a log shorter than before starts over.
+async fn follow_log(log_path: &std::path::Path, mut pos: u64) -> Result<()> {
+    use std::io::{Read, Seek, SeekFrom, Write};
+
+    let mut partial = String::new();
+    loop {
+        sleep(Duration::from_millis(500)).await;
+        let len = fs::metadata(log_path).map(|meta| meta.len()).unwrap_or(0);
+        if len < pos {
+            pos = 0;
+            partial.clear();
+        }
+        if len == pos {
+            continue;
+        }
+        let mut file = fs::File::open(log_path)?;
+        file.seek(SeekFrom::Start(pos))?;
+        let mut added = Vec::new();
+        file.take(len - pos).read_to_end(&mut added)?;
+        pos = len;
+        partial.push_str(&String::from_utf8_lossy(&added));
+        // Hold back a line the daemon is still writing
+        if let Some(end) = partial.rfind('\n') {
+            let mut stdout = io::stdout().lock();
+            stdout.write_all(&partial.as_bytes()[..=end])?;
+            stdout.flush()?;
+            partial.drain(..=end);
+        }
     }
+}
 
-    let result = server.run().await;
-    if let Err(err) = cleanup_pid_file(Some(pid)) {
-        log::warn!("Failed to clean up daemon pid file: {err}");
+/// When `--since` means: a duration back from `now` (`30m`, `2h`, `1d`), a date
+/// (local midnight) or a date and time
+fn parse_since(
+    since: &str,
+    now: chrono::DateTime<chrono::Utc>,
+) -> Result<chrono::DateTime<chrono::Utc>> {
+    use chrono::{Local, NaiveDate, NaiveDateTime, TimeZone, Utc};
+
+    let since = since.trim();
+    if let Ok(secs) = parse_pause_duration(since) {
+        return Ok(now - chrono::Duration::seconds(secs as i64));
     }
-    result
+    if let Ok(time) = chrono::DateTime::parse_from_rfc3339(since) {
+        return Ok(time.with_timezone(&Utc));
+    }
+    let local = [
+        "%Y-%m-%d %H:%M:%S",
+        "%Y-%m-%d %H:%M",
+        "%Y-%m-%dT%H:%M:%S",
+        "%Y-%m-%dT%H:%M",
+    ]
+    .iter()
+    .find_map(|format| NaiveDateTime::parse_from_str(since, format).ok())
+    .or_else(|| {
+        NaiveDate::parse_from_str(since, "%Y-%m-%d")
+            .ok()
+            .and_then(|date| date.and_hms_opt(0, 0, 0))
+    });
+    local
+        .and_then(|time| Local.from_local_datetime(&time).earliest())
+        .map(|time| time.with_timezone(&Utc))
+        .ok_or_else(|| {
+            anyhow::anyhow!(
+                "Invalid --since '{}': use a duration like 2h or 1d, or a date like 2024-05-01",
+                since
+            )
+        })
 }
 
-fn read_daemon_pid() -> Result<Option<u32>> {
-    let pid_path = DaemonPaths::new()?.pid;
-    if !pid_path.exists() {
-        return Ok(None);
+/// Parse a pause length like `90s`, `30m`, `2h` or `1d` into seconds
+fn parse_pause_duration(duration: &str) -> Result<u64> {
+    let duration = duration.trim();
+    let split = duration
+        .find(|c: char| !c.is_ascii_digit())
+        .unwrap_or(duration.len());
+    let (num, unit) = duration.split_at(split);
+    let n: u64 = num
+        .parse()
+        .map_err(|_| anyhow::anyhow!("Invalid duration: '{}' (e.g. 2h)", duration))?;
+    let unit_secs = match unit {
+        "s" => 1,
+        "m" | "" => 60,
+        "h" => 3600,
+        "d" => 86400,
+        _ => anyhow::bail!("Unknown unit in '{}': use s, m, h or d", duration),
+    };
+    let secs = n
+        .checked_mul(unit_secs)
+        .ok_or_else(|| anyhow::anyhow!("Duration too long: '{}'", duration))?;
+    if secs == 0 {
+        anyhow::bail!("Pause for at least a second, or leave out --for to pause until resumed");
     }
+    Ok(secs)
+}
 
-    let contents = fs::read_to_string(&pid_path)?;
-    match contents.trim().parse::<u32>() {
-        Ok(pid) if pid > 0 => Ok(Some(pid)),
-        _ => Ok(None),
-    }
+/// Pause the daemon serving this context, until resumed or for `duration`
+pub fn pause(duration: Option<&str>) -> Result<()> {
+    let secs = duration.map(parse_pause_duration).transpose()?;
+    control(DaemonRequest::Pause(secs))
 }
 
-fn is_process_running(pid: u32) -> bool {
-    unsafe {
-        if libc::kill(pid as libc::pid_t, 0) == 0 {
-            true
-        } else {
-            // ESRCH = no such process
-            io::Error::last_os_error().raw_os_error() != Some(libc::ESRCH)
+/// Send `request` to the daemon serving this context and report what it did
+pub fn control(request: DaemonRequest) -> Result<()> {
+    let status = ipc::send(request)?;
+    match request {
+        DaemonRequest::Status => {
+            Output::key_value("PID", &status.pid.to_string());
+            Output::key_value(
+                "Started",
+                &crate::cli::output::relative_time(status.started_at),
+            );
+            for context in &status.contexts {
+                Output::key_value(context.label(), &context.describe());
+            }
+        }
+        DaemonRequest::SyncNow => Output::success("Daemon is syncing now"),
+        DaemonRequest::Pause(secs) => {
+            let current = crate::config::current_context()?;
+            match status
+                .contexts
+                .iter()
+                .find(|c| c.context == current)
+                .filter(|_| secs.is_some())
+            {
+                Some(context) => Output::success(&format!(
+                    "Daemon paused {}; 'tether daemon resume' resumes it sooner",
+                    context.pause_end()
+                )),
+                None => Output::success(
+                    "Daemon paused; run 'tether daemon resume' to sync on schedule again",
+                ),
+            }
         }
+        DaemonRequest::Resume => Output::success("Daemon resumed"),
+        DaemonRequest::ReloadConfig => Output::success("Daemon reloaded its config"),
     }
+    Ok(())
 }
 
-fn cleanup_pid_file(expected_pid: Option<u32>) -> Result<()> {
-    let paths = DaemonPaths::new()?;
-    if !paths.pid.exists() {
-        return Ok(());
-    }
+pub async fn run_daemon(all_contexts: bool) -> Result<()> {
+    let pid = std::process::id();
+    log::info!("Daemon process starting (PID {pid})");
 
-    let contents = fs::read_to_string(&paths.pid)?;
-    if expected_pid
-        .map(|pid| contents.trim() == pid.to_string())
-        .unwrap_or(true)
-    {
-        let _ = fs::remove_file(&paths.pid);
+    // Write PID files so dashboard/CLI in every served context detect the running daemon
+    let home = crate::home_dir()?;
+    let contexts = served_contexts(all_contexts)?;
+    let pid_paths: Vec<PathBuf> = contexts
+        .iter()
+        .map(|c| crate::config::context_dir(&home, c.as_deref()).join("daemon.pid"))
+        .collect();
+    for path in &pid_paths {
+        let _ = fs::write(path, pid.to_string());
     }
 
-    Ok(())
+    let result = crate::daemon::server::run(contexts).await;
+    for path in &pid_paths {
+        if let Err(err) = pid::remove(path, Some(pid)) {
+            log::warn!("Failed to clean up daemon pid file: {err}");
+        }
+    }
+    result
 }
 
-const LAUNCHD_LABEL: &str = "com.tether.daemon";
-
 fn launchd_plist_path() -> Result<PathBuf> {
     let home = crate::home_dir()?;
     Ok(home
         .join("Library")
         .join("LaunchAgents")
-        .join(format!("{LAUNCHD_LABEL}.plist")))
+        .join(format!("{}.plist", crate::daemon::launchd_label()?)))
 }
 
-fn generate_plist() -> Result<String> {
+fn generate_plist(all_contexts: bool) -> Result<String> {
     let exe = std::env::current_exe()?;
     let paths = DaemonPaths::new()?;
+    let label = crate::daemon::launchd_label()?;
+    let context_args = match crate::config::current_context()? {
+        Some(context) => {
+            format!("\n        <string>--context</string>\n        <string>{context}</string>")
+        }
+        None => String::new(),
+    };
+    let all_contexts_arg = if all_contexts {
+        "\n        <string>--all-contexts</string>"
+    } else {
+        ""
+    };
 
     Ok(format!(
         r#"<?xml version="1.0" encoding="UTF-8"?>
@@ -226,12 +401,12 @@ fn generate_plist() -> Result<String> {
 <plist version="1.0">
 <dict>
     <key>Label</key>
-    <string>{LAUNCHD_LABEL}</string>
+    <string>{label}</string>
     <key>ProgramArguments</key>
     <array>
-        <string>{}</string>
+        <string>{}</string>{context_args}
         <string>daemon</string>
-        <string>run</string>
+        <string>run</string>{all_contexts_arg}
     </array>
     <key>RunAtLoad</key>
     <true/>
@@ -252,11 +427,25 @@ fn generate_plist() -> Result<String> {
     ))
 }
 
-pub async fn install() -> Result<()> {
-    #[cfg(not(target_os = "macos"))]
+pub async fn install(all_contexts: bool) -> Result<()> {
+    // A login agent would outlive the sandbox and sync a HOME that's gone
+    if std::env::var_os(super::sandbox::SANDBOX_ENV).is_some() {
+        anyhow::bail!("Can't install the daemon from a tether sandbox; use 'tether daemon start'");
+    }
+    #[cfg(windows)]
     {
+        if pid::check()?.running().is_some() {
+            Output::info("Stopping existing daemon...");
+            stop().await?;
+        }
+        return install_scheduled_task(all_contexts);
+    }
+
+    #[cfg(not(any(target_os = "macos", windows)))]
+    {
+        let _ = all_contexts;
         return Err(anyhow::anyhow!(
-            "Launchd is only available on macOS. Use 'tether daemon start' instead."
+            "Auto-start needs launchd (macOS) or Task Scheduler (Windows). Use 'tether daemon start' instead."
         ));
     }
 
@@ -265,11 +454,9 @@ pub async fn install() -> Result<()> {
         let plist_path = launchd_plist_path()?;
 
         // Stop existing daemon if running via manual start
-        if let Some(pid) = read_daemon_pid()? {
-            if is_process_running(pid) {
-                Output::info("Stopping existing daemon...");
-                stop().await?;
-            }
+        if pid::check()?.running().is_some() {
+            Output::info("Stopping existing daemon...");
+            stop().await?;
         }
 
         // Unload if already loaded
@@ -284,7 +471,7 @@ pub async fn install() -> Result<()> {
         }
 
         // Write plist
-        let plist = generate_plist()?;
+        let plist = generate_plist(all_contexts)?;
         fs::write(&plist_path, plist)?;
 
         // Load the service
@@ -308,9 +495,29 @@ pub async fn install() -> Result<()> {
 }
 
 pub async fn uninstall() -> Result<()> {
-    #[cfg(not(target_os = "macos"))]
+    #[cfg(windows)]
     {
-        return Err(anyhow::anyhow!("Launchd is only available on macOS"));
+        let name = crate::daemon::scheduled_task_name()?;
+        if crate::daemon::scheduled_task_status().is_none() {
+            Output::info("Scheduled task is not installed");
+            return Ok(());
+        }
+        let _ = Command::new("schtasks")
+            .args(["/End", "/TN", &name])
+            .output();
+        schtasks(&["/Delete", "/TN", &name, "/F"])?;
+        if pid::check()?.running().is_some() {
+            stop().await?;
+        }
+        Output::success(&format!("Scheduled task '{}' removed", name));
+        return Ok(());
+    }
+
+    #[cfg(not(any(target_os = "macos", windows)))]
+    {
+        return Err(anyhow::anyhow!(
+            "Auto-start needs launchd (macOS) or Task Scheduler (Windows)"
+        ));
     }
 
     #[cfg(target_os = "macos")]
@@ -340,3 +547,98 @@ pub async fn uninstall() -> Result<()> {
         Ok(())
     }
 }
+
+/// The command a login task runs: the daemon under a hidden PowerShell window (a
+/// console program started at login otherwise opens one), appending to `daemon.log`
+#[cfg(windows)]
+fn scheduled_task_command(all_contexts: bool) -> Result<String> {
+    let exe = std::env::current_exe()?;
+    let mut daemon = format!("& '{}'", exe.display());
+    if let Some(context) = crate::config::current_context()? {
+        daemon.push_str(&format!(" --context {}", context));
+    }
+    daemon.push_str(" daemon run");
+    if all_contexts {
+        daemon.push_str(" --all-contexts");
+    }
+    Ok(format!(
+        "powershell.exe -NoProfile -WindowStyle Hidden -Command \"{} *>> '{}'\"",
+        daemon,
+        DaemonPaths::new()?.log.display()
+    ))
+}
+
+/// Run `schtasks` with `args`, failing with what it printed
+#[cfg(windows)]
+fn schtasks(args: &[&str]) -> Result<()> {
+    let output = Command::new("schtasks").args(args).output()?;
+    if !output.status.success() {
+        anyhow::bail!("{}", String::from_utf8_lossy(&output.stderr).trim());
+    }
+    Ok(())
+}
+
+/// Register a Task Scheduler task that starts the daemon at login, and start it now
+#[cfg(windows)]
+fn install_scheduled_task(all_contexts: bool) -> Result<()> {
+    let name = crate::daemon::scheduled_task_name()?;
+    let command = scheduled_task_command(all_contexts)?;
+    // Task Scheduler's limit for /TR
+    if command.len() > 261 {
+        anyhow::bail!(
+            "The daemon command is too long for Task Scheduler ({} characters); install tether to a shorter path",
+            command.len()
+        );
+    }
+    fs::create_dir_all(DaemonPaths::new()?.dir)?;
+    schtasks(&[
+        "/Create", "/TN", &name, "/TR", &command, "/SC", "ONLOGON", "/RL", "LIMITED", "/F",
+    ])
+    .map_err(|e| {
+        anyhow::anyhow!(
+            "Failed to create scheduled task: {}. Login tasks may need an administrator prompt.",
+            e
+        )
+    })?;
+    schtasks(&["/Run", "/TN", &name])?;
+
+    Output::success(&format!("Scheduled task '{}' installed", name));
+    Output::info("Daemon will now start automatically when you log in");
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_pause_duration() {
+        assert_eq!(parse_pause_duration("2h").unwrap(), 7200);
+        assert_eq!(parse_pause_duration("90s").unwrap(), 90);
+        assert_eq!(parse_pause_duration("45").unwrap(), 2700);
+        assert_eq!(parse_pause_duration("1d").unwrap(), 86400);
+        assert!(parse_pause_duration("0h").is_err());
+        assert!(parse_pause_duration("2 weeks").is_err());
+        assert!(parse_pause_duration("h").is_err());
+    }
+
+    #[test]
+    fn test_parse_since() {
+        let now: chrono::DateTime<chrono::Utc> = "2024-05-10T12:00:00Z".parse().unwrap();
+        assert_eq!(
+            parse_since("2h", now).unwrap(),
+            "2024-05-10T10:00:00Z"
+                .parse::<chrono::DateTime<chrono::Utc>>()
+                .unwrap()
+        );
+        assert_eq!(
+            parse_since("2024-05-01T09:30:00+02:00", now).unwrap(),
+            "2024-05-01T07:30:00Z"
+                .parse::<chrono::DateTime<chrono::Utc>>()
+                .unwrap()
+        );
+        assert!(parse_since("2024-05-01", now).is_ok());
+        assert!(parse_since("2024-05-01 09:30", now).is_ok());
+        assert!(parse_since("yesterday", now).is_err());
+    }
+}
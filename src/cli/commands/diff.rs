@@ -1,10 +1,10 @@
 use crate::cli::Output;
 use crate::config::Config;
-use crate::sync::{GitBackend, MachineState, SyncEngine, SyncState};
+use crate::sync::{metadata, GitBackend, MachineDiff, MachineState, SyncEngine, SyncState};
 use anyhow::Result;
 use comfy_table::{Attribute, Cell, Color};
 use owo_colors::OwoColorize;
-use std::collections::{HashMap, HashSet};
+use std::collections::HashMap;
 
 pub async fn run(machine: Option<&str>) -> Result<()> {
     let config = match Config::load() {
@@ -44,7 +44,8 @@ pub async fn run(machine: Option<&str>) -> Result<()> {
             Some(other_machine) => {
                 // Build current machine state for comparison
                 let current_state = build_current_machine_state(&config, &state, &home)?;
-                show_machine_diff(&current_state, &other_machine)?;
+                // Added means only on this machine
+                print_machine_diff(&MachineDiff::between(&other_machine, &current_state));
             }
             None => {
                 Output::error(&format!("Machine '{}' not found", target_machine));
@@ -79,7 +80,6 @@ fn show_dotfile_diff(
     let mut diffs: Vec<(String, String, String)> = Vec::new(); // (file, status, details)
 
     let machine_id = &state.machine_id;
-    let profile = config.profile_name(machine_id);
 
     for entry in config.effective_dotfiles(machine_id) {
         let file = entry.path();
@@ -90,7 +90,7 @@ fn show_dotfile_diff(
             sync_path,
             file,
             config.security.encrypt_dotfiles,
-            profile,
+            config.dotfile_profile(machine_id, file),
             shared,
         );
         let remote_path = sync_path.join(&repo_rel);
@@ -193,8 +193,7 @@ async fn show_package_diff(config: &Config, sync_path: &std::path::Path) -> Resu
         let brew = BrewManager::new();
         if brew.is_available().await {
             let brewfile_path = manifests_dir.join("Brewfile");
-            if brewfile_path.exists() {
-                let remote_manifest = std::fs::read_to_string(&brewfile_path)?;
+            if let Some(remote_manifest) = metadata::read_to_string(&brewfile_path)? {
                 let local_manifest = brew.export_manifest().await?;
 
                 let remote_packages = parse_brewfile(&remote_manifest);
@@ -256,14 +255,12 @@ async fn show_package_diff(config: &Config, sync_path: &std::path::Path) -> Resu
         if !enabled || !manager.is_available().await {
             continue;
         }
-        let manifest_path = manifests_dir.join(filename);
-        if !manifest_path.exists() {
+        let Some(remote_manifest) = metadata::read_to_string(&manifests_dir.join(filename))? else {
             continue;
-        }
-        let remote_manifest = std::fs::read_to_string(&manifest_path)?;
+        };
         let local_manifest = manager.export_manifest().await?;
 
-        let remote_packages: Vec<_> = remote_manifest.lines().filter(|l| !l.is_empty()).collect();
+        let remote_packages = crate::packages::manifest_packages(&remote_manifest);
         let local_packages: Vec<_> = local_manifest.lines().filter(|l| !l.is_empty()).collect();
 
         let diff = diff_package_lists(&remote_packages, &local_packages);
@@ -403,33 +400,37 @@ fn build_current_machine_state(
     Ok(machine)
 }
 
-fn show_machine_diff(current: &MachineState, other: &MachineState) -> Result<()> {
+/// Print a machine comparison as tables
+pub(super) fn print_machine_diff(diff: &MachineDiff) {
     println!(
         "Comparing {} ({}) vs {} ({})",
-        current.machine_id.cyan(),
-        current.hostname.dimmed(),
-        other.machine_id.cyan(),
-        other.hostname.dimmed()
+        diff.from.machine_id.cyan(),
+        diff.from.hostname.dimmed(),
+        diff.to.machine_id.cyan(),
+        diff.to.hostname.dimmed()
     );
     println!();
 
-    // File differences
-    let current_files: HashSet<_> = current.files.keys().collect();
-    let other_files: HashSet<_> = other.files.keys().collect();
-
-    let mut file_diffs = Vec::new();
-
-    for file in current_files.difference(&other_files) {
-        file_diffs.push(((*file).clone(), "only on this machine".to_string()));
-    }
-    for file in other_files.difference(&current_files) {
-        file_diffs.push(((*file).clone(), "only on other machine".to_string()));
-    }
-    for file in current_files.intersection(&other_files) {
-        if current.files.get(*file) != other.files.get(*file) {
-            file_diffs.push(((*file).clone(), "content differs".to_string()));
-        }
-    }
+    let only_to = format!("only on {}", diff.to.machine_id);
+    let only_from = format!("only on {}", diff.from.machine_id);
+    let file_diffs: Vec<(&String, &str, Color)> = diff
+        .files
+        .added
+        .iter()
+        .map(|file| (file, only_to.as_str(), Color::Green))
+        .chain(
+            diff.files
+                .removed
+                .iter()
+                .map(|file| (file, only_from.as_str(), Color::Red)),
+        )
+        .chain(
+            diff.files
+                .changed
+                .iter()
+                .map(|file| (file, "content differs", Color::Yellow)),
+        )
+        .collect();
 
     if file_diffs.is_empty() {
         println!(
@@ -448,57 +449,25 @@ fn show_machine_diff(current: &MachineState, other: &MachineState) -> Result<()>
                 .fg(Color::Cyan),
         ]);
 
-        for (file, diff) in &file_diffs {
-            let color = match diff.as_str() {
-                "only on this machine" => Color::Green,
-                "only on other machine" => Color::Red,
-                _ => Color::Yellow,
-            };
-            table.add_row(vec![Cell::new(file), Cell::new(diff).fg(color)]);
+        for (file, diff, color) in &file_diffs {
+            table.add_row(vec![Cell::new(file), Cell::new(diff).fg(*color)]);
         }
         println!("{table}");
     }
     println!();
 
-    // Package differences
-    let current_pkgs: HashSet<_> = current.packages.keys().collect();
-    let other_pkgs: HashSet<_> = other.packages.keys().collect();
-    let all_managers: HashSet<_> = current_pkgs.union(&other_pkgs).collect();
-
-    let mut has_pkg_diff = false;
-
-    for manager in all_managers {
-        let current_list: HashSet<_> = current
-            .packages
-            .get(*manager)
-            .map(|v| v.iter().collect())
-            .unwrap_or_default();
-        let other_list: HashSet<_> = other
-            .packages
-            .get(*manager)
-            .map(|v| v.iter().collect())
-            .unwrap_or_default();
-
-        let mut diffs = Vec::new();
-        for pkg in current_list.difference(&other_list) {
-            diffs.push(((*pkg).clone(), "added".to_string()));
-        }
-        for pkg in other_list.difference(&current_list) {
-            diffs.push(((*pkg).clone(), "removed".to_string()));
+    for (manager, changes) in &diff.packages {
+        println!("{}", format!("{}:", manager).bright_cyan().bold());
+        for pkg in &changes.added {
+            Output::diff_line("+", pkg, "added");
         }
-
-        if !diffs.is_empty() {
-            has_pkg_diff = true;
-            println!("{}", format!("{}:", manager).bright_cyan().bold());
-            for (pkg, status) in diffs {
-                let symbol = if status == "added" { "+" } else { "-" };
-                Output::diff_line(symbol, &pkg, &status);
-            }
-            println!();
+        for pkg in &changes.removed {
+            Output::diff_line("-", pkg, "removed");
         }
+        println!();
     }
 
-    if !has_pkg_diff {
+    if diff.packages.is_empty() {
         println!(
             "{} {}",
             Output::CHECK.green(),
@@ -506,6 +475,4 @@ fn show_machine_diff(current: &MachineState, other: &MachineState) -> Result<()>
         );
         println!();
     }
-
-    Ok(())
 }
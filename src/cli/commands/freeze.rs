@@ -0,0 +1,549 @@
+use anyhow::Result;
+use std::collections::{BTreeMap, HashMap, HashSet};
+use std::path::Path;
+
+use crate::cli::output::{relative_time, Output};
+use crate::cli::progress::Progress;
+use crate::cli::prompts::Prompt;
+use crate::config::Config;
+use crate::packages::{
+    BrewManager, BunManager, GemManager, NpmManager, PackageInfo, PackageManager, PnpmManager,
+    UvManager,
+};
+use crate::sync::freeze::EnvLock;
+use crate::sync::{GitBackend, SyncEngine, SyncState};
+
+/// Managers whose exact versions are frozen, by machine state key. Homebrew casks and
+/// taps are handled separately since they aren't separate managers.
+fn version_managers() -> Vec<(&'static str, Box<dyn PackageManager>)> {
+    vec![
+        ("brew_formulae", Box::new(BrewManager::new())),
+        ("npm", Box::new(NpmManager::new())),
+        ("pnpm", Box::new(PnpmManager::new())),
+        ("bun", Box::new(BunManager::new())),
+        ("gem", Box::new(GemManager::new())),
+        ("uv", Box::new(UvManager::new())),
+    ]
+}
+
+pub async fn run(name: &str, yes: bool) -> Result<()> {
+    if !EnvLock::is_valid_name(name) {
+        anyhow::bail!(
+            "Invalid lock name '{}': use letters, digits, '-', '_' or '.'",
+            name
+        );
+    }
+
+    let sync_path = SyncEngine::sync_path()?;
+    if EnvLock::exists(&sync_path, name)
+        && !yes
+        && !Prompt::confirm(&format!("Lock '{}' exists. Overwrite it?", name), false)?
+    {
+        Output::info("Cancelled");
+        return Ok(());
+    }
+
+    // The lock points at a sync repo commit, so local dotfile changes must be in it
+    Output::info("Syncing before freezing...");
+    super::sync::run(false, false, false, true, crate::sync::SyncMode::Full).await?;
+
+    let config = Config::load()?;
+    let state = SyncState::load()?;
+    let home = crate::home_dir()?;
+    let git = GitBackend::open(&sync_path)?;
+    let machine_id = &state.machine_id;
+
+    let mut dotfiles = BTreeMap::new();
+    if config.features.personal_dotfiles {
+        let encrypted = config.security.encrypt_dotfiles;
+        for entry in config.effective_dotfiles(machine_id) {
+            if !entry.is_safe_path() {
+                continue;
+            }
+            let pattern = entry.path();
+            let profile = config.dotfile_profile(machine_id, pattern);
+            let shared = config.is_dotfile_shared(machine_id, pattern);
+            for file in crate::sync::expand_dotfile_glob(pattern, &home) {
+                let repo_path = crate::sync::resolve_dotfile_repo_path(
+                    &sync_path, &file, encrypted, profile, shared,
+                );
+                if sync_path.join(&repo_path).exists() {
+                    dotfiles.insert(file, repo_path);
+                }
+            }
+        }
+    }
+
+    let pb = Progress::spinner("Collecting package versions...");
+    let mut packages: BTreeMap<String, Vec<PackageInfo>> = BTreeMap::new();
+    for (key, manager) in version_managers() {
+        if !config.is_manager_enabled(machine_id, key) || !manager.is_available().await {
+            continue;
+        }
+        // Kept even when empty, so `tether verify` reports anything installed later
+        match manager.list_versions().await {
+            Ok(list) => {
+                packages.insert(key.to_string(), list);
+            }
+            Err(e) => Output::warning(&format!("Skipping {}: {}", key, e)),
+        }
+    }
+    let brew = BrewManager::new();
+    if config.is_manager_enabled(machine_id, "brew") && brew.is_available().await {
+        if let Ok(casks) = brew.list_cask_versions().await {
+            packages.insert("brew_casks".to_string(), casks);
+        }
+        if let Ok(taps) = brew.list_taps().await {
+            let taps = taps
+                .into_iter()
+                .map(|name| PackageInfo {
+                    name,
+                    version: None,
+                })
+                .collect();
+            packages.insert("brew_taps".to_string(), taps);
+        }
+    }
+    pb.finish_and_clear();
+
+    let lock = EnvLock {
+        name: name.to_string(),
+        created_at: chrono::Utc::now(),
+        machine_id: machine_id.clone(),
+        cli_version: env!("CARGO_PKG_VERSION").to_string(),
+        dotfiles_commit: git.head_commit()?,
+        dotfiles,
+        packages,
+    };
+    lock.save(&sync_path)?;
+
+    let pb = Progress::spinner("Pushing lock...");
+    git.commit(&format!("Freeze {}", name), machine_id)?;
+    git.push()?;
+    pb.finish_and_clear();
+
+    Output::success(&format!(
+        "Froze '{}': {} packages, {} dotfiles at {}",
+        name,
+        lock.package_count(),
+        lock.dotfiles.len(),
+        &lock.dotfiles_commit[..7]
+    ));
+    Output::dim(&format!(
+        "  Reproduce it elsewhere with `tether thaw {}`",
+        name
+    ));
+    Ok(())
+}
+
+pub async fn thaw(name: Option<&str>, dry_run: bool, yes: bool) -> Result<()> {
+    let sync_path = SyncEngine::sync_path()?;
+    let git = GitBackend::open(&sync_path)?;
+    if let Err(e) = git.pull() {
+        Output::warning(&format!("Could not pull latest locks: {}", e));
+    }
+
+    let Some(name) = name else {
+        return list_locks(&sync_path);
+    };
+    let Some(lock) = EnvLock::load(&sync_path, name)? else {
+        Output::error(&format!("Lock '{}' not found", name));
+        return list_locks(&sync_path);
+    };
+
+    let home = crate::home_dir()?;
+    let dotfile_changes = dotfile_drift(&lock, &git, &home)?;
+
+    let pb = Progress::spinner("Comparing packages...");
+    let (drift, unavailable) = package_drift(&lock).await;
+    pb.finish_and_clear();
+    for key in &unavailable {
+        Output::warning(&format!("{} isn't available; skipping its packages", key));
+    }
+    // Thaw only installs; extra packages are left alone
+    let plan: Vec<PackageDrift> = drift
+        .into_iter()
+        .filter(|d| d.kind != DriftKind::Extra && !unavailable.contains(&d.manager_key))
+        .collect();
+
+    println!();
+    Output::section(&format!(
+        "Thaw '{}' (frozen on {} {})",
+        lock.name,
+        lock.machine_id,
+        relative_time(lock.created_at)
+    ));
+    for change in &dotfile_changes {
+        Output::list_item(&format!("~/{}", change.file));
+    }
+    for step in &plan {
+        Output::list_item(&step.describe());
+    }
+    if dotfile_changes.is_empty() && plan.is_empty() {
+        Output::success("This machine already matches the lock");
+        return Ok(());
+    }
+    println!();
+
+    if dry_run {
+        return Ok(());
+    }
+    if !yes && !Prompt::confirm("Apply these changes?", true)? {
+        Output::info("Cancelled");
+        return Ok(());
+    }
+
+    if !dotfile_changes.is_empty() {
+        let backup_dir = crate::sync::create_backup_dir()?;
+        for change in &dotfile_changes {
+            let dest = home.join(&change.file);
+            if dest.exists() {
+                crate::sync::backup_file(&backup_dir, "dotfiles", &change.file, &dest)?;
+            }
+            if let Some(parent) = dest.parent() {
+                std::fs::create_dir_all(parent)?;
+            }
+            std::fs::write(&dest, &change.content)?;
+        }
+        // State hashes are left alone so the next sync pushes the frozen content
+        Output::success(&format!("Restored {} dotfiles", dotfile_changes.len()));
+    }
+
+    let mut failed = 0;
+    let brew = BrewManager::new();
+    let managers: HashMap<&str, Box<dyn PackageManager>> = version_managers().into_iter().collect();
+    for step in &plan {
+        let pkg = &step.package;
+        let pb = Progress::spinner(&format!("Installing {}...", pkg.name));
+        let result = match step.manager_key {
+            "brew_taps" => brew.tap(&pkg.name).await,
+            "brew_casks" => brew.install_cask(&pkg.name, true).await.and_then(|ok| {
+                ok.then_some(())
+                    .ok_or_else(|| anyhow::anyhow!("needs a password, install it manually"))
+            }),
+            key => match managers.get(key) {
+                Some(manager) => manager.install(pkg).await,
+                None => Err(anyhow::anyhow!("unknown package manager {}", key)),
+            },
+        };
+        pb.finish_and_clear();
+        if let Err(e) = result {
+            Output::warning(&format!("Failed to install {}: {}", pkg.name, e));
+            failed += 1;
+        }
+    }
+
+    // Homebrew can't install a given version, so frozen versions may still differ
+    let unpinned: Vec<String> = package_drift(&lock)
+        .await
+        .0
+        .into_iter()
+        .filter(|d| d.manager_key.starts_with("brew_") && matches!(d.kind, DriftKind::Version(_)))
+        .map(|d| d.package.name)
+        .collect();
+    if !unpinned.is_empty() {
+        Output::warning(&format!(
+            "Homebrew versions differ from the lock: {}",
+            unpinned.join(", ")
+        ));
+    }
+
+    if failed == 0 {
+        Output::success(&format!("Thawed '{}'", lock.name));
+    } else {
+        Output::warning(&format!("Thawed '{}' with {} failures", lock.name, failed));
+    }
+    Ok(())
+}
+
+/// Report every way this machine deviates from a lock. Returns whether it matches.
+pub async fn verify(name: &str) -> Result<bool> {
+    let sync_path = SyncEngine::sync_path()?;
+    let git = GitBackend::open(&sync_path)?;
+    if let Err(e) = git.pull() {
+        Output::warning(&format!("Could not pull latest locks: {}", e));
+    }
+    let Some(lock) = EnvLock::load(&sync_path, name)? else {
+        anyhow::bail!("Lock '{}' not found", name);
+    };
+
+    let home = crate::home_dir()?;
+    let dotfiles = dotfile_drift(&lock, &git, &home)?;
+
+    let pb = Progress::spinner("Comparing packages...");
+    let (packages, _) = package_drift(&lock).await;
+    pb.finish_and_clear();
+
+    println!();
+    Output::section(&format!(
+        "Verify against '{}' (frozen on {} {})",
+        lock.name,
+        lock.machine_id,
+        relative_time(lock.created_at)
+    ));
+    for change in &dotfiles {
+        let status = if change.missing {
+            "missing"
+        } else {
+            "modified"
+        };
+        Output::list_item(&format!("~/{} {}", change.file, status));
+    }
+    for drift in &packages {
+        Output::list_item(&drift.describe());
+    }
+
+    let deviations = dotfiles.len() + packages.len();
+    if deviations == 0 {
+        Output::success(&format!("Matches '{}'", lock.name));
+    } else {
+        println!();
+        Output::error(&format!(
+            "{} deviation{} from '{}'",
+            deviations,
+            if deviations == 1 { "" } else { "s" },
+            lock.name
+        ));
+    }
+    Ok(deviations == 0)
+}
+
+/// A frozen dotfile whose content differs from the local copy
+struct DotfileDrift {
+    file: String,
+    /// Content at the frozen commit
+    content: Vec<u8>,
+    missing: bool,
+}
+
+fn dotfile_drift(lock: &EnvLock, git: &GitBackend, home: &Path) -> Result<Vec<DotfileDrift>> {
+    let config = Config::load()?;
+    let key = if config.security.encrypt_dotfiles && !lock.dotfiles.is_empty() {
+        Some(crate::security::get_encryption_key()?)
+    } else {
+        None
+    };
+
+    let mut drift = Vec::new();
+    for (file, repo_path) in &lock.dotfiles {
+        if !crate::config::is_safe_dotfile_path(file) {
+            Output::warning(&format!("Skipping unsafe dotfile path: {}", file));
+            continue;
+        }
+        let content = match git.show_at_commit(&lock.dotfiles_commit, repo_path) {
+            Ok(content) => content,
+            Err(e) => {
+                Output::warning(&format!("Skipping {}: {}", file, e));
+                continue;
+            }
+        };
+        let content = match &key {
+            Some(key) if repo_path.ends_with(".enc") => crate::security::decrypt(&content, key)?,
+            _ => content,
+        };
+        let local = std::fs::read(home.join(file)).ok();
+        if local.as_deref() != Some(content.as_slice()) {
+            drift.push(DotfileDrift {
+                file: file.clone(),
+                content,
+                missing: local.is_none(),
+            });
+        }
+    }
+    Ok(drift)
+}
+
+#[derive(Debug, PartialEq)]
+enum DriftKind {
+    /// In the lock, not installed
+    Missing,
+    /// Installed at another version (the installed one)
+    Version(Option<String>),
+    /// Installed, not in the lock
+    Extra,
+}
+
+struct PackageDrift {
+    manager_key: &'static str,
+    package: PackageInfo,
+    kind: DriftKind,
+}
+
+impl PackageDrift {
+    fn describe(&self) -> String {
+        let target = match &self.package.version {
+            Some(v) => format!("{} {}", self.package.name, v),
+            None => self.package.name.clone(),
+        };
+        match &self.kind {
+            DriftKind::Missing => format!("{}: {} missing", self.manager_key, target),
+            DriftKind::Version(have) => format!(
+                "{}: {}, have {}",
+                self.manager_key,
+                target,
+                have.as_deref().unwrap_or("unknown")
+            ),
+            DriftKind::Extra => format!("{}: {} not in lock", self.manager_key, target),
+        }
+    }
+}
+
+/// How installed packages differ from the lock, plus the managers in the lock that
+/// aren't available here (all their packages count as missing)
+async fn package_drift(lock: &EnvLock) -> (Vec<PackageDrift>, Vec<&'static str>) {
+    let managers: HashMap<&str, Box<dyn PackageManager>> = version_managers().into_iter().collect();
+    let mut drift = Vec::new();
+    let mut unavailable = Vec::new();
+    // Taps before the formulae and casks that come from them
+    for key in THAW_ORDER {
+        let Some(wanted) = lock.packages.get(key) else {
+            continue;
+        };
+        let installed = match installed_versions(key, &managers).await {
+            Some(installed) => installed,
+            None => {
+                unavailable.push(key);
+                Vec::new()
+            }
+        };
+        drift.extend(
+            diff_packages(wanted, &installed)
+                .into_iter()
+                .map(|(package, kind)| PackageDrift {
+                    manager_key: key,
+                    package,
+                    kind,
+                }),
+        );
+    }
+    (drift, unavailable)
+}
+
+const THAW_ORDER: [&str; 8] = [
+    "brew_taps",
+    "brew_formulae",
+    "brew_casks",
+    "npm",
+    "pnpm",
+    "bun",
+    "gem",
+    "uv",
+];
+
+/// What's installed for a machine state key, `None` when its manager isn't available
+async fn installed_versions(
+    key: &str,
+    managers: &HashMap<&str, Box<dyn PackageManager>>,
+) -> Option<Vec<PackageInfo>> {
+    let brew = BrewManager::new();
+    match key {
+        "brew_taps" | "brew_casks" if !brew.is_available().await => None,
+        "brew_taps" => Some(
+            brew.list_taps()
+                .await
+                .unwrap_or_default()
+                .into_iter()
+                .map(|name| PackageInfo {
+                    name,
+                    version: None,
+                })
+                .collect(),
+        ),
+        "brew_casks" => Some(brew.list_cask_versions().await.unwrap_or_default()),
+        key => {
+            let manager = managers.get(key)?;
+            if !manager.is_available().await {
+                return None;
+            }
+            Some(manager.list_versions().await.unwrap_or_default())
+        }
+    }
+}
+
+/// Wanted packages that are missing or at another version, then installed packages
+/// the lock doesn't have. A lock entry without a version matches any version.
+fn diff_packages(
+    wanted: &[PackageInfo],
+    installed: &[PackageInfo],
+) -> Vec<(PackageInfo, DriftKind)> {
+    let installed_versions: HashMap<&str, &Option<String>> = installed
+        .iter()
+        .map(|p| (p.name.as_str(), &p.version))
+        .collect();
+    let mut drift: Vec<(PackageInfo, DriftKind)> = wanted
+        .iter()
+        .filter_map(|pkg| match installed_versions.get(pkg.name.as_str()) {
+            None => Some((pkg.clone(), DriftKind::Missing)),
+            Some(&have) if pkg.version.is_some() && *have != pkg.version => {
+                Some((pkg.clone(), DriftKind::Version(have.clone())))
+            }
+            Some(_) => None,
+        })
+        .collect();
+
+    let wanted_names: HashSet<&str> = wanted.iter().map(|p| p.name.as_str()).collect();
+    drift.extend(
+        installed
+            .iter()
+            .filter(|p| !wanted_names.contains(p.name.as_str()))
+            .map(|p| (p.clone(), DriftKind::Extra)),
+    );
+    drift
+}
+
+fn list_locks(sync_path: &std::path::Path) -> Result<()> {
+    let locks = EnvLock::list(sync_path)?;
+    if locks.is_empty() {
+        Output::info("No locks yet. Create one with `tether freeze <name>`");
+        return Ok(());
+    }
+    Output::section("Locks");
+    for lock in &locks {
+        Output::list_item(&format!(
+            "{}  {} packages, {} dotfiles  ({}, {})",
+            lock.name,
+            lock.package_count(),
+            lock.dotfiles.len(),
+            lock.machine_id,
+            relative_time(lock.created_at)
+        ));
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn pkg(name: &str, version: Option<&str>) -> PackageInfo {
+        PackageInfo {
+            name: name.to_string(),
+            version: version.map(str::to_string),
+        }
+    }
+
+    #[test]
+    fn test_diff_packages() {
+        let wanted = vec![
+            pkg("typescript", Some("5.4.2")),
+            pkg("eslint", Some("9.0.0")),
+            pkg("prettier", Some("3.2.5")),
+            pkg("homebrew/cask-fonts", None),
+        ];
+        let installed = vec![
+            pkg("typescript", Some("5.4.2")),
+            pkg("eslint", Some("8.57.0")),
+            pkg("homebrew/cask-fonts", None),
+            pkg("cowsay", Some("1.6.0")),
+        ];
+
+        let diff = diff_packages(&wanted, &installed);
+        assert_eq!(diff.len(), 3);
+        assert_eq!(diff[0].0.name, "eslint");
+        assert_eq!(diff[0].1, DriftKind::Version(Some("8.57.0".to_string())));
+        assert_eq!(diff[1].0.name, "prettier");
+        assert_eq!(diff[1].1, DriftKind::Missing);
+        assert_eq!(diff[2].0.name, "cowsay");
+        assert_eq!(diff[2].1, DriftKind::Extra);
+    }
+}
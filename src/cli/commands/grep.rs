@@ -0,0 +1,172 @@
+use super::browse::{classify, Kind, RepoEntry};
+use crate::sync::{GitBackend, SyncEngine};
+use anyhow::Result;
+use owo_colors::OwoColorize;
+use regex::Regex;
+use std::collections::HashSet;
+
+/// How many past versions of each file `--history` searches
+const HISTORY_DEPTH: usize = 500;
+
+/// Lines of `content` matching `re`, as (line number, line with matches highlighted)
+fn matching_lines(re: &Regex, content: &str) -> Vec<(usize, String)> {
+    content
+        .lines()
+        .enumerate()
+        .filter(|(_, line)| re.is_match(line))
+        .map(|(i, line)| {
+            let highlighted = re.replace_all(line, |caps: &regex::Captures| {
+                (&caps[0]).red().bold().to_string()
+            });
+            (i + 1, highlighted.into_owned())
+        })
+        .collect()
+}
+
+/// `~/.zshrc`, or `~/.zshrc@abc1234 (dev)` for a past version of a profile dotfile
+fn label(entry: &RepoEntry, commit: Option<&str>) -> String {
+    let path = match &entry.home_path {
+        Some(home) => format!("~/{}", home),
+        None => entry.repo_path.clone(),
+    };
+    let at = commit.map(|c| format!("@{}", c)).unwrap_or_default();
+    match &entry.profile {
+        Some(profile) => format!("{}{} ({})", path, at, profile),
+        None => format!("{}{}", path, at),
+    }
+}
+
+/// Decrypt a stored file; `load` fetches chunks as of the same version
+fn decode(
+    key: Option<&[u8]>,
+    repo_path: &str,
+    content: Vec<u8>,
+    load: impl FnMut(&str) -> Result<Vec<u8>>,
+) -> Result<Vec<u8>> {
+    match key {
+        Some(key) if repo_path.ends_with(".enc") => {
+            let plaintext = crate::security::decrypt(&content, key)?;
+            crate::sync::chunks::reassemble(repo_path, plaintext, key, load)
+        }
+        _ => Ok(content),
+    }
+}
+
+fn print_matches(label: &str, re: &Regex, content: &[u8]) -> bool {
+    if crate::sync::is_binary(content) {
+        return false;
+    }
+    let lines = matching_lines(re, &String::from_utf8_lossy(content));
+    for (number, line) in &lines {
+        println!(
+            "{}:{}:{}",
+            label.magenta(),
+            number.to_string().green(),
+            line
+        );
+    }
+    !lines.is_empty()
+}
+
+/// Search decrypted dotfiles, synced directory files and project configs for
+/// `pattern` (a regex). With `history`, every past version is searched too, including
+/// files no longer in the repo. Secrets are never searched. Returns whether anything
+/// matched.
+pub async fn run(pattern: &str, ignore_case: bool, history: bool) -> Result<bool> {
+    let re = regex::RegexBuilder::new(pattern)
+        .case_insensitive(ignore_case)
+        .build()
+        .map_err(|e| anyhow::anyhow!("Invalid pattern: {}", e))?;
+
+    let sync_path = SyncEngine::sync_path()?;
+    if !sync_path.exists() {
+        anyhow::bail!("Sync repository not found. Run 'tether init' first.");
+    }
+    let git = GitBackend::open(&sync_path)?;
+    let paths = if history {
+        git.paths_in_history()?
+    } else {
+        git.list_tracked_files(".")?
+    };
+    let entries: Vec<RepoEntry> = paths
+        .iter()
+        .filter_map(|p| classify(p))
+        .filter(|e| matches!(e.kind, Kind::Dotfile | Kind::DirFile | Kind::Project))
+        .collect();
+
+    let key = if entries.iter().any(|e| e.repo_path.ends_with(".enc")) {
+        Some(crate::security::get_encryption_key()?)
+    } else {
+        None
+    };
+    let mut found = false;
+    for entry in &entries {
+        if !history {
+            let label = label(entry, None);
+            let content = std::fs::read(sync_path.join(&entry.repo_path))?;
+            let load = |rel: &str| Ok(std::fs::read(sync_path.join(rel))?);
+            match decode(key.as_deref(), &entry.repo_path, content, load) {
+                Ok(content) => found |= print_matches(&label, &re, &content),
+                Err(e) => eprintln!("Warning: Skipping {}: {}", label, e),
+            }
+            continue;
+        }
+
+        found |= search_history(&git, key.as_deref(), entry, &re)?;
+    }
+    Ok(found)
+}
+
+/// Search each distinct past version of a file, newest first, labelled `file@commit`
+fn search_history(
+    git: &GitBackend,
+    key: Option<&[u8]>,
+    entry: &RepoEntry,
+    re: &Regex,
+) -> Result<bool> {
+    let mut found = false;
+    let mut seen = HashSet::new();
+    for commit in git.file_log(&entry.repo_path, HISTORY_DEPTH)? {
+        // Fails for the commit that deleted the file
+        let Ok(content) = git.show_at_commit(&commit.commit_hash, &entry.repo_path) else {
+            continue;
+        };
+        let load = |rel: &str| git.show_at_commit(&commit.commit_hash, rel);
+        let Ok(content) = decode(key, &entry.repo_path, content, load) else {
+            continue;
+        };
+        if !seen.insert(crate::sha256_hex(&content)) {
+            continue;
+        }
+        let label = label(entry, Some(&commit.short_hash));
+        found |= print_matches(&label, re, &content);
+    }
+    Ok(found)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_matching_lines() {
+        let re = regex::RegexBuilder::new("alias g")
+            .case_insensitive(true)
+            .build()
+            .unwrap();
+        let lines = matching_lines(&re, "export A=1\nalias gs='git status'\nALIAS GP=push\n");
+        assert_eq!(lines.len(), 2);
+        assert_eq!(lines[0].0, 2);
+        assert!(lines[0].1.ends_with("s='git status'"));
+        assert_eq!(lines[1].0, 3);
+    }
+
+    #[test]
+    fn test_label() {
+        let dotfile = classify("profiles/dev/zshrc.enc").unwrap();
+        assert_eq!(label(&dotfile, None), "~/.zshrc (dev)");
+        assert_eq!(label(&dotfile, Some("abc1234")), "~/.zshrc@abc1234 (dev)");
+        let project = classify("projects/github.com/u/app/.env.enc").unwrap();
+        assert_eq!(label(&project, None), "projects/github.com/u/app/.env.enc");
+    }
+}
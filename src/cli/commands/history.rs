@@ -1,7 +1,7 @@
 use crate::cli::output::relative_time;
 use crate::cli::Output;
 use crate::config::Config;
-use crate::sync::{GitBackend, SyncEngine, SyncState};
+use crate::sync::{FileLogEntry, GitBackend, SyncEngine, SyncState};
 use anyhow::Result;
 
 pub async fn run(file: &str, limit: usize) -> Result<()> {
@@ -15,22 +15,30 @@ pub async fn run(file: &str, limit: usize) -> Result<()> {
     let state = SyncState::load()?;
 
     let encrypted = config.security.encrypt_dotfiles;
-    let profile = config.profile_name(&state.machine_id);
+    let profile = config.dotfile_profile(&state.machine_id, file);
     let shared = config.is_dotfile_shared(&state.machine_id, file);
     let repo_path =
         crate::sync::resolve_dotfile_repo_path(&sync_path, file, encrypted, profile, shared);
-    let entries = git.file_log(&repo_path, limit)?;
+    print_log(file, &git.file_log(&repo_path, limit)?);
+    Ok(())
+}
 
+/// Print the commits that touched `label`, newest first
+pub fn print_log(label: &str, entries: &[FileLogEntry]) {
     if entries.is_empty() {
-        Output::info(&format!("No history found for {}", file));
-        return Ok(());
+        Output::info(&format!("No history found for {}", label));
+        return;
     }
 
     println!();
-    Output::section(&format!("History for {} ({} entries)", file, entries.len()));
+    Output::section(&format!(
+        "History for {} ({} entries)",
+        label,
+        entries.len()
+    ));
     println!();
 
-    for entry in &entries {
+    for entry in entries {
         let time = relative_time(entry.date);
         println!(
             "  {}  {:>12}   {:15}  {}",
@@ -39,5 +47,4 @@ pub async fn run(file: &str, limit: usize) -> Result<()> {
     }
 
     println!();
-    Ok(())
 }
@@ -3,10 +3,11 @@ use owo_colors::OwoColorize;
 
 use crate::cli::output::Output;
 use crate::cli::prompts::Prompt;
-use crate::security::recipients;
+use crate::security::{hardware, recipients};
 
-/// Initialize a new age identity
-pub async fn init() -> Result<()> {
+/// Initialize a new age identity. With `yubikey`, or when chosen at the prompt, the
+/// identity lives on a YubiKey through age-plugin-yubikey instead of behind a passphrase.
+pub async fn init(yubikey: bool) -> Result<()> {
     if recipients::has_identity() {
         Output::warning("Identity already exists");
         println!("Run 'tether identity show' to see your public key");
@@ -14,17 +15,35 @@ pub async fn init() -> Result<()> {
         return Ok(());
     }
 
-    Output::info("Generating age identity...");
-
-    let passphrase = Prompt::password_with_confirm(
-        "Enter passphrase to protect your identity:",
-        "Confirm passphrase:",
-    )?;
-
-    let identity = recipients::generate_identity();
-    recipients::store_identity(&identity, &passphrase)?;
+    let hardware = if yubikey {
+        if !hardware::plugin_available() {
+            anyhow::bail!("age-plugin-yubikey is not installed (brew install age-plugin-yubikey)");
+        }
+        true
+    } else if hardware::plugin_available() {
+        let options = vec![
+            "Passphrase-protected key",
+            "YubiKey (age-plugin-yubikey; unlock with touch)",
+        ];
+        Prompt::select("Where should the identity live?", options, 0)? == 1
+    } else {
+        false
+    };
+
+    let pubkey = if hardware {
+        init_hardware()?
+    } else {
+        Output::info("Generating age identity...");
+
+        let passphrase = Prompt::password_with_confirm(
+            "Enter passphrase to protect your identity:",
+            "Confirm passphrase:",
+        )?;
 
-    let pubkey = recipients::get_public_key_from_identity(&identity);
+        let identity = recipients::generate_identity();
+        recipients::store_identity(&identity, &passphrase)?;
+        recipients::get_public_key_from_identity(&identity)
+    };
 
     Output::success("Identity created");
     println!();
@@ -36,6 +55,46 @@ pub async fn init() -> Result<()> {
     Ok(())
 }
 
+/// Set up a YubiKey identity (reusing one already on the key, e.g. from another
+/// machine) and wrap the personal encryption key for it, so `tether unlock` takes a
+/// touch instead of the passphrase
+fn init_hardware() -> Result<String> {
+    let existing = hardware::existing().unwrap_or_default();
+    let (identity, recipient) = match existing.len() {
+        0 => {
+            Output::info("Generating an identity on your YubiKey (touch it when it blinks)...");
+            hardware::generate()?
+        }
+        _ => {
+            let mut options: Vec<String> = existing
+                .iter()
+                .map(|(_, recipient)| format!("Use {}", recipient))
+                .collect();
+            options.push("Generate a new identity".to_string());
+            let choice = Prompt::select(
+                "Your YubiKey already has an age identity",
+                options.iter().map(String::as_str).collect(),
+                0,
+            )?;
+            match existing.into_iter().nth(choice) {
+                Some(pair) => pair,
+                None => hardware::generate()?,
+            }
+        }
+    };
+    hardware::store_identity(&identity, &recipient)?;
+
+    // Only possible while the key is unlocked; otherwise `tether unlock` offers it later
+    if crate::security::has_encryption_key() && crate::security::is_unlocked() {
+        let key = crate::security::get_encryption_key()?;
+        let sync_path = crate::sync::SyncEngine::sync_path()?;
+        hardware::wrap_key(&sync_path, &key, &recipient)?;
+        Output::success("Encryption key can now be unlocked with this YubiKey");
+        Output::dim("  Run 'tether sync' to make it available on your other machines");
+    }
+    Ok(recipient)
+}
+
 /// Show public key
 pub async fn show() -> Result<()> {
     let pubkey = recipients::get_public_key()?;
@@ -50,6 +109,18 @@ pub async fn unlock() -> Result<()> {
         return Ok(());
     }
 
+    if hardware::has_identity() {
+        Output::info("Identity is on your YubiKey; it asks for PIN and touch when used");
+        return Ok(());
+    }
+
+    if let Some(left) = crate::security::agent::remaining() {
+        Output::info(&format!(
+            "Identity already unlocked for {} more minute(s)",
+            left.as_secs().div_ceil(60)
+        ));
+        return Ok(());
+    }
     if recipients::is_identity_unlocked() {
         Output::info("Identity already unlocked");
         return Ok(());
@@ -58,7 +129,13 @@ pub async fn unlock() -> Result<()> {
     let passphrase = Prompt::password("Enter passphrase:")?;
     recipients::load_identity(Some(&passphrase))?;
 
-    Output::success("Identity unlocked");
+    match crate::security::agent::session_length() {
+        Some(length) => Output::success(&format!(
+            "Identity unlocked for {} minute(s)",
+            length.as_secs() / 60
+        )),
+        None => Output::success("Identity unlocked"),
+    }
     Ok(())
 }
 
@@ -70,7 +147,7 @@ pub async fn lock() -> Result<()> {
 }
 
 /// Reset identity (generate new)
-pub async fn reset() -> Result<()> {
+pub async fn reset(yubikey: bool) -> Result<()> {
     if recipients::has_identity() {
         let confirm = Prompt::confirm(
             "This will delete your existing identity. You will lose access to any team secrets encrypted to your current key. Continue?",
@@ -82,11 +159,12 @@ pub async fn reset() -> Result<()> {
         }
 
         // Clear existing
-        let home = crate::home_dir()?;
-        let _ = std::fs::remove_file(home.join(".tether").join("identity.age"));
-        let _ = std::fs::remove_file(home.join(".tether").join("identity.pub"));
-        let _ = std::fs::remove_file(home.join(".tether").join("identity.cache"));
+        let dir = crate::config::Config::config_dir()?;
+        let _ = std::fs::remove_file(dir.join("identity.age"));
+        let _ = std::fs::remove_file(dir.join("identity.pub"));
+        let _ = recipients::clear_cached_identity();
+        let _ = std::fs::remove_file(dir.join("identity.yubikey"));
     }
 
-    init().await
+    init(yubikey).await
 }
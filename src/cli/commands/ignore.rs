@@ -1,14 +1,38 @@
 use crate::cli::Output;
-use crate::config::Config;
+use crate::security::secrets::{ignore_file_path, IGNORE_FINGERPRINT_PREFIX, IGNORE_PATH_PREFIX};
 use crate::sync::{MachineState, SyncEngine, SyncState};
 use anyhow::Result;
-use std::path::PathBuf;
 
-fn ignore_file_path() -> Result<PathBuf> {
-    Ok(Config::config_dir()?.join("ignore"))
+/// The ignore file line for a pattern, optionally scoped to one file, or for a
+/// single finding's fingerprint
+fn ignore_entry(
+    pattern: Option<&str>,
+    file: Option<&str>,
+    fingerprint: Option<&str>,
+) -> Result<String> {
+    if let Some(fingerprint) = fingerprint {
+        if fingerprint.is_empty() || !fingerprint.chars().all(|c| c.is_ascii_hexdigit()) {
+            anyhow::bail!("Invalid fingerprint: {}", fingerprint);
+        }
+        return Ok(format!("{}{}", IGNORE_FINGERPRINT_PREFIX, fingerprint));
+    }
+    match (file, pattern) {
+        (Some(file), _) if file.is_empty() || file.contains(char::is_whitespace) => {
+            anyhow::bail!("Invalid path: '{}'", file)
+        }
+        (Some(file), Some(pattern)) => Ok(format!("{}{} {}", IGNORE_PATH_PREFIX, file, pattern)),
+        (Some(file), None) => Ok(format!("{}{}", IGNORE_PATH_PREFIX, file)),
+        (None, Some(pattern)) if !pattern.trim().is_empty() => Ok(pattern.to_string()),
+        (None, _) => anyhow::bail!("Give a pattern, --path or --fingerprint"),
+    }
 }
 
-pub async fn add(pattern: &str) -> Result<()> {
+pub async fn add(
+    pattern: Option<&str>,
+    file: Option<&str>,
+    fingerprint: Option<&str>,
+) -> Result<()> {
+    let pattern = &ignore_entry(pattern, file, fingerprint)?;
     let path = ignore_file_path()?;
 
     // Read existing patterns
@@ -65,7 +89,12 @@ pub async fn list() -> Result<()> {
     Ok(())
 }
 
-pub async fn remove(pattern: &str) -> Result<()> {
+pub async fn remove(
+    pattern: Option<&str>,
+    file: Option<&str>,
+    fingerprint: Option<&str>,
+) -> Result<()> {
+    let pattern = &ignore_entry(pattern, file, fingerprint)?;
     let path = ignore_file_path()?;
 
     if !path.exists() {
@@ -1,14 +1,27 @@
 use crate::cli::{Output, Progress, Prompt};
 use crate::config::{Config, FeaturesConfig};
+#[cfg(feature = "github")]
 use crate::github::GitHubCli;
+use crate::security::pairing::Invite;
 use crate::sync::{GitBackend, SyncEngine, SyncState};
 use anyhow::Result;
 
-pub async fn run(repo: Option<&str>, no_daemon: bool, team_only: bool) -> Result<()> {
+pub async fn run(
+    repo: Option<&str>,
+    no_daemon: bool,
+    team_only: bool,
+    from_qr: Option<&str>,
+) -> Result<()> {
     Output::header("Welcome to Tether!");
     Output::dim("Sync your dev environment across machines");
     println!();
 
+    // A pairing invite: unlock its key up front, so a wrong code fails before anything changes
+    let paired = match from_qr {
+        Some(source) => Some(read_invite(source)?),
+        None => None,
+    };
+
     // Check if already initialized
     let config_path = Config::config_path()?;
     let already_initialized = config_path.exists();
@@ -25,7 +38,9 @@ pub async fn run(repo: Option<&str>, no_daemon: bool, team_only: bool) -> Result
 
         if has_personal {
             Output::info("Running sync to preserve your data...");
-            if let Err(e) = super::sync::run(false, false, false).await {
+            if let Err(e) =
+                super::sync::run(false, false, false, true, crate::sync::SyncMode::Full).await
+            {
                 Output::warning(&format!("Sync failed: {}", e));
                 if !Prompt::confirm(
                     "Continue with reinit anyway? (may lose unsynced changes)",
@@ -49,7 +64,12 @@ pub async fn run(repo: Option<&str>, no_daemon: bool, team_only: bool) -> Result
     };
 
     // Legacy --team-only flag support
-    if team_only {
+    if let Some((invite, key)) = &paired {
+        config.features.personal_dotfiles = true;
+        config.features.personal_packages = true;
+        config.security.encrypt_dotfiles = key.is_some();
+        Output::info(&format!("Joining {}", invite.repo));
+    } else if team_only {
         config.features.personal_dotfiles = false;
         config.features.personal_packages = false;
         config.features.team_dotfiles = true;
@@ -64,7 +84,9 @@ pub async fn run(repo: Option<&str>, no_daemon: bool, team_only: bool) -> Result
 
     // Personal repo setup (if personal features enabled)
     if needs_personal_repo {
-        let repo_url = if let Some(url) = repo {
+        let repo_url = if let Some((invite, _)) = &paired {
+            invite.repo.clone()
+        } else if let Some(url) = repo {
             url.to_string()
         } else if already_initialized && !config.backend.url.is_empty() {
             Output::dim(&format!("  Current repo: {}", config.backend.url));
@@ -88,13 +110,31 @@ pub async fn run(repo: Option<&str>, no_daemon: bool, team_only: bool) -> Result
         let tether_dir = Config::config_dir()?;
         std::fs::create_dir_all(&tether_dir)?;
 
-        // Clone or pull repository
+        // A checkout of another repo would otherwise be pulled from its old remote
         let sync_path = SyncEngine::sync_path()?;
+        if sync_path.exists() {
+            let checkout_url = crate::sync::git::get_remote_url(&sync_path).unwrap_or_default();
+            if !checkout_url.is_empty()
+                && crate::security::key_namespace(&checkout_url)
+                    != crate::security::key_namespace(&repo_url)
+                && !move_aside_checkout(&sync_path, &checkout_url, &repo_url)?
+            {
+                return Ok(());
+            }
+        }
+
+        // Clone or pull repository
         if sync_path.exists() {
             let git = GitBackend::open(&sync_path)?;
             git.pull()?;
         } else {
-            GitBackend::clone(&repo_url, &sync_path)?;
+            let pb = Progress::spinner("Cloning sync repository...");
+            GitBackend::clone_with_progress(
+                &repo_url,
+                &sync_path,
+                Some(Progress::transfer(&pb, "Cloning sync repository...")),
+            )?;
+            pb.finish_and_clear();
         }
 
         // Create sync repo structure (profiles/ created by migration or export)
@@ -103,8 +143,12 @@ pub async fn run(repo: Option<&str>, no_daemon: bool, team_only: bool) -> Result
 
         crate::sync::check_sync_format_version(&sync_path)?;
 
-        // Setup encryption if enabled
-        if config.security.encrypt_dotfiles {
+        // Setup encryption if enabled. The key is cached under the repo URL, so save it first.
+        if let Some((_, Some(key))) = &paired {
+            config.save()?;
+            crate::security::unlock_with_key(key)?;
+        } else if config.security.encrypt_dotfiles {
+            config.save()?;
             setup_encryption()?;
         }
 
@@ -128,24 +172,39 @@ pub async fn run(repo: Option<&str>, no_daemon: bool, team_only: bool) -> Result
     }
 
     // Profile assignment
-    if needs_personal_repo {
+    if let Some(profile) = paired
+        .as_ref()
+        .and_then(|(invite, _)| invite.profile.as_ref())
+        .filter(|profile| config.profiles.contains_key(*profile))
+    {
+        let state = SyncState::load()?;
+        config
+            .machine_profiles
+            .insert(state.machine_id.clone(), profile.clone());
+        Output::success(&format!("Assigned profile '{}'", profile));
+    } else if needs_personal_repo {
         assign_profile_during_init(&mut config)?;
     }
 
     config.save()?;
 
     // Create initial state
-    let state = SyncState::load()?;
+    let mut state = SyncState::load()?;
+    let public = needs_personal_repo && repo_is_public(&config, &mut state);
     state.save()?;
+    if public && !Prompt::confirm("Sync your dotfiles to this public repo anyway?", false)? {
+        Output::info("Make the repo private, then run 'tether sync' and 'tether daemon install'");
+        return Ok(());
+    }
 
     // Initial sync (only if personal features enabled)
     if needs_personal_repo {
-        super::sync::run(false, false, false).await?;
+        super::sync::run(false, false, false, true, crate::sync::SyncMode::Full).await?;
     }
 
     // Install daemon for auto-sync (unless opted out)
     if !no_daemon {
-        if let Err(err) = super::daemon::install().await {
+        if let Err(err) = super::daemon::install(false).await {
             Output::warning(&format!("Failed to install daemon: {}", err));
         }
     }
@@ -175,6 +234,72 @@ pub async fn run(repo: Option<&str>, no_daemon: bool, team_only: bool) -> Result
     Ok(())
 }
 
+/// Read a pairing invite from the camera, a screenshot or pasted text, and decrypt its
+/// key with the pairing code
+fn read_invite(source: &str) -> Result<(Invite, Option<Vec<u8>>)> {
+    let payload = if source.is_empty() {
+        scan_or_paste()?
+    } else if std::path::Path::new(source).is_file() {
+        scan_image(source)?
+    } else {
+        source.to_string()
+    };
+    let invite = Invite::decode(&payload)?;
+    if invite.is_expired() {
+        anyhow::bail!(
+            "This pairing code expired at {}; run 'tether machines pair' again",
+            invite
+                .expires
+                .with_timezone(&chrono::Local)
+                .format("%Y-%m-%d %H:%M")
+        );
+    }
+    if invite.wrapped_key.is_none() {
+        return Ok((invite, None));
+    }
+    let code = Prompt::input("Pairing code (shown next to the QR code)", None)?;
+    let key = invite.unwrap_key(&code)?;
+    Output::success("Pairing code accepted");
+    Ok((invite, key))
+}
+
+/// Scan a QR code with the camera when zbarcam is installed, or ask for the text
+fn scan_or_paste() -> Result<String> {
+    if which::which("zbarcam").is_ok()
+        && Prompt::confirm("Scan the pairing QR code with the camera?", true)?
+    {
+        Output::info("Hold the QR code up to the camera...");
+        let output = std::process::Command::new("zbarcam")
+            .args(["--raw", "-1", "--nodisplay"])
+            .stderr(std::process::Stdio::null())
+            .output()?;
+        let scanned = String::from_utf8_lossy(&output.stdout).trim().to_string();
+        if output.status.success() && !scanned.is_empty() {
+            return Ok(scanned);
+        }
+        Output::warning("Couldn't read a QR code from the camera");
+    }
+    Prompt::input("Paste the text shown under the QR code", None)
+}
+
+/// Read the QR code in a screenshot or photo with zbarimg
+fn scan_image(path: &str) -> Result<String> {
+    let output = std::process::Command::new("zbarimg")
+        .args(["--raw", "-q", path])
+        .output()
+        .map_err(|_| {
+            anyhow::anyhow!(
+                "Reading a QR code from an image needs zbarimg (from zbar); paste the text instead"
+            )
+        })?;
+    let scanned = String::from_utf8_lossy(&output.stdout);
+    scanned
+        .lines()
+        .find(|line| line.starts_with("tether-pair:"))
+        .map(String::from)
+        .ok_or_else(|| anyhow::anyhow!("No pairing QR code found in {}", path))
+}
+
 /// Prompt user to select features
 fn select_features(current: &FeaturesConfig) -> Result<FeaturesConfig> {
     let options = vec![
@@ -267,6 +392,9 @@ fn assign_profile_during_init(config: &mut Config) -> Result<()> {
                     dotfiles: vec![],
                     dirs: vec![],
                     packages: detect_local_managers(),
+                    extends: None,
+                    role: None,
+                    server: None,
                 },
             );
         }
@@ -349,6 +477,28 @@ fn detect_local_managers() -> Vec<String> {
         .collect()
 }
 
+/// Move the checkout of the previous sync repo aside so the new one is cloned fresh.
+/// The old repo's cached key stays filed under its URL.
+fn move_aside_checkout(sync_path: &std::path::Path, old_url: &str, new_url: &str) -> Result<bool> {
+    Output::warning(&format!(
+        "Switching sync repo from {} to {}",
+        old_url, new_url
+    ));
+    Output::dim(
+        "  The current checkout is kept as a backup and the new repo needs its own passphrase",
+    );
+    if !Prompt::confirm("Switch repositories?", true)? {
+        return Ok(false);
+    }
+    let backup = sync_path.with_file_name(format!(
+        "sync.{}.bak",
+        chrono::Local::now().format("%Y%m%d-%H%M%S")
+    ));
+    std::fs::rename(sync_path, &backup)?;
+    Output::info(&format!("Old checkout moved to {}", backup.display()));
+    Ok(true)
+}
+
 fn setup_encryption() -> Result<()> {
     if crate::security::has_encryption_key() {
         Output::info("Encrypted key found. Enter passphrase:");
@@ -376,22 +526,48 @@ fn setup_encryption() -> Result<()> {
     Ok(())
 }
 
+/// Check whether the sync repo is public, warning loudly if it is. A failed check
+/// (offline, say) doesn't hold up init; the daemon checks again later.
+fn repo_is_public(config: &Config, state: &mut SyncState) -> bool {
+    match crate::sync::visibility::refresh(config, state) {
+        Ok(Some(true)) => {
+            println!();
+            Output::error(crate::sync::visibility::PUBLIC_WARNING);
+            println!();
+            true
+        }
+        Ok(_) => false,
+        Err(e) => {
+            log::debug!("Repo visibility check failed: {}", e);
+            false
+        }
+    }
+}
+
 async fn setup_repository() -> Result<String> {
-    let options = vec![
-        "GitHub (automatic - recommended)",
+    let mut options = Vec::new();
+    if cfg!(feature = "github") {
+        options.push("GitHub (automatic - recommended)");
+    }
+    options.extend([
         "GitHub (manual - I'll create the repo)",
         "GitLab",
         "Custom Git URL",
-    ];
+    ]);
 
-    let selection = Prompt::select("How would you like to sync your dotfiles?", options, 0)?;
+    let selection = Prompt::select(
+        "How would you like to sync your dotfiles?",
+        options.clone(),
+        0,
+    )?;
 
-    match selection {
-        0 => {
+    match options[selection] {
+        #[cfg(feature = "github")]
+        "GitHub (automatic - recommended)" => {
             Output::info("Setting up GitHub sync...");
             setup_github_automatic().await
         }
-        1 => {
+        "GitHub (manual - I'll create the repo)" => {
             Output::info("Create a private repository on GitHub first");
             Output::dim("  Visit: https://github.com/new");
             println!();
@@ -401,7 +577,7 @@ async fn setup_repository() -> Result<String> {
                 "e.g., https://github.com/user/tether-sync.git",
             )
         }
-        2 => {
+        "GitLab" => {
             Output::info("Create a private repository on GitLab first");
             Output::dim("  Visit: https://gitlab.com/projects/new");
             println!();
@@ -411,11 +587,11 @@ async fn setup_repository() -> Result<String> {
                 "e.g., https://gitlab.com/user/tether-sync.git",
             )
         }
-        3 => Prompt::input_with_help("Git repository URL", None, "SSH or HTTPS URL"),
-        _ => unreachable!(),
+        _ => Prompt::input_with_help("Git repository URL", None, "SSH or HTTPS URL"),
     }
 }
 
+#[cfg(feature = "github")]
 async fn setup_github_automatic() -> Result<String> {
     if !GitHubCli::is_installed() {
         Output::warning("GitHub CLI (gh) is not installed");
@@ -498,6 +674,7 @@ async fn setup_github_automatic() -> Result<String> {
     create_github_repo(&repo_name).await
 }
 
+#[cfg(feature = "github")]
 async fn create_github_repo(repo_name: &str) -> Result<String> {
     let pb = Progress::spinner("Creating private repository...");
     let repo_url = GitHubCli::create_repo(repo_name, true).await?;
@@ -0,0 +1,206 @@
+use super::bootstrap::KEY_ENV;
+use super::push_to::shell_quote;
+use crate::cli::Output;
+use crate::config::Config;
+use crate::sync::SyncState;
+use anyhow::Result;
+use serde_json::json;
+use std::path::Path;
+
+/// Provides cargo for building tether inside the container
+const RUST_FEATURE: &str = "ghcr.io/devcontainers/features/rust:1";
+
+/// Where the feature's install script leaves the bootstrap for postCreateCommand
+const FEATURE_BOOTSTRAP: &str = "/usr/local/share/tether/bootstrap.sh";
+
+/// Builds this version of tether from source; there are no Linux release binaries
+fn install_command() -> String {
+    format!(
+        "cargo install --locked --git https://github.com/paddo-tech/tether-cli --tag v{} --no-default-features",
+        env!("CARGO_PKG_VERSION")
+    )
+}
+
+/// "owner/repo" for a GitHub sync repo URL (SSH or HTTPS)
+fn github_repo(url: &str) -> Option<String> {
+    let rest = url
+        .strip_prefix("git@github.com:")
+        .or_else(|| url.split_once("github.com/").map(|(_, rest)| rest))?;
+    let repo = rest.trim_end_matches('/').trim_end_matches(".git");
+    (repo.matches('/').count() == 1).then(|| repo.to_string())
+}
+
+/// The URL a container clones from. Codespaces authenticates HTTPS clones of GitHub
+/// repos but has no SSH key, so GitHub SSH URLs become HTTPS ones.
+fn clone_url(url: &str) -> String {
+    match github_repo(url) {
+        Some(repo) if !url.starts_with("http") => format!("https://github.com/{}.git", repo),
+        _ => url.to_string(),
+    }
+}
+
+fn bootstrap_command(url: &str, profile: &str, packages: bool) -> String {
+    let mut command = format!(
+        "tether bootstrap --ephemeral {} --profile {}",
+        shell_quote(url),
+        shell_quote(profile)
+    );
+    if packages {
+        command.push_str(" --packages");
+    }
+    command
+}
+
+/// The `TETHER_KEY` secret, and read access to a GitHub sync repo so the Codespaces
+/// token can clone it
+fn codespaces_settings(snippet: &mut serde_json::Value, config: &Config, url: &str) {
+    if config.security.encrypt_dotfiles {
+        snippet["secrets"] = json!({
+            KEY_ENV: {
+                "description": "Identity from 'tether bootstrap --issue-identity' (or your tether passphrase)"
+            }
+        });
+    }
+    if let Some(repo) = github_repo(url) {
+        snippet["customizations"] = json!({
+            "codespaces": {
+                "repositories": { repo: { "permissions": { "contents": "read" } } }
+            }
+        });
+    }
+}
+
+/// devcontainer.json properties that install tether and bootstrap the dotfiles
+fn post_create_snippet(config: &Config, profile: &str, packages: bool) -> serde_json::Value {
+    let url = clone_url(&config.backend.url);
+    let mut snippet = json!({
+        "features": { RUST_FEATURE: {} },
+        "postCreateCommand": format!(
+            "{} && {}",
+            install_command(),
+            bootstrap_command(&url, profile, packages)
+        ),
+    });
+    codespaces_settings(&mut snippet, config, &url);
+    snippet
+}
+
+/// A local devcontainer feature: tether is built into the image, and the bootstrap
+/// runs as the container user once it's created
+fn write_feature(dir: &Path, config: &Config, profile: &str, packages: bool) -> Result<()> {
+    let url = clone_url(&config.backend.url);
+    let manifest = json!({
+        "id": "tether",
+        "version": env!("CARGO_PKG_VERSION"),
+        "name": "tether dotfiles",
+        "description": "Applies dotfiles from a tether sync repo",
+        "options": {
+            "repo": { "type": "string", "default": url },
+            "profile": { "type": "string", "default": profile },
+            "packages": { "type": "boolean", "default": packages },
+        },
+        "dependsOn": { RUST_FEATURE: {} },
+        "postCreateCommand": FEATURE_BOOTSTRAP,
+    });
+    let install = format!(
+        r#"#!/bin/sh
+set -e
+{install} --root /usr/local
+mkdir -p "$(dirname {bootstrap})"
+PACKAGES_FLAG=""
+[ "$PACKAGES" = "true" ] && PACKAGES_FLAG="--packages"
+cat > {bootstrap} <<EOF
+#!/bin/sh
+exec tether bootstrap --ephemeral '$REPO' --profile '$PROFILE' $PACKAGES_FLAG
+EOF
+chmod 755 {bootstrap}
+"#,
+        install = install_command(),
+        bootstrap = FEATURE_BOOTSTRAP,
+    );
+
+    std::fs::create_dir_all(dir)?;
+    std::fs::write(
+        dir.join("devcontainer-feature.json"),
+        serde_json::to_string_pretty(&manifest)? + "\n",
+    )?;
+    let script = dir.join("install.sh");
+    std::fs::write(&script, install)?;
+    #[cfg(unix)]
+    {
+        use std::os::unix::fs::PermissionsExt;
+        std::fs::set_permissions(&script, std::fs::Permissions::from_mode(0o755))?;
+    }
+    Ok(())
+}
+
+/// Print a devcontainer.json snippet that bootstraps this sync repo in every container
+/// of the project, or write it as a local feature under `.devcontainer/`
+pub async fn devcontainer(
+    profile: Option<&str>,
+    packages: bool,
+    feature: Option<&Path>,
+) -> Result<()> {
+    let config = Config::load()?;
+    let state = SyncState::load()?;
+    let profile = profile.unwrap_or_else(|| config.profile_name(&state.machine_id));
+    if !config.profiles.contains_key(profile) {
+        anyhow::bail!("Profile '{}' not found", profile);
+    }
+    let url = &config.backend.url;
+    if Path::new(url).is_absolute() || url.starts_with("file://") {
+        anyhow::bail!(
+            "The sync repo is a local path ({}), which containers can't clone",
+            url
+        );
+    }
+
+    let Some(dir) = feature else {
+        let snippet = post_create_snippet(&config, profile, packages);
+        println!("{}", serde_json::to_string_pretty(&snippet)?);
+        return Ok(());
+    };
+
+    write_feature(dir, &config, profile, packages)?;
+    Output::success(&format!(
+        "Wrote a devcontainer feature to {}",
+        dir.display()
+    ));
+    let name = dir
+        .file_name()
+        .map(|n| n.to_string_lossy().to_string())
+        .unwrap_or_else(|| "tether".to_string());
+    let mut snippet = json!({ "features": { format!("./{}", name): {} } });
+    codespaces_settings(&mut snippet, &config, &clone_url(&config.backend.url));
+    Output::info(
+        "Add it to .devcontainer/devcontainer.json (the feature must live in .devcontainer/):",
+    );
+    println!("{}", serde_json::to_string_pretty(&snippet)?);
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_clone_url() {
+        assert_eq!(
+            github_repo("git@github.com:paddo/dotfiles.git").as_deref(),
+            Some("paddo/dotfiles")
+        );
+        assert_eq!(
+            clone_url("git@github.com:paddo/dotfiles.git"),
+            "https://github.com/paddo/dotfiles.git"
+        );
+        assert_eq!(
+            clone_url("https://github.com/paddo/dotfiles"),
+            "https://github.com/paddo/dotfiles"
+        );
+        assert_eq!(
+            clone_url("git@gitlab.com:paddo/dotfiles.git"),
+            "git@gitlab.com:paddo/dotfiles.git"
+        );
+        assert_eq!(github_repo("https://gitlab.com/paddo/dotfiles"), None);
+    }
+}
@@ -1,6 +1,7 @@
 use crate::cli::{Output, Prompt};
 use crate::config::Config;
-use crate::sync::{GitBackend, MachineState, SyncEngine, SyncState};
+use crate::sync::report::DriftReport;
+use crate::sync::{metadata, GitBackend, MachineDiff, MachineState, SyncEngine, SyncState};
 use anyhow::Result;
 use chrono::Local;
 use comfy_table::{Attribute, Cell, Color};
@@ -39,6 +40,9 @@ pub async fn list() -> Result<()> {
         Cell::new("Hostname")
             .add_attribute(Attribute::Bold)
             .fg(Color::Cyan),
+        Cell::new("OS")
+            .add_attribute(Attribute::Bold)
+            .fg(Color::Cyan),
         Cell::new("Version")
             .add_attribute(Attribute::Bold)
             .fg(Color::Cyan),
@@ -54,9 +58,17 @@ pub async fn list() -> Result<()> {
         let local_time = machine.last_sync.with_timezone(&Local);
 
         let version = if machine.cli_version.is_empty() {
-            "-".to_string()
+            Cell::new("-")
+        } else if crate::sync::version_incompatible(&machine.cli_version) {
+            Cell::new(format!("{} (incompatible)", machine.cli_version)).fg(Color::Yellow)
         } else {
-            machine.cli_version.clone()
+            Cell::new(&machine.cli_version)
+        };
+        let os = match (machine.os_version.as_str(), machine.arch.as_str()) {
+            ("", "") => "-".to_string(),
+            (os, "") => os.to_string(),
+            ("", arch) => arch.to_string(),
+            (os, arch) => format!("{} ({})", os, arch),
         };
 
         let profile = machine
@@ -72,7 +84,8 @@ pub async fn list() -> Result<()> {
             },
             Cell::new(profile),
             Cell::new(&machine.hostname),
-            Cell::new(version),
+            Cell::new(os),
+            version,
             Cell::new(local_time.format("%Y-%m-%d %H:%M:%S").to_string()),
             Cell::new(marker).fg(Color::Green),
         ]);
@@ -81,6 +94,14 @@ pub async fn list() -> Result<()> {
     println!("{table}");
     println!();
 
+    let incompatible = crate::sync::incompatible_peers(&machines, current_machine);
+    if !incompatible.is_empty() {
+        Output::warning(&format!(
+            "This machine runs tether {}; upgrade whichever side is older so every machine understands the others' syncs",
+            env!("CARGO_PKG_VERSION")
+        ));
+    }
+
     Ok(())
 }
 
@@ -148,17 +169,15 @@ pub async fn rename(old: &str, new: &str) -> Result<()> {
     }
 
     let sync_path = SyncEngine::sync_path()?;
-    let machines_dir = sync_path.join("machines");
+    let old_file = MachineState::repo_file(&sync_path, old);
+    let new_file = MachineState::repo_file(&sync_path, new);
 
-    let old_file = machines_dir.join(format!("{}.json", old));
-    let new_file = machines_dir.join(format!("{}.json", new));
-
-    if !old_file.exists() {
+    if !metadata::exists(&old_file) {
         Output::error(&format!("Machine '{}' not found", old));
         return Ok(());
     }
 
-    if new_file.exists() {
+    if metadata::exists(&new_file) {
         Output::error(&format!("Machine '{}' already exists", new));
         return Ok(());
     }
@@ -169,11 +188,10 @@ pub async fn rename(old: &str, new: &str) -> Result<()> {
     machine.machine_id = new.to_string();
 
     // Write to new file
-    let content = serde_json::to_string_pretty(&machine)?;
-    std::fs::write(&new_file, content)?;
+    machine.save_to_repo(&sync_path)?;
 
     // Remove old file
-    std::fs::remove_file(&old_file)?;
+    metadata::remove(&old_file)?;
 
     // Update local state if this is the current machine
     let mut state = SyncState::load()?;
@@ -197,6 +215,148 @@ pub async fn rename(old: &str, new: &str) -> Result<()> {
     Ok(())
 }
 
+pub async fn diff(from: &str, to: &str, json: bool) -> Result<()> {
+    let config = Config::load()?;
+    if !config.has_personal_features() {
+        anyhow::bail!("Machine management not available in team-only mode");
+    }
+
+    let sync_path = SyncEngine::sync_path()?;
+    let load = |name: &str| {
+        MachineState::load_from_repo(&sync_path, name)?.ok_or_else(|| {
+            anyhow::anyhow!(
+                "Machine '{}' not found. Use 'tether machines list' to see available machines",
+                name
+            )
+        })
+    };
+    let diff = MachineDiff::between(&load(from)?, &load(to)?);
+
+    if json {
+        println!("{}", serde_json::to_string_pretty(&diff)?);
+    } else {
+        println!();
+        super::diff::print_machine_diff(&diff);
+    }
+    Ok(())
+}
+
+pub async fn report(json: bool, send: bool) -> Result<()> {
+    let config = Config::load()?;
+    if !config.has_personal_features() {
+        anyhow::bail!("Machine management not available in team-only mode");
+    }
+    let state = SyncState::load()?;
+    let report = DriftReport::build(&config, &state, &SyncEngine::sync_path()?)?;
+
+    if json {
+        println!("{}", serde_json::to_string_pretty(&report)?);
+    } else {
+        print_report(&report);
+    }
+    if send {
+        report.deliver(&config)?;
+        if !json {
+            Output::success("Report delivered");
+        }
+    }
+    Ok(())
+}
+
+fn print_report(report: &DriftReport) {
+    println!();
+    println!("{}", "Drift Report".bright_cyan().bold());
+    println!();
+    Output::key_value("Summary", &report.summary());
+    for machine in &report.machines {
+        println!();
+        let last_sync = machine
+            .last_sync
+            .with_timezone(&Local)
+            .format("%Y-%m-%d %H:%M");
+        let heading = format!(
+            "{} ({})",
+            machine.machine_id,
+            machine.profile.as_deref().unwrap_or("no profile")
+        );
+        println!("  {}", heading.bold());
+        if machine.behind {
+            Output::warning(&format!("  Behind: last synced {}", last_sync));
+        } else {
+            Output::dim(&format!("    Last synced {}", last_sync));
+        }
+        if machine.incompatible {
+            Output::warning(&format!(
+                "  Runs tether {}, too far from this one ({})",
+                machine.cli_version,
+                env!("CARGO_PKG_VERSION")
+            ));
+        }
+        for (manager, changes) in &machine.packages {
+            if !changes.removed.is_empty() {
+                println!("    {} missing: {}", manager, changes.removed.join(", "));
+            }
+            if !changes.added.is_empty() {
+                println!("    {} only there: {}", manager, changes.added.join(", "));
+            }
+        }
+    }
+    if !report.conflicts.is_empty() {
+        println!();
+        Output::warning(&format!(
+            "{} conflict(s) on this machine; run 'tether resolve'",
+            report.conflicts.len()
+        ));
+        for file in &report.conflicts {
+            Output::dim(&format!("    {}", file));
+        }
+    }
+    for warning in &report.warnings {
+        Output::warning(warning);
+    }
+}
+
+/// Show a QR code a new machine can scan with `tether init --from-qr` to join with
+/// this repo, profile and key
+pub async fn pair(profile: Option<&str>, json: bool) -> Result<()> {
+    let config = Config::load()?;
+    let state = SyncState::load()?;
+    let (invite, code) =
+        crate::security::pairing::Invite::from_config(&config, &state.machine_id, profile)?;
+    let payload = invite.encode();
+    let expires = invite.expires.with_timezone(&Local).format("%H:%M");
+
+    if json {
+        let out = serde_json::json!({
+            "payload": payload,
+            "code": code,
+            "expires": invite.expires,
+        });
+        println!("{}", serde_json::to_string_pretty(&out)?);
+        return Ok(());
+    }
+
+    println!();
+    for line in crate::security::pairing::qr_lines(&payload)? {
+        println!("{}", line);
+    }
+    println!();
+    Output::info("On the new machine, run 'tether init --from-qr' and scan this, or paste:");
+    Output::dim(&format!("  {}", payload));
+    println!();
+    if let Some(code) = code {
+        Output::key_value("Pairing code", &code);
+        Output::dim(
+            "  Type it on the new machine; it isn't in the QR code, so share it separately",
+        );
+    }
+    if let Some(profile) = &invite.profile {
+        Output::key_value("Profile", profile);
+    }
+    Output::key_value("Valid until", &expires.to_string());
+    Ok(())
+}
+
 pub async fn remove(name: &str) -> Result<()> {
     let mut config = Config::load()?;
     if !config.has_personal_features() {
@@ -213,10 +373,9 @@ pub async fn remove(name: &str) -> Result<()> {
     }
 
     let sync_path = SyncEngine::sync_path()?;
-    let machines_dir = sync_path.join("machines");
-    let machine_file = machines_dir.join(format!("{}.json", name));
+    let machine_file = MachineState::repo_file(&sync_path, name);
 
-    if !machine_file.exists() {
+    if !metadata::exists(&machine_file) {
         Output::error(&format!("Machine '{}' not found", name));
         return Ok(());
     }
@@ -225,7 +384,7 @@ pub async fn remove(name: &str) -> Result<()> {
         return Ok(());
     }
 
-    std::fs::remove_file(&machine_file)?;
+    metadata::remove(&machine_file)?;
 
     // Clean up profile assignment
     if config.machine_profiles.remove(name).is_some() {
@@ -241,7 +400,7 @@ pub async fn remove(name: &str) -> Result<()> {
     Ok(())
 }
 
-pub async fn profile_create(name: &str) -> Result<()> {
+pub async fn profile_create(name: &str, extends: Option<&str>) -> Result<()> {
     let mut config = Config::load()?;
 
     if !Config::is_safe_profile_name(name) {
@@ -254,6 +413,23 @@ pub async fn profile_create(name: &str) -> Result<()> {
         return Ok(());
     }
 
+    // Inherited entries don't need to be picked again
+    let parent = match extends {
+        Some(parent) => match config.resolve_profile(parent) {
+            Ok(resolved) => Some(resolved),
+            Err(e) => {
+                Output::error(&e.to_string());
+                return Ok(());
+            }
+        },
+        None => None,
+    };
+    let inherited = |path: &str| {
+        parent
+            .as_ref()
+            .is_some_and(|p| p.dotfiles.iter().any(|e| e.path() == path))
+    };
+
     // Gather all known dotfiles from all existing profiles
     let mut all_dotfiles: Vec<String> = Vec::new();
     for profile in config.profiles.values() {
@@ -271,6 +447,7 @@ pub async fn profile_create(name: &str) -> Result<()> {
             all_dotfiles.push(path);
         }
     }
+    all_dotfiles.retain(|p| !inherited(p));
     all_dotfiles.sort();
 
     // Select dotfiles
@@ -302,17 +479,18 @@ pub async fn profile_create(name: &str) -> Result<()> {
 
     // Select dirs
     let mut all_dirs: Vec<String> = Vec::new();
-    for profile in config.profiles.values() {
-        for dir in &profile.dirs {
-            if !all_dirs.contains(dir) {
-                all_dirs.push(dir.clone());
-            }
+    for dir in config
+        .profiles
+        .values()
+        .flat_map(|p| &p.dirs)
+        .chain(&config.dotfiles.dirs)
+    {
+        if !all_dirs.iter().any(|d| d == dir.path()) {
+            all_dirs.push(dir.path().to_string());
         }
     }
-    for dir in &config.dotfiles.dirs {
-        if !all_dirs.contains(dir) {
-            all_dirs.push(dir.clone());
-        }
+    if let Some(parent) = &parent {
+        all_dirs.retain(|d| !parent.dirs.iter().any(|p| p.path() == d));
     }
     all_dirs.sort();
 
@@ -325,21 +503,28 @@ pub async fn profile_create(name: &str) -> Result<()> {
     };
     let dirs: Vec<String> = selected_dirs.iter().map(|i| all_dirs[*i].clone()).collect();
 
-    // Select package managers
-    let all_managers = ["brew", "npm", "pnpm", "bun", "gem", "uv"];
-    let manager_options: Vec<&str> = all_managers.to_vec();
-    let mgr_defaults: Vec<usize> = (0..all_managers.len()).collect();
-    let selected_managers =
-        Prompt::multi_select("Select package managers", manager_options, &mgr_defaults)?;
-    let packages: Vec<String> = selected_managers
-        .iter()
-        .map(|i| all_managers[*i].to_string())
-        .collect();
+    // Select package managers (child profiles inherit the parent's list)
+    let packages: Vec<String> = if parent.is_some() {
+        Vec::new()
+    } else {
+        let all_managers = ["brew", "npm", "pnpm", "bun", "gem", "uv"];
+        let manager_options: Vec<&str> = all_managers.to_vec();
+        let mgr_defaults: Vec<usize> = (0..all_managers.len()).collect();
+        let selected_managers =
+            Prompt::multi_select("Select package managers", manager_options, &mgr_defaults)?;
+        selected_managers
+            .iter()
+            .map(|i| all_managers[*i].to_string())
+            .collect()
+    };
 
     let profile = crate::config::ProfileConfig {
         dotfiles: profile_dotfiles,
-        dirs,
+        dirs: dirs.into_iter().map(Into::into).collect(),
         packages,
+        extends: extends.map(|p| p.to_string()),
+        role: None,
+        server: None,
     };
 
     config.profiles.insert(name.to_string(), profile);
@@ -441,6 +626,9 @@ pub async fn profile_edit(name: &str) -> Result<()> {
         dotfiles: new_dotfiles,
         dirs: profile.dirs.clone(),
         packages,
+        extends: profile.extends.clone(),
+        role: profile.role,
+        server: profile.server,
     };
 
     config.profiles.insert(name.to_string(), updated);
@@ -476,6 +664,9 @@ pub async fn profile_list() -> Result<()> {
         Cell::new("Packages")
             .add_attribute(Attribute::Bold)
             .fg(Color::Cyan),
+        Cell::new("Extends")
+            .add_attribute(Attribute::Bold)
+            .fg(Color::Cyan),
         Cell::new("Machines")
             .add_attribute(Attribute::Bold)
             .fg(Color::Cyan),
@@ -504,6 +695,7 @@ pub async fn profile_list() -> Result<()> {
             Cell::new(profile.dotfiles.len().to_string()),
             Cell::new(profile.dirs.len().to_string()),
             Cell::new(packages_display),
+            Cell::new(profile.extends.as_deref().unwrap_or("-")),
             Cell::new(if machines.is_empty() {
                 "-".to_string()
             } else {
@@ -517,3 +709,89 @@ pub async fn profile_list() -> Result<()> {
 
     Ok(())
 }
+
+pub async fn profile_show(name: &str, resolved: bool) -> Result<()> {
+    let config = Config::load()?;
+
+    let Some(own) = config.profiles.get(name) else {
+        Output::error(&format!("Profile '{}' not found", name));
+        return Ok(());
+    };
+    let chain = config.profile_chain(name)?;
+    let profile = if resolved {
+        config.resolve_profile(name)?
+    } else {
+        own.clone()
+    };
+
+    println!();
+    Output::section(&format!("Profile: {}", name));
+    println!();
+    if chain.len() > 1 {
+        Output::key_value("Extends", &chain[1..].join(" -> "));
+    }
+    if profile.role == Some(crate::config::MachineRole::ReadOnly) {
+        Output::key_value("Role", "read-only");
+    }
+    if profile.server == Some(true) {
+        Output::key_value("Mode", "server");
+    }
+    Output::key_value(
+        "Packages",
+        &if profile.packages.is_empty() {
+            "all".to_string()
+        } else {
+            profile.packages.join(", ")
+        },
+    );
+    if !profile.dirs.is_empty() {
+        let dirs: Vec<&str> = profile.dirs.iter().map(|d| d.path()).collect();
+        Output::key_value("Dirs", &dirs.join(", "));
+    }
+    println!();
+
+    if profile.dotfiles.is_empty() {
+        Output::info("No dotfiles");
+        return Ok(());
+    }
+
+    let mut table = Output::table_full();
+    table.set_header(vec![
+        Cell::new("Dotfile")
+            .add_attribute(Attribute::Bold)
+            .fg(Color::Cyan),
+        Cell::new("Storage")
+            .add_attribute(Attribute::Bold)
+            .fg(Color::Cyan),
+        Cell::new("From")
+            .add_attribute(Attribute::Bold)
+            .fg(Color::Cyan),
+    ]);
+    for entry in &profile.dotfiles {
+        // Nearest profile in the chain that declares the entry
+        let from = chain
+            .iter()
+            .find(|p| {
+                config.profiles[**p]
+                    .dotfiles
+                    .iter()
+                    .any(|e| e.path() == entry.path())
+            })
+            .copied()
+            .unwrap_or(name);
+        table.add_row(vec![
+            Cell::new(entry.path()),
+            Cell::new(if entry.shared() {
+                "shared".to_string()
+            } else {
+                format!("profiles/{}", from)
+            }),
+            Cell::new(if from == name { "-" } else { from }),
+        ]);
+    }
+
+    println!("{table}");
+    println!();
+
+    Ok(())
+}
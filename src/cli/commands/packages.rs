@@ -1,11 +1,14 @@
 use anyhow::Result;
 
 use crate::cli::output::Output;
+use crate::cli::progress::Progress;
 use crate::cli::prompts::Prompt;
+use crate::config::{Config, PackagesMode};
 use crate::packages::{
     BrewManager, BunManager, GemManager, NpmManager, PackageInfo, PackageManager, PnpmManager,
     UvManager,
 };
+use crate::sync::{MachineState, SyncEngine, SyncState};
 
 struct PackageEntry {
     manager: String,
@@ -185,3 +188,334 @@ async fn uninstall_package(
 
     Ok(())
 }
+
+/// Results shown per package manager by `tether packages search`
+const SEARCH_LIMIT: usize = 10;
+
+struct SearchHit {
+    /// Machine state key, e.g. "brew_formulae"
+    manager_key: &'static str,
+    name: String,
+    version: Option<String>,
+}
+
+pub async fn search(query: &str) -> Result<()> {
+    let brew = BrewManager::new();
+    let npm = NpmManager::new();
+    let gem = GemManager::new();
+
+    let pb = Progress::spinner(&format!("Searching for {}...", query));
+    let (formulae, casks, npm_results, gems) = tokio::join!(
+        search_manager(&brew, query),
+        async {
+            if !brew.is_available().await {
+                return Vec::new();
+            }
+            brew.search_casks(query)
+                .await
+                .unwrap_or_default()
+                .into_iter()
+                .map(|name| PackageInfo {
+                    name,
+                    version: None,
+                })
+                .collect()
+        },
+        search_manager(&npm, query),
+        search_manager(&gem, query),
+    );
+    pb.finish_and_clear();
+
+    let mut hits: Vec<SearchHit> = Vec::new();
+    for (manager_key, mut results) in [
+        ("brew_formulae", formulae),
+        ("brew_casks", casks),
+        ("npm", npm_results),
+        ("gem", gems),
+    ] {
+        // Exact matches first, otherwise keep the registry's relevance order
+        results.sort_by_key(|p| p.name != query);
+        hits.extend(results.into_iter().take(SEARCH_LIMIT).map(|p| SearchHit {
+            manager_key,
+            name: p.name,
+            version: p.version,
+        }));
+    }
+
+    if hits.is_empty() {
+        Output::info(&format!("No packages found for '{}'", query));
+        return Ok(());
+    }
+
+    let machines = SyncEngine::sync_path()
+        .and_then(|path| MachineState::list_all(&path))
+        .unwrap_or_default();
+    let machine_id = SyncState::load().map(|s| s.machine_id).unwrap_or_default();
+
+    let mut section = "";
+    for hit in &hits {
+        if hit.manager_key != section {
+            section = hit.manager_key;
+            Output::section(crate::packages::manager_label(section));
+        }
+        let has_it = |m: &&MachineState| {
+            m.packages
+                .get(hit.manager_key)
+                .is_some_and(|pkgs| pkgs.contains(&hit.name))
+        };
+        let mut line = match &hit.version {
+            Some(v) => format!("{} ({})", hit.name, v),
+            None => hit.name.clone(),
+        };
+        if machines
+            .iter()
+            .filter(|m| m.machine_id == machine_id)
+            .any(|m| has_it(&m))
+        {
+            line.push_str(" - installed here");
+        }
+        let peers: Vec<&str> = machines
+            .iter()
+            .filter(|m| m.machine_id != machine_id)
+            .filter(has_it)
+            .map(|m| m.machine_id.as_str())
+            .collect();
+        if !peers.is_empty() {
+            line.push_str(&format!(" - on {}", peers.join(", ")));
+        }
+        Output::list_item(&line);
+    }
+    println!();
+
+    if !std::io::IsTerminal::is_terminal(&std::io::stdin()) {
+        return Ok(());
+    }
+
+    let mut options: Vec<String> = hits
+        .iter()
+        .map(|h| {
+            format!(
+                "{} ({})",
+                h.name,
+                crate::packages::manager_label(h.manager_key)
+            )
+        })
+        .collect();
+    options.push("Don't install".to_string());
+    let option_refs: Vec<&str> = options.iter().map(|s| s.as_str()).collect();
+    let choice = match Prompt::select("Install a package?", option_refs, hits.len()) {
+        Ok(choice) => choice,
+        Err(_) => return Ok(()),
+    };
+    let Some(hit) = hits.get(choice) else {
+        return Ok(());
+    };
+
+    Output::info(&format!("Installing {}...", hit.name));
+    let installed = match hit.manager_key {
+        "brew_casks" => brew.install_cask(&hit.name, true).await,
+        manager_key => {
+            let manager: &dyn PackageManager = match manager_key {
+                "brew_formulae" => &brew,
+                "npm" => &npm,
+                _ => &gem,
+            };
+            manager
+                .install(&PackageInfo {
+                    name: hit.name.clone(),
+                    version: None,
+                })
+                .await
+                .map(|_| true)
+        }
+    };
+    match installed {
+        Ok(true) => {}
+        Ok(false) => anyhow::bail!("Failed to install {}", hit.name),
+        Err(e) => anyhow::bail!("Failed to install {}: {}", hit.name, e),
+    }
+
+    match add_to_manifests(hit.manager_key, &hit.name).await {
+        Ok(()) => Output::success(&format!(
+            "Installed {} and added it to the manifests; it syncs on the next `tether sync`",
+            hit.name
+        )),
+        Err(e) => {
+            Output::success(&format!("Installed {}", hit.name));
+            Output::warning(&format!("Couldn't add it to the manifests: {}", e));
+        }
+    }
+    Ok(())
+}
+
+/// Registry matches from `manager`, or nothing if it isn't installed or the search fails
+async fn search_manager(manager: &dyn PackageManager, query: &str) -> Vec<PackageInfo> {
+    if !manager.is_available().await {
+        return Vec::new();
+    }
+    manager.search(query).await.unwrap_or_else(|e| {
+        log::debug!("{} search failed: {}", manager.name(), e);
+        Vec::new()
+    })
+}
+
+/// Record a freshly installed package in this machine's state and regenerate the
+/// manifests, so it's shared without waiting for the next package rescan
+async fn add_to_manifests(manager_key: &str, name: &str) -> Result<()> {
+    let config = Config::load()?;
+    let sync_path = SyncEngine::sync_path()?;
+    if config.packages.mode == PackagesMode::ManifestAuthoritative {
+        // The next sync would uninstall it if the manifest didn't list it
+        return crate::sync::append_to_manifest(&sync_path, manager_key, name);
+    }
+    let mut state = SyncState::load()?;
+    let mut machine = MachineState::load_from_repo(&sync_path, &state.machine_id)?
+        .unwrap_or_else(|| MachineState::new(&state.machine_id));
+
+    let packages = machine.packages.entry(manager_key.to_string()).or_default();
+    if !packages.iter().any(|p| p == name) {
+        packages.push(name.to_string());
+        packages.sort();
+    }
+    // An earlier uninstall would otherwise keep it out of the union manifest
+    if let Some(removed) = machine.removed_packages.get_mut(manager_key) {
+        removed.retain(|p| p != name);
+    }
+    machine.removed_packages.retain(|_, pkgs| !pkgs.is_empty());
+    machine.save_to_repo(&sync_path)?;
+
+    crate::sync::sync_packages(&config, &mut state, &sync_path, &machine, false).await?;
+    state.save()
+}
+
+/// Queued installs matching `names`, or all of them with `yes`, or else the ones
+/// picked interactively
+fn select_pending(state: &SyncState, names: &[String], yes: bool) -> Result<Vec<(String, String)>> {
+    let pending: Vec<(String, String)> = state
+        .pending_installs
+        .iter()
+        .flat_map(|(key, packages)| packages.iter().map(|p| (key.clone(), p.clone())))
+        .collect();
+    if pending.is_empty() {
+        Output::info("No package installs are waiting for approval");
+        return Ok(Vec::new());
+    }
+
+    if !names.is_empty() {
+        for name in names {
+            if !pending.iter().any(|(_, p)| p == name) {
+                anyhow::bail!("'{}' isn't waiting for approval", name);
+            }
+        }
+        return Ok(pending
+            .into_iter()
+            .filter(|(_, p)| names.contains(p))
+            .collect());
+    }
+    if yes {
+        return Ok(pending);
+    }
+
+    let options: Vec<String> = pending
+        .iter()
+        .map(|(key, name)| format!("[{}] {}", crate::packages::manager_label(key), name))
+        .collect();
+    let option_refs: Vec<&str> = options.iter().map(|s| s.as_str()).collect();
+    let selected = match Prompt::multi_select("Select packages:", option_refs, &[]) {
+        Ok(indices) => indices,
+        Err(_) => return Ok(Vec::new()),
+    };
+    Ok(selected.into_iter().map(|i| pending[i].clone()).collect())
+}
+
+/// Install one queued package; casks may ask for a password
+async fn install_queued(manager_key: &str, name: &str) -> Result<bool> {
+    if manager_key == "brew_casks" {
+        return BrewManager::new().install_cask(name, true).await;
+    }
+    let manager: Box<dyn PackageManager> = match manager_key {
+        "brew_formulae" => Box::new(BrewManager::new()),
+        "npm" => Box::new(NpmManager::new()),
+        "pnpm" => Box::new(PnpmManager::new()),
+        "bun" => Box::new(BunManager::new()),
+        "gem" => Box::new(GemManager::new()),
+        "uv" => Box::new(UvManager::new()),
+        _ => anyhow::bail!("Unknown package manager: {}", manager_key),
+    };
+    manager
+        .install(&PackageInfo {
+            name: name.to_string(),
+            version: None,
+        })
+        .await?;
+    Ok(true)
+}
+
+/// Install packages other machines added, queued by `packages.approve_installs`
+pub async fn approve(names: &[String], yes: bool) -> Result<()> {
+    let mut state = SyncState::load()?;
+    let selected = select_pending(&state, names, yes)?;
+    if selected.is_empty() {
+        return Ok(());
+    }
+
+    let mut installed = 0;
+    for (manager_key, name) in &selected {
+        Output::info(&format!("Installing {}...", name));
+        match install_queued(manager_key, name).await {
+            Ok(true) => {
+                state.take_pending_install(manager_key, name);
+                installed += 1;
+            }
+            Ok(false) => Output::error(&format!("Failed to install {}", name)),
+            Err(e) => Output::error(&format!("Failed to install {}: {}", name, e)),
+        }
+    }
+    state.save()?;
+
+    Output::success(&format!(
+        "Installed {} of {} approved package{}",
+        installed,
+        selected.len(),
+        if selected.len() == 1 { "" } else { "s" }
+    ));
+    Ok(())
+}
+
+/// Keep queued packages off this machine: they're recorded as removed here, like an
+/// uninstall, so later syncs don't queue them again
+pub async fn reject(names: &[String], yes: bool) -> Result<()> {
+    let mut state = SyncState::load()?;
+    let selected = select_pending(&state, names, yes)?;
+    if selected.is_empty() {
+        return Ok(());
+    }
+
+    let sync_path = SyncEngine::sync_path()?;
+    let mut machine = MachineState::load_from_repo(&sync_path, &state.machine_id)?
+        .unwrap_or_else(|| MachineState::new(&state.machine_id));
+    for (manager_key, name) in &selected {
+        let removed = machine
+            .removed_packages
+            .entry(manager_key.clone())
+            .or_default();
+        if !removed.contains(name) {
+            removed.push(name.clone());
+            removed.sort();
+        }
+        state.take_pending_install(manager_key, name);
+    }
+    machine.save_to_repo(&sync_path)?;
+    state.save()?;
+
+    Output::success(&format!(
+        "Rejected {}; {} won't be installed on this machine",
+        if selected.len() == 1 {
+            selected[0].1.clone()
+        } else {
+            format!("{} packages", selected.len())
+        },
+        if selected.len() == 1 { "it" } else { "they" }
+    ));
+    Ok(())
+}
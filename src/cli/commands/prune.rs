@@ -0,0 +1,95 @@
+use crate::cli::{Output, Progress, Prompt};
+use crate::config::{Config, MachineRole};
+use crate::sync::state::MachineState;
+use crate::sync::{GitBackend, SyncEngine, SyncState};
+use anyhow::Result;
+
+/// Delete sync repo files that no machine's config references anymore.
+pub async fn run(dry_run: bool, yes: bool) -> Result<()> {
+    let sync_path = SyncEngine::sync_path()?;
+    if !sync_path.exists() {
+        anyhow::bail!("Sync repository not found. Run 'tether init' first.");
+    }
+
+    let _sync_lock = if !dry_run {
+        Some(crate::sync::acquire_sync_lock(true)?)
+    } else {
+        None
+    };
+
+    let config = Config::load()?;
+    let state = SyncState::load()?;
+    if !dry_run && config.machine_role(&state.machine_id) == MachineRole::ReadOnly {
+        anyhow::bail!("This machine is read-only and can't modify the sync repo");
+    }
+    let git = GitBackend::open(&sync_path)?;
+    if !dry_run {
+        git.pull()?;
+    }
+
+    // Every machine that might still sync from this repo, including ones only
+    // known through profile assignments
+    let mut machine_ids: Vec<String> = MachineState::list_all(&sync_path)?
+        .into_iter()
+        .map(|m| m.machine_id)
+        .collect();
+    machine_ids.extend(config.machine_profiles.keys().cloned());
+    machine_ids.push(state.machine_id.clone());
+    machine_ids.sort();
+    machine_ids.dedup();
+
+    let mut tracked = git.list_tracked_files("profiles/")?;
+    tracked.extend(git.list_tracked_files("configs/")?);
+    let orphans = crate::sync::orphans::orphaned_repo_files(&config, &machine_ids, &tracked);
+
+    if orphans.is_empty() {
+        Output::success("No orphaned files in the sync repo");
+        return Ok(());
+    }
+
+    println!();
+    Output::section(&format!("Orphaned files ({})", orphans.len()));
+    println!();
+    for path in &orphans {
+        println!("  {}", path);
+    }
+    println!();
+
+    if dry_run {
+        return Ok(());
+    }
+
+    Output::info("These files will be deleted from the sync repo (history keeps them).");
+    if !yes && !Prompt::confirm("Delete orphaned files?", false)? {
+        Output::info("Cancelled");
+        return Ok(());
+    }
+
+    for path in &orphans {
+        let full = sync_path.join(path);
+        if full.exists() {
+            std::fs::remove_file(&full)?;
+        }
+        if let Some(parent) = full.parent() {
+            crate::sync::deletions::remove_empty_dirs(parent, &sync_path);
+        }
+    }
+
+    // Deleted chunk manifests leave their chunks unreferenced
+    if config.security.encrypt_dotfiles {
+        let key = crate::security::get_encryption_key()?;
+        crate::sync::chunks::gc_unreferenced(&sync_path, &key)?;
+    }
+
+    crate::sync::integrity::sign_repo(&config, &sync_path, &state.machine_id)?;
+    let pb = Progress::spinner("Pushing changes...");
+    git.commit(
+        &format!("Prune {} orphaned files", orphans.len()),
+        &state.machine_id,
+    )?;
+    git.push()?;
+    pb.finish_and_clear();
+
+    Output::success(&format!("Pruned {} orphaned files", orphans.len()));
+    Ok(())
+}
@@ -0,0 +1,382 @@
+use crate::cli::Output;
+use crate::config::Config;
+use crate::sync::{SyncEngine, SyncState};
+use anyhow::{Context, Result};
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::io::Write;
+use std::path::Path;
+use std::process::{Command, Stdio};
+
+/// Left in the remote home, listing what the last push wrote there so the next one
+/// can remove files the profile no longer has
+const MANIFEST_FILENAME: &str = ".tether-pushed.json";
+
+#[derive(Debug, Serialize, Deserialize)]
+struct PushManifest {
+    profile: String,
+    pushed_by: String,
+    pushed_at: DateTime<Utc>,
+    /// Home-relative paths
+    files: Vec<String>,
+}
+
+/// Home-relative path -> (content, executable)
+pub(super) type Rendered = BTreeMap<String, (Vec<u8>, bool)>;
+
+#[cfg(unix)]
+pub(super) fn is_executable(path: &Path) -> bool {
+    use std::os::unix::fs::PermissionsExt;
+    std::fs::metadata(path)
+        .map(|m| m.permissions().mode() & 0o100 != 0)
+        .unwrap_or(false)
+}
+
+#[cfg(not(unix))]
+pub(super) fn is_executable(_path: &Path) -> bool {
+    false
+}
+
+#[cfg(unix)]
+pub(super) fn make_executable(path: &Path) -> Result<()> {
+    use std::os::unix::fs::PermissionsExt;
+    std::fs::set_permissions(path, std::fs::Permissions::from_mode(0o700))?;
+    Ok(())
+}
+
+#[cfg(not(unix))]
+pub(super) fn make_executable(_path: &Path) -> Result<()> {
+    Ok(())
+}
+
+/// Read a repo file, decrypting it (and reassembling chunks) when it's encrypted
+pub(super) fn read_stored(sync_path: &Path, path: &Path, key: Option<&[u8]>) -> Result<Vec<u8>> {
+    let content = std::fs::read(path)?;
+    match key {
+        Some(key) if path.extension() == Some("enc".as_ref()) => {
+            let plaintext = crate::security::decrypt(&content, key)?;
+            crate::sync::chunks::read_from_repo(sync_path, path, plaintext, key)
+        }
+        _ => Ok(content),
+    }
+}
+
+/// The dotfiles and synced directory files `profile` resolves to, as a machine using
+/// it would have them. `key` is `None` for unencrypted repos.
+pub(super) fn render(
+    config: &Config,
+    sync_path: &Path,
+    profile: &str,
+    key: Option<&[u8]>,
+) -> Result<Rendered> {
+    let resolved = config.resolve_profile(profile)?;
+    let encrypted = key.is_some();
+    let mut files = Rendered::new();
+
+    for entry in &resolved.dotfiles {
+        let pattern = entry.path();
+        if !crate::config::is_safe_dotfile_path(pattern) {
+            Output::warning(&format!("Skipping unsafe dotfile path: {}", pattern));
+            continue;
+        }
+        let shared = entry.shared();
+        let owner = config.declaring_profile(profile, pattern);
+        let dir = sync_path
+            .join("profiles")
+            .join(if shared { "shared" } else { owner });
+        let expanded = if encrypted {
+            crate::sync::expand_from_sync_repo(pattern, &dir)
+        } else {
+            crate::sync::expand_dotfile_glob(pattern, &dir)
+        };
+        for file in expanded {
+            let repo_path =
+                crate::sync::resolve_dotfile_repo_path(sync_path, &file, encrypted, owner, shared);
+            let path = sync_path.join(&repo_path);
+            if !path.exists() {
+                log::debug!("{} is not in the sync repo", file);
+                continue;
+            }
+            let content = read_stored(sync_path, &path, key)
+                .with_context(|| format!("Failed to read {}", repo_path))?;
+            files.insert(file, (content, is_executable(&path)));
+        }
+    }
+
+    let configs_dir = sync_path.join("configs");
+    for dir in &resolved.dirs {
+        let rel = dir.path().strip_prefix("~/").unwrap_or(dir.path());
+        let root = configs_dir.join(rel.trim_end_matches('/'));
+        if !root.exists() {
+            continue;
+        }
+        for entry in walkdir::WalkDir::new(&root)
+            .follow_links(false)
+            .into_iter()
+            .filter_map(|e| e.ok())
+            .filter(|e| e.file_type().is_file())
+        {
+            let stored = entry.path().strip_prefix(&configs_dir)?.to_string_lossy();
+            let file = if encrypted {
+                match stored.strip_suffix(".enc") {
+                    Some(file) => file.to_string(),
+                    None => continue,
+                }
+            } else {
+                stored.to_string()
+            };
+            if !crate::config::is_safe_dotfile_path(&file) {
+                continue;
+            }
+            let content = read_stored(sync_path, entry.path(), key)
+                .with_context(|| format!("Failed to read configs/{}", stored))?;
+            files.insert(file, (content, is_executable(entry.path())));
+        }
+    }
+    Ok(files)
+}
+
+/// Files the previous push wrote that this one doesn't
+fn stale_files(previous: &PushManifest, current: &Rendered) -> Vec<String> {
+    previous
+        .files
+        .iter()
+        .filter(|f| !current.contains_key(*f) && crate::config::is_safe_dotfile_path(f))
+        .cloned()
+        .collect()
+}
+
+/// Quote an argument for the remote shell
+pub(super) fn shell_quote(arg: &str) -> String {
+    format!("'{}'", arg.replace('\'', r"'\''"))
+}
+
+fn ssh(host: &str, command: &str) -> Command {
+    let mut cmd = Command::new("ssh");
+    cmd.args([host, command]);
+    cmd
+}
+
+/// The manifest left by the last push to `host`, if any
+fn fetch_manifest(host: &str) -> Option<PushManifest> {
+    let output = ssh(host, &format!("cat {}", MANIFEST_FILENAME))
+        .stderr(Stdio::null())
+        .output()
+        .ok()?;
+    if !output.status.success() {
+        return None;
+    }
+    serde_json::from_slice(&output.stdout).ok()
+}
+
+/// Copy `files` (relative to `staging`) into the remote home in one transfer: rsync
+/// when it's installed, tar over ssh otherwise. Existing remote directories keep their
+/// permissions either way.
+fn transfer(host: &str, staging: &Path, files: &[&str]) -> Result<()> {
+    let list = files.join("\n");
+    if which::which("rsync").is_ok() {
+        let mut rsync = Command::new("rsync")
+            .args(["-a", "--no-implied-dirs", "--files-from=-"])
+            .arg(format!("{}/", staging.display()))
+            .arg(format!("{}:", host))
+            .stdin(Stdio::piped())
+            .spawn()
+            .context("Failed to run rsync")?;
+        rsync
+            .stdin
+            .take()
+            .expect("piped stdin")
+            .write_all(list.as_bytes())?;
+        if !rsync.wait()?.success() {
+            anyhow::bail!("rsync to {} failed", host);
+        }
+        return Ok(());
+    }
+
+    let mut tar = Command::new("tar")
+        .arg("-C")
+        .arg(staging)
+        .args(["-cf", "-", "-T", "-"])
+        .stdin(Stdio::piped())
+        .stdout(Stdio::piped())
+        .spawn()
+        .context("Failed to run tar")?;
+    tar.stdin
+        .take()
+        .expect("piped stdin")
+        .write_all(list.as_bytes())?;
+    let archive = tar.stdout.take().expect("piped stdout");
+    let extracted = ssh(host, "tar -xpf -").stdin(archive).status()?;
+    if !tar.wait()?.success() || !extracted.success() {
+        anyhow::bail!("Copying files to {} over ssh failed", host);
+    }
+    Ok(())
+}
+
+/// Render the dotfiles and synced directories of `profile` (this machine's by default)
+/// and copy them to `host` over SSH, for machines without tether. A manifest left in
+/// the remote home lets the next push remove files the profile dropped.
+pub async fn run(host: &str, profile: Option<&str>, dry_run: bool) -> Result<()> {
+    if host.is_empty() || host.starts_with('-') || host.contains(char::is_whitespace) {
+        anyhow::bail!("Invalid host: {}", host);
+    }
+    let config = Config::load()?;
+    let sync_path = SyncEngine::sync_path()?;
+    if !sync_path.exists() {
+        anyhow::bail!("Sync repository not found. Run 'tether init' first.");
+    }
+    let state = SyncState::load()?;
+    let profile = profile.unwrap_or_else(|| config.profile_name(&state.machine_id));
+
+    let key = if config.security.encrypt_dotfiles {
+        Some(crate::security::get_encryption_key()?)
+    } else {
+        None
+    };
+    let files = render(&config, &sync_path, profile, key.as_deref())?;
+    if files.is_empty() {
+        Output::info(&format!(
+            "Profile '{}' has no files in the sync repo",
+            profile
+        ));
+        return Ok(());
+    }
+
+    let stale = fetch_manifest(host)
+        .map(|previous| stale_files(&previous, &files))
+        .unwrap_or_default();
+
+    Output::section(&format!("Profile '{}' -> {}", profile, host));
+    for file in files.keys() {
+        Output::list_item(&format!("~/{}", file));
+    }
+    for file in &stale {
+        Output::list_item(&format!("~/{} (remove)", file));
+    }
+    if dry_run {
+        Output::dim("  Dry run: nothing was copied");
+        return Ok(());
+    }
+
+    let staging = tempfile::TempDir::new()?;
+    for (file, (content, executable)) in &files {
+        let path = staging.path().join(file);
+        if let Some(parent) = path.parent() {
+            std::fs::create_dir_all(parent)?;
+        }
+        crate::security::write_owner_only(&path, content)?;
+        if *executable {
+            make_executable(&path)?;
+        }
+    }
+    let manifest = PushManifest {
+        profile: profile.to_string(),
+        pushed_by: state.machine_id.clone(),
+        pushed_at: Utc::now(),
+        files: files.keys().cloned().collect(),
+    };
+    std::fs::write(
+        staging.path().join(MANIFEST_FILENAME),
+        serde_json::to_string_pretty(&manifest)?,
+    )?;
+
+    let mut paths: Vec<&str> = files.keys().map(String::as_str).collect();
+    paths.push(MANIFEST_FILENAME);
+    transfer(host, staging.path(), &paths)?;
+
+    if !stale.is_empty() {
+        let quoted: Vec<String> = stale.iter().map(|f| shell_quote(f)).collect();
+        let status = ssh(host, &format!("rm -f -- {}", quoted.join(" "))).status()?;
+        if !status.success() {
+            Output::warning("Failed to remove files the profile no longer has");
+        }
+    }
+
+    Output::success(&format!("Pushed {} file(s) to {}", files.len(), host));
+    if !stale.is_empty() {
+        Output::info(&format!(
+            "Removed {} file(s) no longer in the profile",
+            stale.len()
+        ));
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::config::{DotfileEntry, ProfileConfig, ProfileDotfileEntry};
+    use tempfile::TempDir;
+
+    #[test]
+    fn test_render_profile() {
+        let temp = TempDir::new().unwrap();
+        let root = temp.path();
+        let key = crate::security::generate_key();
+        let store = |rel: &str, content: &[u8]| {
+            let path = root.join(rel);
+            std::fs::create_dir_all(path.parent().unwrap()).unwrap();
+            std::fs::write(&path, crate::security::encrypt(content, &key).unwrap()).unwrap();
+        };
+        store("profiles/base/zshrc.enc", b"alias g=git");
+        store("profiles/shared/gitconfig.enc", b"[user]");
+        store("profiles/server/tmux.conf.enc", b"set -g mouse on");
+        store("configs/.config/htop/htoprc.enc", b"fields=0");
+        store("profiles/other/vimrc.enc", b"set nu");
+
+        let mut config = Config::default();
+        config.profiles.insert(
+            "base".to_string(),
+            ProfileConfig {
+                dotfiles: vec![
+                    ProfileDotfileEntry::Simple(".zshrc".to_string()),
+                    ProfileDotfileEntry::WithOptions {
+                        path: ".gitconfig".to_string(),
+                        shared: true,
+                        create_if_missing: false,
+                    },
+                ],
+                ..Default::default()
+            },
+        );
+        config.profiles.insert(
+            "server".to_string(),
+            ProfileConfig {
+                dotfiles: vec![ProfileDotfileEntry::Simple(".tmux.conf".to_string())],
+                dirs: vec![DotfileEntry::from("~/.config/htop")],
+                extends: Some("base".to_string()),
+                ..Default::default()
+            },
+        );
+
+        let files = render(&config, root, "server", Some(&key)).unwrap();
+        let paths: Vec<&str> = files.keys().map(String::as_str).collect();
+        assert_eq!(
+            paths,
+            vec![".config/htop/htoprc", ".gitconfig", ".tmux.conf", ".zshrc"]
+        );
+        assert_eq!(files[".zshrc"].0, b"alias g=git");
+        assert!(render(&config, root, "missing", Some(&key)).is_err());
+    }
+
+    #[test]
+    fn test_stale_files_and_quoting() {
+        let previous = PushManifest {
+            profile: "server".to_string(),
+            pushed_by: "laptop".to_string(),
+            pushed_at: Utc::now(),
+            files: vec![
+                ".zshrc".to_string(),
+                ".old rc".to_string(),
+                "../escape".to_string(),
+            ],
+        };
+        let mut current = Rendered::new();
+        current.insert(".zshrc".to_string(), (Vec::new(), false));
+        assert_eq!(stale_files(&previous, &current), vec![".old rc"]);
+
+        assert_eq!(shell_quote(".old rc"), "'.old rc'");
+        assert_eq!(shell_quote("it's"), r"'it'\''s'");
+    }
+}
@@ -0,0 +1,185 @@
+use crate::cli::{Output, Prompt};
+use crate::config::{Config, MachineRole};
+use crate::sync::growth::format_size;
+use crate::sync::{GitBackend, SyncEngine, SyncState};
+use anyhow::Result;
+use chrono::{Duration, Utc};
+
+/// Parse a retention period like `90d`, `12w`, `6m` or `1y`
+fn parse_keep(keep: &str) -> Result<Duration> {
+    let keep = keep.trim();
+    let split = keep
+        .find(|c: char| !c.is_ascii_digit())
+        .unwrap_or(keep.len());
+    let (num, unit) = keep.split_at(split);
+    let n: i64 = num
+        .parse()
+        .map_err(|_| anyhow::anyhow!("Invalid retention period: '{}' (e.g. 90d)", keep))?;
+    let days = match unit {
+        "d" | "" => n,
+        "w" => n * 7,
+        "m" => n * 30,
+        "y" => n * 365,
+        _ => anyhow::bail!("Unknown unit in '{}': use d, w, m or y", keep),
+    };
+    if days <= 0 {
+        anyhow::bail!("Retention period must be at least one day");
+    }
+    Ok(Duration::days(days))
+}
+
+/// Squash sync repo history older than `keep` and force-push the result.
+pub async fn prune(keep: &str, yes: bool) -> Result<()> {
+    let cutoff = Utc::now() - parse_keep(keep)?;
+    let sync_path = SyncEngine::sync_path()?;
+    if !sync_path.exists() {
+        anyhow::bail!("Sync repository not found. Run 'tether init' first.");
+    }
+
+    let mut state = SyncState::load()?;
+    if Config::load()?.machine_role(&state.machine_id) == MachineRole::ReadOnly {
+        anyhow::bail!("This machine is read-only and can't rewrite sync repo history");
+    }
+
+    let _sync_lock = crate::sync::acquire_sync_lock(true)?;
+    let git = GitBackend::open(&sync_path)?;
+    if git.has_changes()? {
+        anyhow::bail!("Sync repo has uncommitted changes. Run 'tether sync' first.");
+    }
+
+    // Start from the latest remote history so nothing pushed elsewhere is dropped
+    git.pull()?;
+
+    Output::warning(&format!(
+        "This rewrites sync repo history: commits before {} are squashed into one \
+         and file history older than that is permanently removed.",
+        cutoff.format("%Y-%m-%d")
+    ));
+    Output::info("Other machines adopt the new history automatically on their next sync.");
+    if !yes && !Prompt::confirm("Prune history and force-push?", false)? {
+        Output::info("Cancelled");
+        return Ok(());
+    }
+
+    let Some(summary) = git.prune_history(cutoff, &state.machine_id)? else {
+        Output::info(&format!(
+            "Nothing to prune before {}",
+            cutoff.format("%Y-%m-%d")
+        ));
+        return Ok(());
+    };
+
+    git.force_push()?;
+    crate::sync::growth::remeasure(&mut state, &git, Utc::now());
+    state.save()?;
+
+    Output::success(&format!(
+        "Squashed {} commits, kept {} ({} → {})",
+        summary.squashed,
+        summary.kept,
+        format_size(summary.size_before),
+        format_size(summary.size_after)
+    ));
+    Ok(())
+}
+
+/// Pack the sync repo's loose objects. Only this machine's copy changes, so nothing
+/// is pushed.
+pub async fn compact() -> Result<()> {
+    let sync_path = SyncEngine::sync_path()?;
+    if !sync_path.exists() {
+        anyhow::bail!("Sync repository not found. Run 'tether init' first.");
+    }
+
+    let _sync_lock = crate::sync::acquire_sync_lock(true)?;
+    let git = GitBackend::open(&sync_path)?;
+    let before = git.object_store_size()?;
+    let pb = crate::cli::Progress::spinner("Packing sync repo...");
+    let result = git.compact();
+    pb.finish_and_clear();
+    result?;
+
+    let mut state = SyncState::load()?;
+    crate::sync::growth::remeasure(&mut state, &git, Utc::now());
+    state.save()?;
+    Output::success(&format!(
+        "Compacted sync repo ({} → {})",
+        format_size(before),
+        format_size(git.object_store_size()?)
+    ));
+    Ok(())
+}
+
+/// Check every file in the sync repo for corruption before it reaches other
+/// machines. Returns whether the repo is clean.
+pub async fn verify() -> Result<bool> {
+    let config = Config::load()?;
+    let sync_path = SyncEngine::sync_path()?;
+    let git = GitBackend::open(&sync_path)?;
+    if let Err(e) = git.pull() {
+        Output::warning(&format!("Could not pull latest changes: {}", e));
+    }
+    let key = if config.security.encrypt_dotfiles {
+        Some(crate::security::get_encryption_key()?)
+    } else {
+        None
+    };
+
+    let pb = crate::cli::Progress::spinner("Checking sync repo...");
+    let mut report = crate::sync::repo_check::check_files(&sync_path, key.as_deref())?;
+    let state = SyncState::load()?;
+    report.changed =
+        crate::sync::repo_check::changed_since_sync(&config, &state, &sync_path, key.as_deref());
+    pb.finish_and_clear();
+
+    println!();
+    Output::section("Sync repo");
+    for (file, problem) in &report.corrupt {
+        Output::list_item(&format!("{}: {}", file, problem));
+    }
+    if !report.changed.is_empty() {
+        Output::info(&format!(
+            "{} file{} changed in the repo since this machine's last sync (run tether sync):",
+            report.changed.len(),
+            if report.changed.len() == 1 { "" } else { "s" }
+        ));
+        for file in &report.changed {
+            Output::dim(&format!("  {}", file));
+        }
+    }
+
+    if report.is_clean() {
+        Output::success(&format!("Checked {} files, no corruption", report.checked));
+    } else {
+        println!();
+        Output::error(&format!(
+            "{} problem{} in {} files checked",
+            report.corrupt.len(),
+            if report.corrupt.len() == 1 { "" } else { "s" },
+            report.checked
+        ));
+    }
+    Ok(report.is_clean())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_keep_units() {
+        assert_eq!(parse_keep("90d").unwrap(), Duration::days(90));
+        assert_eq!(parse_keep("2w").unwrap(), Duration::days(14));
+        assert_eq!(parse_keep("6m").unwrap(), Duration::days(180));
+        assert_eq!(parse_keep("1y").unwrap(), Duration::days(365));
+        assert_eq!(parse_keep("30").unwrap(), Duration::days(30));
+    }
+
+    #[test]
+    fn test_parse_keep_rejects_invalid() {
+        assert!(parse_keep("").is_err());
+        assert!(parse_keep("0d").is_err());
+        assert!(parse_keep("90h").is_err());
+        assert!(parse_keep("d").is_err());
+    }
+}
@@ -1,10 +1,14 @@
+use crate::cli::output::relative_time;
 use crate::cli::Output;
 use crate::config::Config;
-use crate::sync::{ConflictResolution, ConflictState, FileConflict, SyncEngine};
+use crate::sync::{
+    restore_hint, snapshot_before_resolution, ConflictResolution, ConflictState, ResolvedSide,
+    SyncEngine,
+};
 use anyhow::Result;
 use owo_colors::OwoColorize;
 
-pub async fn run(file: Option<&str>) -> Result<()> {
+pub async fn run(file: Option<&str>, reveal: bool) -> Result<()> {
     let config = Config::load()?;
 
     if !config.has_personal_features() {
@@ -16,6 +20,7 @@ pub async fn run(file: Option<&str>) -> Result<()> {
 
     if conflict_state.conflicts.is_empty() {
         Output::success("No conflicts to resolve");
+        show_auto_resolved(&conflict_state);
         return Ok(());
     }
 
@@ -23,7 +28,6 @@ pub async fn run(file: Option<&str>) -> Result<()> {
     let sync_path = SyncEngine::sync_path()?;
     let state = crate::sync::SyncState::load()?;
     let machine_id = &state.machine_id;
-    let profile = config.profile_name(machine_id);
 
     // Get encryption key if needed
     let key = if config.security.encrypt_dotfiles {
@@ -62,46 +66,11 @@ pub async fn run(file: Option<&str>) -> Result<()> {
     println!();
 
     for pending in &conflicts_to_resolve {
-        // Load local and remote content
         let local_path = home.join(&pending.file_path);
-        let local_content = if local_path.exists() {
-            std::fs::read(&local_path)?
-        } else {
-            Vec::new()
-        };
-
-        // Get remote content
-        let shared = config.is_dotfile_shared(machine_id, &pending.file_path);
-        let repo_rel = crate::sync::resolve_dotfile_repo_path(
-            &sync_path,
-            &pending.file_path,
-            config.security.encrypt_dotfiles,
-            profile,
-            shared,
-        );
-        let remote_file = sync_path.join(&repo_rel);
-        let remote_content = if remote_file.exists() {
-            let raw = std::fs::read(&remote_file)?;
-            if config.security.encrypt_dotfiles {
-                crate::security::decrypt(&raw, key.as_ref().unwrap())?
-            } else {
-                raw
-            }
-        } else {
-            Vec::new()
-        };
-
-        let conflict = FileConflict {
-            file_path: pending.file_path.clone(),
-            local_hash: crate::sha256_hex(&local_content),
-            last_synced_hash: None,
-            remote_hash: crate::sha256_hex(&remote_content),
-            local_content,
-            remote_content,
-        };
+        let conflict = pending.load(&config, key.as_deref(), &home, &sync_path, machine_id)?;
 
         // Show diff and prompt for resolution
-        conflict.show_diff()?;
+        conflict.show_diff(reveal)?;
         let resolution = conflict.prompt_resolution()?;
 
         match resolution {
@@ -110,12 +79,17 @@ pub async fn run(file: Option<&str>) -> Result<()> {
                 conflict_state.remove_conflict(&pending.file_path);
             }
             ConflictResolution::UseRemote => {
+                let snapshot = snapshot_before_resolution(&pending.file_path, &local_path)?;
                 std::fs::write(&local_path, &conflict.remote_content)?;
                 Output::success(&format!("  {} (applied remote)", pending.file_path));
+                show_undo(snapshot.as_deref(), &pending.file_path);
                 conflict_state.remove_conflict(&pending.file_path);
             }
             ConflictResolution::Merged => {
-                conflict.launch_merge_tool(&config.merge, &home)?;
+                let snapshot = snapshot_before_resolution(&pending.file_path, &local_path)?;
+                if conflict.launch_merge_tool(&config.merge, &home)? == ConflictResolution::Merged {
+                    show_undo(snapshot.as_deref(), &pending.file_path);
+                }
                 conflict_state.remove_conflict(&pending.file_path);
             }
             ConflictResolution::Skip => {
@@ -137,3 +111,96 @@ pub async fn run(file: Option<&str>) -> Result<()> {
 
     Ok(())
 }
+
+fn show_undo(snapshot: Option<&str>, file: &str) {
+    if let Some(backup) = snapshot {
+        Output::dim(&format!("    undo: {}", restore_hint(backup, file)));
+    }
+}
+
+/// Show pending conflicts with their diffs, without resolving anything
+pub async fn list(reveal: bool) -> Result<()> {
+    let config = Config::load()?;
+    if !config.has_personal_features() {
+        Output::warning("Conflicts not available without personal features");
+        return Ok(());
+    }
+
+    let conflict_state = ConflictState::load()?;
+    if conflict_state.conflicts.is_empty() {
+        Output::success("No pending conflicts");
+        show_auto_resolved(&conflict_state);
+        return Ok(());
+    }
+
+    let home = crate::home_dir()?;
+    let sync_path = SyncEngine::sync_path()?;
+    let state = crate::sync::SyncState::load()?;
+    let key = if config.security.encrypt_dotfiles {
+        Some(crate::security::get_encryption_key()?)
+    } else {
+        None
+    };
+
+    Output::section(&format!(
+        "{} pending conflict(s)",
+        conflict_state.conflicts.len()
+    ));
+    for pending in &conflict_state.conflicts {
+        Output::list_item(&format!(
+            "{}  detected {}",
+            pending.file_path,
+            relative_time(pending.detected_at)
+        ));
+    }
+
+    for pending in &conflict_state.conflicts {
+        let conflict = pending.load(
+            &config,
+            key.as_deref(),
+            &home,
+            &sync_path,
+            &state.machine_id,
+        )?;
+        conflict.show_diff(reveal)?;
+    }
+
+    println!();
+    Output::info("Resolve with: tether conflicts resolve [file]");
+    Ok(())
+}
+
+/// Recent conflicts settled by the last-write-wins strategy, with how to undo each
+fn show_auto_resolved(conflict_state: &ConflictState) {
+    let recent: Vec<_> = conflict_state.auto_resolved.iter().rev().take(5).collect();
+    if recent.is_empty() {
+        return;
+    }
+
+    println!();
+    Output::section("Recently auto-resolved (last write wins)");
+    for resolution in recent {
+        let kept = match resolution.kept {
+            ResolvedSide::Local => "local",
+            ResolvedSide::Remote => "remote",
+        };
+        Output::list_item(&format!(
+            "{}  kept {} copy {}",
+            resolution.file_path,
+            kept,
+            relative_time(resolution.resolved_at)
+        ));
+        if let Some(backup) = &resolution.backup {
+            Output::dim(&format!(
+                "    undo: {}",
+                restore_hint(backup, &resolution.file_path)
+            ));
+        } else if let Some(commit) = &resolution.commit {
+            Output::dim(&format!(
+                "    undo: tether restore git {} --commit {}",
+                resolution.file_path,
+                &commit[..7.min(commit.len())]
+            ));
+        }
+    }
+}
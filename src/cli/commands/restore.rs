@@ -2,7 +2,7 @@ use crate::cli::output::relative_time;
 use crate::cli::{Output, Prompt};
 use crate::config::Config;
 use crate::sync::{
-    list_backup_files, list_backups, restore_file, GitBackend, SyncEngine, SyncState,
+    list_backup_files, list_backups, restore_file, restore_hint, GitBackend, SyncEngine, SyncState,
 };
 use anyhow::Result;
 
@@ -70,10 +70,30 @@ pub async fn run(timestamp: Option<&str>, file: Option<&str>) -> Result<()> {
         return Ok(());
     }
 
+    // Keep what's being replaced so a wrong pick can be undone
+    let mut previous = None;
+    if category == "dotfiles" {
+        let dest = crate::home_dir()?.join(&rel_path);
+        if dest.exists() {
+            let backup_dir = crate::sync::create_backup_dir()?;
+            crate::sync::backup_file(&backup_dir, "dotfiles", &rel_path, &dest)?;
+            previous = backup_dir
+                .file_name()
+                .map(|n| n.to_string_lossy().into_owned());
+        }
+    }
+
     // Do the restore
     match restore_file(&selected_timestamp, &category, &rel_path) {
         Ok(dest) => {
+            crate::security::audit::record(
+                crate::security::audit::AuditEvent::Restore,
+                &format!("{} from backup {}", rel_path, selected_timestamp),
+            );
             Output::success(&format!("Restored to {}", dest.display()));
+            if let Some(backup) = previous {
+                Output::dim(&format!("  undo: {}", restore_hint(&backup, &rel_path)));
+            }
         }
         Err(e) => {
             Output::error(&format!("Failed to restore: {}", e));
@@ -90,15 +110,26 @@ pub async fn git_restore(file: &str, commit: Option<&str>) -> Result<()> {
 
     let config = Config::load()?;
     let sync_path = SyncEngine::sync_path()?;
-    let git = GitBackend::open(&sync_path)?;
-    let home = crate::home_dir()?;
     let state = SyncState::load()?;
 
     let encrypted = config.security.encrypt_dotfiles;
-    let profile = config.profile_name(&state.machine_id);
+    let profile = config.dotfile_profile(&state.machine_id, file);
     let shared = config.is_dotfile_shared(&state.machine_id, file);
     let repo_path =
         crate::sync::resolve_dotfile_repo_path(&sync_path, file, encrypted, profile, shared);
+    restore_from_history(&repo_path, file, commit)
+}
+
+/// Write a past version of the repo file `repo_path` to `~/<file>`, backing up the
+/// current copy. Picks the commit interactively when `commit` is `None`.
+pub fn restore_from_history(repo_path: &str, file: &str, commit: Option<&str>) -> Result<()> {
+    if !crate::config::is_safe_dotfile_path(file) {
+        anyhow::bail!("Unsafe file path: {}", file);
+    }
+    let sync_path = SyncEngine::sync_path()?;
+    let git = GitBackend::open(&sync_path)?;
+    let home = crate::home_dir()?;
+    let repo_path = repo_path.to_string();
 
     // Get or pick commit
     let selected_commit = match commit {
@@ -132,18 +163,26 @@ pub async fn git_restore(file: &str, commit: Option<&str>) -> Result<()> {
     let content = git.show_at_commit(&selected_commit, &repo_path)?;
 
     // Decrypt if needed
-    let plaintext = if config.security.encrypt_dotfiles {
+    let plaintext = if repo_path.ends_with(".enc") {
         let key = crate::security::get_encryption_key()?;
-        crate::security::decrypt(&content, &key)?
+        let plaintext = crate::security::decrypt(&content, &key)?;
+        // Chunks as of the same commit, since later syncs may have dropped them
+        crate::sync::chunks::reassemble(&repo_path, plaintext, &key, |rel| {
+            git.show_at_commit(&selected_commit, rel)
+        })?
     } else {
         content
     };
 
     // Backup current file
     let dest = home.join(file);
+    let mut previous = None;
     if dest.exists() {
         let backup_dir = crate::sync::create_backup_dir()?;
         crate::sync::backup_file(&backup_dir, "dotfiles", file, &dest)?;
+        previous = backup_dir
+            .file_name()
+            .map(|n| n.to_string_lossy().into_owned());
     }
 
     // Write restored content
@@ -155,11 +194,22 @@ pub async fn git_restore(file: &str, commit: Option<&str>) -> Result<()> {
     // Don't update state hash — leaving it unchanged makes the next sync see
     // "local changed, remote unchanged" and push restored content to repo.
 
+    crate::security::audit::record(
+        crate::security::audit::AuditEvent::Restore,
+        &format!(
+            "{} from commit {}",
+            file,
+            &selected_commit[..7.min(selected_commit.len())]
+        ),
+    );
     Output::success(&format!(
         "Restored {} from commit {}",
         file,
         &selected_commit[..7.min(selected_commit.len())]
     ));
+    if let Some(backup) = previous {
+        Output::dim(&format!("  undo: {}", restore_hint(&backup, file)));
+    }
     Ok(())
 }
 
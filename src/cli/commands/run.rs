@@ -0,0 +1,313 @@
+use crate::cli::Output;
+use crate::config::Config;
+use crate::sync::git::{get_remote_url, normalize_remote_url};
+use crate::sync::{personal_secrets, SyncEngine};
+use anyhow::Result;
+use std::collections::BTreeMap;
+use std::path::Path;
+
+/// Variables to inject, name -> (value, where it came from). Later sources override
+/// earlier ones.
+type Vars = BTreeMap<String, (String, String)>;
+
+fn is_env_name(name: &str) -> bool {
+    let mut chars = name.chars();
+    chars
+        .next()
+        .is_some_and(|c| c.is_ascii_alphabetic() || c == '_')
+        && chars.all(|c| c.is_ascii_alphanumeric() || c == '_')
+}
+
+/// Whether a project file holds environment variables (`.env`, `.env.local`, `prod.env`)
+fn is_env_file(file: &str) -> bool {
+    let name = file.rsplit('/').next().unwrap_or(file);
+    name.starts_with(".env") || name.ends_with(".env")
+}
+
+/// Parse `KEY=value` lines of a `.env` file. Handles comments, `export`, and single
+/// or double quotes (with `\n` escapes in double quotes).
+fn parse_dotenv(content: &str) -> Vec<(String, String)> {
+    let mut vars = Vec::new();
+    for line in content.lines() {
+        let line = line.trim();
+        if line.is_empty() || line.starts_with('#') {
+            continue;
+        }
+        let line = line.strip_prefix("export ").unwrap_or(line);
+        let Some((key, value)) = line.split_once('=') else {
+            continue;
+        };
+        let key = key.trim();
+        if !is_env_name(key) {
+            continue;
+        }
+        let value = value.trim();
+        let value = if let Some(inner) = value
+            .strip_prefix('"')
+            .and_then(|v| v.rsplit_once('"'))
+            .map(|(v, _)| v)
+        {
+            inner.replace("\\n", "\n").replace("\\\"", "\"")
+        } else if let Some(inner) = value
+            .strip_prefix('\'')
+            .and_then(|v| v.rsplit_once('\''))
+            .map(|(v, _)| v)
+        {
+            inner.to_string()
+        } else {
+            // Unquoted values end at an inline comment
+            value
+                .split_once(" #")
+                .map_or(value, |(v, _)| v)
+                .trim_end()
+                .to_string()
+        };
+        vars.push((key.to_string(), value));
+    }
+    vars
+}
+
+/// Decrypt the env files of one project directory (`projects/<url>/` in some repo)
+fn add_project_files(
+    vars: &mut Vars,
+    dir: &Path,
+    suffix: &str,
+    source: &str,
+    decrypt: &dyn Fn(&[u8]) -> Result<Vec<u8>>,
+) {
+    if !dir.exists() {
+        return;
+    }
+    for entry in walkdir::WalkDir::new(dir)
+        .follow_links(false)
+        .sort_by_file_name()
+        .into_iter()
+        .filter_map(|e| e.ok())
+        .filter(|e| e.file_type().is_file())
+    {
+        let rel = entry.path().strip_prefix(dir).unwrap_or(entry.path());
+        let rel = rel.to_string_lossy();
+        let Some(file) = rel.strip_suffix(suffix) else {
+            continue;
+        };
+        if !is_env_file(file) {
+            continue;
+        }
+        let content = std::fs::read(entry.path())
+            .map_err(anyhow::Error::from)
+            .and_then(|c| decrypt(&c));
+        match content {
+            Ok(content) => {
+                let label = format!("{} {}", source, file);
+                for (key, value) in parse_dotenv(&String::from_utf8_lossy(&content)) {
+                    vars.insert(key, (value, label.clone()));
+                }
+            }
+            Err(e) => eprintln!("Warning: Failed to decrypt {} {}: {}", source, file, e),
+        }
+    }
+}
+
+/// Collect the secrets that apply in `project_dir`, lowest precedence first: active
+/// teams' shared secrets, the personal `tether secrets` store, then the project's env
+/// files from its team, its collabs and the personal sync repo.
+fn collect(config: &Config, project_dir: &Path) -> Result<Vars> {
+    let mut vars = Vars::new();
+    let project_url = get_remote_url(project_dir)
+        .ok()
+        .map(|url| normalize_remote_url(&url));
+    let identity = crate::security::load_identity(None).ok();
+    let age_decrypt = |data: &[u8]| -> Result<Vec<u8>> {
+        match &identity {
+            Some(id) => crate::security::decrypt_with_identity(data, id),
+            None => anyhow::bail!("identity not unlocked (run 'tether identity unlock')"),
+        }
+    };
+
+    if let Some(teams) = &config.teams {
+        for team in &teams.active {
+            let Ok(repo_dir) = Config::team_repo_dir(team) else {
+                continue;
+            };
+            let secrets_dir = repo_dir.join("secrets");
+            let Ok(entries) = std::fs::read_dir(&secrets_dir) else {
+                continue;
+            };
+            let mut paths: Vec<_> = entries.filter_map(|e| e.ok()).map(|e| e.path()).collect();
+            paths.sort();
+            for path in paths {
+                let Some(name) = path
+                    .file_name()
+                    .and_then(|n| n.to_str())
+                    .and_then(|n| n.strip_suffix(".age"))
+                else {
+                    continue;
+                };
+                if !is_env_name(name) {
+                    continue;
+                }
+                match std::fs::read(&path)
+                    .map_err(anyhow::Error::from)
+                    .and_then(|c| age_decrypt(&c))
+                {
+                    Ok(value) => {
+                        let value = String::from_utf8_lossy(&value).into_owned();
+                        vars.insert(name.to_string(), (value, format!("team {}", team)));
+                    }
+                    Err(e) => eprintln!("Warning: Skipping team secret {}: {}", name, e),
+                }
+            }
+        }
+    }
+
+    let sync_path = SyncEngine::sync_path()?;
+    let key = if config.has_personal_features() {
+        match crate::security::get_encryption_key() {
+            Ok(key) => Some(key),
+            Err(e) => {
+                eprintln!("Warning: Skipping personal secrets: {}", e);
+                None
+            }
+        }
+    } else {
+        None
+    };
+    if let Some(key) = &key {
+        for name in personal_secrets::list(&sync_path)? {
+            if !is_env_name(&name) {
+                continue;
+            }
+            match personal_secrets::get(&sync_path, key, &name) {
+                Ok(Some(value)) => {
+                    let value = String::from_utf8_lossy(&value).into_owned();
+                    vars.insert(name, (value, "tether secrets".to_string()));
+                }
+                Ok(None) => {}
+                Err(e) => eprintln!("Warning: Skipping secret {}: {}", name, e),
+            }
+        }
+    }
+
+    let Some(url) = project_url else {
+        return Ok(vars);
+    };
+    if let Some(teams) = &config.teams {
+        let team = crate::sync::team::find_team_for_project(&url, &teams.teams)
+            .filter(|t| teams.active.contains(t));
+        if let Some((team, dir)) =
+            team.and_then(|t| Config::team_repo_dir(&t).ok().map(|dir| (t, dir)))
+        {
+            add_project_files(
+                &mut vars,
+                &dir.join("projects").join(&url),
+                ".age",
+                &format!("team {}", team),
+                &age_decrypt,
+            );
+        }
+        for (name, collab) in &teams.collabs {
+            if !collab.enabled || !collab.projects.contains(&url) {
+                continue;
+            }
+            if let Ok(dir) = Config::collab_repo_dir(name) {
+                add_project_files(
+                    &mut vars,
+                    &dir.join("projects").join(&url),
+                    ".age",
+                    &format!("collab {}", name),
+                    &age_decrypt,
+                );
+            }
+        }
+    }
+    if let Some(key) = &key {
+        add_project_files(
+            &mut vars,
+            &sync_path.join("projects").join(&url),
+            ".enc",
+            "project",
+            &|data: &[u8]| crate::security::decrypt(data, key),
+        );
+    }
+    Ok(vars)
+}
+
+/// Run `command` with the current project's secrets in its environment, so `.env`
+/// files never have to exist on disk. Exits with the command's exit code.
+pub async fn run(command: &[String], list: bool) -> Result<()> {
+    let config = Config::load()?;
+    let vars = collect(&config, &std::env::current_dir()?)?;
+
+    if list {
+        if vars.is_empty() {
+            Output::info("No secrets apply here");
+            return Ok(());
+        }
+        Output::section(&format!("{} variable(s)", vars.len()));
+        for (name, (_, source)) in &vars {
+            Output::list_item(&format!("{:<30} {}", name, source));
+        }
+        return Ok(());
+    }
+
+    let Some((program, args)) = command.split_first() else {
+        anyhow::bail!("No command given (usage: tether run -- <command> [args...])");
+    };
+    if vars.is_empty() {
+        eprintln!(
+            "Warning: No secrets apply here; running {} without any",
+            program
+        );
+    }
+    let status = std::process::Command::new(program)
+        .args(args)
+        .envs(vars.iter().map(|(name, (value, _))| (name, value)))
+        .status()
+        .map_err(|e| anyhow::anyhow!("Failed to run {}: {}", program, e))?;
+    std::process::exit(status.code().unwrap_or(1));
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_dotenv() {
+        let vars = parse_dotenv(
+            "# comment\n\
+             API_KEY=abc123\n\
+             export DB_URL=\"postgres://x\"\n\
+             SINGLE='a #b'\n\
+             INLINE=value # note\n\
+             MULTI=\"line1\\nline2\"\n\
+             bad-name=1\n\
+             NOEQUALS\n\
+             EMPTY=\n",
+        );
+        assert_eq!(
+            vars,
+            vec![
+                ("API_KEY".to_string(), "abc123".to_string()),
+                ("DB_URL".to_string(), "postgres://x".to_string()),
+                ("SINGLE".to_string(), "a #b".to_string()),
+                ("INLINE".to_string(), "value".to_string()),
+                ("MULTI".to_string(), "line1\nline2".to_string()),
+                ("EMPTY".to_string(), String::new()),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_env_names_and_files() {
+        assert!(is_env_name("GITHUB_TOKEN"));
+        assert!(is_env_name("_x1"));
+        assert!(!is_env_name("1X"));
+        assert!(!is_env_name("npm-token"));
+        assert!(!is_env_name("work.key"));
+
+        assert!(is_env_file(".env"));
+        assert!(is_env_file("apps/web/.env.local"));
+        assert!(is_env_file("config/prod.env"));
+        assert!(!is_env_file("config/settings.json"));
+    }
+}
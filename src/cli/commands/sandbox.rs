@@ -0,0 +1,296 @@
+use crate::cli::Output;
+use crate::config::Config;
+use crate::sync::SyncState;
+use anyhow::{Context, Result};
+use std::path::Path;
+use std::process::Command;
+
+/// Set in the sandbox shell to the sandbox directory
+pub const SANDBOX_ENV: &str = "TETHER_SANDBOX";
+
+/// Package managers that get a stub in the sandbox
+const MANAGERS: &[&str] = &["brew", "npm", "pnpm", "bun", "gem", "uv"];
+
+/// Open a shell in a throwaway HOME with its own local sync repo, set up from this
+/// machine's config and dotfiles without secrets, to try config changes safely.
+/// Package managers can list packages there but not install or remove any.
+pub async fn run(keep: bool) -> Result<()> {
+    if std::env::var_os(SANDBOX_ENV).is_some() {
+        anyhow::bail!("Already in a tether sandbox");
+    }
+    if cfg!(windows) {
+        anyhow::bail!("tether sandbox isn't supported on Windows yet");
+    }
+
+    let config = Config::load()?;
+    let machine_id = SyncState::load()?.machine_id;
+    let real_home = crate::home_dir()?;
+
+    let dir = tempfile::Builder::new()
+        .prefix("tether-sandbox-")
+        .tempdir()?;
+    let home = dir.path().join("home");
+    let remote = dir.path().join("remote.git");
+    let bin = dir.path().join("bin");
+    let tether_dir = home.join(".tether");
+    std::fs::create_dir_all(&tether_dir)?;
+    std::fs::create_dir_all(&bin)?;
+
+    git(
+        dir.path(),
+        &[
+            "init",
+            "--bare",
+            "--quiet",
+            "--initial-branch=main",
+            "remote.git",
+        ],
+    )?;
+    git(
+        dir.path(),
+        &[
+            "clone",
+            "--quiet",
+            &remote.to_string_lossy(),
+            &tether_dir.join("sync").to_string_lossy(),
+        ],
+    )?;
+
+    let sandbox = sandbox_config(&config, &remote.to_string_lossy());
+    std::fs::write(
+        tether_dir.join("config.toml"),
+        toml::to_string_pretty(&sandbox)?,
+    )?;
+    // Same machine ID, so profile assignments apply
+    std::fs::write(
+        tether_dir.join("state.json"),
+        serde_json::to_string_pretty(&serde_json::json!({
+            "machine_id": machine_id,
+            "last_sync": chrono::Utc::now(),
+            "files": {},
+            "packages": {},
+        }))?,
+    )?;
+
+    let skipped = copy_dotfiles(&config, &machine_id, &real_home, &home)?;
+    write_package_stubs(&bin)?;
+
+    Output::success(&format!("Sandbox created in {}", dir.path().display()));
+    for file in &skipped {
+        Output::warning(&format!(
+            "Left out {} (it looks like it holds a secret)",
+            file
+        ));
+    }
+    if !config.secrets.files.is_empty() || config.teams.is_some() {
+        Output::dim("  Secret provider files and team repos are left out");
+    }
+
+    // The first sync fills the sandbox repo, as `tether init` would
+    let exe = std::env::current_exe()?;
+    let status = sandbox_command(&exe, dir.path(), &home, &bin)
+        .arg("sync")
+        .status()?;
+    if !status.success() {
+        Output::warning("The first sandbox sync failed; the shell is open to look into it");
+    }
+
+    println!();
+    Output::info("In this shell HOME is the sandbox: edit ~/.tether/config.toml and run tether");
+    Output::dim("  Package installs and removals are only written to $TETHER_SANDBOX/packages.log");
+    Output::dim("  Exit the shell to leave the sandbox");
+    let shell = std::env::var("SHELL").unwrap_or_else(|_| "/bin/sh".to_string());
+    sandbox_command(Path::new(&shell), dir.path(), &home, &bin)
+        .current_dir(&home)
+        .status()
+        .with_context(|| format!("Failed to start {}", shell))?;
+
+    if keep {
+        let path = dir.keep();
+        Output::info(&format!("Sandbox kept in {}", path.display()));
+    } else {
+        dir.close()?;
+        Output::success("Sandbox deleted");
+    }
+    Ok(())
+}
+
+/// `config` pointed at the sandbox repo, with everything that reaches outside it
+/// removed: encryption (its key lives in the real keychain), secret providers, team
+/// and collab repos, and report delivery
+fn sandbox_config(config: &Config, remote: &str) -> Config {
+    let mut sandbox = config.clone();
+    sandbox.backend.url = remote.to_string();
+    sandbox.security.encrypt_dotfiles = false;
+    sandbox.security.encrypt_manifests = false;
+    sandbox.secrets = Default::default();
+    sandbox.team = None;
+    sandbox.teams = None;
+    sandbox.features.team_dotfiles = false;
+    sandbox.features.collab_secrets = false;
+    sandbox.report.enabled = false;
+    sandbox.report.webhook = None;
+    sandbox.report.file = None;
+    sandbox
+}
+
+/// Copy the dotfiles and dirs this machine syncs into the sandbox home, leaving out
+/// any file the secret scanner flags. Returns the files left out.
+fn copy_dotfiles(
+    config: &Config,
+    machine_id: &str,
+    real_home: &Path,
+    home: &Path,
+) -> Result<Vec<String>> {
+    let scanner = crate::security::secrets::SecretScanner::default();
+    let mut skipped = Vec::new();
+    let mut copy = |rel: &str| -> Result<()> {
+        let from = real_home.join(rel);
+        if let Ok(content) = std::fs::read_to_string(&from) {
+            if !scanner.scan_content(&content).is_empty() {
+                skipped.push(rel.to_string());
+                return Ok(());
+            }
+        }
+        let to = home.join(rel);
+        if let Some(parent) = to.parent() {
+            std::fs::create_dir_all(parent)?;
+        }
+        std::fs::copy(&from, &to).with_context(|| format!("Failed to copy {}", rel))?;
+        Ok(())
+    };
+
+    for entry in config.effective_dotfiles(machine_id) {
+        let path = entry.path().strip_prefix("~/").unwrap_or(entry.path());
+        if !entry.is_safe_path() {
+            continue;
+        }
+        for file in crate::sync::expand_dotfile_glob(path, real_home) {
+            if real_home.join(&file).is_file() {
+                copy(&file)?;
+            }
+        }
+    }
+    for dir in config.effective_dirs(machine_id) {
+        let dir = dir.strip_prefix("~/").unwrap_or(&dir).trim_end_matches('/');
+        if !crate::config::is_safe_dotfile_path(dir) {
+            continue;
+        }
+        for entry in walkdir::WalkDir::new(real_home.join(dir))
+            .into_iter()
+            .filter_map(|e| e.ok())
+            .filter(|e| e.file_type().is_file())
+        {
+            if let Ok(rel) = entry.path().strip_prefix(real_home) {
+                copy(&rel.to_string_lossy())?;
+            }
+        }
+    }
+    Ok(skipped)
+}
+
+/// A stand-in for each package manager on PATH: commands that change packages are
+/// logged to `packages.log` and skipped, everything else runs the real one
+fn write_package_stubs(bin: &Path) -> Result<()> {
+    for manager in MANAGERS {
+        let Ok(real) = which::which(manager) else {
+            continue;
+        };
+        let path = bin.join(manager);
+        std::fs::write(&path, package_stub(manager, &real.to_string_lossy()))?;
+        #[cfg(unix)]
+        {
+            use std::os::unix::fs::PermissionsExt;
+            std::fs::set_permissions(&path, std::fs::Permissions::from_mode(0o755))?;
+        }
+    }
+    Ok(())
+}
+
+fn package_stub(manager: &str, real: &str) -> String {
+    format!(
+        r#"#!/bin/sh
+# tether sandbox: {manager} may list packages but not change them
+case "$1 $2" in
+  install*|uninstall*|remove*|rm\ *|add*|upgrade*|update*|reinstall*|link*|unlink*|tap*|untap*|bundle*|"tool install"*|"tool uninstall"*|"tool upgrade"*)
+    echo "{manager} $*" >> "$TETHER_SANDBOX/packages.log"
+    echo "sandbox: skipped '{manager} $*'" >&2
+    exit 0
+    ;;
+esac
+exec '{real}' "$@"
+"#
+    )
+}
+
+/// `program` run inside the sandbox: its HOME, its package stubs first on PATH, and
+/// nothing pointing back at the real home
+fn sandbox_command(program: &Path, dir: &Path, home: &Path, bin: &Path) -> Command {
+    let mut command = Command::new(program);
+    let path = std::env::var_os("PATH").unwrap_or_default();
+    let mut paths = vec![bin.to_path_buf()];
+    paths.extend(std::env::split_paths(&path));
+    command
+        .env("HOME", home)
+        .env(SANDBOX_ENV, dir)
+        .env("PATH", std::env::join_paths(paths).unwrap_or(path))
+        .env_remove("TETHER_CONTEXT")
+        .env_remove("TETHER_KEY")
+        .env_remove("ZDOTDIR")
+        .env_remove("GIT_CONFIG_GLOBAL");
+    for var in [
+        "XDG_CONFIG_HOME",
+        "XDG_DATA_HOME",
+        "XDG_STATE_HOME",
+        "XDG_CACHE_HOME",
+    ] {
+        command.env_remove(var);
+    }
+    command
+}
+
+fn git(dir: &Path, args: &[&str]) -> Result<()> {
+    let output = Command::new("git").args(args).current_dir(dir).output()?;
+    if !output.status.success() {
+        anyhow::bail!(
+            "git {} failed: {}",
+            args[0],
+            String::from_utf8_lossy(&output.stderr).trim()
+        );
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_sandbox_config() {
+        let mut config = Config::default();
+        config.backend.url = "git@github.com:me/dotfiles.git".to_string();
+        config.security.encrypt_dotfiles = true;
+        config
+            .secrets
+            .files
+            .insert(".npmrc".to_string(), "bw:npm#token".to_string());
+        config.report.webhook = Some("https://hooks.example.com/drift".to_string());
+        config.packages.remove_unlisted = true;
+
+        let sandbox = sandbox_config(&config, "/tmp/sandbox/remote.git");
+        assert_eq!(sandbox.backend.url, "/tmp/sandbox/remote.git");
+        assert!(!sandbox.security.encrypt_dotfiles);
+        assert!(sandbox.secrets.files.is_empty());
+        assert_eq!(sandbox.report.webhook, None);
+        // The strategies to try stay as they are
+        assert!(sandbox.packages.remove_unlisted);
+    }
+
+    #[test]
+    fn test_package_stub() {
+        let stub = package_stub("brew", "/opt/homebrew/bin/brew");
+        assert!(stub.contains("exec '/opt/homebrew/bin/brew' \"$@\""));
+        assert!(stub.contains("uninstall*|"));
+        assert!(stub.contains("\"tool uninstall\"*"));
+    }
+}
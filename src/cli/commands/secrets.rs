@@ -0,0 +1,180 @@
+use crate::cli::output::relative_time;
+use crate::cli::{Output, Progress, Prompt};
+use crate::config::Config;
+use crate::sync::{personal_secrets, GitBackend, SyncEngine, SyncState};
+use anyhow::Result;
+use std::path::{Path, PathBuf};
+
+fn open_store() -> Result<Option<(Config, PathBuf)>> {
+    let config = Config::load()?;
+    if !config.has_personal_features() {
+        Output::warning("Secrets not available without personal features");
+        return Ok(None);
+    }
+    Ok(Some((config, SyncEngine::sync_path()?)))
+}
+
+/// Pull, apply `change` to the repo, re-sign the integrity manifest, then commit and
+/// push. Secrets have no local copy a later sync could re-export, so they go straight
+/// to the remote instead of being queued.
+pub(super) fn commit_change(
+    config: &Config,
+    sync_path: &Path,
+    message: &str,
+    change: impl FnOnce() -> Result<()>,
+) -> Result<()> {
+    let _sync_lock = crate::sync::acquire_sync_lock(true)?;
+    let git = GitBackend::open(sync_path)?;
+    let state = SyncState::load()?;
+
+    let pb = Progress::spinner("Fetching changes...");
+    let pulled = git.pull();
+    pb.finish_and_clear();
+    pulled?;
+
+    change()?;
+    crate::sync::integrity::sign_repo(config, sync_path, &state.machine_id)?;
+    git.commit(message, &state.machine_id)?;
+
+    let pb = Progress::spinner("Pushing changes...");
+    let pushed = git.push();
+    pb.finish_and_clear();
+    pushed
+}
+
+pub async fn set(name: &str, value: Option<&str>) -> Result<()> {
+    let Some((config, sync_path)) = open_store()? else {
+        return Ok(());
+    };
+    if !personal_secrets::is_valid_name(name) {
+        anyhow::bail!(
+            "Invalid secret name '{}': use letters, digits, '-', '_' or '.'",
+            name
+        );
+    }
+    let key = crate::security::get_encryption_key()?;
+
+    let value = match value {
+        Some(v) => v.to_string(),
+        // Piped input (`pass show x | tether secrets set X`), minus the trailing newline
+        None if !std::io::IsTerminal::is_terminal(&std::io::stdin()) => {
+            let mut input = String::new();
+            std::io::Read::read_to_string(&mut std::io::stdin(), &mut input)?;
+            input.trim_end_matches(['\r', '\n']).to_string()
+        }
+        None => Prompt::password(&format!("Enter value for '{}':", name))?,
+    };
+    if value.is_empty() {
+        anyhow::bail!("Secret value is empty");
+    }
+
+    let existed = personal_secrets::exists(&sync_path, name);
+    commit_change(
+        &config,
+        &sync_path,
+        &format!("Set secret: {}", name),
+        || personal_secrets::set(&sync_path, &key, name, value.as_bytes()),
+    )?;
+
+    Output::success(&format!(
+        "Secret '{}' {}",
+        name,
+        if existed { "updated" } else { "saved" }
+    ));
+    Ok(())
+}
+
+/// Print a secret, or with `copy` put it on the clipboard for that many seconds.
+/// `quiet` prints the bare value for scripts: no trailing newline, no status messages.
+pub(super) fn output_secret(value: &str, copy: Option<u64>, quiet: bool) -> Result<()> {
+    match copy {
+        Some(clear_after) => {
+            crate::cli::clipboard::copy_secret(value, clear_after)?;
+            if !quiet {
+                Output::success(&match clear_after {
+                    0 => "Copied to clipboard".to_string(),
+                    secs => format!("Copied to clipboard; clears in {}s", secs),
+                });
+            }
+        }
+        None if quiet => {
+            use std::io::Write;
+            let mut stdout = std::io::stdout();
+            stdout.write_all(value.as_bytes())?;
+            stdout.flush()?;
+        }
+        None => println!("{}", value),
+    }
+    Ok(())
+}
+
+pub async fn get(name: &str, copy: Option<u64>, quiet: bool) -> Result<()> {
+    let Some((_config, sync_path)) = open_store()? else {
+        return Ok(());
+    };
+    // Provider references (`bw:item#field`) can't collide with store names, which
+    // have no ':'
+    if name.contains(':') {
+        return output_secret(&crate::security::providers::resolve(name)?, copy, quiet);
+    }
+    let key = crate::security::get_encryption_key()?;
+
+    let Some(value) = personal_secrets::get(&sync_path, &key, name)? else {
+        anyhow::bail!(
+            "Secret '{}' not found (run 'tether sync' if it was set on another machine)",
+            name
+        );
+    };
+    crate::security::audit::record(crate::security::audit::AuditEvent::SecretRead, name);
+    output_secret(&String::from_utf8(value)?, copy, quiet)
+}
+
+pub async fn list() -> Result<()> {
+    let Some((_config, sync_path)) = open_store()? else {
+        return Ok(());
+    };
+    let names = personal_secrets::list(&sync_path)?;
+    if names.is_empty() {
+        Output::info("No secrets yet");
+        Output::dim("  Add one with `tether secrets set <NAME>`");
+        return Ok(());
+    }
+
+    let git = GitBackend::open(&sync_path).ok();
+    Output::section(&format!("{} secret(s)", names.len()));
+    for name in &names {
+        let last = git
+            .as_ref()
+            .and_then(|g| g.file_log(&personal_secrets::repo_path(name), 1).ok())
+            .and_then(|log| log.into_iter().next());
+        match last {
+            Some(entry) => Output::list_item(&format!(
+                "{}  set {} on {}",
+                name,
+                relative_time(entry.date),
+                entry.machine_id
+            )),
+            None => Output::list_item(name),
+        }
+    }
+    Ok(())
+}
+
+pub async fn remove(name: &str) -> Result<()> {
+    let Some((config, sync_path)) = open_store()? else {
+        return Ok(());
+    };
+    if !personal_secrets::exists(&sync_path, name) {
+        Output::error(&format!("Secret '{}' not found", name));
+        return Ok(());
+    }
+
+    commit_change(
+        &config,
+        &sync_path,
+        &format!("Remove secret: {}", name),
+        || personal_secrets::remove(&sync_path, name).map(|_| ()),
+    )?;
+    Output::success(&format!("Removed secret '{}'", name));
+    Ok(())
+}
@@ -0,0 +1,96 @@
+use crate::config::Config;
+use crate::sync::{GitBackend, SyncEngine, SyncState};
+use anyhow::Result;
+use std::io::Write;
+
+/// Split `path@commit` into its parts. Only a hex suffix counts as a commit, so files
+/// with `@` in their name still work.
+fn split_commit(spec: &str) -> (&str, Option<&str>) {
+    match spec.rsplit_once('@') {
+        Some((path, commit))
+            if !path.is_empty()
+                && commit.len() >= 4
+                && commit.chars().all(|c| c.is_ascii_hexdigit()) =>
+        {
+            (path, Some(commit))
+        }
+        _ => (spec, None),
+    }
+}
+
+/// Repo paths `file` may be stored at, most likely first: the dotfile path for this
+/// machine's profile, a synced directory file, then `file` taken as a repo path itself.
+fn candidates(
+    config: &Config,
+    machine_id: &str,
+    sync_path: &std::path::Path,
+    file: &str,
+) -> Vec<String> {
+    let rel = file.strip_prefix("~/").unwrap_or(file);
+    let encrypted = config.security.encrypt_dotfiles;
+    let profile = config.dotfile_profile(machine_id, rel);
+    let shared = config.is_dotfile_shared(machine_id, rel);
+    vec![
+        crate::sync::resolve_dotfile_repo_path(sync_path, rel, encrypted, profile, shared),
+        format!("configs/{}.enc", rel),
+        format!("configs/{}", rel),
+        rel.to_string(),
+    ]
+}
+
+/// Print the decrypted repo copy of `spec` (`<path>[@commit]`) to stdout, as stored in
+/// the sync repo rather than in $HOME
+pub async fn run(spec: &str) -> Result<()> {
+    let (file, commit) = split_commit(spec);
+    if !crate::config::is_safe_dotfile_path(file) {
+        anyhow::bail!("Unsafe file path: {}", file);
+    }
+
+    let config = Config::load()?;
+    let sync_path = SyncEngine::sync_path()?;
+    let git = GitBackend::open(&sync_path)?;
+    let state = SyncState::load()?;
+
+    let load = |rel: &str| -> Result<Vec<u8>> {
+        match commit {
+            Some(c) => git.show_at_commit(c, rel),
+            None => Ok(std::fs::read(sync_path.join(rel))?),
+        }
+    };
+    let found = candidates(&config, &state.machine_id, &sync_path, file)
+        .into_iter()
+        .find_map(|rel| load(&rel).ok().map(|content| (rel, content)));
+    let Some((repo_path, content)) = found else {
+        match commit {
+            Some(c) => anyhow::bail!("{} is not in the sync repo at {}", file, c),
+            None => anyhow::bail!("{} is not in the sync repo", file),
+        }
+    };
+
+    let content = if repo_path.ends_with(".enc") {
+        let key = crate::security::get_encryption_key()?;
+        let plaintext = crate::security::decrypt(&content, &key)?;
+        crate::sync::chunks::reassemble(&repo_path, plaintext, &key, load)?
+    } else {
+        content
+    };
+
+    let mut stdout = std::io::stdout().lock();
+    stdout.write_all(&content)?;
+    stdout.flush()?;
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_split_commit() {
+        assert_eq!(split_commit(".zshrc@abc1234"), (".zshrc", Some("abc1234")));
+        assert_eq!(split_commit("~/.zshrc"), ("~/.zshrc", None));
+        assert_eq!(split_commit(".config/a@b/x"), (".config/a@b/x", None));
+        assert_eq!(split_commit("user@host.conf"), ("user@host.conf", None));
+        assert_eq!(split_commit("@abcd"), ("@abcd", None));
+    }
+}
@@ -25,24 +25,67 @@ pub async fn run() -> Result<()> {
     println!();
 
     // Machine
+    if let Some(context) = crate::config::current_context()? {
+        Output::key_value("Context", &context);
+    }
     Output::key_value("Machine", &state.machine_id);
     Output::key_value("Profile", config.profile_name(&state.machine_id));
     Output::key_value("Version", env!("CARGO_PKG_VERSION"));
 
     // Last Sync
     let sync_time = relative_time(state.last_sync);
-    let sync_badge = Output::badge("synced", true);
+    let pending_push = pending_push_count();
+    let sync_badge = if pending_push > 0 {
+        Output::badge("pending push", false)
+    } else {
+        Output::badge("synced", true)
+    };
     Output::key_value("Last Sync", &format!("{}  {}", sync_time, sync_badge));
+    if pending_push > 0 {
+        Output::key_value(
+            "Pending Push",
+            &format!(
+                "{} commit(s) held back (offline or batching), pushed on a later sync",
+                pending_push
+            ),
+        );
+    }
 
     // Daemon status
-    let pid = read_daemon_pid()?;
-    let (status_label, is_running) = match pid {
-        Some(pid) if is_process_running(pid) => (format!("Running (PID {pid})"), true),
-        Some(pid) => (format!("Not running (stale PID {pid})"), false),
-        None => ("Not running".to_string(), false),
+    use crate::daemon::pid::PidState;
+    let (status_label, is_running) = match crate::daemon::pid::check()? {
+        PidState::Running(pid) => (format!("Running (PID {pid})"), true),
+        PidState::Stale { pid, .. } => (format!("Not running (stale PID {pid})"), false),
+        PidState::Missing => ("Not running".to_string(), false),
     };
     let daemon_badge = Output::badge(if is_running { "active" } else { "stopped" }, is_running);
     Output::key_value("Daemon", &format!("{}  {}", status_label, daemon_badge));
+    #[cfg(windows)]
+    if let Some(task) = crate::daemon::scheduled_task_status() {
+        Output::key_value("  Login task", &task);
+    }
+    // A daemon serving several contexts reports each one
+    if let Some(status) = is_running.then(crate::daemon::ipc::query).flatten() {
+        if status.contexts.len() > 1 {
+            for context in &status.contexts {
+                Output::key_value(&format!("  {}", context.label()), &context.describe());
+            }
+        } else if let Some(context) = status.contexts.first() {
+            if context.is_paused() {
+                Output::key_value("  Paused", &context.pause_end());
+            }
+            if let Some(degraded) = context.degraded() {
+                Output::key_value_colored("  Degraded", &degraded, |s| s.red().to_string());
+            }
+        }
+    }
+    // Kept on disk, so it shows after the daemon stopped or crashed too
+    if let Some(error) = crate::daemon::last_error::LastError::current(state.last_sync) {
+        Output::key_value_colored("  Last Error", &error.headline(), |s| {
+            s.red().bold().to_string()
+        });
+        Output::dim(&format!("{:18}{}", "", error.first_line()));
+    }
 
     // Features summary
     let mut enabled_features = Vec::new();
@@ -61,6 +104,43 @@ pub async fn run() -> Result<()> {
     if !enabled_features.is_empty() {
         Output::key_value("Features", &enabled_features.join(", "));
     }
+    if let Some(latest) = state.repo_sizes.last() {
+        let size = crate::sync::growth::format_size(latest.bytes);
+        Output::key_value(
+            "Repo Size",
+            &match crate::sync::growth::growth(&state.repo_sizes) {
+                Some((grown, days)) => format!(
+                    "{}  (+{} in {} days)",
+                    size,
+                    crate::sync::growth::format_size(grown),
+                    days
+                ),
+                None => size,
+            },
+        );
+    }
+
+    if state.repo_visibility.as_ref().is_some_and(|v| v.public) {
+        println!();
+        println!(
+            "  {}",
+            format!(
+                "{} {}",
+                Output::WARN,
+                crate::sync::visibility::PUBLIC_WARNING
+            )
+            .red()
+            .bold()
+        );
+    }
+
+    if let Some(recommendation) = crate::sync::growth::recommendation(&state.repo_sizes) {
+        println!();
+        println!(
+            "  {}",
+            format!("{} {}", Output::WARN, recommendation.message()).yellow()
+        );
+    }
 
     // Conflicts warning
     let conflict_state = ConflictState::load().unwrap_or_default();
@@ -226,25 +306,11 @@ pub async fn run() -> Result<()> {
     Ok(())
 }
 
-fn read_daemon_pid() -> Result<Option<u32>> {
-    let pid_path = Config::config_dir()?.join("daemon.pid");
-    if !pid_path.exists() {
-        return Ok(None);
-    }
-
-    let contents = std::fs::read_to_string(&pid_path)?;
-    match contents.trim().parse::<u32>() {
-        Ok(pid) if pid > 0 => Ok(Some(pid)),
-        _ => Ok(None),
-    }
-}
-
-fn is_process_running(pid: u32) -> bool {
-    unsafe {
-        if libc::kill(pid as libc::pid_t, 0) == 0 {
-            return true;
-        }
-        // ESRCH = no such process, EPERM = exists but no permission
-        std::io::Error::last_os_error().raw_os_error() == Some(libc::EPERM)
-    }
+/// Sync repo commits not yet pushed (made offline or held for batching)
+pub fn pending_push_count() -> usize {
+    crate::sync::SyncEngine::sync_path()
+        .ok()
+        .and_then(|path| crate::sync::GitBackend::open(&path).ok())
+        .and_then(|git| git.unpushed_commits().ok())
+        .unwrap_or(0)
 }
@@ -1,25 +1,30 @@
 use crate::cli::{Output, Progress, Prompt};
-use crate::config::Config;
+use crate::config::{Config, MachineRole};
 use crate::packages::{
     BrewManager, BunManager, GemManager, NpmManager, PackageManager, PnpmManager, UvManager,
 };
 use crate::sync::git::{find_git_repos, get_remote_url, normalize_remote_url};
 use crate::sync::{
-    import_packages, sync_packages, GitBackend, MachineState, SyncEngine, SyncState,
+    import_packages, sync_packages, verify::verify_applied, GitBackend, MachineState, SyncEngine,
+    SyncMode, SyncState,
 };
 use anyhow::Result;
+use rayon::prelude::*;
 use std::collections::{HashMap, HashSet};
 use std::path::{Path, PathBuf};
 
 /// Build a map of normalized project URLs to all local checkout paths
-fn build_project_map(search_paths: &[PathBuf]) -> HashMap<String, Vec<PathBuf>> {
+fn build_project_map(
+    search_paths: &[PathBuf],
+    settings: &crate::config::ProjectConfigSettings,
+) -> HashMap<String, Vec<PathBuf>> {
     let mut project_map: HashMap<String, Vec<PathBuf>> = HashMap::new();
 
     for search_path in search_paths {
         if !search_path.exists() {
             continue;
         }
-        if let Ok(repos) = find_git_repos(search_path) {
+        if let Ok(repos) = find_git_repos(search_path, settings) {
             for repo in repos {
                 if let Ok(url) = get_remote_url(&repo) {
                     let normalized = normalize_remote_url(&url);
@@ -32,10 +37,25 @@ fn build_project_map(search_paths: &[PathBuf]) -> HashMap<String, Vec<PathBuf>>
     project_map
 }
 
-pub async fn run(dry_run: bool, _force: bool, rediscover: bool) -> Result<()> {
+/// Sync everything. With `interactive`, conflicts, deletions and integrity problems
+/// are asked about on the terminal; without it (or from the daemon, or on a server)
+/// nothing prompts, and a locked key is an error.
+pub async fn run(
+    dry_run: bool,
+    _force: bool,
+    rediscover: bool,
+    interactive: bool,
+    mode: SyncMode,
+) -> Result<()> {
+    let interactive = interactive && !crate::daemon::is_daemon_mode();
     if dry_run {
         Output::info("Dry-run mode");
     }
+    match mode {
+        SyncMode::PullOnly => Output::info("Pull-only: local changes won't be pushed"),
+        SyncMode::PushOnly => Output::info("Push-only: remote changes won't be applied"),
+        SyncMode::Full => {}
+    }
 
     // Acquire sync lock (wait up to 2s for other syncs to finish)
     let _sync_lock = if !dry_run {
@@ -48,32 +68,27 @@ pub async fn run(dry_run: bool, _force: bool, rediscover: bool) -> Result<()> {
 
     // No personal features: skip personal sync, only sync teams
     if !config.has_personal_features() {
-        return run_team_only_sync(&config, dry_run).await;
+        return run_team_only_sync(&config, dry_run, mode).await;
     }
 
     let mut config = config;
 
-    // Ensure encryption key is unlocked if encryption is enabled
-    if config.security.encrypt_dotfiles && !crate::security::is_unlocked() {
-        if !crate::security::has_encryption_key() {
-            return Err(anyhow::anyhow!(
-                "No encryption key found. Run 'tether init' first."
-            ));
-        }
-
-        Output::info("Enter passphrase:");
-        let passphrase = Prompt::password("Passphrase")?;
-        crate::security::unlock_with_passphrase(&passphrase)?;
-    }
+    ensure_unlocked(&config, interactive && !crate::config::server_mode())?;
     let sync_path = SyncEngine::sync_path()?;
     let home = crate::home_dir()?;
 
     // Pull latest changes from personal repo
     let git = GitBackend::open(&sync_path)?;
+    let mut offline = false;
     if !dry_run {
         Output::info("Pulling latest changes...");
-        git.pull()?;
+        offline = pull_or_offline(&git)?;
         crate::sync::check_sync_format_version(&sync_path)?;
+        check_repo_integrity(
+            &config,
+            &sync_path,
+            interactive && !crate::config::server_mode(),
+        )?;
     }
 
     // Pull from team repo if enabled
@@ -82,7 +97,7 @@ pub async fn run(dry_run: bool, _force: bool, rediscover: bool) -> Result<()> {
             let team_sync_dir = Config::team_sync_dir()?;
 
             if team_sync_dir.exists() {
-                if !dry_run {
+                if !dry_run && !offline {
                     let team_git = GitBackend::open(&team_sync_dir)?;
                     team_git.pull()?;
                 }
@@ -94,13 +109,15 @@ pub async fn run(dry_run: bool, _force: bool, rediscover: bool) -> Result<()> {
 
     // Always sync tether config first (hardcoded, not dependent on config)
     // This ensures config changes from other machines are applied before using config
-    if config.security.encrypt_dotfiles && !dry_run {
-        if let Some(new_config) = sync_tether_config(&sync_path, &home)? {
+    if config.security.encrypt_dotfiles && !dry_run && mode.applies_remote() {
+        if let Some(new_config) = sync_tether_config(&sync_path)? {
             config = new_config;
         }
     }
 
     let mut state = SyncState::load()?;
+    let read_only = config.machine_role(&state.machine_id) == MachineRole::ReadOnly;
+    let mode = effective_mode(&config, &state.machine_id, mode)?;
 
     // Auto-assign machine to default profile on first run after v2 migration
     if !config.profiles.is_empty() && !config.machine_profiles.contains_key(&state.machine_id) {
@@ -114,11 +131,13 @@ pub async fn run(dry_run: bool, _force: bool, rediscover: bool) -> Result<()> {
     // Load machine state early to get ignored lists for decrypt phase
     let machine_state_for_decrypt =
         MachineState::load_from_repo(&sync_path, &state.machine_id)?.unwrap_or_default();
+    warn_incompatible_peers(&sync_path, &state.machine_id);
 
     // Apply dotfiles from sync repo (if encrypted) - with conflict detection
-    // Interactive mode when run manually, non-interactive when run by daemon
-    let interactive = !crate::daemon::is_daemon_mode();
-    if config.security.encrypt_dotfiles && !dry_run {
+    // The synced config may have made this machine a server
+    let interactive = interactive && !config.is_server(&state.machine_id);
+    if config.security.encrypt_dotfiles && !dry_run && mode.applies_remote() {
+        apply_remote_deletions(&config, &sync_path, &home, &mut state)?;
         decrypt_from_repo(
             &config,
             &sync_path,
@@ -126,11 +145,12 @@ pub async fn run(dry_run: bool, _force: bool, rediscover: bool) -> Result<()> {
             &mut state,
             &machine_state_for_decrypt,
             interactive,
+            None,
         )?;
     }
 
     // Interactive mode: offer files from other profiles
-    if interactive && !dry_run && config.features.personal_dotfiles {
+    if interactive && !dry_run && config.features.personal_dotfiles && mode.applies_remote() {
         if rediscover {
             state.dismissed_imports.clear();
         }
@@ -147,15 +167,17 @@ pub async fn run(dry_run: bool, _force: bool, rediscover: bool) -> Result<()> {
                     &mut state,
                     &machine_state_for_decrypt,
                     interactive,
+                    None,
                 )?;
             }
         }
     }
 
     // Sync dotfiles (local → Git) - only if personal dotfiles enabled
-    if config.features.personal_dotfiles {
+    if config.features.personal_dotfiles && mode.pushes_local() {
         let machine_id = state.machine_id.clone();
-        let upload_profile = config.profile_name(&machine_id).to_string();
+
+        propagate_local_deletions(&config, &sync_path, &home, &mut state, interactive, dry_run)?;
 
         // Sync individual dotfiles (with glob expansion)
         for entry in config.effective_dotfiles(&machine_id) {
@@ -167,6 +189,7 @@ pub async fn run(dry_run: bool, _force: bool, rediscover: bool) -> Result<()> {
 
             let pattern = entry.path();
             let shared = config.is_dotfile_shared(&machine_id, pattern);
+            let upload_profile = config.dotfile_profile(&machine_id, pattern).to_string();
             let expanded = crate::sync::expand_dotfile_glob(pattern, &home);
 
             for file in expanded {
@@ -178,56 +201,15 @@ pub async fn run(dry_run: bool, _force: bool, rediscover: bool) -> Result<()> {
                         &upload_profile,
                         shared,
                     )?;
-                }
-
-                let source = home.join(&file);
-
-                if source.exists() {
-                    if let Ok(content) = std::fs::read(&source) {
-                        let hash = crate::sha256_hex(&content);
-
-                        let file_changed = state
-                            .files
-                            .get(&file)
-                            .map(|f| f.hash != hash)
-                            .unwrap_or(true);
-
-                        if file_changed && !dry_run {
-                            if config.security.encrypt_dotfiles {
-                                let key = crate::security::get_encryption_key()?;
-                                let encrypted_data = crate::security::encrypt(&content, &key)?;
-                                let repo_path = crate::sync::dotfile_to_repo_path_profiled(
-                                    &file,
-                                    true,
-                                    &upload_profile,
-                                    shared,
-                                );
-                                let dest = sync_path.join(&repo_path);
-                                if let Some(parent) = dest.parent() {
-                                    std::fs::create_dir_all(parent)?;
-                                }
-                                std::fs::write(&dest, encrypted_data)?;
-                                #[cfg(unix)]
-                                preserve_executable_bit(&source, &dest);
-                            } else {
-                                let repo_path = crate::sync::dotfile_to_repo_path_profiled(
-                                    &file,
-                                    false,
-                                    &upload_profile,
-                                    shared,
-                                );
-                                let dest = sync_path.join(&repo_path);
-                                if let Some(parent) = dest.parent() {
-                                    std::fs::create_dir_all(parent)?;
-                                }
-                                std::fs::write(&dest, &content)?;
-                                #[cfg(unix)]
-                                preserve_executable_bit(&source, &dest);
-                            }
-
-                            state.update_file(&file, hash.clone());
-                        }
-                    }
+                    export_dotfile(
+                        &config,
+                        &sync_path,
+                        &mut state,
+                        &home.join(&file),
+                        &file,
+                        &upload_profile,
+                        shared,
+                    )?;
                 }
             }
         }
@@ -241,21 +223,21 @@ pub async fn run(dry_run: bool, _force: bool, rediscover: bool) -> Result<()> {
                 // Push to current profile's dirs (or global if no profile)
                 let current_profile = config.profile_name(&machine_id).to_string();
                 if let Some(profile) = config.profiles.get_mut(&current_profile) {
-                    if !profile.dirs.contains(&dir) {
+                    if !profile.dirs.iter().any(|d| d.path() == dir) {
                         Output::info(&format!("Auto-discovered sourced directory: {}", dir));
-                        profile.dirs.push(dir);
+                        profile.dirs.push(dir.into());
                         config_changed = true;
                     }
-                } else if !config.dotfiles.dirs.contains(&dir) {
+                } else if !config.dotfiles.dirs.iter().any(|d| d.path() == dir) {
                     Output::info(&format!("Auto-discovered sourced directory: {}", dir));
-                    config.dotfiles.dirs.push(dir);
+                    config.dotfiles.dirs.push(dir.into());
                     config_changed = true;
                 }
             }
             if config_changed {
-                config.dotfiles.dirs.sort();
+                config.dotfiles.dirs.sort_by(|a, b| a.path().cmp(b.path()));
                 for profile in config.profiles.values_mut() {
-                    profile.dirs.sort();
+                    profile.dirs.sort_by(|a, b| a.path().cmp(b.path()));
                 }
                 config.save()?;
             }
@@ -274,7 +256,7 @@ pub async fn run(dry_run: bool, _force: bool, rediscover: bool) -> Result<()> {
     } // end personal dotfiles feature block
 
     // Sync team project secrets
-    if !dry_run {
+    if !dry_run && mode.applies_remote() {
         sync_team_project_secrets(&config, &home, &mut state)?;
     }
 
@@ -283,7 +265,7 @@ pub async fn run(dry_run: bool, _force: bool, rediscover: bool) -> Result<()> {
 
     // Import packages from manifests (install missing packages, respecting removed_packages)
     // Interactive mode: install deferred casks from daemon syncs
-    if config.features.personal_packages && !dry_run {
+    if config.features.personal_packages && !dry_run && mode.applies_remote() {
         let deferred_casks = state.deferred_casks.clone();
 
         import_packages(
@@ -302,47 +284,83 @@ pub async fn run(dry_run: bool, _force: bool, rediscover: bool) -> Result<()> {
             state.deferred_casks_hash = None;
             state.save()?;
         }
+        let pending = state.pending_install_count();
+        if pending > 0 {
+            Output::info(&format!(
+                "{} package install{} waiting for approval: tether packages approve",
+                pending,
+                if pending == 1 { "" } else { "s" }
+            ));
+        }
 
         // Rebuild machine state after import to capture newly installed packages
         machine_state = build_machine_state(&config, &state, &sync_path).await?;
     }
 
     // Export package manifests using union of all machine states
-    if config.features.personal_packages {
+    if config.features.personal_packages && mode.pushes_local() {
         sync_packages(&config, &mut state, &sync_path, &machine_state, dry_run).await?;
     }
 
     // Save machine state for cross-machine comparison
-    if !dry_run {
+    if !dry_run && mode.pushes_local() {
         machine_state.save_to_repo(&sync_path)?;
     }
 
     // Always export tether config (hardcoded, not dependent on feature flags)
     // This ensures config settings (including features) are synced across machines
     // even when personal features are disabled, allowing remote config changes
-    if config.security.encrypt_dotfiles && !dry_run {
-        export_tether_config(&sync_path, &home, &mut state)?;
-    }
-
-    // Commit and push changes
-    if !dry_run {
-        let has_changes = git.has_changes()?;
-
-        if has_changes {
-            let pb = Progress::spinner("Pushing changes...");
+    if config.security.encrypt_dotfiles && !dry_run && mode.pushes_local() {
+        export_tether_config(&sync_path, &mut state)?;
+    }
+
+    // Commit and push changes. Pull-only drops anything the run wrote to the
+    // repo (e.g. layout migrations) so it never diverges from the remote;
+    // read-only machines still report their machine state.
+    if !dry_run && !mode.pushes_local() {
+        git.discard_changes()?;
+        if read_only {
+            machine_state.save_to_repo(&sync_path)?;
+            if git.has_changes()? {
+                git.commit("Update machine state", &state.machine_id)?;
+            }
+            push_or_queue(&git, offline)?;
+        }
+    } else if !dry_run {
+        crate::sync::integrity::sign_repo(&config, &sync_path, &state.machine_id)?;
+        if git.has_changes()? {
             git.commit("Sync dotfiles and packages", &state.machine_id)?;
-            git.push()?;
-            pb.finish_and_clear();
         }
+        // Land commits the daemon held back for batching as one
+        if config.sync.batching_enabled() {
+            git.squash_unpushed("Sync dotfiles and packages", &state.machine_id)?;
+        }
+        push_or_queue(&git, offline)?;
+    }
+    if !dry_run {
+        let detail = match mode {
+            SyncMode::Full => "full",
+            SyncMode::PullOnly => "pull-only",
+            SyncMode::PushOnly => "push-only",
+        };
+        crate::security::audit::record(crate::security::audit::AuditEvent::Sync, detail);
     }
 
     // Check and push team repo changes (if write access enabled)
-    if !dry_run {
+    if !dry_run && !offline && mode.pushes_local() {
         if let Some(team) = &config.team {
             if team.enabled && !team.read_only {
                 let team_sync_dir = Config::team_sync_dir()?;
                 if team_sync_dir.exists() {
                     let team_git = GitBackend::open(&team_sync_dir)?;
+                    if config.security.audit_sync {
+                        if let Err(e) = crate::security::audit::export_to_team(
+                            &team_sync_dir,
+                            &state.machine_id,
+                        ) {
+                            Output::warning(&format!("Could not export the audit log: {}", e));
+                        }
+                    }
 
                     if team_git.has_changes()? {
                         // Scan for secrets before pushing to team repo
@@ -351,15 +369,25 @@ pub async fn run(dry_run: bool, _force: bool, rediscover: bool) -> Result<()> {
                             for entry in std::fs::read_dir(&dotfiles_dir)? {
                                 let entry = entry?;
                                 if entry.file_type()?.is_file() {
+                                    let name = entry.file_name().to_string_lossy().to_string();
                                     if let Ok(findings) =
-                                        crate::security::scan_for_secrets(&entry.path())
+                                        crate::security::scan_for_secrets(&entry.path(), &name)
                                     {
                                         if !findings.is_empty() {
                                             Output::error(&format!(
                                                 "Team push blocked: {} contains {} secret(s)",
-                                                entry.file_name().to_string_lossy(),
+                                                name,
                                                 findings.len()
                                             ));
+                                            for finding in &findings {
+                                                Output::dim(&format!(
+                                                    "  Line {}: {} (fingerprint {})",
+                                                    finding.line_number,
+                                                    finding.secret_type.description(),
+                                                    finding.fingerprint
+                                                ));
+                                            }
+                                            Output::info("If one is a false positive: tether ignore add --fingerprint <fingerprint>");
                                             anyhow::bail!(
                                                 "Cannot push secrets to team repo. Remove sensitive data first."
                                             );
@@ -378,10 +406,14 @@ pub async fn run(dry_run: bool, _force: bool, rediscover: bool) -> Result<()> {
     }
 
     // Sync collab secrets (only if feature enabled)
-    if !dry_run && config.features.collab_secrets {
+    if !dry_run && config.features.collab_secrets && mode.applies_remote() {
         sync_collab_secrets(&config, &home, &mut state)?;
     }
 
+    if !dry_run && mode.applies_remote() && !config.secrets.is_empty() {
+        write_provider_secrets(&config, &state.machine_id, &home);
+    }
+
     // Prune old backups
     if let Ok(pruned) = crate::sync::prune_old_backups() {
         if pruned > 0 {
@@ -390,6 +422,7 @@ pub async fn run(dry_run: bool, _force: bool, rediscover: bool) -> Result<()> {
     }
 
     if !dry_run {
+        crate::sync::growth::record(&mut state, &git, chrono::Utc::now());
         state.mark_synced();
         state.save()?;
     }
@@ -398,6 +431,280 @@ pub async fn run(dry_run: bool, _force: bool, rediscover: bool) -> Result<()> {
     Ok(())
 }
 
+/// Narrow `mode` for read-only machines, which never publish local changes
+fn effective_mode(config: &Config, machine_id: &str, mode: SyncMode) -> Result<SyncMode> {
+    if config.machine_role(machine_id) != MachineRole::ReadOnly {
+        return Ok(mode);
+    }
+    if mode == SyncMode::PushOnly {
+        anyhow::bail!("This machine is read-only and can't push local changes");
+    }
+    Ok(SyncMode::PullOnly)
+}
+
+/// Ensure the encryption key is unlocked if encryption is enabled, asking for the
+/// passphrase when `interactive`
+fn ensure_unlocked(config: &Config, interactive: bool) -> Result<()> {
+    if config.security.encrypt_dotfiles && !crate::security::is_unlocked() {
+        if !crate::security::has_encryption_key() {
+            return Err(anyhow::anyhow!(
+                "No encryption key found. Run 'tether init' first."
+            ));
+        }
+        if !interactive {
+            anyhow::bail!("Encryption key is locked. Run 'tether unlock' first.");
+        }
+
+        Output::info("Enter passphrase:");
+        let passphrase = Prompt::password("Passphrase")?;
+        crate::security::unlock_with_passphrase(&passphrase)?;
+    }
+    Ok(())
+}
+
+/// Warn about encrypted files changed in the repo outside a tether sync and let the
+/// user stop before they're applied. Continuing re-signs the repo as it is.
+fn check_repo_integrity(config: &Config, sync_path: &Path, interactive: bool) -> Result<()> {
+    let problems = crate::sync::integrity::check(config, sync_path)?;
+    confirm_integrity_problems(&problems, sync_path, interactive)
+}
+
+/// Stop on `problems` unless the user, when `interactive`, chooses to apply anyway
+fn confirm_integrity_problems(
+    problems: &[String],
+    sync_path: &Path,
+    interactive: bool,
+) -> Result<()> {
+    if problems.is_empty() {
+        return Ok(());
+    }
+    Output::warning("Encrypted files in the sync repo changed outside a tether sync:");
+    for problem in problems {
+        Output::list_item(problem);
+    }
+    Output::info("Anyone with write access to the repo could have done this");
+    if !interactive || !Prompt::confirm("Apply remote dotfiles anyway?", false)? {
+        anyhow::bail!(
+            "Sync stopped. Review the repo history with: git -C {} log --stat",
+            sync_path.display()
+        );
+    }
+    Ok(())
+}
+
+/// Normalize `.zshrc`, `~/.zshrc` or `/Users/me/.zshrc` to a home-relative path
+pub(crate) fn home_relative(file: &str, home: &Path) -> String {
+    let path = Path::new(file);
+    let rel = match file.strip_prefix("~/") {
+        Some(stripped) => stripped.to_string(),
+        None => path
+            .strip_prefix(home)
+            .map(|p| p.to_string_lossy().to_string())
+            .unwrap_or_else(|_| file.to_string()),
+    };
+    rel.trim_end_matches('/').to_string()
+}
+
+/// Sync one path and nothing else: pull, apply the remote copy (with conflict
+/// detection and backups), export the local copy, then commit and push.
+pub async fn run_file(file: &str, dry_run: bool, mode: SyncMode) -> Result<()> {
+    let home = crate::home_dir()?;
+    let rel = home_relative(file, &home);
+    if !crate::config::is_safe_dotfile_path(&rel) {
+        anyhow::bail!("Unsafe file path: {}", file);
+    }
+
+    let _sync_lock = if !dry_run {
+        Some(crate::sync::acquire_sync_lock(true)?)
+    } else {
+        None
+    };
+
+    let config = Config::load()?;
+    if !config.features.personal_dotfiles {
+        anyhow::bail!("Personal dotfile sync is disabled");
+    }
+
+    let mut state = SyncState::load()?;
+    let machine_id = state.machine_id.clone();
+    let mode = effective_mode(&config, &machine_id, mode)?;
+
+    // Either a dotfile entry (exact or glob match) or a file inside a synced dir
+    let dotfile_pattern = config
+        .effective_dotfiles(&machine_id)
+        .into_iter()
+        .map(|e| e.path().to_string())
+        .find(|pattern| {
+            pattern == &rel
+                || (crate::sync::is_glob_pattern(pattern)
+                    && glob::Pattern::new(pattern).is_ok_and(|p| p.matches(&rel)))
+        });
+    let in_synced_dir = config.effective_dirs(&machine_id).iter().any(|dir| {
+        let dir = dir.strip_prefix("~/").unwrap_or(dir).trim_end_matches('/');
+        rel == dir || rel.starts_with(&format!("{}/", dir))
+    });
+    if dotfile_pattern.is_none() && !in_synced_dir {
+        anyhow::bail!(
+            "{} is not a synced dotfile or inside a synced directory",
+            rel
+        );
+    }
+
+    let state_key = if dotfile_pattern.is_some() {
+        rel.clone()
+    } else {
+        format!("~/{}", rel)
+    };
+    let local_path = home.join(&rel);
+
+    if dry_run {
+        let local_hash = std::fs::read(&local_path)
+            .ok()
+            .map(|c| crate::sha256_hex(&c));
+        let synced_hash = state.files.get(&state_key).map(|f| f.hash.clone());
+        match local_hash {
+            None => Output::info(&format!("{} (not present locally)", rel)),
+            Some(h) if Some(&h) == synced_hash.as_ref() => {
+                Output::info(&format!("{} (unchanged locally)", rel))
+            }
+            Some(_) if mode.pushes_local() => {
+                Output::info(&format!("{} (changed locally, would push)", rel))
+            }
+            Some(_) => Output::info(&format!("{} (changed locally, not pushed)", rel)),
+        }
+        return Ok(());
+    }
+
+    let interactive = !crate::daemon::is_daemon_mode() && !config.is_server(&machine_id);
+    ensure_unlocked(&config, interactive)?;
+    let sync_path = SyncEngine::sync_path()?;
+    let git = GitBackend::open(&sync_path)?;
+    let offline = pull_or_offline(&git)?;
+    crate::sync::check_sync_format_version(&sync_path)?;
+    check_repo_integrity(&config, &sync_path, interactive)?;
+
+    // Remote → local
+    if config.security.encrypt_dotfiles && mode.applies_remote() {
+        let machine_state =
+            MachineState::load_from_repo(&sync_path, &machine_id)?.unwrap_or_default();
+        decrypt_from_repo(
+            &config,
+            &sync_path,
+            &home,
+            &mut state,
+            &machine_state,
+            interactive,
+            Some(&rel),
+        )?;
+    }
+
+    let conflicts = crate::sync::ConflictState::load().unwrap_or_default();
+    if conflicts.conflicts.iter().any(|c| c.file_path == rel) {
+        state.save()?;
+        Output::warning(&format!(
+            "{} has an unresolved conflict - run 'tether resolve {}'",
+            rel, rel
+        ));
+        return Ok(());
+    }
+
+    if !mode.pushes_local() {
+        git.discard_changes()?;
+        state.save()?;
+        Output::success(&format!("Pulled {}", rel));
+        return Ok(());
+    }
+
+    // Local → remote
+    if local_path.exists() {
+        if let Some(pattern) = &dotfile_pattern {
+            let shared = config.is_dotfile_shared(&machine_id, pattern);
+            let profile = config.dotfile_profile(&machine_id, pattern);
+            crate::sync::migrate_dotfile_shared_change(
+                &sync_path,
+                &rel,
+                config.security.encrypt_dotfiles,
+                profile,
+                shared,
+            )?;
+            export_dotfile(
+                &config,
+                &sync_path,
+                &mut state,
+                &local_path,
+                &rel,
+                profile,
+                shared,
+            )?;
+        } else {
+            export_config_file(
+                &config,
+                &sync_path,
+                &mut state,
+                &local_path,
+                Path::new(&rel),
+                &state_key,
+            )?;
+        }
+    }
+
+    crate::sync::integrity::sign_repo(&config, &sync_path, &machine_id)?;
+    if git.has_changes()? {
+        git.commit(&format!("Sync {}", rel), &machine_id)?;
+    }
+    push_or_queue(&git, offline)?;
+    state.save()?;
+
+    Output::success(&format!("Synced {}", rel));
+    Ok(())
+}
+
+/// Write the `[secrets.files]` entries from their providers. A locked vault or missing
+/// item is a warning and the file keeps its last fetched value.
+fn write_provider_secrets(config: &Config, machine_id: &str, home: &Path) {
+    let synced_dirs = config.effective_dirs(machine_id);
+    let synced_files = config.effective_dotfiles(machine_id);
+    for (file, reference) in &config.secrets.files {
+        if !crate::config::is_safe_dotfile_path(file) {
+            Output::warning(&format!("  {} (unsafe secret file path, skipping)", file));
+            continue;
+        }
+        // A synced path would carry the fetched secret into the repo
+        let synced = synced_files.iter().any(|e| e.path() == file)
+            || synced_dirs
+                .iter()
+                .any(|d| file.starts_with(&format!("{}/", d.trim_end_matches('/'))));
+        if synced {
+            Output::warning(&format!(
+                "  {} (secret file is also synced through git, skipping)",
+                file
+            ));
+            continue;
+        }
+
+        let value = match crate::security::providers::resolve(reference) {
+            Ok(value) => value,
+            Err(e) => {
+                Output::warning(&format!("  {} (secret not fetched: {})", file, e));
+                continue;
+            }
+        };
+        let dest = home.join(file);
+        if std::fs::read(&dest).is_ok_and(|current| current == value.as_bytes()) {
+            continue;
+        }
+        let written = dest
+            .parent()
+            .map_or(Ok(()), std::fs::create_dir_all)
+            .map_err(anyhow::Error::from)
+            .and_then(|()| crate::security::write_owner_only(&dest, value.as_bytes()));
+        match written {
+            Ok(()) => Output::info(&format!("  {} (fetched secret)", file)),
+            Err(e) => Output::warning(&format!("  {} (failed to write secret: {})", file, e)),
+        }
+    }
+}
+
 /// Sync secrets from collab repos to local projects
 pub fn sync_collab_secrets(config: &Config, home: &Path, state: &mut SyncState) -> Result<()> {
     use crate::sync::{backup_file, create_backup_dir};
@@ -430,7 +737,7 @@ pub fn sync_collab_secrets(config: &Config, home: &Path, state: &mut SyncState)
     };
 
     // Build map of normalized_url -> list of local checkouts
-    let project_map = build_project_map(&search_paths);
+    let project_map = build_project_map(&search_paths, &config.project_configs);
 
     // Load user's identity for decryption
     let identity = match crate::security::load_identity(None) {
@@ -633,6 +940,125 @@ pub fn sync_collab_secrets(config: &Config, home: &Path, state: &mut SyncState)
     Ok(())
 }
 
+/// Warn about machines running a tether version this one may not understand
+pub fn warn_incompatible_peers(sync_path: &Path, machine_id: &str) {
+    let Ok(machines) = MachineState::list_all(sync_path) else {
+        return;
+    };
+    for peer in crate::sync::incompatible_peers(&machines, machine_id) {
+        Output::warning(&format!(
+            "{} runs tether {} and this machine runs {}; upgrade the older one so both understand what the other syncs",
+            peer.machine_id,
+            peer.cli_version,
+            env!("CARGO_PKG_VERSION")
+        ));
+    }
+}
+
+/// Delete local files that other machines deleted and propagated.
+pub fn apply_remote_deletions(
+    config: &Config,
+    sync_path: &Path,
+    home: &Path,
+    state: &mut SyncState,
+) -> Result<()> {
+    let machines = MachineState::list_all(sync_path)?;
+    let result = crate::sync::deletions::apply_remote_deletions(
+        config, state, home, sync_path, &machines, &mut None,
+    )?;
+    for key in &result.deleted {
+        Output::info(&format!("  {} (deleted on another machine)", key));
+    }
+    for key in &result.kept {
+        Output::warning(&format!(
+            "  {} (deleted on another machine, keeping local edits)",
+            key
+        ));
+    }
+    Ok(())
+}
+
+/// Propagate files deleted locally since the last sync: remove their repo copies and
+/// publish tombstones so other machines delete them too. Asks first unless
+/// `dotfiles.propagate_deletions` is set; without it, non-interactive runs leave
+/// deletions for the next manual sync.
+pub fn propagate_local_deletions(
+    config: &Config,
+    sync_path: &Path,
+    home: &Path,
+    state: &mut SyncState,
+    interactive: bool,
+    dry_run: bool,
+) -> Result<()> {
+    use crate::sync::deletions;
+
+    deletions::prune_tombstones(state, sync_path);
+    let deleted = deletions::detect_local_deletions(config, state, home, sync_path);
+    if deleted.is_empty() {
+        return Ok(());
+    }
+
+    if dry_run {
+        for (key, _) in &deleted {
+            Output::info(&format!("  {} (deleted locally, would propagate)", key));
+        }
+        return Ok(());
+    }
+
+    if !config.dotfiles.propagate_deletions {
+        if !interactive {
+            return Ok(());
+        }
+        println!();
+        Output::section(&format!("Deleted locally ({})", deleted.len()));
+        for (key, _) in &deleted {
+            println!("  {}", key);
+        }
+        println!();
+        if !Prompt::confirm("Delete these on your other machines too?", false)? {
+            // Leave the repo copies alone and stop tracking, so we don't ask again
+            for (key, _) in &deleted {
+                state.files.remove(key);
+            }
+            return Ok(());
+        }
+    }
+
+    for (key, repo_path) in &deleted {
+        deletions::record_deletion(sync_path, state, key, repo_path)?;
+        Output::info(&format!("  {} (deleted)", key));
+    }
+
+    // Deleted chunk manifests leave their chunks unreferenced
+    if config.security.encrypt_dotfiles && sync_path.join(crate::sync::chunks::CHUNKS_DIR).exists()
+    {
+        let key = crate::security::get_encryption_key()?;
+        crate::sync::chunks::gc_unreferenced(sync_path, &key)?;
+    }
+    Ok(())
+}
+
+/// Whether a remote file from a synced dir should be written locally. Dirs with
+/// `create_if_missing = false` are only updated where they already exist; files
+/// outside this machine's dirs keep the old always-apply behavior.
+fn dir_materializes(config: &Config, machine_id: &str, home: &Path, rel: &str) -> bool {
+    let entry = config
+        .effective_dir_entries(machine_id)
+        .into_iter()
+        .find(|e| {
+            let dir = e.path().strip_prefix("~/").unwrap_or(e.path());
+            let dir = dir.trim_end_matches('/');
+            rel == dir || rel.starts_with(&format!("{}/", dir))
+        });
+    match entry {
+        Some(entry) if !entry.create_if_missing() => {
+            let dir = entry.path().strip_prefix("~/").unwrap_or(entry.path());
+            home.join(dir.trim_end_matches('/')).exists()
+        }
+        _ => true,
+    }
+}
+
 /// Copy owner executable bit from source to dest.
 /// Git tracks this bit, so it travels across machines via the sync repo.
 #[cfg(unix)]
@@ -649,6 +1075,88 @@ fn preserve_executable_bit(source: &Path, dest: &Path) {
     }
 }
 
+/// Copy a local dotfile into its profile directory in the sync repo (encrypted if
+/// enabled) when it changed since the last sync. Returns whether anything was written.
+pub fn export_dotfile(
+    config: &Config,
+    sync_path: &Path,
+    state: &mut SyncState,
+    source: &Path,
+    file: &str,
+    profile: &str,
+    shared: bool,
+) -> Result<bool> {
+    if state.disk_unchanged(file, source) {
+        return Ok(false);
+    }
+    let Ok(content) = std::fs::read(source) else {
+        return Ok(false);
+    };
+    let hash = crate::sha256_hex(&content);
+    let file_changed = state
+        .files
+        .get(file)
+        .map(|f| f.hash != hash)
+        .unwrap_or(true);
+    if !file_changed {
+        // Touched but identical: remember the new mtime so it isn't re-read next time
+        state.record_disk(file, source);
+        return Ok(false);
+    }
+
+    let encrypted = config.security.encrypt_dotfiles;
+    let repo_path = crate::sync::dotfile_to_repo_path_profiled(file, encrypted, profile, shared);
+    let dest = sync_path.join(&repo_path);
+    let key = if encrypted {
+        Some(crate::security::get_encryption_key()?)
+    } else {
+        None
+    };
+    let wrote = !repo_copy_matches(sync_path, &dest, &content, key.as_deref());
+    if wrote {
+        if let Some(parent) = dest.parent() {
+            std::fs::create_dir_all(parent)?;
+        }
+        match &key {
+            Some(key) => std::fs::write(&dest, crate::security::encrypt(&content, key)?)?,
+            None => std::fs::write(&dest, &content)?,
+        }
+        #[cfg(unix)]
+        preserve_executable_bit(source, &dest);
+        warn_if_broken(config, file, source, &content);
+    }
+
+    state.update_file(file, hash);
+    state.record_disk(file, source);
+    Ok(wrote)
+}
+
+/// Warn about a pushed file that fails its syntax check; other machines would get the
+/// broken copy on their next sync
+fn warn_if_broken(config: &Config, file: &str, source: &Path, content: &[u8]) {
+    if !config.dotfiles.lint {
+        return;
+    }
+    if let Some(problem) = crate::sync::lint::check(file, source, content) {
+        Output::warning(&format!("  {} may be broken ({})", file, problem));
+    }
+}
+
+/// Whether the repo copy at `dest` already holds `content` (decrypting with `key`
+/// when set). Rewriting it would only churn the repo, since encryption uses a fresh
+/// nonce each time.
+fn repo_copy_matches(sync_path: &Path, dest: &Path, content: &[u8], key: Option<&[u8]>) -> bool {
+    let Ok(stored) = std::fs::read(dest) else {
+        return false;
+    };
+    let plaintext = match key {
+        Some(key) => crate::security::decrypt(&stored, key)
+            .and_then(|p| crate::sync::chunks::read_from_repo(sync_path, dest, p, key)),
+        None => Ok(stored),
+    };
+    plaintext.is_ok_and(|p| p == content)
+}
+
 /// Write decrypted content with secure permissions (0o600 on Unix)
 fn write_decrypted(path: &Path, contents: &[u8]) -> Result<()> {
     crate::security::write_owner_only(path, contents)
@@ -671,6 +1179,32 @@ fn backup_and_write_dotfile(
         }
         backup_file(backup_dir.as_ref().unwrap(), "dotfiles", file, local_file)?;
     }
+    write_dotfile(local_file, enc_file, plaintext)
+}
+
+/// Snapshot the local copy of a conflicted dotfile into this run's `conflict-` backup
+/// before a resolution replaces it, so `tether restore` can undo the resolution
+fn snapshot_conflict(
+    conflict_backup: &mut Option<PathBuf>,
+    file: &str,
+    local_file: &Path,
+) -> Result<()> {
+    use crate::sync::{backup_file, create_conflict_backup_dir};
+    if local_file.exists() {
+        if conflict_backup.is_none() {
+            *conflict_backup = Some(create_conflict_backup_dir()?);
+        }
+        backup_file(
+            conflict_backup.as_ref().unwrap(),
+            "dotfiles",
+            file,
+            local_file,
+        )?;
+    }
+    Ok(())
+}
+
+fn write_dotfile(local_file: &Path, enc_file: &Path, plaintext: &[u8]) -> Result<()> {
     if let Some(parent) = local_file.parent() {
         std::fs::create_dir_all(parent)?;
     }
@@ -680,6 +1214,36 @@ fn backup_and_write_dotfile(
     Ok(())
 }
 
+/// Merge non-overlapping local and remote edits of a text dotfile, using the version
+/// this machine last synced (found in the repo history by its hash) as the base.
+/// `None` when the edits overlap or no base is available.
+fn auto_merge_dotfile(
+    sync_path: &Path,
+    repo_path: &str,
+    key: &[u8],
+    last_synced_hash: Option<&str>,
+    local: &[u8],
+    remote: &[u8],
+) -> Option<Vec<u8>> {
+    let base_hash = last_synced_hash?;
+    let local = std::str::from_utf8(local).ok()?;
+    let remote = std::str::from_utf8(remote).ok()?;
+    let git = GitBackend::open(sync_path).ok()?;
+    let base = git
+        .file_log(repo_path, 50)
+        .ok()?
+        .into_iter()
+        .find_map(|entry| {
+            let encrypted = git.show_at_commit(&entry.commit_hash, repo_path).ok()?;
+            let plaintext = crate::security::decrypt(&encrypted, key).ok()?;
+            (crate::sha256_hex(&plaintext) == base_hash).then_some(plaintext)
+        })?;
+    let base = String::from_utf8(base).ok()?;
+    crate::sync::merge::three_way_merge(&base, local, remote).map(String::into_bytes)
+}
+
+/// Apply remote changes from the sync repo to local files. `only` restricts the pass
+/// to a single home-relative path (e.g. `.zshrc` or `.config/nvim/init.lua`).
 pub fn decrypt_from_repo(
     config: &Config,
     sync_path: &Path,
@@ -687,8 +1251,13 @@ pub fn decrypt_from_repo(
     state: &mut SyncState,
     machine_state: &MachineState,
     interactive: bool,
+    only: Option<&str>,
 ) -> Result<()> {
-    use crate::sync::{detect_conflict, ConflictResolution, ConflictState};
+    use crate::config::ConflictStrategy;
+    use crate::sync::{
+        detect_conflict, last_write_wins, AutoResolution, ConflictResolution, ConflictState,
+        ResolvedSide,
+    };
 
     let key = crate::security::get_encryption_key()?;
     let dotfiles_dir = sync_path.join("dotfiles");
@@ -697,9 +1266,10 @@ pub fn decrypt_from_repo(
 
     // Create backup directory for this sync (lazily - only if needed)
     let mut backup_dir: Option<PathBuf> = None;
+    // Local copies replaced by conflict resolutions, kept apart from routine backups
+    let mut conflict_backup: Option<PathBuf> = None;
 
     let machine_id = &state.machine_id.clone();
-    let profile_name = config.profile_name(machine_id).to_string();
 
     // Migrate flat repo to profiled layout on first sync after config v2 migration
     if let Err(e) = crate::sync::migrate_repo_to_profiled(sync_path, config, machine_id) {
@@ -723,10 +1293,11 @@ pub fn decrypt_from_repo(
         let create_if_missing = entry.create_if_missing() || crate::sync::is_glob_pattern(pattern);
 
         let shared = config.is_dotfile_shared(machine_id, pattern);
+        let profile_name = config.dotfile_profile(machine_id, pattern);
 
         // Expand glob pattern by scanning sync repo for matching .enc files
         // Check both profiled and flat dirs for backwards compat
-        let subdir = if shared { "shared" } else { &profile_name };
+        let subdir = if shared { "shared" } else { profile_name };
         let profiled_dir = sync_path.join("profiles").join(subdir);
         let mut expanded = if profiled_dir.exists() {
             crate::sync::expand_from_sync_repo(pattern, &profiled_dir)
@@ -742,6 +1313,10 @@ pub fn decrypt_from_repo(
         }
 
         for file in expanded {
+            if only.is_some_and(|o| o != file) {
+                continue;
+            }
+
             // Skip if this dotfile is ignored on this machine
             if machine_state.ignored_dotfiles.iter().any(|f| f == &file) {
                 continue;
@@ -752,11 +1327,17 @@ pub fn decrypt_from_repo(
                 sync_path,
                 &file,
                 true, // encrypted
-                &profile_name,
+                profile_name,
                 shared,
             );
             let enc_file = sync_path.join(&repo_path);
 
+            // Neither side touched since they last matched: nothing to decrypt or compare
+            if state.in_sync_on_disk(&file, &home.join(&file), &enc_file) {
+                conflict_state.remove_conflict(&file);
+                continue;
+            }
+
             if enc_file.exists() {
                 let encrypted_content = std::fs::read(&enc_file)?;
                 match crate::security::decrypt(&encrypted_content, &key) {
@@ -791,8 +1372,111 @@ pub fn decrypt_from_repo(
                                     &remote_hash,
                                     last_synced_hash,
                                 ) {
+                                    // Edits that don't overlap merge without asking; the
+                                    // export that follows pushes the result
+                                    if let Some(merged) = auto_merge_dotfile(
+                                        sync_path,
+                                        &repo_path,
+                                        &key,
+                                        last_synced_hash,
+                                        lc,
+                                        &plaintext,
+                                    ) {
+                                        snapshot_conflict(
+                                            &mut conflict_backup,
+                                            &file,
+                                            &local_file,
+                                        )?;
+                                        write_dotfile(&local_file, &enc_file, &merged)?;
+                                        // A merge that fails its check is handled like
+                                        // any other conflict below
+                                        if verify_applied(
+                                            config,
+                                            home,
+                                            &file,
+                                            conflict_backup.as_deref(),
+                                        )? {
+                                            state.update_file(&file, remote_hash.clone());
+                                            Output::info(&format!(
+                                                "  {} (merged local and remote changes)",
+                                                file
+                                            ));
+                                            conflict_state.remove_conflict(&file);
+                                            continue;
+                                        }
+                                    }
+
+                                    if matches!(
+                                        config.sync.strategy,
+                                        ConflictStrategy::LastWriteWins
+                                    ) {
+                                        let local_modified = std::fs::metadata(&local_file)
+                                            .and_then(|m| m.modified())
+                                            .ok()
+                                            .map(chrono::DateTime::<chrono::Utc>::from);
+                                        let remote_commit = GitBackend::open(sync_path)
+                                            .and_then(|git| git.file_log(&repo_path, 1))
+                                            .ok()
+                                            .and_then(|log| log.into_iter().next());
+                                        let kept = last_write_wins(
+                                            local_modified,
+                                            remote_commit.as_ref().map(|c| c.date),
+                                        );
+                                        let mut resolution = AutoResolution {
+                                            file_path: file.clone(),
+                                            kept,
+                                            overwritten_hash: conflict.remote_hash.clone(),
+                                            backup: None,
+                                            commit: remote_commit.map(|c| c.commit_hash),
+                                            resolved_at: chrono::Utc::now(),
+                                        };
+                                        if kept == ResolvedSide::Remote {
+                                            snapshot_conflict(
+                                                &mut conflict_backup,
+                                                &file,
+                                                &local_file,
+                                            )?;
+                                            write_dotfile(&local_file, &enc_file, &plaintext)?;
+                                            if !verify_applied(
+                                                config,
+                                                home,
+                                                &file,
+                                                conflict_backup.as_deref(),
+                                            )? {
+                                                new_conflicts.push((
+                                                    file.to_string(),
+                                                    conflict.local_hash.clone(),
+                                                    conflict.remote_hash.clone(),
+                                                ));
+                                                continue;
+                                            }
+                                            state.update_file(&file, remote_hash.clone());
+                                            state.record_in_sync(&file, &local_file, &enc_file);
+                                            resolution.overwritten_hash =
+                                                conflict.local_hash.clone();
+                                            resolution.backup = conflict_backup
+                                                .as_ref()
+                                                .and_then(|d| d.file_name())
+                                                .map(|n| n.to_string_lossy().into_owned());
+                                            resolution.commit = None;
+                                        }
+                                        // A kept local copy is pushed over the remote one by
+                                        // the export that follows
+                                        Output::info(&format!(
+                                            "  {} (conflict - kept newer {} copy)",
+                                            file,
+                                            if kept == ResolvedSide::Remote {
+                                                "remote"
+                                            } else {
+                                                "local"
+                                            }
+                                        ));
+                                        conflict_state.record_auto_resolution(resolution);
+                                        continue;
+                                    }
+
                                     if interactive {
-                                        conflict.show_diff()?;
+                                        conflict.show_diff(false)?;
                                         let resolution = conflict.prompt_resolution()?;
 
                                         match resolution {
@@ -800,16 +1484,34 @@ pub fn decrypt_from_repo(
                                                 conflict_state.remove_conflict(&file);
                                             }
                                             ConflictResolution::UseRemote => {
-                                                backup_and_write_dotfile(
-                                                    &mut backup_dir,
+                                                snapshot_conflict(
+                                                    &mut conflict_backup,
+                                                    &file,
+                                                    &local_file,
+                                                )?;
+                                                write_dotfile(&local_file, &enc_file, &plaintext)?;
+                                                if verify_applied(
+                                                    config,
+                                                    home,
+                                                    &file,
+                                                    conflict_backup.as_deref(),
+                                                )? {
+                                                    state.update_file(&file, remote_hash.clone());
+                                                    conflict_state.remove_conflict(&file);
+                                                } else {
+                                                    new_conflicts.push((
+                                                        file.to_string(),
+                                                        conflict.local_hash.clone(),
+                                                        conflict.remote_hash.clone(),
+                                                    ));
+                                                }
+                                            }
+                                            ConflictResolution::Merged => {
+                                                snapshot_conflict(
+                                                    &mut conflict_backup,
                                                     &file,
                                                     &local_file,
-                                                    &enc_file,
-                                                    &plaintext,
                                                 )?;
-                                                conflict_state.remove_conflict(&file);
-                                            }
-                                            ConflictResolution::Merged => {
                                                 conflict.launch_merge_tool(&config.merge, home)?;
                                                 conflict_state.remove_conflict(&file);
                                             }
@@ -853,6 +1555,18 @@ pub fn decrypt_from_repo(
                                 &enc_file,
                                 &plaintext,
                             )?;
+                            // Record the applied content as synced so it isn't
+                            // mistaken for a local edit on the next run. A version
+                            // that failed its check stays "remote changed" and is
+                            // retried once fixed upstream.
+                            if verify_applied(config, home, &file, backup_dir.as_deref())? {
+                                state.update_file(&file, remote_hash);
+                                state.record_in_sync(&file, &local_file, &enc_file);
+                            }
+                        } else if local_hash.as_deref() == Some(remote_hash.as_str())
+                            && last_synced_hash == Some(remote_hash.as_str())
+                        {
+                            state.record_in_sync(&file, &local_file, &enc_file);
                         }
                         conflict_state.remove_conflict(&file);
                     }
@@ -879,70 +1593,111 @@ pub fn decrypt_from_repo(
         conflict_state.save()?;
     }
 
+    if let Some(name) = conflict_backup
+        .as_ref()
+        .and_then(|d| d.file_name())
+        .map(|n| n.to_string_lossy())
+    {
+        Output::dim(&format!(
+            "  Local copies replaced by conflict resolutions saved: tether restore file --from {}",
+            name
+        ));
+    }
+
+    // Removals inside synced dirs, before applying what's left
+    if only.is_none() {
+        apply_dir_removals(config, sync_path, home, state, interactive)?;
+    }
+
     // Decrypt global config directories
     let configs_dir = sync_path.join("configs");
     if configs_dir.exists() {
         use walkdir::WalkDir;
+        let mut files: Vec<(PathBuf, String)> = Vec::new();
         for entry in WalkDir::new(&configs_dir).follow_links(false) {
             let entry = match entry {
                 Ok(e) => e,
                 Err(_) => continue,
             };
+            if !entry.file_type().is_file() || !entry.path().to_string_lossy().ends_with(".enc") {
+                continue;
+            }
 
-            if entry.file_type().is_file() {
-                let file_path = entry.path();
-                let file_name = file_path.to_string_lossy();
+            let rel_path = entry
+                .path()
+                .strip_prefix(&configs_dir)
+                .map_err(|e| anyhow::anyhow!("Failed to strip prefix: {}", e))?;
+            let rel_path_str = rel_path.to_string_lossy();
+            let rel_path_no_enc = rel_path_str.trim_end_matches(".enc");
+            if only.is_some_and(|o| o != rel_path_no_enc) {
+                continue;
+            }
 
-                if file_name.ends_with(".enc") {
-                    let rel_path = file_path
-                        .strip_prefix(&configs_dir)
-                        .map_err(|e| anyhow::anyhow!("Failed to strip prefix: {}", e))?;
-                    let rel_path_str = rel_path.to_string_lossy();
-                    let rel_path_no_enc = rel_path_str.trim_end_matches(".enc");
+            // Validate path is safe (defense-in-depth)
+            if !crate::config::is_safe_dotfile_path(rel_path_no_enc) {
+                Output::warning(&format!("  {} (unsafe path, skipping)", rel_path_no_enc));
+                continue;
+            }
+            if !dir_materializes(config, &state.machine_id, home, rel_path_no_enc) {
+                continue;
+            }
+            let state_key = format!("~/{}", rel_path_no_enc);
+            if state.in_sync_on_disk(&state_key, &home.join(rel_path_no_enc), entry.path()) {
+                continue;
+            }
+            files.push((entry.path().to_path_buf(), rel_path_no_enc.to_string()));
+        }
 
-                    // Validate path is safe (defense-in-depth)
-                    if !crate::config::is_safe_dotfile_path(rel_path_no_enc) {
-                        Output::warning(&format!("  {} (unsafe path, skipping)", rel_path_no_enc));
-                        continue;
-                    }
+        // Decrypt and hash local copies in parallel; files are written in order below
+        let cached: &SyncState = state;
+        let decrypted: Vec<_> = files
+            .par_iter()
+            .map(|(file_path, rel)| {
+                let plaintext = std::fs::read(file_path).ok().map(|encrypted| {
+                    crate::security::decrypt(&encrypted, &key).and_then(|plaintext| {
+                        crate::sync::chunks::read_from_repo(sync_path, file_path, plaintext, &key)
+                    })
+                });
+                let local_hash = cached.local_hash(&format!("~/{}", rel), &home.join(rel));
+                (plaintext, local_hash)
+            })
+            .collect();
 
-                    if let Ok(encrypted_content) = std::fs::read(file_path) {
-                        match crate::security::decrypt(&encrypted_content, &key) {
-                            Ok(plaintext) => {
-                                let local_file = home.join(rel_path_no_enc);
-                                if let Some(parent) = local_file.parent() {
-                                    std::fs::create_dir_all(parent)?;
-                                }
-                                // Only write if local unchanged since last sync AND remote differs
-                                let state_key = format!("~/{}", rel_path_no_enc);
-                                let last_synced_hash =
-                                    state.files.get(&state_key).map(|f| f.hash.as_str());
-                                let remote_hash = crate::sha256_hex(&plaintext);
-                                let local_hash = std::fs::read(&local_file)
-                                    .ok()
-                                    .map(|c| crate::sha256_hex(&c));
-                                let local_unchanged = local_hash.as_deref() == last_synced_hash;
-                                if local_unchanged && local_hash.as_ref() != Some(&remote_hash) {
-                                    write_decrypted(&local_file, &plaintext)?;
-                                    #[cfg(unix)]
-                                    preserve_executable_bit(file_path, &local_file);
-                                }
-                            }
-                            Err(e) => {
-                                Output::warning(&format!(
-                                    "  ~/{} (failed to decrypt: {})",
-                                    rel_path_no_enc, e
-                                ));
-                            }
-                        }
+        for ((file_path, rel_path_no_enc), (plaintext, local_hash)) in files.iter().zip(decrypted) {
+            match plaintext {
+                Some(Ok(plaintext)) => {
+                    let local_file = home.join(rel_path_no_enc);
+                    if let Some(parent) = local_file.parent() {
+                        std::fs::create_dir_all(parent)?;
+                    }
+                    // Only write if local unchanged since last sync AND remote differs
+                    let state_key = format!("~/{}", rel_path_no_enc);
+                    let last_synced_hash = state.files.get(&state_key).map(|f| f.hash.as_str());
+                    let remote_hash = crate::sha256_hex(&plaintext);
+                    let local_unchanged = local_hash.as_deref() == last_synced_hash;
+                    if local_unchanged && local_hash.as_ref() != Some(&remote_hash) {
+                        write_decrypted(&local_file, &plaintext)?;
+                        #[cfg(unix)]
+                        preserve_executable_bit(file_path, &local_file);
+                        state.update_file(&state_key, remote_hash);
+                        state.record_in_sync(&state_key, &local_file, file_path);
+                    } else if local_unchanged && local_hash.as_ref() == Some(&remote_hash) {
+                        state.record_in_sync(&state_key, &local_file, file_path);
                     }
                 }
+                Some(Err(e)) => {
+                    Output::warning(&format!(
+                        "  ~/{} (failed to decrypt: {})",
+                        rel_path_no_enc, e
+                    ));
+                }
+                None => {}
             }
         }
     }
 
     // Decrypt project-local configs
-    if config.project_configs.enabled {
+    if config.project_configs.enabled && only.is_none() {
         decrypt_project_configs(config, sync_path, home, machine_state, state, &key)?;
     }
 
@@ -960,6 +1715,11 @@ pub fn prompt_new_items(
 ) -> Result<bool> {
     let encrypted = config.security.encrypt_dotfiles;
     let current_profile = config.profile_name(machine_id).to_string();
+    // Inherited profiles' files are already synced to this machine
+    let inherited_profiles: Vec<String> = config
+        .profile_chain(&current_profile)
+        .map(|chain| chain.iter().map(|p| p.to_string()).collect())
+        .unwrap_or_default();
     let profiles_dir = sync_path.join("profiles");
 
     // Gather current profile's dotfile paths
@@ -983,7 +1743,10 @@ pub fn prompt_new_items(
             }
             let dir_name = entry.file_name().to_string_lossy().to_string();
             // Skip current profile and shared (shared is already accessible)
-            if dir_name == current_profile || dir_name == "shared" {
+            if dir_name == current_profile
+                || dir_name == "shared"
+                || inherited_profiles.contains(&dir_name)
+            {
                 continue;
             }
             // Only scan known profile directories
@@ -1197,7 +1960,7 @@ fn decrypt_project_configs(
         })
         .collect();
 
-    let repo_map = build_project_map(&search_paths);
+    let repo_map = build_project_map(&search_paths, &config.project_configs);
 
     // Find all unique project names from encrypted files
     let mut projects_in_sync: HashSet<String> = HashSet::new();
@@ -1234,6 +1997,9 @@ fn decrypt_project_configs(
 
     // Process each project
     for project_name in &projects_in_sync {
+        if !config.project_configs.allows_repo(project_name) {
+            continue;
+        }
         // Skip projects that belong to a team (team sync handles those)
         if let Some(teams) = &config.teams {
             if crate::sync::find_team_for_project(project_name, &teams.teams).is_some() {
@@ -1278,6 +2044,22 @@ fn decrypt_project_configs(
                     }
                 }
 
+                // Patterns with create_if_missing = false only update checkouts
+                // that already have the file
+                let creates = config
+                    .project_configs
+                    .patterns
+                    .iter()
+                    .find(|p| crate::sync::project_pattern_matches(p.path(), rel_path_no_enc))
+                    .is_none_or(|p| p.create_if_missing());
+                if !creates
+                    && !checkouts
+                        .iter()
+                        .any(|c| c.join(rel_path_no_enc).symlink_metadata().is_ok())
+                {
+                    continue;
+                }
+
                 if let Ok(encrypted_content) = std::fs::read(enc_file) {
                     match crate::security::decrypt(&encrypted_content, key) {
                         Ok(plaintext) => {
@@ -1380,7 +2162,7 @@ fn decrypt_project_configs(
 /// Sync tether config from remote (always, independent of config file list)
 /// Only applies remote if local config hasn't changed since last sync (to avoid overwriting local edits)
 /// Returns Some(config) if remote config was applied, None otherwise
-pub fn sync_tether_config(sync_path: &Path, home: &Path) -> Result<Option<Config>> {
+pub fn sync_tether_config(sync_path: &Path) -> Result<Option<Config>> {
     let new_path = sync_path.join("configs/tether/config.toml.enc");
     let legacy_path = sync_path.join("dotfiles/tether/config.toml.enc");
     let enc_file = if new_path.exists() {
@@ -1398,7 +2180,7 @@ pub fn sync_tether_config(sync_path: &Path, home: &Path) -> Result<Option<Config
 
     match crate::security::decrypt(&encrypted_content, &key) {
         Ok(plaintext) => {
-            let local_config_path = home.join(".tether/config.toml");
+            let local_config_path = Config::config_path()?;
             let local_content = std::fs::read(&local_config_path).ok();
 
             let remote_hash = crate::sha256_hex(&plaintext);
@@ -1442,8 +2224,8 @@ pub fn sync_tether_config(sync_path: &Path, home: &Path) -> Result<Option<Config
 }
 
 /// Export tether config to sync repo (always, independent of config file list)
-pub fn export_tether_config(sync_path: &Path, home: &Path, state: &mut SyncState) -> Result<()> {
-    let config_path = home.join(".tether/config.toml");
+pub fn export_tether_config(sync_path: &Path, state: &mut SyncState) -> Result<()> {
+    let config_path = Config::config_path()?;
 
     if !config_path.exists() {
         return Ok(());
@@ -1487,6 +2269,7 @@ pub fn sync_directories(
 
     let configs_dir = sync_path.join("configs");
     std::fs::create_dir_all(&configs_dir)?;
+    let mut wrote_any = false;
 
     for dir_path in &config.effective_dirs(machine_id) {
         // Validate path is safe (security: prevents path traversal via synced config)
@@ -1506,90 +2289,435 @@ pub fn sync_directories(
             continue;
         }
 
+        if dry_run {
+            continue;
+        }
+
         if expanded_path.is_file() {
-            if let Ok(content) = std::fs::read(&expanded_path) {
-                let hash = crate::sha256_hex(&content);
-                let file_changed = state
-                    .files
-                    .get(dir_path)
-                    .map(|f| f.hash != hash)
-                    .unwrap_or(true);
-
-                if file_changed && !dry_run {
-                    let rel_path = expanded_path.strip_prefix(home).unwrap_or(&expanded_path);
-                    let dest = configs_dir.join(rel_path);
-
-                    if let Some(parent) = dest.parent() {
-                        std::fs::create_dir_all(parent)?;
-                    }
+            let rel_path = expanded_path.strip_prefix(home).unwrap_or(&expanded_path);
+            wrote_any |=
+                export_config_file(config, sync_path, state, &expanded_path, rel_path, dir_path)?;
+        } else if expanded_path.is_dir() {
+            let files: Vec<PathBuf> = WalkDir::new(&expanded_path)
+                .follow_links(false)
+                .into_iter()
+                .filter_map(|e| e.ok())
+                .filter(|e| e.file_type().is_file())
+                .map(|e| e.into_path())
+                .filter(|path| {
+                    let rel_to_home = path.strip_prefix(home).unwrap_or(path);
+                    !crate::sync::is_dir_file_excluded(
+                        &config.dotfiles,
+                        &rel_to_home.to_string_lossy(),
+                    )
+                })
+                .collect();
+
+            // Hash and encrypt in parallel; state is updated afterwards, in order
+            let key = if config.security.encrypt_dotfiles {
+                Some(crate::security::get_encryption_key()?)
+            } else {
+                None
+            };
+            let shared_state: &SyncState = state;
+            let outcomes: Vec<Result<ConfigExport>> = files
+                .par_iter()
+                .map(|file_path| {
+                    let rel_to_home = file_path.strip_prefix(home).unwrap_or(file_path);
+                    write_config_export(
+                        config,
+                        sync_path,
+                        shared_state,
+                        key.as_deref(),
+                        file_path,
+                        rel_to_home,
+                        &format!("~/{}", rel_to_home.display()),
+                    )
+                })
+                .collect();
+            for (file_path, outcome) in files.iter().zip(outcomes) {
+                let rel_to_home = file_path.strip_prefix(home).unwrap_or(file_path);
+                let state_key = format!("~/{}", rel_to_home.display());
+                wrote_any |= apply_config_export(state, file_path, &state_key, outcome?);
+            }
 
-                    if config.security.encrypt_dotfiles {
-                        let key = crate::security::get_encryption_key()?;
-                        let encrypted = crate::security::encrypt(&content, &key)?;
-                        let enc_dest = PathBuf::from(format!("{}.enc", dest.display()));
-                        std::fs::write(&enc_dest, encrypted)?;
-                        #[cfg(unix)]
-                        preserve_executable_bit(&expanded_path, &enc_dest);
-                    } else {
-                        std::fs::write(&dest, &content)?;
-                        #[cfg(unix)]
-                        preserve_executable_bit(&expanded_path, &dest);
-                    }
+            let dir_rel = expanded_path
+                .strip_prefix(home)
+                .unwrap_or(&expanded_path)
+                .to_string_lossy()
+                .to_string();
+            wrote_any |= record_dir_removals(config, sync_path, state, &expanded_path, &dir_rel)?;
+        }
+    }
 
-                    state.update_file(dir_path, hash);
-                }
+    // Rewritten or shrunk files can leave chunks nothing references anymore
+    if wrote_any
+        && config.security.encrypt_dotfiles
+        && sync_path.join(crate::sync::chunks::CHUNKS_DIR).exists()
+    {
+        let key = crate::security::get_encryption_key()?;
+        crate::sync::chunks::gc_unreferenced(sync_path, &key)?;
+    }
+
+    Ok(())
+}
+
+/// Pull the sync repo, tolerating an unreachable remote. Returns whether it was
+/// unreachable, in which case the sync carries on with local data only.
+fn pull_or_offline(git: &GitBackend) -> Result<bool> {
+    let pb = Progress::spinner("Fetching changes...");
+    let result = git
+        .with_progress(Progress::transfer(&pb, "Fetching changes..."))
+        .with_stall_report(Progress::stall(&pb))
+        .pull();
+    pb.finish_and_clear();
+    match result {
+        Ok(()) => Ok(false),
+        Err(e) if crate::sync::git::is_offline_error(&e) => {
+            Output::warning(&format!("Offline, syncing locally: {}", e));
+            Ok(true)
+        }
+        Err(e) => Err(e),
+    }
+}
+
+/// Push local commits. When offline they stay queued for the next sync.
+fn push_or_queue(git: &GitBackend, offline: bool) -> Result<()> {
+    let pending = git.unpushed_commits()?;
+    if pending == 0 {
+        return Ok(());
+    }
+    if !offline {
+        let pb = Progress::spinner("Pushing changes...");
+        let result = git
+            .with_progress(Progress::transfer(&pb, "Pushing changes..."))
+            .with_stall_report(Progress::stall(&pb))
+            .push();
+        pb.finish_and_clear();
+        match result {
+            Ok(()) => return Ok(()),
+            Err(e) if crate::sync::git::is_offline_error(&e) => {
+                Output::warning(&format!("Push failed: {}", e));
             }
-        } else if expanded_path.is_dir() {
-            for entry in WalkDir::new(&expanded_path).follow_links(false) {
-                let entry = match entry {
-                    Ok(e) => e,
-                    Err(_) => continue,
-                };
+            Err(e) => return Err(e),
+        }
+    }
+    Output::info(&format!(
+        "{} commit(s) queued, will push when the remote is reachable",
+        pending
+    ));
+    Ok(())
+}
 
-                if entry.file_type().is_file() {
-                    let file_path = entry.path();
-                    let rel_to_home = file_path.strip_prefix(home).unwrap_or(file_path);
-                    let state_key = format!("~/{}", rel_to_home.display());
+/// Update a synced dir's manifest from its local contents, removing the repo copies
+/// of files this machine deleted and of excluded files synced before they were
+/// excluded. Returns whether any repo file was removed.
+fn record_dir_removals(
+    config: &Config,
+    sync_path: &Path,
+    state: &mut SyncState,
+    dir: &Path,
+    dir_rel: &str,
+) -> Result<bool> {
+    use crate::sync::dir_manifest::{local_files, DirManifest};
 
-                    if let Ok(content) = std::fs::read(file_path) {
-                        let hash = crate::sha256_hex(&content);
-                        let file_changed = state
-                            .files
-                            .get(&state_key)
-                            .map(|f| f.hash != hash)
-                            .unwrap_or(true);
+    let excluded = |f: &str| {
+        crate::sync::is_dir_file_excluded(&config.dotfiles, &format!("{}/{}", dir_rel, f))
+    };
 
-                        if file_changed && !dry_run {
-                            let dest = configs_dir.join(rel_to_home);
+    // Excluded files leave the repo without counting as removals, so other machines
+    // keep their local copies
+    let prefix = format!("~/{}/", dir_rel);
+    let mut dropped: Vec<String> = state
+        .files
+        .keys()
+        .filter_map(|key| key.strip_prefix(&prefix))
+        .filter(|f| excluded(f))
+        .map(str::to_string)
+        .collect();
+    dropped.sort();
+    for file in &dropped {
+        remove_dir_file_from_repo(config, sync_path, state, dir_rel, file)?;
+        Output::info(&format!(
+            "  ~/{}/{} (excluded, removed from repo)",
+            dir_rel, file
+        ));
+    }
 
-                            if let Some(parent) = dest.parent() {
-                                std::fs::create_dir_all(parent)?;
-                            }
+    let mut manifest = DirManifest::load(sync_path, dir_rel)?;
+    manifest.files.retain(|f| !excluded(f));
+    let present = local_files(dir)
+        .into_iter()
+        .filter(|f| !excluded(f))
+        .collect();
+    let removed = manifest.record(&present, |f| {
+        state.files.contains_key(&format!("~/{}/{}", dir_rel, f))
+    });
+    for file in &removed {
+        remove_dir_file_from_repo(config, sync_path, state, dir_rel, file)?;
+        Output::info(&format!("  ~/{}/{} (removed)", dir_rel, file));
+    }
+    manifest.save(sync_path, dir_rel)?;
+    Ok(!removed.is_empty() || !dropped.is_empty())
+}
 
-                            if config.security.encrypt_dotfiles {
-                                let key = crate::security::get_encryption_key()?;
-                                let encrypted = crate::security::encrypt(&content, &key)?;
-                                let enc_dest = PathBuf::from(format!("{}.enc", dest.display()));
-                                std::fs::write(&enc_dest, encrypted)?;
-                                #[cfg(unix)]
-                                preserve_executable_bit(file_path, &enc_dest);
-                            } else {
-                                std::fs::write(&dest, &content)?;
-                                #[cfg(unix)]
-                                preserve_executable_bit(file_path, &dest);
-                            }
+/// Delete a synced dir file's repo copy and forget it in state
+fn remove_dir_file_from_repo(
+    config: &Config,
+    sync_path: &Path,
+    state: &mut SyncState,
+    dir_rel: &str,
+    file: &str,
+) -> Result<()> {
+    let mut repo_file = sync_path.join("configs").join(dir_rel).join(file);
+    if config.security.encrypt_dotfiles {
+        repo_file = PathBuf::from(format!("{}.enc", repo_file.display()));
+    }
+    if repo_file.exists() {
+        std::fs::remove_file(&repo_file)?;
+    }
+    if let Some(parent) = repo_file.parent() {
+        crate::sync::deletions::remove_empty_dirs(parent, sync_path);
+    }
+    state.files.remove(&format!("~/{}/{}", dir_rel, file));
+    Ok(())
+}
 
-                            state.update_file(&state_key, hash);
-                        }
-                    }
+/// Apply removals other machines recorded in synced dirs' manifests: unchanged local
+/// copies are backed up and deleted, and emptied subdirectories pruned. Like
+/// propagating deletions, this asks first unless `dotfiles.propagate_deletions` is
+/// set; without it, non-interactive runs leave removals for the next manual sync.
+fn apply_dir_removals(
+    config: &Config,
+    sync_path: &Path,
+    home: &Path,
+    state: &mut SyncState,
+    interactive: bool,
+) -> Result<()> {
+    use crate::sync::dir_manifest::DirManifest;
+    use crate::sync::{backup_file, create_backup_dir};
+
+    // (home-relative path, synced dir it's in)
+    let mut removals: Vec<(String, String)> = Vec::new();
+    for dir in config.effective_dirs(&state.machine_id) {
+        if !crate::config::is_safe_dotfile_path(&dir) {
+            continue;
+        }
+        let dir_rel = dir.strip_prefix("~/").unwrap_or(&dir).trim_end_matches('/');
+        let manifest = DirManifest::load(sync_path, dir_rel)?;
+        for file in manifest.removed.keys() {
+            let rel = format!("{}/{}", dir_rel, file);
+            if !crate::config::is_safe_dotfile_path(&rel) {
+                continue;
+            }
+            let state_key = format!("~/{}", rel);
+            let Some(synced_hash) = state.files.get(&state_key).map(|f| f.hash.clone()) else {
+                continue;
+            };
+            match std::fs::read(home.join(&rel))
+                .ok()
+                .map(|c| crate::sha256_hex(&c))
+            {
+                Some(hash) if hash != synced_hash => {
+                    Output::warning(&format!(
+                        "  ~/{} (removed on another machine, keeping local edits)",
+                        rel
+                    ));
+                }
+                Some(_) => {
+                    removals.push((rel, dir_rel.to_string()));
+                    continue;
                 }
+                None => {}
+            }
+            state.files.remove(&state_key);
+        }
+    }
+    if removals.is_empty() {
+        return Ok(());
+    }
+
+    if !config.dotfiles.propagate_deletions {
+        if !interactive {
+            return Ok(());
+        }
+        println!();
+        Output::section(&format!("Removed on another machine ({})", removals.len()));
+        for (rel, _) in &removals {
+            println!("  ~/{}", rel);
+        }
+        println!();
+        if !Prompt::confirm("Delete these here too?", false)? {
+            // Keep the local copies and stop tracking them, so we don't ask again
+            for (rel, _) in &removals {
+                state.files.remove(&format!("~/{}", rel));
             }
+            return Ok(());
         }
     }
 
+    let backup_dir = create_backup_dir()?;
+    for (rel, dir_rel) in &removals {
+        let local_file = home.join(rel);
+        backup_file(&backup_dir, "configs", rel, &local_file)?;
+        std::fs::remove_file(&local_file)?;
+        if let Some(parent) = local_file.parent() {
+            crate::sync::deletions::remove_empty_dirs(parent, &home.join(dir_rel));
+        }
+        Output::info(&format!("  ~/{} (removed on another machine)", rel));
+        state.files.remove(&format!("~/{}", rel));
+    }
     Ok(())
 }
 
+/// Copy a file from a synced directory into `configs/` in the sync repo when it
+/// changed since the last sync. Returns whether anything was written.
+fn export_config_file(
+    config: &Config,
+    sync_path: &Path,
+    state: &mut SyncState,
+    source: &Path,
+    rel_to_home: &Path,
+    state_key: &str,
+) -> Result<bool> {
+    let key = if config.security.encrypt_dotfiles {
+        Some(crate::security::get_encryption_key()?)
+    } else {
+        None
+    };
+    let outcome = write_config_export(
+        config,
+        sync_path,
+        state,
+        key.as_deref(),
+        source,
+        rel_to_home,
+        state_key,
+    )?;
+    Ok(apply_config_export(state, source, state_key, outcome))
+}
+
+/// What exporting one config file did, for `apply_config_export` to record
+enum ConfigExport {
+    /// Size and mtime match the last sync
+    Unchanged,
+    /// Touched but content identical to the last sync
+    Touched,
+    /// New content; `wrote` is false when the repo already held it. `problem` is why
+    /// the pushed file failed its syntax check.
+    Changed {
+        hash: String,
+        wrote: bool,
+        problem: Option<String>,
+    },
+}
+
+/// The repo-writing half of `export_config_file`. Only reads `state`, so many files
+/// can be prepared in parallel before their outcomes are applied in order.
+fn write_config_export(
+    config: &Config,
+    sync_path: &Path,
+    state: &SyncState,
+    key: Option<&[u8]>,
+    source: &Path,
+    rel_to_home: &Path,
+    state_key: &str,
+) -> Result<ConfigExport> {
+    if state.disk_unchanged(state_key, source) {
+        return Ok(ConfigExport::Unchanged);
+    }
+    let Ok(content) = std::fs::read(source) else {
+        return Ok(ConfigExport::Unchanged);
+    };
+    let hash = crate::sha256_hex(&content);
+    let file_changed = state
+        .files
+        .get(state_key)
+        .map(|f| f.hash != hash)
+        .unwrap_or(true);
+    if !file_changed {
+        return Ok(ConfigExport::Touched);
+    }
+
+    let dest = sync_path.join("configs").join(rel_to_home);
+    if let Some(parent) = dest.parent() {
+        std::fs::create_dir_all(parent)?;
+    }
+
+    let mut wrote = false;
+    if let Some(key) = key {
+        let enc_dest = PathBuf::from(format!("{}.enc", dest.display()));
+        if !repo_copy_matches(sync_path, &enc_dest, &content, Some(key)) {
+            write_encrypted_config(config, sync_path, &enc_dest, &content, key)?;
+            #[cfg(unix)]
+            preserve_executable_bit(source, &enc_dest);
+            wrote = true;
+        }
+    } else if !repo_copy_matches(sync_path, &dest, &content, None) {
+        std::fs::write(&dest, &content)?;
+        #[cfg(unix)]
+        preserve_executable_bit(source, &dest);
+        wrote = true;
+    }
+    let problem = if wrote && config.dotfiles.lint {
+        crate::sync::lint::check(&rel_to_home.to_string_lossy(), source, &content)
+    } else {
+        None
+    };
+    Ok(ConfigExport::Changed {
+        hash,
+        wrote,
+        problem,
+    })
+}
+
+/// Record a config export in `state`. Returns whether the repo was written.
+fn apply_config_export(
+    state: &mut SyncState,
+    source: &Path,
+    state_key: &str,
+    outcome: ConfigExport,
+) -> bool {
+    match outcome {
+        ConfigExport::Unchanged => false,
+        ConfigExport::Touched => {
+            // Remember the new mtime so it isn't re-read next time
+            state.record_disk(state_key, source);
+            false
+        }
+        ConfigExport::Changed {
+            hash,
+            wrote,
+            problem,
+        } => {
+            if let Some(problem) = problem {
+                Output::warning(&format!("  {} may be broken ({})", state_key, problem));
+            }
+            state.update_file(state_key, hash);
+            state.record_disk(state_key, source);
+            wrote
+        }
+    }
+}
+
+/// Encrypt a config file into the repo, chunking it when it's over `chunk_threshold_kb`.
+fn write_encrypted_config(
+    config: &Config,
+    sync_path: &Path,
+    enc_dest: &Path,
+    content: &[u8],
+    key: &[u8],
+) -> Result<()> {
+    let threshold = config.dotfiles.chunk_threshold_kb.saturating_mul(1024);
+    if threshold > 0 && content.len() as u64 >= threshold {
+        crate::sync::chunks::write_chunked(sync_path, enc_dest, content, key)
+    } else {
+        let encrypted = crate::security::encrypt(&crate::sync::chunks::encode_whole(content), key)?;
+        std::fs::write(enc_dest, encrypted)?;
+        Ok(())
+    }
+}
+
 pub fn sync_project_configs(
     config: &Config,
     state: &mut SyncState,
@@ -1601,34 +2729,57 @@ pub fn sync_project_configs(
         find_git_repos, get_remote_url, is_gitignored, normalize_remote_url,
         should_skip_dir_for_project_configs,
     };
+    use crate::sync::project_index::{ProjectEvents, ProjectIndex};
+    use std::collections::BTreeMap;
     use walkdir::WalkDir;
 
     let projects_dir = sync_path.join("projects");
     std::fs::create_dir_all(&projects_dir)?;
 
-    for search_path_str in &config.project_configs.search_paths {
-        let search_path = if let Some(stripped) = search_path_str.strip_prefix("~/") {
-            home.join(stripped)
-        } else {
-            PathBuf::from(search_path_str)
-        };
+    // With the daemon watching the search paths, only revisit repos that changed
+    // since the last scan
+    let scan_started = chrono::Utc::now();
+    let roots = config.project_configs.search_roots(home);
+    let mut index = ProjectIndex::load(&ProjectIndex::settings_hash(config));
+    let incremental = ProjectEvents::live(&roots)
+        .and_then(|events| index.covered_since(&events).map(|since| (events, since)));
+    let mut scanned = BTreeMap::new();
 
+    for search_path in &roots {
         if !search_path.exists() {
             continue;
         }
 
-        let repos = match find_git_repos(&search_path) {
-            Ok(r) => r,
-            Err(_) => continue,
+        let repos = match &incremental {
+            Some((events, since)) if !events.layout_changed_since(&index, *since) => {
+                index.repos_under(search_path)
+            }
+            _ => match find_git_repos(search_path, &config.project_configs) {
+                Ok(r) => r,
+                Err(_) => continue,
+            },
         };
 
         for repo_path in repos {
+            if let Some((events, since)) = &incremental {
+                if let Some(url) = index.repos.get(&repo_path) {
+                    if !events.repo_changed_since(&repo_path, *since) {
+                        scanned.insert(repo_path, url.clone());
+                        continue;
+                    }
+                }
+            }
+
             let remote_url = match get_remote_url(&repo_path) {
                 Ok(url) => url,
                 Err(_) => continue,
             };
 
             let normalized_url = normalize_remote_url(&remote_url);
+            scanned.insert(repo_path.clone(), normalized_url.clone());
+            if !config.project_configs.allows_repo(&normalized_url) {
+                continue;
+            }
 
             // Skip projects that belong to a team (team sync handles those)
             if let Some(teams) = &config.teams {
@@ -1644,10 +2795,17 @@ pub fn sync_project_configs(
                     .into_iter()
                     .filter_entry(|e| {
                         e.file_type().is_file()
-                            || e.file_name()
+                            || (e
+                                .file_name()
                                 .to_str()
                                 .map(|n| !should_skip_dir_for_project_configs(n))
                                 .unwrap_or(true)
+                                && !e.path().strip_prefix(&repo_path).is_ok_and(|rel| {
+                                    crate::sync::is_project_dir_excluded(
+                                        &config.project_configs.exclude_dirs,
+                                        &rel.to_string_lossy(),
+                                    )
+                                }))
                     });
                 for entry in walker {
                     let entry = match entry {
@@ -1660,28 +2818,13 @@ pub fn sync_project_configs(
                     }
 
                     let file_path = entry.path();
-                    let file_name = match file_path.file_name() {
-                        Some(name) => name.to_string_lossy(),
-                        None => continue,
-                    };
-
-                    // Handle ** for directory patterns (e.g., ".idea/**")
-                    let matches = if pattern.contains("**") {
-                        // For ** patterns, match against full relative path
-                        if let Ok(rel_path) = file_path.strip_prefix(&repo_path) {
-                            let rel_str = rel_path.to_string_lossy();
-                            // Convert ** to match any path
-                            let pattern_for_path = pattern.replace("**", "*");
-                            crate::sync::glob_match(&pattern_for_path, &rel_str)
-                        } else {
-                            false
-                        }
-                    } else {
-                        // For single * patterns, match filename only
-                        crate::sync::glob_match(pattern, &file_name)
+                    let Ok(rel_path) = file_path.strip_prefix(&repo_path) else {
+                        continue;
                     };
-
-                    if !matches {
+                    if !crate::sync::project_pattern_matches(
+                        pattern.path(),
+                        &rel_path.to_string_lossy(),
+                    ) {
                         continue;
                     }
 
@@ -1735,6 +2878,14 @@ pub fn sync_project_configs(
         }
     }
 
+    if !dry_run {
+        index.repos = scanned;
+        index.scanned_at = Some(scan_started);
+        if let Err(e) = index.save() {
+            log::warn!("Failed to save project index: {}", e);
+        }
+    }
+
     Ok(())
 }
 
@@ -1748,10 +2899,11 @@ pub async fn build_machine_state(
     let mut machine_state = MachineState::load_from_repo(sync_path, &state.machine_id)?
         .unwrap_or_else(|| MachineState::new(&state.machine_id));
 
-    // Update last_sync time, CLI version, and profile
+    // Update last_sync time, system info, profile and propagated deletions
     machine_state.last_sync = chrono::Utc::now();
-    machine_state.cli_version = env!("CARGO_PKG_VERSION").to_string();
+    machine_state.refresh_system_info();
     machine_state.profile = config.machine_profiles.get(&state.machine_id).cloned();
+    machine_state.deleted_files = state.deleted_files.clone();
 
     // Collect file hashes
     machine_state.files.clear();
@@ -1883,7 +3035,7 @@ pub async fn build_machine_state(
         })
         .collect();
 
-    let project_map = build_project_map(&search_paths);
+    let project_map = build_project_map(&search_paths, &config.project_configs);
     for (normalized_url, checkouts) in project_map {
         use crate::sync::git::checkout_id_from_path;
         use crate::sync::CheckoutInfo;
@@ -1965,7 +3117,7 @@ pub fn sync_team_project_secrets(
         })
         .collect();
 
-    let local_projects = build_project_map(&search_paths);
+    let local_projects = build_project_map(&search_paths, &config.project_configs);
 
     // Try to load user's identity for decryption
     let identity = match crate::security::load_identity(None) {
@@ -2156,8 +3308,9 @@ pub fn sync_team_project_secrets(
 }
 
 /// Team-only sync: skip personal dotfiles/packages, only sync team repos
-async fn run_team_only_sync(config: &Config, dry_run: bool) -> Result<()> {
+async fn run_team_only_sync(config: &Config, dry_run: bool, mode: SyncMode) -> Result<()> {
     let home = crate::home_dir()?;
+    let mode = effective_mode(config, &SyncState::load()?.machine_id, mode)?;
 
     let teams = match &config.teams {
         Some(t) if !t.active.is_empty() => t,
@@ -2188,7 +3341,7 @@ async fn run_team_only_sync(config: &Config, dry_run: bool) -> Result<()> {
             Output::success(&format!("Team '{}' synced", team_name));
 
             // Push changes if we have write access
-            if !team_config.read_only && team_git.has_changes()? {
+            if !team_config.read_only && mode.pushes_local() && team_git.has_changes()? {
                 let state = SyncState::load()?;
                 team_git.commit("Update team configs", &state.machine_id)?;
                 team_git.push()?;
@@ -2199,7 +3352,7 @@ async fn run_team_only_sync(config: &Config, dry_run: bool) -> Result<()> {
     }
 
     // Sync team project secrets to local projects
-    if !dry_run {
+    if !dry_run && mode.applies_remote() {
         let mut state = SyncState::load()?;
         sync_team_project_secrets(config, &home, &mut state)?;
         state.save()?;
@@ -2214,6 +3367,16 @@ mod tests {
     use super::*;
     use tempfile::TempDir;
 
+    /// Embedded and unattended syncs stop on integrity problems instead of asking
+    #[test]
+    fn test_integrity_problems_stop_non_interactive_sync() {
+        let temp = TempDir::new().unwrap();
+        confirm_integrity_problems(&[], temp.path(), false).unwrap();
+        let problems = ["dotfiles/zshrc.enc was replaced".to_string()];
+        let error = confirm_integrity_problems(&problems, temp.path(), false).unwrap_err();
+        assert!(error.to_string().starts_with("Sync stopped"), "{}", error);
+    }
+
     #[test]
     fn test_write_decrypted_creates_file_with_content() {
         let temp = TempDir::new().unwrap();
@@ -2287,6 +3450,99 @@ mod tests {
         assert_eq!(std::fs::read(&path).unwrap(), content);
     }
 
+    #[test]
+    fn test_dir_materializes_respects_create_if_missing() {
+        let home = TempDir::new().unwrap();
+        let mut config = Config::default();
+        config.dotfiles.dirs = vec![
+            "~/.config/nvim".into(),
+            crate::config::DotfileEntry::WithOptions {
+                path: "~/.config/karabiner".to_string(),
+                create_if_missing: false,
+            },
+        ];
+
+        assert!(dir_materializes(
+            &config,
+            "m",
+            home.path(),
+            ".config/nvim/init.lua"
+        ));
+        assert!(!dir_materializes(
+            &config,
+            "m",
+            home.path(),
+            ".config/karabiner/k.json"
+        ));
+        // Unknown dirs keep applying
+        assert!(dir_materializes(
+            &config,
+            "m",
+            home.path(),
+            ".config/htop/htoprc"
+        ));
+
+        std::fs::create_dir_all(home.path().join(".config/karabiner")).unwrap();
+        assert!(dir_materializes(
+            &config,
+            "m",
+            home.path(),
+            ".config/karabiner/k.json"
+        ));
+    }
+
+    /// Without `propagate_deletions`, unattended syncs leave files removed elsewhere
+    /// in place and keep asking on the next manual sync
+    #[test]
+    fn test_dir_removals_wait_for_confirmation() {
+        use crate::sync::dir_manifest::DirManifest;
+
+        let home = TempDir::new().unwrap();
+        let repo = TempDir::new().unwrap();
+        let mut config = Config::default();
+        config.dotfiles.dirs = vec!["~/.config/nvim".into()];
+        let mut state = SyncState {
+            machine_id: "m".to_string(),
+            last_sync: chrono::Utc::now(),
+            files: Default::default(),
+            packages: Default::default(),
+            last_upgrade: None,
+            last_upgrade_with_updates: None,
+            deferred_casks: Vec::new(),
+            deferred_casks_hash: None,
+            pending_installs: Default::default(),
+            dismissed_imports: Default::default(),
+            deleted_files: Default::default(),
+            repo_visibility: None,
+            last_report: None,
+            repo_sizes: Vec::new(),
+        };
+        let file = home.path().join(".config/nvim/init.lua");
+        std::fs::create_dir_all(file.parent().unwrap()).unwrap();
+        std::fs::write(&file, "set number").unwrap();
+        state.update_file("~/.config/nvim/init.lua", crate::sha256_hex(b"set number"));
+        let mut manifest = DirManifest::default();
+        manifest
+            .removed
+            .insert("init.lua".to_string(), chrono::Utc::now());
+        manifest.save(repo.path(), ".config/nvim").unwrap();
+
+        apply_dir_removals(&config, repo.path(), home.path(), &mut state, false).unwrap();
+        assert!(file.exists());
+        assert!(state.files.contains_key("~/.config/nvim/init.lua"));
+    }
+
+    #[test]
+    fn test_home_relative() {
+        let home = Path::new("/home/me");
+        assert_eq!(home_relative(".zshrc", home), ".zshrc");
+        assert_eq!(home_relative("~/.zshrc", home), ".zshrc");
+        assert_eq!(
+            home_relative("/home/me/.config/nvim/", home),
+            ".config/nvim"
+        );
+    }
+
     #[cfg(unix)]
     #[test]
     fn test_preserve_executable_bit() {
@@ -28,8 +28,8 @@ fn sanitize_team_name(name: &str) -> String {
 /// Re-encrypt all .age files in a directory with new recipients
 fn reencrypt_age_files(
     dir: &std::path::Path,
-    identity: &age::x25519::Identity,
-    recipients: &[age::x25519::Recipient],
+    identity: &crate::security::AgeIdentity,
+    recipients: &[crate::security::AgeRecipient],
 ) -> Result<usize> {
     use walkdir::WalkDir;
 
@@ -188,13 +188,12 @@ pub async fn setup() -> Result<()> {
     };
 
     // Step 2: Identity setup
-    let identity_path = Config::config_dir()?.join("identity.age");
-    if !identity_path.exists() {
+    if !crate::security::has_identity() {
         println!();
         Output::info("Encryption identity required");
         Output::dim("An identity is needed to encrypt/decrypt team secrets");
         if Prompt::confirm("Create identity now?", true)? {
-            crate::cli::commands::identity::init().await?;
+            crate::cli::commands::identity::init(false).await?;
         }
     } else {
         Output::success("Encryption identity ✓");
@@ -237,7 +236,7 @@ pub async fn setup() -> Result<()> {
                     .filter_map(|e| e.ok())
                     .any(|e| {
                         std::fs::read_to_string(e.path())
-                            .map(|content| content.trim() == my_pubkey)
+                            .map(|content| content.lines().any(|l| l.trim() == my_pubkey))
                             .unwrap_or(false)
                     })
             } else {
@@ -246,8 +245,12 @@ pub async fn setup() -> Result<()> {
 
             if !am_recipient {
                 let username = std::env::var("USER").unwrap_or_else(|_| "user".to_string());
-                secrets_add_recipient(&identity_pub_path.to_string_lossy(), Some(&username))
-                    .await?;
+                secrets_add_recipient(
+                    Some(&identity_pub_path.to_string_lossy()),
+                    None,
+                    Some(&username),
+                )
+                .await?;
             }
             Output::success("You are a recipient ✓");
         }
@@ -349,7 +352,11 @@ pub async fn add(url: &str, name: Option<&str>, _no_auto_inject: bool) -> Result
     }
 
     let pb = Progress::spinner("Cloning team repository...");
-    GitBackend::clone(url, &team_repo_dir)?;
+    GitBackend::clone_with_progress(
+        url,
+        &team_repo_dir,
+        Some(Progress::transfer(&pb, "Cloning team repository...")),
+    )?;
     Progress::finish_success(&pb, "Team repository cloned");
 
     // Security check: Scan for secrets in team repo
@@ -381,7 +388,7 @@ pub async fn add(url: &str, name: Option<&str>, _no_auto_inject: bool) -> Result
                 team_files.push(rel_str.to_string());
 
                 // Scan for secrets
-                if let Ok(findings) = crate::security::scan_for_secrets(entry.path()) {
+                if let Ok(findings) = crate::security::scan_for_secrets(entry.path(), rel_str) {
                     if !findings.is_empty() {
                         secrets_found = true;
                         Output::warning(&format!(
@@ -391,9 +398,10 @@ pub async fn add(url: &str, name: Option<&str>, _no_auto_inject: bool) -> Result
                         ));
                         for finding in findings.iter().take(2) {
                             Output::warning(&format!(
-                                "    Line {}: {}",
+                                "    Line {}: {} (fingerprint {})",
                                 finding.line_number,
-                                finding.secret_type.description()
+                                finding.secret_type.description(),
+                                finding.fingerprint
                             ));
                         }
                     }
@@ -943,7 +951,7 @@ async fn apply_layer_sync(
                     team_file_path.display()
                 );
 
-                if inject_source_line(&personal_file, &source_line)? {
+                if crate::cli::shell_rc::inject_source_line(&personal_file, &source_line)? {
                     Output::success(&format!("  {} → {} (source injected)", file, personal_name));
                 } else {
                     Output::dim(&format!("  {} → {} (already sourced)", file, personal_name));
@@ -1002,40 +1010,6 @@ async fn apply_layer_sync(
     Ok(())
 }
 
-/// Inject a source line into a shell config file (at the top, after any shebang/comments)
-/// Caller must verify file exists before calling.
-fn inject_source_line(file: &std::path::Path, source_line: &str) -> Result<bool> {
-    let content = std::fs::read_to_string(file)?;
-
-    // Check if already sourced
-    if content.contains(source_line) {
-        return Ok(false);
-    }
-
-    // Find insertion point (after shebang and initial comments)
-    let mut lines: Vec<&str> = content.lines().collect();
-    let mut insert_idx = 0;
-
-    for (i, line) in lines.iter().enumerate() {
-        let trimmed = line.trim();
-        if trimmed.is_empty() || trimmed.starts_with('#') {
-            insert_idx = i + 1;
-        } else {
-            break;
-        }
-    }
-
-    // Insert with a blank line after for readability
-    lines.insert(insert_idx, source_line);
-    if insert_idx + 1 < lines.len() && !lines[insert_idx + 1].is_empty() {
-        lines.insert(insert_idx + 1, "");
-    }
-
-    let new_content = lines.join("\n");
-    std::fs::write(file, new_content)?;
-    Ok(true)
-}
-
 /// Inject an [include] directive into gitconfig (at the top)
 /// Caller must verify file exists before calling.
 fn inject_gitconfig_include(file: &std::path::Path, team_file: &std::path::Path) -> Result<bool> {
@@ -1802,26 +1776,64 @@ fn get_active_team_repo() -> Result<(String, std::path::PathBuf)> {
     Ok((active.clone(), repo_dir))
 }
 
-pub async fn secrets_add_recipient(key: &str, name: Option<&str>) -> Result<()> {
+/// A member's SSH keys from GitHub that can be age recipients (ed25519 and RSA)
+async fn github_recipient_keys(user: &str) -> Result<Vec<String>> {
+    use crate::github::GitHubCli;
+
+    let keys = GitHubCli::get_ssh_keys(user).await?;
+    let total = keys.len();
+    let usable: Vec<String> = keys
+        .into_iter()
+        .filter(|k| crate::security::validate_pubkey(k).is_ok())
+        .collect();
+    if usable.is_empty() {
+        anyhow::bail!(
+            "{} has no ssh-ed25519 or ssh-rsa keys on GitHub ({} other key(s))",
+            user,
+            total
+        );
+    }
+    if usable.len() < total {
+        Output::dim(&format!(
+            "  Skipped {} key(s) of unsupported types",
+            total - usable.len()
+        ));
+    }
+    Ok(usable)
+}
+
+pub async fn secrets_add_recipient(
+    key: Option<&str>,
+    github: Option<&str>,
+    name: Option<&str>,
+) -> Result<()> {
     let (team_name, repo_dir) = get_active_team_repo()?;
     let recipients_dir = repo_dir.join("recipients");
     std::fs::create_dir_all(&recipients_dir)?;
 
     // Validate key format
-    let pubkey = if std::path::Path::new(key).exists() {
-        std::fs::read_to_string(key)?
-    } else {
-        key.to_string()
+    let pubkeys = match (github, key) {
+        (Some(user), _) => github_recipient_keys(user).await?,
+        (None, Some(key)) => {
+            let pubkey = if std::path::Path::new(key).exists() {
+                std::fs::read_to_string(key)?
+            } else {
+                key.to_string()
+            };
+            crate::security::validate_pubkey(&pubkey)?;
+            vec![pubkey.trim().to_string()]
+        }
+        (None, None) => anyhow::bail!("Give a public key or --github <user>"),
     };
-    crate::security::validate_pubkey(&pubkey)?;
 
     // Determine recipient name
     let recipient_name = name
+        .or(github)
         .map(|s| s.to_string())
         .unwrap_or_else(|| std::env::var("USER").unwrap_or_else(|_| "unknown".to_string()));
 
     let pubkey_file = recipients_dir.join(format!("{}.pub", recipient_name));
-    std::fs::write(&pubkey_file, pubkey.trim())?;
+    std::fs::write(&pubkey_file, pubkeys.join("\n"))?;
 
     // Re-encrypt existing secrets with new recipient list
     let recipients = crate::security::load_recipients(&recipients_dir)?;
@@ -1849,6 +1861,10 @@ pub async fn secrets_add_recipient(key: &str, name: Option<&str>) -> Result<()>
         format!("Add recipient: {}", recipient_name)
     };
     git.commit(&commit_msg, "tether")?;
+    crate::security::audit::record(
+        crate::security::audit::AuditEvent::RecipientAdded,
+        &format!("team {}: {}", team_name, recipient_name),
+    );
 
     Output::success(&format!(
         "Added recipient '{}' to team '{}'",
@@ -1883,13 +1899,19 @@ pub async fn secrets_list_recipients() -> Result<()> {
         let entry = entry?;
         if entry.path().extension().is_some_and(|e| e == "pub") {
             if let Some(name) = entry.path().file_stem().and_then(|s| s.to_str()) {
-                let pubkey = std::fs::read_to_string(entry.path())?;
+                let pubkeys = std::fs::read_to_string(entry.path())?;
+                let keys: Vec<&str> = pubkeys.lines().filter(|l| !l.trim().is_empty()).collect();
+                let pubkey = keys.first().map_or("", |k| k.trim());
                 let short_key = if pubkey.len() > 20 {
-                    format!("{}...", &pubkey.trim()[..20])
+                    format!("{}...", &pubkey[..20])
                 } else {
-                    pubkey.trim().to_string()
+                    pubkey.to_string()
                 };
-                println!("  • {} ({})", name, short_key);
+                if keys.len() > 1 {
+                    println!("  • {} ({}, +{} more)", name, short_key, keys.len() - 1);
+                } else {
+                    println!("  • {} ({})", name, short_key);
+                }
             }
         }
     }
@@ -1934,6 +1956,10 @@ pub async fn secrets_remove_recipient(name: &str) -> Result<()> {
         format!("Remove recipient: {}", name)
     };
     git.commit(&commit_msg, "tether")?;
+    crate::security::audit::record(
+        crate::security::audit::AuditEvent::RecipientRemoved,
+        &format!("team {}: {}", team_name, name),
+    );
 
     Output::success(&format!(
         "Removed recipient '{}' from team '{}'",
@@ -1984,13 +2010,12 @@ pub async fn secrets_set(name: &str, value: Option<&str>) -> Result<()> {
     Ok(())
 }
 
-pub async fn secrets_get(name: &str) -> Result<()> {
-    let (_team_name, repo_dir) = get_active_team_repo()?;
+pub async fn secrets_get(name: &str, copy: Option<u64>, quiet: bool) -> Result<()> {
+    let (team_name, repo_dir) = get_active_team_repo()?;
     let secret_file = repo_dir.join("secrets").join(format!("{}.age", name));
 
     if !secret_file.exists() {
-        Output::error(&format!("Secret '{}' not found", name));
-        return Ok(());
+        anyhow::bail!("Secret '{}' not found", name);
     }
 
     // Load user's identity
@@ -2002,9 +2027,12 @@ pub async fn secrets_get(name: &str) -> Result<()> {
     let encrypted = std::fs::read(&secret_file)?;
     let decrypted = crate::security::decrypt_with_identity(&encrypted, &identity)?;
     let value = String::from_utf8(decrypted)?;
+    crate::security::audit::record(
+        crate::security::audit::AuditEvent::SecretRead,
+        &format!("team {}: {}", team_name, name),
+    );
 
-    println!("{}", value);
-    Ok(())
+    super::secrets::output_secret(&value, copy, quiet)
 }
 
 pub async fn secrets_list() -> Result<()> {
@@ -2210,8 +2238,9 @@ pub async fn files_unignore(file: &str) -> Result<()> {
     Ok(())
 }
 
-/// Show diff between local and team version of a file
-pub async fn files_diff(file: Option<&str>) -> Result<()> {
+/// Show diff between local and team version of a file, with secret values masked
+/// unless `reveal`
+pub async fn files_diff(file: Option<&str>, reveal: bool) -> Result<()> {
     use similar::{ChangeTag, TextDiff};
 
     let (team_name, _repo_dir) = get_active_team_repo()?;
@@ -2241,26 +2270,46 @@ pub async fn files_diff(file: Option<&str>) -> Result<()> {
             continue;
         }
 
-        let team_content = std::fs::read_to_string(&team_file)?;
-        let home_content = std::fs::read_to_string(&home_file)?;
+        let team_bytes = std::fs::read(&team_file)?;
+        let home_bytes = std::fs::read(&home_file)?;
 
-        if team_content == home_content {
+        if team_bytes == home_bytes {
             println!("--- {}: identical", filename);
             continue;
         }
 
+        if crate::sync::is_binary(&team_bytes) || crate::sync::is_binary(&home_bytes) {
+            println!(
+                "--- {}: {}",
+                filename,
+                crate::sync::binary_summary(home_bytes.len())
+            );
+            continue;
+        }
+
+        let team_content = String::from_utf8_lossy(&team_bytes);
+        let home_content = String::from_utf8_lossy(&home_bytes);
+
         println!("--- {} (team vs local)", filename);
         let diff = TextDiff::from_lines(&team_content, &home_content);
 
-        for change in diff.iter_all_changes() {
+        let changes: Vec<_> = diff
+            .iter_all_changes()
+            .filter(|change| change.tag() != ChangeTag::Equal)
+            .collect();
+        let lines: Vec<String> = changes.iter().map(|change| change.to_string()).collect();
+        let lines = if reveal {
+            lines
+        } else {
+            crate::security::redact_lines(&lines)
+        };
+        for (change, line) in changes.iter().zip(&lines) {
             let (sign, color) = match change.tag() {
                 ChangeTag::Delete => ("-", "\x1b[31m"), // red
                 ChangeTag::Insert => ("+", "\x1b[32m"), // green
                 ChangeTag::Equal => (" ", ""),
             };
-            if change.tag() != ChangeTag::Equal {
-                print!("{}{}{}\x1b[0m", color, sign, change);
-            }
+            print!("{}{}{}\x1b[0m", color, sign, line);
         }
         println!();
     }
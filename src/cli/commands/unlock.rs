@@ -1,26 +1,229 @@
 use crate::cli::{Output, Prompt};
+use crate::config::Config;
 use anyhow::Result;
 
-pub async fn run() -> Result<()> {
+pub async fn run(keychain: bool) -> Result<()> {
     if !crate::security::has_encryption_key() {
         Output::error("No encrypted key found. Run 'tether init' first.");
         return Err(anyhow::anyhow!("No encryption key"));
     }
 
+    if keychain && !enable_login_keychain()? {
+        return Ok(());
+    }
+
     if crate::security::is_unlocked() {
-        Output::success("Key is already unlocked");
+        // Moves a file-cached key into the keychain after the switch
+        crate::security::get_encryption_key()?;
+        if keychain {
+            Output::success(&format!(
+                "Key stored in the {}",
+                crate::security::login_keychain_name()
+            ));
+        } else {
+            Output::success("Key is already unlocked");
+        }
         return Ok(());
     }
 
+    if crate::security::hardware::has_identity() {
+        Output::info("Unlocking with your YubiKey (touch it when it blinks)...");
+        match crate::security::unlock_with_hardware_key() {
+            Ok(Some(_)) => {
+                crate::security::audit::record(
+                    crate::security::audit::AuditEvent::Unlock,
+                    "yubikey",
+                );
+                Output::success("Key unlocked with YubiKey");
+                return Ok(());
+            }
+            Ok(None) => Output::dim("  The key isn't wrapped for this YubiKey yet"),
+            Err(e) => Output::warning(&format!("YubiKey unlock failed: {}", e)),
+        }
+    }
+
     let passphrase = Prompt::password("Passphrase")?;
-    crate::security::unlock_with_passphrase(&passphrase)?;
+    let key = crate::security::unlock_with_passphrase(&passphrase)?;
+    crate::security::audit::record(crate::security::audit::AuditEvent::Unlock, "passphrase");
+    offer_hardware_unlock(&key)?;
 
-    Output::success("Key unlocked and cached");
+    if crate::security::biometrics_required() {
+        Output::success("Key unlocked; using it from now on needs Touch ID");
+    } else if crate::security::login_keychain_enabled() {
+        Output::success(&format!(
+            "Key unlocked and stored in the {}",
+            crate::security::login_keychain_name()
+        ));
+    } else {
+        Output::success("Key unlocked and cached");
+    }
     Ok(())
 }
 
+/// Wrap the key for this machine's YubiKey identity so the next unlock is a touch
+fn offer_hardware_unlock(key: &[u8]) -> Result<()> {
+    if !crate::security::hardware::has_identity()
+        || !Prompt::confirm("Unlock with your YubiKey from now on?", true)?
+    {
+        return Ok(());
+    }
+    let recipient = crate::security::get_public_key()?;
+    let sync_path = crate::sync::SyncEngine::sync_path()?;
+    crate::security::hardware::wrap_key(&sync_path, key, &recipient)?;
+    Output::success("Encryption key can now be unlocked with this YubiKey");
+    Output::dim("  Run 'tether sync' to make it available on your other machines");
+    Ok(())
+}
+
+/// Turn on `security.login_keychain` after spelling out what it trades away
+fn enable_login_keychain() -> Result<bool> {
+    if cfg!(target_os = "linux") && !crate::security::secret_service::available() {
+        anyhow::bail!(
+            "Secret Service unlock needs secret-tool (install libsecret-tools or libsecret)"
+        );
+    }
+    if !cfg!(any(target_os = "macos", target_os = "linux")) {
+        anyhow::bail!("Login keychain unlock is only available on macOS and Linux");
+    }
+    let mut config = Config::load()?;
+    if config.security.login_keychain {
+        return Ok(true);
+    }
+
+    Output::warning("The key will unlock with your login instead of a passphrase:");
+    Output::list_item("The daemon keeps syncing after a reboot without asking");
+    if cfg!(target_os = "linux") {
+        Output::list_item(
+            "Any program in your logged-in session can read the key from the keyring",
+        );
+    } else {
+        Output::list_item(
+            "Anything that runs tether in your logged-in session can decrypt your dotfiles",
+        );
+    }
+    Output::list_item("The setting syncs, so your other machines switch on their next unlock");
+    Output::list_item("'tether lock' removes the key; the passphrase is needed again after that");
+    let prompt = format!(
+        "Store the encryption key in the {}?",
+        crate::security::login_keychain_name()
+    );
+    if !Prompt::confirm(&prompt, false)? {
+        Output::info("Cancelled");
+        return Ok(false);
+    }
+
+    config.security.login_keychain = true;
+    config.save()?;
+    Ok(true)
+}
+
 pub async fn lock() -> Result<()> {
     crate::security::clear_cached_key()?;
     Output::success("Key cache cleared");
     Ok(())
 }
+
+/// Protect the encryption key with a new passphrase. Only the passphrase-protected
+/// copy in the sync repo changes, so other machines keep their unlocked key and use
+/// the new passphrase on their next unlock.
+pub async fn rotate_passphrase() -> Result<()> {
+    if !crate::security::has_encryption_key() {
+        Output::error("No encrypted key found. Run 'tether init' first.");
+        return Err(anyhow::anyhow!("No encryption key"));
+    }
+
+    let old = Prompt::password("Current passphrase")?;
+    crate::security::verify_passphrase(&old)?;
+    let new = Prompt::password("New passphrase")?;
+    if new.len() < 8 {
+        anyhow::bail!("Passphrase must be at least 8 characters");
+    }
+    if new == old {
+        anyhow::bail!("The new passphrase is the same as the current one");
+    }
+    if Prompt::password("Confirm new passphrase")? != new {
+        anyhow::bail!("Passphrases do not match");
+    }
+
+    let config = Config::load()?;
+    let sync_path = crate::sync::SyncEngine::sync_path()?;
+    super::secrets::commit_change(&config, &sync_path, "Rotate passphrase", || {
+        crate::security::change_passphrase(&old, &new)
+    })?;
+
+    Output::success("Passphrase changed");
+    Output::dim("  Other machines stay unlocked and use the new passphrase from their next unlock");
+    Ok(())
+}
+
+/// Replace the encryption key with a new one and re-encrypt the sync repo under it.
+/// The passphrase stays the same; other machines notice the new key-version marker on
+/// their next sync and ask to unlock again.
+pub async fn rotate_key() -> Result<()> {
+    if !crate::security::has_encryption_key() {
+        Output::error("No encrypted key found. Run 'tether init' first.");
+        return Err(anyhow::anyhow!("No encryption key"));
+    }
+
+    Output::warning("Rotating the key re-encrypts every encrypted file in the sync repo:");
+    Output::list_item("Other machines must run 'tether unlock' (same passphrase) to sync again");
+    Output::list_item(
+        "Versions in the repo history from before the rotation stay under the old key, \
+         so history, show and restore can't decrypt them",
+    );
+    if crate::security::hardware::has_identity() {
+        Output::list_item(
+            "Other machines' YubiKeys are set up again on their next passphrase unlock",
+        );
+    }
+    if !Prompt::confirm("Rotate the encryption key?", false)? {
+        return Ok(());
+    }
+
+    let passphrase = Prompt::password("Passphrase")?;
+    crate::security::verify_passphrase(&passphrase)?;
+
+    let config = Config::load()?;
+    let sync_path = crate::sync::SyncEngine::sync_path()?;
+    let machine_id = crate::sync::SyncState::load()?.machine_id;
+    let _sync_lock = crate::sync::acquire_sync_lock(true)?;
+    let git = crate::sync::GitBackend::open(&sync_path)?;
+    let pb = crate::cli::Progress::spinner("Fetching changes...");
+    let pulled = git.pull();
+    pb.finish_and_clear();
+    pulled?;
+
+    // Decrypted after the pull, in case the key file changed meanwhile
+    let old_key = crate::security::decrypt_with_passphrase(&passphrase)?;
+    let new_key = crate::security::generate_key();
+    let mut version = 0;
+    let pb = crate::cli::Progress::spinner("Re-encrypting sync repo...");
+    let files = crate::sync::rekey::rotate(&git, &old_key, &new_key, |_| {
+        // Keys wrapped for YubiKeys hold the old key; only this machine's can be redone
+        let wrapped = sync_path.join(crate::security::hardware::WRAPPED_KEYS_DIR);
+        if wrapped.exists() {
+            std::fs::remove_dir_all(&wrapped)?;
+        }
+        version = crate::security::install_rotated_key(&new_key, &passphrase, &machine_id)?;
+        if crate::security::hardware::has_identity() {
+            let recipient = crate::security::get_public_key()?;
+            crate::security::hardware::wrap_key(&sync_path, &new_key, &recipient)?;
+        }
+        // Signed with the new key, which isn't cached yet
+        if config.security.encrypt_dotfiles {
+            crate::sync::integrity::sign(&sync_path, &new_key, &machine_id)?;
+        }
+        git.commit("Rotate encryption key", &machine_id)?;
+        git.push()
+    });
+    pb.finish_and_clear();
+    let files = files?;
+    crate::security::unlock_with_key(&new_key)?;
+
+    Output::success(&format!(
+        "Encryption key rotated (version {}), {} file(s) re-encrypted",
+        version, files
+    ));
+    Output::dim("  Run 'tether unlock' on your other machines");
+    Ok(())
+}
@@ -1,8 +1,11 @@
+pub mod clipboard;
 pub mod commands;
 pub mod output;
 pub mod progress;
 pub mod prompts;
+pub mod shell_rc;
 
+#[cfg(feature = "cli")]
 pub use commands::Cli;
 pub use output::Output;
 pub use progress::Progress;
@@ -1,13 +1,48 @@
 use indicatif::{ProgressBar, ProgressStyle};
 use owo_colors::OwoColorize;
+use std::io::IsTerminal;
+use std::sync::atomic::{AtomicBool, Ordering};
 use std::time::Duration;
 
 use super::Output;
+use crate::sync::git::{ProgressFn, Stall, StallFn, TransferProgress};
+
+/// Set by the dashboard on the `tether sync` it spawns: transfer progress is then also
+/// written to stderr as JSON lines for it to display
+pub const PROGRESS_JSON_ENV: &str = "TETHER_PROGRESS_JSON";
+
+/// `--quiet`: no spinners or progress lines
+static QUIET: AtomicBool = AtomicBool::new(false);
 
 pub struct Progress;
 
 impl Progress {
+    pub fn set_quiet(quiet: bool) {
+        QUIET.store(quiet, Ordering::Relaxed);
+    }
+
+    pub fn is_quiet() -> bool {
+        QUIET.load(Ordering::Relaxed)
+    }
+
+    /// Whether progress is written as plain lines instead of animated: with `--quiet`,
+    /// or when stderr (where spinners draw) isn't a terminal, as under cron, CI or a pipe
+    fn plain() -> bool {
+        Self::is_quiet() || !std::io::stderr().is_terminal()
+    }
+
+    /// A hidden bar for plain output, printing `message` as a line unless quiet
+    fn plain_line(message: &str) -> ProgressBar {
+        if !Self::is_quiet() {
+            println!("{}", message);
+        }
+        ProgressBar::hidden()
+    }
+
     pub fn spinner(message: &str) -> ProgressBar {
+        if Self::plain() {
+            return Self::plain_line(message);
+        }
         let pb = ProgressBar::new_spinner();
         pb.set_style(
             ProgressStyle::default_spinner()
@@ -21,6 +56,11 @@ impl Progress {
     }
 
     pub fn bar(total: u64, message: &str) -> ProgressBar {
+        if Self::plain() {
+            let pb = Self::plain_line(message);
+            pb.set_length(total);
+            return pb;
+        }
         let pb = ProgressBar::new(total);
         pb.set_style(
             ProgressStyle::default_bar()
@@ -32,11 +72,49 @@ impl Progress {
         pb
     }
 
+    /// `GitBackend` progress callback showing git's transfer progress after `message`
+    pub fn transfer(pb: &ProgressBar, message: &str) -> ProgressFn {
+        let pb = pb.clone();
+        let message = message.to_string();
+        let json = std::env::var_os(PROGRESS_JSON_ENV).is_some();
+        std::sync::Arc::new(move |progress: &TransferProgress| {
+            pb.set_message(format!("{} {}", message, progress.to_string().dimmed()));
+            if json {
+                if let Ok(line) = serde_json::to_string(progress) {
+                    eprintln!("{}", line);
+                }
+            }
+        })
+    }
+
+    /// `GitBackend` stall callback printing what a long transfer is doing above `pb`
+    pub fn stall(pb: &ProgressBar) -> StallFn {
+        let pb = pb.clone();
+        std::sync::Arc::new(move |stall: &Stall| {
+            pb.suspend(|| {
+                Output::warning(&stall.to_string());
+                for hint in stall.hints() {
+                    Output::dim(&format!("  - {}", hint));
+                }
+            });
+        })
+    }
+
     pub fn finish_success(pb: &ProgressBar, message: &str) {
+        if pb.is_hidden() {
+            if !Self::is_quiet() {
+                Output::success(message);
+            }
+            return;
+        }
         pb.finish_with_message(format!("{} {}", Output::CHECK.green(), message));
     }
 
     pub fn finish_error(pb: &ProgressBar, message: &str) {
+        if pb.is_hidden() {
+            Output::error(message);
+            return;
+        }
         pb.finish_with_message(format!("{} {}", Output::CROSS.red(), message));
     }
 }
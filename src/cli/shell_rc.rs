@@ -0,0 +1,135 @@
+//! Adding lines to the user's shell startup files: team source lines, completions and
+//! PATH entries. Every edit checks for the line first, so running it again is a no-op.
+
+use anyhow::Result;
+use std::path::{Path, PathBuf};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Shell {
+    Zsh,
+    Bash,
+    Fish,
+}
+
+impl Shell {
+    /// The user's login shell, from `$SHELL`
+    pub fn detect() -> Option<Self> {
+        let shell = std::env::var("SHELL").ok()?;
+        Self::from_name(shell.rsplit('/').next()?)
+    }
+
+    pub fn from_name(name: &str) -> Option<Self> {
+        match name {
+            "zsh" => Some(Self::Zsh),
+            "bash" => Some(Self::Bash),
+            "fish" => Some(Self::Fish),
+            _ => None,
+        }
+    }
+
+    pub fn name(self) -> &'static str {
+        match self {
+            Self::Zsh => "zsh",
+            Self::Bash => "bash",
+            Self::Fish => "fish",
+        }
+    }
+
+    /// The startup file interactive shells read. macOS Terminal opens bash as a login
+    /// shell, which reads `.bash_profile` rather than `.bashrc`.
+    pub fn rc_file(self, home: &Path) -> PathBuf {
+        match self {
+            Self::Zsh => home.join(".zshrc"),
+            Self::Bash if cfg!(target_os = "macos") => home.join(".bash_profile"),
+            Self::Bash => home.join(".bashrc"),
+            Self::Fish => home.join(".config/fish/config.fish"),
+        }
+    }
+}
+
+/// Inject a source line into a shell config file (at the top, after any shebang/comments)
+/// Caller must verify file exists before calling.
+pub fn inject_source_line(file: &Path, source_line: &str) -> Result<bool> {
+    let content = std::fs::read_to_string(file)?;
+
+    // Check if already sourced
+    if content.contains(source_line) {
+        return Ok(false);
+    }
+
+    // Find insertion point (after shebang and initial comments)
+    let mut lines: Vec<&str> = content.lines().collect();
+    let mut insert_idx = 0;
+
+    for (i, line) in lines.iter().enumerate() {
+        let trimmed = line.trim();
+        if trimmed.is_empty() || trimmed.starts_with('#') {
+            insert_idx = i + 1;
+        } else {
+            break;
+        }
+    }
+
+    // Insert with a blank line after for readability
+    lines.insert(insert_idx, source_line);
+    if insert_idx + 1 < lines.len() && !lines[insert_idx + 1].is_empty() {
+        lines.insert(insert_idx + 1, "");
+    }
+
+    let new_content = lines.join("\n");
+    std::fs::write(file, new_content)?;
+    Ok(true)
+}
+
+/// Append `line` to the end of `file` (creating it if needed) under a `# tether`
+/// comment, for lines that must run after the rest of the file, like completions that
+/// need `compinit`
+pub fn append_line(file: &Path, line: &str) -> Result<bool> {
+    let mut content = std::fs::read_to_string(file).unwrap_or_default();
+    if content.lines().any(|existing| existing.trim() == line) {
+        return Ok(false);
+    }
+    if !content.is_empty() && !content.ends_with('\n') {
+        content.push('\n');
+    }
+    if !content.is_empty() {
+        content.push('\n');
+    }
+    content.push_str("# tether\n");
+    content.push_str(line);
+    content.push('\n');
+    if let Some(parent) = file.parent() {
+        std::fs::create_dir_all(parent)?;
+    }
+    std::fs::write(file, content)?;
+    Ok(true)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::TempDir;
+
+    #[test]
+    fn test_rc_edits_are_idempotent() {
+        let temp = TempDir::new().unwrap();
+        let rc = temp.path().join(".zshrc");
+        std::fs::write(&rc, "# my zshrc\nautoload -Uz compinit && compinit").unwrap();
+
+        assert!(inject_source_line(&rc, "source ~/team.sh").unwrap());
+        assert!(!inject_source_line(&rc, "source ~/team.sh").unwrap());
+        assert!(append_line(&rc, "eval \"$(tether completions zsh)\"").unwrap());
+        assert!(!append_line(&rc, "eval \"$(tether completions zsh)\"").unwrap());
+        assert_eq!(
+            std::fs::read_to_string(&rc).unwrap(),
+            "# my zshrc\nsource ~/team.sh\n\nautoload -Uz compinit && compinit\n\n# tether\neval \"$(tether completions zsh)\"\n"
+        );
+
+        let fish = temp.path().join(".config/fish/config.fish");
+        assert!(append_line(&fish, "tether completions fish | source").unwrap());
+        assert_eq!(
+            std::fs::read_to_string(&fish).unwrap(),
+            "# tether\ntether completions fish | source\n"
+        );
+    }
+}
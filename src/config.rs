@@ -1,8 +1,8 @@
 use anyhow::{bail, Result};
 use chrono::{DateTime, Utc};
 use serde::{Deserialize, Serialize};
-use std::collections::HashMap;
-use std::path::PathBuf;
+use std::collections::{BTreeMap, HashMap};
+use std::path::{Path, PathBuf};
 
 /// Config format version. Bump when making breaking changes that require migration.
 ///
@@ -15,6 +15,85 @@ use std::path::PathBuf;
 pub const CURRENT_CONFIG_VERSION: u32 = 2;
 pub const DEFAULT_PROFILE: &str = "dev";
 
+/// Selects an isolated tether context (config dir, sync repo, daemon). Set by
+/// `--context` so spawned syncs and daemons inherit it.
+pub const CONTEXT_ENV: &str = "TETHER_CONTEXT";
+
+tokio::task_local! {
+    /// Context for one task of a daemon serving several contexts; wins over the env var
+    static TASK_CONTEXT: Option<String>;
+}
+
+/// Run `future` in `context`, for a daemon serving several contexts in one process.
+/// Only code on the task itself sees it, so threads it starts must resolve paths first.
+pub async fn with_context<F: std::future::Future>(context: Option<String>, future: F) -> F::Output {
+    TASK_CONTEXT.scope(context, future).await
+}
+
+/// The context selected by `TETHER_CONTEXT` (or the daemon task), or `None` for the
+/// default one
+pub fn current_context() -> Result<Option<String>> {
+    if let Ok(context) = TASK_CONTEXT.try_with(|c| c.clone()) {
+        return Ok(context);
+    }
+    match std::env::var(CONTEXT_ENV) {
+        Ok(name) if !name.is_empty() && name != "default" => {
+            if !is_valid_context_name(&name) {
+                bail!(
+                    "Invalid context name '{}': use letters, digits, '-' and '_'",
+                    name
+                );
+            }
+            Ok(Some(name))
+        }
+        _ => Ok(None),
+    }
+}
+
+pub fn is_valid_context_name(name: &str) -> bool {
+    !name.is_empty()
+        && name.len() <= 64
+        && name
+            .chars()
+            .all(|c| c.is_ascii_alphanumeric() || c == '-' || c == '_')
+}
+
+/// Contexts set up on this machine: the default one (`None`) if initialized, then
+/// each `~/.tether-<name>` with a config, by name
+pub fn discover_contexts(home: &Path) -> Vec<Option<String>> {
+    let mut named: Vec<String> = std::fs::read_dir(home)
+        .into_iter()
+        .flatten()
+        .flatten()
+        .filter_map(|entry| {
+            let name = entry
+                .file_name()
+                .to_str()?
+                .strip_prefix(".tether-")?
+                .to_string();
+            (is_valid_context_name(&name) && entry.path().join("config.toml").exists())
+                .then_some(name)
+        })
+        .collect();
+    named.sort();
+
+    let default = context_dir(home, None).join("config.toml").exists();
+    default
+        .then_some(None)
+        .into_iter()
+        .chain(named.into_iter().map(Some))
+        .collect()
+}
+
+/// Tether's directory for a context: `~/.tether`, or `~/.tether-<name>` so a second
+/// context shares nothing with the default one
+pub fn context_dir(home: &Path, context: Option<&str>) -> PathBuf {
+    match context {
+        Some(name) => home.join(format!(".tether-{}", name)),
+        None => home.join(".tether"),
+    }
+}
+
 fn default_config_version() -> u32 {
     1
 }
@@ -43,6 +122,10 @@ pub struct Config {
     pub security: SecurityConfig,
     #[serde(default)]
     pub merge: MergeConfig,
+    #[serde(default)]
+    pub report: ReportConfig,
+    #[serde(default)]
+    pub notifications: NotificationsConfig,
     #[serde(skip_serializing_if = "Option::is_none")]
     pub team: Option<TeamConfig>, // Deprecated: kept for backwards compatibility
     #[serde(skip_serializing_if = "Option::is_none")]
@@ -55,6 +138,23 @@ pub struct Config {
     /// Named profiles that restrict what a machine syncs
     #[serde(default, skip_serializing_if = "HashMap::is_empty")]
     pub profiles: HashMap<String, ProfileConfig>,
+    #[serde(default, skip_serializing_if = "SecretsConfig::is_empty")]
+    pub secrets: SecretsConfig,
+}
+
+/// Files filled in from a secret provider at sync time instead of synced through git
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct SecretsConfig {
+    /// Home-relative path -> secret reference (`bw:<item>[#field]`, `tether:<name>`).
+    /// Written owner-only on every sync and never stored in the sync repo.
+    #[serde(default)]
+    pub files: BTreeMap<String, String>,
+}
+
+impl SecretsConfig {
+    pub fn is_empty(&self) -> bool {
+        self.files.is_empty()
+    }
 }
 
 /// Feature toggles - what tether should sync
@@ -97,6 +197,148 @@ impl Default for FeaturesConfig {
 pub struct SyncConfig {
     pub interval: String,
     pub strategy: ConflictStrategy,
+    /// Seconds a git network operation (clone, fetch, push) may run before it's killed
+    #[serde(default = "default_network_timeout_secs")]
+    pub network_timeout_secs: u64,
+    /// Extra attempts for network operations that time out or hit a connection error
+    #[serde(default = "default_network_retries")]
+    pub network_retries: u32,
+    /// Daemon: hold commits locally until synced files have been quiet this many
+    /// minutes, then push them as one commit. 0 pushes on every sync.
+    #[serde(default)]
+    pub batch_quiet_minutes: u64,
+    /// Daemon: hold commits until they change at least this many files (machine
+    /// state doesn't count)
+    #[serde(default)]
+    pub batch_min_files: usize,
+    /// Daemon: push held commits once the oldest is this many minutes old, even if
+    /// the batch isn't quiet or big enough yet
+    #[serde(default = "default_batch_max_minutes")]
+    pub batch_max_minutes: u64,
+    /// Daemon: fetch this often (minutes) and notify when synced dotfiles are behind
+    /// the remote, so they're pulled before being edited. 0 turns it off.
+    #[serde(default)]
+    pub stale_check_minutes: u64,
+    /// Daemon: a sync still running after this many minutes is stuck; the daemon kills
+    /// the processes it's waiting on and carries on. 0 turns the watchdog off.
+    #[serde(default = "default_watchdog_minutes")]
+    pub watchdog_minutes: u64,
+    /// Daemon: sync right away after the machine wakes from sleep or its network
+    /// changes, instead of waiting for the next interval
+    #[serde(default = "default_true")]
+    pub sync_on_wake: bool,
+    /// Daemon: start a new `daemon.log` once it's this many megabytes
+    #[serde(default = "default_log_max_mb")]
+    pub log_max_mb: u64,
+    /// Daemon: start a new `daemon.log` once its oldest entry is this many days old.
+    /// 0 rotates by size only.
+    #[serde(default = "default_log_max_days")]
+    pub log_max_days: u64,
+    /// Daemon: rotated logs to keep (`daemon.log.1` is the newest)
+    #[serde(default = "default_log_keep")]
+    pub log_keep: u32,
+    /// Daemon: `text`, or `json` for one JSON object per log line.
+    /// `TETHER_LOG_FORMAT` overrides it.
+    #[serde(default)]
+    pub log_format: LogFormat,
+    /// Daemon: what scheduled syncs do on battery below `low_battery_percent`:
+    /// `sync` as usual, `stretch` to every fourth interval, or `skip`. Daily package
+    /// upgrades wait unless it's `sync`.
+    #[serde(default)]
+    pub on_low_battery: PowerPolicy,
+    /// Daemon: battery percentage below which `on_low_battery` applies
+    #[serde(default = "default_low_battery_percent")]
+    pub low_battery_percent: u8,
+    /// Daemon: the same as `on_low_battery`, on a metered connection or phone hotspot
+    #[serde(default)]
+    pub on_metered: PowerPolicy,
+}
+
+/// What the daemon does with scheduled syncs while on low battery or a metered
+/// connection. Ordered from least to most held back.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, PartialOrd, Ord, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum PowerPolicy {
+    #[default]
+    Sync,
+    /// Sync every fourth interval, and leave package upgrades for later
+    Stretch,
+    /// Don't sync on schedule or upgrade packages; manual syncs still run
+    Skip,
+}
+
+/// How the daemon writes `daemon.log`
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum LogFormat {
+    #[default]
+    Text,
+    /// One JSON object per line, with the time, level, message and event fields
+    Json,
+}
+
+impl SyncConfig {
+    /// Whether the daemon batches commits instead of pushing every sync
+    pub fn batching_enabled(&self) -> bool {
+        self.batch_quiet_minutes > 0 || self.batch_min_files > 1
+    }
+
+    /// How often the daemon syncs: `interval` as `30s`, `5m`, `1h` or plain seconds,
+    /// at least a minute. Unparseable values fall back to 5 minutes.
+    pub fn interval_duration(&self) -> std::time::Duration {
+        let secs = Self::parse_interval(&self.interval).unwrap_or(300);
+        std::time::Duration::from_secs(secs.max(60))
+    }
+
+    /// Seconds in an interval like `30s`, `5m`, `1h` or `300`, before the one-minute
+    /// minimum; `None` if it doesn't parse
+    pub fn parse_interval(interval: &str) -> Option<u64> {
+        let interval = interval.trim();
+        let (value, unit) = interval.split_at(
+            interval
+                .find(|c: char| !c.is_ascii_digit())
+                .unwrap_or(interval.len()),
+        );
+        let n = value.parse::<u64>().ok()?;
+        match unit {
+            "" | "s" => Some(n),
+            "m" => n.checked_mul(60),
+            "h" => n.checked_mul(3600),
+            _ => None,
+        }
+    }
+}
+
+fn default_network_timeout_secs() -> u64 {
+    60
+}
+
+fn default_network_retries() -> u32 {
+    3
+}
+
+fn default_batch_max_minutes() -> u64 {
+    60
+}
+
+fn default_watchdog_minutes() -> u64 {
+    15
+}
+
+fn default_low_battery_percent() -> u8 {
+    20
+}
+
+fn default_log_max_mb() -> u64 {
+    5
+}
+
+fn default_log_max_days() -> u64 {
+    7
+}
+
+fn default_log_keep() -> u32 {
+    3
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -114,6 +356,42 @@ pub struct BackendConfig {
     #[serde(rename = "type")]
     pub backend_type: BackendType,
     pub url: String,
+    /// Sign commits to the sync repo and check incoming ones on pull
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub signing: Option<SigningConfig>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct SigningConfig {
+    #[serde(default)]
+    pub format: SigningFormat,
+    /// SSH public key path (the private key can stay in ssh-agent) or GPG key id
+    pub key: String,
+    /// What a pull does with incoming commits that aren't validly signed
+    #[serde(default)]
+    pub verify: SignatureCheck,
+    /// SSH `allowed_signers` file listing trusted keys; needed to check SSH signatures
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub allowed_signers: Option<String>,
+}
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum SigningFormat {
+    #[default]
+    Ssh,
+    Gpg,
+}
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum SignatureCheck {
+    /// Sign only
+    #[default]
+    Off,
+    Warn,
+    /// Refuse to pull until the unsigned commits are dealt with
+    Reject,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -124,8 +402,16 @@ pub enum BackendType {
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct PackagesConfig {
+    #[serde(default)]
+    pub mode: PackagesMode,
     #[serde(default)]
     pub remove_unlisted: bool,
+    /// Queue packages other machines added instead of installing them, until
+    /// `tether packages approve`
+    #[serde(default)]
+    pub approve_installs: bool,
+    #[serde(default)]
+    pub trust: PackageTrust,
     #[serde(default = "default_brew_config")]
     pub brew: BrewConfig,
     #[serde(default = "default_npm_config")]
@@ -140,6 +426,59 @@ pub struct PackagesConfig {
     pub uv: UvConfig,
 }
 
+/// Where the package manifests in the sync repo come from
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum PackagesMode {
+    /// Exported from the union of every machine's installed packages
+    #[default]
+    MachineUnion,
+    /// Edited by hand; machines install and remove packages to match them exactly
+    /// and never export
+    ManifestAuthoritative,
+}
+
+/// Which packages from the manifests this machine installs on its own. An empty list
+/// trusts everything for its package managers.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct PackageTrust {
+    /// Taps brew formulae and casks may come from; bare names are from
+    /// "homebrew/core" and "homebrew/cask"
+    #[serde(default)]
+    pub taps: Vec<String>,
+    /// npm, pnpm and bun packages: exact names or whole scopes ("@acme/*")
+    #[serde(default)]
+    pub npm: Vec<String>,
+}
+
+impl PackageTrust {
+    pub fn allows_tap(&self, tap: &str) -> bool {
+        self.taps.is_empty() || self.taps.iter().any(|t| t.eq_ignore_ascii_case(tap))
+    }
+
+    pub fn allows_brew(&self, name: &str, cask: bool) -> bool {
+        self.allows_tap(crate::packages::formula_tap(name, cask))
+    }
+
+    /// Whether an npm manifest entry (optionally `name@version`) is allowed
+    pub fn allows_npm(&self, entry: &str) -> bool {
+        let name = match entry.rfind('@') {
+            Some(i) if i > 0 => &entry[..i],
+            _ => entry,
+        };
+        self.npm.is_empty()
+            || self
+                .npm
+                .iter()
+                .any(|allowed| match allowed.strip_suffix("/*") {
+                    Some(scope) => name
+                        .strip_prefix(scope)
+                        .is_some_and(|rest| rest.starts_with('/')),
+                    None => allowed == name,
+                })
+    }
+}
+
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct BrewConfig {
     pub enabled: bool,
@@ -227,12 +566,19 @@ impl Default for SecurityConfig {
         Self {
             encrypt_dotfiles: true,
             scan_secrets: true,
+            login_keychain: false,
+            require_biometrics: false,
+            audit_sync: false,
+            encrypt_manifests: false,
+            session_minutes: 0,
         }
     }
 }
 
-/// A dotfile entry - either a simple string path or an object with options
-#[derive(Debug, Clone, Serialize, Deserialize)]
+/// A dotfile entry - either a simple string path or an object with options.
+/// Also used for synced dirs and project file patterns, where `create_if_missing = false`
+/// only applies remote files on machines that already have the dir (or file).
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
 #[serde(untagged)]
 pub enum DotfileEntry {
     /// Simple string path (create_if_missing defaults to true)
@@ -272,6 +618,18 @@ impl DotfileEntry {
     }
 }
 
+impl From<&str> for DotfileEntry {
+    fn from(path: &str) -> Self {
+        DotfileEntry::Simple(path.to_string())
+    }
+}
+
+impl From<String> for DotfileEntry {
+    fn from(path: String) -> Self {
+        DotfileEntry::Simple(path)
+    }
+}
+
 /// Validates a dotfile path is safe from path traversal attacks.
 /// Rejects absolute paths and paths containing `..` components.
 /// Allows `~` prefix (home-relative paths) as these are expanded safely.
@@ -364,13 +722,98 @@ impl ProfileDotfileEntry {
 pub struct DotfilesConfig {
     pub files: Vec<DotfileEntry>,
     #[serde(default)]
-    pub dirs: Vec<String>,
+    pub dirs: Vec<DotfileEntry>,
+    /// Store encrypted config files at least this large (KiB) as deduplicated chunks,
+    /// so small edits don't re-upload the whole file. 0 disables chunking; keep it off
+    /// while any machine runs a tether version that can't read chunk manifests.
+    #[serde(default)]
+    pub chunk_threshold_kb: u64,
+    /// Propagate local deletions of synced files to other machines without asking.
+    /// When off, manual syncs ask first and the daemon leaves deletions alone.
+    #[serde(default)]
+    pub propagate_deletions: bool,
+    /// Files to skip inside synced dirs, on top of the built-in cache/state exclusions.
+    /// Patterns with a `/` match the path, others the file name; `!pattern` re-includes.
+    #[serde(default)]
+    pub exclude: Vec<String>,
+    /// Apply the built-in exclusions (`.DS_Store`, swap/undo files, `.zcompdump`, ...)
+    #[serde(default = "default_true")]
+    pub default_excludes: bool,
+    /// Checks run after a remote version of a dotfile is applied, keyed by path
+    /// (e.g. `".zshrc" = "zsh -n {file}"`). If the check fails the previous copy is
+    /// put back. Commands are split on whitespace and run without a shell.
+    #[serde(default, skip_serializing_if = "HashMap::is_empty")]
+    pub verify: HashMap<String, String>,
+    /// Warn when a changed shell rc, JSON, TOML or YAML file doesn't parse before it's
+    /// pushed
+    #[serde(default = "default_true")]
+    pub lint: bool,
+}
+
+/// Programs allowed as `dotfiles.verify` checks. The config is synced between machines,
+/// so an arbitrary command here would run everywhere on the next sync.
+const ALLOWED_VERIFY_COMMANDS: &[&str] = &[
+    "bash", "fish", "git", "jq", "nvim", "plutil", "sh", "ssh", "tmux", "vim", "zsh",
+];
+
+impl DotfilesConfig {
+    /// The verify command for `file`, with `{file}` and a leading `~/` expanded.
+    /// `None` if the file has no check; an error if the program isn't allowed.
+    pub fn verify_command(&self, file: &str, home: &Path) -> Result<Option<Vec<String>>> {
+        let Some(command) = self.verify.get(file) else {
+            return Ok(None);
+        };
+        let local_path = home.join(file);
+        let args: Vec<String> = command
+            .split_whitespace()
+            .map(|arg| {
+                let arg = arg.replace("{file}", &local_path.to_string_lossy());
+                match arg.strip_prefix("~/") {
+                    Some(rest) => home.join(rest).to_string_lossy().into_owned(),
+                    None => arg,
+                }
+            })
+            .collect();
+        let Some(program) = args.first() else {
+            return Ok(None);
+        };
+        let name = program.rsplit('/').next().unwrap_or(program);
+        if !ALLOWED_VERIFY_COMMANDS.contains(&name) {
+            bail!(
+                "'{}' is not an allowed verify command (allowed: {})",
+                name,
+                ALLOWED_VERIFY_COMMANDS.join(", ")
+            );
+        }
+        Ok(Some(args))
+    }
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct SecurityConfig {
     pub encrypt_dotfiles: bool,
     pub scan_secrets: bool,
+    /// Keep the unlocked key in the macOS login keychain or the Linux Secret Service
+    /// keyring, where it is available whenever the user is logged in, instead of a
+    /// passphrase-unlocked file cache
+    #[serde(default)]
+    pub login_keychain: bool,
+    /// macOS: keep the unlocked key wrapped by a Secure Enclave key that needs Touch
+    /// ID to use, instead of a file cache or the login keychain
+    #[serde(default)]
+    pub require_biometrics: bool,
+    /// Also publish this machine's audit log to the team repo, encrypted to the team's
+    /// recipients, so team admins can review it
+    #[serde(default)]
+    pub audit_sync: bool,
+    /// Store package manifests and machine states encrypted, like dotfiles, so the repo
+    /// doesn't reveal what software each machine has installed
+    #[serde(default)]
+    pub encrypt_manifests: bool,
+    /// Minutes an unlocked age identity stays available to later commands, held in
+    /// memory by a background agent. 0 caches it in a file until `tether identity lock`.
+    #[serde(default)]
+    pub session_minutes: u64,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -457,6 +900,149 @@ impl MergeConfig {
     }
 }
 
+/// The drift report the daemon makes for people with several machines (`tether
+/// machines report` makes one on demand)
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ReportConfig {
+    /// Let the daemon make a report every `every_days` days
+    #[serde(default)]
+    pub enabled: bool,
+    #[serde(default = "default_report_days")]
+    pub every_days: u32,
+    /// Show the report's summary as a notification
+    #[serde(default = "default_true")]
+    pub notify: bool,
+    /// Write the report as Markdown to this file (`~/` is the home directory)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub file: Option<String>,
+    /// POST the report as JSON to this URL
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub webhook: Option<String>,
+}
+
+fn default_report_days() -> u32 {
+    7
+}
+
+impl Default for ReportConfig {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            every_days: default_report_days(),
+            notify: true,
+            file: None,
+            webhook: None,
+        }
+    }
+}
+
+/// Which desktop notifications tether shows, from the daemon or a command
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct NotificationsConfig {
+    /// Show notifications at all
+    #[serde(default = "default_true")]
+    pub enabled: bool,
+    /// Leave out notifications less important than this
+    #[serde(default)]
+    pub min_severity: Severity,
+    /// Events not to notify about
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub disabled: Vec<NotificationEvent>,
+    /// Local times (`22:00-08:00`) when only critical notifications are shown
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub quiet_hours: Option<String>,
+}
+
+impl Default for NotificationsConfig {
+    fn default() -> Self {
+        Self {
+            enabled: true,
+            min_severity: Severity::Info,
+            disabled: Vec::new(),
+            quiet_hours: None,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, PartialOrd, Ord, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Severity {
+    #[default]
+    Info,
+    Warning,
+    /// Shown even in quiet hours
+    Critical,
+}
+
+/// Something tether notifies about
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum NotificationEvent {
+    /// Files that changed here and on another machine
+    Conflicts,
+    /// Dotfiles behind the remote (`sync.stale_check_minutes`)
+    StaleFiles,
+    /// Encrypted files changed outside a sync
+    Integrity,
+    /// Casks that need a password to install
+    DeferredCasks,
+    /// Package installs waiting for `tether packages approve`
+    PendingInstalls,
+    /// The sync repo is public
+    PublicRepo,
+    DriftReport,
+}
+
+impl NotificationEvent {
+    pub fn severity(self) -> Severity {
+        match self {
+            Self::Integrity | Self::PublicRepo => Severity::Critical,
+            Self::Conflicts => Severity::Warning,
+            Self::StaleFiles | Self::DeferredCasks | Self::PendingInstalls | Self::DriftReport => {
+                Severity::Info
+            }
+        }
+    }
+}
+
+impl NotificationsConfig {
+    /// Whether to show a notification for `event` at local time `now`
+    pub fn allows(&self, event: NotificationEvent, now: chrono::NaiveTime) -> bool {
+        let severity = event.severity();
+        self.enabled
+            && severity >= self.min_severity
+            && !self.disabled.contains(&event)
+            && (severity == Severity::Critical || !self.in_quiet_hours(now))
+    }
+
+    fn in_quiet_hours(&self, now: chrono::NaiveTime) -> bool {
+        match self.quiet_hours.as_deref().and_then(parse_quiet_hours) {
+            // Quiet hours can run past midnight
+            Some((start, end)) if start <= end => start <= now && now < end,
+            Some((start, end)) => now >= start || now < end,
+            None => false,
+        }
+    }
+
+    pub fn validate(&self) -> Result<()> {
+        if let Some(hours) = &self.quiet_hours {
+            if parse_quiet_hours(hours).is_none() {
+                bail!(
+                    "notifications.quiet_hours '{}' should look like 22:00-08:00",
+                    hours
+                );
+            }
+        }
+        Ok(())
+    }
+}
+
+fn parse_quiet_hours(hours: &str) -> Option<(chrono::NaiveTime, chrono::NaiveTime)> {
+    let (start, end) = hours.split_once('-')?;
+    let time = |t: &str| chrono::NaiveTime::parse_from_str(t.trim(), "%H:%M").ok();
+    Some((time(start)?, time(end)?))
+}
+
 impl Default for MergeConfig {
     fn default() -> Self {
         Self {
@@ -578,8 +1164,55 @@ where
 pub struct ProjectConfigSettings {
     pub enabled: bool,
     pub search_paths: Vec<String>,
-    pub patterns: Vec<String>,
+    pub patterns: Vec<DotfileEntry>,
     pub only_if_gitignored: bool,
+    /// Directories never scanned for repos or project files. Plain names match at any
+    /// depth (e.g. `fixtures`); patterns with a `/` match trailing path parts (e.g. `archive/*`)
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub exclude_dirs: Vec<String>,
+    /// How many directory levels below each search path are searched for repos
+    #[serde(default = "default_project_max_depth")]
+    pub max_depth: usize,
+    /// Only sync these repos, by normalized remote URL (`github.com/user/repo`, globs
+    /// allowed). Empty means every repo found under the search paths.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub include_repos: Vec<String>,
+    /// Never sync these repos; takes precedence over `include_repos`
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub exclude_repos: Vec<String>,
+    /// Have the daemon watch the search paths so scans only revisit repos that
+    /// changed. Off by default: large trees can exhaust inotify watches on Linux.
+    #[serde(default)]
+    pub watch: bool,
+}
+
+fn default_project_max_depth() -> usize {
+    3
+}
+
+impl ProjectConfigSettings {
+    /// Whether project configs of the repo with this normalized remote URL are synced
+    pub fn allows_repo(&self, normalized_url: &str) -> bool {
+        let matches = |patterns: &[String]| {
+            patterns.iter().any(|p| {
+                let p = crate::sync::git::normalize_remote_url(p);
+                crate::sync::glob_match(&p, normalized_url)
+            })
+        };
+        (self.include_repos.is_empty() || matches(&self.include_repos))
+            && !matches(&self.exclude_repos)
+    }
+
+    /// Search paths with `~/` expanded
+    pub fn search_roots(&self, home: &Path) -> Vec<PathBuf> {
+        self.search_paths
+            .iter()
+            .map(|p| match p.strip_prefix("~/") {
+                Some(stripped) => home.join(stripped),
+                None => PathBuf::from(p),
+            })
+            .collect()
+    }
 }
 
 impl Default for ProjectConfigSettings {
@@ -588,15 +1221,20 @@ impl Default for ProjectConfigSettings {
             enabled: false,
             search_paths: vec!["~/Projects".to_string(), "~/Code".to_string()],
             patterns: vec![
-                ".env*".to_string(),              // .env, .env.local, .env.development, etc.
-                ".dev.vars".to_string(),          // Cloudflare Workers
-                "appsettings.*.json".to_string(), // .NET
-                ".vscode/settings.json".to_string(),
-                ".idea/**".to_string(),               // JetBrains
-                "*.xcconfig".to_string(),             // Xcode
-                "*service-account*.json".to_string(), // GCP
+                ".env*".into(),              // .env, .env.local, .env.development, etc.
+                ".dev.vars".into(),          // Cloudflare Workers
+                "appsettings.*.json".into(), // .NET
+                ".vscode/settings.json".into(),
+                ".idea/**".into(),               // JetBrains
+                "*.xcconfig".into(),             // Xcode
+                "*service-account*.json".into(), // GCP
             ],
             only_if_gitignored: true,
+            exclude_dirs: Vec::new(),
+            max_depth: default_project_max_depth(),
+            include_repos: Vec::new(),
+            exclude_repos: Vec::new(),
+            watch: false,
         }
     }
 }
@@ -610,17 +1248,39 @@ pub struct ProfileConfig {
     pub dotfiles: Vec<ProfileDotfileEntry>,
     /// Directories to sync
     #[serde(default, skip_serializing_if = "Vec::is_empty")]
-    pub dirs: Vec<String>,
+    pub dirs: Vec<DotfileEntry>,
     /// Enabled package managers (e.g., ["brew", "npm", "pnpm"])
     /// Empty = all globally-enabled managers
     #[serde(default, skip_serializing_if = "Vec::is_empty")]
     pub packages: Vec<String>,
+    /// Parent profile whose dotfiles, dirs and packages this profile inherits.
+    /// Inherited dotfiles stay stored under the parent's directory.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub extends: Option<String>,
+    /// Role of machines using this profile (inherited when unset)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub role: Option<MachineRole>,
+    /// Headless server mode: no notifications, GUI casks, dashboard or interactive
+    /// prompts (inherited when unset)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub server: Option<bool>,
+}
+
+/// What a machine may do to the sync repo
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum MachineRole {
+    #[default]
+    ReadWrite,
+    /// Applies incoming changes and reports its machine state, but never pushes
+    /// dotfile, config or package manifest changes
+    ReadOnly,
 }
 
 impl Config {
     pub fn config_dir() -> Result<PathBuf> {
         let home = crate::home_dir()?;
-        Ok(home.join(".tether"))
+        Ok(context_dir(&home, current_context()?.as_deref()))
     }
 
     pub fn config_path() -> Result<PathBuf> {
@@ -698,9 +1358,118 @@ impl Config {
             .unwrap_or(DEFAULT_PROFILE)
     }
 
-    /// Get the profile assigned to a machine, if any
-    pub fn machine_profile(&self, machine_id: &str) -> Option<&ProfileConfig> {
-        self.profiles.get(self.profile_name(machine_id))
+    /// Get the profile assigned to a machine with inheritance resolved, if any
+    pub fn machine_profile(&self, machine_id: &str) -> Option<ProfileConfig> {
+        self.resolve_profile(self.profile_name(machine_id)).ok()
+    }
+
+    /// Inheritance chain for a profile, starting with the profile itself.
+    /// Errors on unknown profiles and `extends` cycles.
+    pub fn profile_chain<'a>(&'a self, name: &'a str) -> Result<Vec<&'a str>> {
+        let mut chain: Vec<&str> = Vec::new();
+        let mut current = name;
+        loop {
+            if chain.contains(&current) {
+                chain.push(current);
+                bail!("Profile inheritance cycle: {}", chain.join(" -> "));
+            }
+            let Some(profile) = self.profiles.get(current) else {
+                match chain.last() {
+                    Some(child) => {
+                        bail!("Profile '{}' extends unknown profile '{}'", child, current)
+                    }
+                    None => bail!("Profile '{}' not found", current),
+                }
+            };
+            chain.push(current);
+            match profile.extends.as_deref() {
+                Some(parent) => current = parent,
+                None => return Ok(chain),
+            }
+        }
+    }
+
+    /// Flatten a profile and its ancestors into one profile. Child dotfile entries
+    /// override inherited entries with the same path; dirs are merged; a non-empty
+    /// packages list replaces the inherited one.
+    pub fn resolve_profile(&self, name: &str) -> Result<ProfileConfig> {
+        let chain = self.profile_chain(name)?;
+        let mut resolved = ProfileConfig::default();
+        for profile_name in chain.iter().rev() {
+            let profile = &self.profiles[*profile_name];
+            for entry in &profile.dotfiles {
+                match resolved
+                    .dotfiles
+                    .iter_mut()
+                    .find(|e| e.path() == entry.path())
+                {
+                    Some(existing) => *existing = entry.clone(),
+                    None => resolved.dotfiles.push(entry.clone()),
+                }
+            }
+            for dir in &profile.dirs {
+                match resolved.dirs.iter_mut().find(|d| d.path() == dir.path()) {
+                    Some(existing) => *existing = dir.clone(),
+                    None => resolved.dirs.push(dir.clone()),
+                }
+            }
+            if !profile.packages.is_empty() {
+                resolved.packages = profile.packages.clone();
+            }
+            if profile.role.is_some() {
+                resolved.role = profile.role;
+            }
+            if profile.server.is_some() {
+                resolved.server = profile.server;
+            }
+        }
+        resolved.extends = self.profiles[name].extends.clone();
+        Ok(resolved)
+    }
+
+    /// Role of a machine, from its (resolved) profile
+    pub fn machine_role(&self, machine_id: &str) -> MachineRole {
+        self.machine_profile(machine_id)
+            .and_then(|p| p.role)
+            .unwrap_or_default()
+    }
+
+    /// Whether a machine's (resolved) profile puts it in server mode
+    pub fn is_server(&self, machine_id: &str) -> bool {
+        self.machine_profile(machine_id)
+            .and_then(|p| p.server)
+            .unwrap_or(false)
+    }
+
+    /// Profile directory a machine stores a non-shared dotfile under: the nearest
+    /// profile in the inheritance chain that declares it, so inherited files aren't
+    /// duplicated into every child profile.
+    pub fn dotfile_profile(&self, machine_id: &str, dotfile_path: &str) -> &str {
+        self.declaring_profile(self.profile_name(machine_id), dotfile_path)
+    }
+
+    /// Nearest profile in `own`'s inheritance chain that declares `dotfile_path`
+    pub fn declaring_profile<'a>(&'a self, own: &'a str, dotfile_path: &str) -> &'a str {
+        let Ok(chain) = self.profile_chain(own) else {
+            return own;
+        };
+        chain
+            .into_iter()
+            .find(|name| {
+                self.profiles[*name]
+                    .dotfiles
+                    .iter()
+                    .any(|e| e.path() == dotfile_path)
+            })
+            .unwrap_or(own)
+    }
+
+    /// Check every profile's `extends` chain resolves without cycles
+    pub fn validate_profiles(&self) -> Result<()> {
+        for name in self.profiles.keys() {
+            self.profile_chain(name)?;
+        }
+        Ok(())
     }
 
     /// Get effective dotfiles for a machine as DotfileEntry vec.
@@ -725,23 +1494,32 @@ impl Config {
     }
 
     /// Get profile dotfile entries (with shared flag) for a machine.
-    pub fn profile_dotfiles(&self, machine_id: &str) -> Option<&[ProfileDotfileEntry]> {
+    pub fn profile_dotfiles(&self, machine_id: &str) -> Option<Vec<ProfileDotfileEntry>> {
         let profile = self.machine_profile(machine_id)?;
         if profile.dotfiles.is_empty() {
             None
         } else {
-            Some(&profile.dotfiles)
+            Some(profile.dotfiles)
         }
     }
 
     /// Get effective dirs for a machine. Profile dirs merge with global dirs;
     /// profile entries take priority on duplicates.
     pub fn effective_dirs(&self, machine_id: &str) -> Vec<String> {
+        self.effective_dir_entries(machine_id)
+            .into_iter()
+            .map(|e| e.path().to_string())
+            .collect()
+    }
+
+    /// Directory entries (with options) for a machine; profile entries win over
+    /// global ones for the same path
+    pub fn effective_dir_entries(&self, machine_id: &str) -> Vec<DotfileEntry> {
         if let Some(profile) = self.machine_profile(machine_id) {
             if !profile.dirs.is_empty() {
                 let mut dirs = profile.dirs.clone();
                 for global in &self.dotfiles.dirs {
-                    if !dirs.contains(global) {
+                    if !dirs.iter().any(|d| d.path() == global.path()) {
                         dirs.push(global.clone());
                     }
                 }
@@ -872,6 +1650,12 @@ impl Config {
             config.features.personal_packages = false;
         }
 
+        config.validate_profiles()?;
+        config.notifications.validate()?;
+        crate::sync::git::set_network_options(&config.sync);
+        crate::sync::git::set_signing(config.backend.signing.clone());
+        crate::sync::metadata::set_encrypt(config.security.encrypt_manifests);
+
         // v1 → v2 migration: create "dev" profile from global dotfiles/dirs/packages
         if config.config_version < 2 && config.profiles.is_empty() {
             config.migrate_v1_to_v2();
@@ -922,6 +1706,9 @@ impl Config {
             dotfiles,
             dirs: self.dotfiles.dirs.clone(),
             packages,
+            extends: None,
+            role: None,
+            server: None,
         };
 
         self.profiles
@@ -941,6 +1728,15 @@ impl Config {
     }
 }
 
+/// Whether this machine's profile puts it in server mode. Loads the config and sync
+/// state each call, so config edits apply to a running daemon; `false` before init.
+pub fn server_mode() -> bool {
+    let (Ok(config), Ok(state)) = (Config::load(), crate::sync::SyncState::load()) else {
+        return false;
+    };
+    config.is_server(&state.machine_id)
+}
+
 impl Default for Config {
     fn default() -> Self {
         Self {
@@ -950,13 +1746,32 @@ impl Default for Config {
             sync: SyncConfig {
                 interval: "5m".to_string(),
                 strategy: ConflictStrategy::LastWriteWins,
+                network_timeout_secs: default_network_timeout_secs(),
+                network_retries: default_network_retries(),
+                batch_quiet_minutes: 0,
+                batch_min_files: 0,
+                batch_max_minutes: default_batch_max_minutes(),
+                stale_check_minutes: 0,
+                watchdog_minutes: default_watchdog_minutes(),
+                sync_on_wake: true,
+                log_max_mb: default_log_max_mb(),
+                log_max_days: default_log_max_days(),
+                log_keep: default_log_keep(),
+                log_format: LogFormat::Text,
+                on_low_battery: PowerPolicy::Sync,
+                low_battery_percent: default_low_battery_percent(),
+                on_metered: PowerPolicy::Sync,
             },
             backend: BackendConfig {
                 backend_type: BackendType::Git,
                 url: String::new(),
+                signing: None,
             },
             packages: PackagesConfig {
+                mode: PackagesMode::default(),
                 remove_unlisted: false,
+                approve_installs: false,
+                trust: PackageTrust::default(),
                 brew: BrewConfig {
                     enabled: true,
                     sync_casks: true,
@@ -1012,16 +1827,30 @@ impl Default for Config {
                     // Note: .tether/config.toml is always synced (hardcoded in sync logic)
                 ],
                 dirs: vec![],
+                chunk_threshold_kb: 0,
+                propagate_deletions: false,
+                exclude: vec![],
+                default_excludes: true,
+                verify: HashMap::new(),
+                lint: true,
             },
             security: SecurityConfig {
                 encrypt_dotfiles: true,
                 scan_secrets: true,
+                login_keychain: false,
+                require_biometrics: false,
+                audit_sync: false,
+                encrypt_manifests: false,
+                session_minutes: 0,
             },
             merge: MergeConfig::default(),
+            report: ReportConfig::default(),
+            notifications: NotificationsConfig::default(),
             team: None,
             teams: None,
             project_configs: ProjectConfigSettings::default(),
             machine_profiles: HashMap::new(),
+            secrets: SecretsConfig::default(),
             profiles: HashMap::new(),
         }
     }
@@ -1031,6 +1860,80 @@ impl Default for Config {
 mod tests {
     use super::*;
 
+    #[test]
+    fn test_package_trust() {
+        let open = PackageTrust::default();
+        assert!(open.allows_brew("evil/tap/miner", false));
+        assert!(open.allows_npm("left-pad"));
+
+        let trust = PackageTrust {
+            taps: vec!["homebrew/core".to_string(), "oven-sh/bun".to_string()],
+            npm: vec!["@acme/*".to_string(), "typescript".to_string()],
+        };
+        assert!(trust.allows_brew("jq", false));
+        assert!(trust.allows_brew("oven-sh/bun/bun", false));
+        assert!(!trust.allows_brew("evil/tap/miner", false));
+        assert!(!trust.allows_brew("firefox", true));
+        assert!(trust.allows_npm("@acme/cli"));
+        assert!(trust.allows_npm("@acme/cli@2.1.0"));
+        assert!(trust.allows_npm("typescript@5.4.5"));
+        assert!(!trust.allows_npm("@acme-evil/cli"));
+        assert!(!trust.allows_npm("left-pad"));
+    }
+
+    #[test]
+    fn test_sync_interval_duration() {
+        let mut sync = Config::default().sync;
+        assert_eq!(
+            sync.interval_duration(),
+            std::time::Duration::from_secs(300)
+        );
+        for (interval, secs) in [
+            ("2h", 7200),
+            ("90s", 90),
+            ("600", 600),
+            ("10s", 60),
+            ("soon", 300),
+        ] {
+            sync.interval = interval.to_string();
+            assert_eq!(sync.interval_duration().as_secs(), secs, "{}", interval);
+        }
+        assert_eq!(SyncConfig::parse_interval("10s"), Some(10));
+        assert_eq!(SyncConfig::parse_interval("5 minutes"), None);
+    }
+
+    #[test]
+    fn test_discover_contexts() {
+        let temp = tempfile::TempDir::new().unwrap();
+        let home = temp.path();
+        assert!(discover_contexts(home).is_empty());
+
+        for dir in [
+            ".tether",
+            ".tether-work",
+            ".tether-side",
+            ".tether-bad.name",
+        ] {
+            std::fs::create_dir_all(home.join(dir)).unwrap();
+            std::fs::write(home.join(dir).join("config.toml"), "").unwrap();
+        }
+        std::fs::create_dir_all(home.join(".tether-empty")).unwrap();
+        assert_eq!(
+            discover_contexts(home),
+            vec![None, Some("side".to_string()), Some("work".to_string())]
+        );
+    }
+
+    #[test]
+    fn test_context_dir() {
+        let home = Path::new("/home/me");
+        assert_eq!(context_dir(home, None), home.join(".tether"));
+        assert_eq!(context_dir(home, Some("work")), home.join(".tether-work"));
+        assert!(is_valid_context_name("work_2"));
+        assert!(!is_valid_context_name("../work"));
+        assert!(!is_valid_context_name(""));
+    }
+
     // Path safety tests
     #[test]
     fn test_safe_dotfile_path_simple() {
@@ -1126,6 +2029,53 @@ mod tests {
         assert!(config.is_valid_command());
     }
 
+    #[test]
+    fn test_verify_command_expands_file_and_home() {
+        let mut dotfiles = Config::default().dotfiles;
+        dotfiles
+            .verify
+            .insert(".zshrc".to_string(), "zsh -n {file}".to_string());
+        dotfiles.verify.insert(
+            ".tmux.conf".to_string(),
+            "tmux -f ~/.tmux.conf start ; kill-server".to_string(),
+        );
+        let home = Path::new("/home/u");
+
+        assert_eq!(
+            dotfiles.verify_command(".zshrc", home).unwrap().unwrap(),
+            vec!["zsh", "-n", "/home/u/.zshrc"]
+        );
+        assert_eq!(
+            dotfiles
+                .verify_command(".tmux.conf", home)
+                .unwrap()
+                .unwrap(),
+            vec![
+                "tmux",
+                "-f",
+                "/home/u/.tmux.conf",
+                "start",
+                ";",
+                "kill-server"
+            ]
+        );
+        assert!(dotfiles
+            .verify_command(".gitconfig", home)
+            .unwrap()
+            .is_none());
+    }
+
+    #[test]
+    fn test_verify_command_rejects_unlisted_program() {
+        let mut dotfiles = Config::default().dotfiles;
+        dotfiles
+            .verify
+            .insert(".zshrc".to_string(), "/bin/rm -rf {file}".to_string());
+        assert!(dotfiles
+            .verify_command(".zshrc", Path::new("/home/u"))
+            .is_err());
+    }
+
     // DotfileEntry tests
     #[test]
     fn test_dotfile_entry_simple_path() {
@@ -1312,6 +2262,49 @@ files = [".zshrc"]
         assert_eq!(parsed.config_version, 1);
     }
 
+    #[test]
+    fn test_dir_and_pattern_entries_parse_with_options() {
+        let dotfiles: DotfilesConfig = toml::from_str(
+            r#"
+files = [".zshrc"]
+dirs = ["~/.config/nvim", { path = "~/.config/karabiner", create_if_missing = false }]
+"#,
+        )
+        .unwrap();
+        assert!(dotfiles.dirs[0].create_if_missing());
+        assert_eq!(dotfiles.dirs[1].path(), "~/.config/karabiner");
+        assert!(!dotfiles.dirs[1].create_if_missing());
+
+        let projects: ProjectConfigSettings = toml::from_str(
+            r#"
+enabled = true
+search_paths = ["~/Projects"]
+patterns = [".env*", { path = ".idea/**", create_if_missing = false }]
+only_if_gitignored = true
+"#,
+        )
+        .unwrap();
+        assert!(!projects.patterns[1].create_if_missing());
+    }
+
+    #[test]
+    fn test_project_repo_allow_deny() {
+        let mut projects = ProjectConfigSettings::default();
+        assert!(projects.allows_repo("github.com/user/app"));
+
+        projects.include_repos = vec![
+            "git@github.com:user/app.git".into(),
+            "github.com/acme/*".into(),
+        ];
+        assert!(projects.allows_repo("github.com/user/app"));
+        assert!(projects.allows_repo("github.com/acme/api"));
+        assert!(!projects.allows_repo("github.com/user/other"));
+
+        projects.exclude_repos = vec!["https://github.com/acme/legacy".into()];
+        assert!(!projects.allows_repo("github.com/acme/legacy"));
+        assert!(projects.allows_repo("github.com/acme/api"));
+    }
+
     #[test]
     fn test_config_default_has_current_version() {
         let config = Config::default();
@@ -1389,6 +2382,9 @@ files = []
                 dotfiles: vec![ProfileDotfileEntry::Simple(".zshrc".to_string())],
                 dirs: vec![],
                 packages: vec![],
+                extends: None,
+                role: None,
+                server: None,
             },
         );
         config
@@ -1422,6 +2418,9 @@ files = []
                 }],
                 dirs: vec![],
                 packages: vec![],
+                extends: None,
+                role: None,
+                server: None,
             },
         );
         config
@@ -1444,6 +2443,9 @@ files = []
                 dotfiles: vec![ProfileDotfileEntry::Simple(".vimrc".to_string())],
                 dirs: vec![],
                 packages: vec![],
+                extends: None,
+                role: None,
+                server: None,
             },
         );
         config
@@ -1466,6 +2468,9 @@ files = []
                 dotfiles: vec![],
                 dirs: vec![],
                 packages: vec!["brew".to_string()],
+                extends: None,
+                role: None,
+                server: None,
             },
         );
         config
@@ -1484,6 +2489,9 @@ files = []
                 dotfiles: vec![],
                 dirs: vec![],
                 packages: vec![],
+                extends: None,
+                role: None,
+                server: None,
             },
         );
         config
@@ -1492,6 +2500,115 @@ files = []
         assert!(config.is_manager_enabled("other-box", "brew"));
     }
 
+    fn inheriting_config() -> Config {
+        let mut config = Config::default();
+        config.profiles.insert(
+            "base".to_string(),
+            ProfileConfig {
+                dotfiles: vec![
+                    ProfileDotfileEntry::Simple(".zshrc".to_string()),
+                    ProfileDotfileEntry::WithOptions {
+                        path: ".gitconfig".to_string(),
+                        shared: true,
+                        create_if_missing: false,
+                    },
+                ],
+                dirs: vec!["~/.config/nvim".into()],
+                packages: vec!["brew".to_string()],
+                extends: None,
+                role: None,
+                server: None,
+            },
+        );
+        config.profiles.insert(
+            "server".to_string(),
+            ProfileConfig {
+                dotfiles: vec![ProfileDotfileEntry::Simple(".tmux.conf".to_string())],
+                dirs: vec!["~/.config/htop".into()],
+                packages: vec![],
+                extends: Some("base".to_string()),
+                role: None,
+                server: None,
+            },
+        );
+        config
+            .machine_profiles
+            .insert("my-server".to_string(), "server".to_string());
+        config
+    }
+
+    #[test]
+    fn test_resolve_profile_inherits_parent() {
+        let config = inheriting_config();
+        let resolved = config.resolve_profile("server").unwrap();
+        let paths: Vec<&str> = resolved.dotfiles.iter().map(|e| e.path()).collect();
+        assert_eq!(paths, vec![".zshrc", ".gitconfig", ".tmux.conf"]);
+        assert_eq!(
+            resolved.dirs,
+            vec![
+                DotfileEntry::from("~/.config/nvim"),
+                DotfileEntry::from("~/.config/htop")
+            ]
+        );
+        // Empty packages list inherits the parent's
+        assert_eq!(resolved.packages, vec!["brew"]);
+        assert!(config.is_dotfile_shared("my-server", ".gitconfig"));
+        assert!(!config.is_manager_enabled("my-server", "npm"));
+    }
+
+    #[test]
+    fn test_machine_role_inherits_and_parses() {
+        let mut config = inheriting_config();
+        assert_eq!(config.machine_role("my-server"), MachineRole::ReadWrite);
+
+        let parsed: ProfileConfig = toml::from_str("role = \"read-only\"").unwrap();
+        config.profiles.get_mut("base").unwrap().role = parsed.role;
+        assert_eq!(config.machine_role("my-server"), MachineRole::ReadOnly);
+        assert_eq!(config.machine_role("unassigned"), MachineRole::ReadWrite);
+    }
+
+    #[test]
+    fn test_server_mode_inherits_and_overrides() {
+        let mut config = inheriting_config();
+        assert!(!config.is_server("my-server"));
+
+        let parsed: ProfileConfig = toml::from_str("server = true").unwrap();
+        config.profiles.get_mut("base").unwrap().server = parsed.server;
+        assert!(config.is_server("my-server"));
+        config.profiles.get_mut("server").unwrap().server = Some(false);
+        assert!(!config.is_server("my-server"));
+        assert!(!config.is_server("unassigned"));
+    }
+
+    #[test]
+    fn test_dotfile_profile_uses_declaring_profile() {
+        let mut config = inheriting_config();
+        assert_eq!(config.dotfile_profile("my-server", ".zshrc"), "base");
+        assert_eq!(config.dotfile_profile("my-server", ".tmux.conf"), "server");
+
+        // Redeclaring in the child gives it its own copy
+        config
+            .profiles
+            .get_mut("server")
+            .unwrap()
+            .dotfiles
+            .push(ProfileDotfileEntry::Simple(".zshrc".to_string()));
+        assert_eq!(config.dotfile_profile("my-server", ".zshrc"), "server");
+    }
+
+    #[test]
+    fn test_profile_inheritance_cycle_detected() {
+        let mut config = inheriting_config();
+        config.profiles.get_mut("base").unwrap().extends = Some("server".to_string());
+        let err = config.validate_profiles().unwrap_err().to_string();
+        assert!(err.contains("cycle"), "{}", err);
+        assert!(config.resolve_profile("server").is_err());
+
+        config.profiles.get_mut("base").unwrap().extends = Some("missing".to_string());
+        let err = config.resolve_profile("server").unwrap_err().to_string();
+        assert!(err.contains("unknown profile 'missing'"), "{}", err);
+    }
+
     #[test]
     fn test_is_dotfile_shared() {
         let mut config = Config::default();
@@ -1508,6 +2625,9 @@ files = []
                 ],
                 dirs: vec![],
                 packages: vec![],
+                extends: None,
+                role: None,
+                server: None,
             },
         );
         config
@@ -1627,7 +2747,7 @@ files = [".zshrc"]
                 create_if_missing: false,
             },
         ];
-        config.dotfiles.dirs = vec![".config/karabiner".to_string()];
+        config.dotfiles.dirs = vec![".config/karabiner".into()];
 
         config.migrate_v1_to_v2();
 
@@ -1639,7 +2759,7 @@ files = [".zshrc"]
         assert_eq!(dev.dotfiles[1].path(), ".zshrc");
         assert!(!dev.dotfiles[1].shared());
         assert!(!dev.dotfiles[1].create_if_missing()); // WithOptions preserves false
-        assert_eq!(dev.dirs, vec![".config/karabiner"]);
+        assert_eq!(dev.dirs, vec![DotfileEntry::from(".config/karabiner")]);
         // All managers enabled in default config
         assert!(dev.packages.contains(&"brew".to_string()));
         assert!(dev.packages.contains(&"npm".to_string()));
@@ -1759,7 +2879,7 @@ dirs = [".config/karabiner"]
         assert_eq!(dev.dotfiles[1].path(), ".zshrc");
         // WithOptions{false} → preserved as false
         assert!(!dev.dotfiles[1].create_if_missing());
-        assert_eq!(dev.dirs, vec![".config/karabiner"]);
+        assert_eq!(dev.dirs, vec![DotfileEntry::from(".config/karabiner")]);
     }
 
     #[test]
@@ -1989,6 +3109,9 @@ files = [".zshrc"]
                 dotfiles: vec![],
                 dirs: vec![],
                 packages: vec!["brew".to_string()],
+                extends: None,
+                role: None,
+                server: None,
             },
         );
 
@@ -2020,4 +3143,33 @@ files = [".zshrc"]
         assert!(Config::is_safe_profile_name("my-server"));
         assert!(Config::is_safe_profile_name("workstation_01"));
     }
+
+    #[test]
+    fn test_notification_policy() {
+        let config: NotificationsConfig = toml::from_str(
+            r#"
+            min_severity = "warning"
+            disabled = ["public_repo"]
+            quiet_hours = "22:00-08:00"
+            "#,
+        )
+        .unwrap();
+        let at = |t: &str| chrono::NaiveTime::parse_from_str(t, "%H:%M").unwrap();
+
+        assert!(config.allows(NotificationEvent::Conflicts, at("12:00")));
+        assert!(!config.allows(NotificationEvent::DeferredCasks, at("12:00")));
+        assert!(!config.allows(NotificationEvent::PublicRepo, at("12:00")));
+        // Only critical notifications in quiet hours, which run past midnight
+        assert!(!config.allows(NotificationEvent::Conflicts, at("23:30")));
+        assert!(!config.allows(NotificationEvent::Conflicts, at("07:59")));
+        assert!(config.allows(NotificationEvent::Integrity, at("23:30")));
+        assert!(config.allows(NotificationEvent::Conflicts, at("08:00")));
+
+        let bad = NotificationsConfig {
+            quiet_hours: Some("10pm to 8am".to_string()),
+            ..Default::default()
+        };
+        assert!(bad.validate().is_err());
+        assert!(toml::from_str::<NotificationsConfig>(r#"disabled = ["everything"]"#).is_err());
+    }
 }
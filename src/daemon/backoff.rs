@@ -0,0 +1,108 @@
+//! Backing off when syncs keep failing. With the remote unreachable, syncing on the
+//! usual interval just fails again and logs the same error each time, so after a
+//! failure the next scheduled sync waits twice as long as the one before, up to
+//! [`MAX_DELAY`], with some jitter so contexts and machines don't retry in step.
+//!
+//! After [`DEGRADED_AFTER`] failures in a row the context counts as degraded: `tether
+//! status` says since when, and the daemon logs the error again only when it changes.
+//! Syncs asked for (from the dashboard, or on waking) still run straight away, and the
+//! first sync that succeeds ends it all.
+
+use chrono::{DateTime, Utc};
+use std::time::Duration;
+
+/// Longest wait between scheduled syncs while they keep failing, unless the sync
+/// interval itself is longer
+pub const MAX_DELAY: Duration = Duration::from_secs(60 * 60);
+
+/// Failures in a row before the context counts as degraded
+pub const DEGRADED_AFTER: u32 = 3;
+
+/// Up to this fraction of the delay is added or taken away at random
+const JITTER: f64 = 0.2;
+
+#[derive(Debug, Default)]
+pub struct Backoff {
+    /// Syncs that failed in a row
+    pub failures: u32,
+    pub degraded_since: Option<DateTime<Utc>>,
+    last_error: Option<String>,
+}
+
+impl Backoff {
+    /// Record a sync's result. Returns whether its error is worth logging in full:
+    /// always before the context is degraded, then only when it changes.
+    pub fn record(&mut self, error: Option<&str>, now: DateTime<Utc>) -> bool {
+        let Some(error) = error else {
+            *self = Self::default();
+            return false;
+        };
+        self.failures += 1;
+        if self.failures >= DEGRADED_AFTER && self.degraded_since.is_none() {
+            self.degraded_since = Some(now);
+        }
+        let changed = self.last_error.as_deref() != Some(error);
+        self.last_error = Some(error.to_string());
+        self.degraded_since.is_none() || changed || self.failures == DEGRADED_AFTER
+    }
+
+    /// How long to wait before the next scheduled sync, given the usual `interval`
+    pub fn delay(&self, interval: Duration) -> Duration {
+        if self.failures == 0 {
+            return interval;
+        }
+        let ceiling = MAX_DELAY.max(interval);
+        let doublings = self.failures.min(16);
+        let delay = interval.saturating_mul(1 << doublings).min(ceiling);
+        jitter(delay, random_unit()).min(ceiling)
+    }
+}
+
+/// `delay` moved by up to [`JITTER`] of itself, `unit` (0..1) saying how far and which way
+fn jitter(delay: Duration, unit: f64) -> Duration {
+    delay.mul_f64(1.0 + JITTER * (unit * 2.0 - 1.0))
+}
+
+fn random_unit() -> f64 {
+    use aes_gcm::aead::{rand_core::RngCore, OsRng};
+    OsRng.next_u32() as f64 / u32::MAX as f64
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_backoff() {
+        let interval = Duration::from_secs(300);
+        let now = Utc::now();
+        let mut backoff = Backoff::default();
+        assert_eq!(backoff.delay(interval), interval);
+
+        assert!(backoff.record(Some("offline"), now));
+        let first = backoff.delay(interval);
+        assert!(first >= Duration::from_secs(480) && first <= Duration::from_secs(720));
+        assert!(backoff.degraded_since.is_none());
+
+        assert!(backoff.record(Some("offline"), now));
+        assert!(backoff.record(Some("offline"), now));
+        assert_eq!(backoff.degraded_since, Some(now));
+        // Degraded: the same error again isn't logged, a new one is
+        assert!(!backoff.record(Some("offline"), now));
+        assert!(backoff.record(Some("auth failed"), now));
+
+        for _ in 0..20 {
+            backoff.record(Some("offline"), now);
+        }
+        assert!(backoff.delay(interval) <= MAX_DELAY);
+        assert!(backoff.delay(Duration::from_secs(7200)) <= Duration::from_secs(7200));
+
+        assert!(!backoff.record(None, now));
+        assert_eq!(backoff.failures, 0);
+        assert!(backoff.degraded_since.is_none());
+        assert_eq!(backoff.delay(interval), interval);
+
+        assert_eq!(jitter(interval, 0.5), interval);
+        assert_eq!(jitter(interval, 0.0), Duration::from_secs(240));
+    }
+}
@@ -0,0 +1,323 @@
+//! Daemon control over a Unix socket. The daemon listens on `daemon.sock` in each
+//! context it serves; a client connects, writes one [`DaemonRequest`] line and reads
+//! one JSON [`DaemonStatus`] back. A client that writes nothing gets the status, as
+//! before control requests existed.
+
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use std::path::PathBuf;
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+
+/// What a running daemon reports about itself
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct DaemonStatus {
+    pub pid: u32,
+    pub started_at: DateTime<Utc>,
+    /// One entry per context the daemon syncs
+    pub contexts: Vec<ContextStatus>,
+    /// Whether the daemon accepts control requests; older ones only report status
+    #[serde(default)]
+    pub controls: bool,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ContextStatus {
+    /// `None` for the default context
+    pub context: Option<String>,
+    pub interval_secs: u64,
+    pub syncing: bool,
+    pub last_sync: Option<DateTime<Utc>>,
+    /// Why the last sync failed; `None` if it succeeded
+    pub last_error: Option<String>,
+    pub next_sync: Option<DateTime<Utc>>,
+    /// Scheduled syncs are skipped until resumed
+    #[serde(default)]
+    pub paused: bool,
+    /// When a timed pause (`daemon pause --for`) ends; `None` pauses until resumed
+    #[serde(default)]
+    pub paused_until: Option<DateTime<Utc>>,
+    /// Syncs that failed in a row
+    #[serde(default)]
+    pub failures: u32,
+    /// Since when syncs have kept failing, so the daemon retries less often
+    #[serde(default)]
+    pub degraded_since: Option<DateTime<Utc>>,
+    /// What's holding scheduled syncs back, like "on battery at 15%"
+    #[serde(default)]
+    pub held_back: Option<String>,
+}
+
+impl ContextStatus {
+    pub fn label(&self) -> &str {
+        self.context.as_deref().unwrap_or("default")
+    }
+
+    /// Paused, and not by a timed pause that has run out
+    pub fn is_paused(&self) -> bool {
+        self.paused && self.paused_until.is_none_or(|until| until > Utc::now())
+    }
+
+    /// "until resumed" or "until 14:30" (local time)
+    pub fn pause_end(&self) -> String {
+        match self.paused_until {
+            Some(until) => format!(
+                "until {}",
+                until.with_timezone(&chrono::Local).format("%H:%M")
+            ),
+            None => "until resumed".to_string(),
+        }
+    }
+
+    /// "since 14:30 (4 failed syncs in a row)", when degraded
+    pub fn degraded(&self) -> Option<String> {
+        let since = self.degraded_since?;
+        Some(format!(
+            "since {} ({} failed syncs in a row)",
+            since.with_timezone(&chrono::Local).format("%H:%M"),
+            self.failures
+        ))
+    }
+
+    /// One-line summary: syncing, the last result and when the next sync is due
+    pub fn describe(&self) -> String {
+        if self.syncing {
+            return "syncing...".to_string();
+        }
+        let last = match (self.last_sync, &self.last_error) {
+            (Some(at), Some(e)) => format!(
+                "sync failed {}: {}",
+                crate::cli::output::relative_time(at),
+                e
+            ),
+            (Some(at), None) => format!("synced {}", crate::cli::output::relative_time(at)),
+            (None, _) => "no sync yet".to_string(),
+        };
+        if self.is_paused() {
+            return format!("paused {}, {}", self.pause_end(), last);
+        }
+        let last = match self.degraded() {
+            Some(degraded) => format!("degraded {}, {}", degraded, last),
+            None => last,
+        };
+        let last = match &self.held_back {
+            Some(why) => format!("{}, held back {}", last, why),
+            None => last,
+        };
+        match self.next_sync {
+            Some(next) => {
+                let minutes = (next - Utc::now()).num_minutes().max(0);
+                format!("{}, next in {}m", last, minutes)
+            }
+            None => last,
+        }
+    }
+}
+
+pub type SharedStatus = Arc<Mutex<DaemonStatus>>;
+
+/// What a client asks the daemon serving its context to do
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum DaemonRequest {
+    Status,
+    SyncNow,
+    /// Until resumed, or for this many seconds
+    Pause(Option<u64>),
+    Resume,
+    ReloadConfig,
+}
+
+impl DaemonRequest {
+    const ALL: [Self; 5] = [
+        Self::Status,
+        Self::SyncNow,
+        Self::Pause(None),
+        Self::Resume,
+        Self::ReloadConfig,
+    ];
+
+    pub fn as_str(self) -> &'static str {
+        match self {
+            Self::Status => "status",
+            Self::SyncNow => "sync-now",
+            Self::Pause(_) => "pause",
+            Self::Resume => "resume",
+            Self::ReloadConfig => "reload-config",
+        }
+    }
+
+    /// The request as written to the socket: its name, then any argument
+    pub fn to_line(self) -> String {
+        match self {
+            Self::Pause(Some(secs)) => format!("pause {}", secs),
+            _ => self.as_str().to_string(),
+        }
+    }
+
+    pub fn parse(line: &str) -> Option<Self> {
+        match line.split_once(' ') {
+            Some(("pause", secs)) => Some(Self::Pause(Some(secs.trim().parse().ok()?))),
+            Some(_) => None,
+            None => Self::ALL.into_iter().find(|r| r.as_str() == line),
+        }
+    }
+}
+
+/// Requests the socket (and the network watcher) hand to a context's sync worker
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Control {
+    SyncNow,
+    ReloadConfig,
+    /// The machine woke from sleep or joined a network: sync unless paused or
+    /// `sync.sync_on_wake` is off
+    Wake,
+}
+
+/// Socket of the daemon serving the current context
+pub fn socket_path() -> anyhow::Result<PathBuf> {
+    Ok(crate::config::Config::config_dir()?.join("daemon.sock"))
+}
+
+/// Answer requests on `path` for context `index` of `status` until the task is dropped,
+/// passing syncs and reloads to that context's worker over `control`
+#[cfg(unix)]
+pub async fn serve(
+    path: PathBuf,
+    index: usize,
+    status: SharedStatus,
+    control: tokio::sync::mpsc::UnboundedSender<Control>,
+) -> anyhow::Result<()> {
+    use std::os::unix::fs::PermissionsExt;
+    use tokio::io::{AsyncBufReadExt, AsyncWriteExt, BufReader};
+
+    // A socket left by a daemon that didn't exit cleanly blocks the bind
+    let _ = std::fs::remove_file(&path);
+    let listener = tokio::net::UnixListener::bind(&path)?;
+    std::fs::set_permissions(&path, std::fs::Permissions::from_mode(0o600))?;
+
+    loop {
+        let (stream, _) = listener.accept().await?;
+        let (reader, mut writer) = stream.into_split();
+        let mut line = String::new();
+        // Status-only clients connect and read without writing anything
+        let _ = tokio::time::timeout(
+            Duration::from_millis(200),
+            BufReader::new(reader).read_line(&mut line),
+        )
+        .await;
+        let request = match line.trim() {
+            "" => DaemonRequest::Status,
+            line => match DaemonRequest::parse(line) {
+                Some(request) => request,
+                None => {
+                    log::debug!("Ignoring unknown daemon request: {}", line);
+                    continue;
+                }
+            },
+        };
+        if request != DaemonRequest::Status {
+            log::info!("Received {} request", request.to_line());
+        }
+
+        let body = {
+            let Ok(mut status) = status.lock() else {
+                continue;
+            };
+            match request {
+                DaemonRequest::Status => {}
+                DaemonRequest::SyncNow => {
+                    let _ = control.send(Control::SyncNow);
+                }
+                DaemonRequest::ReloadConfig => {
+                    let _ = control.send(Control::ReloadConfig);
+                }
+                DaemonRequest::Pause(secs) => {
+                    if let Some(context) = status.contexts.get_mut(index) {
+                        context.paused = true;
+                        // Too far off to represent: pause until resumed
+                        context.paused_until = secs
+                            .and_then(|secs| i64::try_from(secs).ok())
+                            .and_then(chrono::Duration::try_seconds)
+                            .and_then(|d| Utc::now().checked_add_signed(d));
+                    }
+                }
+                DaemonRequest::Resume => {
+                    if let Some(context) = status.contexts.get_mut(index) {
+                        context.paused = false;
+                        context.paused_until = None;
+                    }
+                }
+            }
+            serde_json::to_vec(&*status)?
+        };
+        if let Err(e) = writer.write_all(&body).await {
+            log::debug!("Daemon client went away: {}", e);
+        }
+    }
+}
+
+/// Send `request` to the daemon serving the current context and return its status
+#[cfg(unix)]
+pub fn send(request: DaemonRequest) -> anyhow::Result<DaemonStatus> {
+    use std::io::{Read, Write};
+
+    let mut stream = std::os::unix::net::UnixStream::connect(socket_path()?).map_err(|_| {
+        anyhow::anyhow!("The daemon isn't running. Start it with 'tether daemon start'.")
+    })?;
+    stream.set_read_timeout(Some(Duration::from_secs(2)))?;
+    stream.write_all(format!("{}\n", request.to_line()).as_bytes())?;
+    let mut body = Vec::new();
+    stream.read_to_end(&mut body)?;
+    let status: DaemonStatus = serde_json::from_slice(&body)
+        .map_err(|_| anyhow::anyhow!("The daemon didn't answer '{}'", request.as_str()))?;
+    if request != DaemonRequest::Status && !status.controls {
+        anyhow::bail!(
+            "The running daemon only reports status; run 'tether daemon restart' to use '{}'",
+            request.as_str()
+        );
+    }
+    Ok(status)
+}
+
+#[cfg(not(unix))]
+pub fn send(_request: DaemonRequest) -> anyhow::Result<DaemonStatus> {
+    anyhow::bail!("Daemon control needs Unix sockets")
+}
+
+/// Status of the daemon serving the current context, if one is listening
+pub fn query() -> Option<DaemonStatus> {
+    send(DaemonRequest::Status).ok()
+}
+
+/// Ask a running daemon to pick up config changes; does nothing when none is listening
+pub fn reload_config() {
+    if let Err(e) = send(DaemonRequest::ReloadConfig) {
+        log::debug!("Daemon not reloaded: {}", e);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_request_round_trip() {
+        for request in DaemonRequest::ALL {
+            assert_eq!(DaemonRequest::parse(request.as_str()), Some(request));
+        }
+        assert_eq!(DaemonRequest::parse("shutdown"), None);
+        let timed = DaemonRequest::Pause(Some(7200));
+        assert_eq!(DaemonRequest::parse(&timed.to_line()), Some(timed));
+        assert_eq!(DaemonRequest::parse("pause soon"), None);
+        assert_eq!(DaemonRequest::parse("resume 5"), None);
+
+        // Daemons from before control requests don't report these fields
+        let old: DaemonStatus = serde_json::from_str(
+            r#"{"pid":1,"started_at":"2026-01-01T00:00:00Z","contexts":[{"context":null,"interval_secs":300,"syncing":false,"last_sync":null,"last_error":null,"next_sync":null}]}"#,
+        )
+        .unwrap();
+        assert!(!old.controls);
+        assert!(!old.contexts[0].paused);
+        assert!(!old.contexts[0].is_paused());
+    }
+}
@@ -0,0 +1,69 @@
+//! The daemon's last failed sync, kept in `daemon-error.json` next to the config so
+//! `tether status` and the dashboard can show it whether or not the daemon is still
+//! running. The next sync the daemon finishes clears it.
+
+use anyhow::Result;
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use std::path::PathBuf;
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct LastError {
+    pub message: String,
+    pub at: DateTime<Utc>,
+    /// The step the sync was on: `pull`, `apply`, `dotfiles`, `packages`, `commit`,
+    /// `push` or `team`
+    pub phase: String,
+    /// Syncs that failed in a row
+    #[serde(default)]
+    pub failures: u32,
+}
+
+impl LastError {
+    fn path() -> Result<PathBuf> {
+        Ok(crate::config::Config::config_dir()?.join("daemon-error.json"))
+    }
+
+    pub fn load() -> Option<Self> {
+        let content = std::fs::read_to_string(Self::path().ok()?).ok()?;
+        serde_json::from_str(&content).ok()
+    }
+
+    pub fn save(&self) -> Result<()> {
+        crate::sync::atomic_write(
+            &Self::path()?,
+            serde_json::to_string_pretty(self)?.as_bytes(),
+        )
+    }
+
+    pub fn clear() -> Result<()> {
+        match std::fs::remove_file(Self::path()?) {
+            Err(e) if e.kind() != std::io::ErrorKind::NotFound => Err(e.into()),
+            _ => Ok(()),
+        }
+    }
+
+    /// The daemon's last failure, unless a sync has succeeded since (`last_sync`, from
+    /// the sync state, counts manual syncs too)
+    pub fn current(last_sync: DateTime<Utc>) -> Option<Self> {
+        Self::load().filter(|error| error.at > last_sync)
+    }
+
+    /// "push failed 5m ago (3 in a row)"
+    pub fn headline(&self) -> String {
+        let mut headline = format!(
+            "{} failed {}",
+            self.phase,
+            crate::cli::output::relative_time(self.at)
+        );
+        if self.failures > 1 {
+            headline.push_str(&format!(" ({} in a row)", self.failures));
+        }
+        headline
+    }
+
+    /// The first line of the message, which is the one that says what went wrong
+    pub fn first_line(&self) -> &str {
+        self.message.lines().next().unwrap_or_default()
+    }
+}
@@ -0,0 +1,371 @@
+//! `daemon.log`: writing it, rotating it, and reading it back for `tether daemon logs`.
+//!
+//! Lines are env_logger's usual text, or with `sync.log_format = "json"` (or
+//! `TETHER_LOG_FORMAT=json`) one JSON object each: `time`, `level`, `target` and
+//! `message`, plus whatever fields the event carries (`event`, `context`, `file`,
+//! `duration_ms`, `error`), for shipping to a log pipeline.
+//!
+//! The daemon's output is appended to `daemon.log` by whatever started it (launchd,
+//! systemd, the Task Scheduler or `daemon start`), so the daemon can't reopen it.
+//! Rotation copies it to `daemon.log.1` (shifting older copies up to
+//! `sync.log_keep`) and truncates it in place, which keeps that writer's handle valid.
+//! It happens once the log passes `sync.log_max_mb` or its first entry is
+//! `sync.log_max_days` old.
+
+use crate::config::{LogFormat, SyncConfig};
+use anyhow::Result;
+use chrono::{DateTime, Utc};
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicBool, Ordering};
+
+/// Events logged when the daemon crashes or stops a sync: panics, sync loops restarted
+/// after one, and syncs the watchdog stopped
+const CRASH_EVENTS: &[&str] = &["panic", "worker_restart", "watchdog", "sync_timeout"];
+
+/// How those events' messages start, for text logs
+const CRASH_MESSAGES: &[&str] = &[
+    "Panic at ",
+    "Panic in the sync loop",
+    "Watchdog:",
+    "Sync timed out",
+];
+
+/// Overrides `sync.log_format`: `text` or `json`
+pub const LOG_FORMAT_ENV: &str = "TETHER_LOG_FORMAT";
+
+static JSON: AtomicBool = AtomicBool::new(false);
+
+/// Set up logging for any tether command, in the format `TETHER_LOG_FORMAT` asks for
+pub fn init() {
+    set_format(LogFormat::Text);
+    let text = env_logger::fmt::ConfigurableFormat::default();
+    env_logger::Builder::from_default_env()
+        .format(move |buf, record| {
+            if JSON.load(Ordering::Relaxed) {
+                use std::io::Write;
+                writeln!(buf, "{}", json_line(record, Utc::now()))
+            } else {
+                text.format(buf, record)
+            }
+        })
+        .init();
+}
+
+/// Log in `format` from now on, unless `TETHER_LOG_FORMAT` says otherwise
+pub fn set_format(format: LogFormat) {
+    let format = match std::env::var(LOG_FORMAT_ENV).ok().as_deref() {
+        Some("json") => LogFormat::Json,
+        Some("text") => LogFormat::Text,
+        _ => format,
+    };
+    JSON.store(format == LogFormat::Json, Ordering::Relaxed);
+}
+
+/// `record` as one line of JSON
+fn json_line(record: &log::Record, now: DateTime<Utc>) -> String {
+    use log::kv::{Error, Key, Value, VisitSource};
+
+    struct Fields(serde_json::Map<String, serde_json::Value>);
+    impl<'kvs> VisitSource<'kvs> for Fields {
+        fn visit_pair(&mut self, key: Key<'kvs>, value: Value<'kvs>) -> Result<(), Error> {
+            let value = if let Some(n) = value.to_u64() {
+                n.into()
+            } else if let Some(n) = value.to_i64() {
+                n.into()
+            } else if let Some(b) = value.to_bool() {
+                b.into()
+            } else {
+                value.to_string().into()
+            };
+            self.0.insert(key.to_string(), value);
+            Ok(())
+        }
+    }
+
+    let mut fields = Fields(serde_json::Map::new());
+    fields.0.insert(
+        "time".into(),
+        now.to_rfc3339_opts(chrono::SecondsFormat::Secs, true)
+            .into(),
+    );
+    fields
+        .0
+        .insert("level".into(), record.level().as_str().into());
+    fields.0.insert("target".into(), record.target().into());
+    fields
+        .0
+        .insert("message".into(), record.args().to_string().into());
+    // Fields the event carries can't fail to visit; a failure only loses them
+    let _ = record.key_values().visit(&mut fields);
+    serde_json::Value::Object(fields.0).to_string()
+}
+
+/// `daemon.log.<n>`
+fn rotated_path(log: &Path, n: u32) -> PathBuf {
+    let mut name = log.as_os_str().to_owned();
+    name.push(format!(".{}", n));
+    PathBuf::from(name)
+}
+
+/// The rotated logs that exist, oldest first
+pub fn rotated(log: &Path) -> Vec<PathBuf> {
+    let mut found: Vec<PathBuf> = (1..)
+        .map(|n| rotated_path(log, n))
+        .take_while(|path| path.exists())
+        .collect();
+    found.reverse();
+    found
+}
+
+/// When a log line was written, from env_logger's `[2024-05-01T12:00:00Z INFO ...]`
+/// prefix or a JSON line's `time`. `None` for the continuation lines of a multi-line
+/// message.
+pub fn line_time(line: &str) -> Option<DateTime<Utc>> {
+    if line.starts_with('{') {
+        let entry: serde_json::Value = serde_json::from_str(line).ok()?;
+        return DateTime::parse_from_rfc3339(entry.get("time")?.as_str()?)
+            .ok()
+            .map(|time| time.with_timezone(&Utc));
+    }
+    let stamp = line.strip_prefix('[')?.split_whitespace().next()?;
+    DateTime::parse_from_rfc3339(stamp)
+        .ok()
+        .map(|time| time.with_timezone(&Utc))
+}
+
+/// The level of a log line that starts an entry: env_logger's `[... ERROR target]`
+/// prefix or a JSON line's `level`
+pub fn line_level(line: &str) -> Option<log::Level> {
+    if line.starts_with('{') {
+        let entry: serde_json::Value = serde_json::from_str(line).ok()?;
+        return entry.get("level")?.as_str()?.parse().ok();
+    }
+    line.strip_prefix('[')?
+        .split_whitespace()
+        .nth(1)?
+        .parse()
+        .ok()
+}
+
+/// The last lines of `log`, from at most its last `max_bytes`. Empty if it can't be
+/// read.
+pub fn tail(log: &Path, max_bytes: u64) -> Vec<String> {
+    use std::io::{BufRead, BufReader, Seek, SeekFrom};
+
+    let Ok(file) = std::fs::File::open(log) else {
+        return Vec::new();
+    };
+    let size = file.metadata().map(|meta| meta.len()).unwrap_or(0);
+    let read_size = max_bytes.min(size);
+    let mut reader = BufReader::new(file);
+    if reader.seek(SeekFrom::End(-(read_size as i64))).is_err() {
+        return Vec::new();
+    }
+    // If we seeked into the middle of a line, skip the partial first line
+    if read_size < size {
+        let mut partial = Vec::new();
+        let _ = reader.read_until(b'\n', &mut partial);
+    }
+    reader.lines().map_while(Result::ok).collect()
+}
+
+/// Why `log` is due for rotation, if it is
+fn rotation_reason(log: &Path, config: &SyncConfig, now: DateTime<Utc>) -> Option<String> {
+    let size = std::fs::metadata(log).ok()?.len();
+    if size == 0 {
+        return None;
+    }
+    if size > config.log_max_mb.max(1) * 1_000_000 {
+        return Some(format!("{} bytes", size));
+    }
+    if config.log_max_days == 0 {
+        return None;
+    }
+    use std::io::BufRead;
+    let first = std::io::BufReader::new(std::fs::File::open(log).ok()?)
+        .lines()
+        .map_while(|line| line.ok())
+        .find_map(|line| line_time(&line))?;
+    let age = now - first;
+    (age >= chrono::Duration::days(config.log_max_days as i64))
+        .then(|| format!("{} days old", age.num_days()))
+}
+
+/// Rotate `log` if it's too big or too old, keeping `sync.log_keep` old copies.
+/// Returns why it was rotated.
+pub fn rotate_if_needed(
+    log: &Path,
+    config: &SyncConfig,
+    now: DateTime<Utc>,
+) -> Result<Option<String>> {
+    let Some(reason) = rotation_reason(log, config, now) else {
+        return Ok(None);
+    };
+    let keep = config.log_keep.max(1);
+    // Copies past the limit, including any left from a higher limit before
+    let mut n = keep;
+    while rotated_path(log, n).exists() {
+        n += 1;
+    }
+    for old in (keep..n).rev() {
+        std::fs::remove_file(rotated_path(log, old))?;
+    }
+    for older in (1..keep).rev() {
+        let from = rotated_path(log, older);
+        if from.exists() {
+            std::fs::rename(&from, rotated_path(log, older + 1))?;
+        }
+    }
+    std::fs::copy(log, rotated_path(log, 1))?;
+    // Truncate in place: the daemon's output handle stays valid
+    std::fs::OpenOptions::new()
+        .write(true)
+        .open(log)?
+        .set_len(0)?;
+    Ok(Some(reason))
+}
+
+/// Lines of `logs` (oldest first) written at or after `since`. A line without a time
+/// goes with the entry before it.
+pub fn lines_since(logs: &[String], since: DateTime<Utc>) -> Vec<&str> {
+    let mut keep = false;
+    logs.iter()
+        .filter(|line| {
+            if let Some(time) = line_time(line) {
+                keep = time >= since;
+            }
+            keep
+        })
+        .map(String::as_str)
+        .collect()
+}
+
+/// Whether a log line starts an entry for a crash
+fn is_crash(line: &str) -> bool {
+    if line.starts_with('{') {
+        return serde_json::from_str::<serde_json::Value>(line)
+            .ok()
+            .and_then(|entry| Some(CRASH_EVENTS.contains(&entry.get("event")?.as_str()?)))
+            .unwrap_or(false);
+    }
+    line.split_once("] ")
+        .is_some_and(|(_, message)| CRASH_MESSAGES.iter().any(|m| message.starts_with(m)))
+}
+
+/// The entries of `logs` for crashes, with their backtraces
+pub fn crash_lines<'a>(logs: &[&'a str]) -> Vec<&'a str> {
+    let mut keep = false;
+    logs.iter()
+        .copied()
+        .filter(|line| {
+            if line_time(line).is_some() {
+                keep = is_crash(line);
+            }
+            keep
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::TempDir;
+
+    #[test]
+    fn test_rotate_if_needed() {
+        let temp = TempDir::new().unwrap();
+        let log = temp.path().join("daemon.log");
+        let now: DateTime<Utc> = "2024-05-10T12:00:00Z".parse().unwrap();
+        let config = SyncConfig {
+            log_max_mb: 1,
+            log_max_days: 7,
+            log_keep: 2,
+            ..crate::config::Config::default().sync
+        };
+
+        std::fs::write(&log, "[2024-05-09T08:00:00Z INFO  tether] recent\n").unwrap();
+        assert_eq!(rotate_if_needed(&log, &config, now).unwrap(), None);
+
+        std::fs::write(&log, "[2024-05-01T08:00:00Z INFO  tether] old\n").unwrap();
+        assert_eq!(
+            rotate_if_needed(&log, &config, now).unwrap().as_deref(),
+            Some("9 days old")
+        );
+        assert_eq!(std::fs::read_to_string(&log).unwrap(), "");
+        assert_eq!(rotated(&log), vec![temp.path().join("daemon.log.1")]);
+
+        std::fs::write(&log, "x".repeat(1_000_001)).unwrap();
+        assert!(rotate_if_needed(&log, &config, now).unwrap().is_some());
+        std::fs::write(&log, "x".repeat(1_000_001)).unwrap();
+        assert!(rotate_if_needed(&log, &config, now).unwrap().is_some());
+        // Only log_keep copies are kept; .1 is the newest
+        assert_eq!(rotated(&log).len(), 2);
+        assert!(std::fs::read_to_string(temp.path().join("daemon.log.2"))
+            .unwrap()
+            .starts_with('x'));
+    }
+
+    #[test]
+    fn test_lines_since() {
+        let logs: Vec<String> = [
+            "[2024-05-01T08:00:00Z INFO  tether] old",
+            "[2024-05-02T08:00:00Z ERROR tether] Sync failed: offline",
+            "    fatal: could not read",
+            "[2024-05-03T08:00:00Z INFO  tether] recovered",
+        ]
+        .iter()
+        .map(|s| s.to_string())
+        .collect();
+        let since = "2024-05-02T00:00:00Z".parse().unwrap();
+        assert_eq!(lines_since(&logs, since).len(), 3);
+        assert_eq!(line_time("    fatal: could not read"), None);
+        assert_eq!(line_level(&logs[1]), Some(log::Level::Error));
+        assert_eq!(line_level(&logs[2]), None);
+    }
+
+    #[test]
+    fn test_tail() {
+        let temp = TempDir::new().unwrap();
+        let log = temp.path().join("daemon.log");
+        assert!(tail(&log, 100).is_empty());
+        std::fs::write(&log, "first line\nsecond\nthird\n").unwrap();
+        assert_eq!(tail(&log, 100), vec!["first line", "second", "third"]);
+        // The cut-off first line is left out
+        assert_eq!(tail(&log, 15), vec!["second", "third"]);
+    }
+
+    #[test]
+    fn test_crash_lines() {
+        let logs = [
+            "[2024-05-01T08:00:00Z INFO  tether::daemon::server] Running periodic sync...",
+            "[2024-05-01T08:00:01Z ERROR tether::daemon::watchdog] Panic at src/sync/git.rs:10: boom",
+            "   0: std::backtrace::Backtrace::force_capture",
+            "[2024-05-01T08:00:02Z ERROR tether::daemon::server] Sync failed: Sync panicked: boom",
+            r#"{"event":"watchdog","level":"ERROR","message":"Watchdog: sync stuck for 15m","time":"2024-05-01T09:00:00Z"}"#,
+            r#"{"event":"sync","level":"INFO","message":"Sync complete","time":"2024-05-01T09:01:00Z"}"#,
+        ];
+        assert_eq!(line_level(logs[4]), Some(log::Level::Error));
+        let crashes = crash_lines(&logs);
+        assert_eq!(crashes.len(), 3);
+        assert!(crashes[1].contains("force_capture"));
+        assert!(crashes[2].contains("stuck for 15m"));
+    }
+
+    #[test]
+    fn test_json_line() {
+        let fields = [("event", "file_changed"), ("file", ".zshrc")];
+        let record = log::Record::builder()
+            .args(format_args!("File changed: .zshrc"))
+            .level(log::Level::Info)
+            .target("tether::daemon::server")
+            .key_values(&fields)
+            .build();
+        let now = "2024-05-01T08:00:00Z".parse().unwrap();
+        let line = json_line(&record, now);
+        assert_eq!(
+            line,
+            r#"{"event":"file_changed","file":".zshrc","level":"INFO","message":"File changed: .zshrc","target":"tether::daemon::server","time":"2024-05-01T08:00:00Z"}"#
+        );
+        assert_eq!(line_time(&line), Some(now));
+    }
+}
@@ -1,3 +1,56 @@
+pub mod backoff;
+pub mod ipc;
+pub mod last_error;
+pub mod logs;
+pub mod pid;
+pub mod power;
 pub mod server;
+pub mod wake;
+pub mod watchdog;
+pub mod watcher;
 
 pub use server::{is_daemon_mode, DaemonServer};
+
+/// Launchd label for the current context's daemon, so each context gets its own agent
+pub fn launchd_label() -> anyhow::Result<String> {
+    Ok(match crate::config::current_context()? {
+        Some(context) => format!("com.tether.daemon.{context}"),
+        None => "com.tether.daemon".to_string(),
+    })
+}
+
+/// Task Scheduler task for the current context's daemon on Windows
+pub fn scheduled_task_name() -> anyhow::Result<String> {
+    Ok(match crate::config::current_context()? {
+        Some(context) => format!("Tether Daemon ({context})"),
+        None => "Tether Daemon".to_string(),
+    })
+}
+
+/// Status of the current context's login task as Task Scheduler reports it
+/// (`Ready`, `Running`, ...), or `None` when it isn't installed
+#[cfg(windows)]
+pub fn scheduled_task_status() -> Option<String> {
+    let output = std::process::Command::new("schtasks")
+        .args([
+            "/Query",
+            "/TN",
+            &scheduled_task_name().ok()?,
+            "/FO",
+            "CSV",
+            "/NH",
+        ])
+        .output()
+        .ok()
+        .filter(|output| output.status.success())?;
+    // "TaskName","Next Run Time","Status"
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    let line = stdout.lines().find(|line| !line.trim().is_empty())?;
+    Some(
+        line.rsplit("\",\"")
+            .next()?
+            .trim()
+            .trim_matches('"')
+            .to_string(),
+    )
+}
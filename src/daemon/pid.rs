@@ -0,0 +1,181 @@
+//! The daemon's pid file (`daemon.pid` in each context it serves). A daemon that
+//! crashed or was killed leaves its pid behind, and the OS can later hand that pid to
+//! an unrelated program, so a pid only counts as the daemon while that process is
+//! alive and is running `tether daemon run`.
+
+use anyhow::Result;
+use std::path::{Path, PathBuf};
+
+/// What the current context's pid file says
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum PidState {
+    /// No pid file, or one that doesn't hold a pid
+    Missing,
+    Running(u32),
+    /// The file names a daemon that's gone; `reason` says why it doesn't count
+    Stale {
+        pid: u32,
+        reason: &'static str,
+    },
+}
+
+impl PidState {
+    pub fn running(self) -> Option<u32> {
+        match self {
+            Self::Running(pid) => Some(pid),
+            _ => None,
+        }
+    }
+}
+
+pub fn pid_path() -> Result<PathBuf> {
+    Ok(crate::config::Config::config_dir()?.join("daemon.pid"))
+}
+
+fn read(path: &Path) -> Option<u32> {
+    std::fs::read_to_string(path)
+        .ok()?
+        .trim()
+        .parse()
+        .ok()
+        .filter(|pid| *pid > 0)
+}
+
+/// Check the current context's pid file against the running processes
+pub fn check() -> Result<PidState> {
+    let Some(pid) = read(&pid_path()?) else {
+        return Ok(PidState::Missing);
+    };
+    Ok(if !is_process_running(pid) {
+        PidState::Stale {
+            pid,
+            reason: "no longer running",
+        }
+    } else if !is_daemon_process(pid) {
+        PidState::Stale {
+            pid,
+            reason: "now another program",
+        }
+    } else {
+        PidState::Running(pid)
+    })
+}
+
+/// Remove a stale pid file and note the recovery in `daemon.log`, where the crash
+/// itself may have left no trace. Returns the stale pid, if there was one.
+pub fn clean_stale() -> Result<Option<u32>> {
+    let PidState::Stale { pid, reason } = check()? else {
+        return Ok(None);
+    };
+    remove(&pid_path()?, Some(pid))?;
+    let message = format!(
+        "Removed stale daemon pid file: pid {} is {} (the daemon didn't exit cleanly)",
+        pid, reason
+    );
+    log::warn!("{}", message);
+    let log_path = crate::config::Config::config_dir()?.join("daemon.log");
+    if let Ok(mut log) = std::fs::OpenOptions::new()
+        .create(true)
+        .append(true)
+        .open(log_path)
+    {
+        use std::io::Write;
+        let _ = writeln!(
+            log,
+            "[{} WARN  tether::daemon::pid] {}",
+            chrono::Utc::now().format("%Y-%m-%dT%H:%M:%SZ"),
+            message
+        );
+    }
+    Ok(Some(pid))
+}
+
+/// Remove the pid file at `path` unless it now belongs to another daemon
+pub fn remove(path: &Path, expected_pid: Option<u32>) -> Result<()> {
+    if !path.exists() {
+        return Ok(());
+    }
+    let contents = std::fs::read_to_string(path)?;
+    if expected_pid
+        .map(|pid| contents.trim() == pid.to_string())
+        .unwrap_or(true)
+    {
+        let _ = std::fs::remove_file(path);
+    }
+    Ok(())
+}
+
+pub fn is_process_running(pid: u32) -> bool {
+    unsafe {
+        if libc::kill(pid as libc::pid_t, 0) == 0 {
+            return true;
+        }
+        // ESRCH = no such process, EPERM = exists but no permission
+        std::io::Error::last_os_error().raw_os_error() == Some(libc::EPERM)
+    }
+}
+
+/// Whether `pid` is a tether daemon. Assumes it is when the command line can't be read.
+fn is_daemon_process(pid: u32) -> bool {
+    command_line(pid).is_none_or(|args| is_daemon_command(&args))
+}
+
+#[cfg(target_os = "linux")]
+fn command_line(pid: u32) -> Option<Vec<String>> {
+    let raw = std::fs::read(format!("/proc/{}/cmdline", pid)).ok()?;
+    Some(
+        raw.split(|b| *b == 0)
+            .filter(|arg| !arg.is_empty())
+            .map(|arg| String::from_utf8_lossy(arg).into_owned())
+            .collect(),
+    )
+}
+
+#[cfg(not(target_os = "linux"))]
+fn command_line(pid: u32) -> Option<Vec<String>> {
+    let output = std::process::Command::new("ps")
+        .args(["-p", &pid.to_string(), "-o", "command="])
+        .output()
+        .ok()
+        .filter(|output| output.status.success())?;
+    Some(
+        String::from_utf8_lossy(&output.stdout)
+            .split_whitespace()
+            .map(String::from)
+            .collect(),
+    )
+}
+
+/// `tether [--context <name>] daemon run [--all-contexts]`
+fn is_daemon_command(args: &[String]) -> bool {
+    let Some(program) = args.first() else {
+        return false;
+    };
+    let name = Path::new(program)
+        .file_name()
+        .map(|name| name.to_string_lossy().into_owned())
+        .unwrap_or_default();
+    name.starts_with("tether") && args.windows(2).any(|pair| pair == ["daemon", "run"])
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn args(line: &str) -> Vec<String> {
+        line.split(' ').map(String::from).collect()
+    }
+
+    #[test]
+    fn test_is_daemon_command() {
+        assert!(is_daemon_command(&args(
+            "/opt/homebrew/bin/tether daemon run"
+        )));
+        assert!(is_daemon_command(&args(
+            "tether --context work daemon run --all-contexts"
+        )));
+        assert!(!is_daemon_command(&args("/usr/bin/tether daemon start")));
+        assert!(!is_daemon_command(&args("/usr/bin/vim daemon run")));
+        assert!(!is_daemon_command(&[]));
+    }
+}
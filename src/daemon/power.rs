@@ -0,0 +1,225 @@
+//! Holding back scheduled syncs on low battery or a metered connection, as
+//! `sync.on_low_battery` and `sync.on_metered` ask. Both are looked up before each
+//! scheduled sync, with whatever the platform offers: `pmset` and the default route on
+//! macOS, `/sys/class/power_supply` and NetworkManager on Linux, WMI and the
+//! connection cost on Windows. Anything that can't be found out counts as plugged in
+//! and unmetered.
+
+use crate::config::{PowerPolicy, SyncConfig};
+use std::process::Command;
+
+/// With `stretch`, one scheduled sync in this many runs
+pub const STRETCH_FACTOR: u32 = 4;
+
+/// Default gateways of phone hotspots: iPhone Personal Hotspot, then Android
+#[cfg_attr(not(target_os = "macos"), allow(dead_code))]
+const HOTSPOT_GATEWAYS: &[&str] = &["172.20.10.1", "192.168.43.1"];
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Constraint {
+    /// On battery, at this percentage
+    LowBattery(u8),
+    Metered,
+}
+
+impl std::fmt::Display for Constraint {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::LowBattery(percent) => write!(f, "on battery at {}%", percent),
+            Self::Metered => write!(f, "on a metered connection"),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+struct Battery {
+    percent: u8,
+    discharging: bool,
+}
+
+/// What's holding syncs back right now and how, if anything is. With both low
+/// battery and a metered connection, the stricter policy wins.
+pub fn check(config: &SyncConfig) -> Option<(Constraint, PowerPolicy)> {
+    let battery = (config.on_low_battery != PowerPolicy::Sync)
+        .then(battery)
+        .flatten()
+        .filter(|b| b.discharging && b.percent < config.low_battery_percent)
+        .map(|b| (Constraint::LowBattery(b.percent), config.on_low_battery));
+    let metered = (config.on_metered != PowerPolicy::Sync && is_metered())
+        .then_some((Constraint::Metered, config.on_metered));
+    strictest(battery, metered)
+}
+
+fn strictest(
+    a: Option<(Constraint, PowerPolicy)>,
+    b: Option<(Constraint, PowerPolicy)>,
+) -> Option<(Constraint, PowerPolicy)> {
+    match (a, b) {
+        (Some(a), Some(b)) => Some(if b.1 > a.1 { b } else { a }),
+        (a, b) => a.or(b),
+    }
+}
+
+fn output(program: &str, args: &[&str]) -> Option<String> {
+    let output = Command::new(program).args(args).output().ok()?;
+    output
+        .status
+        .success()
+        .then(|| String::from_utf8_lossy(&output.stdout).into_owned())
+}
+
+#[cfg(target_os = "macos")]
+fn battery() -> Option<Battery> {
+    parse_pmset(&output("pmset", &["-g", "batt"])?)
+}
+
+#[cfg(target_os = "linux")]
+fn battery() -> Option<Battery> {
+    let supplies = std::fs::read_dir("/sys/class/power_supply").ok()?;
+    supplies.flatten().find_map(|supply| {
+        let read = |name: &str| {
+            std::fs::read_to_string(supply.path().join(name))
+                .map(|value| value.trim().to_string())
+                .ok()
+        };
+        if read("type")? != "Battery" {
+            return None;
+        }
+        Some(Battery {
+            percent: read("capacity")?.parse().ok()?,
+            discharging: read("status")? == "Discharging",
+        })
+    })
+}
+
+#[cfg(windows)]
+fn battery() -> Option<Battery> {
+    let out = output(
+        "powershell",
+        &[
+            "-NoProfile",
+            "-Command",
+            "Get-CimInstance Win32_Battery | ForEach-Object { \"$($_.EstimatedChargeRemaining) $($_.BatteryStatus)\" }",
+        ],
+    )?;
+    let mut fields = out.split_whitespace();
+    Some(Battery {
+        percent: fields.next()?.parse().ok()?,
+        // 1 is "discharging"
+        discharging: fields.next()? == "1",
+    })
+}
+
+#[cfg(not(any(target_os = "macos", target_os = "linux", windows)))]
+fn battery() -> Option<Battery> {
+    None
+}
+
+#[cfg(target_os = "macos")]
+fn is_metered() -> bool {
+    output("route", &["-n", "get", "default"])
+        .and_then(|out| parse_gateway(&out))
+        .is_some_and(|gateway| HOTSPOT_GATEWAYS.contains(&gateway.as_str()))
+}
+
+#[cfg(target_os = "linux")]
+fn is_metered() -> bool {
+    output(
+        "busctl",
+        &[
+            "get-property",
+            "org.freedesktop.NetworkManager",
+            "/org/freedesktop/NetworkManager",
+            "org.freedesktop.NetworkManager",
+            "Metered",
+        ],
+    )
+    .is_some_and(|out| parse_nm_metered(&out))
+}
+
+#[cfg(windows)]
+fn is_metered() -> bool {
+    output(
+        "powershell",
+        &[
+            "-NoProfile",
+            "-Command",
+            "[Windows.Networking.Connectivity.NetworkInformation,Windows.Networking.Connectivity,ContentType=WindowsRuntime]::GetInternetConnectionProfile().GetConnectionCost().NetworkCostType",
+        ],
+    )
+    .is_some_and(|out| matches!(out.trim(), "Fixed" | "Variable"))
+}
+
+#[cfg(not(any(target_os = "macos", target_os = "linux", windows)))]
+fn is_metered() -> bool {
+    false
+}
+
+/// `pmset -g batt`:
+/// ```text
+/// Now drawing from 'Battery Power'
+///  -InternalBattery-0 (id=1234) 15%; discharging; 0:40 remaining present: true
+/// ```
+#[cfg_attr(not(target_os = "macos"), allow(dead_code))]
+fn parse_pmset(out: &str) -> Option<Battery> {
+    let percent = out
+        .split_whitespace()
+        .find_map(|field| field.strip_suffix("%;")?.parse().ok())?;
+    Some(Battery {
+        percent,
+        discharging: out.contains("'Battery Power'"),
+    })
+}
+
+/// `route -n get default`'s `gateway: 172.20.10.1` line
+#[cfg_attr(not(target_os = "macos"), allow(dead_code))]
+fn parse_gateway(out: &str) -> Option<String> {
+    out.lines()
+        .find_map(|line| line.trim().strip_prefix("gateway:"))
+        .map(|gateway| gateway.trim().to_string())
+}
+
+/// NetworkManager's `Metered` property as busctl prints it (`u 1`): 1 is metered and
+/// 3 is guessed to be (a phone hotspot, say)
+#[cfg_attr(not(target_os = "linux"), allow(dead_code))]
+fn parse_nm_metered(out: &str) -> bool {
+    matches!(out.trim().strip_prefix("u "), Some("1" | "3"))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_detection_parsing() {
+        let pmset = "Now drawing from 'Battery Power'\n -InternalBattery-0 (id=1234)\t15%; discharging; 0:40 remaining present: true\n";
+        assert_eq!(
+            parse_pmset(pmset),
+            Some(Battery {
+                percent: 15,
+                discharging: true
+            })
+        );
+        let charging = "Now drawing from 'AC Power'\n -InternalBattery-0 (id=1234)\t80%; charging; 1:00 remaining present: true\n";
+        assert!(!parse_pmset(charging).unwrap().discharging);
+        // Desktops have no battery line
+        assert_eq!(parse_pmset("Now drawing from 'AC Power'\n"), None);
+
+        let route = "   route to: default\ndestination: default\n    gateway: 172.20.10.1\n  interface: en0\n";
+        assert_eq!(parse_gateway(route).as_deref(), Some("172.20.10.1"));
+
+        assert!(parse_nm_metered("u 1\n"));
+        assert!(parse_nm_metered("u 3\n"));
+        assert!(!parse_nm_metered("u 4\n"));
+    }
+
+    #[test]
+    fn test_strictest() {
+        let battery = Some((Constraint::LowBattery(10), PowerPolicy::Stretch));
+        let metered = Some((Constraint::Metered, PowerPolicy::Skip));
+        assert_eq!(strictest(battery, metered), metered);
+        assert_eq!(strictest(battery, None), battery);
+        assert_eq!(strictest(None, None), None);
+        assert_eq!(Constraint::LowBattery(10).to_string(), "on battery at 10%");
+    }
+}
@@ -1,22 +1,34 @@
-use crate::config::Config;
+use super::backoff::{Backoff, DEGRADED_AFTER};
+use super::ipc::{ContextStatus, Control, DaemonStatus, SharedStatus};
+use super::last_error::LastError;
+use super::power;
+use super::watchdog::Watchdog;
+use crate::config::{Config, PowerPolicy};
 use crate::packages::{
     BrewManager, BunManager, GemManager, NpmManager, PackageManager, PnpmManager, UvManager,
 };
+use crate::sync::report::DriftReport;
 use crate::sync::{
-    import_packages, notify_deferred_casks, GitBackend, MachineState, SyncEngine, SyncState,
+    import_packages, notify_deferred_casks, notify_integrity_warning, notify_pending_installs,
+    notify_public_repo, GitBackend, MachineState, SyncEngine, SyncState,
 };
 use anyhow::Result;
-use chrono::Local;
+use chrono::{Local, Utc};
+use std::cell::Cell;
+use std::collections::HashSet;
 use std::path::PathBuf;
 use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{Arc, Mutex};
 use std::time::{Duration, SystemTime};
-use tokio::time::Interval;
+use tokio::sync::mpsc;
 
 #[cfg(unix)]
 use tokio::signal::unix::{signal, SignalKind};
 
 const DEFAULT_SYNC_INTERVAL_SECS: u64 = 300; // 5 minutes
-const MAX_LOG_BYTES: u64 = 5_000_000; // 5 MB
+
+/// Wait before starting a context's sync loop again after it panicked
+const WORKER_RESTART_DELAY: Duration = Duration::from_secs(30);
 
 /// Thread-safe flag indicating daemon mode (avoids unsafe std::env::set_var in async)
 static DAEMON_MODE: AtomicBool = AtomicBool::new(false);
@@ -27,15 +39,181 @@ pub fn is_daemon_mode() -> bool {
 }
 
 enum TickResult {
-    Continue,
+    /// `error` is why the sync failed, if it did, and `phase` the step it was on
+    Continue {
+        error: Option<String>,
+        phase: &'static str,
+        duration: Duration,
+    },
     Exit,
 }
 
+/// Sync each of `contexts` on its own schedule (`sync.interval` in its config) until
+/// signalled to stop or the binary is updated, answering requests on each context's
+/// socket. Contexts take turns on one thread, so their syncs never overlap.
+pub async fn run(contexts: Vec<Option<String>>) -> Result<()> {
+    // Set daemon mode flag (thread-safe alternative to env var)
+    DAEMON_MODE.store(true, Ordering::Relaxed);
+    super::watchdog::log_panics();
+    if let Ok(config) = Config::load() {
+        super::logs::set_format(config.sync.log_format);
+    }
+    log::info!(event = "daemon_start"; "Daemon starting (pid {})", std::process::id());
+
+    let status: SharedStatus = Arc::new(Mutex::new(DaemonStatus {
+        pid: std::process::id(),
+        started_at: Utc::now(),
+        contexts: contexts
+            .iter()
+            .map(|context| ContextStatus {
+                context: context.clone(),
+                interval_secs: 0,
+                syncing: false,
+                last_sync: None,
+                last_error: None,
+                next_sync: None,
+                paused: false,
+                paused_until: None,
+                failures: 0,
+                degraded_since: None,
+                held_back: None,
+            })
+            .collect(),
+        controls: true,
+    }));
+    let mut controls = Vec::new();
+    // One sync runs at a time across contexts, so one watchdog covers them all
+    let watchdog_minutes = Config::load().map(|c| c.sync.watchdog_minutes).unwrap_or(0);
+    let watchdog =
+        (watchdog_minutes > 0).then(|| Watchdog::spawn(Duration::from_secs(watchdog_minutes * 60)));
+    let (exit_tx, exit_rx) = mpsc::unbounded_channel();
+    let home = crate::home_dir()?;
+    let sockets: Vec<PathBuf> = contexts
+        .iter()
+        .map(|c| crate::config::context_dir(&home, c.as_deref()).join("daemon.sock"))
+        .collect();
+
+    let local = tokio::task::LocalSet::new();
+    let result = local
+        .run_until(async {
+            for (index, context) in contexts.into_iter().enumerate() {
+                let (control_tx, control_rx) = mpsc::unbounded_channel();
+                #[cfg(unix)]
+                {
+                    let socket = sockets[index].clone();
+                    let status = Arc::clone(&status);
+                    let control_tx = control_tx.clone();
+                    tokio::task::spawn_local(async move {
+                        if let Err(e) = super::ipc::serve(socket, index, status, control_tx).await {
+                            log::warn!("Control socket unavailable: {}", e);
+                        }
+                    });
+                }
+                controls.push(control_tx);
+
+                let status = Arc::clone(&status);
+                let watchdog = watchdog.clone();
+                let exit_tx = exit_tx.clone();
+                // A worker that panics starts over with a fresh server
+                let worker = async move {
+                    let mut control_rx = control_rx;
+                    loop {
+                        let mut server = DaemonServer::new();
+                        server.watchdog = watchdog.clone();
+                        let run = server.run_context(
+                            index,
+                            Arc::clone(&status),
+                            &mut control_rx,
+                            exit_tx.clone(),
+                        );
+                        let Err(panic) = super::watchdog::catch_panic(run).await else {
+                            break;
+                        };
+                        log::error!(
+                            event = "worker_restart";
+                            "Panic in the sync loop ({}); restarting it in {}s",
+                            panic,
+                            WORKER_RESTART_DELAY.as_secs()
+                        );
+                        if let Ok(mut status) = status.lock() {
+                            if let Some(context) = status.contexts.get_mut(index) {
+                                context.syncing = false;
+                                context.last_error = Some(format!("Daemon panicked: {}", panic));
+                            }
+                        }
+                        tokio::time::sleep(WORKER_RESTART_DELAY).await;
+                    }
+                };
+                tokio::task::spawn_local(crate::config::with_context(context, worker));
+            }
+            super::wake::spawn(controls.clone());
+            wait_for_stop(&controls, exit_rx).await
+        })
+        .await;
+
+    for socket in &sockets {
+        let _ = std::fs::remove_file(socket);
+    }
+    result?;
+    log::info!(event = "daemon_stop"; "Daemon stopped");
+    Ok(())
+}
+
+/// Block until a signal or a worker asks the daemon to stop. SIGHUP makes every
+/// context reload its config and sync right away instead.
+#[cfg(unix)]
+async fn wait_for_stop(
+    controls: &[mpsc::UnboundedSender<Control>],
+    mut exit: mpsc::UnboundedReceiver<()>,
+) -> Result<()> {
+    let mut sigterm = signal(SignalKind::terminate())?;
+    let mut sighup = signal(SignalKind::hangup())?;
+    let ctrl_c = tokio::signal::ctrl_c();
+    tokio::pin!(ctrl_c);
+
+    loop {
+        tokio::select! {
+            _ = exit.recv() => break,
+            _ = &mut ctrl_c => {
+                log::info!("Received Ctrl+C, stopping daemon");
+                break;
+            },
+            _ = sigterm.recv() => {
+                log::info!("Received SIGTERM, stopping daemon");
+                break;
+            },
+            _ = sighup.recv() => {
+                log::info!("Received SIGHUP, reloading config and syncing");
+                for control in controls {
+                    let _ = control.send(Control::ReloadConfig);
+                    let _ = control.send(Control::SyncNow);
+                }
+            },
+        }
+    }
+    Ok(())
+}
+
+#[cfg(not(unix))]
+async fn wait_for_stop(
+    _controls: &[mpsc::UnboundedSender<Control>],
+    mut exit: mpsc::UnboundedReceiver<()>,
+) -> Result<()> {
+    tokio::select! {
+        _ = exit.recv() => {},
+        _ = tokio::signal::ctrl_c() => log::info!("Received Ctrl+C, stopping daemon"),
+    }
+    Ok(())
+}
+
 pub struct DaemonServer {
     sync_interval: Duration,
     last_update_date: Option<chrono::NaiveDate>,
     binary_path: PathBuf,
     binary_mtime: Option<SystemTime>,
+    watchdog: Option<Watchdog>,
+    /// The step `run_sync` is on, for the last error
+    phase: Cell<&'static str>,
 }
 
 impl DaemonServer {
@@ -50,13 +228,11 @@ impl DaemonServer {
             last_update_date: None,
             binary_path,
             binary_mtime,
+            watchdog: None,
+            phase: Cell::new("pull"),
         }
     }
 
-    fn sync_interval(&self) -> Interval {
-        tokio::time::interval(self.sync_interval)
-    }
-
     /// Check if the binary has been updated since daemon started
     fn binary_updated(&self) -> bool {
         let current_mtime = std::fs::metadata(&self.binary_path)
@@ -69,83 +245,277 @@ impl DaemonServer {
         }
     }
 
-    pub async fn run(&mut self) -> Result<()> {
-        // Set daemon mode flag (thread-safe alternative to env var)
-        DAEMON_MODE.store(true, Ordering::Relaxed);
+    /// Sync one context on its own schedule, reporting into `status.contexts[index]`,
+    /// until the binary is updated. Requests from the context's socket arrive on
+    /// `control`. Runs inside [`crate::config::with_context`].
+    async fn run_context(
+        mut self,
+        index: usize,
+        status: SharedStatus,
+        control: &mut mpsc::UnboundedReceiver<Control>,
+        exit: mpsc::UnboundedSender<()>,
+    ) {
+        let label = crate::config::current_context()
+            .ok()
+            .flatten()
+            .unwrap_or_else(|| "default".to_string());
+        let update_status = |f: &dyn Fn(&mut ContextStatus)| {
+            if let Some(context) = status
+                .lock()
+                .ok()
+                .as_mut()
+                .and_then(|s| s.contexts.get_mut(index))
+            {
+                f(context);
+            }
+        };
+        // Ends a timed pause that has run out
+        let paused = || {
+            let Ok(mut status) = status.lock() else {
+                return false;
+            };
+            let Some(context) = status.contexts.get_mut(index) else {
+                return false;
+            };
+            if context.paused && !context.is_paused() {
+                log::info!("[{}] Pause ended, syncing on schedule again", label);
+                context.paused = false;
+                context.paused_until = None;
+            }
+            context.paused
+        };
 
-        log::info!("Daemon starting (pid {})", std::process::id());
-        log::info!("Sync interval: {} seconds", self.sync_interval.as_secs());
+        let mut backoff = Backoff::default();
+        let mut held_back: Option<power::Constraint> = None;
+        // A log that grew or aged while the daemon was stopped
+        self.rotate_log_if_needed();
 
-        #[cfg(unix)]
-        {
-            let mut sync_timer = self.sync_interval();
-            let mut sigterm = signal(SignalKind::terminate())?;
-            let mut sighup = signal(SignalKind::hangup())?;
-            let ctrl_c = tokio::signal::ctrl_c();
-            tokio::pin!(ctrl_c);
-            sync_timer.tick().await;
+        // Each pass runs with the config as it was read; `reload-config` starts another
+        loop {
+            let config = Config::load();
+            if let Ok(config) = &config {
+                // The log is the first context's, so its config says how to write it
+                if index == 0 {
+                    super::logs::set_format(config.sync.log_format);
+                }
+                self.sync_interval = config.sync.interval_duration();
+                if crate::config::SyncConfig::parse_interval(&config.sync.interval).is_none() {
+                    log::warn!(
+                        "[{}] Can't read sync.interval '{}', syncing every 5 minutes",
+                        label,
+                        config.sync.interval
+                    );
+                }
+            }
+            log::info!(
+                "[{}] Sync interval: {} seconds",
+                label,
+                self.sync_interval.as_secs()
+            );
+
+            let _project_watcher = match config
+                .as_ref()
+                .map_err(|e| anyhow::anyhow!("{}", e))
+                .and_then(|config| super::watcher::watch_projects(config, &crate::home_dir()?))
+            {
+                Ok(watcher) => watcher,
+                Err(e) => {
+                    log::warn!("[{}] Project watcher disabled: {}", label, e);
+                    None
+                }
+            };
 
+            // Opt-in stale-file warnings, between full syncs
+            let stale_check_minutes = config
+                .as_ref()
+                .map(|c| c.sync.stale_check_minutes)
+                .unwrap_or(0);
+            let mut stale_timer =
+                tokio::time::interval(Duration::from_secs(stale_check_minutes.max(1) * 60));
+            let mut notified_stale = HashSet::new();
+            let sync_on_wake = config.as_ref().map(|c| c.sync.sync_on_wake).unwrap_or(true);
+
+            let interval = self.sync_interval;
+            let next_sync = |delay: Duration| {
+                chrono::Duration::from_std(delay)
+                    .ok()
+                    .map(|d| Utc::now() + d)
+            };
+            // Scheduled syncs wait longer while they keep failing
+            let schedule = |delay: Duration| {
+                tokio::time::interval_at(tokio::time::Instant::now() + delay, interval)
+            };
+            let delay = backoff.delay(interval);
+            update_status(&|c| {
+                c.interval_secs = interval.as_secs();
+                c.next_sync = next_sync(delay);
+            });
+
+            let mut sync_timer = schedule(delay);
+            // Scheduled syncs passed over while stretched by low battery or metering
+            let mut held_ticks = 0;
             loop {
                 tokio::select! {
                     _ = sync_timer.tick() => {
-                        if let TickResult::Exit = self.run_tick().await { break; }
-                    },
-                    _ = &mut ctrl_c => {
-                        log::info!("Received Ctrl+C, stopping daemon");
-                        break;
+                        if paused() {
+                            log::debug!("[{}] Paused, skipping scheduled sync", label);
+                            update_status(&|c| c.next_sync = next_sync(interval));
+                            continue;
+                        }
+                        let held = config.as_ref().ok().and_then(|c| power::check(&c.sync));
+                        let kind = |c: &power::Constraint| std::mem::discriminant(c);
+                        if held.map(|(why, _)| kind(&why)) != held_back.as_ref().map(kind) {
+                            match held {
+                                Some((why, policy)) => log::info!(
+                                    "[{}] {}: {} scheduled syncs",
+                                    label,
+                                    why,
+                                    if policy == PowerPolicy::Skip { "skipping" } else { "stretching" }
+                                ),
+                                None => log::info!("[{}] Syncing on schedule again", label),
+                            }
+                        }
+                        held_back = held.map(|(why, _)| why);
+                        update_status(&|c| c.held_back = held_back.map(|why| why.to_string()));
+                        match held {
+                            Some((_, PowerPolicy::Skip)) => {
+                                update_status(&|c| c.next_sync = next_sync(interval));
+                                continue;
+                            }
+                            Some((_, PowerPolicy::Stretch)) => {
+                                held_ticks += 1;
+                                if held_ticks < power::STRETCH_FACTOR {
+                                    update_status(&|c| c.next_sync = next_sync(interval));
+                                    continue;
+                                }
+                                held_ticks = 0;
+                            }
+                            _ => held_ticks = 0,
+                        }
                     },
-                    _ = sigterm.recv() => {
-                        log::info!("Received SIGTERM, stopping daemon");
-                        break;
+                    request = control.recv() => match request {
+                        Some(Control::SyncNow) => {
+                            log::info!("[{}] Running immediate sync", label);
+                        }
+                        Some(Control::Wake) => {
+                            if !sync_on_wake || paused() {
+                                continue;
+                            }
+                            log::info!("[{}] Woke or reconnected, syncing now", label);
+                        }
+                        Some(Control::ReloadConfig) => {
+                            log::info!("[{}] Reloading config", label);
+                            break;
+                        }
+                        None => return,
                     },
-                    _ = sighup.recv() => {
-                        log::info!("Received SIGHUP, running immediate sync");
-                        if let Err(e) = self.run_sync().await {
-                            log::error!("Sync failed: {}", e);
+                    _ = stale_timer.tick(), if stale_check_minutes > 0 => {
+                        if let Err(e) = self.run_stale_check(&mut notified_stale) {
+                            log::warn!("[{}] Stale check failed: {}", label, e);
                         }
+                        continue;
                     },
-                };
-            }
-        }
-
-        #[cfg(not(unix))]
-        {
-            let mut sync_timer = self.sync_interval();
-            let ctrl_c = tokio::signal::ctrl_c();
-            tokio::pin!(ctrl_c);
-            sync_timer.tick().await;
+                }
 
-            loop {
-                tokio::select! {
-                    _ = sync_timer.tick() => {
-                        if let TickResult::Exit = self.run_tick().await { break; }
-                    },
-                    _ = &mut ctrl_c => {
-                        log::info!("Received Ctrl+C, stopping daemon");
-                        break;
-                    },
-                };
+                update_status(&|c| c.syncing = true);
+                match self.run_tick().await {
+                    TickResult::Exit => {
+                        let _ = exit.send(());
+                        return;
+                    }
+                    TickResult::Continue {
+                        error,
+                        phase,
+                        duration,
+                    } => {
+                        let failures = backoff.failures;
+                        let log_error = backoff.record(error.as_deref(), Utc::now());
+                        let delay = backoff.delay(interval);
+                        match &error {
+                            Some(e) => {
+                                if log_error {
+                                    log::error!(
+                                        event = "sync_failed",
+                                        context = label.as_str(),
+                                        phase = phase,
+                                        duration_ms = duration.as_millis() as u64,
+                                        error = e.as_str();
+                                        "[{}] Sync failed: {}", label, e
+                                    );
+                                }
+                                if backoff.failures == DEGRADED_AFTER {
+                                    log::warn!(
+                                        "[{}] {} syncs failed in a row; retrying less often until one succeeds",
+                                        label,
+                                        backoff.failures
+                                    );
+                                }
+                                log::info!(
+                                    "[{}] Next sync in {}m ({} failed in a row)",
+                                    label,
+                                    delay.as_secs() / 60,
+                                    backoff.failures
+                                );
+                            }
+                            None if failures > 0 => log::info!(
+                                event = "sync_recovered",
+                                context = label.as_str(),
+                                duration_ms = duration.as_millis() as u64;
+                                "[{}] Sync succeeded after {} failed in a row",
+                                label,
+                                failures
+                            ),
+                            None => log::info!(
+                                event = "sync",
+                                context = label.as_str(),
+                                duration_ms = duration.as_millis() as u64;
+                                "[{}] Sync finished in {:.1}s",
+                                label,
+                                duration.as_secs_f64()
+                            ),
+                        }
+                        let recorded = match &error {
+                            Some(e) => LastError {
+                                message: e.clone(),
+                                at: Utc::now(),
+                                phase: phase.to_string(),
+                                failures: backoff.failures,
+                            }
+                            .save(),
+                            None => LastError::clear(),
+                        };
+                        if let Err(e) = recorded {
+                            log::warn!("[{}] Failed to record the sync's result: {}", label, e);
+                        }
+                        // A manual sync while healthy leaves the schedule alone
+                        if failures > 0 || backoff.failures > 0 {
+                            sync_timer = schedule(delay);
+                        }
+                        update_status(&|c| {
+                            c.syncing = false;
+                            c.last_sync = Some(Utc::now());
+                            c.last_error = error.clone();
+                            c.next_sync = next_sync(delay);
+                            c.failures = backoff.failures;
+                            c.degraded_since = backoff.degraded_since;
+                        });
+                    }
+                }
             }
         }
-
-        log::info!("Daemon stopped");
-        Ok(())
     }
 
-    /// Rotate daemon.log if it exceeds MAX_LOG_BYTES.
-    /// Copies to .log.1 and truncates in-place to keep the logger's fd valid.
+    /// Rotate daemon.log once it's past `sync.log_max_mb` or `sync.log_max_days`
     fn rotate_log_if_needed(&self) {
-        let log_path = match crate::config::Config::config_dir() {
-            Ok(d) => d.join("daemon.log"),
-            Err(_) => return,
+        let (Ok(dir), Ok(config)) = (crate::config::Config::config_dir(), Config::load()) else {
+            return;
         };
-        if let Ok(meta) = std::fs::metadata(&log_path) {
-            if meta.len() > MAX_LOG_BYTES {
-                let backup = log_path.with_extension("log.1");
-                let _ = std::fs::copy(&log_path, &backup);
-                let _ = std::fs::File::create(&log_path); // truncate in-place
-                log::info!("Rotated daemon.log ({} bytes)", meta.len());
+        match super::logs::rotate_if_needed(&dir.join("daemon.log"), &config.sync, Utc::now()) {
+            Ok(Some(reason)) => {
+                log::info!(event = "log_rotated"; "Rotated daemon.log ({})", reason)
             }
+            Ok(None) => {}
+            Err(e) => log::warn!("Failed to rotate daemon.log: {}", e),
         }
     }
 
@@ -159,14 +529,52 @@ impl DaemonServer {
         }
 
         log::info!("Running periodic sync...");
-        if let Err(e) = self.run_sync().await {
-            log::error!("Sync failed: {}", e);
+        if let Some(watchdog) = &self.watchdog {
+            watchdog.start();
         }
+        self.phase.set("pull");
+        let started = std::time::Instant::now();
+        let sync = super::watchdog::catch_panic(self.run_sync());
+        let result = match self.watchdog.as_ref().map(Watchdog::timeout) {
+            Some(limit) => match tokio::time::timeout(limit, sync).await {
+                Ok(result) => result,
+                Err(_) => {
+                    log::error!(
+                        event = "sync_timeout";
+                        "Sync timed out after {}m and was dropped",
+                        limit.as_secs() / 60
+                    );
+                    Ok(Err(anyhow::anyhow!(
+                        "timed out after {}m",
+                        limit.as_secs() / 60
+                    )))
+                }
+            },
+            None => sync.await,
+        };
+        let duration = started.elapsed();
+        let stuck = self.watchdog.as_ref().is_some_and(Watchdog::finish);
+        let error = match result {
+            Ok(Ok(())) => None,
+            Ok(Err(e)) => {
+                let e = if stuck {
+                    format!("Sync hung and was stopped by the watchdog: {}", e)
+                } else {
+                    e.to_string()
+                };
+                Some(e)
+            }
+            // The panic hook has logged it
+            Err(panic) => Some(format!("Sync panicked: {}", panic)),
+        };
+
+        self.run_visibility_check();
+        self.run_drift_report();
 
-        if self.should_run_update() {
+        if !self.upgrades_held() && self.should_run_update() {
             log::info!("Running daily package update...");
             if let Err(e) = self.run_package_updates().await {
-                log::error!("Package update failed: {}", e);
+                log::error!(event = "package_update_failed", error:% = e; "Package update failed: {}", e);
             }
             if self.binary_updated() {
                 log::info!("Binary updated during package upgrade, exiting for restart");
@@ -174,7 +582,100 @@ impl DaemonServer {
             }
         }
 
-        TickResult::Continue
+        TickResult::Continue {
+            error,
+            phase: self.phase.get(),
+            duration,
+        }
+    }
+
+    /// Check once a day whether the sync repo is public, notifying each time it is
+    fn run_visibility_check(&self) {
+        let Ok(_sync_lock) = crate::sync::acquire_sync_lock(false) else {
+            return;
+        };
+        let (Ok(config), Ok(mut state)) = (Config::load(), SyncState::load()) else {
+            return;
+        };
+        if !crate::sync::visibility::is_due(&config, &state) {
+            return;
+        }
+        match crate::sync::visibility::refresh(&config, &mut state) {
+            Ok(Some(true)) => {
+                log::warn!("{}", crate::sync::visibility::PUBLIC_WARNING);
+                notify_public_repo().ok();
+            }
+            Ok(_) => {}
+            Err(e) => {
+                log::debug!("Repo visibility check failed: {}", e);
+                return;
+            }
+        }
+        if let Err(e) = state.save() {
+            log::warn!("Failed to save repo visibility: {}", e);
+        }
+    }
+
+    /// Make and deliver a drift report every `report.every_days` days, if enabled
+    fn run_drift_report(&self) {
+        let Ok(_sync_lock) = crate::sync::acquire_sync_lock(false) else {
+            return;
+        };
+        let (Ok(config), Ok(mut state)) = (Config::load(), SyncState::load()) else {
+            return;
+        };
+        if !crate::sync::report::is_due(&config, &state) {
+            return;
+        }
+        let report = match SyncEngine::sync_path()
+            .and_then(|sync_path| DriftReport::build(&config, &state, &sync_path))
+        {
+            Ok(report) => report,
+            Err(e) => {
+                log::warn!("Drift report failed: {}", e);
+                return;
+            }
+        };
+        log::info!("Drift report: {}", report.summary());
+        if let Err(e) = report.deliver(&config) {
+            log::warn!("{}", e);
+        }
+        // Recorded even after a failed delivery, so a dead webhook isn't retried every tick
+        state.last_report = Some(Utc::now());
+        if let Err(e) = state.save() {
+            log::warn!("Failed to save drift report time: {}", e);
+        }
+    }
+
+    /// Fetch and notify about synced dotfiles that changed on the remote, once per
+    /// remote version
+    fn run_stale_check(&self, notified: &mut HashSet<(String, String)>) -> Result<()> {
+        let Ok(_sync_lock) = crate::sync::acquire_sync_lock(false) else {
+            return Ok(());
+        };
+        let config = Config::load()?;
+        if !config.has_personal_features() {
+            return Ok(());
+        }
+        let state = SyncState::load()?;
+        let git = GitBackend::open(&SyncEngine::sync_path()?)?;
+        git.fetch()?;
+
+        let stale =
+            crate::sync::stale::find_stale(&config, &state, &git, &crate::home_dir()?, None)?;
+        let current: HashSet<(String, String)> =
+            stale.into_iter().map(|f| (f.file, f.remote_hash)).collect();
+        let new: Vec<String> = current
+            .iter()
+            .filter(|key| !notified.contains(*key))
+            .map(|(file, _)| file.clone())
+            .collect();
+        if !new.is_empty() {
+            log::warn!("Behind remote: {}", new.join(", "));
+            crate::sync::notify_stale_files(&new).ok();
+        }
+        *notified = current;
+        Ok(())
     }
 
     async fn run_sync(&self) -> Result<()> {
@@ -196,16 +697,35 @@ impl DaemonServer {
         let sync_path = SyncEngine::sync_path()?;
         let home = crate::home_dir()?;
 
+        self.phase.set("pull");
         // Pull latest changes
         log::debug!("Pulling latest changes...");
         let git = GitBackend::open(&sync_path)?;
-        git.pull()?;
+        let offline = match git.pull() {
+            Ok(()) => false,
+            Err(e) if crate::sync::git::is_offline_error(&e) => {
+                log::warn!("Offline, syncing locally: {}", e);
+                true
+            }
+            Err(e) => return Err(e),
+        };
 
         crate::sync::check_sync_format_version(&sync_path)?;
 
+        // Don't apply or re-sign encrypted files changed outside a sync; a manual
+        // `tether sync` shows what changed and asks first
+        let problems = crate::sync::integrity::check(&config, &sync_path)?;
+        if !problems.is_empty() {
+            for problem in &problems {
+                log::warn!("Sync repo integrity: {}", problem);
+            }
+            notify_integrity_warning(problems.len()).ok();
+            return Ok(());
+        }
+
         // Pull from team repo if enabled
         if let Some(team) = &config.team {
-            if team.enabled {
+            if team.enabled && !offline {
                 let team_sync_dir = Config::team_sync_dir()?;
                 if team_sync_dir.exists() {
                     let team_git = GitBackend::open(&team_sync_dir)?;
@@ -217,15 +737,16 @@ impl DaemonServer {
 
         // Import remote config before using it
         if config.security.encrypt_dotfiles {
-            if let Some(new_config) =
-                crate::cli::commands::sync::sync_tether_config(&sync_path, &home)?
-            {
+            if let Some(new_config) = crate::cli::commands::sync::sync_tether_config(&sync_path)? {
                 config = new_config;
             }
         }
 
         // Load state and machine state
         let mut state = SyncState::load()?;
+        // Read-only machines apply remote changes but only ever push their machine state
+        let read_only =
+            config.machine_role(&state.machine_id) == crate::config::MachineRole::ReadOnly;
 
         // Auto-assign machine to default profile on first run after v2 migration
         if !config.profiles.is_empty() && !config.machine_profiles.contains_key(&state.machine_id) {
@@ -239,8 +760,12 @@ impl DaemonServer {
         let machine_state_for_decrypt =
             MachineState::load_from_repo(&sync_path, &state.machine_id)?.unwrap_or_default();
 
-        // Apply remote changes (dotfiles, config dirs, project configs)
+        self.phase.set("apply");
+        // Apply remote changes (deletions, dotfiles, config dirs, project configs)
         if config.security.encrypt_dotfiles {
+            crate::cli::commands::sync::apply_remote_deletions(
+                &config, &sync_path, &home, &mut state,
+            )?;
             crate::cli::commands::sync::decrypt_from_repo(
                 &config,
                 &sync_path,
@@ -248,16 +773,22 @@ impl DaemonServer {
                 &mut state,
                 &machine_state_for_decrypt,
                 false,
+                None,
             )?;
         }
 
+        self.phase.set("dotfiles");
         // Now sync local changes to remote
         let conflict_state = crate::sync::ConflictState::load().unwrap_or_default();
 
         // Sync dotfiles to remote (only if feature enabled)
-        if config.features.personal_dotfiles {
+        if config.features.personal_dotfiles && !read_only {
             let daemon_machine_id = state.machine_id.clone();
-            let daemon_profile = config.profile_name(&daemon_machine_id).to_string();
+
+            // Only propagates when dotfiles.propagate_deletions is set
+            crate::cli::commands::sync::propagate_local_deletions(
+                &config, &sync_path, &home, &mut state, false, false,
+            )?;
 
             for entry in config.effective_dotfiles(&daemon_machine_id) {
                 // Security: validate path to prevent traversal attacks
@@ -268,6 +799,7 @@ impl DaemonServer {
 
                 let pattern = entry.path();
                 let shared = config.is_dotfile_shared(&daemon_machine_id, pattern);
+                let daemon_profile = config.dotfile_profile(&daemon_machine_id, pattern);
                 let expanded = crate::sync::expand_dotfile_glob(pattern, &home);
 
                 for file in expanded {
@@ -277,49 +809,18 @@ impl DaemonServer {
                     }
 
                     let source = home.join(&file);
-                    if source.exists() {
-                        if let Ok(content) = std::fs::read(&source) {
-                            let hash = crate::sha256_hex(&content);
-                            let file_changed = state
-                                .files
-                                .get(&file)
-                                .map(|f| f.hash != hash)
-                                .unwrap_or(true);
-
-                            if file_changed {
-                                log::info!("File changed: {}", file);
-
-                                if config.security.encrypt_dotfiles {
-                                    let key = crate::security::get_encryption_key()?;
-                                    let encrypted = crate::security::encrypt(&content, &key)?;
-                                    let repo_path = crate::sync::dotfile_to_repo_path_profiled(
-                                        &file,
-                                        true,
-                                        &daemon_profile,
-                                        shared,
-                                    );
-                                    let dest = sync_path.join(&repo_path);
-                                    if let Some(parent) = dest.parent() {
-                                        std::fs::create_dir_all(parent)?;
-                                    }
-                                    std::fs::write(&dest, encrypted)?;
-                                } else {
-                                    let repo_path = crate::sync::dotfile_to_repo_path_profiled(
-                                        &file,
-                                        false,
-                                        &daemon_profile,
-                                        shared,
-                                    );
-                                    let dest = sync_path.join(&repo_path);
-                                    if let Some(parent) = dest.parent() {
-                                        std::fs::create_dir_all(parent)?;
-                                    }
-                                    std::fs::write(&dest, &content)?;
-                                }
-
-                                state.update_file(&file, hash.clone());
-                            }
-                        }
+                    if source.exists()
+                        && crate::cli::commands::sync::export_dotfile(
+                            &config,
+                            &sync_path,
+                            &mut state,
+                            &source,
+                            &file,
+                            daemon_profile,
+                            shared,
+                        )?
+                    {
+                        log::info!(event = "file_changed", file = file.as_str(); "File changed: {}", file);
                     }
                 }
             }
@@ -330,21 +831,21 @@ impl DaemonServer {
             for dir in discovered {
                 let current_profile = config.profile_name(&daemon_machine_id).to_string();
                 if let Some(profile) = config.profiles.get_mut(&current_profile) {
-                    if !profile.dirs.contains(&dir) {
+                    if !profile.dirs.iter().any(|d| d.path() == dir) {
                         log::info!("Auto-discovered sourced directory: {}", dir);
-                        profile.dirs.push(dir);
+                        profile.dirs.push(dir.into());
                         config_changed = true;
                     }
-                } else if !config.dotfiles.dirs.contains(&dir) {
+                } else if !config.dotfiles.dirs.iter().any(|d| d.path() == dir) {
                     log::info!("Auto-discovered sourced directory: {}", dir);
-                    config.dotfiles.dirs.push(dir);
+                    config.dotfiles.dirs.push(dir.into());
                     config_changed = true;
                 }
             }
             if config_changed {
-                config.dotfiles.dirs.sort();
+                config.dotfiles.dirs.sort_by(|a, b| a.path().cmp(b.path()));
                 for profile in config.profiles.values_mut() {
-                    profile.dirs.sort();
+                    profile.dirs.sort_by(|a, b| a.path().cmp(b.path()));
                 }
                 config.save()?;
             }
@@ -376,6 +877,7 @@ impl DaemonServer {
             log::warn!("Failed to sync team project secrets: {}", e);
         }
 
+        self.phase.set("packages");
         // Build machine state (packages, dotfiles, project configs, checkouts)
         let mut machine_state =
             crate::cli::commands::sync::build_machine_state(&config, &state, &sync_path).await?;
@@ -383,6 +885,7 @@ impl DaemonServer {
         // Import packages (daemon mode: defer casks that need password)
         if config.features.personal_packages {
             let previously_deferred = state.deferred_casks.clone();
+            let previously_pending = state.pending_installs.clone();
             let deferred_casks = import_packages(
                 &config,
                 &sync_path,
@@ -424,6 +927,16 @@ impl DaemonServer {
                 state.save()?;
             }
 
+            // Notify once per new set of installs waiting for approval
+            if state.pending_installs != previously_pending {
+                let pending = state.pending_install_count();
+                if pending > 0 {
+                    notify_pending_installs(pending).ok();
+                    log::info!("{} package install(s) waiting for approval", pending);
+                }
+                state.save()?;
+            }
+
             // Rebuild machine state after import to capture newly installed packages
             machine_state =
                 crate::cli::commands::sync::build_machine_state(&config, &state, &sync_path)
@@ -431,52 +944,99 @@ impl DaemonServer {
         }
 
         // Export package manifests using union of all machine states
-        if config.features.personal_packages {
+        if config.features.personal_packages && !read_only {
             crate::sync::sync_packages(&config, &mut state, &sync_path, &machine_state, false)
                 .await?;
         }
 
+        // Drop anything a read-only machine wrote to the repo (e.g. layout migrations)
+        if read_only {
+            git.discard_changes()?;
+        }
+
         // Save machine state
         machine_state.save_to_repo(&sync_path)?;
 
         // Export tether config to sync repo
-        if config.security.encrypt_dotfiles {
-            crate::cli::commands::sync::export_tether_config(&sync_path, &home, &mut state)?;
+        if config.security.encrypt_dotfiles && !read_only {
+            crate::cli::commands::sync::export_tether_config(&sync_path, &mut state)?;
         }
 
-        // Commit and push if changes made
-        let has_changes = git.has_changes()?;
-        if has_changes {
+        self.phase.set("commit");
+        // Commit changes, then push them along with any queued while offline
+        if !read_only {
+            crate::sync::integrity::sign_repo(&config, &sync_path, &state.machine_id)?;
+        }
+        if git.has_changes()? {
             log::info!("Committing changes...");
             git.commit("Auto-sync from daemon", &state.machine_id)?;
-            git.push()?;
-            log::info!("Sync complete - changes pushed");
+            crate::security::audit::record(crate::security::audit::AuditEvent::Sync, "daemon");
         } else {
             log::debug!("No changes to sync");
         }
+        self.phase.set("push");
+        let pending = git.unpushed_commits()?;
+        if pending > 0 && offline {
+            log::info!("{} commit(s) queued until the remote is reachable", pending);
+        } else if pending > 0 && !batch_ready(&config.sync, &git)? {
+            log::debug!("Holding {} commit(s) for the next batch", pending);
+        } else if pending > 0 {
+            if config.sync.batching_enabled() {
+                git.squash_unpushed("Auto-sync from daemon", &state.machine_id)?;
+            }
+            match git.push() {
+                Ok(()) => {
+                    log::info!(event = "push", commits = pending; "Sync complete - changes pushed")
+                }
+                Err(e) if crate::sync::git::is_offline_error(&e) => {
+                    log::warn!(
+                        event = "push_failed",
+                        commits = pending,
+                        error:% = e;
+                        "Push failed, {} commit(s) queued: {}", pending, e
+                    );
+                }
+                Err(e) => return Err(e),
+            }
+        }
 
         state.mark_synced();
 
+        self.phase.set("team");
         // Push team repo changes (if write access enabled)
         if let Some(team) = &config.team {
-            if team.enabled && !team.read_only {
+            if team.enabled && !team.read_only && !read_only && !offline {
                 let team_sync_dir = Config::team_sync_dir()?;
                 if team_sync_dir.exists() {
                     let team_git = GitBackend::open(&team_sync_dir)?;
+                    if config.security.audit_sync {
+                        if let Err(e) = crate::security::audit::export_to_team(
+                            &team_sync_dir,
+                            &state.machine_id,
+                        ) {
+                            log::warn!("Could not export the audit log: {}", e);
+                        }
+                    }
                     if team_git.has_changes()? {
                         let dotfiles_dir = team_sync_dir.join("dotfiles");
                         if dotfiles_dir.exists() {
                             for entry in std::fs::read_dir(&dotfiles_dir)? {
                                 let entry = entry?;
                                 if entry.file_type()?.is_file() {
+                                    let name = entry.file_name().to_string_lossy().to_string();
                                     if let Ok(findings) =
-                                        crate::security::scan_for_secrets(&entry.path())
+                                        crate::security::scan_for_secrets(&entry.path(), &name)
                                     {
                                         if !findings.is_empty() {
+                                            let fingerprints: Vec<_> = findings
+                                                .iter()
+                                                .map(|f| f.fingerprint.as_str())
+                                                .collect();
                                             log::error!(
-                                                "Team push blocked: {} contains {} secret(s)",
-                                                entry.file_name().to_string_lossy(),
-                                                findings.len()
+                                                "Team push blocked: {} contains {} secret(s) (fingerprints: {})",
+                                                name,
+                                                findings.len(),
+                                                fingerprints.join(", ")
                                             );
                                             anyhow::bail!(
                                                 "Cannot push secrets to team repo. Remove sensitive data first."
@@ -509,6 +1069,8 @@ impl DaemonServer {
             }
         }
 
+        crate::sync::growth::record(&mut state, &git, Utc::now());
+
         // Always save state
         state.save()?;
 
@@ -518,6 +1080,11 @@ impl DaemonServer {
     /// Team-only sync: only sync team repositories.
     /// Note: caller (run_sync) already holds the sync lock.
     async fn run_team_only_sync(&self, config: &Config) -> Result<()> {
+        self.phase.set("team");
+        let mut state = SyncState::load()?;
+        let read_only =
+            config.machine_role(&state.machine_id) == crate::config::MachineRole::ReadOnly;
+
         let teams = match &config.teams {
             Some(t) if !t.active.is_empty() => t,
             _ => {
@@ -544,8 +1111,7 @@ impl DaemonServer {
             log::debug!("Team '{}' synced", team_name);
 
             // Push changes if we have write access
-            if !team_config.read_only && team_git.has_changes()? {
-                let state = SyncState::load()?;
+            if !team_config.read_only && !read_only && team_git.has_changes()? {
                 team_git.commit("Update team configs", &state.machine_id)?;
                 team_git.push()?;
             }
@@ -553,7 +1119,6 @@ impl DaemonServer {
 
         // Sync team project secrets
         let home = crate::home_dir()?;
-        let mut state = SyncState::load()?;
         if let Err(e) =
             crate::cli::commands::sync::sync_team_project_secrets(config, &home, &mut state)
         {
@@ -565,6 +1130,19 @@ impl DaemonServer {
         Ok(())
     }
 
+    /// Whether low battery or a metered connection holds back the daily package
+    /// upgrade; it runs on the first sync after that ends
+    fn upgrades_held(&self) -> bool {
+        let Some((why, _)) = Config::load()
+            .ok()
+            .and_then(|config| super::power::check(&config.sync))
+        else {
+            return false;
+        };
+        log::debug!("Leaving package upgrades for later: {}", why);
+        true
+    }
+
     /// Check if we should run daily package updates (once per 24h, catches up on missed runs)
     fn should_run_update(&mut self) -> bool {
         // In-memory guard: don't run twice in same session day
@@ -645,10 +1223,74 @@ impl Default for DaemonServer {
     }
 }
 
+/// Whether commits held under the `[sync]` batching settings should be pushed now
+fn batch_ready(sync: &crate::config::SyncConfig, git: &GitBackend) -> Result<bool> {
+    if !sync.batching_enabled() {
+        return Ok(true);
+    }
+    // Machine state changes every sync; only other files count as changes
+    let changed_files = git
+        .unpushed_files()?
+        .iter()
+        .filter(|f| !f.starts_with("machines/"))
+        .count();
+    let last_change = git
+        .unpushed_commit_times(&[":(exclude)machines"])?
+        .first()
+        .copied();
+    let oldest = git.unpushed_commit_times(&[])?.last().copied();
+    Ok(should_push_batch(
+        sync,
+        chrono::Utc::now(),
+        changed_files,
+        last_change,
+        oldest,
+    ))
+}
+
+fn should_push_batch(
+    sync: &crate::config::SyncConfig,
+    now: chrono::DateTime<chrono::Utc>,
+    changed_files: usize,
+    last_change: Option<chrono::DateTime<chrono::Utc>>,
+    oldest: Option<chrono::DateTime<chrono::Utc>>,
+) -> bool {
+    let minutes = |m: u64| chrono::Duration::minutes(m as i64);
+    if oldest.is_some_and(|t| now - t >= minutes(sync.batch_max_minutes)) {
+        return true;
+    }
+    let Some(last_change) = last_change else {
+        return false;
+    };
+    changed_files >= sync.batch_min_files.max(1)
+        && now - last_change >= minutes(sync.batch_quiet_minutes)
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
 
+    #[test]
+    fn test_should_push_batch() {
+        let mut sync = crate::config::Config::default().sync;
+        sync.batch_quiet_minutes = 10;
+        sync.batch_min_files = 3;
+        let now = chrono::Utc::now();
+        let ago = |m: i64| Some(now - chrono::Duration::minutes(m));
+
+        // Quiet and big enough
+        assert!(should_push_batch(&sync, now, 3, ago(15), ago(30)));
+        // Still being edited
+        assert!(!should_push_batch(&sync, now, 3, ago(2), ago(30)));
+        // Too small
+        assert!(!should_push_batch(&sync, now, 2, ago(15), ago(30)));
+        // Only machine state changed
+        assert!(!should_push_batch(&sync, now, 0, None, ago(30)));
+        // Held too long: push regardless
+        assert!(should_push_batch(&sync, now, 1, ago(1), ago(61)));
+        assert!(should_push_batch(&sync, now, 0, None, ago(61)));
+    }
+
     #[test]
     fn test_daemon_mode_flag_default_false() {
         // Reset to known state (other tests may have set it)
@@ -691,6 +1333,8 @@ mod tests {
             last_update_date: None,
             binary_path: PathBuf::from("/nonexistent/binary"),
             binary_mtime: None,
+            watchdog: None,
+            phase: Cell::new("pull"),
         };
         assert!(!server.binary_updated());
     }
@@ -705,6 +1349,8 @@ mod tests {
             binary_path: std::env::current_exe().unwrap(),
             // Set start mtime to epoch so current binary is always "newer"
             binary_mtime: Some(SystemTime::UNIX_EPOCH),
+            watchdog: None,
+            phase: Cell::new("pull"),
         };
         assert!(server.binary_updated());
     }
@@ -0,0 +1,267 @@
+//! Syncs when a laptop wakes from sleep or joins a network, rather than up to a full
+//! `sync.interval` later. A background thread watches for both:
+//!
+//! - Sleep: the monotonic clock stops while the machine sleeps and the wall clock
+//!   doesn't, so a wall clock that ran well ahead between two checks means it slept.
+//! - Network: the machine's addresses (from `getifaddrs`) changed and it has one.
+//!   On Linux a netlink socket wakes the thread as soon as a link or address changes;
+//!   elsewhere it looks every few seconds.
+//!
+//! Each event sends [`Control::Wake`] to every context's worker, which syncs unless
+//! it's paused or has `sync.sync_on_wake` off.
+
+use super::ipc::Control;
+use std::time::{Duration, Instant, SystemTime};
+use tokio::sync::mpsc::UnboundedSender;
+
+/// How often the watcher looks, when nothing wakes it sooner
+const CHECK_EVERY: Duration = Duration::from_secs(5);
+
+/// Wall-clock time beyond the monotonic clock that counts as having slept
+const SLEEP_GAP: Duration = Duration::from_secs(30);
+
+/// How long the network gets to settle (DHCP, VPN) before syncing
+const SETTLE: Duration = Duration::from_secs(5);
+
+/// Fewest seconds between two wake syncs, so a flapping connection doesn't sync
+/// continuously
+const MIN_BETWEEN: Duration = Duration::from_secs(60);
+
+/// Watch for wake and network changes, sending [`Control::Wake`] to `controls`
+pub fn spawn(controls: Vec<UnboundedSender<Control>>) {
+    std::thread::spawn(move || {
+        let mut waiter = Waiter::new();
+        let mut known = addresses();
+        let mut clock = (Instant::now(), SystemTime::now());
+        let mut last_wake: Option<Instant> = None;
+        loop {
+            waiter.wait();
+            let slept = slept_for(clock);
+            clock = (Instant::now(), SystemTime::now());
+            let current = addresses();
+            let reason = wake_reason(slept, &known, &current);
+            known = current;
+
+            let Some(reason) = reason else {
+                continue;
+            };
+            if last_wake.is_some_and(|last| last.elapsed() < MIN_BETWEEN) {
+                log::debug!("{}, but synced recently", reason);
+                continue;
+            }
+            std::thread::sleep(SETTLE);
+            known = addresses();
+            clock = (Instant::now(), SystemTime::now());
+            last_wake = Some(Instant::now());
+
+            log::info!(event = "wake"; "{}, syncing now", reason);
+            let sent = controls
+                .iter()
+                .filter(|control| control.send(Control::Wake).is_ok())
+                .count();
+            // Every worker is gone: the daemon is stopping
+            if sent == 0 {
+                return;
+            }
+        }
+    });
+}
+
+/// How much longer the wall clock ran than the monotonic one since `since`
+fn slept_for(since: (Instant, SystemTime)) -> Duration {
+    let awake = since.0.elapsed();
+    let wall = since.1.elapsed().unwrap_or_default();
+    wall.saturating_sub(awake)
+}
+
+/// Why a sync is due, given how long the machine slept and its addresses before and
+/// after. A wake without an address waits for the network to come up.
+fn wake_reason(slept: Duration, before: &[String], after: &[String]) -> Option<String> {
+    if after.is_empty() {
+        return None;
+    }
+    if slept >= SLEEP_GAP {
+        Some(format!("Woke from sleep after {}m", slept.as_secs() / 60))
+    } else if before != after {
+        Some(if before.is_empty() {
+            "Network connected".to_string()
+        } else {
+            "Network changed".to_string()
+        })
+    } else {
+        None
+    }
+}
+
+/// `<interface> <address>` for every routable address of an interface that's up,
+/// sorted. Loopback and IPv6 link-local addresses exist without a network, so they
+/// don't count.
+fn addresses() -> Vec<String> {
+    use std::net::{Ipv4Addr, Ipv6Addr};
+
+    let mut found = Vec::new();
+    let mut list: *mut libc::ifaddrs = std::ptr::null_mut();
+    unsafe {
+        if libc::getifaddrs(&mut list) != 0 {
+            return found;
+        }
+        let mut entry = list;
+        while !entry.is_null() {
+            let ifa = &*entry;
+            entry = ifa.ifa_next;
+            let flags = ifa.ifa_flags as libc::c_int;
+            if ifa.ifa_addr.is_null()
+                || flags & libc::IFF_UP == 0
+                || flags & libc::IFF_LOOPBACK != 0
+            {
+                continue;
+            }
+            let address = match (*ifa.ifa_addr).sa_family as libc::c_int {
+                libc::AF_INET => {
+                    let addr = &*(ifa.ifa_addr as *const libc::sockaddr_in);
+                    Ipv4Addr::from(u32::from_be(addr.sin_addr.s_addr)).to_string()
+                }
+                libc::AF_INET6 => {
+                    let addr = &*(ifa.ifa_addr as *const libc::sockaddr_in6);
+                    let ip = Ipv6Addr::from(addr.sin6_addr.s6_addr);
+                    if ip.segments()[0] & 0xffc0 == 0xfe80 {
+                        continue;
+                    }
+                    ip.to_string()
+                }
+                _ => continue,
+            };
+            let name = std::ffi::CStr::from_ptr(ifa.ifa_name).to_string_lossy();
+            found.push(format!("{} {}", name, address));
+        }
+        libc::freeifaddrs(list);
+    }
+    found.sort();
+    found
+}
+
+/// Blocks until a link or address changes, or [`CHECK_EVERY`] passes
+#[cfg(target_os = "linux")]
+struct Waiter {
+    /// Netlink socket subscribed to link and address changes, if one could be opened
+    socket: Option<libc::c_int>,
+}
+
+#[cfg(target_os = "linux")]
+impl Waiter {
+    fn new() -> Self {
+        unsafe {
+            let socket = libc::socket(
+                libc::AF_NETLINK,
+                libc::SOCK_RAW | libc::SOCK_CLOEXEC,
+                libc::NETLINK_ROUTE,
+            );
+            if socket < 0 {
+                return Self { socket: None };
+            }
+            let mut addr: libc::sockaddr_nl = std::mem::zeroed();
+            addr.nl_family = libc::AF_NETLINK as libc::sa_family_t;
+            addr.nl_groups =
+                (libc::RTMGRP_LINK | libc::RTMGRP_IPV4_IFADDR | libc::RTMGRP_IPV6_IFADDR) as u32;
+            let timeout = libc::timeval {
+                tv_sec: CHECK_EVERY.as_secs() as libc::time_t,
+                tv_usec: 0,
+            };
+            let bound = libc::bind(
+                socket,
+                &addr as *const libc::sockaddr_nl as *const libc::sockaddr,
+                std::mem::size_of::<libc::sockaddr_nl>() as libc::socklen_t,
+            ) == 0
+                && libc::setsockopt(
+                    socket,
+                    libc::SOL_SOCKET,
+                    libc::SO_RCVTIMEO,
+                    &timeout as *const libc::timeval as *const libc::c_void,
+                    std::mem::size_of::<libc::timeval>() as libc::socklen_t,
+                ) == 0;
+            if !bound {
+                log::debug!("Netlink unavailable, polling for network changes");
+                libc::close(socket);
+                return Self { socket: None };
+            }
+            Self {
+                socket: Some(socket),
+            }
+        }
+    }
+
+    fn wait(&mut self) {
+        let Some(socket) = self.socket else {
+            std::thread::sleep(CHECK_EVERY);
+            return;
+        };
+        // The messages themselves don't matter: the addresses are read afresh
+        let mut buf = [0u8; 8192];
+        let received =
+            unsafe { libc::recv(socket, buf.as_mut_ptr() as *mut libc::c_void, buf.len(), 0) };
+        if received > 0 {
+            // Changes come in bursts; let the rest arrive
+            std::thread::sleep(Duration::from_millis(500));
+            while unsafe {
+                libc::recv(
+                    socket,
+                    buf.as_mut_ptr() as *mut libc::c_void,
+                    buf.len(),
+                    libc::MSG_DONTWAIT,
+                )
+            } > 0
+            {}
+        } else if received < 0
+            && std::io::Error::last_os_error().kind() != std::io::ErrorKind::WouldBlock
+        {
+            std::thread::sleep(CHECK_EVERY);
+        }
+    }
+}
+
+#[cfg(not(target_os = "linux"))]
+struct Waiter;
+
+#[cfg(not(target_os = "linux"))]
+impl Waiter {
+    fn new() -> Self {
+        Self
+    }
+
+    fn wait(&mut self) {
+        std::thread::sleep(CHECK_EVERY);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_wake_reason() {
+        let wifi = vec!["en0 192.168.1.20".to_string()];
+        let office = vec!["en0 10.0.4.7".to_string()];
+        let short = Duration::from_secs(1);
+        let long = Duration::from_secs(3600);
+
+        assert_eq!(wake_reason(short, &wifi, &wifi), None);
+        assert_eq!(
+            wake_reason(long, &wifi, &wifi).as_deref(),
+            Some("Woke from sleep after 60m")
+        );
+        assert_eq!(
+            wake_reason(short, &wifi, &office).as_deref(),
+            Some("Network changed")
+        );
+        assert_eq!(
+            wake_reason(short, &[], &wifi).as_deref(),
+            Some("Network connected")
+        );
+        // Offline after waking: wait for the connection instead
+        assert_eq!(wake_reason(long, &wifi, &[]), None);
+
+        assert!(addresses()
+            .iter()
+            .all(|address| !address.contains("127.0.0.1")));
+    }
+}
@@ -0,0 +1,316 @@
+//! Watchdog for daemon syncs that hang. Syncs run on the daemon's only worker thread,
+//! so one that never returns (a `git pull` waiting on a dead connection, a package
+//! manager waiting for input) stops every later sync. A background thread watches how
+//! long the current sync has been running; past `sync.watchdog_minutes` it logs what
+//! the sync was waiting on and kills those processes, which makes the sync fail and the
+//! daemon carry on. A sync still stuck after that isn't waiting on a process: if it's
+//! waiting on something async it's dropped at [`Watchdog::timeout`], otherwise the
+//! daemon restarts itself.
+//!
+//! Panics are caught too: a sync that panics fails like any other, and a context's
+//! worker that panics elsewhere is started again. Either way the panic is logged with
+//! where it happened and a backtrace, for `tether daemon logs --crashes`.
+
+use std::any::Any;
+use std::future::Future;
+use std::panic::AssertUnwindSafe;
+use std::path::Path;
+use std::sync::{Arc, Mutex};
+use std::task::Poll;
+use std::time::{Duration, Instant};
+
+/// How often the watchdog looks at the current sync
+const CHECK_EVERY: Duration = Duration::from_secs(10);
+
+/// How long a sync gets to fail after its processes are killed before the daemon
+/// restarts
+const RESTART_AFTER: Duration = Duration::from_secs(120);
+
+/// How long a sync gets to fail after its processes are killed before it's dropped;
+/// shorter than [`RESTART_AFTER`], so a sync stuck in async code doesn't cost a restart
+const TIMEOUT_AFTER_KILL: Duration = Duration::from_secs(60);
+
+#[derive(Default)]
+struct Current {
+    started: Option<Instant>,
+    /// When the watchdog killed this sync's processes
+    killed: Option<Instant>,
+}
+
+#[derive(Clone)]
+pub struct Watchdog {
+    current: Arc<Mutex<Current>>,
+    limit: Duration,
+}
+
+impl Watchdog {
+    /// Start watching syncs, which count as stuck after `limit`
+    pub fn spawn(limit: Duration) -> Self {
+        let watchdog = Self {
+            current: Arc::new(Mutex::new(Current::default())),
+            limit,
+        };
+        let current = Arc::clone(&watchdog.current);
+        std::thread::spawn(move || loop {
+            std::thread::sleep(CHECK_EVERY);
+            let Ok(mut current) = current.lock() else {
+                return;
+            };
+            let Some(started) = current.started else {
+                continue;
+            };
+            match current.killed {
+                None if started.elapsed() >= limit => {
+                    current.killed = Some(Instant::now());
+                    drop(current);
+                    kill_stuck_sync(started.elapsed());
+                }
+                Some(killed) if killed.elapsed() >= RESTART_AFTER => {
+                    log::error!(
+                        event = "watchdog";
+                        "Watchdog: sync still stuck {}s after its processes were killed; restarting the daemon",
+                        killed.elapsed().as_secs()
+                    );
+                    restart();
+                }
+                _ => {}
+            }
+        });
+        watchdog
+    }
+
+    /// How long a sync may run before it's dropped
+    pub fn timeout(&self) -> Duration {
+        self.limit + TIMEOUT_AFTER_KILL
+    }
+
+    /// A sync is starting
+    pub fn start(&self) {
+        if let Ok(mut current) = self.current.lock() {
+            *current = Current {
+                started: Some(Instant::now()),
+                killed: None,
+            };
+        }
+    }
+
+    /// The sync finished, however it ended. Returns whether the watchdog had to kill it.
+    pub fn finish(&self) -> bool {
+        match self.current.lock() {
+            Ok(mut current) => std::mem::take(&mut *current).killed.is_some(),
+            Err(_) => false,
+        }
+    }
+}
+
+/// Run `future`, returning what its panic said instead of unwinding if it panics
+pub async fn catch_panic<F: Future>(future: F) -> Result<F::Output, String> {
+    let mut future = std::pin::pin!(future);
+    std::future::poll_fn(|cx| {
+        match std::panic::catch_unwind(AssertUnwindSafe(|| future.as_mut().poll(cx))) {
+            Ok(Poll::Ready(output)) => Poll::Ready(Ok(output)),
+            Ok(Poll::Pending) => Poll::Pending,
+            Err(panic) => Poll::Ready(Err(panic_message(panic.as_ref()))),
+        }
+    })
+    .await
+}
+
+/// Log panics, with where they happened and a backtrace, instead of printing them
+pub fn log_panics() {
+    std::panic::set_hook(Box::new(|info| {
+        let location = info
+            .location()
+            .map(|l| format!("{}:{}", l.file(), l.line()))
+            .unwrap_or_default();
+        log::error!(
+            event = "panic", location = location.as_str();
+            "Panic at {}: {}\n{}",
+            location,
+            panic_message(info.payload()),
+            std::backtrace::Backtrace::force_capture()
+        );
+    }));
+}
+
+fn panic_message(payload: &(dyn Any + Send)) -> String {
+    payload
+        .downcast_ref::<&str>()
+        .map(|s| s.to_string())
+        .or_else(|| payload.downcast_ref::<String>().cloned())
+        .unwrap_or_else(|| "unknown panic".to_string())
+}
+
+/// Log what the stuck sync is waiting on and kill it
+fn kill_stuck_sync(elapsed: Duration) {
+    // Background helpers outlive syncs on purpose
+    let stuck: Vec<_> = descendants(std::process::id())
+        .into_iter()
+        .filter(|(_, command)| {
+            !command.contains(" identity-agent") && !command.contains(" clear-clipboard")
+        })
+        .collect();
+    if stuck.is_empty() {
+        log::error!(
+            event = "watchdog";
+            "Watchdog: sync stuck for {}m without a child process to kill",
+            elapsed.as_secs() / 60
+        );
+        return;
+    }
+    let commands: Vec<String> = stuck
+        .iter()
+        .map(|(pid, command)| format!("`{}` (pid {})", short_command(command), pid))
+        .collect();
+    log::error!(
+        event = "watchdog";
+        "Watchdog: sync stuck for {}m, killing {}",
+        elapsed.as_secs() / 60,
+        commands.join(", ")
+    );
+    for (pid, _) in &stuck {
+        unsafe { libc::kill(*pid as libc::pid_t, libc::SIGTERM) };
+    }
+    std::thread::sleep(Duration::from_secs(5));
+    for (pid, _) in &stuck {
+        if crate::daemon::pid::is_process_running(*pid) {
+            unsafe { libc::kill(*pid as libc::pid_t, libc::SIGKILL) };
+        }
+    }
+}
+
+/// Replace this daemon with a fresh one
+fn restart() -> ! {
+    // launchd's KeepAlive starts a new daemon when this one exits
+    let under_launchd = std::env::var("XPC_SERVICE_NAME").is_ok_and(|name| name != "0");
+    if !under_launchd {
+        if let Ok(exe) = std::env::current_exe() {
+            let _ = std::process::Command::new(exe)
+                .args(std::env::args_os().skip(1))
+                .stdin(std::process::Stdio::null())
+                .spawn();
+        }
+    }
+    std::process::exit(1);
+}
+
+/// Every process below `root`, with its command line
+fn descendants(root: u32) -> Vec<(u32, String)> {
+    let table = process_table();
+    let mut found = Vec::new();
+    let mut parents = vec![root];
+    while let Some(parent) = parents.pop() {
+        for (pid, ppid, command) in &table {
+            if *ppid == parent && *pid != root {
+                parents.push(*pid);
+                found.push((*pid, command.clone()));
+            }
+        }
+    }
+    found
+}
+
+/// (pid, parent pid, command line) of every process
+#[cfg(target_os = "linux")]
+fn process_table() -> Vec<(u32, u32, String)> {
+    let Ok(entries) = std::fs::read_dir("/proc") else {
+        return Vec::new();
+    };
+    entries
+        .flatten()
+        .filter_map(|entry| {
+            let pid: u32 = entry.file_name().to_str()?.parse().ok()?;
+            let stat = std::fs::read_to_string(entry.path().join("stat")).ok()?;
+            // The command name in parentheses may itself contain spaces
+            let ppid = stat
+                .rsplit_once(')')?
+                .1
+                .split_whitespace()
+                .nth(1)?
+                .parse()
+                .ok()?;
+            let command = std::fs::read(entry.path().join("cmdline"))
+                .map(|raw| {
+                    raw.split(|b| *b == 0)
+                        .filter(|arg| !arg.is_empty())
+                        .map(|arg| String::from_utf8_lossy(arg).into_owned())
+                        .collect::<Vec<_>>()
+                        .join(" ")
+                })
+                .unwrap_or_default();
+            Some((pid, ppid, command))
+        })
+        .collect()
+}
+
+#[cfg(not(target_os = "linux"))]
+fn process_table() -> Vec<(u32, u32, String)> {
+    let Ok(output) = std::process::Command::new("ps")
+        .args(["-A", "-o", "pid=,ppid=,command="])
+        .output()
+    else {
+        return Vec::new();
+    };
+    String::from_utf8_lossy(&output.stdout)
+        .lines()
+        .filter_map(|line| {
+            let mut fields = line.split_whitespace();
+            let pid = fields.next()?.parse().ok()?;
+            let ppid = fields.next()?.parse().ok()?;
+            Some((pid, ppid, fields.collect::<Vec<_>>().join(" ")))
+        })
+        .collect()
+}
+
+/// Shorten a command line for the log: the program's file name and its arguments
+fn short_command(command: &str) -> String {
+    match command.split_once(' ') {
+        Some((program, args)) => format!("{} {}", file_name(program), args),
+        None => file_name(command).to_string(),
+    }
+}
+
+fn file_name(program: &str) -> &str {
+    Path::new(program)
+        .file_name()
+        .and_then(|name| name.to_str())
+        .unwrap_or(program)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn test_catch_panic() {
+        assert_eq!(catch_panic(async { 1 }).await, Ok(1));
+        let result = catch_panic(async {
+            tokio::task::yield_now().await;
+            if true {
+                panic!("index out of range");
+            }
+        })
+        .await;
+        assert_eq!(result, Err("index out of range".to_string()));
+    }
+
+    #[test]
+    fn test_descendants_finds_grandchildren() {
+        let mut child = std::process::Command::new("sh")
+            .args(["-c", "sleep 30 & wait"])
+            .spawn()
+            .unwrap();
+        std::thread::sleep(Duration::from_millis(200));
+
+        let found = descendants(child.id());
+        assert!(found
+            .iter()
+            .any(|(_, command)| command.contains("sleep 30")));
+
+        child.kill().unwrap();
+        let _ = child.wait();
+        for (pid, _) in found {
+            unsafe { libc::kill(pid as libc::pid_t, libc::SIGKILL) };
+        }
+    }
+}
@@ -0,0 +1,75 @@
+//! Watches the project search paths and feeds [`ProjectEvents`], so project config
+//! scans only revisit repos that changed (FSEvents on macOS, inotify on Linux).
+
+use crate::config::Config;
+use crate::sync::project_index::ProjectEvents;
+use anyhow::Result;
+use chrono::{DateTime, Utc};
+use notify_debouncer_full::notify::{RecommendedWatcher, RecursiveMode};
+use notify_debouncer_full::{new_debouncer, DebounceEventResult, Debouncer, RecommendedCache};
+use std::path::Path;
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+
+const DEBOUNCE: Duration = Duration::from_secs(2);
+
+/// Stops watching when dropped
+pub type ProjectWatcher = Debouncer<RecommendedWatcher, RecommendedCache>;
+
+/// Start watching when `project_configs.watch` is on
+pub fn watch_projects(config: &Config, home: &Path) -> Result<Option<ProjectWatcher>> {
+    let settings = &config.project_configs;
+    if !settings.enabled || !settings.watch {
+        return Ok(None);
+    }
+
+    let roots = settings.search_roots(home);
+    // No scan counts as covered until every root is watched
+    let mut events = ProjectEvents::new(roots.clone());
+    events.watching_since = DateTime::<Utc>::MAX_UTC;
+    let events = Arc::new(Mutex::new(events));
+    // Resolved here: the handler runs on the watcher's thread, outside the daemon task
+    // that selects the context
+    let events_path = ProjectEvents::path()?;
+    let handler_path = events_path.clone();
+    let handler_events = Arc::clone(&events);
+    let mut debouncer = new_debouncer(DEBOUNCE, None, move |result: DebounceEventResult| {
+        let batch = match result {
+            Ok(batch) => batch,
+            Err(errors) => {
+                for e in errors {
+                    log::warn!("Project watcher error: {}", e);
+                }
+                return;
+            }
+        };
+        let Ok(mut events) = handler_events.lock() else {
+            return;
+        };
+        let now = Utc::now();
+        for event in &batch {
+            for path in &event.paths {
+                events.record(path, now);
+            }
+        }
+        if let Err(e) = events.save_to(&handler_path) {
+            log::warn!("Failed to save project events: {}", e);
+        }
+    })?;
+
+    for root in roots.iter().filter(|r| r.exists()) {
+        debouncer.watch(root, RecursiveMode::Recursive)?;
+    }
+
+    // Only changes from here on are seen; scans from before this can't be trusted
+    {
+        let mut events = events
+            .lock()
+            .map_err(|_| anyhow::anyhow!("Lock poisoned"))?;
+        events.watching_since = Utc::now();
+        events.save_to(&events_path)?;
+    }
+    log::info!("Watching {} project search path(s)", roots.len());
+
+    Ok(Some(debouncer))
+}
@@ -1,4 +1,4 @@
-use crate::config::{is_safe_dotfile_path, Config, ConflictStrategy, DotfileEntry};
+use crate::config::{is_safe_dotfile_path, Config, ConflictStrategy, DotfileEntry, PackagesMode};
 use std::sync::LazyLock;
 
 #[derive(Clone, Copy, PartialEq)]
@@ -75,6 +75,12 @@ static FIELDS: LazyLock<Vec<ConfigField>> = LazyLock::new(|| {
             section: "Security",
             kind: FieldKind::Bool,
         },
+        ConfigField {
+            key: "encrypt_manifests",
+            label: "Encrypt manifests",
+            section: "Security",
+            kind: FieldKind::Bool,
+        },
         // Dotfiles
         ConfigField {
             key: "dotfiles.files",
@@ -89,12 +95,24 @@ static FIELDS: LazyLock<Vec<ConfigField>> = LazyLock::new(|| {
             kind: FieldKind::List,
         },
         // Packages
+        ConfigField {
+            key: "mode",
+            label: "Package mode",
+            section: "Packages",
+            kind: FieldKind::Text,
+        },
         ConfigField {
             key: "remove_unlisted",
             label: "Remove unlisted",
             section: "Packages",
             kind: FieldKind::Bool,
         },
+        ConfigField {
+            key: "approve_installs",
+            label: "Approve installs",
+            section: "Packages",
+            kind: FieldKind::Bool,
+        },
         ConfigField {
             key: "brew.enabled",
             label: "Brew enabled",
@@ -218,11 +236,17 @@ pub fn get_value(config: &Config, idx: usize) -> String {
         // Security
         "encrypt_dotfiles" => config.security.encrypt_dotfiles.to_string(),
         "scan_secrets" => config.security.scan_secrets.to_string(),
+        "encrypt_manifests" => config.security.encrypt_manifests.to_string(),
         // Dotfiles
         "dotfiles.files" => format!("{} items", config.dotfiles.files.len()),
         "dotfiles.dirs" => format!("{} items", config.dotfiles.dirs.len()),
         // Packages
+        "mode" => match config.packages.mode {
+            PackagesMode::MachineUnion => "machine-union".into(),
+            PackagesMode::ManifestAuthoritative => "manifest-authoritative".into(),
+        },
         "remove_unlisted" => config.packages.remove_unlisted.to_string(),
+        "approve_installs" => config.packages.approve_installs.to_string(),
         "brew.enabled" => config.packages.brew.enabled.to_string(),
         "brew.sync_casks" => config.packages.brew.sync_casks.to_string(),
         "brew.sync_taps" => config.packages.brew.sync_taps.to_string(),
@@ -264,6 +288,13 @@ pub fn set_value(config: &mut Config, idx: usize, val: &str) -> bool {
                 _ => return false,
             };
         }
+        "mode" => {
+            config.packages.mode = match val {
+                "machine-union" => PackagesMode::MachineUnion,
+                "manifest-authoritative" => PackagesMode::ManifestAuthoritative,
+                _ => return false,
+            };
+        }
         _ => return false,
     }
     config.save().is_ok()
@@ -284,7 +315,11 @@ pub fn toggle(config: &mut Config, idx: usize) -> bool {
         "team_layering" => config.features.team_layering = !config.features.team_layering,
         "encrypt_dotfiles" => config.security.encrypt_dotfiles = !config.security.encrypt_dotfiles,
         "scan_secrets" => config.security.scan_secrets = !config.security.scan_secrets,
+        "encrypt_manifests" => {
+            config.security.encrypt_manifests = !config.security.encrypt_manifests
+        }
         "remove_unlisted" => config.packages.remove_unlisted = !config.packages.remove_unlisted,
+        "approve_installs" => config.packages.approve_installs = !config.packages.approve_installs,
         "brew.enabled" => config.packages.brew.enabled = !config.packages.brew.enabled,
         "brew.sync_casks" => config.packages.brew.sync_casks = !config.packages.brew.sync_casks,
         "brew.sync_taps" => config.packages.brew.sync_taps = !config.packages.brew.sync_taps,
@@ -317,13 +352,17 @@ pub fn toggle(config: &mut Config, idx: usize) -> bool {
 /// Get items for a List field
 pub fn get_list_items(config: &Config, key: &str) -> Vec<String> {
     match key {
-        "dotfiles.dirs" => config.dotfiles.dirs.clone(),
+        "dotfiles.dirs" => entry_paths(&config.dotfiles.dirs),
         "project_configs.search_paths" => config.project_configs.search_paths.clone(),
-        "project_configs.patterns" => config.project_configs.patterns.clone(),
+        "project_configs.patterns" => entry_paths(&config.project_configs.patterns),
         _ => Vec::new(),
     }
 }
 
+fn entry_paths(entries: &[DotfileEntry]) -> Vec<String> {
+    entries.iter().map(|e| e.path().to_string()).collect()
+}
+
 /// Get dotfile items as (path, create_if_missing) pairs
 pub fn get_dotfile_items(config: &Config) -> Vec<(String, bool)> {
     config
@@ -340,31 +379,48 @@ pub fn add_list_item(config: &mut Config, key: &str, value: &str) -> bool {
     if value.is_empty() {
         return false;
     }
-    let list = match key {
+    let entries = match key {
         "dotfiles.dirs" => &mut config.dotfiles.dirs,
-        "project_configs.search_paths" => &mut config.project_configs.search_paths,
         "project_configs.patterns" => &mut config.project_configs.patterns,
+        "project_configs.search_paths" => {
+            let list = &mut config.project_configs.search_paths;
+            if list.iter().any(|v| v == value) {
+                return false;
+            }
+            list.push(value.to_string());
+            return config.save().is_ok();
+        }
         _ => return false,
     };
-    if list.iter().any(|v| v == value) {
+    if entries.iter().any(|e| e.path() == value) {
         return false;
     }
-    list.push(value.to_string());
+    entries.push(value.into());
     config.save().is_ok()
 }
 
 /// Remove an item from a List field by index. Returns false on out-of-bounds or save failure.
 pub fn remove_list_item(config: &mut Config, key: &str, index: usize) -> bool {
-    let list = match key {
-        "dotfiles.dirs" => &mut config.dotfiles.dirs,
-        "project_configs.search_paths" => &mut config.project_configs.search_paths,
-        "project_configs.patterns" => &mut config.project_configs.patterns,
+    let len = match key {
+        "dotfiles.dirs" => config.dotfiles.dirs.len(),
+        "project_configs.search_paths" => config.project_configs.search_paths.len(),
+        "project_configs.patterns" => config.project_configs.patterns.len(),
         _ => return false,
     };
-    if index >= list.len() {
+    if index >= len {
         return false;
     }
-    list.remove(index);
+    match key {
+        "dotfiles.dirs" => {
+            config.dotfiles.dirs.remove(index);
+        }
+        "project_configs.search_paths" => {
+            config.project_configs.search_paths.remove(index);
+        }
+        _ => {
+            config.project_configs.patterns.remove(index);
+        }
+    }
     config.save().is_ok()
 }
 
@@ -9,11 +9,29 @@ use crossterm::{
     ExecutableCommand,
 };
 use ratatui::prelude::*;
-use std::collections::{HashMap, HashSet};
+use std::collections::{HashMap, HashSet, VecDeque};
 use std::io::{stdout, IsTerminal};
 use std::time::{Duration, Instant};
 
 use state::DashboardState;
+use widgets::help::KeyContext;
+
+/// How many past flash messages the `l` overlay keeps
+const FLASH_LOG_LIMIT: usize = 50;
+
+/// How much of the end of daemon.log the Logs tab reads
+const LOG_TAB_BYTES: u64 = 256 * 1024;
+
+/// How many lines of a dashboard sync's output the Logs tab keeps
+const SYNC_OUTPUT_LIMIT: usize = 500;
+
+/// A pairing invite from `a` on the Machines tab, shown until dismissed
+pub struct PairingPopup {
+    qr: Vec<String>,
+    code: Option<String>,
+    profile: Option<String>,
+    expires: String,
+}
 
 pub struct ImportItem {
     path: String,
@@ -34,6 +52,8 @@ pub struct PkgImportItem {
 pub struct PkgImportPickerState {
     items: Vec<PkgImportItem>,
     cursor: usize,
+    /// Listing installs queued by `packages.approve_installs`
+    approving: bool,
 }
 
 #[derive(Clone, Copy, PartialEq)]
@@ -43,6 +63,8 @@ pub enum Tab {
     Packages,
     Machines,
     Config,
+    Conflicts,
+    Logs,
 }
 
 impl Tab {
@@ -53,6 +75,8 @@ impl Tab {
             Tab::Packages => "Packages",
             Tab::Machines => "Machines",
             Tab::Config => "Config",
+            Tab::Conflicts => "Conflicts",
+            Tab::Logs => "Logs",
         }
     }
 
@@ -63,6 +87,8 @@ impl Tab {
             Tab::Packages,
             Tab::Machines,
             Tab::Config,
+            Tab::Conflicts,
+            Tab::Logs,
         ]
     }
 }
@@ -94,6 +120,8 @@ pub struct FilesTabState {
     pub restore_confirm: Option<(String, String, String)>, // (dotfile_path, commit_hash, short_hash)
     pub deleted: HashMap<String, Vec<String>>,
     pub show_deleted: HashSet<String>,
+    /// The `/` filter on file paths
+    pub filter: String,
 }
 
 impl FilesTabState {
@@ -108,16 +136,97 @@ impl FilesTabState {
             restore_confirm: None,
             deleted,
             show_deleted: HashSet::new(),
+            filter: String::new(),
         }
     }
 }
 
+pub struct LogsTabState {
+    /// The end of daemon.log
+    pub lines: Vec<String>,
+    /// What the last `tether sync` started from the dashboard printed
+    pub sync_output: Vec<String>,
+    /// Stay on the newest lines as the log grows
+    pub follow: bool,
+    /// The first line shown when not following
+    pub top: usize,
+    /// How many lines of the log fit, as of the last draw
+    pub height: std::cell::Cell<usize>,
+    last_read: Instant,
+}
+
+impl LogsTabState {
+    fn new() -> Self {
+        let mut logs = Self {
+            lines: Vec::new(),
+            sync_output: Vec::new(),
+            follow: true,
+            top: 0,
+            height: std::cell::Cell::new(0),
+            last_read: Instant::now(),
+        };
+        logs.reload();
+        logs
+    }
+
+    fn reload(&mut self) {
+        self.lines = crate::config::Config::config_dir()
+            .map(|dir| crate::daemon::logs::tail(&dir.join("daemon.log"), LOG_TAB_BYTES))
+            .unwrap_or_default();
+        self.last_read = Instant::now();
+    }
+
+    /// The first line shown with the newest ones at the bottom
+    fn bottom(&self) -> usize {
+        self.lines.len().saturating_sub(self.height.get())
+    }
+
+    pub fn first_shown(&self) -> usize {
+        if self.follow {
+            self.bottom()
+        } else {
+            self.top.min(self.bottom())
+        }
+    }
+
+    /// Stop following, keeping the view where it is
+    fn unfollow(&mut self) {
+        if self.follow {
+            self.follow = false;
+            self.top = self.bottom();
+        }
+    }
+
+    /// Scroll to the last error above the view, or the last one in the log while
+    /// following. Returns whether there was one.
+    fn jump_to_error(&mut self) -> bool {
+        let before = if self.follow {
+            self.lines.len()
+        } else {
+            self.first_shown()
+        };
+        let Some(idx) = self.lines[..before]
+            .iter()
+            .rposition(|line| crate::daemon::logs::line_level(line) == Some(log::Level::Error))
+        else {
+            return false;
+        };
+        self.follow = false;
+        self.top = idx;
+        true
+    }
+}
+
 pub struct App {
     state: DashboardState,
     active_tab: Tab,
-    scroll_offsets: [usize; 5],
+    scroll_offsets: [usize; 7],
     should_quit: bool,
     sync_child: Option<std::process::Child>,
+    sync_progress_rx: Option<std::sync::mpsc::Receiver<crate::sync::git::TransferProgress>>,
+    sync_progress: Option<crate::sync::git::TransferProgress>,
+    /// Lines the dashboard's sync prints, for the Logs tab
+    sync_output_rx: Option<std::sync::mpsc::Receiver<String>>,
     daemon_child: Option<std::process::Child>,
     daemon_op: DaemonOp,
     show_help: bool,
@@ -126,10 +235,20 @@ pub struct App {
     config_edit_buf: String,
     flash_error: Option<(Instant, String)>,
     flash_message: Option<(Instant, String)>,
+    flash_log: VecDeque<widgets::status::FlashLogEntry>,
+    show_flash_log: bool,
     list_edit: Option<ListEditState>,
     pkg_expanded: Option<String>,
     pkg_cursor: usize,
+    /// The `/` filters on the Packages and Machines tabs (the Files one is in `files`)
+    pkg_filter: String,
+    machine_filter: String,
+    /// Typing into the current tab's filter
+    filter_editing: bool,
     uninstall_confirm: Option<(String, String)>,
+    /// Installed packages that depend on the one pending uninstall; `None` while checking
+    uninstall_dependents: Option<Vec<String>>,
+    dependents_rx: Option<std::sync::mpsc::Receiver<Vec<String>>>,
     uninstalling: Option<(String, String)>,
     uninstall_rx: Option<std::sync::mpsc::Receiver<std::result::Result<(), String>>>,
     machine_expanded: Option<String>,
@@ -138,16 +257,37 @@ pub struct App {
     profile_picker_options: Vec<String>,
     profile_picker_cursor: usize,
     files: FilesTabState,
+    logs: LogsTabState,
+    conflict_cursor: usize,
+    /// Diff of the selected conflict, or why it couldn't be loaded
+    conflict_diff: std::result::Result<Vec<String>, String>,
+    /// Show secret values in conflict and history diffs instead of masking them
+    reveal_secrets: bool,
+    conflict_confirm: Option<(String, crate::sync::ResolvedSide)>,
+    /// Conflict to open in the merge tool once the terminal is handed back
+    conflict_merge: Option<String>,
     file_delete_confirm: Option<String>,
     file_import_picker: Option<ImportPickerState>,
     pkg_import_picker: Option<PkgImportPickerState>,
     pkg_install_confirm: Option<(String, String)>,
     installing: Option<(String, String)>,
     install_rx: Option<std::sync::mpsc::Receiver<std::result::Result<(), String>>>,
+    pairing: Option<PairingPopup>,
+    pairing_rx: Option<std::sync::mpsc::Receiver<std::result::Result<PairingPopup, String>>>,
     pkg_refresh_rx: Option<std::sync::mpsc::Receiver<HashMap<String, Vec<String>>>>,
 }
 
 impl App {
+    /// The current tab's `/` filter and cursor, on the tabs that have one
+    fn filter_mut(&mut self) -> Option<(&mut String, &mut usize)> {
+        match self.active_tab {
+            Tab::Files => Some((&mut self.files.filter, &mut self.files.cursor)),
+            Tab::Packages => Some((&mut self.pkg_filter, &mut self.pkg_cursor)),
+            Tab::Machines => Some((&mut self.machine_filter, &mut self.machine_cursor)),
+            _ => None,
+        }
+    }
+
     fn scroll_offset(&self) -> usize {
         let idx = Tab::all()
             .iter()
@@ -164,39 +304,139 @@ impl App {
         &mut self.scroll_offsets[idx]
     }
 
+    /// Sync in a child process, or ask the running daemon to sync so the two don't
+    /// queue on the sync lock
     fn spawn_sync(&mut self) {
         if self.sync_child.is_some() {
             return;
         }
+        if self.state.daemon_running
+            && crate::daemon::ipc::send(crate::daemon::ipc::DaemonRequest::SyncNow).is_ok()
+        {
+            self.set_flash_message("daemon syncing");
+            self.reload_state();
+            return;
+        }
         let exe = std::env::current_exe().unwrap_or_else(|_| "tether".into());
-        if let Ok(child) = std::process::Command::new(exe)
+        if let Ok(mut child) = std::process::Command::new(exe)
             .arg("sync")
-            .stdout(std::process::Stdio::null())
-            .stderr(std::process::Stdio::null())
+            .env(crate::cli::progress::PROGRESS_JSON_ENV, "1")
+            .stdout(std::process::Stdio::piped())
+            .stderr(std::process::Stdio::piped())
             .spawn()
         {
+            use std::io::BufRead;
+            let (output_tx, output_rx) = std::sync::mpsc::channel();
+            // Transfer progress arrives as JSON lines on stderr; everything else is
+            // output for the Logs tab
+            if let Some(stderr) = child.stderr.take() {
+                let (tx, rx) = std::sync::mpsc::channel();
+                let output_tx = output_tx.clone();
+                std::thread::spawn(move || {
+                    for line in std::io::BufReader::new(stderr).lines() {
+                        let Ok(line) = line else { break };
+                        match serde_json::from_str(&line) {
+                            Ok(progress) => {
+                                let _ = tx.send(progress);
+                            }
+                            Err(_) => {
+                                let _ = output_tx.send(line);
+                            }
+                        }
+                    }
+                });
+                self.sync_progress_rx = Some(rx);
+            }
+            if let Some(stdout) = child.stdout.take() {
+                std::thread::spawn(move || {
+                    for line in std::io::BufReader::new(stdout).lines() {
+                        let Ok(line) = line else { break };
+                        if output_tx.send(line).is_err() {
+                            break;
+                        }
+                    }
+                });
+            }
+            self.logs.sync_output.clear();
+            self.sync_output_rx = Some(output_rx);
             self.sync_child = Some(child);
         }
     }
 
+    fn set_flash_error(&mut self, msg: impl Into<String>) {
+        let msg = msg.into();
+        self.log_flash(true, &msg);
+        self.flash_error = Some((Instant::now(), msg));
+    }
+
+    fn set_flash_message(&mut self, msg: impl Into<String>) {
+        let msg = msg.into();
+        self.log_flash(false, &msg);
+        self.flash_message = Some((Instant::now(), msg));
+    }
+
+    /// Keep flashes around after they fade so they can be reviewed with `l`
+    fn log_flash(&mut self, error: bool, msg: &str) {
+        if self.flash_log.len() >= FLASH_LOG_LIMIT {
+            self.flash_log.pop_front();
+        }
+        self.flash_log.push_back(widgets::status::FlashLogEntry {
+            at: chrono::Local::now(),
+            error,
+            message: msg.to_string(),
+        });
+    }
+
+    /// Which bindings the help overlay shows, mirroring the order `handle_key` checks
+    /// popups in. `None` while typing into a text field, where `?` is just a character.
+    fn key_context(&self) -> Option<KeyContext> {
+        if self.uninstall_confirm.is_some()
+            || self.files.restore_confirm.is_some()
+            || self.file_delete_confirm.is_some()
+            || self.pkg_install_confirm.is_some()
+            || self.conflict_confirm.is_some()
+        {
+            Some(KeyContext::Confirm)
+        } else if self.file_import_picker.is_some() || self.pkg_import_picker.is_some() {
+            Some(KeyContext::ImportPicker)
+        } else if self.profile_editing {
+            Some(KeyContext::ProfilePicker)
+        } else if let Some(ref le) = self.list_edit {
+            (!le.adding).then_some(KeyContext::ListEdit)
+        } else if self.config_editing || self.filter_editing {
+            None
+        } else {
+            Some(KeyContext::Main)
+        }
+    }
+
     fn reload_state(&mut self) {
         self.state = DashboardState::load();
         self.files.deleted = load_deleted_files(&self.state);
         refresh_files_expanded(self);
+        refresh_conflict_diff(self);
         self.last_refresh = Instant::now();
     }
 
     fn item_count(&self) -> usize {
         match self.active_tab {
             Tab::Files => widgets::files::build_rows(&self.state, &self.files).len(),
-            Tab::Packages => {
-                widgets::packages::build_rows(&self.state, self.pkg_expanded.as_deref()).len()
-            }
-            Tab::Machines => {
-                widgets::machines::build_rows(&self.state, self.machine_expanded.as_deref()).len()
-            }
+            Tab::Packages => widgets::packages::build_rows(
+                &self.state,
+                self.pkg_expanded.as_deref(),
+                &self.pkg_filter,
+            )
+            .len(),
+            Tab::Machines => widgets::machines::build_rows(
+                &self.state,
+                self.machine_expanded.as_deref(),
+                &self.machine_filter,
+            )
+            .len(),
             Tab::Overview => widgets::files::build_overview_rows(&self.state).len(),
             Tab::Config => config_edit::fields().len(),
+            Tab::Conflicts => self.state.conflicts.conflicts.len(),
+            Tab::Logs => self.logs.lines.len(),
         }
     }
 }
@@ -222,9 +462,12 @@ pub fn run() -> Result<()> {
     let mut app = App {
         state,
         active_tab: Tab::Overview,
-        scroll_offsets: [0; 5],
+        scroll_offsets: [0; 7],
         should_quit: false,
         sync_child: None,
+        sync_progress_rx: None,
+        sync_progress: None,
+        sync_output_rx: None,
         daemon_child: None,
         daemon_op: DaemonOp::None,
         show_help: false,
@@ -233,10 +476,17 @@ pub fn run() -> Result<()> {
         config_edit_buf: String::new(),
         flash_error: None,
         flash_message: None,
+        flash_log: VecDeque::new(),
+        show_flash_log: false,
         list_edit: None,
         pkg_expanded: None,
         pkg_cursor: 0,
+        pkg_filter: String::new(),
+        machine_filter: String::new(),
+        filter_editing: false,
         uninstall_confirm: None,
+        uninstall_dependents: None,
+        dependents_rx: None,
         uninstalling: None,
         uninstall_rx: None,
         machine_expanded: None,
@@ -245,15 +495,25 @@ pub fn run() -> Result<()> {
         profile_picker_options: Vec::new(),
         profile_picker_cursor: 0,
         files: FilesTabState::new(files_deleted),
+        logs: LogsTabState::new(),
+        conflict_cursor: 0,
+        conflict_diff: Ok(Vec::new()),
+        reveal_secrets: false,
+        conflict_confirm: None,
+        conflict_merge: None,
         file_delete_confirm: None,
         file_import_picker: None,
         pkg_import_picker: None,
         pkg_install_confirm: None,
         installing: None,
         install_rx: None,
+        pairing: None,
+        pairing_rx: None,
         pkg_refresh_rx: None,
     };
 
+    refresh_conflict_diff(&mut app);
+
     // Spawn background thread to collect live package data
     {
         let config = app.state.config.clone();
@@ -299,9 +559,60 @@ pub fn run() -> Result<()> {
             }
         }
 
+        if let Some(file) = app.conflict_merge.take() {
+            disable_raw_mode()?;
+            stdout().execute(LeaveAlternateScreen)?;
+            let result = run_conflict_merge(&file);
+            enable_raw_mode()?;
+            stdout().execute(EnterAlternateScreen)?;
+            terminal.clear()?;
+            match result {
+                Ok(true) => {
+                    app.set_flash_message(format!(
+                        "merged {} (previous copy in `tether restore list`)",
+                        file
+                    ));
+                    app.reload_state();
+                    app.spawn_sync();
+                }
+                Ok(false) => app.set_flash_message(format!("{} unchanged, still pending", file)),
+                Err(e) => app.set_flash_error(format!("merge failed: {}", e)),
+            }
+        }
+
+        if let Some(ref rx) = app.sync_progress_rx {
+            if let Some(progress) = rx.try_iter().last() {
+                app.sync_progress = Some(progress);
+            }
+        }
+
+        if let Some(ref rx) = app.sync_output_rx {
+            loop {
+                match rx.try_recv() {
+                    Ok(line) => {
+                        let output = &mut app.logs.sync_output;
+                        if output.len() >= SYNC_OUTPUT_LIMIT {
+                            output.remove(0);
+                        }
+                        output.push(console::strip_ansi_codes(&line).into_owned());
+                    }
+                    Err(std::sync::mpsc::TryRecvError::Empty) => break,
+                    Err(std::sync::mpsc::TryRecvError::Disconnected) => {
+                        app.sync_output_rx = None;
+                        break;
+                    }
+                }
+            }
+        }
+        if app.active_tab == Tab::Logs && app.logs.last_read.elapsed() >= Duration::from_secs(1) {
+            app.logs.reload();
+        }
+
         if let Some(ref mut child) = app.sync_child {
             if let Ok(Some(_)) = child.try_wait() {
                 app.sync_child = None;
+                app.sync_progress_rx = None;
+                app.sync_progress = None;
                 app.reload_state();
             }
         }
@@ -314,6 +625,13 @@ pub fn run() -> Result<()> {
             }
         }
 
+        if let Some(ref rx) = app.dependents_rx {
+            if let Ok(dependents) = rx.try_recv() {
+                app.uninstall_dependents = Some(dependents);
+                app.dependents_rx = None;
+            }
+        }
+
         if let Some(ref rx) = app.uninstall_rx {
             if let Ok(result) = rx.try_recv() {
                 match result {
@@ -321,8 +639,7 @@ pub fn run() -> Result<()> {
                         app.spawn_sync();
                     }
                     Err(msg) => {
-                        app.flash_error =
-                            Some((Instant::now(), format!("uninstall failed: {}", msg)));
+                        app.set_flash_error(format!("uninstall failed: {}", msg));
                     }
                 }
                 app.uninstalling = None;
@@ -334,11 +651,16 @@ pub fn run() -> Result<()> {
             if let Ok(result) = rx.try_recv() {
                 match result {
                     Ok(()) => {
-                        if let Some((ref manager_key, ref pkg_name)) = app.installing {
-                            app.flash_message =
-                                Some((Instant::now(), format!("installed {}", pkg_name)));
+                        if let Some((ref manager_key, ref pkg_name)) = app.installing.clone() {
+                            app.set_flash_message(format!("installed {}", pkg_name));
                             // Remove from removed_packages so sync doesn't uninstall it
                             remove_from_removed_packages(&app.state, manager_key, pkg_name);
+                            // Installing a queued package approves it
+                            if let Ok(mut sync_state) = crate::sync::SyncState::load() {
+                                if sync_state.take_pending_install(manager_key, pkg_name) {
+                                    let _ = sync_state.save();
+                                }
+                            }
                             // Remove from import picker if still open
                             if let Some(ref mut picker) = app.pkg_import_picker {
                                 picker.items.retain(|i| {
@@ -354,8 +676,7 @@ pub fn run() -> Result<()> {
                         app.spawn_sync();
                     }
                     Err(msg) => {
-                        app.flash_error =
-                            Some((Instant::now(), format!("install failed: {}", msg)));
+                        app.set_flash_error(format!("install failed: {}", msg));
                     }
                 }
                 app.installing = None;
@@ -363,6 +684,16 @@ pub fn run() -> Result<()> {
             }
         }
 
+        if let Some(ref rx) = app.pairing_rx {
+            if let Ok(result) = rx.try_recv() {
+                match result {
+                    Ok(popup) => app.pairing = Some(popup),
+                    Err(msg) => app.set_flash_error(format!("pairing failed: {}", msg)),
+                }
+                app.pairing_rx = None;
+            }
+        }
+
         // Check background package refresh
         if let Some(ref rx) = app.pkg_refresh_rx {
             if let Ok(packages) = rx.try_recv() {
@@ -436,10 +767,33 @@ fn handle_key(app: &mut App, key: crossterm::event::KeyEvent) {
         return;
     }
 
+    // Pairing popup: any key closes it
+    if app.pairing.is_some() {
+        app.pairing = None;
+        return;
+    }
+
+    // Help is modal and documents whichever popup or mode it was opened from
+    if app.show_help {
+        if matches!(
+            key.code,
+            KeyCode::Char('?') | KeyCode::Char('q') | KeyCode::Esc
+        ) {
+            app.show_help = false;
+        }
+        return;
+    }
+    if key.code == KeyCode::Char('?') && app.key_context().is_some() {
+        app.show_help = true;
+        return;
+    }
+
     // Uninstall confirmation popup intercepts keys
     if app.uninstall_confirm.is_some() {
         match key.code {
-            KeyCode::Char('y') | KeyCode::Enter => {
+            // Wait for the dependents check so its warning can't be skipped
+            KeyCode::Char('y') | KeyCode::Enter if app.uninstall_dependents.is_some() => {
+                app.uninstall_dependents = None;
                 if let Some((manager_key, pkg_name)) = app.uninstall_confirm.take() {
                     let (tx, rx) = std::sync::mpsc::channel();
                     let mk = manager_key.clone();
@@ -460,6 +814,8 @@ fn handle_key(app: &mut App, key: crossterm::event::KeyEvent) {
             }
             KeyCode::Char('n') | KeyCode::Esc => {
                 app.uninstall_confirm = None;
+                app.uninstall_dependents = None;
+                app.dependents_rx = None;
             }
             _ => {}
         }
@@ -475,15 +831,14 @@ fn handle_key(app: &mut App, key: crossterm::event::KeyEvent) {
                 {
                     match run_restore(app, &dotfile_path, &commit_hash) {
                         Ok(()) => {
-                            app.flash_message = Some((
-                                Instant::now(),
-                                format!("Restored {} to {}", dotfile_path, short_hash),
+                            app.set_flash_message(format!(
+                                "Restored {} to {}",
+                                dotfile_path, short_hash
                             ));
                             app.spawn_sync();
                         }
                         Err(e) => {
-                            app.flash_error =
-                                Some((Instant::now(), format!("restore failed: {}", e)));
+                            app.set_flash_error(format!("restore failed: {}", e));
                         }
                     }
                 }
@@ -496,6 +851,41 @@ fn handle_key(app: &mut App, key: crossterm::event::KeyEvent) {
         return;
     }
 
+    // Conflict resolution confirmation popup
+    if app.conflict_confirm.is_some() {
+        match key.code {
+            KeyCode::Char('y') | KeyCode::Enter => {
+                if let Some((file, side)) = app.conflict_confirm.take() {
+                    match resolve_conflict(&file, side) {
+                        Ok(snapshot) => {
+                            app.set_flash_message(match (side, snapshot) {
+                                (crate::sync::ResolvedSide::Local, _) => {
+                                    format!("{} (kept local)", file)
+                                }
+                                (crate::sync::ResolvedSide::Remote, Some(backup)) => {
+                                    format!("{} (applied remote, local saved in {})", file, backup)
+                                }
+                                (crate::sync::ResolvedSide::Remote, None) => {
+                                    format!("{} (applied remote)", file)
+                                }
+                            });
+                            app.reload_state();
+                            app.spawn_sync();
+                        }
+                        Err(e) => {
+                            app.set_flash_error(format!("resolve failed: {}", e));
+                        }
+                    }
+                }
+            }
+            KeyCode::Char('n') | KeyCode::Esc => {
+                app.conflict_confirm = None;
+            }
+            _ => {}
+        }
+        return;
+    }
+
     // File delete confirmation popup
     if app.file_delete_confirm.is_some() {
         match key.code {
@@ -506,7 +896,7 @@ fn handle_key(app: &mut App, key: crossterm::event::KeyEvent) {
                     {
                         let ok = config_edit::remove_profile_dotfile(config, &ss.machine_id, &path);
                         if ok {
-                            app.flash_message = Some((Instant::now(), format!("removed {}", path)));
+                            app.set_flash_message(format!("removed {}", path));
                             app.reload_state();
                             // Clamp cursor
                             let new_rows = widgets::files::build_rows(&app.state, &app.files);
@@ -515,7 +905,7 @@ fn handle_key(app: &mut App, key: crossterm::event::KeyEvent) {
                             }
                             app.spawn_sync();
                         } else {
-                            app.flash_error = Some((Instant::now(), "remove failed".into()));
+                            app.set_flash_error("remove failed");
                         }
                     }
                 }
@@ -557,11 +947,10 @@ fn handle_key(app: &mut App, key: crossterm::event::KeyEvent) {
                                 sync_state.dismissed_imports.remove(&item.path);
                                 let _ = sync_state.save();
                             }
-                            app.flash_message =
-                                Some((Instant::now(), format!("imported {}", item.path)));
+                            app.set_flash_message(format!("imported {}", item.path));
                             app.reload_state();
                         } else {
-                            app.flash_error = Some((Instant::now(), "import failed".into()));
+                            app.set_flash_error("import failed");
                         }
                     }
                     // Clamp cursor
@@ -662,7 +1051,7 @@ fn handle_key(app: &mut App, key: crossterm::event::KeyEvent) {
                             config.machine_profiles.insert(machine_id, profile_name);
                         }
                         if config.save().is_err() {
-                            app.flash_error = Some((Instant::now(), "save failed".into()));
+                            app.set_flash_error("save failed");
                         }
                         app.reload_state();
                     }
@@ -702,7 +1091,7 @@ fn handle_key(app: &mut App, key: crossterm::event::KeyEvent) {
                             .unwrap_or(false)
                     };
                     if !ok {
-                        app.flash_error = Some((Instant::now(), "save failed".into()));
+                        app.set_flash_error("save failed");
                     }
                     // Refresh items
                     refresh_list_edit(app);
@@ -754,7 +1143,7 @@ fn handle_key(app: &mut App, key: crossterm::event::KeyEvent) {
                         .unwrap_or(false)
                 };
                 if !ok {
-                    app.flash_error = Some((Instant::now(), "save failed".into()));
+                    app.set_flash_error("save failed");
                 }
                 refresh_list_edit(app);
                 // Adjust cursor if needed
@@ -774,7 +1163,7 @@ fn handle_key(app: &mut App, key: crossterm::event::KeyEvent) {
                         .map(|c| config_edit::toggle_dotfile_create(c, cursor))
                         .unwrap_or(false);
                     if !ok {
-                        app.flash_error = Some((Instant::now(), "save failed".into()));
+                        app.set_flash_error("save failed");
                     }
                     refresh_list_edit(app);
                 }
@@ -801,7 +1190,9 @@ fn handle_key(app: &mut App, key: crossterm::event::KeyEvent) {
                     .map(|c| config_edit::set_value(c, idx, &buf))
                     .unwrap_or(false);
                 if !ok {
-                    app.flash_error = Some((Instant::now(), "save failed".into()));
+                    app.set_flash_error("save failed");
+                } else if app.state.daemon_running {
+                    crate::daemon::ipc::reload_config();
                 }
                 app.config_editing = false;
                 app.config_edit_buf.clear();
@@ -817,6 +1208,25 @@ fn handle_key(app: &mut App, key: crossterm::event::KeyEvent) {
         return;
     }
 
+    // Typing a `/` filter: the rows narrow as it changes
+    if app.filter_editing {
+        if let Some((filter, cursor)) = app.filter_mut() {
+            match key.code {
+                KeyCode::Esc => filter.clear(),
+                KeyCode::Backspace => {
+                    filter.pop();
+                }
+                KeyCode::Char(c) => filter.push(c),
+                _ => {}
+            }
+            *cursor = 0;
+        }
+        if matches!(key.code, KeyCode::Esc | KeyCode::Enter) {
+            app.filter_editing = false;
+        }
+        return;
+    }
+
     // Config tab Enter: toggle bool, start text edit, or open list sub-view
     if app.active_tab == Tab::Config && key.code == KeyCode::Enter {
         let idx = app.scroll_offset();
@@ -831,7 +1241,9 @@ fn handle_key(app: &mut App, key: crossterm::event::KeyEvent) {
                         .map(|c| config_edit::toggle(c, idx))
                         .unwrap_or(false);
                     if !ok {
-                        app.flash_error = Some((Instant::now(), "save failed".into()));
+                        app.set_flash_error("save failed");
+                    } else if app.state.daemon_running {
+                        crate::daemon::ipc::reload_config();
                     }
                 }
                 config_edit::FieldKind::Text => {
@@ -881,7 +1293,11 @@ fn handle_key(app: &mut App, key: crossterm::event::KeyEvent) {
 
     // Machines tab Enter: expand/collapse
     if app.active_tab == Tab::Machines && key.code == KeyCode::Enter {
-        let rows = widgets::machines::build_rows(&app.state, app.machine_expanded.as_deref());
+        let rows = widgets::machines::build_rows(
+            &app.state,
+            app.machine_expanded.as_deref(),
+            &app.machine_filter,
+        );
         if app.machine_cursor < rows.len() {
             if let widgets::machines::MachineRow::Header { machine_id, .. } =
                 &rows[app.machine_cursor]
@@ -892,8 +1308,11 @@ fn handle_key(app: &mut App, key: crossterm::event::KeyEvent) {
                     app.machine_expanded = Some(machine_id.clone());
                 }
                 // Clamp cursor to new row count
-                let new_rows =
-                    widgets::machines::build_rows(&app.state, app.machine_expanded.as_deref());
+                let new_rows = widgets::machines::build_rows(
+                    &app.state,
+                    app.machine_expanded.as_deref(),
+                    &app.machine_filter,
+                );
                 if app.machine_cursor >= new_rows.len() {
                     app.machine_cursor = new_rows.len().saturating_sub(1);
                 }
@@ -902,6 +1321,27 @@ fn handle_key(app: &mut App, key: crossterm::event::KeyEvent) {
         return;
     }
 
+    // Machines tab: a shows a pairing QR code for a new machine
+    if app.active_tab == Tab::Machines && key.code == KeyCode::Char('a') {
+        if app.pairing_rx.is_some() {
+            return;
+        }
+        let (Some(config), Some(machine_id)) = (
+            app.state.config.clone(),
+            app.state.sync_state.as_ref().map(|s| s.machine_id.clone()),
+        ) else {
+            return;
+        };
+        let (tx, rx) = std::sync::mpsc::channel();
+        // Wrapping the key is deliberately slow (scrypt)
+        std::thread::spawn(move || {
+            let _ = tx.send(pairing_popup(&config, &machine_id).map_err(|e| e.to_string()));
+        });
+        app.pairing_rx = Some(rx);
+        app.set_flash_message("creating pairing code...");
+        return;
+    }
+
     // Machines tab: p opens profile picker
     if app.active_tab == Tab::Machines && key.code == KeyCode::Char('p') {
         if let Some(ref config) = app.state.config {
@@ -961,33 +1401,8 @@ fn handle_key(app: &mut App, key: crossterm::event::KeyEvent) {
                         app.files.expanded_commit = None;
                         app.files.expanded_diff.clear();
                     } else {
-                        let encrypted = app
-                            .state
-                            .config
-                            .as_ref()
-                            .map(|c| c.security.encrypt_dotfiles)
-                            .unwrap_or(false);
-                        let diff = app
-                            .files
-                            .expanded_file
-                            .as_ref()
-                            .and_then(|repo_path| {
-                                let dotfile = repo_path_to_dotfile(
-                                    repo_path,
-                                    encrypted,
-                                    app.state.config.as_ref(),
-                                );
-                                crate::sync::SyncEngine::sync_path()
-                                    .ok()
-                                    .and_then(|p| crate::sync::GitBackend::open(&p).ok())
-                                    .and_then(|git| {
-                                        git.file_diff(commit_hash, repo_path, &dotfile, encrypted)
-                                            .ok()
-                                    })
-                            })
-                            .unwrap_or_default();
-                        app.files.expanded_diff = diff.lines().map(|l| l.to_string()).collect();
                         app.files.expanded_commit = Some(commit_hash.clone());
+                        refresh_history_diff(app);
                     }
                 }
                 widgets::files::FileRow::DeletedHeader { section, .. } => {
@@ -1009,7 +1424,8 @@ fn handle_key(app: &mut App, key: crossterm::event::KeyEvent) {
 
     // Packages tab Enter: expand/collapse or uninstall
     if app.active_tab == Tab::Packages && key.code == KeyCode::Enter {
-        let rows = widgets::packages::build_rows(&app.state, app.pkg_expanded.as_deref());
+        let rows =
+            widgets::packages::build_rows(&app.state, app.pkg_expanded.as_deref(), &app.pkg_filter);
         if app.pkg_cursor < rows.len() {
             match &rows[app.pkg_cursor] {
                 widgets::packages::PkgRow::Header { manager_key, .. } => {
@@ -1019,8 +1435,11 @@ fn handle_key(app: &mut App, key: crossterm::event::KeyEvent) {
                         app.pkg_expanded = Some(manager_key.clone());
                     }
                     // Clamp cursor to new row count
-                    let new_rows =
-                        widgets::packages::build_rows(&app.state, app.pkg_expanded.as_deref());
+                    let new_rows = widgets::packages::build_rows(
+                        &app.state,
+                        app.pkg_expanded.as_deref(),
+                        &app.pkg_filter,
+                    );
                     if app.pkg_cursor >= new_rows.len() {
                         app.pkg_cursor = new_rows.len().saturating_sub(1);
                     }
@@ -1029,7 +1448,20 @@ fn handle_key(app: &mut App, key: crossterm::event::KeyEvent) {
                     manager_key, name, ..
                 } => {
                     if app.uninstalling.is_none() && manager_key != "brew_taps" {
+                        let (tx, rx) = std::sync::mpsc::channel();
+                        let mk = manager_key.clone();
+                        let pn = name.clone();
+                        std::thread::spawn(move || {
+                            let dependents = tokio::runtime::Builder::new_current_thread()
+                                .enable_all()
+                                .build()
+                                .map(|rt| rt.block_on(run_dependents(&mk, &pn)))
+                                .unwrap_or_default();
+                            let _ = tx.send(dependents);
+                        });
                         app.uninstall_confirm = Some((manager_key.clone(), name.clone()));
+                        app.uninstall_dependents = None;
+                        app.dependents_rx = Some(rx);
                     }
                 }
             }
@@ -1038,9 +1470,18 @@ fn handle_key(app: &mut App, key: crossterm::event::KeyEvent) {
     }
 
     match key.code {
+        KeyCode::Char('/') if app.filter_mut().is_some() => {
+            app.filter_editing = true;
+        }
         KeyCode::Char('q') | KeyCode::Esc => {
-            if app.show_help {
-                app.show_help = false;
+            if app.show_flash_log {
+                app.show_flash_log = false;
+            } else if let Some((filter, cursor)) = app
+                .filter_mut()
+                .filter(|(filter, _)| key.code == KeyCode::Esc && !filter.is_empty())
+            {
+                filter.clear();
+                *cursor = 0;
             } else {
                 app.should_quit = true;
             }
@@ -1071,38 +1512,74 @@ fn handle_key(app: &mut App, key: crossterm::event::KeyEvent) {
                 }
             }
         }
+        KeyCode::Char('P') => {
+            use crate::daemon::ipc::DaemonRequest;
+            // The socket pauses the context this dashboard is in
+            let current = crate::config::current_context().ok().flatten();
+            let paused = app
+                .state
+                .daemon_status
+                .as_ref()
+                .and_then(|status| status.contexts.iter().find(|c| c.context == current))
+                .is_some_and(|c| c.is_paused());
+            let request = if paused {
+                DaemonRequest::Resume
+            } else {
+                DaemonRequest::Pause(None)
+            };
+            match crate::daemon::ipc::send(request) {
+                Ok(_) => {
+                    app.set_flash_message(if paused {
+                        "daemon resumed"
+                    } else {
+                        "daemon paused"
+                    });
+                    app.reload_state();
+                }
+                Err(e) => app.set_flash_error(e.to_string()),
+            }
+        }
         KeyCode::Char('r') => {
             app.reload_state();
         }
+        KeyCode::Char('v') => {
+            app.reveal_secrets = !app.reveal_secrets;
+            refresh_conflict_diff(app);
+            refresh_history_diff(app);
+            app.set_flash_message(format!(
+                "Secrets in diffs: {}",
+                if app.reveal_secrets {
+                    "shown"
+                } else {
+                    "masked"
+                }
+            ));
+        }
         KeyCode::Char('t') => {
             if app.active_tab == Tab::Files {
                 let rows = widgets::files::build_rows(&app.state, &app.files);
                 if app.files.cursor < rows.len() {
                     if let widgets::files::FileRow::File { path, .. } = &rows[app.files.cursor] {
                         let path = path.clone();
-                        if let (Some(ref mut config), Some(ref ss)) =
+                        let toggled = if let (Some(ref mut config), Some(ref ss)) =
                             (&mut app.state.config, &app.state.sync_state)
                         {
-                            let ok = config_edit::toggle_profile_dotfile_shared(
+                            config_edit::toggle_profile_dotfile_shared(
                                 config,
                                 &ss.machine_id,
                                 &path,
-                            );
-                            if ok {
-                                app.flash_message = Some((
-                                    Instant::now(),
-                                    format!(
-                                        "{} shared: {}",
-                                        path,
-                                        if config.is_dotfile_shared(&ss.machine_id, &path) {
-                                            "on"
-                                        } else {
-                                            "off"
-                                        }
-                                    ),
-                                ));
-                                app.reload_state();
-                            }
+                            )
+                            .then(|| config.is_dotfile_shared(&ss.machine_id, &path))
+                        } else {
+                            None
+                        };
+                        if let Some(shared) = toggled {
+                            app.set_flash_message(format!(
+                                "{} shared: {}",
+                                path,
+                                if shared { "on" } else { "off" }
+                            ));
+                            app.reload_state();
                         }
                     }
                 }
@@ -1262,10 +1739,51 @@ fn handle_key(app: &mut App, key: crossterm::event::KeyEvent) {
                 items.sort_by(|a, b| a.manager_key.cmp(&b.manager_key).then(a.name.cmp(&b.name)));
 
                 if !items.is_empty() {
-                    app.pkg_import_picker = Some(PkgImportPickerState { items, cursor: 0 });
+                    app.pkg_import_picker = Some(PkgImportPickerState {
+                        items,
+                        cursor: 0,
+                        approving: false,
+                    });
                 }
             }
         }
+        KeyCode::Char('a') if app.active_tab == Tab::Packages && app.installing.is_none() => {
+            // Installs queued by packages.approve_installs, with the machines that have them
+            let Some(ref sync_state) = app.state.sync_state else {
+                return;
+            };
+            let items: Vec<PkgImportItem> = sync_state
+                .pending_installs
+                .iter()
+                .flat_map(|(manager_key, packages)| {
+                    packages.iter().map(|name| PkgImportItem {
+                        manager_key: manager_key.clone(),
+                        name: name.clone(),
+                        sources: app
+                            .state
+                            .machines
+                            .iter()
+                            .filter(|m| m.machine_id != sync_state.machine_id)
+                            .filter(|m| {
+                                m.packages
+                                    .get(manager_key)
+                                    .is_some_and(|p| p.contains(name))
+                            })
+                            .map(|m| m.machine_id.clone())
+                            .collect(),
+                    })
+                })
+                .collect();
+            if items.is_empty() {
+                app.set_flash_message("no installs waiting for approval");
+            } else {
+                app.pkg_import_picker = Some(PkgImportPickerState {
+                    items,
+                    cursor: 0,
+                    approving: true,
+                });
+            }
+        }
         KeyCode::Tab => {
             let tabs = Tab::all();
             let current = tabs.iter().position(|t| *t == app.active_tab).unwrap_or(0);
@@ -1276,6 +1794,55 @@ fn handle_key(app: &mut App, key: crossterm::event::KeyEvent) {
         KeyCode::Char('3') => app.active_tab = Tab::Packages,
         KeyCode::Char('4') => app.active_tab = Tab::Machines,
         KeyCode::Char('5') => app.active_tab = Tab::Config,
+        KeyCode::Char('6') => app.active_tab = Tab::Conflicts,
+        KeyCode::Char('7') => app.active_tab = Tab::Logs,
+        KeyCode::Char('f') if app.active_tab == Tab::Logs => {
+            if app.logs.follow {
+                app.logs.unfollow();
+            } else {
+                app.logs.follow = true;
+            }
+        }
+        KeyCode::Char('e') if app.active_tab == Tab::Logs && !app.logs.jump_to_error() => {
+            app.set_flash_message("no earlier errors in the daemon log");
+        }
+        KeyCode::Char('L') | KeyCode::Char('T') | KeyCode::Char('m')
+            if app.active_tab == Tab::Conflicts =>
+        {
+            let Some(file) = app
+                .state
+                .conflicts
+                .conflicts
+                .get(app.conflict_cursor)
+                .map(|c| c.file_path.clone())
+            else {
+                return;
+            };
+            // A running sync rewrites the same state; let it finish first
+            if app.sync_child.is_some() {
+                app.set_flash_error("sync in progress, try again when it finishes");
+                return;
+            }
+            match key.code {
+                KeyCode::Char('L') => {
+                    app.conflict_confirm = Some((file, crate::sync::ResolvedSide::Local))
+                }
+                KeyCode::Char('T') => {
+                    app.conflict_confirm = Some((file, crate::sync::ResolvedSide::Remote))
+                }
+                _ => app.conflict_merge = Some(file),
+            }
+        }
+        KeyCode::Char('J') if app.active_tab == Tab::Conflicts => {
+            let max = app.conflict_diff.as_ref().map_or(0, |d| d.len());
+            if app.scroll_offset() + 1 < max {
+                *app.scroll_offset_mut() += 1;
+            }
+        }
+        KeyCode::Char('K') if app.active_tab == Tab::Conflicts => {
+            let offset = app.scroll_offset_mut();
+            *offset = offset.saturating_sub(1);
+        }
         KeyCode::Char('j') | KeyCode::Down => {
             if app.active_tab == Tab::Files {
                 let max = app.item_count().saturating_sub(1);
@@ -1292,6 +1859,16 @@ fn handle_key(app: &mut App, key: crossterm::event::KeyEvent) {
                 if app.machine_cursor < max {
                     app.machine_cursor += 1;
                 }
+            } else if app.active_tab == Tab::Conflicts {
+                let max = app.item_count().saturating_sub(1);
+                if app.conflict_cursor < max {
+                    app.conflict_cursor += 1;
+                    refresh_conflict_diff(app);
+                }
+            } else if app.active_tab == Tab::Logs {
+                if !app.logs.follow {
+                    app.logs.top = (app.logs.first_shown() + 1).min(app.logs.bottom());
+                }
             } else {
                 let max = app.item_count().saturating_sub(1);
                 if app.scroll_offset() < max {
@@ -1306,13 +1883,21 @@ fn handle_key(app: &mut App, key: crossterm::event::KeyEvent) {
                 app.pkg_cursor = app.pkg_cursor.saturating_sub(1);
             } else if app.active_tab == Tab::Machines {
                 app.machine_cursor = app.machine_cursor.saturating_sub(1);
+            } else if app.active_tab == Tab::Conflicts {
+                if app.conflict_cursor > 0 {
+                    app.conflict_cursor -= 1;
+                    refresh_conflict_diff(app);
+                }
+            } else if app.active_tab == Tab::Logs {
+                app.logs.unfollow();
+                app.logs.top = app.logs.first_shown().saturating_sub(1);
             } else {
                 let offset = app.scroll_offset_mut();
                 *offset = offset.saturating_sub(1);
             }
         }
-        KeyCode::Char('?') => {
-            app.show_help = !app.show_help;
+        KeyCode::Char('l') => {
+            app.show_flash_log = !app.show_flash_log;
         }
         _ => {}
     }
@@ -1337,6 +1922,137 @@ fn refresh_files_expanded(app: &mut App) {
     }
 }
 
+/// Reload the diff of the expanded history entry on the Files tab
+fn refresh_history_diff(app: &mut App) {
+    let (Some(repo_path), Some(commit_hash)) =
+        (&app.files.expanded_file, &app.files.expanded_commit)
+    else {
+        app.files.expanded_diff.clear();
+        return;
+    };
+    let encrypted = app
+        .state
+        .config
+        .as_ref()
+        .map(|c| c.security.encrypt_dotfiles)
+        .unwrap_or(false);
+    let dotfile = repo_path_to_dotfile(repo_path, encrypted, app.state.config.as_ref());
+    let diff = crate::sync::SyncEngine::sync_path()
+        .ok()
+        .and_then(|p| crate::sync::GitBackend::open(&p).ok())
+        .and_then(|git| {
+            git.file_diff(commit_hash, repo_path, &dotfile, encrypted)
+                .ok()
+        })
+        .unwrap_or_default();
+    let lines: Vec<&str> = diff.lines().collect();
+    app.files.expanded_diff = if app.reveal_secrets {
+        lines.iter().map(|l| l.to_string()).collect()
+    } else {
+        crate::security::redact_lines(&lines)
+    };
+}
+
+/// Reload the diff for the selected conflict, keeping the cursor in range
+fn refresh_conflict_diff(app: &mut App) {
+    let conflicts = &app.state.conflicts.conflicts;
+    app.conflict_cursor = app.conflict_cursor.min(conflicts.len().saturating_sub(1));
+    let idx = Tab::all()
+        .iter()
+        .position(|t| *t == Tab::Conflicts)
+        .unwrap_or(0);
+    app.scroll_offsets[idx] = 0;
+    app.conflict_diff = match conflicts.get(app.conflict_cursor) {
+        Some(pending) => load_conflict(pending)
+            .map(|c| c.diff_text(app.reveal_secrets))
+            .map_err(|e| e.to_string()),
+        None => Ok(Vec::new()),
+    };
+}
+
+fn load_conflict(pending: &crate::sync::PendingConflict) -> Result<crate::sync::FileConflict> {
+    let config = crate::config::Config::load()?;
+    let key = if config.security.encrypt_dotfiles {
+        Some(crate::security::get_encryption_key()?)
+    } else {
+        None
+    };
+    let state = crate::sync::SyncState::load()?;
+    pending.load(
+        &config,
+        key.as_deref(),
+        &crate::home_dir()?,
+        &crate::sync::SyncEngine::sync_path()?,
+        &state.machine_id,
+    )
+}
+
+/// Settle a pending conflict on one side. The synced hash is moved to the remote's so
+/// the next sync sees only a local change (keep local, pushed) or none (take remote).
+/// Returns the backup holding the replaced local copy, if any.
+fn resolve_conflict(file: &str, side: crate::sync::ResolvedSide) -> Result<Option<String>> {
+    let mut conflict_state = crate::sync::ConflictState::load()?;
+    let Some(pending) = conflict_state
+        .conflicts
+        .iter()
+        .find(|c| c.file_path == file)
+        .cloned()
+    else {
+        anyhow::bail!("no pending conflict for {}", file);
+    };
+    let conflict = load_conflict(&pending)?;
+
+    let mut snapshot = None;
+    if side == crate::sync::ResolvedSide::Remote {
+        let dest = crate::home_dir()?.join(file);
+        snapshot = crate::sync::snapshot_before_resolution(file, &dest)?;
+        if let Some(parent) = dest.parent() {
+            std::fs::create_dir_all(parent)?;
+        }
+        std::fs::write(&dest, &conflict.remote_content)?;
+    }
+
+    let mut state = crate::sync::SyncState::load()?;
+    state.update_file(file, conflict.remote_hash);
+    state.save()?;
+    conflict_state.remove_conflict(file);
+    conflict_state.save()?;
+    Ok(snapshot)
+}
+
+/// Open the configured merge tool on a pending conflict, with the terminal released.
+/// Returns whether the file was merged; an untouched file stays pending.
+fn run_conflict_merge(file: &str) -> Result<bool> {
+    let mut conflict_state = crate::sync::ConflictState::load()?;
+    let Some(pending) = conflict_state
+        .conflicts
+        .iter()
+        .find(|c| c.file_path == file)
+        .cloned()
+    else {
+        anyhow::bail!("no pending conflict for {}", file);
+    };
+    let conflict = load_conflict(&pending)?;
+    if conflict.is_binary() {
+        anyhow::bail!("{} is binary and can't be merged", file);
+    }
+
+    let config = crate::config::Config::load()?;
+    let home = crate::home_dir()?;
+    crate::sync::snapshot_before_resolution(file, &home.join(file))?;
+    let resolution = conflict.launch_merge_tool(&config.merge, &home)?;
+    if resolution != crate::sync::ConflictResolution::Merged {
+        return Ok(false);
+    }
+
+    let mut state = crate::sync::SyncState::load()?;
+    state.update_file(file, conflict.remote_hash);
+    state.save()?;
+    conflict_state.remove_conflict(file);
+    conflict_state.save()?;
+    Ok(true)
+}
+
 /// Refresh list_edit items from current config state
 fn refresh_list_edit(app: &mut App) {
     let Some(ref le) = app.list_edit else {
@@ -1372,22 +2088,38 @@ fn refresh_list_edit(app: &mut App) {
     });
 }
 
-async fn run_uninstall(manager_key: &str, package: &str) -> std::result::Result<(), String> {
+fn uninstall_manager(manager_key: &str) -> Option<Box<dyn crate::packages::PackageManager>> {
     use crate::packages::*;
 
-    let manager: Box<dyn PackageManager> = match manager_key {
+    Some(match manager_key {
         "brew_formulae" | "brew_casks" => Box::new(BrewManager),
         "npm" => Box::new(NpmManager),
         "pnpm" => Box::new(PnpmManager),
         "bun" => Box::new(BunManager),
         "gem" => Box::new(GemManager),
         "uv" => Box::new(UvManager),
-        _ => return Err(format!("Unknown manager: {}", manager_key)),
-    };
+        _ => return None,
+    })
+}
 
+async fn run_uninstall(manager_key: &str, package: &str) -> std::result::Result<(), String> {
+    let manager = uninstall_manager(manager_key)
+        .ok_or_else(|| format!("Unknown manager: {}", manager_key))?;
     manager.uninstall(package).await.map_err(|e| e.to_string())
 }
 
+/// Installed packages that would break if `package` were uninstalled
+async fn run_dependents(manager_key: &str, package: &str) -> Vec<String> {
+    // Casks can't be depended on, and `brew uses` would look up a formula of that name
+    if manager_key == "brew_casks" {
+        return Vec::new();
+    }
+    match uninstall_manager(manager_key) {
+        Some(manager) => manager.get_dependents(package).await.unwrap_or_default(),
+        None => Vec::new(),
+    }
+}
+
 async fn run_install(manager_key: &str, package: &str) -> std::result::Result<(), String> {
     use crate::packages::*;
 
@@ -1476,17 +2208,15 @@ fn remove_from_removed_packages(state: &DashboardState, manager_key: &str, pkg_n
         return;
     }
     if let Ok(sync_path) = crate::sync::SyncEngine::sync_path() {
-        let machines_dir = sync_path.join("machines");
-        let path = machines_dir.join(format!("{}.json", current_machine_id));
-        if let Ok(content) = std::fs::read_to_string(&path) {
-            if let Ok(mut machine) = serde_json::from_str::<crate::sync::MachineState>(&content) {
-                if let Some(removed) = machine.removed_packages.get_mut(manager_key) {
-                    removed.retain(|p| p != pkg_name);
-                    if removed.is_empty() {
-                        machine.removed_packages.remove(manager_key);
-                    }
-                    let _ = machine.save_to_repo(&sync_path);
+        if let Ok(Some(mut machine)) =
+            crate::sync::MachineState::load_from_repo(&sync_path, current_machine_id)
+        {
+            if let Some(removed) = machine.removed_packages.get_mut(manager_key) {
+                removed.retain(|p| p != pkg_name);
+                if removed.is_empty() {
+                    machine.removed_packages.remove(manager_key);
                 }
+                let _ = machine.save_to_repo(&sync_path);
             }
         }
     }
@@ -1539,22 +2269,101 @@ fn run_restore(
     Ok(())
 }
 
-fn render_confirm_popup(f: &mut Frame, title: &str, msg: &str, border_color: Color) {
+fn pairing_popup(config: &crate::Config, machine_id: &str) -> Result<PairingPopup> {
+    let (invite, code) = crate::security::pairing::Invite::from_config(config, machine_id, None)?;
+    Ok(PairingPopup {
+        qr: crate::security::pairing::qr_lines(&invite.encode())?,
+        code,
+        profile: invite.profile,
+        expires: invite
+            .expires
+            .with_timezone(&chrono::Local)
+            .format("%H:%M")
+            .to_string(),
+    })
+}
+
+fn render_pairing_popup(f: &mut Frame, popup: &PairingPopup) {
     let area = f.area();
-    let width = (msg.len() as u16 + 8).min(area.width.saturating_sub(4));
-    let height = 5u16.min(area.height.saturating_sub(2));
+    let qr_width = popup.qr.first().map_or(0, |line| line.chars().count());
+    let width = (qr_width as u16 + 4).max(56).min(area.width);
+    let height = (popup.qr.len() as u16 + 8).min(area.height);
     let x = (area.width.saturating_sub(width)) / 2;
     let y = (area.height.saturating_sub(height)) / 2;
     let popup_area = Rect::new(x, y, width, height);
 
     f.render_widget(ratatui::widgets::Clear, popup_area);
 
-    let text = vec![
+    let mut text: Vec<Line> = popup
+        .qr
+        .iter()
+        .map(|line| Line::from(Span::raw(format!(" {}", line))).centered())
+        .collect();
+    text.push(Line::from(Span::styled(
+        "On the new machine: tether init --from-qr",
+        Style::default().fg(Color::White),
+    )));
+    if let Some(ref code) = popup.code {
+        text.push(Line::from(vec![
+            Span::styled("Pairing code: ", Style::default().fg(Color::Gray)),
+            Span::styled(code.clone(), Style::default().fg(Color::Yellow).bold()),
+        ]));
+    }
+    let mut details = format!("Valid until {}", popup.expires);
+    if let Some(ref profile) = popup.profile {
+        details = format!("Profile {} · {}", profile, details);
+    }
+    text.push(Line::from(Span::styled(
+        details,
+        Style::default().fg(Color::Gray),
+    )));
+    text.push(Line::from(Span::styled(
+        "No camera? Run 'tether machines pair' for text to paste",
+        Style::default().fg(Color::DarkGray),
+    )));
+
+    let paragraph = ratatui::widgets::Paragraph::new(text)
+        .alignment(Alignment::Center)
+        .block(
+            ratatui::widgets::Block::default()
+                .title(" Add a machine (any key closes) ")
+                .borders(ratatui::widgets::Borders::ALL)
+                .border_style(Style::default().fg(Color::Cyan)),
+        );
+    f.render_widget(paragraph, popup_area);
+}
+
+fn render_confirm_popup(
+    f: &mut Frame,
+    title: &str,
+    msg: &str,
+    note: Option<(&str, Color)>,
+    border_color: Color,
+) {
+    let area = f.area();
+    let text_len = msg.len().max(note.map_or(0, |(n, _)| n.len()));
+    let width = (text_len as u16 + 8).min(area.width.saturating_sub(4));
+    let height = (5u16 + note.map_or(0, |_| 1)).min(area.height.saturating_sub(2));
+    let x = (area.width.saturating_sub(width)) / 2;
+    let y = (area.height.saturating_sub(height)) / 2;
+    let popup_area = Rect::new(x, y, width, height);
+
+    f.render_widget(ratatui::widgets::Clear, popup_area);
+
+    let mut text = vec![
         Line::from(""),
         Line::from(Span::styled(
             format!("  {}", msg),
             Style::default().fg(Color::White),
         )),
+    ];
+    if let Some((note, color)) = note {
+        text.push(Line::from(Span::styled(
+            format!("  {}", note),
+            Style::default().fg(color),
+        )));
+    }
+    text.extend([
         Line::from(""),
         Line::from(vec![
             Span::styled("  y", Style::default().fg(Color::Yellow).bold()),
@@ -1562,7 +2371,7 @@ fn render_confirm_popup(f: &mut Frame, title: &str, msg: &str, border_color: Col
             Span::styled("n/Esc", Style::default().fg(Color::Yellow).bold()),
             Span::styled(" cancel", Style::default().fg(Color::Gray)),
         ]),
-    ];
+    ]);
 
     let paragraph = ratatui::widgets::Paragraph::new(text).block(
         ratatui::widgets::Block::default()
@@ -1651,7 +2460,11 @@ fn render_file_import_popup(f: &mut Frame, picker: &ImportPickerState) {
 
 fn render_pkg_import_popup(f: &mut Frame, picker: &PkgImportPickerState) {
     let area = f.area();
-    let title = " Import package ";
+    let title = if picker.approving {
+        " Approve install "
+    } else {
+        " Import package "
+    };
     let max_item_len = picker
         .items
         .iter()
@@ -1751,6 +2564,7 @@ fn draw(f: &mut Frame, app: &App) {
         main_chunks[0],
         &app.state,
         app.sync_child.is_some(),
+        app.sync_progress.as_ref(),
         app.daemon_op,
         flash,
         app.uninstalling.as_ref(),
@@ -1796,7 +2610,13 @@ fn draw(f: &mut Frame, app: &App) {
 
     match app.active_tab {
         Tab::Overview => draw_overview(f, content_chunks[1], app),
-        Tab::Files => widgets::files::render(f, content_chunks[1], &app.state, &app.files),
+        Tab::Files => widgets::files::render(
+            f,
+            content_chunks[1],
+            &app.state,
+            &app.files,
+            app.filter_editing,
+        ),
         Tab::Packages => {
             widgets::packages::render(
                 f,
@@ -1804,6 +2624,8 @@ fn draw(f: &mut Frame, app: &App) {
                 &app.state,
                 app.pkg_expanded.as_deref(),
                 app.pkg_cursor,
+                &app.pkg_filter,
+                app.filter_editing,
             );
         }
         Tab::Machines => widgets::machines::render(
@@ -1812,6 +2634,8 @@ fn draw(f: &mut Frame, app: &App) {
             &app.state,
             app.machine_expanded.as_deref(),
             app.machine_cursor,
+            &app.machine_filter,
+            app.filter_editing,
         ),
         Tab::Config => widgets::config::render(
             f,
@@ -1822,12 +2646,21 @@ fn draw(f: &mut Frame, app: &App) {
             &app.config_edit_buf,
             app.list_edit.as_ref(),
         ),
+        Tab::Conflicts => widgets::conflicts::render(
+            f,
+            content_chunks[1],
+            &app.state,
+            app.conflict_cursor,
+            &app.conflict_diff,
+            app.scroll_offset(),
+        ),
+        Tab::Logs => widgets::logs::render(f, content_chunks[1], &app.logs),
     }
 
     widgets::help::render_bar(f, main_chunks[2], app.active_tab);
 
-    if app.show_help {
-        widgets::help::render_overlay(f);
+    if app.show_flash_log {
+        widgets::status::render_flash_log(f, &app.flash_log);
     }
 
     // Profile picker popup
@@ -1838,10 +2671,22 @@ fn draw(f: &mut Frame, app: &App) {
     // Uninstall confirmation popup
     if let Some((ref manager_key, ref pkg_name)) = app.uninstall_confirm {
         let label = widgets::manager_label(manager_key);
+        let note = match app.uninstall_dependents.as_deref() {
+            None => ("Checking dependents...".to_string(), Color::Gray),
+            Some([]) => (
+                "No installed packages depend on it".to_string(),
+                Color::Gray,
+            ),
+            Some(dependents) => (
+                format!("⚠ Required by: {}", dependents.join(", ")),
+                Color::Yellow,
+            ),
+        };
         render_confirm_popup(
             f,
             "Uninstall",
             &format!("Uninstall {} ({})?", pkg_name, label),
+            Some((note.0.as_str(), note.1)),
             Color::Red,
         );
     }
@@ -1852,16 +2697,33 @@ fn draw(f: &mut Frame, app: &App) {
             f,
             "Restore",
             &format!("Restore {} to {}?", dotfile_path, short_hash),
+            None,
             Color::Yellow,
         );
     }
 
+    // Conflict resolution confirmation popup
+    if let Some((ref file, side)) = app.conflict_confirm {
+        let (msg, note) = match side {
+            crate::sync::ResolvedSide::Local => (
+                format!("Keep local {}?", file),
+                "The next sync pushes it over the remote copy",
+            ),
+            crate::sync::ResolvedSide::Remote => (
+                format!("Take remote {}?", file),
+                "Local copy is backed up, then replaced",
+            ),
+        };
+        render_confirm_popup(f, "Resolve", &msg, Some((note, Color::Gray)), Color::Yellow);
+    }
+
     // File delete confirmation popup
     if let Some(ref path) = app.file_delete_confirm {
         render_confirm_popup(
             f,
             "Remove",
             &format!("Remove {} from profile?", path),
+            None,
             Color::Red,
         );
     }
@@ -1878,6 +2740,7 @@ fn draw(f: &mut Frame, app: &App) {
             f,
             "Install",
             &format!("Install {} ({})?", pkg_name, label),
+            None,
             Color::Green,
         );
     }
@@ -1886,6 +2749,17 @@ fn draw(f: &mut Frame, app: &App) {
     if let Some(ref picker) = app.pkg_import_picker {
         render_pkg_import_popup(f, picker);
     }
+
+    if let Some(ref popup) = app.pairing {
+        render_pairing_popup(f, popup);
+    }
+
+    // Help goes over everything, including the popup it documents
+    if app.show_help {
+        if let Some(context) = app.key_context() {
+            widgets::help::render_overlay(f, context);
+        }
+    }
 }
 
 fn render_profile_popup(f: &mut Frame, options: &[String], cursor: usize) {
@@ -1976,9 +2850,6 @@ fn load_deleted_files(state: &DashboardState) -> HashMap<String, Vec<String>> {
     let machine_id = ss.machine_id.as_str();
     let sync_path_opt = crate::sync::SyncEngine::sync_path().ok();
     let config_ref = state.config.as_ref();
-    let profile = config_ref
-        .map(|c| c.profile_name(machine_id))
-        .unwrap_or(crate::config::DEFAULT_PROFILE);
 
     let mut state_repo_paths: HashSet<String> = HashSet::new();
     for path in ss.files.keys() {
@@ -1999,6 +2870,9 @@ fn load_deleted_files(state: &DashboardState) -> HashMap<String, Vec<String>> {
             let shared = config_ref
                 .map(|c| c.is_dotfile_shared(machine_id, path))
                 .unwrap_or(false);
+            let profile = config_ref
+                .map(|c| c.dotfile_profile(machine_id, path))
+                .unwrap_or(crate::config::DEFAULT_PROFILE);
             if let Some(ref sp) = sync_path_opt {
                 crate::sync::resolve_dotfile_repo_path(sp, path, encrypted, profile, shared)
             } else {
@@ -2084,6 +2958,32 @@ fn repo_path_to_dotfile_with_profiles(
 }
 
 fn draw_overview(f: &mut Frame, area: Rect, app: &App) {
+    // A failed daemon sync, then a sync repo growing fast, get a line above everything else
+    let mut banners = Vec::new();
+    if let Some(error) = &app.state.last_error {
+        banners.push(Span::styled(
+            format!(" Daemon sync {}: {}", error.headline(), error.first_line()),
+            Style::default().fg(Color::Red).add_modifier(Modifier::BOLD),
+        ));
+    }
+    if let Some(recommendation) = app
+        .state
+        .sync_state
+        .as_ref()
+        .and_then(|state| crate::sync::growth::recommendation(&state.repo_sizes))
+    {
+        banners.push(Span::styled(
+            format!(" {}", recommendation.message()),
+            Style::default().fg(Color::Yellow),
+        ));
+    }
+    let [banner, area] =
+        Layout::vertical([Constraint::Length(banners.len() as u16), Constraint::Min(0)])
+            .areas(area);
+    f.render_widget(
+        ratatui::widgets::Paragraph::new(banners.into_iter().map(Line::from).collect::<Vec<_>>()),
+        banner,
+    );
     let content_chunks = Layout::vertical([
         Constraint::Percentage(40),
         Constraint::Percentage(30),
@@ -1,5 +1,5 @@
 use crate::config::Config;
-use crate::sync::{ConflictState, MachineState, SyncEngine, SyncState, TeamManifest};
+use crate::sync::{ConflictState, GitBackend, MachineState, SyncEngine, SyncState, TeamManifest};
 
 pub struct DashboardState {
     pub config: Option<Config>,
@@ -9,6 +9,12 @@ pub struct DashboardState {
     pub team_manifest: TeamManifest,
     pub daemon_pid: Option<u32>,
     pub daemon_running: bool,
+    /// What the running daemon reports over its socket, per context
+    pub daemon_status: Option<crate::daemon::ipc::DaemonStatus>,
+    /// The daemon's last failed sync, unless one has succeeded since
+    pub last_error: Option<crate::daemon::last_error::LastError>,
+    /// Sync repo commits made offline and not yet pushed
+    pub pending_push: usize,
     pub activity_lines: Vec<String>,
 }
 
@@ -26,6 +32,16 @@ impl DashboardState {
             .unwrap_or_default();
 
         let (daemon_pid, daemon_running) = Self::check_daemon();
+        let daemon_status = daemon_running.then(crate::daemon::ipc::query).flatten();
+        let pending_push = sync_state
+            .as_ref()
+            .and_then(|_| SyncEngine::sync_path().ok())
+            .and_then(|p| GitBackend::open(&p).ok())
+            .and_then(|git| git.unpushed_commits().ok())
+            .unwrap_or(0);
+        let last_error = sync_state
+            .as_ref()
+            .and_then(|state| crate::daemon::last_error::LastError::current(state.last_sync));
         let activity_lines = Self::read_activity_log();
 
         Self {
@@ -36,31 +52,26 @@ impl DashboardState {
             team_manifest,
             daemon_pid,
             daemon_running,
+            daemon_status,
+            last_error,
+            pending_push,
             activity_lines,
         }
     }
 
     fn check_daemon() -> (Option<u32>, bool) {
         // Try PID file first
-        if let Ok(dir) = Config::config_dir() {
-            let pid_path = dir.join("daemon.pid");
-            if let Ok(contents) = std::fs::read_to_string(&pid_path) {
-                if let Ok(pid) = contents.trim().parse::<u32>() {
-                    if pid > 0 {
-                        let running = unsafe { libc::kill(pid as libc::pid_t, 0) == 0 };
-                        if running {
-                            return (Some(pid), true);
-                        }
-                    }
-                }
-            }
+        if let Some(pid) = crate::daemon::pid::check().ok().and_then(|s| s.running()) {
+            return (Some(pid), true);
         }
 
         // Fallback: check launchd (handles missing/stale PID file)
         #[cfg(target_os = "macos")]
         {
+            let label =
+                crate::daemon::launchd_label().unwrap_or_else(|_| "com.tether.daemon".to_string());
             if let Ok(output) = std::process::Command::new("launchctl")
-                .args(["list", "com.tether.daemon"])
+                .args(["list", &label])
                 .output()
             {
                 if output.status.success() {
@@ -80,46 +91,21 @@ impl DashboardState {
             }
         }
 
+        // Fallback: a Task Scheduler daemon the PID file doesn't account for
+        #[cfg(windows)]
+        if crate::daemon::scheduled_task_status().as_deref() == Some("Running") {
+            return (None, true);
+        }
+
         (None, false)
     }
 
     fn read_activity_log() -> Vec<String> {
-        use std::io::{BufRead, BufReader, Seek, SeekFrom};
-
-        let log_path = match Config::config_dir() {
-            Ok(d) => d.join("daemon.log"),
-            Err(_) => return Vec::new(),
-        };
-
-        let file = match std::fs::File::open(&log_path) {
-            Ok(f) => f,
-            Err(_) => return Vec::new(),
-        };
-
-        let metadata = match file.metadata() {
-            Ok(m) => m,
-            Err(_) => return Vec::new(),
-        };
-
-        let file_size = metadata.len();
-        if file_size == 0 {
-            return Vec::new();
-        }
-
-        let read_size = 8192u64.min(file_size);
-        let mut reader = BufReader::new(file);
-        if reader.seek(SeekFrom::End(-(read_size as i64))).is_err() {
+        let Ok(dir) = Config::config_dir() else {
             return Vec::new();
-        }
-
-        // If we seeked into the middle of a line, skip the partial first line
-        if read_size < file_size {
-            let mut partial = String::new();
-            let _ = reader.read_line(&mut partial);
-        }
-
-        let lines: Vec<String> = reader.lines().map_while(Result::ok).collect();
-        let start = lines.len().saturating_sub(20);
-        lines[start..].to_vec()
+        };
+        let mut lines = crate::daemon::logs::tail(&dir.join("daemon.log"), 8192);
+        lines.drain(..lines.len().saturating_sub(20));
+        lines
     }
 }
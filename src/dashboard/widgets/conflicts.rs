@@ -0,0 +1,116 @@
+use crate::cli::output::relative_time;
+use crate::dashboard::state::DashboardState;
+use ratatui::{prelude::*, widgets::*};
+
+pub fn render(
+    f: &mut Frame,
+    area: Rect,
+    state: &DashboardState,
+    cursor: usize,
+    diff: &std::result::Result<Vec<String>, String>,
+    diff_scroll: usize,
+) {
+    let conflicts = &state.conflicts.conflicts;
+    let list_height = (conflicts.len().max(1) as u16 + 2)
+        .min(area.height / 3)
+        .max(3);
+    let chunks =
+        Layout::vertical([Constraint::Length(list_height), Constraint::Min(3)]).split(area);
+
+    let block = Block::default()
+        .title(format!(" Conflicts ({}) ", conflicts.len()))
+        .borders(Borders::ALL)
+        .border_style(Style::default().fg(if conflicts.is_empty() {
+            Color::Gray
+        } else {
+            Color::Red
+        }));
+    let inner_area = block.inner(chunks[0]);
+    f.render_widget(block, chunks[0]);
+
+    if conflicts.is_empty() {
+        let msg = Paragraph::new(Span::styled(
+            "  No pending conflicts",
+            Style::default().fg(Color::Gray),
+        ));
+        f.render_widget(msg, inner_area);
+        return;
+    }
+
+    let visible_height = inner_area.height as usize;
+    let scroll = if cursor >= visible_height {
+        cursor - visible_height + 1
+    } else {
+        0
+    };
+
+    let rows = inner_area.y..inner_area.y + inner_area.height;
+    for (y, (idx, pending)) in rows.zip(conflicts.iter().enumerate().skip(scroll)) {
+        let is_selected = idx == cursor;
+        let (name_style, dim_style, bg_style) = if is_selected {
+            (
+                Style::default().fg(Color::White).bg(Color::Indexed(240)),
+                Style::default()
+                    .fg(Color::Indexed(250))
+                    .bg(Color::Indexed(240)),
+                Style::default().bg(Color::Indexed(240)),
+            )
+        } else {
+            (
+                Style::default().fg(Color::White),
+                Style::default().fg(Color::Gray),
+                Style::default(),
+            )
+        };
+        let line = Line::from(vec![
+            Span::styled("  ! ", Style::default().fg(Color::Red).patch(bg_style)),
+            Span::styled(format!("~/{}", pending.file_path), name_style),
+            Span::styled(
+                format!("  detected {}", relative_time(pending.detected_at)),
+                dim_style,
+            ),
+            Span::styled(" ".repeat(inner_area.width as usize), bg_style),
+        ]);
+        f.render_widget(
+            Paragraph::new(line),
+            Rect::new(inner_area.x, y, inner_area.width, 1),
+        );
+    }
+
+    let title = conflicts
+        .get(cursor)
+        .map(|c| format!(" ~/{}: local → remote ", c.file_path))
+        .unwrap_or_default();
+    let lines: Vec<Line> = match diff {
+        Ok(diff) if diff.is_empty() => vec![Line::from(Span::styled(
+            "  Local and remote are identical",
+            Style::default().fg(Color::Gray),
+        ))],
+        Ok(diff) => diff
+            .iter()
+            .map(|l| {
+                let fg = if l.starts_with('+') {
+                    Color::Green
+                } else if l.starts_with('-') {
+                    Color::Red
+                } else {
+                    Color::Gray
+                };
+                Line::from(Span::styled(l.as_str(), Style::default().fg(fg)))
+            })
+            .collect(),
+        Err(e) => vec![Line::from(Span::styled(
+            format!("  Could not load conflict: {}", e),
+            Style::default().fg(Color::Red),
+        ))],
+    };
+    let paragraph = Paragraph::new(lines)
+        .block(
+            Block::default()
+                .title(title)
+                .borders(Borders::ALL)
+                .border_style(Style::default().fg(Color::Gray)),
+        )
+        .scroll((diff_scroll.min(u16::MAX as usize) as u16, 0));
+    f.render_widget(paragraph, chunks[1]);
+}
@@ -1,3 +1,4 @@
+use super::{filter_title, highlight, matches_filter};
 use crate::cli::output::relative_time;
 use crate::dashboard::state::DashboardState;
 use crate::dashboard::FilesTabState;
@@ -161,7 +162,7 @@ fn collect_sections(state: &DashboardState) -> Vec<SectionData> {
                     .unwrap_or("");
                 let config_ref = state.config.as_ref();
                 let profile = config_ref
-                    .map(|c| c.profile_name(machine_id))
+                    .map(|c| c.dotfile_profile(machine_id, path))
                     .unwrap_or(crate::config::DEFAULT_PROFILE);
                 let shared = config_ref
                     .map(|c| c.is_dotfile_shared(machine_id, path))
@@ -246,22 +247,39 @@ fn collect_sections(state: &DashboardState) -> Vec<SectionData> {
     sections
 }
 
-/// Build rows for the interactive Files tab
+/// Build rows for the interactive Files tab. With a `/` filter, sections list only
+/// the files matching it, expanded, and sections without any are left out.
 pub fn build_rows(state: &DashboardState, ft: &FilesTabState) -> Vec<FileRow> {
     let sections = collect_sections(state);
+    let filtering = !ft.filter.is_empty();
     let mut rows = Vec::new();
 
     for section in &sections {
-        let is_collapsed = ft.collapsed.contains(&section.label);
+        let files: Vec<&FileEntry> = section
+            .files
+            .iter()
+            .filter(|(path, ..)| matches_filter(path, &ft.filter))
+            .collect();
+        let deleted: Vec<&String> = ft
+            .deleted
+            .get(&section.label)
+            .into_iter()
+            .flatten()
+            .filter(|path| matches_filter(path, &ft.filter))
+            .collect();
+        if filtering && files.is_empty() && deleted.is_empty() {
+            continue;
+        }
+        let is_collapsed = !filtering && ft.collapsed.contains(&section.label);
 
         rows.push(FileRow::SectionHeader {
             label: section.label.clone(),
             url: section.url.clone(),
-            count: section.files.len(),
+            count: files.len(),
         });
 
         if !is_collapsed {
-            for (path, shared, synced, time, repo_path) in &section.files {
+            for (path, shared, synced, time, repo_path) in files {
                 rows.push(FileRow::File {
                     path: path.clone(),
                     shared: *shared,
@@ -292,17 +310,15 @@ pub fn build_rows(state: &DashboardState, ft: &FilesTabState) -> Vec<FileRow> {
             }
 
             // Deleted files footer
-            if let Some(deleted) = ft.deleted.get(&section.label) {
-                if !deleted.is_empty() {
-                    rows.push(FileRow::DeletedHeader {
-                        section: section.label.clone(),
-                        count: deleted.len(),
-                    });
-
-                    if ft.show_deleted.contains(&section.label) {
-                        for path in deleted {
-                            rows.push(FileRow::DeletedFile { path: path.clone() });
-                        }
+            if !deleted.is_empty() {
+                rows.push(FileRow::DeletedHeader {
+                    section: section.label.clone(),
+                    count: deleted.len(),
+                });
+
+                if filtering || ft.show_deleted.contains(&section.label) {
+                    for path in deleted {
+                        rows.push(FileRow::DeletedFile { path: path.clone() });
                     }
                 }
             }
@@ -338,17 +354,31 @@ pub fn build_overview_rows(state: &DashboardState) -> Vec<FileRow> {
 }
 
 /// Render the interactive Files tab with cursor, expand/collapse
-pub fn render(f: &mut Frame, area: Rect, state: &DashboardState, ft: &FilesTabState) {
+pub fn render(
+    f: &mut Frame,
+    area: Rect,
+    state: &DashboardState,
+    ft: &FilesTabState,
+    filter_editing: bool,
+) {
     let rows = build_rows(state, ft);
     let cursor = ft.cursor;
 
     let block = Block::default()
-        .title(" Files ")
+        .title(filter_title("Files", &ft.filter, filter_editing))
         .borders(Borders::ALL)
         .border_style(Style::default().fg(Color::Gray));
     let inner_area = block.inner(area);
     f.render_widget(block, area);
 
+    if rows.is_empty() && !ft.filter.is_empty() {
+        let msg = Paragraph::new(Span::styled(
+            format!("  No files match /{}", ft.filter),
+            Style::default().fg(Color::Gray),
+        ));
+        f.render_widget(msg, inner_area);
+        return;
+    }
     if rows.is_empty() {
         let msg = Paragraph::new(Span::styled(
             "  No sync state",
@@ -382,7 +412,7 @@ pub fn render(f: &mut Frame, area: Rect, state: &DashboardState, ft: &FilesTabSt
 
         match row {
             FileRow::SectionHeader { label, url, count } => {
-                let is_collapsed = ft.collapsed.contains(label.as_str());
+                let is_collapsed = ft.filter.is_empty() && ft.collapsed.contains(label.as_str());
                 let arrow = if is_collapsed { ">" } else { "v" };
 
                 let mut spans = vec![
@@ -440,8 +470,12 @@ pub fn render(f: &mut Frame, area: Rect, state: &DashboardState, ft: &FilesTabSt
                     ),
                     badge,
                     Span::styled(" ", Style::default().bg(bg)),
-                    Span::styled(path, Style::default().fg(Color::White).bg(bg)),
                 ];
+                spans.extend(highlight(
+                    path,
+                    &ft.filter,
+                    Style::default().fg(Color::White).bg(bg),
+                ));
                 if *shared {
                     spans.push(Span::styled(
                         " [shared]",
@@ -512,14 +546,17 @@ pub fn render(f: &mut Frame, area: Rect, state: &DashboardState, ft: &FilesTabSt
                 f.render_widget(Paragraph::new(line), row_area);
             }
             FileRow::DeletedFile { path } => {
-                let line = Line::from(vec![
-                    Span::styled("      ", Style::default().bg(bg)),
-                    Span::styled(path, Style::default().fg(Color::Red).bg(bg)),
-                    Span::styled(
-                        " ".repeat(inner_area.width as usize),
-                        Style::default().bg(bg),
-                    ),
-                ]);
+                let mut spans = vec![Span::styled("      ", Style::default().bg(bg))];
+                spans.extend(highlight(
+                    path,
+                    &ft.filter,
+                    Style::default().fg(Color::Red).bg(bg),
+                ));
+                spans.push(Span::styled(
+                    " ".repeat(inner_area.width as usize),
+                    Style::default().bg(bg),
+                ));
+                let line = Line::from(spans);
                 f.render_widget(Paragraph::new(line), row_area);
             }
             FileRow::DiffRow { line: diff_line } => {
@@ -24,6 +24,8 @@ pub fn render_bar(f: &mut Frame, area: Rect, active_tab: Tab) {
             spans.extend([
                 Span::styled("Enter", Style::default().fg(Color::Yellow).bold()),
                 Span::styled(" expand/uninstall ", Style::default().fg(Color::Gray)),
+                Span::styled("a", Style::default().fg(Color::Yellow).bold()),
+                Span::styled("pprove ", Style::default().fg(Color::Gray)),
             ]);
         }
         Tab::Machines => {
@@ -32,6 +34,8 @@ pub fn render_bar(f: &mut Frame, area: Rect, active_tab: Tab) {
                 Span::styled(" expand ", Style::default().fg(Color::Gray)),
                 Span::styled("p", Style::default().fg(Color::Yellow).bold()),
                 Span::styled(" profile ", Style::default().fg(Color::Gray)),
+                Span::styled("a", Style::default().fg(Color::Yellow).bold()),
+                Span::styled(" add machine ", Style::default().fg(Color::Gray)),
             ]);
         }
         Tab::Files => {
@@ -44,8 +48,34 @@ pub fn render_bar(f: &mut Frame, area: Rect, active_tab: Tab) {
                 Span::styled("estore ", Style::default().fg(Color::Gray)),
             ]);
         }
+        Tab::Conflicts => {
+            spans.extend([
+                Span::styled("L", Style::default().fg(Color::Yellow).bold()),
+                Span::styled(" keep local ", Style::default().fg(Color::Gray)),
+                Span::styled("T", Style::default().fg(Color::Yellow).bold()),
+                Span::styled(" take remote ", Style::default().fg(Color::Gray)),
+                Span::styled("m", Style::default().fg(Color::Yellow).bold()),
+                Span::styled("erge ", Style::default().fg(Color::Gray)),
+                Span::styled("v", Style::default().fg(Color::Yellow).bold()),
+                Span::styled(" reveal ", Style::default().fg(Color::Gray)),
+            ]);
+        }
+        Tab::Logs => {
+            spans.extend([
+                Span::styled("f", Style::default().fg(Color::Yellow).bold()),
+                Span::styled("ollow ", Style::default().fg(Color::Gray)),
+                Span::styled("e", Style::default().fg(Color::Yellow).bold()),
+                Span::styled(" last error ", Style::default().fg(Color::Gray)),
+            ]);
+        }
         _ => {}
     }
+    if matches!(active_tab, Tab::Files | Tab::Packages | Tab::Machines) {
+        spans.extend([
+            Span::styled("/", Style::default().fg(Color::Yellow).bold()),
+            Span::styled(" filter ", Style::default().fg(Color::Gray)),
+        ]);
+    }
 
     spans.extend([
         Span::styled("?", Style::default().fg(Color::Yellow).bold()),
@@ -56,7 +86,168 @@ pub fn render_bar(f: &mut Frame, area: Rect, active_tab: Tab) {
     f.render_widget(paragraph, area);
 }
 
-pub fn render_overlay(f: &mut Frame) {
+/// Which popup or mode has keyboard focus, and so which bindings are live
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum KeyContext {
+    Main,
+    ListEdit,
+    ProfilePicker,
+    Confirm,
+    ImportPicker,
+}
+
+impl KeyContext {
+    fn title(self) -> &'static str {
+        match self {
+            KeyContext::Main => " Keyboard Shortcuts ",
+            KeyContext::ListEdit => " Keys: List Editor ",
+            KeyContext::ProfilePicker => " Keys: Profile Picker ",
+            KeyContext::Confirm => " Keys: Confirm ",
+            KeyContext::ImportPicker => " Keys: Import Picker ",
+        }
+    }
+}
+
+pub struct KeyBinding {
+    /// `None` applies in every context
+    pub context: Option<KeyContext>,
+    /// Heading the binding is grouped under; empty for the context's general keys
+    pub section: &'static str,
+    pub keys: &'static str,
+    pub action: &'static str,
+}
+
+const fn bind(
+    context: Option<KeyContext>,
+    section: &'static str,
+    keys: &'static str,
+    action: &'static str,
+) -> KeyBinding {
+    KeyBinding {
+        context,
+        section,
+        keys,
+        action,
+    }
+}
+
+const MAIN: Option<KeyContext> = Some(KeyContext::Main);
+const LIST_EDIT: Option<KeyContext> = Some(KeyContext::ListEdit);
+const PROFILE_PICKER: Option<KeyContext> = Some(KeyContext::ProfilePicker);
+const CONFIRM: Option<KeyContext> = Some(KeyContext::Confirm);
+const IMPORT_PICKER: Option<KeyContext> = Some(KeyContext::ImportPicker);
+
+/// Every dashboard key binding, in display order. `handle_key` is the behavior;
+/// keep this table next to it in sync when adding keys.
+pub const KEY_BINDINGS: &[KeyBinding] = &[
+    bind(MAIN, "", "q / Esc", "Quit"),
+    bind(MAIN, "", "s", "Trigger sync"),
+    bind(MAIN, "", "d", "Start/stop daemon"),
+    bind(MAIN, "", "P", "Pause/resume daemon syncs"),
+    bind(MAIN, "", "r", "Refresh data"),
+    bind(MAIN, "", "l", "Recent messages"),
+    bind(MAIN, "", "Tab", "Next tab"),
+    bind(MAIN, "", "1-7", "Switch tab"),
+    bind(MAIN, "", "j/k", "Scroll down/up"),
+    bind(MAIN, "", "Enter", "Expand/edit (context)"),
+    bind(MAIN, "", "v", "Show/mask secret values in diffs"),
+    bind(MAIN, "", "/", "Filter Files/Packages/Machines (Esc clears)"),
+    bind(
+        MAIN,
+        "Files tab:",
+        "Enter",
+        "Expand section/file/history/diff",
+    ),
+    bind(MAIN, "Files tab:", "t", "Toggle shared across profiles"),
+    bind(MAIN, "Files tab:", "R", "Restore file to selected commit"),
+    bind(MAIN, "Files tab:", "x", "Remove file from profile"),
+    bind(MAIN, "Files tab:", "i", "Import file from another profile"),
+    bind(MAIN, "Packages tab:", "Enter", "Expand/uninstall"),
+    bind(
+        MAIN,
+        "Packages tab:",
+        "i",
+        "Install packages from other machines",
+    ),
+    bind(
+        MAIN,
+        "Packages tab:",
+        "a",
+        "Approve installs queued by packages.approve_installs",
+    ),
+    bind(MAIN, "Machines tab:", "Enter", "Expand"),
+    bind(MAIN, "Machines tab:", "p", "Change this machine's profile"),
+    bind(
+        MAIN,
+        "Machines tab:",
+        "a",
+        "Show a QR code to add a machine",
+    ),
+    bind(MAIN, "Config tab:", "Enter", "Toggle, edit or open list"),
+    bind(MAIN, "Conflicts tab:", "L", "Keep local version"),
+    bind(MAIN, "Conflicts tab:", "T", "Take remote version"),
+    bind(MAIN, "Conflicts tab:", "m", "Open merge tool"),
+    bind(MAIN, "Conflicts tab:", "J/K", "Scroll diff"),
+    bind(MAIN, "Logs tab:", "f", "Follow new lines on/off"),
+    bind(
+        MAIN,
+        "Logs tab:",
+        "e",
+        "Jump to the last (then earlier) error",
+    ),
+    bind(LIST_EDIT, "", "j/k", "Move down/up"),
+    bind(LIST_EDIT, "", "a", "Add item"),
+    bind(LIST_EDIT, "", "d / Del", "Delete item"),
+    bind(LIST_EDIT, "", "t", "Toggle create (dotfiles)"),
+    bind(LIST_EDIT, "", "Esc", "Back to config"),
+    bind(LIST_EDIT, "While adding:", "Enter", "Save item"),
+    bind(LIST_EDIT, "While adding:", "Esc", "Cancel"),
+    bind(PROFILE_PICKER, "", "j/k", "Move down/up"),
+    bind(PROFILE_PICKER, "", "Enter", "Assign profile"),
+    bind(PROFILE_PICKER, "", "Esc", "Cancel"),
+    bind(CONFIRM, "", "y / Enter", "Confirm"),
+    bind(CONFIRM, "", "n / Esc", "Cancel"),
+    bind(IMPORT_PICKER, "", "j/k", "Move down/up"),
+    bind(IMPORT_PICKER, "", "Enter", "Import selected item"),
+    bind(IMPORT_PICKER, "", "Esc", "Close"),
+    bind(None, "", "?", "Toggle help"),
+    bind(None, "", "Ctrl+c", "Force quit"),
+];
+
+/// Bindings live in `context`, in display order
+pub fn bindings(context: KeyContext) -> impl Iterator<Item = &'static KeyBinding> {
+    KEY_BINDINGS
+        .iter()
+        .filter(move |b| b.context.is_none_or(|c| c == context))
+}
+
+fn help_lines(context: KeyContext) -> Vec<Line<'static>> {
+    let mut lines = vec![Line::from("")];
+    let mut section = "";
+    for binding in bindings(context) {
+        if binding.section != section {
+            section = binding.section;
+            lines.push(Line::from(""));
+            if !section.is_empty() {
+                lines.push(Line::from(Span::styled(
+                    format!("  {}", section),
+                    Style::default().fg(Color::Cyan).bold(),
+                )));
+            }
+        }
+        lines.push(Line::from(vec![
+            Span::styled(
+                format!("  {:<10}", binding.keys),
+                Style::default().fg(Color::Yellow).bold(),
+            ),
+            Span::raw(binding.action),
+        ]));
+    }
+    lines.push(Line::from(""));
+    lines
+}
+
+pub fn render_overlay(f: &mut Frame, context: KeyContext) {
     let area = f.area();
     if area.height < 10 || area.width < 30 {
         let hint = Paragraph::new(Span::styled(
@@ -68,106 +259,18 @@ pub fn render_overlay(f: &mut Frame) {
         return;
     }
 
+    let help_text = help_lines(context);
     let width = 50u16.min(area.width.saturating_sub(4));
-    let height = 29u16.min(area.height.saturating_sub(4));
+    let height = (help_text.len() as u16 + 2).min(area.height.saturating_sub(4));
     let x = (area.width.saturating_sub(width)) / 2;
     let y = (area.height.saturating_sub(height)) / 2;
     let popup_area = Rect::new(x, y, width, height);
 
     f.render_widget(Clear, popup_area);
 
-    let help_text = vec![
-        Line::from(""),
-        Line::from(vec![
-            Span::styled("  q / Esc   ", Style::default().fg(Color::Yellow).bold()),
-            Span::raw("Quit"),
-        ]),
-        Line::from(vec![
-            Span::styled("  s         ", Style::default().fg(Color::Yellow).bold()),
-            Span::raw("Trigger sync"),
-        ]),
-        Line::from(vec![
-            Span::styled("  d         ", Style::default().fg(Color::Yellow).bold()),
-            Span::raw("Start/stop daemon"),
-        ]),
-        Line::from(vec![
-            Span::styled("  r         ", Style::default().fg(Color::Yellow).bold()),
-            Span::raw("Refresh data"),
-        ]),
-        Line::from(vec![
-            Span::styled("  Tab       ", Style::default().fg(Color::Yellow).bold()),
-            Span::raw("Next tab"),
-        ]),
-        Line::from(vec![
-            Span::styled("  1-5       ", Style::default().fg(Color::Yellow).bold()),
-            Span::raw("Switch tab"),
-        ]),
-        Line::from(vec![
-            Span::styled("  j/k       ", Style::default().fg(Color::Yellow).bold()),
-            Span::raw("Scroll down/up"),
-        ]),
-        Line::from(vec![
-            Span::styled("  Enter     ", Style::default().fg(Color::Yellow).bold()),
-            Span::raw("Expand/edit (context)"),
-        ]),
-        Line::from(""),
-        Line::from(Span::styled(
-            "  Files tab:",
-            Style::default().fg(Color::Cyan).bold(),
-        )),
-        Line::from(vec![
-            Span::styled("  Enter     ", Style::default().fg(Color::Yellow).bold()),
-            Span::raw("Expand section/file/history/diff"),
-        ]),
-        Line::from(vec![
-            Span::styled("  t         ", Style::default().fg(Color::Yellow).bold()),
-            Span::raw("Toggle shared across profiles"),
-        ]),
-        Line::from(vec![
-            Span::styled("  R         ", Style::default().fg(Color::Yellow).bold()),
-            Span::raw("Restore file to selected commit"),
-        ]),
-        Line::from(""),
-        Line::from(Span::styled(
-            "  Config list sub-view:",
-            Style::default().fg(Color::Cyan).bold(),
-        )),
-        Line::from(vec![
-            Span::styled("  a         ", Style::default().fg(Color::Yellow).bold()),
-            Span::raw("Add item"),
-        ]),
-        Line::from(vec![
-            Span::styled("  d         ", Style::default().fg(Color::Yellow).bold()),
-            Span::raw("Delete item"),
-        ]),
-        Line::from(vec![
-            Span::styled("  t         ", Style::default().fg(Color::Yellow).bold()),
-            Span::raw("Toggle create (dotfiles)"),
-        ]),
-        Line::from(""),
-        Line::from(Span::styled(
-            "  Packages tab:",
-            Style::default().fg(Color::Cyan).bold(),
-        )),
-        Line::from(vec![
-            Span::styled("  Enter     ", Style::default().fg(Color::Yellow).bold()),
-            Span::raw("Expand/uninstall"),
-        ]),
-        Line::from(""),
-        Line::from(vec![
-            Span::styled("  ?         ", Style::default().fg(Color::Yellow).bold()),
-            Span::raw("Toggle help"),
-        ]),
-        Line::from(vec![
-            Span::styled("  Ctrl+c    ", Style::default().fg(Color::Yellow).bold()),
-            Span::raw("Force quit"),
-        ]),
-        Line::from(""),
-    ];
-
     let paragraph = Paragraph::new(help_text).block(
         Block::default()
-            .title(" Keyboard Shortcuts ")
+            .title(context.title())
             .borders(Borders::ALL)
             .border_style(Style::default().fg(Color::Cyan)),
     );
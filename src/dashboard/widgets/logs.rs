@@ -0,0 +1,112 @@
+use crate::daemon::logs::line_level;
+use crate::dashboard::LogsTabState;
+use ratatui::{prelude::*, widgets::*};
+
+pub fn render(f: &mut Frame, area: Rect, logs: &LogsTabState) {
+    let chunks = if logs.sync_output.is_empty() {
+        Layout::vertical([Constraint::Min(3)]).split(area)
+    } else {
+        let output_height = logs.sync_output.len().min(8) as u16 + 2;
+        Layout::vertical([Constraint::Min(5), Constraint::Length(output_height)]).split(area)
+    };
+
+    let title = if logs.follow {
+        " Daemon log (following) "
+    } else {
+        " Daemon log "
+    };
+    let block = Block::default()
+        .title(title)
+        .borders(Borders::ALL)
+        .border_style(Style::default().fg(Color::Gray));
+    let inner_area = block.inner(chunks[0]);
+    f.render_widget(block, chunks[0]);
+    logs.height.set(inner_area.height as usize);
+
+    if logs.lines.is_empty() {
+        let msg = Paragraph::new(Span::styled(
+            "  No daemon log yet",
+            Style::default().fg(Color::Gray),
+        ));
+        f.render_widget(msg, inner_area);
+    } else {
+        // Continuation lines (backtraces, multi-line errors) take their entry's color
+        let mut level = None;
+        let styles: Vec<Style> = logs
+            .lines
+            .iter()
+            .map(|line| {
+                if let Some(found) = line_level(line) {
+                    level = Some(found);
+                }
+                level_style(level)
+            })
+            .collect();
+        let first = logs.first_shown();
+        let lines: Vec<Line> = logs.lines[first..]
+            .iter()
+            .zip(&styles[first..])
+            .take(inner_area.height as usize)
+            .map(|(line, style)| Line::from(Span::styled(readable(line), *style)))
+            .collect();
+        f.render_widget(Paragraph::new(lines), inner_area);
+    }
+
+    if let Some(&area) = chunks.get(1) {
+        let block = Block::default()
+            .title(" Last sync from the dashboard ")
+            .borders(Borders::ALL)
+            .border_style(Style::default().fg(Color::Gray));
+        let inner_area = block.inner(area);
+        f.render_widget(block, area);
+        let start = logs
+            .sync_output
+            .len()
+            .saturating_sub(inner_area.height as usize);
+        let lines: Vec<Line> = logs.sync_output[start..]
+            .iter()
+            .map(|line| {
+                let color = if line.starts_with("Error") {
+                    Color::Red
+                } else {
+                    Color::White
+                };
+                Line::from(Span::styled(line.as_str(), Style::default().fg(color)))
+            })
+            .collect();
+        f.render_widget(Paragraph::new(lines), inner_area);
+    }
+}
+
+fn level_style(level: Option<log::Level>) -> Style {
+    match level {
+        Some(log::Level::Error) => Style::default().fg(Color::Red),
+        Some(log::Level::Warn) => Style::default().fg(Color::Yellow),
+        Some(log::Level::Info) => Style::default().fg(Color::White),
+        _ => Style::default().fg(Color::Gray),
+    }
+}
+
+/// JSON log lines as `time LEVEL message`, like the text ones; others as they are
+fn readable(line: &str) -> String {
+    let entry = line
+        .starts_with('{')
+        .then(|| serde_json::from_str::<serde_json::Value>(line).ok())
+        .flatten();
+    let field = |entry: &serde_json::Value, name: &str| {
+        entry
+            .get(name)
+            .and_then(|value| value.as_str())
+            .unwrap_or_default()
+            .to_string()
+    };
+    match entry {
+        Some(entry) => format!(
+            "{} {:<5} {}",
+            field(&entry, "time"),
+            field(&entry, "level"),
+            field(&entry, "message")
+        ),
+        None => line.to_string(),
+    }
+}
@@ -1,4 +1,4 @@
-use super::manager_label;
+use super::{filter_title, highlight, manager_label, matches_filter};
 use crate::cli::output::relative_time;
 use crate::dashboard::state::DashboardState;
 use ratatui::{prelude::*, widgets::*};
@@ -12,6 +12,8 @@ pub enum MachineRow {
         pkg_count: usize,
         last_sync: String,
         profile: Option<String>,
+        /// Runs a tether version this machine may not understand
+        incompatible: bool,
     },
     Detail {
         label: String,
@@ -19,8 +21,9 @@ pub enum MachineRow {
     },
 }
 
-/// Build the flat list of rows from dashboard state
-pub fn build_rows(state: &DashboardState, expanded: Option<&str>) -> Vec<MachineRow> {
+/// Build the flat list of rows from dashboard state. With a `filter`, only machines
+/// whose ID, hostname or profile matches are listed.
+pub fn build_rows(state: &DashboardState, expanded: Option<&str>, filter: &str) -> Vec<MachineRow> {
     let current_machine_id = state
         .sync_state
         .as_ref()
@@ -32,6 +35,22 @@ pub fn build_rows(state: &DashboardState, expanded: Option<&str>) -> Vec<Machine
         let is_current = m.machine_id == current_machine_id;
         let file_count = m.files.len();
         let pkg_count: usize = m.packages.values().map(|v| v.len()).sum();
+        let profile = m
+            .profile
+            .clone()
+            .or_else(|| {
+                state
+                    .config
+                    .as_ref()
+                    .map(|c| c.profile_name(&m.machine_id).to_string())
+            })
+            .unwrap_or_else(|| crate::config::DEFAULT_PROFILE.to_string());
+        if ![&m.machine_id, &m.hostname, &profile]
+            .iter()
+            .any(|text| matches_filter(text, filter))
+        {
+            continue;
+        }
 
         rows.push(MachineRow::Header {
             machine_id: m.machine_id.clone(),
@@ -39,17 +58,8 @@ pub fn build_rows(state: &DashboardState, expanded: Option<&str>) -> Vec<Machine
             file_count,
             pkg_count,
             last_sync: relative_time(m.last_sync),
-            profile: Some(
-                m.profile
-                    .clone()
-                    .or_else(|| {
-                        state
-                            .config
-                            .as_ref()
-                            .map(|c| c.profile_name(&m.machine_id).to_string())
-                    })
-                    .unwrap_or_else(|| crate::config::DEFAULT_PROFILE.to_string()),
-            ),
+            profile: Some(profile),
+            incompatible: !is_current && crate::sync::version_incompatible(&m.cli_version),
         });
 
         if expanded == Some(m.machine_id.as_str()) {
@@ -63,6 +73,18 @@ pub fn build_rows(state: &DashboardState, expanded: Option<&str>) -> Vec<Machine
                     value: m.os_version.clone(),
                 });
             }
+            if !m.arch.is_empty() {
+                rows.push(MachineRow::Detail {
+                    label: "Arch".to_string(),
+                    value: m.arch.clone(),
+                });
+            }
+            if !m.cli_version.is_empty() {
+                rows.push(MachineRow::Detail {
+                    label: "Tether".to_string(),
+                    value: m.cli_version.clone(),
+                });
+            }
             if !m.dotfiles.is_empty() {
                 for (i, dotfile) in m.dotfiles.iter().enumerate() {
                     rows.push(MachineRow::Detail {
@@ -102,16 +124,26 @@ pub fn render(
     state: &DashboardState,
     expanded: Option<&str>,
     cursor: usize,
+    filter: &str,
+    filter_editing: bool,
 ) {
-    let rows = build_rows(state, expanded);
+    let rows = build_rows(state, expanded, filter);
 
     let block = Block::default()
-        .title(" Machines ")
+        .title(filter_title("Machines", filter, filter_editing))
         .borders(Borders::ALL)
         .border_style(Style::default().fg(Color::Gray));
     let inner_area = block.inner(area);
     f.render_widget(block, area);
 
+    if rows.is_empty() && !filter.is_empty() {
+        let msg = Paragraph::new(Span::styled(
+            format!("  No machines match /{}", filter),
+            Style::default().fg(Color::Gray),
+        ));
+        f.render_widget(msg, inner_area);
+        return;
+    }
     if rows.is_empty() {
         let msg = Paragraph::new(Span::styled(
             "  No machines found",
@@ -145,7 +177,7 @@ pub fn render(
                 pkg_count,
                 last_sync,
                 profile,
-                ..
+                incompatible,
             } => {
                 let is_expanded = expanded == Some(machine_id.as_str());
                 let arrow = if is_expanded { "v" } else { ">" };
@@ -199,16 +231,23 @@ pub fn render(
                     Span::styled("", dim_style)
                 };
 
-                let line = Line::from(vec![
+                let mut spans = vec![
                     Span::styled(format!("  {} ", arrow), name_style),
                     Span::styled(marker, marker_style),
-                    Span::styled(machine_id, name_style),
+                ];
+                spans.extend(highlight(machine_id, filter, name_style));
+                spans.extend([
                     profile_span,
                     Span::styled(format!("  {}f {}p", file_count, pkg_count), dim_style),
                     Span::styled(format!("  {}", last_sync), dim_style),
+                    if *incompatible {
+                        Span::styled("  incompatible version", bg_style.fg(Color::Yellow))
+                    } else {
+                        Span::raw("")
+                    },
                     Span::styled(" ".repeat(inner_area.width as usize), bg_style),
                 ]);
-                f.render_widget(Paragraph::new(line), row_area);
+                f.render_widget(Paragraph::new(Line::from(spans)), row_area);
             }
             MachineRow::Detail { label, value } => {
                 let style = if is_selected {
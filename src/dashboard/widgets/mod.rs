@@ -1,23 +1,73 @@
 pub mod activity;
 pub mod config;
+pub mod conflicts;
 pub mod files;
 pub mod help;
-
+pub mod logs;
 pub mod machines;
 pub mod packages;
 pub mod status;
 
-/// Display label for a package manager key
-pub fn manager_label(key: &str) -> &str {
-    match key {
-        "brew_formulae" => "Brew formulae",
-        "brew_casks" => "Brew casks",
-        "brew_taps" => "Brew taps",
-        "npm" => "npm",
-        "pnpm" => "pnpm",
-        "bun" => "Bun",
-        "gem" => "Gem",
-        "uv" => "uv",
-        _ => key,
+pub use crate::packages::manager_label;
+
+use ratatui::prelude::*;
+
+/// Whether `text` contains the `/` filter, ignoring case; everything matches no filter
+pub fn matches_filter(text: &str, filter: &str) -> bool {
+    filter.is_empty() || text.to_lowercase().contains(&filter.to_lowercase())
+}
+
+/// `text` in `style`, with the first match of `filter` highlighted
+pub fn highlight(text: &str, filter: &str, style: Style) -> Vec<Span<'static>> {
+    let needle = filter.to_lowercase();
+    let lower = text.to_lowercase();
+    // Lowercasing can change byte lengths outside ASCII; don't highlight then
+    let found = (!needle.is_empty() && lower.len() == text.len())
+        .then(|| lower.find(&needle))
+        .flatten()
+        .and_then(|start| {
+            let end = start + needle.len();
+            Some((text.get(..start)?, text.get(start..end)?, text.get(end..)?))
+        });
+    match found {
+        Some((before, matched, after)) => vec![
+            Span::styled(before.to_string(), style),
+            Span::styled(
+                matched.to_string(),
+                style.fg(Color::Black).bg(Color::Yellow),
+            ),
+            Span::styled(after.to_string(), style),
+        ],
+        None => vec![Span::styled(text.to_string(), style)],
+    }
+}
+
+/// A tab's block title, showing its filter while there is one
+pub fn filter_title(name: &str, filter: &str, editing: bool) -> String {
+    match (filter.is_empty(), editing) {
+        (true, false) => format!(" {} ", name),
+        (_, true) => format!(" {}  /{}_ ", name, filter),
+        (false, false) => format!(" {}  /{} ", name, filter),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_highlight() {
+        assert!(matches_filter(".zshrc", "ZSH"));
+        assert!(!matches_filter(".bashrc", "zsh"));
+        assert!(matches_filter(".bashrc", ""));
+
+        let spans = highlight("ripgrep", "GREP", Style::default());
+        let texts: Vec<&str> = spans.iter().map(|s| s.content.as_ref()).collect();
+        assert_eq!(texts, vec!["rip", "grep", ""]);
+        assert_eq!(spans[1].style.bg, Some(Color::Yellow));
+        assert_eq!(highlight("ripgrep", "", Style::default()).len(), 1);
+
+        assert_eq!(filter_title("Files", "", false), " Files ");
+        assert_eq!(filter_title("Files", "zsh", true), " Files  /zsh_ ");
     }
 }
@@ -1,4 +1,4 @@
-use super::manager_label;
+use super::{filter_title, highlight, manager_label, matches_filter};
 use crate::dashboard::state::DashboardState;
 use ratatui::{prelude::*, widgets::*};
 
@@ -15,8 +15,9 @@ pub enum PkgRow {
     },
 }
 
-/// Build the flat list of rows from machine state
-pub fn build_rows(state: &DashboardState, expanded: Option<&str>) -> Vec<PkgRow> {
+/// Build the flat list of rows from machine state. With a `filter`, only matching
+/// packages are listed, under every manager that has some.
+pub fn build_rows(state: &DashboardState, expanded: Option<&str>, filter: &str) -> Vec<PkgRow> {
     let current_machine_id = state
         .sync_state
         .as_ref()
@@ -37,13 +38,20 @@ pub fn build_rows(state: &DashboardState, expanded: Option<&str>) -> Vec<PkgRow>
 
     let mut rows = Vec::new();
     for (key, packages) in &managers {
+        let mut sorted_pkgs: Vec<_> = packages
+            .iter()
+            .filter(|pkg| matches_filter(pkg, filter))
+            .cloned()
+            .collect();
+        if !filter.is_empty() && sorted_pkgs.is_empty() {
+            continue;
+        }
         rows.push(PkgRow::Header {
             manager_key: (*key).clone(),
             label: manager_label(key).to_string(),
-            count: packages.len(),
+            count: sorted_pkgs.len(),
         });
-        if expanded == Some(key.as_str()) {
-            let mut sorted_pkgs: Vec<_> = (*packages).clone();
+        if expanded == Some(key.as_str()) || !filter.is_empty() {
             sorted_pkgs.sort();
             for pkg in &sorted_pkgs {
                 rows.push(PkgRow::Package {
@@ -62,16 +70,26 @@ pub fn render(
     state: &DashboardState,
     expanded: Option<&str>,
     cursor: usize,
+    filter: &str,
+    filter_editing: bool,
 ) {
-    let rows = build_rows(state, expanded);
+    let rows = build_rows(state, expanded, filter);
 
     let block = Block::default()
-        .title(" Packages ")
+        .title(filter_title("Packages", filter, filter_editing))
         .borders(Borders::ALL)
         .border_style(Style::default().fg(Color::Gray));
     let inner_area = block.inner(area);
     f.render_widget(block, area);
 
+    if rows.is_empty() && !filter.is_empty() {
+        let msg = Paragraph::new(Span::styled(
+            format!("  No packages match /{}", filter),
+            Style::default().fg(Color::Gray),
+        ));
+        f.render_widget(msg, inner_area);
+        return;
+    }
     if rows.is_empty() {
         let msg = Paragraph::new(Span::styled(
             "  No package data for this machine",
@@ -104,7 +122,7 @@ pub fn render(
                 count,
                 ..
             } => {
-                let arrow = if expanded == Some(manager_key.as_str()) {
+                let arrow = if expanded == Some(manager_key.as_str()) || !filter.is_empty() {
                     "v"
                 } else {
                     ">"
@@ -144,10 +162,10 @@ pub fn render(
                 } else {
                     Style::default().fg(Color::White)
                 };
-                let line = Line::from(vec![
-                    Span::styled(format!("      {}", name), style),
-                    Span::styled(" ".repeat(inner_area.width as usize), style),
-                ]);
+                let mut spans = vec![Span::styled("      ", style)];
+                spans.extend(highlight(name, filter, style));
+                spans.push(Span::styled(" ".repeat(inner_area.width as usize), style));
+                let line = Line::from(spans);
                 f.render_widget(Paragraph::new(line), row_area);
             }
         }
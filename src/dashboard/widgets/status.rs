@@ -1,19 +1,29 @@
 use crate::cli::output::relative_time;
 use crate::dashboard::state::DashboardState;
 use crate::dashboard::DaemonOp;
+use crate::sync::git::TransferProgress;
 use ratatui::{prelude::*, widgets::*};
+use std::collections::VecDeque;
 
 pub enum FlashMessage<'a> {
     Error(&'a str),
     Success(&'a str),
 }
 
+/// A flash message kept after it fades from the status bar
+pub struct FlashLogEntry {
+    pub at: chrono::DateTime<chrono::Local>,
+    pub error: bool,
+    pub message: String,
+}
+
 #[allow(clippy::too_many_arguments)]
 pub fn render(
     f: &mut Frame,
     area: Rect,
     state: &DashboardState,
     syncing: bool,
+    sync_progress: Option<&TransferProgress>,
     daemon_op: DaemonOp,
     flash: Option<FlashMessage>,
     uninstalling: Option<&(String, String)>,
@@ -32,6 +42,12 @@ pub fn render(
             Style::default().fg(Color::White).bold(),
         ));
     }
+    if let Ok(Some(context)) = crate::config::current_context() {
+        spans.push(Span::styled(
+            format!(" [{}]", context),
+            Style::default().fg(Color::Magenta),
+        ));
+    }
 
     spans.push(Span::raw("  "));
 
@@ -56,12 +72,65 @@ pub fn render(
                     .map(|p| format!("daemon: running ({})", p))
                     .unwrap_or_else(|| "daemon: running".to_string());
                 spans.push(Span::styled(pid_info, Style::default().fg(Color::Green)));
+                if let Some(context) = state
+                    .daemon_status
+                    .as_ref()
+                    .filter(|s| s.contexts.len() == 1)
+                    .map(|s| &s.contexts[0])
+                {
+                    if context.syncing {
+                        spans.push(Span::styled(" syncing", Style::default().fg(Color::Yellow)));
+                    } else if context.is_paused() {
+                        spans.push(Span::styled(
+                            format!(" paused {}", context.pause_end()),
+                            Style::default().fg(Color::Yellow),
+                        ));
+                    } else if let Some(degraded) = context.degraded() {
+                        spans.push(Span::styled(
+                            format!(" degraded {}", degraded),
+                            Style::default().fg(Color::Red),
+                        ));
+                    }
+                }
+                // One marker per context when the daemon serves several
+                if let Some(status) = state
+                    .daemon_status
+                    .as_ref()
+                    .filter(|s| s.contexts.len() > 1)
+                {
+                    for context in &status.contexts {
+                        let color = if context.syncing {
+                            Color::Yellow
+                        } else if context.is_paused() {
+                            Color::Gray
+                        } else if context.last_error.is_some() {
+                            Color::Red
+                        } else {
+                            Color::Green
+                        };
+                        spans.push(Span::raw(" "));
+                        spans.push(Span::styled(
+                            format!("[{}]", context.label()),
+                            Style::default().fg(color),
+                        ));
+                    }
+                }
             } else {
                 spans.push(Span::styled(
                     "daemon: stopped",
                     Style::default().fg(Color::Red),
                 ));
             }
+            let degraded = state
+                .daemon_status
+                .as_ref()
+                .is_some_and(|s| s.contexts.iter().any(|c| c.degraded_since.is_some()));
+            if let Some(error) = state.last_error.as_ref().filter(|_| !degraded) {
+                spans.push(Span::styled(
+                    format!(" {} failed", error.phase),
+                    Style::default().fg(Color::Red).add_modifier(Modifier::BOLD),
+                ));
+            }
         }
     }
 
@@ -73,6 +142,10 @@ pub fn render(
             "syncing...",
             Style::default().fg(Color::Yellow),
         ));
+        if let Some(progress) = sync_progress {
+            spans.push(Span::raw(" "));
+            spans.extend(progress_gauge(progress));
+        }
     } else if let Some(ref sync_state) = state.sync_state {
         spans.push(Span::styled(
             format!("last sync: {}", relative_time(sync_state.last_sync)),
@@ -80,6 +153,28 @@ pub fn render(
         ));
     }
 
+    if state
+        .sync_state
+        .as_ref()
+        .and_then(|s| s.repo_visibility.as_ref())
+        .is_some_and(|v| v.public)
+    {
+        spans.push(Span::raw("  "));
+        spans.push(Span::styled(
+            " PUBLIC REPO ",
+            Style::default().fg(Color::White).bg(Color::Red).bold(),
+        ));
+    }
+
+    // Commits queued while offline
+    if state.pending_push > 0 {
+        spans.push(Span::raw("  "));
+        spans.push(Span::styled(
+            format!("{} pending push", state.pending_push),
+            Style::default().fg(Color::Yellow).bold(),
+        ));
+    }
+
     // Conflicts
     if state.conflicts.has_conflicts() {
         spans.push(Span::raw("  "));
@@ -130,3 +225,83 @@ pub fn render(
     );
     f.render_widget(paragraph, area);
 }
+
+const GAUGE_WIDTH: usize = 20;
+
+/// Inline gauge for a git transfer phase, e.g. `Receiving objects ━━━━━─────── 45%`
+fn progress_gauge(progress: &TransferProgress) -> Vec<Span<'static>> {
+    let filled = GAUGE_WIDTH * progress.percent as usize / 100;
+    let mut spans = vec![
+        Span::styled(
+            format!("{} ", progress.phase),
+            Style::default().fg(Color::Gray),
+        ),
+        Span::styled("━".repeat(filled), Style::default().fg(Color::Cyan)),
+        Span::styled(
+            "─".repeat(GAUGE_WIDTH - filled),
+            Style::default().fg(Color::DarkGray),
+        ),
+        Span::styled(
+            format!(" {}%", progress.percent),
+            Style::default().fg(Color::Gray),
+        ),
+    ];
+    if let Some(bytes) = &progress.bytes {
+        spans.push(Span::styled(
+            format!(" {}", bytes),
+            Style::default().fg(Color::DarkGray),
+        ));
+    }
+    spans
+}
+
+/// Overlay listing recent flash messages, newest first
+pub fn render_flash_log(f: &mut Frame, entries: &VecDeque<FlashLogEntry>) {
+    let area = f.area();
+    let width = 70u16.min(area.width.saturating_sub(4));
+    let height = 20u16.min(area.height.saturating_sub(4));
+    if width < 20 || height < 4 {
+        return;
+    }
+    let x = (area.width.saturating_sub(width)) / 2;
+    let y = (area.height.saturating_sub(height)) / 2;
+    let popup_area = Rect::new(x, y, width, height);
+
+    f.render_widget(Clear, popup_area);
+
+    let max_visible = height.saturating_sub(2) as usize;
+    let mut lines: Vec<Line> = entries
+        .iter()
+        .rev()
+        .take(max_visible)
+        .map(|entry| {
+            let (marker, color) = if entry.error {
+                ("✗ ", Color::Red)
+            } else {
+                ("✓ ", Color::Green)
+            };
+            Line::from(vec![
+                Span::styled(
+                    format!(" {} ", entry.at.format("%H:%M:%S")),
+                    Style::default().fg(Color::Gray),
+                ),
+                Span::styled(marker, Style::default().fg(color)),
+                Span::styled(entry.message.as_str(), Style::default().fg(color)),
+            ])
+        })
+        .collect();
+    if lines.is_empty() {
+        lines.push(Line::from(Span::styled(
+            " No messages yet",
+            Style::default().fg(Color::Gray),
+        )));
+    }
+
+    let paragraph = Paragraph::new(lines).block(
+        Block::default()
+            .title(" Messages (l to close) ")
+            .borders(Borders::ALL)
+            .border_style(Style::default().fg(Color::Cyan)),
+    );
+    f.render_widget(paragraph, popup_area);
+}
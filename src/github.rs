@@ -218,6 +218,31 @@ impl GitHubCli {
         Ok(collaborators)
     }
 
+    /// A user's public SSH keys (what github.com/<user>.keys serves), one per entry
+    pub async fn get_ssh_keys(user: &str) -> Result<Vec<String>> {
+        if user.is_empty() || !user.chars().all(|c| c.is_ascii_alphanumeric() || c == '-') {
+            return Err(anyhow::anyhow!("Invalid GitHub username: {}", user));
+        }
+        let endpoint = format!("users/{}/keys", user);
+        let output = Command::new("gh")
+            .args(["api", &endpoint, "--jq", "[.[].key]"])
+            .output()
+            .await
+            .context("Failed to get SSH keys")?;
+
+        if !output.status.success() {
+            let stderr = String::from_utf8_lossy(&output.stderr);
+            return Err(anyhow::anyhow!(
+                "Failed to get SSH keys for {}: {}",
+                user,
+                stderr
+            ));
+        }
+
+        let json_output = String::from_utf8(output.stdout)?;
+        serde_json::from_str(&json_output).context("Failed to parse SSH keys JSON")
+    }
+
     /// Parse owner/repo from a GitHub URL (SSH or HTTPS)
     pub fn parse_repo_url(url: &str) -> Option<(String, String)> {
         // SSH format: git@github.com:owner/repo.git
@@ -1,7 +1,11 @@
+pub mod api;
+pub mod bench;
 pub mod cli;
 pub mod config;
 pub mod daemon;
+#[cfg(feature = "dashboard")]
 pub mod dashboard;
+#[cfg(feature = "github")]
 pub mod github;
 pub mod packages;
 pub mod security;
@@ -4,7 +4,7 @@ use tether::cli::{Cli, Prompt};
 
 #[tokio::main]
 async fn main() -> Result<()> {
-    env_logger::init();
+    tether::daemon::logs::init();
     inquire::set_global_render_config(Prompt::theme());
 
     let cli = Cli::parse();
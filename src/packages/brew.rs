@@ -19,26 +19,38 @@ pub fn normalize_formula_name(name: &str) -> &str {
     name.rsplit('/').next().unwrap_or(name)
 }
 
+/// The tap a formula or cask comes from: the "user/repo" prefix of a qualified name,
+/// or homebrew/core (homebrew/cask for casks) for a bare one
+pub fn formula_tap(name: &str, cask: bool) -> &str {
+    match name.rsplit_once('/') {
+        Some((tap, _)) => tap,
+        None if cask => "homebrew/cask",
+        None => "homebrew/core",
+    }
+}
+
+/// The (kind, name) a Brewfile line declares, for `tap`, `brew` and `cask` lines
+pub fn brewfile_entry(line: &str) -> Option<(&str, &str)> {
+    let line = line.trim();
+    let kind = ["tap", "brew", "cask"].into_iter().find(|kind| {
+        line.strip_prefix(kind)
+            .is_some_and(|rest| rest.starts_with(' '))
+    })?;
+    // Extract the quoted package name
+    let name = line.split('"').nth(1)?;
+    Some((kind, name))
+}
+
 impl BrewfilePackages {
     /// Parse a Brewfile string into structured package lists
     pub fn parse(content: &str) -> Self {
         let mut packages = Self::default();
 
-        for line in content.lines() {
-            let line = line.trim();
-            if line.is_empty() || line.starts_with('#') {
-                continue;
-            }
-
-            // Extract the quoted package name
-            if let Some(name) = line.split('"').nth(1) {
-                if line.starts_with("tap ") {
-                    packages.taps.push(name.to_string());
-                } else if line.starts_with("brew ") {
-                    packages.formulae.push(name.to_string());
-                } else if line.starts_with("cask ") {
-                    packages.casks.push(name.to_string());
-                }
+        for (kind, name) in content.lines().filter_map(brewfile_entry) {
+            match kind {
+                "tap" => packages.taps.push(name.to_string()),
+                "brew" => packages.formulae.push(name.to_string()),
+                _ => packages.casks.push(name.to_string()),
             }
         }
 
@@ -68,6 +80,21 @@ impl BrewfilePackages {
     }
 }
 
+/// Packages from `brew list --versions` output (`name 1.2 1.3`), keeping the newest
+fn parse_list_versions(output: &str) -> Vec<PackageInfo> {
+    output
+        .lines()
+        .filter_map(|line| {
+            let mut parts = line.split_whitespace();
+            let name = parts.next()?;
+            Some(PackageInfo {
+                name: name.to_string(),
+                version: parts.last().map(str::to_string),
+            })
+        })
+        .collect()
+}
+
 pub struct BrewManager;
 
 impl BrewManager {
@@ -134,8 +161,7 @@ impl BrewManager {
 
     /// Get a temporary file path for Brewfile operations
     fn temp_brewfile_path() -> Result<PathBuf> {
-        let home = crate::home_dir()?;
-        Ok(home.join(".tether").join("Brewfile.tmp"))
+        Ok(crate::config::Config::config_dir()?.join("Brewfile.tmp"))
     }
 
     /// List installed casks
@@ -148,6 +174,12 @@ impl BrewManager {
             .collect())
     }
 
+    /// Installed casks with their versions
+    pub async fn list_cask_versions(&self) -> Result<Vec<PackageInfo>> {
+        let output = self.run_brew(&["list", "--cask", "--versions"]).await?;
+        Ok(parse_list_versions(&output))
+    }
+
     /// List installed taps
     pub async fn list_taps(&self) -> Result<Vec<String>> {
         let output = self.run_brew(&["tap"]).await?;
@@ -164,6 +196,15 @@ impl BrewManager {
         Ok(())
     }
 
+    /// Search casks matching `query`
+    pub async fn search_casks(&self, query: &str) -> Result<Vec<String>> {
+        let output = self.run_brew(&["search", "--cask", query]).await?;
+        Ok(super::manager::parse_search_names(&output)
+            .into_iter()
+            .map(|p| p.name)
+            .collect())
+    }
+
     /// Install a single cask.
     /// Returns Ok(true) if installed, Ok(false) if needs password (flagged for manual sync).
     pub async fn install_cask(&self, cask: &str, allow_interactive: bool) -> Result<bool> {
@@ -240,6 +281,13 @@ impl PackageManager for BrewManager {
         Ok(packages)
     }
 
+    async fn list_versions(&self) -> Result<Vec<PackageInfo>> {
+        let output = self
+            .run_brew(&["list", "--formula", "--installed-on-request", "--versions"])
+            .await?;
+        Ok(parse_list_versions(&output))
+    }
+
     async fn install(&self, package: &PackageInfo) -> Result<()> {
         self.run_brew(&["install", &package.name]).await?;
         Ok(())
@@ -342,44 +390,41 @@ impl PackageManager for BrewManager {
         Ok(())
     }
 
-    async fn remove_unlisted(&self, manifest_content: &str) -> Result<()> {
-        // Parse manifest to get desired packages
-        let desired: std::collections::HashSet<&str> = manifest_content
-            .lines()
-            .filter_map(|line| {
-                let line = line.trim();
-                // Parse Brewfile format: brew "package" or cask "package"
-                if line.starts_with("brew \"") || line.starts_with("cask \"") {
-                    line.split('"').nth(1)
-                } else {
-                    None
-                }
-            })
-            .collect();
-
-        if desired.is_empty() {
-            return Ok(());
+    async fn remove_unlisted(&self, manifest_content: &str) -> Result<Vec<String>> {
+        let desired = BrewfilePackages::parse(manifest_content);
+        if desired.formulae.is_empty() && desired.casks.is_empty() {
+            return Ok(Vec::new());
         }
+        // Tapped packages are listed by their short name once installed
+        let short_names = |names: &[String]| -> std::collections::HashSet<String> {
+            names
+                .iter()
+                .map(|name| normalize_formula_name(name).to_string())
+                .collect()
+        };
+        let (formulae, casks) = (short_names(&desired.formulae), short_names(&desired.casks));
 
-        // Get installed packages
-        let installed = self.list_installed().await?;
-
-        // Remove packages not in manifest
-        for pkg in installed {
-            if !desired.contains(pkg.name.as_str()) {
-                let output = Command::new("brew")
-                    .args(["uninstall", &pkg.name])
-                    .output()
-                    .await?;
-
-                if !output.status.success() {
-                    let stderr = String::from_utf8_lossy(&output.stderr);
-                    eprintln!("Warning: Failed to uninstall {}: {}", pkg.name, stderr);
-                }
+        let mut removed = Vec::new();
+        for pkg in self.list_installed().await? {
+            if formulae.contains(&pkg.name) {
+                continue;
+            }
+            match self.uninstall(&pkg.name).await {
+                Ok(()) => removed.push(pkg.name),
+                Err(e) => eprintln!("Warning: Failed to uninstall {}: {}", pkg.name, e),
+            }
+        }
+        for cask in self.list_installed_casks().await? {
+            if casks.contains(&cask) {
+                continue;
+            }
+            match self.run_brew(&["uninstall", "--cask", &cask]).await {
+                Ok(_) => removed.push(cask),
+                Err(e) => eprintln!("Warning: Failed to uninstall {}: {}", cask, e),
             }
         }
 
-        Ok(())
+        Ok(removed)
     }
 
     async fn update_all(&self) -> Result<()> {
@@ -416,6 +461,11 @@ impl PackageManager for BrewManager {
         Ok(())
     }
 
+    async fn search(&self, query: &str) -> Result<Vec<PackageInfo>> {
+        let output = self.run_brew(&["search", "--formula", query]).await?;
+        Ok(super::manager::parse_search_names(&output))
+    }
+
     async fn get_dependents(&self, package: &str) -> Result<Vec<String>> {
         let output = Command::new("brew")
             .args(["uses", "--installed", package])
@@ -439,6 +489,16 @@ impl PackageManager for BrewManager {
 mod tests {
     use super::*;
 
+    #[test]
+    fn test_parse_list_versions() {
+        let packages = parse_list_versions("git 2.44.0\npython@3.12 3.12.1 3.12.2\nbare\n");
+        assert_eq!(packages.len(), 3);
+        assert_eq!(packages[0].version.as_deref(), Some("2.44.0"));
+        assert_eq!(packages[1].name, "python@3.12");
+        assert_eq!(packages[1].version.as_deref(), Some("3.12.2"));
+        assert_eq!(packages[2].version, None);
+    }
+
     // Brewfile parsing tests
     #[test]
     fn test_parse_brewfile() {
@@ -521,6 +581,16 @@ brew "git"
     }
 
     // normalize_formula_name tests
+    #[test]
+    fn test_parse_search_names() {
+        let output = "==> Formulae\nripgrep\nripgrep-all\n\nIf you meant \"rg\" precisely:\n";
+        let names: Vec<_> = super::super::manager::parse_search_names(output)
+            .into_iter()
+            .map(|p| p.name)
+            .collect();
+        assert_eq!(names, vec!["ripgrep", "ripgrep-all"]);
+    }
+
     #[test]
     fn test_normalize_formula_name_simple() {
         assert_eq!(normalize_formula_name("git"), "git");
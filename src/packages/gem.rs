@@ -113,6 +113,16 @@ impl PackageManager for GemManager {
         Ok(())
     }
 
+    async fn search(&self, query: &str) -> Result<Vec<PackageInfo>> {
+        let output = self.run_gem(&["search", "--remote", query]).await?;
+        Ok(parse_gem_list(&output))
+    }
+
+    async fn list_versions(&self) -> Result<Vec<PackageInfo>> {
+        let output = self.run_gem(&["list", "--local"]).await?;
+        Ok(parse_gem_list(&output))
+    }
+
     async fn get_dependents(&self, package: &str) -> Result<Vec<String>> {
         // gem dependency -R shows reverse dependencies
         let output = Command::new("gem")
@@ -151,3 +161,35 @@ impl PackageManager for GemManager {
         Ok(dependents)
     }
 }
+
+/// Gems from `gem list`/`gem search` output: lines like `rails (7.1.3, 7.0.8)` or
+/// `json (default: 2.7.1)` after a `*** LOCAL GEMS ***` header. Keeps the newest version.
+fn parse_gem_list(output: &str) -> Vec<PackageInfo> {
+    output
+        .lines()
+        .filter_map(|line| {
+            let (name, versions) = line.trim().split_once(" (")?;
+            let newest = versions.trim_end_matches(')').split(", ").next()?;
+            Some(PackageInfo {
+                name: name.to_string(),
+                version: Some(newest.trim_start_matches("default: ").to_string()),
+            })
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_gem_list() {
+        let output = "\n*** LOCAL GEMS ***\n\nbundler (default: 2.5.6, 2.4.22)\nrails (7.1.3)\n";
+        let gems = parse_gem_list(output);
+        assert_eq!(gems.len(), 2);
+        assert_eq!(gems[0].name, "bundler");
+        assert_eq!(gems[0].version.as_deref(), Some("2.5.6"));
+        assert_eq!(gems[1].name, "rails");
+        assert_eq!(gems[1].version.as_deref(), Some("7.1.3"));
+    }
+}
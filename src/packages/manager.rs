@@ -14,6 +14,12 @@ pub trait PackageManager: Send + Sync {
     /// List all installed packages (legacy method, kept for compatibility)
     async fn list_installed(&self) -> Result<Vec<PackageInfo>>;
 
+    /// List installed packages with their exact versions, for lockfiles.
+    /// Default implementation is `list_installed` (already versioned for most managers)
+    async fn list_versions(&self) -> Result<Vec<PackageInfo>> {
+        self.list_installed().await
+    }
+
     /// Install a specific package (legacy method, kept for compatibility)
     async fn install(&self, package: &PackageInfo) -> Result<()>;
 
@@ -38,11 +44,7 @@ pub trait PackageManager: Send + Sync {
     /// Import packages from a manifest file using native tooling
     /// The manifest_content is the content that was previously exported
     async fn import_manifest(&self, manifest_content: &str) -> Result<()> {
-        let package_names: Vec<&str> = manifest_content
-            .lines()
-            .map(|line| line.trim())
-            .filter(|line| !line.is_empty())
-            .collect();
+        let package_names = manifest_packages(manifest_content);
 
         if package_names.is_empty() {
             return Ok(());
@@ -68,29 +70,28 @@ pub trait PackageManager: Send + Sync {
         Ok(())
     }
 
-    /// Remove packages not in the manifest
-    async fn remove_unlisted(&self, manifest_content: &str) -> Result<()> {
-        let desired: HashSet<&str> = manifest_content
-            .lines()
-            .map(|l| l.trim())
-            .filter(|l| !l.is_empty())
-            .collect();
+    /// Remove packages not in the manifest, returning the ones removed. An empty
+    /// manifest removes nothing.
+    async fn remove_unlisted(&self, manifest_content: &str) -> Result<Vec<String>> {
+        let desired: HashSet<&str> = manifest_packages(manifest_content).into_iter().collect();
 
         if desired.is_empty() {
-            return Ok(());
+            return Ok(Vec::new());
         }
 
         let installed = self.list_installed().await?;
+        let mut removed = Vec::new();
 
         for pkg in installed {
             if !desired.contains(pkg.name.as_str()) {
-                if let Err(e) = self.uninstall(&pkg.name).await {
-                    eprintln!("Warning: Failed to uninstall {}: {}", pkg.name, e);
+                match self.uninstall(&pkg.name).await {
+                    Ok(()) => removed.push(pkg.name),
+                    Err(e) => eprintln!("Warning: Failed to uninstall {}: {}", pkg.name, e),
                 }
             }
         }
 
-        Ok(())
+        Ok(removed)
     }
 
     /// Update all installed packages to latest versions
@@ -110,4 +111,35 @@ pub trait PackageManager: Send + Sync {
     async fn get_dependents(&self, _package: &str) -> Result<Vec<String>> {
         Ok(vec![])
     }
+
+    /// Search the manager's registry for packages matching `query`
+    /// Default implementation returns empty (no searchable registry)
+    async fn search(&self, _query: &str) -> Result<Vec<PackageInfo>> {
+        Ok(vec![])
+    }
+}
+
+/// The package a line of a one-per-line manifest names; `#` starts a comment
+pub fn manifest_entry(line: &str) -> Option<&str> {
+    let name = line.split('#').next().unwrap_or_default().trim();
+    (!name.is_empty()).then_some(name)
+}
+
+/// Package names in a one-per-line manifest, skipping comments and blank lines
+pub fn manifest_packages(content: &str) -> Vec<&str> {
+    content.lines().filter_map(manifest_entry).collect()
+}
+
+/// Package names from `brew search`-style output: one name per line, with `==>`
+/// section headers and any explanatory prose skipped
+pub(crate) fn parse_search_names(output: &str) -> Vec<PackageInfo> {
+    output
+        .lines()
+        .map(str::trim)
+        .filter(|l| !l.is_empty() && !l.starts_with("==>") && !l.contains(' '))
+        .map(|name| PackageInfo {
+            name: name.to_string(),
+            version: None,
+        })
+        .collect()
 }
@@ -6,10 +6,27 @@ pub mod npm;
 pub mod pnpm;
 pub mod uv;
 
-pub use brew::{normalize_formula_name, BrewManager, BrewfilePackages};
+pub use brew::{
+    brewfile_entry, formula_tap, normalize_formula_name, BrewManager, BrewfilePackages,
+};
 pub use bun::BunManager;
 pub use gem::GemManager;
-pub use manager::{PackageInfo, PackageManager};
+pub use manager::{manifest_entry, manifest_packages, PackageInfo, PackageManager};
 pub use npm::NpmManager;
 pub use pnpm::PnpmManager;
 pub use uv::UvManager;
+
+/// Display label for a package manager key
+pub fn manager_label(key: &str) -> &str {
+    match key {
+        "brew_formulae" => "Brew formulae",
+        "brew_casks" => "Brew casks",
+        "brew_taps" => "Brew taps",
+        "npm" => "npm",
+        "pnpm" => "pnpm",
+        "bun" => "Bun",
+        "gem" => "Gem",
+        "uv" => "uv",
+        _ => key,
+    }
+}
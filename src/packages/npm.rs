@@ -15,6 +15,12 @@ struct NpmPackage {
     version: String,
 }
 
+#[derive(Debug, Deserialize)]
+struct NpmSearchResult {
+    name: String,
+    version: Option<String>,
+}
+
 pub struct NpmManager;
 
 impl NpmManager {
@@ -99,6 +105,20 @@ impl PackageManager for NpmManager {
         Ok(())
     }
 
+    async fn search(&self, query: &str) -> Result<Vec<PackageInfo>> {
+        let output = self
+            .run_npm(&["search", "--json", "--searchlimit=10", query])
+            .await?;
+        let results: Vec<NpmSearchResult> = serde_json::from_str(&output)?;
+        Ok(results
+            .into_iter()
+            .map(|r| PackageInfo {
+                name: r.name,
+                version: r.version,
+            })
+            .collect())
+    }
+
     async fn uninstall(&self, package: &str) -> Result<()> {
         let output = Command::new("npm")
             .args(["uninstall", "-g", package])
@@ -65,7 +65,13 @@ impl PackageManager for UvManager {
     }
 
     async fn install(&self, package: &PackageInfo) -> Result<()> {
-        self.run_uv(&["tool", "install", &package.name]).await?;
+        let pkg_spec = if let Some(version) = &package.version {
+            format!("{}=={}", package.name, version)
+        } else {
+            package.name.clone()
+        };
+
+        self.run_uv(&["tool", "install", &pkg_spec]).await?;
         Ok(())
     }
 
@@ -0,0 +1,219 @@
+//! Session cache for the unlocked age identity. With `security.session_minutes` set,
+//! `tether identity unlock` starts a small background process that holds the identity
+//! in memory and hands it to later tether commands over a Unix socket until the session
+//! runs out, instead of writing it to a file that stays until `tether identity lock`.
+//!
+//! The socket is `identity-agent.sock` in the context's config dir, readable only by
+//! the user. Requests are one line (`get`, `remaining` or `lock`); the agent answers and
+//! closes the connection.
+
+use anyhow::Result;
+use std::path::PathBuf;
+use std::time::Duration;
+
+const SOCKET_FILENAME: &str = "identity-agent.sock";
+
+fn socket_path() -> Result<PathBuf> {
+    Ok(crate::config::Config::config_dir()?.join(SOCKET_FILENAME))
+}
+
+/// How long an unlocked identity stays available, from `security.session_minutes`.
+/// `None` when sessions are off and the identity is cached in a file instead.
+pub fn session_length() -> Option<Duration> {
+    let minutes = crate::config::Config::load()
+        .map(|c| c.security.session_minutes)
+        .unwrap_or(0);
+    (cfg!(unix) && minutes > 0).then(|| Duration::from_secs(minutes * 60))
+}
+
+#[cfg(unix)]
+fn request(command: &str) -> Option<String> {
+    request_at(&socket_path().ok()?, command)
+}
+
+#[cfg(unix)]
+fn request_at(path: &std::path::Path, command: &str) -> Option<String> {
+    use std::io::{Read, Write};
+
+    let mut stream = std::os::unix::net::UnixStream::connect(path).ok()?;
+    stream.set_read_timeout(Some(Duration::from_secs(1))).ok()?;
+    stream.write_all(format!("{}\n", command).as_bytes()).ok()?;
+    let mut response = String::new();
+    stream.read_to_string(&mut response).ok()?;
+    Some(response)
+}
+
+#[cfg(not(unix))]
+fn request(_command: &str) -> Option<String> {
+    None
+}
+
+/// The identity held by a running session, if there is one
+pub fn identity() -> Option<age::x25519::Identity> {
+    request("get")?.trim().parse().ok()
+}
+
+/// Time left in the running session
+pub fn remaining() -> Option<Duration> {
+    let secs = request("remaining")?.trim().parse().ok()?;
+    Some(Duration::from_secs(secs))
+}
+
+/// End the running session. Returns whether there was one.
+pub fn lock() -> bool {
+    request("lock").is_some()
+}
+
+/// Start a session holding `identity` for `length`, replacing any running one
+#[cfg(unix)]
+pub fn start(identity: &age::x25519::Identity, length: Duration) -> Result<()> {
+    use age::secrecy::ExposeSecret;
+    use std::io::Write;
+    use std::process::{Command, Stdio};
+
+    lock();
+    // The identity goes over stdin, not argv, where `ps` would show it
+    let mut child = Command::new(std::env::current_exe()?)
+        .args(["identity-agent", "--ttl", &length.as_secs().to_string()])
+        .stdin(Stdio::piped())
+        .stdout(Stdio::null())
+        .stderr(Stdio::null())
+        .spawn()?;
+    if let Some(mut stdin) = child.stdin.take() {
+        stdin.write_all(identity.to_string().expose_secret().as_bytes())?;
+    }
+    for _ in 0..100 {
+        if remaining().is_some() {
+            return Ok(());
+        }
+        std::thread::sleep(Duration::from_millis(20));
+    }
+    anyhow::bail!("The identity agent didn't start")
+}
+
+#[cfg(not(unix))]
+pub fn start(_identity: &age::x25519::Identity, _length: Duration) -> Result<()> {
+    anyhow::bail!("Identity sessions need Unix sockets")
+}
+
+/// The background half of [`start`]: reads the identity from stdin and answers
+/// requests on the socket for `ttl` seconds.
+#[cfg(unix)]
+pub fn serve(ttl: u64) -> Result<()> {
+    use std::io::Read;
+    use std::os::unix::fs::PermissionsExt;
+
+    let mut input = String::new();
+    std::io::stdin().read_to_string(&mut input)?;
+    let identity: age::x25519::Identity = input
+        .trim()
+        .parse()
+        .map_err(|e| anyhow::anyhow!("Invalid identity: {}", e))?;
+    drop(input);
+
+    let path = socket_path()?;
+    // A socket left by an agent that didn't exit cleanly blocks the bind
+    let _ = std::fs::remove_file(&path);
+    let listener = std::os::unix::net::UnixListener::bind(&path)?;
+    std::fs::set_permissions(&path, std::fs::Permissions::from_mode(0o600))?;
+
+    let ttl = Duration::from_secs(ttl);
+    // Backstop for a socket replaced under us, which can't be woken to expire
+    std::thread::spawn(move || {
+        std::thread::sleep(ttl + Duration::from_secs(5));
+        std::process::exit(0);
+    });
+    serve_on(listener, &path, &identity, ttl);
+    Ok(())
+}
+
+/// Answer requests on `listener` (bound at `path`) until `ttl` runs out or a `lock`
+/// request, then remove the socket
+#[cfg(unix)]
+fn serve_on(
+    listener: std::os::unix::net::UnixListener,
+    path: &std::path::Path,
+    identity: &age::x25519::Identity,
+    ttl: Duration,
+) {
+    use age::secrecy::ExposeSecret;
+    use std::io::{BufRead, BufReader, Write};
+    use std::time::Instant;
+
+    let deadline = Instant::now() + ttl;
+    // Wake the accept loop when the session runs out
+    let wake_path = path.to_path_buf();
+    std::thread::spawn(move || {
+        std::thread::sleep(ttl);
+        let _ = std::os::unix::net::UnixStream::connect(wake_path);
+    });
+
+    for stream in listener.incoming() {
+        if Instant::now() >= deadline {
+            break;
+        }
+        let Ok(mut stream) = stream else {
+            continue;
+        };
+        let _ = stream.set_read_timeout(Some(Duration::from_secs(1)));
+        let mut line = String::new();
+        if BufReader::new(&stream).read_line(&mut line).is_err() {
+            continue;
+        }
+        let response = match line.trim() {
+            "get" => identity.to_string().expose_secret().to_string(),
+            "remaining" => deadline
+                .saturating_duration_since(Instant::now())
+                .as_secs()
+                .to_string(),
+            "lock" => break,
+            _ => continue,
+        };
+        let _ = stream.write_all(response.as_bytes());
+    }
+    let _ = std::fs::remove_file(path);
+}
+
+#[cfg(not(unix))]
+pub fn serve(_ttl: u64) -> Result<()> {
+    anyhow::bail!("Identity sessions need Unix sockets")
+}
+
+#[cfg(all(test, unix))]
+mod tests {
+    use super::*;
+    use age::secrecy::ExposeSecret;
+    use std::os::unix::net::UnixListener;
+    use std::path::Path;
+
+    fn agent(path: &Path, ttl: Duration) -> (age::x25519::Identity, std::thread::JoinHandle<()>) {
+        let identity = age::x25519::Identity::generate();
+        let listener = UnixListener::bind(path).unwrap();
+        let served = identity.clone();
+        let path = path.to_path_buf();
+        let handle = std::thread::spawn(move || serve_on(listener, &path, &served, ttl));
+        (identity, handle)
+    }
+
+    #[test]
+    fn test_agent_lock_and_expiry() {
+        let temp = tempfile::TempDir::new().unwrap();
+        let path = temp.path().join(SOCKET_FILENAME);
+
+        let (identity, handle) = agent(&path, Duration::from_secs(60));
+        let held = request_at(&path, "get").unwrap();
+        assert_eq!(held, *identity.to_string().expose_secret());
+        let remaining: u64 = request_at(&path, "remaining").unwrap().parse().unwrap();
+        assert!(remaining > 50);
+        request_at(&path, "lock");
+        handle.join().unwrap();
+        assert!(!path.exists());
+        assert_eq!(request_at(&path, "get"), None);
+
+        let (_, handle) = agent(&path, Duration::from_secs(1));
+        assert!(request_at(&path, "get").is_some());
+        handle.join().unwrap();
+        assert!(!path.exists());
+        assert_eq!(request_at(&path, "get"), None);
+    }
+}
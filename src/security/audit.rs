@@ -0,0 +1,215 @@
+//! Append-only audit log of sensitive operations: syncs, restores, secret reads, key
+//! unlocks and team recipient changes. Each entry carries the hash of the one before
+//! it, so editing or deleting an entry breaks the chain from that point on.
+
+use anyhow::Result;
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use std::io::{Read, Write};
+use std::path::{Path, PathBuf};
+
+const LOG_FILENAME: &str = "audit.log";
+
+/// Where team repos keep members' exported logs
+pub const TEAM_AUDIT_DIR: &str = "audit";
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum AuditEvent {
+    Sync,
+    Restore,
+    SecretRead,
+    Unlock,
+    RecipientAdded,
+    RecipientRemoved,
+}
+
+impl std::fmt::Display for AuditEvent {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let name = match self {
+            Self::Sync => "sync",
+            Self::Restore => "restore",
+            Self::SecretRead => "secret-read",
+            Self::Unlock => "unlock",
+            Self::RecipientAdded => "recipient-added",
+            Self::RecipientRemoved => "recipient-removed",
+        };
+        f.pad(name)
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct AuditEntry {
+    pub seq: u64,
+    pub at: DateTime<Utc>,
+    pub machine: String,
+    pub event: AuditEvent,
+    pub detail: String,
+    /// Hash of the previous entry (empty for the first)
+    pub prev: String,
+    pub hash: String,
+}
+
+impl AuditEntry {
+    fn digest(&self) -> String {
+        crate::sha256_hex(
+            format!(
+                "{}\n{}\n{}\n{}\n{}\n{}",
+                self.seq,
+                self.at.to_rfc3339(),
+                self.machine,
+                self.event,
+                self.detail,
+                self.prev
+            )
+            .as_bytes(),
+        )
+    }
+}
+
+pub fn log_path() -> Result<PathBuf> {
+    Ok(crate::config::Config::config_dir()?.join(LOG_FILENAME))
+}
+
+fn parse(content: &str) -> Result<Vec<AuditEntry>> {
+    content
+        .lines()
+        .filter(|line| !line.trim().is_empty())
+        .enumerate()
+        .map(|(i, line)| {
+            serde_json::from_str(line)
+                .map_err(|e| anyhow::anyhow!("Audit log line {} is unreadable: {}", i + 1, e))
+        })
+        .collect()
+}
+
+/// All entries in the log at `path`, oldest first
+pub fn read(path: &Path) -> Result<Vec<AuditEntry>> {
+    if !path.exists() {
+        return Ok(Vec::new());
+    }
+    parse(&std::fs::read_to_string(path)?)
+}
+
+/// Index of the first entry that doesn't chain onto the one before it
+pub fn verify(entries: &[AuditEntry]) -> Option<usize> {
+    let mut prev = "";
+    for (i, entry) in entries.iter().enumerate() {
+        if entry.seq != i as u64 + 1 || entry.prev != prev || entry.hash != entry.digest() {
+            return Some(i);
+        }
+        prev = &entry.hash;
+    }
+    None
+}
+
+/// Chain a new entry onto the log at `path`, holding an exclusive lock so concurrent
+/// writers (CLI and daemon) can't fork the chain
+fn append(path: &Path, machine: &str, event: AuditEvent, detail: &str) -> Result<AuditEntry> {
+    use fs2::FileExt;
+
+    if let Some(parent) = path.parent() {
+        std::fs::create_dir_all(parent)?;
+    }
+    let mut options = std::fs::OpenOptions::new();
+    options.create(true).read(true).append(true);
+    #[cfg(unix)]
+    {
+        use std::os::unix::fs::OpenOptionsExt;
+        options.mode(0o600);
+    }
+    let mut file = options.open(path)?;
+    file.lock_exclusive()?;
+
+    let mut content = String::new();
+    file.read_to_string(&mut content)?;
+    let last = content
+        .lines()
+        .rev()
+        .find(|line| !line.trim().is_empty())
+        .map(serde_json::from_str::<AuditEntry>)
+        .transpose()?;
+
+    let mut entry = AuditEntry {
+        seq: last.as_ref().map_or(1, |e| e.seq + 1),
+        at: Utc::now(),
+        machine: machine.to_string(),
+        event,
+        detail: detail.to_string(),
+        prev: last.map(|e| e.hash).unwrap_or_default(),
+        hash: String::new(),
+    };
+    entry.hash = entry.digest();
+    let mut line = serde_json::to_string(&entry)?;
+    line.push('\n');
+    file.write_all(line.as_bytes())?;
+    file.unlock()?;
+    Ok(entry)
+}
+
+/// Record a sensitive operation. Never fails the operation itself: a log that can't
+/// be written is reported and skipped.
+pub fn record(event: AuditEvent, detail: &str) {
+    let result = crate::sync::SyncState::load().and_then(|state| {
+        let path = log_path()?;
+        append(&path, &state.machine_id, event, detail)
+    });
+    if let Err(e) = result {
+        log::warn!("Could not write audit log entry ({}): {}", event, e);
+    }
+}
+
+/// Publish this machine's log to a team repo, encrypted to the team's recipients, at
+/// `audit/<machine>.log.age`. A plaintext `.head` file next to it holds the newest
+/// entry's hash so an unchanged log isn't re-encrypted (and re-committed) every sync.
+/// Returns whether anything was written.
+pub fn export_to_team(team_dir: &Path, machine_id: &str) -> Result<bool> {
+    let path = log_path()?;
+    let entries = read(&path)?;
+    let Some(head) = entries.last().map(|e| e.hash.clone()) else {
+        return Ok(false);
+    };
+    let dir = team_dir.join(TEAM_AUDIT_DIR);
+    let head_path = dir.join(format!("{}.head", machine_id));
+    if std::fs::read_to_string(&head_path).is_ok_and(|h| h.trim() == head) {
+        return Ok(false);
+    }
+    let recipients = super::load_recipients(&team_dir.join("recipients"))?;
+    if recipients.is_empty() {
+        log::debug!("Team repo has no recipients, not exporting the audit log");
+        return Ok(false);
+    }
+    let encrypted = super::encrypt_to_recipients(&std::fs::read(&path)?, &recipients)?;
+    std::fs::create_dir_all(&dir)?;
+    std::fs::write(dir.join(format!("{}.log.age", machine_id)), encrypted)?;
+    std::fs::write(&head_path, format!("{}\n", head))?;
+    Ok(true)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::TempDir;
+
+    #[test]
+    fn test_chain_detects_tampering() {
+        let temp = TempDir::new().unwrap();
+        let path = temp.path().join(LOG_FILENAME);
+        append(&path, "mac", AuditEvent::Unlock, "passphrase").unwrap();
+        append(&path, "mac", AuditEvent::SecretRead, "GITHUB_TOKEN").unwrap();
+        append(&path, "mac", AuditEvent::Sync, "full").unwrap();
+
+        let entries = read(&path).unwrap();
+        assert_eq!(entries.len(), 3);
+        assert_eq!(entries[2].seq, 3);
+        assert_eq!(verify(&entries), None);
+
+        let mut edited = entries.clone();
+        edited[1].detail = "NPM_TOKEN".to_string();
+        assert_eq!(verify(&edited), Some(1));
+
+        let mut removed = entries.clone();
+        removed.remove(1);
+        assert_eq!(verify(&removed), Some(1));
+    }
+}
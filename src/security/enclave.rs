@@ -0,0 +1,107 @@
+//! Touch ID-protected key storage through age-plugin-se. With
+//! `security.require_biometrics`, the unlocked encryption key is kept wrapped to a
+//! Secure Enclave key that only decrypts after a fingerprint, so nothing on disk or in
+//! the login keychain hands it out silently.
+
+use super::recipients::AgeIdentity;
+use anyhow::{Context, Result};
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::process::Command;
+
+const PLUGIN_BINARY: &str = "age-plugin-se";
+const IDENTITY_FILENAME: &str = "identity.se";
+
+/// Whether age-plugin-se is installed
+pub fn plugin_available() -> bool {
+    which::which(PLUGIN_BINARY).is_ok()
+}
+
+fn identity_path() -> Result<PathBuf> {
+    Ok(crate::config::Config::config_dir()?.join(IDENTITY_FILENAME))
+}
+
+/// (identity, recipient) from an age-plugin-se key file, where the
+/// `AGE-PLUGIN-SE-1...` line follows a `# public key: age1se1...` comment
+fn parse_identity(content: &str) -> Option<(String, String)> {
+    let mut recipient = None;
+    for line in content.lines().map(str::trim) {
+        if let Some((_, r)) = line.split_once("public key:") {
+            recipient = Some(r.trim().to_string());
+        } else if line.starts_with("AGE-PLUGIN-SE-") {
+            return recipient.map(|r| (line.to_string(), r));
+        }
+    }
+    None
+}
+
+/// This machine's Secure Enclave identity, generating one that needs Touch ID on
+/// first use. The key file is only usable on this Mac's Secure Enclave.
+fn ensure_identity() -> Result<(String, String)> {
+    let path = identity_path()?;
+    if !path.exists() {
+        if !plugin_available() {
+            anyhow::bail!(
+                "security.require_biometrics needs {} (brew install {})",
+                PLUGIN_BINARY,
+                PLUGIN_BINARY
+            );
+        }
+        if let Some(parent) = path.parent() {
+            fs::create_dir_all(parent)?;
+        }
+        let status = Command::new(PLUGIN_BINARY)
+            .args(["keygen", "--access-control=any-biometry", "-o"])
+            .arg(&path)
+            .status()
+            .with_context(|| format!("Failed to run {}", PLUGIN_BINARY))?;
+        if !status.success() {
+            anyhow::bail!("{} keygen failed", PLUGIN_BINARY);
+        }
+        super::write_owner_only(&path, &fs::read(&path)?)?;
+    }
+    parse_identity(&fs::read_to_string(&path)?)
+        .ok_or_else(|| anyhow::anyhow!("Invalid Secure Enclave identity in {}", path.display()))
+}
+
+/// Encrypt `key` to this machine's Secure Enclave identity and write it to `path`
+pub fn wrap_key(path: &Path, key: &[u8]) -> Result<()> {
+    let (_, recipient) = ensure_identity()?;
+    let encrypted = super::encrypt_to_recipients(key, &[super::validate_pubkey(&recipient)?])?;
+    if let Some(parent) = path.parent() {
+        fs::create_dir_all(parent)?;
+    }
+    super::write_owner_only(path, &encrypted)
+}
+
+/// Decrypt a key wrapped by `wrap_key`; the plugin asks for Touch ID
+pub fn unwrap_key(path: &Path) -> Result<Vec<u8>> {
+    let (identity, _) = ensure_identity()?;
+    let identity = identity
+        .parse::<age::plugin::Identity>()
+        .map_err(|e| anyhow::anyhow!("Invalid Secure Enclave identity: {}", e))?;
+    super::decrypt_with_identity(&fs::read(path)?, &AgeIdentity::Plugin(identity))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_identity() {
+        let content = "\
+# created: 2026-10-18T09:00:00Z
+# access control: any biometry
+# public key: age1se1qexample
+AGE-PLUGIN-SE-1EXAMPLE
+";
+        assert_eq!(
+            parse_identity(content),
+            Some((
+                "AGE-PLUGIN-SE-1EXAMPLE".to_string(),
+                "age1se1qexample".to_string()
+            ))
+        );
+        assert_eq!(parse_identity("AGE-PLUGIN-SE-1EXAMPLE\n"), None);
+    }
+}
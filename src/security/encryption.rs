@@ -45,6 +45,44 @@ pub fn encrypt(plaintext: &[u8], key: &[u8]) -> Result<Vec<u8>> {
     Ok(result)
 }
 
+/// Encrypt data using AES-256-GCM with a nonce derived from the key and plaintext
+/// (synthetic IV). Identical plaintext always produces identical ciphertext, so
+/// unchanged content yields unchanged blobs in git. Only use where revealing
+/// "these two blobs are equal" is acceptable (e.g., content-addressed chunks).
+/// Output format matches `encrypt`, so `decrypt` reads it unchanged.
+pub fn encrypt_deterministic(plaintext: &[u8], key: &[u8]) -> Result<Vec<u8>> {
+    if key.len() != KEY_SIZE {
+        return Err(anyhow::anyhow!(
+            "Invalid key size: expected {} bytes, got {}",
+            KEY_SIZE,
+            key.len()
+        ));
+    }
+
+    let cipher = Aes256Gcm::new_from_slice(key).context("Failed to create cipher from key")?;
+
+    let digest = {
+        use sha2::{Digest, Sha256};
+        let mut hasher = Sha256::new();
+        hasher.update(b"tether-siv");
+        hasher.update(key);
+        hasher.update(plaintext);
+        hasher.finalize()
+    };
+    let mut nonce_bytes = [0u8; NONCE_SIZE];
+    nonce_bytes.copy_from_slice(&digest[..NONCE_SIZE]);
+
+    let ciphertext = cipher
+        .encrypt((&nonce_bytes).into(), plaintext)
+        .map_err(|e| anyhow::anyhow!("Encryption failed: {}", e))?;
+
+    let mut result = Vec::with_capacity(NONCE_SIZE + ciphertext.len());
+    result.extend_from_slice(&nonce_bytes);
+    result.extend_from_slice(&ciphertext);
+
+    Ok(result)
+}
+
 /// Decrypt data using AES-256-GCM
 /// Expects format: [nonce (12 bytes)][ciphertext + auth tag]
 pub fn decrypt(encrypted_data: &[u8], key: &[u8]) -> Result<Vec<u8>> {
@@ -125,6 +163,18 @@ mod tests {
         assert_eq!(decrypt(&encrypted2, &key).unwrap(), plaintext);
     }
 
+    #[test]
+    fn test_encrypt_deterministic_stable_and_decryptable() {
+        let key = generate_key();
+        let plaintext = b"chunk content";
+
+        let a = encrypt_deterministic(plaintext, &key).unwrap();
+        let b = encrypt_deterministic(plaintext, &key).unwrap();
+        assert_eq!(a, b);
+        assert_ne!(a, encrypt_deterministic(b"other chunk", &key).unwrap());
+        assert_eq!(decrypt(&a, &key).unwrap(), plaintext);
+    }
+
     #[test]
     fn test_wrong_key_fails() {
         let key1 = generate_key();
@@ -0,0 +1,232 @@
+//! Hardware-backed identities through age-plugin-yubikey. The private key never leaves
+//! the YubiKey: tether keeps only the plugin's identity stub (which slot on which key),
+//! and every decryption goes through the plugin, which asks for the PIN and a touch.
+
+use super::recipients::{AgeIdentity, AgeRecipient};
+use age::secrecy::SecretString;
+use anyhow::{Context, Result};
+use std::fs;
+use std::io::IsTerminal;
+use std::path::{Path, PathBuf};
+use std::process::{Command, Stdio};
+
+const PLUGIN_BINARY: &str = "age-plugin-yubikey";
+const IDENTITY_FILENAME: &str = "identity.yubikey";
+
+/// Sync repo directory holding the personal encryption key wrapped for each hardware
+/// identity, one file per recipient
+pub const WRAPPED_KEYS_DIR: &str = "hardware-keys";
+
+/// Relays age prompts (YubiKey PIN, "insert your YubiKey", SSH key passphrase) to the
+/// terminal. Without one they are refused, so a background sync fails instead of
+/// waiting for a touch.
+#[derive(Clone, Copy)]
+pub struct TerminalCallbacks;
+
+fn interactive() -> bool {
+    std::io::stdin().is_terminal()
+}
+
+impl age::Callbacks for TerminalCallbacks {
+    fn display_message(&self, message: &str) {
+        eprintln!("{}", message);
+    }
+
+    fn confirm(&self, message: &str, yes_string: &str, no_string: Option<&str>) -> Option<bool> {
+        if !interactive() {
+            return None;
+        }
+        let options = vec![yes_string, no_string.unwrap_or("No")];
+        crate::cli::Prompt::select(message, options, 0)
+            .ok()
+            .map(|choice| choice == 0)
+    }
+
+    fn request_public_string(&self, description: &str) -> Option<String> {
+        if !interactive() {
+            return None;
+        }
+        crate::cli::Prompt::input(description, None).ok()
+    }
+
+    fn request_passphrase(&self, description: &str) -> Option<SecretString> {
+        if !interactive() {
+            return None;
+        }
+        crate::cli::Prompt::password(description)
+            .ok()
+            .map(SecretString::from)
+    }
+}
+
+/// Whether age-plugin-yubikey is installed
+pub fn plugin_available() -> bool {
+    which::which(PLUGIN_BINARY).is_ok()
+}
+
+fn identity_path() -> Result<PathBuf> {
+    Ok(crate::config::Config::config_dir()?.join(IDENTITY_FILENAME))
+}
+
+/// Whether this machine has a hardware identity
+pub fn has_identity() -> bool {
+    identity_path().map(|p| p.exists()).unwrap_or(false)
+}
+
+/// (identity stub, recipient) pairs in age-plugin-yubikey output, where each
+/// `AGE-PLUGIN-YUBIKEY-1...` line follows a `# Recipient: age1yubikey1...` comment
+fn parse_identities(output: &str) -> Vec<(String, String)> {
+    let mut found = Vec::new();
+    let mut recipient = None;
+    for line in output.lines().map(str::trim) {
+        if let Some((_, r)) = line.split_once("Recipient:") {
+            recipient = Some(r.trim().to_string());
+        } else if line.starts_with("AGE-PLUGIN-YUBIKEY-") {
+            if let Some(r) = recipient.take() {
+                found.push((line.to_string(), r));
+            }
+        }
+    }
+    found
+}
+
+/// Run the plugin with the terminal attached (it asks for PIN and touch policies)
+/// and collect the identities it prints
+fn run_plugin(args: &[&str]) -> Result<Vec<(String, String)>> {
+    let output = Command::new(PLUGIN_BINARY)
+        .args(args)
+        .stdin(Stdio::inherit())
+        .stderr(Stdio::inherit())
+        .output()
+        .with_context(|| format!("{} is not installed", PLUGIN_BINARY))?;
+    if !output.status.success() {
+        anyhow::bail!("{} {} failed", PLUGIN_BINARY, args.join(" "));
+    }
+    Ok(parse_identities(&String::from_utf8_lossy(&output.stdout)))
+}
+
+/// Generate a new identity in a free slot of the connected YubiKey
+pub fn generate() -> Result<(String, String)> {
+    run_plugin(&["--generate"])?
+        .into_iter()
+        .next()
+        .ok_or_else(|| anyhow::anyhow!("{} printed no identity", PLUGIN_BINARY))
+}
+
+/// Identities already on the connected YubiKeys (e.g. one generated on another machine)
+pub fn existing() -> Result<Vec<(String, String)>> {
+    run_plugin(&["--identity"])
+}
+
+/// Save the identity stub and its recipient as this machine's identity
+pub fn store_identity(identity: &str, recipient: &str) -> Result<()> {
+    identity
+        .parse::<age::plugin::Identity>()
+        .map_err(|e| anyhow::anyhow!("Invalid plugin identity: {}", e))?;
+    let path = identity_path()?;
+    if let Some(parent) = path.parent() {
+        fs::create_dir_all(parent)?;
+    }
+    super::write_owner_only(&path, format!("{}\n", identity).as_bytes())?;
+    fs::write(path.with_file_name("identity.pub"), recipient)?;
+    Ok(())
+}
+
+/// This machine's hardware identity, if it has one
+pub fn load_identity() -> Result<Option<age::plugin::Identity>> {
+    let path = identity_path()?;
+    if !path.exists() {
+        return Ok(None);
+    }
+    let stub = fs::read_to_string(&path)?;
+    let identity = stub
+        .lines()
+        .map(str::trim)
+        .find(|l| !l.is_empty() && !l.starts_with('#'))
+        .ok_or_else(|| anyhow::anyhow!("Empty hardware identity in {}", path.display()))?
+        .parse()
+        .map_err(|e| anyhow::anyhow!("Invalid hardware identity: {}", e))?;
+    Ok(Some(identity))
+}
+
+/// Repo-relative path of the personal key wrapped for `recipient`
+pub fn wrapped_key_path(recipient: &str) -> String {
+    format!(
+        "{}/{}.age",
+        WRAPPED_KEYS_DIR,
+        &crate::sha256_hex(recipient.trim().as_bytes())[..16]
+    )
+}
+
+/// Encrypt the personal repo key to a hardware recipient, so that key alone unlocks it
+pub fn wrap_key(sync_path: &Path, key: &[u8], recipient: &str) -> Result<String> {
+    let recipient_key: AgeRecipient = super::validate_pubkey(recipient)?;
+    let encrypted = super::encrypt_to_recipients(key, &[recipient_key])?;
+    let rel = wrapped_key_path(recipient);
+    let path = sync_path.join(&rel);
+    if let Some(parent) = path.parent() {
+        fs::create_dir_all(parent)?;
+    }
+    fs::write(&path, encrypted)?;
+    Ok(rel)
+}
+
+/// Unwrap the personal repo key with this machine's hardware identity. `None` when
+/// there is no hardware identity or the key was never wrapped for it.
+pub fn unwrap_key(sync_path: &Path) -> Result<Option<Vec<u8>>> {
+    let Some(identity) = load_identity()? else {
+        return Ok(None);
+    };
+    let recipient = super::get_public_key()?;
+    let path = sync_path.join(wrapped_key_path(&recipient));
+    if !path.exists() {
+        return Ok(None);
+    }
+    let encrypted = fs::read(&path)?;
+    let key = super::decrypt_with_identity(&encrypted, &AgeIdentity::Plugin(identity))?;
+    Ok(Some(key))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_identities() {
+        let output = "\
+#       Serial: 12345678, Slot: 1
+#         Name: age identity 1a2b3c4d
+#      Created: Mon, 5 Oct 2026 10:00:00 +00:00
+#   PIN policy: Once   (A PIN is required once per session, if set)
+# Touch policy: Always (A physical touch is required for every decryption)
+#    Recipient: age1yubikey1qexample
+AGE-PLUGIN-YUBIKEY-1EXAMPLE
+
+#       Serial: 12345678, Slot: 2
+#    Recipient: age1yubikey1qsecond
+AGE-PLUGIN-YUBIKEY-1SECOND
+";
+        assert_eq!(
+            parse_identities(output),
+            vec![
+                (
+                    "AGE-PLUGIN-YUBIKEY-1EXAMPLE".to_string(),
+                    "age1yubikey1qexample".to_string()
+                ),
+                (
+                    "AGE-PLUGIN-YUBIKEY-1SECOND".to_string(),
+                    "age1yubikey1qsecond".to_string()
+                ),
+            ]
+        );
+        assert!(parse_identities("no identities\n").is_empty());
+    }
+
+    #[test]
+    fn test_wrapped_key_path_per_recipient() {
+        let a = wrapped_key_path("age1yubikey1qexample");
+        assert!(a.starts_with("hardware-keys/") && a.ends_with(".age"));
+        assert_eq!(a, wrapped_key_path("age1yubikey1qexample\n"));
+        assert_ne!(a, wrapped_key_path("age1yubikey1qsecond"));
+    }
+}
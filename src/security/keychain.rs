@@ -1,10 +1,21 @@
 use age::secrecy::SecretString;
 use anyhow::{Context, Result};
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
 use std::fs;
 use std::io::{Read, Write};
 use std::path::PathBuf;
+use std::process::{Command, Stdio};
+use std::sync::Mutex;
 
-const ENCRYPTED_KEY_FILENAME: &str = "encryption.key.age";
+pub const ENCRYPTED_KEY_FILENAME: &str = "encryption.key.age";
+
+/// Sync repo marker naming the current key, written by `tether key rotate`
+const KEY_VERSION_FILENAME: &str = "key_version.json";
+
+/// Login keychain (macOS) or Secret Service (Linux) service for keys when
+/// `security.login_keychain` is on; the account is the repo namespace
+const KEYCHAIN_SERVICE: &str = "tether-encryption-key";
 
 /// Get the path to the encrypted key in the sync repo
 fn encrypted_key_path() -> Result<PathBuf> {
@@ -12,35 +23,246 @@ fn encrypted_key_path() -> Result<PathBuf> {
     Ok(sync_path.join(ENCRYPTED_KEY_FILENAME))
 }
 
+/// Namespace for a sync repo's cached key, from its normalized URL, so a key cached
+/// for one repo is never used with another
+pub fn key_namespace(repo_url: &str) -> String {
+    let normalized = crate::sync::git::normalize_remote_url(repo_url.trim());
+    crate::sha256_hex(normalized.as_bytes())[..16].to_string()
+}
+
+fn repo_namespace() -> Result<String> {
+    let config = crate::config::Config::load()?;
+    Ok(key_namespace(&config.backend.url))
+}
+
 /// Get the path to the cached decrypted key (local only, not synced)
 fn cached_key_path() -> Result<PathBuf> {
-    let home = crate::home_dir()?;
-    Ok(home.join(".tether").join("key.cache"))
+    Ok(crate::config::Config::config_dir()?
+        .join("keys")
+        .join(format!("{}.cache", repo_namespace()?)))
+}
+
+/// The key wrapped to this Mac's Secure Enclave, with `security.require_biometrics`
+fn enclave_key_path() -> Result<PathBuf> {
+    Ok(crate::config::Config::config_dir()?
+        .join("keys")
+        .join(format!("{}.se.age", repo_namespace()?)))
+}
+
+/// The key unwrapped with Touch ID and a hash of the wrapped file it came from, kept
+/// so one command (or a running daemon) asks only once. `tether lock` or a new
+/// unlock changes the file, which invalidates it.
+static BIOMETRIC_KEY: Mutex<Option<(String, Vec<u8>)>> = Mutex::new(None);
+
+/// Hash of the wrapped key file, if there is one
+fn wrapped_hash(path: &std::path::Path) -> Option<String> {
+    fs::read(path).ok().map(|data| crate::sha256_hex(&data))
+}
+
+/// Where the key was cached before caches were per repo
+fn legacy_cached_key_path() -> Result<PathBuf> {
+    Ok(crate::config::Config::config_dir()?.join("key.cache"))
 }
 
 /// Store the encryption key encrypted with a passphrase
 /// The encrypted key is stored in the sync repo (syncs via git)
 pub fn store_encryption_key_with_passphrase(key: &[u8], passphrase: &str) -> Result<()> {
-    let encryptor = age::Encryptor::with_user_passphrase(SecretString::from(passphrase.to_owned()));
-
-    let mut encrypted = vec![];
-    let mut writer = encryptor
-        .wrap_output(&mut encrypted)
-        .map_err(|e| anyhow::anyhow!("Failed to create encryptor: {}", e))?;
-    writer.write_all(key)?;
-    writer
-        .finish()
-        .map_err(|e| anyhow::anyhow!("Failed to finish encryption: {}", e))?;
-
+    let encrypted = wrap_with_passphrase(key, passphrase)?;
     let path = encrypted_key_path()?;
     fs::write(&path, &encrypted).context("Failed to write encrypted key")?;
 
     Ok(())
 }
 
+/// Whether this OS has a login keyring tether can use: the macOS login keychain or
+/// the Linux Secret Service
+const LOGIN_KEYCHAIN_SUPPORTED: bool = cfg!(any(target_os = "macos", target_os = "linux"));
+
+/// What the login keyring is called on this OS, for messages
+pub fn login_keychain_name() -> &'static str {
+    if cfg!(target_os = "linux") {
+        "Secret Service keyring"
+    } else {
+        "login keychain"
+    }
+}
+
+/// Whether the key lives in the login keychain (Secret Service on Linux) instead of
+/// the file cache. `security.require_biometrics` takes precedence.
+pub fn login_keychain_enabled() -> bool {
+    LOGIN_KEYCHAIN_SUPPORTED
+        && crate::config::Config::load()
+            .map(|c| c.security.login_keychain && !c.security.require_biometrics)
+            .unwrap_or(false)
+}
+
+/// Whether the unlocked key is kept behind Touch ID
+pub fn biometrics_required() -> bool {
+    cfg!(target_os = "macos")
+        && crate::config::Config::load()
+            .map(|c| c.security.require_biometrics)
+            .unwrap_or(false)
+}
+
+/// Unwrap the Secure Enclave copy of the key (once per process). A key cached in a
+/// file or the login keychain before the switch is moved behind Touch ID first.
+fn load_biometric_key() -> Result<Vec<u8>> {
+    let path = enclave_key_path()?;
+    let hash = wrapped_hash(&path);
+    if let Some((held, key)) = BIOMETRIC_KEY.lock().unwrap().as_ref() {
+        if hash.as_ref() == Some(held) {
+            return Ok(key.clone());
+        }
+    }
+
+    let cached = cached_key_path()?;
+    let earlier = fs::read(&cached)
+        .ok()
+        .filter(|key| key.len() == crate::security::encryption::KEY_SIZE)
+        .or_else(load_from_login_keychain);
+    if let Some(key) = earlier {
+        cache_key(&key)?;
+        let _ = fs::remove_file(&cached);
+        remove_from_login_keychain();
+        log::info!("Moved cached encryption key behind Touch ID");
+        return Ok(key);
+    }
+
+    let Some(hash) = hash else {
+        anyhow::bail!("Encryption key not cached. Run 'tether unlock' to decrypt with passphrase.");
+    };
+    let key = super::enclave::unwrap_key(&path).context("Touch ID unlock failed")?;
+    *BIOMETRIC_KEY.lock().unwrap() = Some((hash, key.clone()));
+    Ok(key)
+}
+
+/// Store the key in the login keychain, readable without a prompt by this tether
+/// binary while the user is logged in. On Linux, the Secret Service keyring hands it
+/// to any process in the session while the keyring is unlocked.
+fn store_in_login_keychain(key: &[u8]) -> Result<()> {
+    let config = crate::config::Config::load()?;
+    let account = key_namespace(&config.backend.url);
+    if cfg!(target_os = "linux") {
+        let label = format!("tether ({})", config.backend.url);
+        super::secret_service::store(KEYCHAIN_SERVICE, &account, &label, &hex::encode(key))?;
+        if load_from_login_keychain().as_deref() != Some(key) {
+            anyhow::bail!("Failed to store key in the Secret Service keyring");
+        }
+        return Ok(());
+    }
+    let exe = std::env::current_exe()?;
+    let mut child = Command::new("security")
+        .arg("-i")
+        .stdin(Stdio::piped())
+        .stdout(Stdio::null())
+        .stderr(Stdio::null())
+        .spawn()
+        .context("Failed to run security")?;
+    // Through stdin so the key never shows up in a process listing
+    if let Some(mut stdin) = child.stdin.take() {
+        writeln!(
+            stdin,
+            "add-generic-password -U -s {} -a {} -l \"tether ({})\" -T \"{}\" -w {}",
+            KEYCHAIN_SERVICE,
+            account,
+            config.backend.url.replace('"', ""),
+            exe.display(),
+            hex::encode(key)
+        )?;
+    }
+    child.wait()?;
+
+    // `security -i` exits 0 even when a command fails, so read it back
+    if load_from_login_keychain().as_deref() != Some(key) {
+        anyhow::bail!("Failed to store key in the login keychain");
+    }
+    Ok(())
+}
+
+fn load_from_login_keychain() -> Option<Vec<u8>> {
+    let account = repo_namespace().ok()?;
+    if cfg!(target_os = "linux") {
+        let key = hex::decode(super::secret_service::lookup(KEYCHAIN_SERVICE, &account)?).ok()?;
+        return (key.len() == crate::security::encryption::KEY_SIZE).then_some(key);
+    }
+    let output = Command::new("security")
+        .args([
+            "find-generic-password",
+            "-s",
+            KEYCHAIN_SERVICE,
+            "-a",
+            &account,
+            "-w",
+        ])
+        .stderr(Stdio::null())
+        .output()
+        .ok()?;
+    if !output.status.success() {
+        return None;
+    }
+    let key = hex::decode(String::from_utf8_lossy(&output.stdout).trim()).ok()?;
+    (key.len() == crate::security::encryption::KEY_SIZE).then_some(key)
+}
+
+/// Whether the login keychain holds a key, without reading it
+fn in_login_keychain() -> bool {
+    let Ok(account) = repo_namespace() else {
+        return false;
+    };
+    if cfg!(target_os = "linux") {
+        return super::secret_service::lookup(KEYCHAIN_SERVICE, &account).is_some();
+    }
+    Command::new("security")
+        .args([
+            "find-generic-password",
+            "-s",
+            KEYCHAIN_SERVICE,
+            "-a",
+            &account,
+        ])
+        .stdout(Stdio::null())
+        .stderr(Stdio::null())
+        .status()
+        .is_ok_and(|status| status.success())
+}
+
+fn remove_from_login_keychain() {
+    let Ok(account) = repo_namespace() else {
+        return;
+    };
+    if cfg!(target_os = "linux") {
+        return super::secret_service::remove(KEYCHAIN_SERVICE, &account);
+    }
+    let _ = Command::new("security")
+        .args([
+            "delete-generic-password",
+            "-s",
+            KEYCHAIN_SERVICE,
+            "-a",
+            &account,
+        ])
+        .stdout(Stdio::null())
+        .stderr(Stdio::null())
+        .status();
+}
+
 /// Cache the decrypted key locally for the session
 /// This avoids prompting for passphrase on every operation
 fn cache_key(key: &[u8]) -> Result<()> {
+    // No fallback: a plaintext cache would defeat the point
+    if biometrics_required() {
+        let path = enclave_key_path()?;
+        super::enclave::wrap_key(&path, key)?;
+        *BIOMETRIC_KEY.lock().unwrap() = wrapped_hash(&path).map(|hash| (hash, key.to_vec()));
+        return Ok(());
+    }
+    if login_keychain_enabled() {
+        match store_in_login_keychain(key) {
+            Ok(()) => return Ok(()),
+            Err(e) => log::warn!("{}, caching key in a file instead", e),
+        }
+    }
+
     let path = cached_key_path()?;
     if let Some(parent) = path.parent() {
         #[cfg(unix)]
@@ -59,47 +281,178 @@ fn cache_key(key: &[u8]) -> Result<()> {
     Ok(())
 }
 
-/// Clear the cached key
+/// Clear the cached key, from the file cache, the login keychain and behind Touch ID
 pub fn clear_cached_key() -> Result<()> {
-    let path = cached_key_path()?;
-    if path.exists() {
-        fs::remove_file(&path)?;
+    if LOGIN_KEYCHAIN_SUPPORTED {
+        remove_from_login_keychain();
+    }
+    *BIOMETRIC_KEY.lock().unwrap() = None;
+    if let Ok(path) = enclave_key_path() {
+        let _ = fs::remove_file(path);
+    }
+    for path in [cached_key_path()?, legacy_cached_key_path()?] {
+        if path.exists() {
+            fs::remove_file(&path)?;
+        }
     }
     Ok(())
 }
 
+/// Adopt a key cached before caches were per repo, if it decrypts this repo's files
+fn migrate_legacy_cache() -> Option<Vec<u8>> {
+    let path = legacy_cached_key_path().ok()?;
+    let key = fs::read(&path)
+        .ok()
+        .filter(|key| key.len() == crate::security::encryption::KEY_SIZE)?;
+    if !key_matches_repo(&key) {
+        log::warn!("Ignoring cached key that doesn't decrypt the current sync repo");
+        return None;
+    }
+    cache_key(&key).ok()?;
+    let _ = fs::remove_file(&path);
+    Some(key)
+}
+
+/// Whether `key` decrypts an encrypted file in the sync repo (true while it has none)
+fn key_matches_repo(key: &[u8]) -> bool {
+    let Ok(sync_path) = crate::sync::SyncEngine::sync_path() else {
+        return false;
+    };
+    let sample = walkdir::WalkDir::new(&sync_path)
+        .into_iter()
+        .filter_entry(|e| e.file_name() != ".git")
+        .filter_map(|e| e.ok())
+        .find(|e| e.file_type().is_file() && e.path().extension() == Some("enc".as_ref()));
+    match sample {
+        Some(entry) => {
+            fs::read(entry.path()).is_ok_and(|data| crate::security::decrypt(&data, key).is_ok())
+        }
+        None => true,
+    }
+}
+
+/// The key currently in use, so machines still holding a rotated-out key notice
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct KeyVersion {
+    /// 1 for the key from `tether init`, bumped by each rotation
+    pub version: u32,
+    /// Fingerprint of the key (see `key_id`)
+    pub key_id: String,
+    pub rotated_by: String,
+    pub rotated_at: DateTime<Utc>,
+}
+
+/// Non-secret fingerprint of a key
+pub fn key_id(key: &[u8]) -> String {
+    let mut data = b"tether-key-id".to_vec();
+    data.extend_from_slice(key);
+    crate::sha256_hex(&data)[..16].to_string()
+}
+
+/// The sync repo's key-version marker; `None` until the key is first rotated
+pub fn key_version() -> Option<KeyVersion> {
+    let path = crate::sync::SyncEngine::sync_path()
+        .ok()?
+        .join(KEY_VERSION_FILENAME);
+    serde_json::from_str(&fs::read_to_string(path).ok()?).ok()
+}
+
+/// Fail (and drop the cached key) when the key was rotated on another machine
+fn check_key_current(key: &[u8]) -> Result<()> {
+    match key_version() {
+        Some(current) if current.key_id != key_id(key) => {
+            let _ = clear_cached_key();
+            anyhow::bail!(
+                "The encryption key was rotated by {} on {}. Run 'tether unlock' to unlock the new key.",
+                current.rotated_by,
+                current.rotated_at.format("%Y-%m-%d")
+            )
+        }
+        _ => Ok(()),
+    }
+}
+
 /// Get the encryption key, prompting for passphrase if needed
 /// First checks cache, then decrypts from sync repo
 pub fn get_encryption_key() -> Result<Vec<u8>> {
+    let key = load_cached_key()?;
+    check_key_current(&key)?;
+    Ok(key)
+}
+
+fn load_cached_key() -> Result<Vec<u8>> {
+    if biometrics_required() {
+        return load_biometric_key();
+    }
+
     // Try cached key first
     if let Ok(path) = cached_key_path() {
         if path.exists() {
             if let Ok(key) = fs::read(&path) {
                 if key.len() == crate::security::encryption::KEY_SIZE {
+                    // Move a file cache from before the switch into the keychain
+                    if login_keychain_enabled() && store_in_login_keychain(&key).is_ok() {
+                        log::info!("Moved cached encryption key into the login keychain");
+                        let _ = fs::remove_file(&path);
+                    }
                     return Ok(key);
                 }
             }
         }
     }
 
+    if login_keychain_enabled() {
+        if let Some(key) = load_from_login_keychain() {
+            return Ok(key);
+        }
+    }
+
+    if let Some(key) = migrate_legacy_cache() {
+        return Ok(key);
+    }
+
     // No cache - need to decrypt with passphrase
     Err(anyhow::anyhow!(
         "Encryption key not cached. Run 'tether unlock' to decrypt with passphrase."
     ))
 }
 
-/// Decrypt and cache the key using a passphrase
-pub fn unlock_with_passphrase(passphrase: &str) -> Result<Vec<u8>> {
+/// Decrypt the passphrase-protected key in the sync repo, without caching it
+pub fn decrypt_with_passphrase(passphrase: &str) -> Result<Vec<u8>> {
     let path = encrypted_key_path()?;
     if !path.exists() {
         return Err(anyhow::anyhow!(
             "No encrypted key found. Run 'tether init' first."
         ));
     }
+    decrypt_key_file(&path, passphrase)
+}
 
-    let encrypted = fs::read(&path).context("Failed to read encrypted key")?;
+/// Decrypt a passphrase-protected key file, such as the one in a checkout of the sync
+/// repo outside `~/.tether`
+pub fn decrypt_key_file(path: &std::path::Path, passphrase: &str) -> Result<Vec<u8>> {
+    let encrypted = fs::read(path).context("Failed to read encrypted key")?;
+    unwrap_with_passphrase(&encrypted, passphrase)
+}
 
-    let decryptor = age::Decryptor::new(&encrypted[..])
+/// Encrypt the key with a passphrase (age scrypt)
+pub fn wrap_with_passphrase(key: &[u8], passphrase: &str) -> Result<Vec<u8>> {
+    let encryptor = age::Encryptor::with_user_passphrase(SecretString::from(passphrase.to_owned()));
+
+    let mut encrypted = vec![];
+    let mut writer = encryptor
+        .wrap_output(&mut encrypted)
+        .map_err(|e| anyhow::anyhow!("Failed to create encryptor: {}", e))?;
+    writer.write_all(key)?;
+    writer
+        .finish()
+        .map_err(|e| anyhow::anyhow!("Failed to finish encryption: {}", e))?;
+    Ok(encrypted)
+}
+
+/// Decrypt a key encrypted with [`wrap_with_passphrase`]
+pub fn unwrap_with_passphrase(encrypted: &[u8], passphrase: &str) -> Result<Vec<u8>> {
+    let decryptor = age::Decryptor::new(encrypted)
         .map_err(|e| anyhow::anyhow!("Failed to create decryptor: {}", e))?;
 
     let mut key = vec![];
@@ -112,6 +465,12 @@ pub fn unlock_with_passphrase(passphrase: &str) -> Result<Vec<u8>> {
     if key.len() != crate::security::encryption::KEY_SIZE {
         return Err(anyhow::anyhow!("Decrypted key has wrong size"));
     }
+    Ok(key)
+}
+
+/// Decrypt and cache the key using a passphrase
+pub fn unlock_with_passphrase(passphrase: &str) -> Result<Vec<u8>> {
+    let key = decrypt_with_passphrase(passphrase)?;
 
     // Cache for future use
     cache_key(&key)?;
@@ -119,14 +478,98 @@ pub fn unlock_with_passphrase(passphrase: &str) -> Result<Vec<u8>> {
     Ok(key)
 }
 
+/// Check a passphrase against the key in the sync repo without caching anything
+pub fn verify_passphrase(passphrase: &str) -> Result<()> {
+    decrypt_with_passphrase(passphrase).map(|_| ())
+}
+
+/// Cache a key that was unwrapped some other way, such as from a pairing invite
+pub fn unlock_with_key(key: &[u8]) -> Result<()> {
+    if key.len() != crate::security::encryption::KEY_SIZE {
+        return Err(anyhow::anyhow!("Decrypted key has wrong size"));
+    }
+    cache_key(key)
+}
+
+/// Re-wrap the key in the sync repo with a new passphrase, once `old` decrypts it.
+/// The key itself stays the same, so nothing else needs re-encrypting.
+pub fn change_passphrase(old: &str, new: &str) -> Result<()> {
+    let path = encrypted_key_path()?;
+    if !path.exists() {
+        return Err(anyhow::anyhow!(
+            "No encrypted key found. Run 'tether init' first."
+        ));
+    }
+    let key = rewrap_key_file(&path, old, new, || get_encryption_key().ok())?;
+    // Refresh the cache (or login keychain entry) for the same key
+    cache_key(&key)
+}
+
+/// Re-wrap the passphrase-protected key file at `path` from `old` to `new`, returning
+/// the key. Refuses when this machine has a different key `unlocked`, which would
+/// otherwise be left unable to read the repo.
+fn rewrap_key_file(
+    path: &std::path::Path,
+    old: &str,
+    new: &str,
+    unlocked: impl FnOnce() -> Option<Vec<u8>>,
+) -> Result<Vec<u8>> {
+    let key = decrypt_key_file(path, old)?;
+    if unlocked().is_some_and(|cached| cached != key) {
+        anyhow::bail!(
+            "The unlocked key doesn't match the sync repo's key. Run 'tether lock' and 'tether unlock' first."
+        );
+    }
+    let encrypted = wrap_with_passphrase(&key, new)?;
+    crate::sync::atomic_write(path, &encrypted).context("Failed to write encrypted key")?;
+    Ok(key)
+}
+
+/// Decrypt and cache the key with this machine's YubiKey identity (the plugin asks for
+/// PIN and touch). `None` when there is no hardware identity or the key was never
+/// wrapped for it.
+pub fn unlock_with_hardware_key() -> Result<Option<Vec<u8>>> {
+    let sync_path = crate::sync::SyncEngine::sync_path()?;
+    let Some(key) = super::hardware::unwrap_key(&sync_path)? else {
+        return Ok(None);
+    };
+    if key.len() != crate::security::encryption::KEY_SIZE {
+        return Err(anyhow::anyhow!("Decrypted key has wrong size"));
+    }
+    cache_key(&key)?;
+    Ok(Some(key))
+}
+
+/// Switch this repo to `new_key`: wrap it with `passphrase` and bump the key-version
+/// marker so other machines ask to unlock again. Returns the new version. The key
+/// isn't cached; cache it with `unlock_with_key` once the rotation is pushed.
+pub fn install_rotated_key(new_key: &[u8], passphrase: &str, machine_id: &str) -> Result<u32> {
+    let version = key_version().map_or(1, |v| v.version) + 1;
+    store_encryption_key_with_passphrase(new_key, passphrase)?;
+    let marker = KeyVersion {
+        version,
+        key_id: key_id(new_key),
+        rotated_by: machine_id.to_string(),
+        rotated_at: Utc::now(),
+    };
+    let path = crate::sync::SyncEngine::sync_path()?.join(KEY_VERSION_FILENAME);
+    let content = serde_json::to_string_pretty(&marker)? + "\n";
+    crate::sync::atomic_write(&path, content.as_bytes())?;
+    Ok(version)
+}
+
 /// Check if an encrypted key exists in the sync repo
 pub fn has_encryption_key() -> bool {
     encrypted_key_path().map(|p| p.exists()).unwrap_or(false)
 }
 
-/// Check if the key is currently unlocked (cached)
+/// Check if the key is currently unlocked (cached, in the login keychain or behind
+/// Touch ID)
 pub fn is_unlocked() -> bool {
     cached_key_path().map(|p| p.exists()).unwrap_or(false)
+        || (biometrics_required() && enclave_key_path().is_ok_and(|p| p.exists()))
+        || (login_keychain_enabled() && in_login_keychain())
+        || migrate_legacy_cache().is_some()
 }
 
 /// Delete the encryption key (both encrypted and cached)
@@ -134,9 +577,7 @@ pub fn delete_encryption_key() -> Result<()> {
     if let Ok(path) = encrypted_key_path() {
         let _ = fs::remove_file(&path);
     }
-    if let Ok(path) = cached_key_path() {
-        let _ = fs::remove_file(&path);
-    }
+    let _ = clear_cached_key();
     Ok(())
 }
 
@@ -191,4 +632,45 @@ mod tests {
 
         assert!(result.is_err());
     }
+
+    #[test]
+    fn test_rewrap_key_file() {
+        let temp = tempfile::TempDir::new().unwrap();
+        let path = temp.path().join(ENCRYPTED_KEY_FILENAME);
+        let key = crate::security::encryption::generate_key();
+        fs::write(&path, wrap_with_passphrase(&key, "old-passphrase").unwrap()).unwrap();
+
+        // A wrong current passphrase or a different unlocked key changes nothing
+        assert!(rewrap_key_file(&path, "wrong", "new-passphrase", || None).is_err());
+        let other = crate::security::encryption::generate_key().to_vec();
+        assert!(rewrap_key_file(&path, "old-passphrase", "new", || Some(other)).is_err());
+        assert_eq!(decrypt_key_file(&path, "old-passphrase").unwrap(), key);
+
+        let unlocked = key.to_vec();
+        let rewrapped =
+            rewrap_key_file(&path, "old-passphrase", "new-passphrase", || Some(unlocked)).unwrap();
+        assert_eq!(rewrapped, key);
+        assert!(decrypt_key_file(&path, "old-passphrase").is_err());
+        assert_eq!(decrypt_key_file(&path, "new-passphrase").unwrap(), key);
+    }
+
+    #[test]
+    fn test_key_id_fingerprints_key() {
+        let key = crate::security::encryption::generate_key();
+        assert_eq!(key_id(&key), key_id(&key));
+        assert_eq!(key_id(&key).len(), 16);
+        assert_ne!(
+            key_id(&key),
+            key_id(&crate::security::encryption::generate_key())
+        );
+        assert!(!hex::encode(&key[..]).contains(&key_id(&key)));
+    }
+
+    #[test]
+    fn test_key_namespace_per_repo() {
+        let ns = key_namespace("git@github.com:user/dotfiles.git");
+        assert_eq!(ns, key_namespace("https://github.com/user/dotfiles"));
+        assert_ne!(ns, key_namespace("git@github.com:user/other-dotfiles.git"));
+        assert_eq!(ns.len(), 16);
+    }
 }
@@ -1,6 +1,13 @@
+pub mod agent;
+pub mod audit;
+pub mod enclave;
 pub mod encryption;
+pub mod hardware;
 pub mod keychain;
+pub mod pairing;
+pub mod providers;
 pub mod recipients;
+pub mod secret_service;
 pub mod secrets;
 
 use anyhow::Result;
@@ -58,14 +65,18 @@ pub fn write_owner_only(path: &Path, data: &[u8]) -> Result<()> {
     Ok(())
 }
 
-pub use encryption::{decrypt, encrypt, generate_key};
+pub use encryption::{decrypt, encrypt, encrypt_deterministic, generate_key};
 pub use keychain::{
-    clear_cached_key, get_encryption_key, has_encryption_key, is_unlocked,
-    store_encryption_key_with_passphrase, unlock_with_passphrase,
+    biometrics_required, change_passphrase, clear_cached_key, decrypt_key_file,
+    decrypt_with_passphrase, get_encryption_key, has_encryption_key, install_rotated_key,
+    is_unlocked, key_namespace, key_version, login_keychain_enabled, login_keychain_name,
+    store_encryption_key_with_passphrase, unlock_with_hardware_key, unlock_with_key,
+    unlock_with_passphrase, verify_passphrase,
 };
 pub use recipients::{
     clear_cached_identity, decrypt_with_identity, encrypt_to_recipients, generate_identity,
     get_public_key, get_public_key_from_identity, has_identity, is_identity_unlocked,
     load_identity, load_recipients, load_recipients_authorized, store_identity, validate_pubkey,
+    AgeIdentity, AgeRecipient,
 };
-pub use secrets::{scan_for_secrets, SecretFinding, SecretType};
+pub use secrets::{redact_lines, scan_for_secrets, ScanIgnores, SecretFinding, SecretType};
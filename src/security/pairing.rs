@@ -0,0 +1,265 @@
+//! Pairing invites: everything a new machine needs to join, in one line short enough
+//! for a QR code. `tether machines pair` (or the dashboard) makes one on a machine
+//! that's already set up, and `tether init --from-qr` reads it on the new one.
+//!
+//! The invite carries the repo URL, the profile to assign and the personal repo key
+//! encrypted with a one-time pairing code. The code is shown next to the QR but never
+//! put in it, so a photo or copy of the invite alone doesn't unlock anything.
+//!
+//! `tether init --from-qr` refuses an invite after [`VALID_FOR`]. The expiry is in
+//! plain text, but the key is wrapped with the code and the expiry together, so an
+//! invite whose expiry was pushed back no longer unwraps.
+
+use crate::config::Config;
+use anyhow::{Context, Result};
+use base64::{engine::general_purpose::URL_SAFE_NO_PAD, Engine};
+use chrono::{DateTime, Duration, Utc};
+
+const PREFIX: &str = "tether-pair:1:";
+
+/// How long an invite can be used after it's made
+pub const VALID_FOR: Duration = Duration::hours(1);
+
+/// Pairing code characters: no 0/O, 1/I/L, so it reads back unambiguously
+const CODE_ALPHABET: &[u8] = b"ABCDEFGHJKMNPQRSTUVWXYZ23456789";
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Invite {
+    pub repo: String,
+    /// Profile to assign to the new machine
+    pub profile: Option<String>,
+    /// The personal repo key encrypted with the pairing code, when the repo is encrypted
+    pub wrapped_key: Option<Vec<u8>>,
+    pub expires: DateTime<Utc>,
+}
+
+impl Invite {
+    /// An invite for `repo`, wrapping `key` with a new pairing code. Returns the code
+    /// too, when there's a key.
+    pub fn create(
+        repo: &str,
+        profile: Option<&str>,
+        key: Option<&[u8]>,
+    ) -> Result<(Self, Option<String>)> {
+        // Whole seconds, as the payload stores them
+        let expires =
+            DateTime::from_timestamp((Utc::now() + VALID_FOR).timestamp(), 0).unwrap_or_default();
+        let code = key.map(|_| new_code());
+        let wrapped_key = match (key, &code) {
+            (Some(key), Some(code)) => Some(super::keychain::wrap_with_passphrase(
+                key,
+                &wrapping_passphrase(code, expires),
+            )?),
+            _ => None,
+        };
+        let invite = Self {
+            repo: repo.to_string(),
+            profile: profile.map(String::from),
+            wrapped_key,
+            expires,
+        };
+        Ok((invite, code))
+    }
+
+    /// An invite to this machine's repo, for `profile` or else this machine's own
+    /// profile, wrapping the unlocked key when the repo is encrypted
+    pub fn from_config(
+        config: &Config,
+        machine_id: &str,
+        profile: Option<&str>,
+    ) -> Result<(Self, Option<String>)> {
+        if !config.has_personal_repo() {
+            anyhow::bail!("Pairing needs a personal sync repo; run 'tether init' first");
+        }
+        let profile = profile.unwrap_or_else(|| config.profile_name(machine_id));
+        if !config.profiles.is_empty() && !config.profiles.contains_key(profile) {
+            anyhow::bail!(
+                "Profile '{}' not found. Use 'tether machines profile list' to see profiles",
+                profile
+            );
+        }
+        let key = if config.security.encrypt_dotfiles {
+            Some(super::get_encryption_key()?)
+        } else {
+            None
+        };
+        Self::create(
+            &config.backend.url,
+            Some(profile).filter(|_| !config.profiles.is_empty()),
+            key.as_deref(),
+        )
+    }
+
+    /// `tether-pair:1:<expires>:<profile>:<wrapped key>:<repo>`, with the repo last
+    /// because it may itself contain colons
+    pub fn encode(&self) -> String {
+        format!(
+            "{}{}:{}:{}:{}",
+            PREFIX,
+            self.expires.timestamp(),
+            self.profile.as_deref().unwrap_or(""),
+            self.wrapped_key
+                .as_ref()
+                .map(|key| URL_SAFE_NO_PAD.encode(key))
+                .unwrap_or_default(),
+            self.repo
+        )
+    }
+
+    pub fn decode(payload: &str) -> Result<Self> {
+        // Pasted text may have been wrapped across lines
+        let payload: String = payload.split_whitespace().collect();
+        let rest = payload.strip_prefix(PREFIX).ok_or_else(|| {
+            anyhow::anyhow!("Not a tether pairing code (it should start with 'tether-pair:')")
+        })?;
+        let mut fields = rest.splitn(4, ':');
+        let (Some(expires), Some(profile), Some(key), Some(repo)) =
+            (fields.next(), fields.next(), fields.next(), fields.next())
+        else {
+            anyhow::bail!("Incomplete pairing code; copy all of it");
+        };
+        let expires = expires
+            .parse()
+            .ok()
+            .and_then(|secs| DateTime::from_timestamp(secs, 0))
+            .context("Invalid pairing code expiry")?;
+        let wrapped_key = match key {
+            "" => None,
+            key => Some(
+                URL_SAFE_NO_PAD
+                    .decode(key)
+                    .context("Damaged pairing code; copy all of it")?,
+            ),
+        };
+        if repo.is_empty() {
+            anyhow::bail!("Pairing code has no repository URL");
+        }
+        Ok(Self {
+            repo: repo.to_string(),
+            profile: Some(profile.to_string()).filter(|p| !p.is_empty()),
+            wrapped_key,
+            expires,
+        })
+    }
+
+    pub fn is_expired(&self) -> bool {
+        Utc::now() >= self.expires
+    }
+
+    /// Decrypt the key with the pairing code. `None` when the repo isn't encrypted.
+    /// Fails for a wrong code and for an invite whose expiry was edited.
+    pub fn unwrap_key(&self, code: &str) -> Result<Option<Vec<u8>>> {
+        let Some(wrapped) = &self.wrapped_key else {
+            return Ok(None);
+        };
+        super::keychain::unwrap_with_passphrase(wrapped, &wrapping_passphrase(code, self.expires))
+            .map(Some)
+            .map_err(|_| anyhow::anyhow!("Wrong pairing code, or the invite was altered"))
+    }
+}
+
+/// What the key is wrapped with: the code as typed, bound to the invite's expiry
+fn wrapping_passphrase(code: &str, expires: DateTime<Utc>) -> String {
+    format!("{}:{}", normalize_code(code), expires.timestamp())
+}
+
+/// A random `XXXX-XXXX-XXXX` code
+fn new_code() -> String {
+    use aes_gcm::aead::{rand_core::RngCore, OsRng};
+
+    let mut chars = Vec::new();
+    while chars.len() < 12 {
+        let mut byte = [0u8];
+        OsRng.fill_bytes(&mut byte);
+        // Reject the tail so every character is equally likely
+        let limit = 256 - 256 % CODE_ALPHABET.len();
+        if (byte[0] as usize) < limit {
+            chars.push(CODE_ALPHABET[byte[0] as usize % CODE_ALPHABET.len()] as char);
+        }
+    }
+    chars
+        .chunks(4)
+        .map(|group| group.iter().collect::<String>())
+        .collect::<Vec<_>>()
+        .join("-")
+}
+
+/// The code as typed, without dashes, spaces or case
+fn normalize_code(code: &str) -> String {
+    code.chars()
+        .filter(|c| c.is_ascii_alphanumeric())
+        .map(|c| c.to_ascii_uppercase())
+        .collect()
+}
+
+/// `payload` as a QR code, one string per terminal line. Light modules are drawn and
+/// dark ones left blank, which reads correctly on the usual dark terminal background.
+pub fn qr_lines(payload: &str) -> Result<Vec<String>> {
+    use qrcode::render::unicode::Dense1x2;
+
+    let code = qrcode::QrCode::with_error_correction_level(payload, qrcode::EcLevel::L)
+        .context("Pairing code too long for a QR code")?;
+    let image = code
+        .render::<Dense1x2>()
+        .dark_color(Dense1x2::Light)
+        .light_color(Dense1x2::Dark)
+        .quiet_zone(true)
+        .build();
+    Ok(image.lines().map(String::from).collect())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_invite_round_trip() {
+        let key = crate::security::encryption::generate_key();
+        let (invite, code) =
+            Invite::create("git@github.com:me/dotfiles.git", Some("laptop"), Some(&key)).unwrap();
+        let code = code.unwrap();
+        assert_eq!(code.len(), 14);
+        assert!(code
+            .chars()
+            .all(|c| c == '-' || CODE_ALPHABET.contains(&(c as u8))));
+
+        let payload = invite.encode();
+        assert!(payload.starts_with("tether-pair:1:"));
+        let decoded = Invite::decode(&format!("  {}\n", payload)).unwrap();
+        assert_eq!(decoded, invite);
+        assert!(!decoded.is_expired());
+
+        // Dashes and case don't matter when typing the code
+        let typed = code.replace('-', " ").to_lowercase();
+        assert_eq!(decoded.unwrap_key(&typed).unwrap(), Some(key.to_vec()));
+        assert!(decoded.unwrap_key("AAAA-AAAA-AAAA").is_err());
+
+        // Pushing the expiry back breaks the key, even with the right code
+        let extended = payload.replacen(
+            &invite.expires.timestamp().to_string(),
+            &(invite.expires + Duration::days(30))
+                .timestamp()
+                .to_string(),
+            1,
+        );
+        let extended = Invite::decode(&extended).unwrap();
+        assert!(!extended.is_expired());
+        assert!(extended.unwrap_key(&code).is_err());
+
+        assert!(qr_lines(&payload).unwrap().len() > 20);
+    }
+
+    #[test]
+    fn test_decode_unencrypted_and_invalid() {
+        let invite =
+            Invite::decode("tether-pair:1:1700000000::: https://example.com/d.git").unwrap();
+        assert_eq!(invite.repo, "https://example.com/d.git");
+        assert_eq!(invite.profile, None);
+        assert!(invite.is_expired());
+        assert_eq!(invite.unwrap_key("anything").unwrap(), None);
+
+        assert!(Invite::decode("https://example.com/d.git").is_err());
+        assert!(Invite::decode("tether-pair:1:1700000000:dev").is_err());
+        assert!(Invite::decode("tether-pair:1:soon:dev::https://example.com").is_err());
+    }
+}
@@ -0,0 +1,171 @@
+//! Secret providers: secrets fetched by reference at sync time instead of being stored
+//! in the sync repo. A reference is `<scheme>:<item>` with an optional `#<field>`,
+//! e.g. `bw:GitHub#password` (Bitwarden/Vaultwarden) or `tether:NPM_TOKEN` (the
+//! personal `tether secrets` store).
+
+use anyhow::{Context, Result};
+use std::process::{Command, Stdio};
+
+pub trait SecretProvider {
+    /// Scheme used in references (`bw` in `bw:item`)
+    fn scheme(&self) -> &'static str;
+    /// Fetch a secret. `field` picks one part of an item that has several.
+    fn fetch(&self, item: &str, field: Option<&str>) -> Result<String>;
+}
+
+#[derive(Debug, PartialEq, Eq)]
+pub struct SecretRef<'a> {
+    pub scheme: &'a str,
+    pub item: &'a str,
+    pub field: Option<&'a str>,
+}
+
+impl<'a> SecretRef<'a> {
+    pub fn parse(reference: &'a str) -> Option<Self> {
+        let (scheme, rest) = reference.split_once(':')?;
+        let (item, field) = match rest.rsplit_once('#') {
+            Some((item, field)) => (item, Some(field)),
+            None => (rest, None),
+        };
+        if scheme.is_empty() || item.is_empty() || field == Some("") {
+            return None;
+        }
+        Some(Self {
+            scheme,
+            item,
+            field,
+        })
+    }
+}
+
+/// Every provider tether knows, by scheme
+pub fn providers() -> Vec<Box<dyn SecretProvider>> {
+    vec![Box::new(Bitwarden), Box::new(TetherStore)]
+}
+
+/// Fetch the secret a reference points to
+pub fn resolve(reference: &str) -> Result<String> {
+    let parsed = SecretRef::parse(reference).ok_or_else(|| {
+        anyhow::anyhow!(
+            "Invalid secret reference '{}' (expected <provider>:<item>[#field])",
+            reference
+        )
+    })?;
+    let provider = providers()
+        .into_iter()
+        .find(|p| p.scheme() == parsed.scheme)
+        .ok_or_else(|| {
+            let schemes: Vec<_> = providers().iter().map(|p| p.scheme()).collect();
+            anyhow::anyhow!(
+                "Unknown secret provider '{}' (available: {})",
+                parsed.scheme,
+                schemes.join(", ")
+            )
+        })?;
+    provider.fetch(parsed.item, parsed.field)
+}
+
+/// Bitwarden through its `bw` CLI; works with Vaultwarden once `bw config server` points
+/// at it. The vault must already be unlocked (`BW_SESSION`), since tether never prompts
+/// for the master password.
+pub struct Bitwarden;
+
+/// Fields `bw get` returns directly; anything else is looked up among custom fields
+const BW_FIELDS: &[&str] = &["password", "username", "notes", "totp", "uri"];
+
+impl Bitwarden {
+    fn bw(args: &[&str]) -> Result<String> {
+        let output = Command::new("bw")
+            .arg("--nointeraction")
+            .args(args)
+            .stdin(Stdio::null())
+            .output()
+            .context("bw (Bitwarden CLI) is not installed")?;
+        if !output.status.success() {
+            let stderr = String::from_utf8_lossy(&output.stderr);
+            let reason = stderr
+                .lines()
+                .find(|l| !l.trim().is_empty())
+                .unwrap_or("unknown error");
+            anyhow::bail!("bw get failed: {}", reason.trim());
+        }
+        let stdout = String::from_utf8(output.stdout)?;
+        Ok(stdout.trim_end_matches(['\r', '\n']).to_string())
+    }
+}
+
+impl SecretProvider for Bitwarden {
+    fn scheme(&self) -> &'static str {
+        "bw"
+    }
+
+    fn fetch(&self, item: &str, field: Option<&str>) -> Result<String> {
+        let field = field.unwrap_or("password");
+        if BW_FIELDS.contains(&field) {
+            return Self::bw(&["get", field, item]);
+        }
+
+        let json: serde_json::Value = serde_json::from_str(&Self::bw(&["get", "item", item])?)?;
+        json["fields"]
+            .as_array()
+            .and_then(|fields| fields.iter().find(|f| f["name"] == field))
+            .and_then(|f| f["value"].as_str())
+            .map(str::to_string)
+            .ok_or_else(|| anyhow::anyhow!("Bitwarden item '{}' has no field '{}'", item, field))
+    }
+}
+
+/// The personal store managed by `tether secrets set`
+pub struct TetherStore;
+
+impl SecretProvider for TetherStore {
+    fn scheme(&self) -> &'static str {
+        "tether"
+    }
+
+    fn fetch(&self, item: &str, field: Option<&str>) -> Result<String> {
+        if field.is_some() {
+            anyhow::bail!("tether secrets have no fields");
+        }
+        let sync_path = crate::sync::SyncEngine::sync_path()?;
+        let key = super::get_encryption_key()?;
+        let value = crate::sync::personal_secrets::get(&sync_path, &key, item)?
+            .ok_or_else(|| anyhow::anyhow!("Secret '{}' not found", item))?;
+        Ok(String::from_utf8(value)?)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_reference() {
+        assert_eq!(
+            SecretRef::parse("bw:GitHub token#api"),
+            Some(SecretRef {
+                scheme: "bw",
+                item: "GitHub token",
+                field: Some("api"),
+            })
+        );
+        assert_eq!(
+            SecretRef::parse("tether:NPM_TOKEN"),
+            Some(SecretRef {
+                scheme: "tether",
+                item: "NPM_TOKEN",
+                field: None,
+            })
+        );
+        assert!(SecretRef::parse("NPM_TOKEN").is_none());
+        assert!(SecretRef::parse("bw:").is_none());
+        assert!(SecretRef::parse("bw:item#").is_none());
+    }
+
+    #[test]
+    fn test_resolve_unknown_provider() {
+        let err = resolve("1password:item").unwrap_err().to_string();
+        assert!(err.contains("Unknown secret provider '1password'"));
+        assert!(err.contains("bw, tether"));
+    }
+}
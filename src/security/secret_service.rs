@@ -0,0 +1,69 @@
+//! Linux counterpart of the macOS login keychain: the Secret Service API (GNOME
+//! Keyring, KWallet) through libsecret's `secret-tool`. Items are looked up by
+//! `service` and `account` attributes, like generic passwords in the keychain.
+
+use anyhow::{Context, Result};
+use std::io::Write;
+use std::process::{Command, Stdio};
+
+const SECRET_TOOL: &str = "secret-tool";
+
+/// Whether `secret-tool` is installed (libsecret-tools / libsecret)
+pub fn available() -> bool {
+    which::which(SECRET_TOOL).is_ok()
+}
+
+/// Store `secret` under `service`/`account`, replacing any earlier item
+pub fn store(service: &str, account: &str, label: &str, secret: &str) -> Result<()> {
+    let mut child = Command::new(SECRET_TOOL)
+        .args([
+            "store",
+            &format!("--label={}", label),
+            "service",
+            service,
+            "account",
+            account,
+        ])
+        .stdin(Stdio::piped())
+        .stdout(Stdio::null())
+        .stderr(Stdio::piped())
+        .spawn()
+        .with_context(|| format!("Failed to run {}", SECRET_TOOL))?;
+    // Through stdin so the secret never shows up in a process listing
+    if let Some(mut stdin) = child.stdin.take() {
+        stdin.write_all(secret.as_bytes())?;
+    }
+    let output = child.wait_with_output()?;
+    if !output.status.success() {
+        anyhow::bail!(
+            "Secret Service refused the key: {}",
+            String::from_utf8_lossy(&output.stderr).trim()
+        );
+    }
+    Ok(())
+}
+
+/// The secret under `service`/`account`, if the keyring has one (and is unlocked)
+pub fn lookup(service: &str, account: &str) -> Option<String> {
+    if !available() {
+        return None;
+    }
+    let output = Command::new(SECRET_TOOL)
+        .args(["lookup", "service", service, "account", account])
+        .stderr(Stdio::null())
+        .output()
+        .ok()?;
+    let secret = String::from_utf8_lossy(&output.stdout).trim().to_string();
+    (output.status.success() && !secret.is_empty()).then_some(secret)
+}
+
+pub fn remove(service: &str, account: &str) {
+    if !available() {
+        return;
+    }
+    let _ = Command::new(SECRET_TOOL)
+        .args(["clear", "service", service, "account", account])
+        .stdout(Stdio::null())
+        .stderr(Stdio::null())
+        .status();
+}
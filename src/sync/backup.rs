@@ -3,21 +3,61 @@ use chrono::{DateTime, Utc};
 use std::path::{Path, PathBuf};
 
 const MAX_BACKUPS: usize = 5;
+/// Conflict snapshots are pruned separately so routine sync backups can't push out
+/// the only copy of a file a resolution overwrote
+const MAX_CONFLICT_BACKUPS: usize = 20;
+const TIMESTAMP_FORMAT: &str = "%Y-%m-%dT%H-%M-%S";
+
+/// Prefix of backups taken right before a conflict resolution replaces a local file
+pub const CONFLICT_BACKUP_PREFIX: &str = "conflict-";
 
 /// Get the backups directory
 pub fn backups_dir() -> Result<PathBuf> {
-    let home = crate::home_dir()?;
-    Ok(home.join(".tether/backups"))
+    Ok(crate::config::Config::config_dir()?.join("backups"))
 }
 
 /// Create a timestamped backup directory and return its path
 pub fn create_backup_dir() -> Result<PathBuf> {
-    let timestamp = Utc::now().format("%Y-%m-%dT%H-%M-%S").to_string();
+    let timestamp = Utc::now().format(TIMESTAMP_FORMAT).to_string();
     let backup_dir = backups_dir()?.join(&timestamp);
     std::fs::create_dir_all(&backup_dir)?;
     Ok(backup_dir)
 }
 
+/// Create a `conflict-<timestamp>` backup directory for local files about to be
+/// replaced by a conflict resolution
+pub fn create_conflict_backup_dir() -> Result<PathBuf> {
+    let name = format!(
+        "{}{}",
+        CONFLICT_BACKUP_PREFIX,
+        Utc::now().format(TIMESTAMP_FORMAT)
+    );
+    let backup_dir = backups_dir()?.join(name);
+    std::fs::create_dir_all(&backup_dir)?;
+    Ok(backup_dir)
+}
+
+/// Snapshot a dotfile before a conflict resolution overwrites it.
+/// Returns the backup name, or `None` if there was no local file to keep.
+pub fn snapshot_before_resolution(relative_path: &str, source: &Path) -> Result<Option<String>> {
+    if !source.exists() {
+        return Ok(None);
+    }
+    let backup_dir = create_conflict_backup_dir()?;
+    backup_file(&backup_dir, "dotfiles", relative_path, source)?;
+    Ok(backup_dir
+        .file_name()
+        .map(|n| n.to_string_lossy().into_owned()))
+}
+
+/// Command that puts a backed-up dotfile back in place
+pub fn restore_hint(backup: &str, relative_path: &str) -> String {
+    format!(
+        "tether restore file --from {} dotfiles/{}",
+        backup, relative_path
+    )
+}
+
 /// Backup a single file before it gets overwritten
 /// Returns true if backup was created, false if skipped (file doesn't exist)
 pub fn backup_file(
@@ -52,9 +92,8 @@ pub fn list_backups() -> Result<Vec<String>> {
         .filter_map(|e| e.file_name().to_str().map(|s| s.to_string()))
         .collect();
 
-    // Sort newest first (reverse chronological)
-    backups.sort();
-    backups.reverse();
+    // Sort newest first (reverse chronological), conflict snapshots interleaved
+    backups.sort_by(|a, b| backup_timestamp_part(b).cmp(backup_timestamp_part(a)));
 
     Ok(backups)
 }
@@ -131,18 +170,21 @@ pub fn restore_file(timestamp: &str, category: &str, relative_path: &str) -> Res
     Ok(dest)
 }
 
-/// Prune old backups, keeping only the most recent MAX_BACKUPS
+/// Prune old backups, keeping only the most recent MAX_BACKUPS regular backups and
+/// MAX_CONFLICT_BACKUPS conflict snapshots
 pub fn prune_old_backups() -> Result<usize> {
-    let backups = list_backups()?;
-
-    if backups.len() <= MAX_BACKUPS {
-        return Ok(0);
-    }
-
-    let to_remove = &backups[MAX_BACKUPS..];
+    let (conflict, regular): (Vec<String>, Vec<String>) = list_backups()?
+        .into_iter()
+        .partition(|b| b.starts_with(CONFLICT_BACKUP_PREFIX));
+
+    let to_remove: Vec<&String> = regular
+        .iter()
+        .skip(MAX_BACKUPS)
+        .chain(conflict.iter().skip(MAX_CONFLICT_BACKUPS))
+        .collect();
     let dir = backups_dir()?;
 
-    for backup in to_remove {
+    for backup in &to_remove {
         let path = dir.join(backup);
         std::fs::remove_dir_all(&path)?;
     }
@@ -150,9 +192,13 @@ pub fn prune_old_backups() -> Result<usize> {
     Ok(to_remove.len())
 }
 
+fn backup_timestamp_part(name: &str) -> &str {
+    name.strip_prefix(CONFLICT_BACKUP_PREFIX).unwrap_or(name)
+}
+
 /// Parse a backup timestamp string into DateTime
 pub fn parse_backup_timestamp(timestamp: &str) -> Option<DateTime<Utc>> {
-    chrono::NaiveDateTime::parse_from_str(timestamp, "%Y-%m-%dT%H-%M-%S")
+    chrono::NaiveDateTime::parse_from_str(backup_timestamp_part(timestamp), TIMESTAMP_FORMAT)
         .ok()
         .map(|dt| dt.and_utc())
 }
@@ -218,6 +264,12 @@ mod tests {
         assert_eq!(dt.format("%Y-%m-%d").to_string(), "2024-01-15");
     }
 
+    #[test]
+    fn test_parse_backup_timestamp_conflict_prefix() {
+        let ts = parse_backup_timestamp("conflict-2024-01-15T10-30-45").unwrap();
+        assert_eq!(ts.format("%H:%M:%S").to_string(), "10:30:45");
+    }
+
     #[test]
     fn test_parse_backup_timestamp_invalid() {
         assert!(parse_backup_timestamp("invalid").is_none());
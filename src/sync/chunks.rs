@@ -0,0 +1,351 @@
+//! Content-defined chunk storage for large synced config files.
+//!
+//! AES-GCM with a random nonce turns every re-encryption into a brand new blob, so a
+//! one-line edit to a 2 MB file commits 2 MB. Large files under `configs/` are instead
+//! split at content-defined boundaries (gear rolling hash), each chunk is encrypted
+//! deterministically and stored once under `chunks/`, and the file's usual `.enc` path
+//! holds an encrypted manifest listing chunk IDs. Editing a file only adds the chunks
+//! around the edit.
+//!
+//! Whole-file blobs under `configs/` whose content happens to start with
+//! `tether-chunks ` are stored behind [`PLAIN_MAGIC`], so no file's content can pass
+//! for a manifest. Blobs anywhere else are never chunked and always read as they are.
+
+use anyhow::Result;
+use std::borrow::Cow;
+use std::collections::HashSet;
+use std::path::Path;
+
+/// First line of a decrypted manifest
+pub const MANIFEST_MAGIC: &[u8] = b"tether-chunks v1\n";
+
+/// First line of an escaped whole-file blob; the file's content follows
+pub const PLAIN_MAGIC: &[u8] = b"tether-chunks plain\n";
+
+/// Whole-file content starting with this is escaped with [`PLAIN_MAGIC`]
+const RESERVED_PREFIX: &[u8] = b"tether-chunks ";
+
+/// Repo directory holding encrypted chunks
+pub const CHUNKS_DIR: &str = "chunks";
+
+const MIN_CHUNK: usize = 2 * 1024;
+const MAX_CHUNK: usize = 64 * 1024;
+/// 13 high bits → ~8 KiB average chunk size. High bits of a gear hash depend on a
+/// wider byte window than low bits, giving better-distributed boundaries.
+const BOUNDARY_MASK: u64 = ((1 << 13) - 1) << 51;
+
+const fn gear_table() -> [u64; 256] {
+    let mut table = [0u64; 256];
+    let mut state: u64 = 0x2545_f491_4f6c_dd1d;
+    let mut i = 0;
+    while i < 256 {
+        // splitmix64: fixed seed so every machine picks identical boundaries
+        state = state.wrapping_add(0x9e37_79b9_7f4a_7c15);
+        let mut z = state;
+        z = (z ^ (z >> 30)).wrapping_mul(0xbf58_476d_1ce4_e5b9);
+        z = (z ^ (z >> 27)).wrapping_mul(0x94d0_49bb_1331_11eb);
+        table[i] = z ^ (z >> 31);
+        i += 1;
+    }
+    table
+}
+
+static GEAR: [u64; 256] = gear_table();
+
+/// Split data at content-defined boundaries. Boundaries depend only on nearby bytes,
+/// so an insertion shifts at most the chunks around it.
+pub fn split(data: &[u8]) -> Vec<&[u8]> {
+    let mut chunks = Vec::new();
+    let mut start = 0;
+    while start < data.len() {
+        let end = start + next_boundary(&data[start..]);
+        chunks.push(&data[start..end]);
+        start = end;
+    }
+    chunks
+}
+
+fn next_boundary(data: &[u8]) -> usize {
+    if data.len() <= MIN_CHUNK {
+        return data.len();
+    }
+    let limit = data.len().min(MAX_CHUNK);
+    let mut hash = 0u64;
+    for (i, &byte) in data.iter().enumerate().take(limit).skip(MIN_CHUNK) {
+        hash = (hash << 1).wrapping_add(GEAR[byte as usize]);
+        if hash & BOUNDARY_MASK == 0 {
+            return i + 1;
+        }
+    }
+    limit
+}
+
+/// Keyed chunk ID, so chunk names don't reveal plaintext hashes.
+pub fn chunk_id(key: &[u8], chunk: &[u8]) -> String {
+    let mut data = Vec::with_capacity(16 + key.len() + chunk.len());
+    data.extend_from_slice(b"tether-chunk-id");
+    data.extend_from_slice(key);
+    data.extend_from_slice(chunk);
+    crate::sha256_hex(&data)
+}
+
+/// Repo-relative path of a chunk: `chunks/ab/abcdef….enc`
+pub fn chunk_repo_path(id: &str) -> String {
+    format!("{}/{}/{}.enc", CHUNKS_DIR, &id[..2], id)
+}
+
+fn is_valid_id(id: &str) -> bool {
+    id.len() == 64 && id.chars().all(|c| c.is_ascii_hexdigit())
+}
+
+/// Whether decrypted content is a chunk manifest
+pub fn is_manifest(plaintext: &[u8]) -> bool {
+    plaintext.starts_with(MANIFEST_MAGIC)
+}
+
+/// Whether the repo file at `repo_path` (relative to the repo) can hold chunks: only
+/// config files under `configs/` are chunked
+pub fn may_be_chunked(repo_path: &str) -> bool {
+    repo_path.replace('\\', "/").starts_with("configs/")
+}
+
+/// Content to encrypt as a whole-file blob under `configs/`, escaped when it starts
+/// like a manifest
+pub fn encode_whole(content: &[u8]) -> Cow<'_, [u8]> {
+    if content.starts_with(RESERVED_PREFIX) {
+        Cow::Owned([PLAIN_MAGIC, content].concat())
+    } else {
+        Cow::Borrowed(content)
+    }
+}
+
+fn manifest_ids(plaintext: &[u8]) -> Result<Vec<String>> {
+    let body = std::str::from_utf8(&plaintext[MANIFEST_MAGIC.len()..])?;
+    let ids: Vec<String> = body.lines().map(|l| l.to_string()).collect();
+    if let Some(bad) = ids.iter().find(|id| !is_valid_id(id)) {
+        anyhow::bail!("Invalid chunk id in manifest: {}", bad);
+    }
+    Ok(ids)
+}
+
+/// Write `content` as chunks plus a manifest at `dest` (the file's usual `.enc` path).
+/// Chunks already present in the repo are left untouched.
+pub fn write_chunked(sync_path: &Path, dest: &Path, content: &[u8], key: &[u8]) -> Result<()> {
+    let mut manifest = MANIFEST_MAGIC.to_vec();
+    for chunk in split(content) {
+        let id = chunk_id(key, chunk);
+        let path = sync_path.join(chunk_repo_path(&id));
+        if !path.exists() {
+            let encrypted = crate::security::encrypt_deterministic(chunk, key)?;
+            crate::sync::atomic_write(&path, &encrypted)?;
+        }
+        manifest.extend_from_slice(id.as_bytes());
+        manifest.push(b'\n');
+    }
+    let encrypted = crate::security::encrypt_deterministic(&manifest, key)?;
+    crate::sync::atomic_write(dest, &encrypted)
+}
+
+/// Resolve the decrypted blob at `repo_path` to file content. Regular blobs pass
+/// through, escaped ones are unescaped, and manifests are reassembled from chunks
+/// fetched with `load` (repo path → encrypted bytes).
+pub fn reassemble(
+    repo_path: &str,
+    plaintext: Vec<u8>,
+    key: &[u8],
+    mut load: impl FnMut(&str) -> Result<Vec<u8>>,
+) -> Result<Vec<u8>> {
+    if !may_be_chunked(repo_path) {
+        return Ok(plaintext);
+    }
+    if let Some(content) = plaintext.strip_prefix(PLAIN_MAGIC) {
+        return Ok(content.to_vec());
+    }
+    if !is_manifest(&plaintext) {
+        return Ok(plaintext);
+    }
+    let mut out = Vec::new();
+    for id in manifest_ids(&plaintext)? {
+        let chunk = crate::security::decrypt(&load(&chunk_repo_path(&id))?, key)?;
+        if chunk_id(key, &chunk) != id {
+            anyhow::bail!("Chunk {} failed integrity check", id);
+        }
+        out.extend_from_slice(&chunk);
+    }
+    Ok(out)
+}
+
+/// `reassemble` the blob at `path` against the working tree of the sync repo
+pub fn read_from_repo(
+    sync_path: &Path,
+    path: &Path,
+    plaintext: Vec<u8>,
+    key: &[u8],
+) -> Result<Vec<u8>> {
+    let repo_path = path.strip_prefix(sync_path).unwrap_or(path);
+    reassemble(&repo_path.to_string_lossy(), plaintext, key, |rel| {
+        Ok(std::fs::read(sync_path.join(rel))?)
+    })
+}
+
+/// Delete chunks no manifest under `configs/` references. Returns the number removed.
+/// Bails without deleting anything if any manifest can't be read, so a decryption
+/// hiccup never destroys chunks that are still in use.
+pub fn gc_unreferenced(sync_path: &Path, key: &[u8]) -> Result<usize> {
+    let chunks_dir = sync_path.join(CHUNKS_DIR);
+    if !chunks_dir.exists() {
+        return Ok(0);
+    }
+
+    let mut referenced = HashSet::new();
+    for entry in walkdir::WalkDir::new(sync_path.join("configs"))
+        .follow_links(false)
+        .into_iter()
+        .filter_map(|e| e.ok())
+        .filter(|e| e.file_type().is_file())
+        .filter(|e| e.path().to_string_lossy().ends_with(".enc"))
+    {
+        let plaintext = crate::security::decrypt(&std::fs::read(entry.path())?, key)?;
+        if is_manifest(&plaintext) {
+            referenced.extend(manifest_ids(&plaintext)?);
+        }
+    }
+
+    let mut removed = 0;
+    for entry in walkdir::WalkDir::new(&chunks_dir)
+        .follow_links(false)
+        .into_iter()
+        .filter_map(|e| e.ok())
+        .filter(|e| e.file_type().is_file())
+    {
+        let id = entry
+            .path()
+            .file_name()
+            .and_then(|n| n.to_str())
+            .and_then(|n| n.strip_suffix(".enc"))
+            .unwrap_or_default();
+        if !referenced.contains(id) {
+            std::fs::remove_file(entry.path())?;
+            removed += 1;
+        }
+    }
+    Ok(removed)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::TempDir;
+
+    fn sample(len: usize, seed: usize) -> Vec<u8> {
+        crate::bench::synthetic_content(seed, len)
+    }
+
+    #[test]
+    fn test_split_covers_input_within_bounds() {
+        let data = sample(300 * 1024, 1);
+        let chunks = split(&data);
+        assert_eq!(chunks.concat(), data);
+        assert!(chunks.len() > 1);
+        for chunk in &chunks[..chunks.len() - 1] {
+            assert!(chunk.len() >= MIN_CHUNK && chunk.len() <= MAX_CHUNK);
+        }
+    }
+
+    #[test]
+    fn test_split_small_file_single_chunk() {
+        assert_eq!(split(b"tiny").len(), 1);
+        assert!(split(b"").is_empty());
+    }
+
+    #[test]
+    fn test_edit_only_changes_nearby_chunks() {
+        let key = crate::security::generate_key();
+        let original = sample(256 * 1024, 2);
+        let mut edited = original.clone();
+        edited.splice(100_000..100_000, b"export EDITED=1\n".iter().copied());
+
+        let before: HashSet<_> = split(&original).iter().map(|c| chunk_id(&key, c)).collect();
+        let after: Vec<_> = split(&edited).iter().map(|c| chunk_id(&key, c)).collect();
+        let new_chunks = after.iter().filter(|id| !before.contains(*id)).count();
+        assert!(new_chunks <= 2, "{} new chunks", new_chunks);
+    }
+
+    #[test]
+    fn test_write_and_read_roundtrip_with_gc() {
+        let tmp = TempDir::new().unwrap();
+        let sync_path = tmp.path();
+        let key = crate::security::generate_key();
+        let dest = sync_path.join("configs/.config/app/big.json.enc");
+
+        let v1 = sample(200 * 1024, 3);
+        write_chunked(sync_path, &dest, &v1, &key).unwrap();
+        let plaintext = crate::security::decrypt(&std::fs::read(&dest).unwrap(), &key).unwrap();
+        assert!(is_manifest(&plaintext));
+        assert_eq!(
+            read_from_repo(sync_path, &dest, plaintext, &key).unwrap(),
+            v1
+        );
+
+        // Rewrite with different content; old chunks become garbage
+        let v2 = sample(200 * 1024, 4);
+        write_chunked(sync_path, &dest, &v2, &key).unwrap();
+        assert!(gc_unreferenced(sync_path, &key).unwrap() > 0);
+        let plaintext = crate::security::decrypt(&std::fs::read(&dest).unwrap(), &key).unwrap();
+        assert_eq!(
+            read_from_repo(sync_path, &dest, plaintext, &key).unwrap(),
+            v2
+        );
+    }
+
+    #[test]
+    fn test_reassemble_passes_through_regular_blobs() {
+        let key = crate::security::generate_key();
+        let out = reassemble(
+            "configs/a",
+            b"plain file".to_vec(),
+            &key,
+            |_| unreachable!(),
+        );
+        assert_eq!(out.unwrap(), b"plain file");
+    }
+
+    /// Files whose own content starts like a manifest (or an escaped blob) round-trip
+    #[test]
+    fn test_whole_files_that_look_like_manifests_round_trip() {
+        let key = crate::security::generate_key();
+        for content in [
+            [MANIFEST_MAGIC, b"0123\n"].concat(),
+            [PLAIN_MAGIC, b"notes"].concat(),
+            b"tether-chunks everywhere".to_vec(),
+        ] {
+            let stored = encode_whole(&content).into_owned();
+            assert!(!is_manifest(&stored));
+            let out = reassemble("configs/notes.txt.enc", stored, &key, |_| unreachable!());
+            assert_eq!(out.unwrap(), content);
+        }
+        assert!(matches!(encode_whole(b"plain"), Cow::Borrowed(_)));
+
+        // Outside configs/ nothing is chunked or escaped
+        let dotfile = [MANIFEST_MAGIC, b"0123\n"].concat();
+        let out = reassemble(
+            "dotfiles/notes.enc",
+            dotfile.clone(),
+            &key,
+            |_| unreachable!(),
+        );
+        assert_eq!(out.unwrap(), dotfile);
+    }
+
+    #[test]
+    fn test_reassemble_rejects_tampered_chunk() {
+        let tmp = TempDir::new().unwrap();
+        let key = crate::security::generate_key();
+        let dest = tmp.path().join("configs/file.enc");
+        write_chunked(tmp.path(), &dest, &sample(10 * 1024, 5), &key).unwrap();
+
+        let plaintext = crate::security::decrypt(&std::fs::read(&dest).unwrap(), &key).unwrap();
+        let forged = crate::security::encrypt(b"forged", &key).unwrap();
+        assert!(reassemble("configs/file.enc", plaintext, &key, |_| Ok(forged.clone())).is_err());
+    }
+}
@@ -1,5 +1,5 @@
 use crate::cli::Output;
-use crate::config::MergeConfig;
+use crate::config::{Config, MergeConfig, NotificationEvent};
 use anyhow::Result;
 use chrono::{DateTime, Utc};
 use owo_colors::OwoColorize;
@@ -42,11 +42,14 @@ impl FileConflict {
         }
     }
 
-    /// Show unified diff between local and remote
-    pub fn show_diff(&self) -> Result<()> {
-        let local_str = String::from_utf8_lossy(&self.local_content);
-        let remote_str = String::from_utf8_lossy(&self.remote_content);
+    /// Whether either side of the conflict is binary (textual diff/merge not possible)
+    pub fn is_binary(&self) -> bool {
+        is_binary(&self.local_content) || is_binary(&self.remote_content)
+    }
 
+    /// Show unified diff between local and remote, with secret values masked unless
+    /// `reveal`
+    pub fn show_diff(&self, reveal: bool) -> Result<()> {
         println!();
         println!(
             "{} {}",
@@ -55,38 +58,94 @@ impl FileConflict {
         );
         println!("{}", "─".repeat(60).bright_black());
 
-        // Simple line-by-line diff
-        let local_lines: Vec<&str> = local_str.lines().collect();
-        let remote_lines: Vec<&str> = remote_str.lines().collect();
+        if self.is_binary() {
+            println!(
+                "{}",
+                format!("--- local: {}", binary_summary(self.local_content.len())).red()
+            );
+            println!(
+                "{}",
+                format!("+++ remote: {}", binary_summary(self.remote_content.len())).green()
+            );
+            println!("{}", "─".repeat(60).bright_black());
+            return Ok(());
+        }
 
         println!("{}", "--- local".red());
         println!("{}", "+++ remote".green());
         println!("{}", "─".repeat(60).bright_black());
 
-        // Use a simple diff algorithm
-        let diff = diff_lines(&local_lines, &remote_lines);
-        for line in diff {
-            match line {
-                DiffLine::Same(s) => println!(" {}", s),
-                DiffLine::Removed(s) => println!("{}", format!("-{}", s).red()),
-                DiffLine::Added(s) => println!("{}", format!("+{}", s).green()),
+        let lines = self.diff_text(reveal);
+        for line in &lines {
+            match line.chars().next() {
+                Some('-') => println!("{}", line.red()),
+                Some('+') => println!("{}", line.green()),
+                _ => println!("{}", line),
             }
         }
+        if lines
+            .iter()
+            .any(|line| line.contains(crate::security::secrets::MASK))
+        {
+            println!(
+                "{}",
+                "Secret values are masked; `tether resolve --reveal` shows them".bright_black()
+            );
+        }
 
         println!("{}", "─".repeat(60).bright_black());
         Ok(())
     }
 
+    /// Local-to-remote diff as plain `-`/`+`/` ` prefixed lines, with secret values
+    /// masked unless `reveal`
+    pub fn diff_text(&self, reveal: bool) -> Vec<String> {
+        if self.is_binary() {
+            return vec![
+                format!("--- local: {}", binary_summary(self.local_content.len())),
+                format!("+++ remote: {}", binary_summary(self.remote_content.len())),
+            ];
+        }
+
+        let local_str = String::from_utf8_lossy(&self.local_content);
+        let remote_str = String::from_utf8_lossy(&self.remote_content);
+        let local_lines: Vec<&str> = local_str.lines().collect();
+        let remote_lines: Vec<&str> = remote_str.lines().collect();
+
+        let lines: Vec<String> = diff_lines(&local_lines, &remote_lines)
+            .into_iter()
+            .map(|line| match line {
+                DiffLine::Same(s) => format!(" {}", s),
+                DiffLine::Removed(s) => format!("-{}", s),
+                DiffLine::Added(s) => format!("+{}", s),
+            })
+            .collect();
+        if reveal {
+            lines
+        } else {
+            crate::security::redact_lines(&lines)
+        }
+    }
+
     /// Prompt user for resolution
     pub fn prompt_resolution(&self) -> Result<ConflictResolution> {
         use inquire::Select;
 
-        let options = vec![
-            "Keep local version",
-            "Use remote version",
-            "Launch merge tool",
-            "Skip (decide later)",
-        ];
+        // Binary files can't be merged textually, so only offer whole-file choices
+        let options = if self.is_binary() {
+            vec![
+                "Keep local version",
+                "Use remote version",
+                "Skip (decide later)",
+            ]
+        } else {
+            vec![
+                "Keep local version",
+                "Use remote version",
+                "Launch merge tool",
+                "Skip (decide later)",
+            ]
+        };
 
         let choice = Select::new(
             &format!("How do you want to resolve {}?", self.file_path),
@@ -164,6 +223,16 @@ impl FileConflict {
     }
 }
 
+/// Detect binary content using git's heuristic: a NUL byte in the first 8000 bytes.
+pub fn is_binary(data: &[u8]) -> bool {
+    data.iter().take(8000).any(|&b| b == 0)
+}
+
+/// Placeholder shown instead of a textual diff for binary content
+pub fn binary_summary(len: usize) -> String {
+    format!("binary file changed ({} bytes)", len)
+}
+
 /// Simple diff line representation
 enum DiffLine<'a> {
     Same(&'a str),
@@ -262,16 +331,107 @@ pub struct PendingConflict {
     pub detected_at: DateTime<Utc>,
 }
 
+impl PendingConflict {
+    /// Current local and remote content of the conflicting file
+    pub fn load(
+        &self,
+        config: &Config,
+        key: Option<&[u8]>,
+        home: &Path,
+        sync_path: &Path,
+        machine_id: &str,
+    ) -> Result<FileConflict> {
+        let local_path = home.join(&self.file_path);
+        let local_content = if local_path.exists() {
+            std::fs::read(&local_path)?
+        } else {
+            Vec::new()
+        };
+
+        let shared = config.is_dotfile_shared(machine_id, &self.file_path);
+        let repo_rel = crate::sync::resolve_dotfile_repo_path(
+            sync_path,
+            &self.file_path,
+            config.security.encrypt_dotfiles,
+            config.dotfile_profile(machine_id, &self.file_path),
+            shared,
+        );
+        let remote_file = sync_path.join(&repo_rel);
+        let remote_content = if remote_file.exists() {
+            let raw = std::fs::read(&remote_file)?;
+            match key {
+                Some(key) => crate::security::decrypt(&raw, key)?,
+                None => raw,
+            }
+        } else {
+            Vec::new()
+        };
+
+        Ok(FileConflict {
+            file_path: self.file_path.clone(),
+            local_hash: crate::sha256_hex(&local_content),
+            last_synced_hash: None,
+            remote_hash: crate::sha256_hex(&remote_content),
+            local_content,
+            remote_content,
+        })
+    }
+}
+
+/// Side kept when a conflict is resolved automatically
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum ResolvedSide {
+    Local,
+    Remote,
+}
+
+/// Which side last-write-wins keeps: the local edit time against the time the remote
+/// version was committed. Local wins ties and unknown times, matching what a later
+/// export does with an unresolved local edit.
+pub fn last_write_wins(
+    local_modified: Option<DateTime<Utc>>,
+    remote_committed: Option<DateTime<Utc>>,
+) -> ResolvedSide {
+    match (local_modified, remote_committed) {
+        (Some(local), Some(remote)) if remote > local => ResolvedSide::Remote,
+        (None, Some(_)) => ResolvedSide::Remote,
+        _ => ResolvedSide::Local,
+    }
+}
+
+/// A conflict resolved by the sync strategy instead of a prompt, kept so the
+/// overwritten side can be restored
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct AutoResolution {
+    pub file_path: String,
+    pub kept: ResolvedSide,
+    /// Hash of the content that was overwritten
+    pub overwritten_hash: String,
+    /// Backup holding the overwritten local file (`tether restore file --from`)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub backup: Option<String>,
+    /// Sync repo commit holding the overwritten remote file (`tether restore git --commit`)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub commit: Option<String>,
+    pub resolved_at: DateTime<Utc>,
+}
+
+/// Auto-resolutions remembered for undo
+const AUTO_RESOLUTION_LIMIT: usize = 50;
+
 /// Conflict state persisted to ~/.tether/conflicts.json
 #[derive(Debug, Clone, Serialize, Deserialize, Default)]
 pub struct ConflictState {
     pub conflicts: Vec<PendingConflict>,
+    /// Most recent last, capped at `AUTO_RESOLUTION_LIMIT`
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub auto_resolved: Vec<AutoResolution>,
 }
 
 impl ConflictState {
     pub fn path() -> Result<std::path::PathBuf> {
-        let home = crate::home_dir()?;
-        Ok(home.join(".tether").join("conflicts.json"))
+        Ok(Config::config_dir()?.join("conflicts.json"))
     }
 
     pub fn load() -> Result<Self> {
@@ -307,6 +467,16 @@ impl ConflictState {
     pub fn has_conflicts(&self) -> bool {
         !self.conflicts.is_empty()
     }
+
+    pub fn record_auto_resolution(&mut self, resolution: AutoResolution) {
+        self.remove_conflict(&resolution.file_path);
+        self.auto_resolved.push(resolution);
+        let excess = self
+            .auto_resolved
+            .len()
+            .saturating_sub(AUTO_RESOLUTION_LIMIT);
+        self.auto_resolved.drain(..excess);
+    }
 }
 
 /// Escape a string for safe use in AppleScript
@@ -317,40 +487,77 @@ fn escape_applescript(s: &str) -> String {
     sanitized.replace('\\', "\\\\").replace('"', "\\\"")
 }
 
+/// Show a macOS notification for `event`, unless `[notifications]` in the config
+/// leaves it out. Skipped in server mode, where nobody is there to see it.
+fn notify(event: NotificationEvent, script: &str) -> Result<()> {
+    if crate::config::server_mode() {
+        return Ok(());
+    }
+    if let Ok(config) = Config::load() {
+        if !config
+            .notifications
+            .allows(event, chrono::Local::now().time())
+        {
+            log::debug!("Notification for {:?} left out by [notifications]", event);
+            return Ok(());
+        }
+    }
+    std::process::Command::new("osascript")
+        .args(["-e", script])
+        .output()?;
+    Ok(())
+}
+
 /// Send macOS notification about conflict
 pub fn notify_conflict(file_path: &str) -> Result<()> {
-    use std::process::Command;
-
     let safe_path = escape_applescript(file_path);
     let script = format!(
         r#"display notification "Conflict detected in {}" with title "Tether" subtitle "Run 'tether resolve' to fix""#,
         safe_path
     );
 
-    Command::new("osascript").args(["-e", &script]).output()?;
-
-    Ok(())
+    notify(NotificationEvent::Conflicts, &script)
 }
 
 /// Send macOS notification about multiple conflicts
 pub fn notify_conflicts(count: usize) -> Result<()> {
-    use std::process::Command;
-
     // count is a usize, no escaping needed
     let script = format!(
         r#"display notification "{} file conflicts detected" with title "Tether" subtitle "Run 'tether resolve' to fix""#,
         count
     );
 
-    Command::new("osascript").args(["-e", &script]).output()?;
+    notify(NotificationEvent::Conflicts, &script)
+}
 
-    Ok(())
+/// Send macOS notification about dotfiles that are behind the sync remote
+pub fn notify_stale_files(files: &[String]) -> Result<()> {
+    let message = match files {
+        [file] => format!("{} changed on another machine", escape_applescript(file)),
+        _ => format!("{} files changed on another machine", files.len()),
+    };
+    let script = format!(
+        r#"display notification "{}" with title "Tether" subtitle "Run 'tether sync' before editing""#,
+        message
+    );
+
+    notify(NotificationEvent::StaleFiles, &script)
+}
+
+/// Send macOS notification about encrypted files changed outside a tether sync
+pub fn notify_integrity_warning(count: usize) -> Result<()> {
+    // count is a usize, no escaping needed
+    let script = format!(
+        r#"display notification "{} encrypted file{} changed outside a sync" with title "Tether" subtitle "Run 'tether sync' to review""#,
+        count,
+        if count == 1 { "" } else { "s" }
+    );
+
+    notify(NotificationEvent::Integrity, &script)
 }
 
 /// Send macOS notification about deferred casks
 pub fn notify_deferred_casks(casks: &[String]) -> Result<()> {
-    use std::process::Command;
-
     let count = casks.len();
     let script = format!(
         r#"display notification "{} cask{} need{} password" with title "Tether" subtitle "Run 'tether sync' to install""#,
@@ -359,15 +566,81 @@ pub fn notify_deferred_casks(casks: &[String]) -> Result<()> {
         if count == 1 { "s" } else { "" }
     );
 
-    Command::new("osascript").args(["-e", &script]).output()?;
+    notify(NotificationEvent::DeferredCasks, &script)
+}
 
-    Ok(())
+/// Send macOS notification about package installs waiting for approval
+pub fn notify_pending_installs(count: usize) -> Result<()> {
+    let script = format!(
+        r#"display notification "{} package{} waiting to install" with title "Tether" subtitle "Run 'tether packages approve' to review""#,
+        count,
+        if count == 1 { "" } else { "s" }
+    );
+
+    notify(NotificationEvent::PendingInstalls, &script)
+}
+
+/// Send macOS notification that the sync repo is public
+pub fn notify_public_repo() -> Result<()> {
+    notify(
+        NotificationEvent::PublicRepo,
+        r#"display notification "Anyone can download your encrypted dotfiles" with title "Tether: sync repo is public" subtitle "Make the repo private""#,
+    )
+}
+
+/// Send macOS notification with a drift report's summary
+pub fn notify_drift_report(summary: &str) -> Result<()> {
+    let script = format!(
+        r#"display notification "{}" with title "Tether: drift report" subtitle "Run 'tether machines report' for details""#,
+        escape_applescript(summary)
+    );
+
+    notify(NotificationEvent::DriftReport, &script)
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
 
+    #[test]
+    fn test_last_write_wins() {
+        let earlier = Utc::now() - chrono::Duration::minutes(5);
+        let later = Utc::now();
+        assert_eq!(
+            last_write_wins(Some(earlier), Some(later)),
+            ResolvedSide::Remote
+        );
+        assert_eq!(
+            last_write_wins(Some(later), Some(earlier)),
+            ResolvedSide::Local
+        );
+        assert_eq!(
+            last_write_wins(Some(later), Some(later)),
+            ResolvedSide::Local
+        );
+        assert_eq!(last_write_wins(Some(later), None), ResolvedSide::Local);
+        assert_eq!(last_write_wins(None, Some(earlier)), ResolvedSide::Remote);
+    }
+
+    #[test]
+    fn test_auto_resolution_replaces_pending_and_caps() {
+        let mut state = ConflictState::default();
+        state.add_conflict(".zshrc", "aaa", "bbb");
+        for i in 0..AUTO_RESOLUTION_LIMIT + 5 {
+            state.record_auto_resolution(AutoResolution {
+                file_path: ".zshrc".to_string(),
+                kept: ResolvedSide::Remote,
+                overwritten_hash: format!("{}", i),
+                backup: None,
+                commit: None,
+                resolved_at: Utc::now(),
+            });
+        }
+        assert!(!state.has_conflicts());
+        assert_eq!(state.auto_resolved.len(), AUTO_RESOLUTION_LIMIT);
+        assert_eq!(state.auto_resolved[0].overwritten_hash, "5");
+    }
+
     // is_true_conflict tests
     #[test]
     fn test_is_true_conflict_both_changed() {
@@ -456,6 +729,35 @@ mod tests {
         assert!(result.is_none());
     }
 
+    // Binary detection tests
+    #[test]
+    fn test_is_binary_detects_nul() {
+        assert!(is_binary(b"PK\x03\x04\x00\x00"));
+        assert!(!is_binary(b"export PATH=$HOME/bin:$PATH\n"));
+        assert!(!is_binary(b""));
+    }
+
+    #[test]
+    fn test_is_binary_ignores_nul_past_sniff_window() {
+        let mut data = vec![b'a'; 9000];
+        data[8500] = 0;
+        assert!(!is_binary(&data));
+    }
+
+    #[test]
+    fn test_conflict_is_binary_either_side() {
+        let conflict = FileConflict {
+            file_path: ".local/share/app.db".to_string(),
+            local_hash: "aaa".to_string(),
+            last_synced_hash: Some("bbb".to_string()),
+            remote_hash: "ccc".to_string(),
+            local_content: b"plain text".to_vec(),
+            remote_content: vec![0, 1, 2, 3],
+        };
+        assert!(conflict.is_binary());
+        assert_eq!(binary_summary(4), "binary file changed (4 bytes)");
+    }
+
     // ConflictState tests
     #[test]
     fn test_conflict_state_add_remove() {
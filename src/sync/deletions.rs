@@ -0,0 +1,364 @@
+//! Propagation of local file deletions to other machines.
+//!
+//! When a synced file disappears locally, its repo copy is removed and a tombstone
+//! (state key → repo path) is recorded in `SyncState` and published in this machine's
+//! `MachineState`. Other machines delete their local copy if it's unchanged since their
+//! last sync and the repo path is still gone; a local edit wins and re-creates the file.
+
+use crate::config::Config;
+use crate::sync::state::{MachineState, SyncState, Tombstone};
+use anyhow::Result;
+use std::path::{Path, PathBuf};
+
+/// Repo locations a state key may be stored under for `machine_id`. Dotfiles still in
+/// config map to exactly one path; ones removed from config could be in the machine's
+/// profile dir or `shared/`. Project configs and the tether config aren't tracked.
+pub fn candidate_repo_paths(config: &Config, machine_id: &str, key: &str) -> Vec<String> {
+    let encrypted = config.security.encrypt_dotfiles;
+    if key.contains(':') || key == ".tether/config.toml" {
+        return Vec::new();
+    }
+    if let Some(rel) = key.strip_prefix("~/") {
+        if !crate::config::is_safe_dotfile_path(rel) {
+            return Vec::new();
+        }
+        let path = if encrypted {
+            format!("configs/{}.enc", rel)
+        } else {
+            format!("configs/{}", rel)
+        };
+        return vec![path];
+    }
+    if !crate::config::is_safe_dotfile_path(key) {
+        return Vec::new();
+    }
+
+    let entry = config.effective_dotfiles(machine_id).into_iter().find(|e| {
+        let pattern = e.path();
+        pattern == key
+            || (crate::sync::is_glob_pattern(pattern)
+                && glob::Pattern::new(pattern).is_ok_and(|p| p.matches(key)))
+    });
+    match entry {
+        Some(entry) => {
+            let pattern = entry.path();
+            let profile = config.dotfile_profile(machine_id, pattern);
+            let shared = config.is_dotfile_shared(machine_id, pattern);
+            vec![crate::sync::dotfile_to_repo_path_profiled(
+                key, encrypted, profile, shared,
+            )]
+        }
+        None => {
+            let profile = config.profile_name(machine_id);
+            vec![
+                crate::sync::dotfile_to_repo_path_profiled(key, encrypted, profile, false),
+                crate::sync::dotfile_to_repo_path_profiled(key, encrypted, profile, true),
+            ]
+        }
+    }
+}
+
+/// Local path for a state key (`~/rel` for config dir files, home-relative otherwise)
+pub fn local_path(home: &Path, key: &str) -> PathBuf {
+    home.join(key.strip_prefix("~/").unwrap_or(key))
+}
+
+/// Files synced from this machine that no longer exist locally, with the repo path
+/// still holding their copy.
+pub fn detect_local_deletions(
+    config: &Config,
+    state: &SyncState,
+    home: &Path,
+    sync_path: &Path,
+) -> Vec<(String, String)> {
+    // Removals inside synced dirs propagate through dir manifests instead. A `dirs`
+    // entry naming a single file has no manifest, so its own key is handled here.
+    let dirs: Vec<String> = config
+        .effective_dirs(&state.machine_id)
+        .iter()
+        .map(|d| {
+            format!(
+                "~/{}",
+                d.strip_prefix("~/").unwrap_or(d).trim_end_matches('/')
+            )
+        })
+        .collect();
+    let mut deleted: Vec<(String, String)> = state
+        .files
+        .keys()
+        .filter(|key| !dirs.iter().any(|d| key.starts_with(&format!("{}/", d))))
+        .filter(|key| !local_path(home, key).exists())
+        .filter_map(|key| {
+            candidate_repo_paths(config, &state.machine_id, key)
+                .into_iter()
+                .find(|p| sync_path.join(p).exists())
+                .map(|p| (key.clone(), p))
+        })
+        .collect();
+    deleted.sort();
+    deleted
+}
+
+/// Remove a deleted file's repo copy and record a tombstone for other machines.
+pub fn record_deletion(
+    sync_path: &Path,
+    state: &mut SyncState,
+    key: &str,
+    repo_path: &str,
+) -> Result<()> {
+    let full = sync_path.join(repo_path);
+    if full.exists() {
+        std::fs::remove_file(&full)?;
+    }
+    if let Some(parent) = full.parent() {
+        remove_empty_dirs(parent, sync_path);
+    }
+    state.files.remove(key);
+    state.deleted_files.insert(
+        key.to_string(),
+        Tombstone {
+            repo_path: repo_path.to_string(),
+            deleted_at: chrono::Utc::now(),
+        },
+    );
+    Ok(())
+}
+
+/// Drop tombstones whose repo path exists again (the file was re-created somewhere).
+pub fn prune_tombstones(state: &mut SyncState, sync_path: &Path) {
+    state
+        .deleted_files
+        .retain(|_, t| !sync_path.join(&t.repo_path).exists());
+}
+
+/// Result of applying other machines' tombstones
+#[derive(Debug, Default)]
+pub struct RemoteDeletions {
+    /// Local files removed (state keys)
+    pub deleted: Vec<String>,
+    /// Files edited locally since the last sync; kept and re-published
+    pub kept: Vec<String>,
+}
+
+/// Delete local copies of files other machines deleted. Only files this machine synced
+/// from the tombstoned repo path are touched; each is backed up first (into
+/// `backup_dir`, created on first use).
+pub fn apply_remote_deletions(
+    config: &Config,
+    state: &mut SyncState,
+    home: &Path,
+    sync_path: &Path,
+    machines: &[MachineState],
+    backup_dir: &mut Option<PathBuf>,
+) -> Result<RemoteDeletions> {
+    let mut result = RemoteDeletions::default();
+
+    for machine in machines {
+        if machine.machine_id == state.machine_id {
+            continue;
+        }
+        for (key, tombstone) in &machine.deleted_files {
+            let Some(synced_hash) = state.files.get(key).map(|f| f.hash.clone()) else {
+                continue;
+            };
+            if sync_path.join(&tombstone.repo_path).exists()
+                || !candidate_repo_paths(config, &state.machine_id, key)
+                    .contains(&tombstone.repo_path)
+            {
+                continue;
+            }
+
+            let local = local_path(home, key);
+            let local_hash = std::fs::read(&local).ok().map(|c| crate::sha256_hex(&c));
+            match local_hash {
+                Some(hash) if hash != synced_hash => {
+                    // Edited since last sync: forget the synced hash so it's exported again
+                    state.files.remove(key);
+                    result.kept.push(key.clone());
+                }
+                Some(_) => {
+                    if backup_dir.is_none() {
+                        *backup_dir = Some(crate::sync::create_backup_dir()?);
+                    }
+                    let category = if key.starts_with("~/") {
+                        "configs"
+                    } else {
+                        "dotfiles"
+                    };
+                    let rel = key.strip_prefix("~/").unwrap_or(key);
+                    crate::sync::backup_file(backup_dir.as_ref().unwrap(), category, rel, &local)?;
+                    std::fs::remove_file(&local)?;
+                    state.files.remove(key);
+                    result.deleted.push(key.clone());
+                }
+                None => {
+                    state.files.remove(key);
+                }
+            }
+        }
+    }
+
+    result.deleted.sort();
+    result.deleted.dedup();
+    result.kept.sort();
+    result.kept.dedup();
+    Ok(result)
+}
+
+/// Remove `dir` and its parents while empty, stopping at `root`
+pub fn remove_empty_dirs(dir: &Path, root: &Path) {
+    let mut current = dir;
+    while current != root && current.starts_with(root) {
+        if std::fs::remove_dir(current).is_err() {
+            break;
+        }
+        match current.parent() {
+            Some(parent) => current = parent,
+            None => break,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::sync::state::FileState;
+    use std::collections::HashMap;
+    use tempfile::TempDir;
+
+    fn state(machine_id: &str) -> SyncState {
+        SyncState {
+            machine_id: machine_id.to_string(),
+            last_sync: chrono::Utc::now(),
+            files: HashMap::new(),
+            packages: HashMap::new(),
+            last_upgrade: None,
+            last_upgrade_with_updates: None,
+            deferred_casks: Vec::new(),
+            deferred_casks_hash: None,
+            pending_installs: Default::default(),
+            dismissed_imports: Default::default(),
+            deleted_files: HashMap::new(),
+            repo_visibility: None,
+            last_report: None,
+            repo_sizes: Vec::new(),
+        }
+    }
+
+    fn synced(state: &mut SyncState, key: &str, content: &[u8]) {
+        state.files.insert(
+            key.to_string(),
+            FileState {
+                hash: crate::sha256_hex(content),
+                last_modified: chrono::Utc::now(),
+                synced: true,
+                disk: None,
+                repo: None,
+            },
+        );
+    }
+
+    #[test]
+    fn test_deletion_round_trip() {
+        let config = Config::default();
+        let sync = TempDir::new().unwrap();
+        let home_a = TempDir::new().unwrap();
+        let home_b = TempDir::new().unwrap();
+        let backups = TempDir::new().unwrap();
+        let repo_path = candidate_repo_paths(&config, "a", ".vimrc").remove(0);
+        std::fs::create_dir_all(sync.path().join(&repo_path).parent().unwrap()).unwrap();
+        std::fs::write(sync.path().join(&repo_path), "enc").unwrap();
+
+        // Machine A deleted .vimrc locally but still has .zshrc
+        let mut state_a = state("a");
+        synced(&mut state_a, ".vimrc", b"set nu");
+        synced(&mut state_a, ".zshrc", b"zsh");
+        std::fs::write(home_a.path().join(".zshrc"), "zsh").unwrap();
+        let deleted = detect_local_deletions(&config, &state_a, home_a.path(), sync.path());
+        assert_eq!(deleted, vec![(".vimrc".to_string(), repo_path.clone())]);
+        record_deletion(sync.path(), &mut state_a, ".vimrc", &repo_path).unwrap();
+        assert!(!sync.path().join(&repo_path).exists());
+
+        let mut machine_a = MachineState::new("a");
+        machine_a.deleted_files = state_a.deleted_files.clone();
+
+        // Machine B has the unchanged file and a locally edited .vimrc variant
+        let mut state_b = state("b");
+        synced(&mut state_b, ".vimrc", b"set nu");
+        std::fs::write(home_b.path().join(".vimrc"), "set nu").unwrap();
+        let result = apply_remote_deletions(
+            &config,
+            &mut state_b,
+            home_b.path(),
+            sync.path(),
+            std::slice::from_ref(&machine_a),
+            &mut Some(backups.path().to_path_buf()),
+        )
+        .unwrap();
+        assert_eq!(result.deleted, vec![".vimrc"]);
+        assert!(!home_b.path().join(".vimrc").exists());
+        assert!(backups.path().join("dotfiles/.vimrc").exists());
+        assert!(!state_b.files.contains_key(".vimrc"));
+
+        let mut state_c = state("c");
+        synced(&mut state_c, ".vimrc", b"set nu");
+        let home_c = TempDir::new().unwrap();
+        std::fs::write(home_c.path().join(".vimrc"), "set nu rnu").unwrap();
+        let result = apply_remote_deletions(
+            &config,
+            &mut state_c,
+            home_c.path(),
+            sync.path(),
+            std::slice::from_ref(&machine_a),
+            &mut Some(backups.path().to_path_buf()),
+        )
+        .unwrap();
+        assert_eq!(result.kept, vec![".vimrc"]);
+        assert!(home_c.path().join(".vimrc").exists());
+    }
+
+    /// A `dirs` entry that is a single file propagates its deletion with a tombstone,
+    /// while files inside a synced dir are left to its manifest
+    #[test]
+    fn test_single_file_dir_entry_deletion() {
+        let mut config = Config::default();
+        config.dotfiles.dirs = vec!["~/.config/app.toml".into(), "~/.config/nvim".into()];
+        let sync = TempDir::new().unwrap();
+        let home_a = TempDir::new().unwrap();
+        let home_b = TempDir::new().unwrap();
+        let backups = TempDir::new().unwrap();
+        let mut state_a = state("a");
+        for key in ["~/.config/app.toml", "~/.config/nvim/init.lua"] {
+            let repo_path = candidate_repo_paths(&config, "a", key).remove(0);
+            std::fs::create_dir_all(sync.path().join(&repo_path).parent().unwrap()).unwrap();
+            std::fs::write(sync.path().join(&repo_path), "enc").unwrap();
+            synced(&mut state_a, key, b"v1");
+        }
+
+        let deleted = detect_local_deletions(&config, &state_a, home_a.path(), sync.path());
+        let repo_path = candidate_repo_paths(&config, "a", "~/.config/app.toml").remove(0);
+        assert_eq!(
+            deleted,
+            vec![("~/.config/app.toml".to_string(), repo_path.clone())]
+        );
+        record_deletion(sync.path(), &mut state_a, "~/.config/app.toml", &repo_path).unwrap();
+        let mut machine_a = MachineState::new("a");
+        machine_a.deleted_files = state_a.deleted_files.clone();
+
+        let mut state_b = state("b");
+        synced(&mut state_b, "~/.config/app.toml", b"v1");
+        std::fs::create_dir_all(home_b.path().join(".config")).unwrap();
+        std::fs::write(home_b.path().join(".config/app.toml"), "v1").unwrap();
+        let result = apply_remote_deletions(
+            &config,
+            &mut state_b,
+            home_b.path(),
+            sync.path(),
+            std::slice::from_ref(&machine_a),
+            &mut Some(backups.path().to_path_buf()),
+        )
+        .unwrap();
+        assert_eq!(result.deleted, vec!["~/.config/app.toml"]);
+        assert!(!home_b.path().join(".config/app.toml").exists());
+    }
+}
@@ -0,0 +1,136 @@
+//! Per-directory manifests so file removals inside synced dirs propagate.
+//!
+//! Each synced dir has `dirs/<home-relative dir>.json` in the sync repo listing the
+//! files it holds and the files removed from it. A machine that deletes a file it had
+//! synced moves it to `removed`; other machines then delete their unchanged copy.
+
+use anyhow::Result;
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeMap, BTreeSet};
+use std::path::{Path, PathBuf};
+
+pub const DIR_MANIFESTS_DIR: &str = "dirs";
+
+#[derive(Debug, Default, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct DirManifest {
+    /// Files in the directory, relative to it
+    #[serde(default)]
+    pub files: BTreeSet<String>,
+    /// Files removed from the directory and when
+    #[serde(default)]
+    pub removed: BTreeMap<String, DateTime<Utc>>,
+}
+
+impl DirManifest {
+    fn path(sync_path: &Path, dir_rel: &str) -> PathBuf {
+        sync_path
+            .join(DIR_MANIFESTS_DIR)
+            .join(format!("{}.json", dir_rel))
+    }
+
+    pub fn load(sync_path: &Path, dir_rel: &str) -> Result<Self> {
+        let path = Self::path(sync_path, dir_rel);
+        if !path.exists() {
+            return Ok(Self::default());
+        }
+        let content = std::fs::read_to_string(path)?;
+        Ok(serde_json::from_str(&content)?)
+    }
+
+    /// Write the manifest, leaving the file untouched when nothing changed
+    pub fn save(&self, sync_path: &Path, dir_rel: &str) -> Result<()> {
+        if Self::load(sync_path, dir_rel).ok().as_ref() == Some(self) {
+            return Ok(());
+        }
+        let path = Self::path(sync_path, dir_rel);
+        if let Some(parent) = path.parent() {
+            std::fs::create_dir_all(parent)?;
+        }
+        std::fs::write(path, serde_json::to_string_pretty(self)? + "\n")?;
+        Ok(())
+    }
+
+    /// Update from the files present locally. Listed files that are gone locally and
+    /// that this machine had synced (`was_synced`) count as removed and are returned;
+    /// ones it never had are left for the machines that do.
+    pub fn record(
+        &mut self,
+        present: &BTreeSet<String>,
+        was_synced: impl Fn(&str) -> bool,
+    ) -> Vec<String> {
+        for file in present {
+            self.files.insert(file.clone());
+            self.removed.remove(file);
+        }
+        let gone: Vec<String> = self
+            .files
+            .iter()
+            .filter(|f| !present.contains(*f) && was_synced(f))
+            .cloned()
+            .collect();
+        let now = Utc::now();
+        for file in &gone {
+            self.files.remove(file);
+            self.removed.insert(file.clone(), now);
+        }
+        gone
+    }
+}
+
+/// Files under `dir` (a directory on disk), relative to it
+pub fn local_files(dir: &Path) -> BTreeSet<String> {
+    walkdir::WalkDir::new(dir)
+        .follow_links(false)
+        .into_iter()
+        .filter_map(|e| e.ok())
+        .filter(|e| e.file_type().is_file())
+        .filter_map(|e| {
+            e.path()
+                .strip_prefix(dir)
+                .ok()
+                .map(|p| p.to_string_lossy().to_string())
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn set(files: &[&str]) -> BTreeSet<String> {
+        files.iter().map(|f| f.to_string()).collect()
+    }
+
+    #[test]
+    fn test_record_tracks_removals_of_synced_files_only() {
+        let mut manifest = DirManifest::default();
+        assert!(manifest
+            .record(&set(&["a.lua", "lua/b.lua"]), |_| true)
+            .is_empty());
+
+        // Another machine added c.lua; this one never synced it
+        manifest.files.insert("c.lua".to_string());
+        let gone = manifest.record(&set(&["a.lua"]), |f| f != "c.lua");
+        assert_eq!(gone, vec!["lua/b.lua"]);
+        assert_eq!(manifest.files, set(&["a.lua", "c.lua"]));
+        assert!(manifest.removed.contains_key("lua/b.lua"));
+
+        // Re-creating a removed file clears its removal
+        manifest.record(&set(&["a.lua", "lua/b.lua"]), |_| false);
+        assert!(manifest.removed.is_empty());
+    }
+
+    #[test]
+    fn test_save_round_trip() {
+        let tmp = tempfile::TempDir::new().unwrap();
+        let mut manifest = DirManifest::default();
+        manifest.record(&set(&["init.lua"]), |_| true);
+        manifest.save(tmp.path(), ".config/nvim").unwrap();
+        assert!(tmp.path().join("dirs/.config/nvim.json").exists());
+        assert_eq!(
+            DirManifest::load(tmp.path(), ".config/nvim").unwrap(),
+            manifest
+        );
+    }
+}
@@ -5,7 +5,29 @@ pub struct SyncEngine;
 
 impl SyncEngine {
     pub fn sync_path() -> Result<PathBuf> {
-        let home = crate::home_dir()?;
-        Ok(home.join(".tether").join("sync"))
+        Ok(crate::config::Config::config_dir()?.join("sync"))
+    }
+}
+
+/// Which directions a sync run may move data.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum SyncMode {
+    #[default]
+    Full,
+    /// Apply remote changes locally; never write to the sync repo or push
+    PullOnly,
+    /// Publish local state; never write remote changes to local files or packages
+    PushOnly,
+}
+
+impl SyncMode {
+    /// Whether remote changes may be applied to local files, config and packages
+    pub fn applies_remote(self) -> bool {
+        self != SyncMode::PushOnly
+    }
+
+    /// Whether local changes may be written to the sync repo and pushed
+    pub fn pushes_local(self) -> bool {
+        self != SyncMode::PullOnly
     }
 }
@@ -0,0 +1,152 @@
+//! Named snapshots of a machine's environment (`tether freeze` / `tether thaw`).
+//!
+//! A lockfile at `locks/<name>.json` in the sync repo pins exact package versions for
+//! every manager plus the sync repo commit holding the dotfiles at freeze time, so
+//! another machine can reproduce that environment.
+
+use crate::packages::PackageInfo;
+use anyhow::Result;
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::path::{Path, PathBuf};
+
+pub const LOCKS_DIR: &str = "locks";
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct EnvLock {
+    pub name: String,
+    pub created_at: DateTime<Utc>,
+    pub machine_id: String,
+    pub cli_version: String,
+    /// Sync repo commit the dotfiles are restored from
+    pub dotfiles_commit: String,
+    /// Home-relative dotfile -> its path in the sync repo at `dotfiles_commit`
+    #[serde(default)]
+    pub dotfiles: BTreeMap<String, String>,
+    /// Machine state key (e.g. "brew_formulae") -> installed packages with versions
+    #[serde(default)]
+    pub packages: BTreeMap<String, Vec<PackageInfo>>,
+}
+
+impl EnvLock {
+    /// Lock names become file names: no separators, traversal or leading dots
+    pub fn is_valid_name(name: &str) -> bool {
+        !name.is_empty()
+            && !name.starts_with('.')
+            && name
+                .chars()
+                .all(|c| c.is_ascii_alphanumeric() || matches!(c, '-' | '_' | '.'))
+    }
+
+    fn path(sync_path: &Path, name: &str) -> PathBuf {
+        sync_path.join(LOCKS_DIR).join(format!("{}.json", name))
+    }
+
+    pub fn exists(sync_path: &Path, name: &str) -> bool {
+        Self::path(sync_path, name).exists()
+    }
+
+    pub fn load(sync_path: &Path, name: &str) -> Result<Option<Self>> {
+        if !Self::is_valid_name(name) {
+            anyhow::bail!("Invalid lock name: {}", name);
+        }
+        let path = Self::path(sync_path, name);
+        if !path.exists() {
+            return Ok(None);
+        }
+        let content = std::fs::read_to_string(path)?;
+        Ok(Some(serde_json::from_str(&content)?))
+    }
+
+    pub fn save(&self, sync_path: &Path) -> Result<()> {
+        if !Self::is_valid_name(&self.name) {
+            anyhow::bail!("Invalid lock name: {}", self.name);
+        }
+        let path = Self::path(sync_path, &self.name);
+        if let Some(parent) = path.parent() {
+            std::fs::create_dir_all(parent)?;
+        }
+        let content = serde_json::to_string_pretty(self)? + "\n";
+        crate::sync::atomic_write(&path, content.as_bytes())
+    }
+
+    /// All locks in the sync repo, newest first
+    pub fn list(sync_path: &Path) -> Result<Vec<Self>> {
+        let dir = sync_path.join(LOCKS_DIR);
+        if !dir.exists() {
+            return Ok(Vec::new());
+        }
+        let mut locks: Vec<Self> = std::fs::read_dir(dir)?
+            .filter_map(|entry| {
+                let path = entry.ok()?.path();
+                if path.extension()? != "json" {
+                    return None;
+                }
+                serde_json::from_str(&std::fs::read_to_string(path).ok()?).ok()
+            })
+            .collect();
+        locks.sort_by_key(|lock| std::cmp::Reverse(lock.created_at));
+        Ok(locks)
+    }
+
+    pub fn package_count(&self) -> usize {
+        self.packages.values().map(Vec::len).sum()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::TempDir;
+
+    fn lock(name: &str) -> EnvLock {
+        EnvLock {
+            name: name.to_string(),
+            created_at: Utc::now(),
+            machine_id: "laptop".to_string(),
+            cli_version: "1.0.0".to_string(),
+            dotfiles_commit: "abc123".to_string(),
+            dotfiles: BTreeMap::from([(
+                ".zshrc".to_string(),
+                "profiles/dev/.zshrc.enc".to_string(),
+            )]),
+            packages: BTreeMap::from([(
+                "npm".to_string(),
+                vec![PackageInfo {
+                    name: "typescript".to_string(),
+                    version: Some("5.4.2".to_string()),
+                }],
+            )]),
+        }
+    }
+
+    #[test]
+    fn test_lock_roundtrip_and_list() {
+        let temp = TempDir::new().unwrap();
+        lock("release-1.0").save(temp.path()).unwrap();
+        lock("onboarding").save(temp.path()).unwrap();
+
+        let loaded = EnvLock::load(temp.path(), "release-1.0").unwrap().unwrap();
+        assert_eq!(loaded.dotfiles_commit, "abc123");
+        assert_eq!(loaded.package_count(), 1);
+        assert!(EnvLock::load(temp.path(), "missing").unwrap().is_none());
+
+        let names: Vec<String> = EnvLock::list(temp.path())
+            .unwrap()
+            .into_iter()
+            .map(|l| l.name)
+            .collect();
+        assert_eq!(names, vec!["onboarding", "release-1.0"]);
+    }
+
+    #[test]
+    fn test_lock_name_validation() {
+        assert!(EnvLock::is_valid_name("q3-baseline_v2.1"));
+        assert!(!EnvLock::is_valid_name(""));
+        assert!(!EnvLock::is_valid_name(".hidden"));
+        assert!(!EnvLock::is_valid_name("../escape"));
+        assert!(!EnvLock::is_valid_name("a/b"));
+        assert!(EnvLock::load(Path::new("/tmp"), "../x").is_err());
+    }
+}
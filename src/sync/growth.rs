@@ -0,0 +1,189 @@
+//! Sync repo growth. Encrypted files don't delta-compress, so every edit to a big
+//! dotfile adds its full size to history and the repo only ever grows. Each sync
+//! records the repo's size (at most once a day) in the sync state, and once it grows
+//! quickly or piles up unpacked objects, `tether status` and the dashboard suggest
+//! `tether repo prune` or `tether repo compact`.
+
+use crate::sync::{GitBackend, SyncState};
+use chrono::{DateTime, Duration, Utc};
+use serde::{Deserialize, Serialize};
+
+/// Samples kept: about three months of daily syncs
+const MAX_SAMPLES: usize = 90;
+
+/// Growth is measured over this many days
+pub const WINDOW_DAYS: i64 = 30;
+
+/// Growth over [`WINDOW_DAYS`] that makes pruning worth suggesting
+const PRUNE_GROWTH_BYTES: u64 = 50 * 1024 * 1024;
+
+/// Unpacked objects that make compacting worth suggesting
+const COMPACT_LOOSE_BYTES: u64 = 25 * 1024 * 1024;
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct RepoSize {
+    pub at: DateTime<Utc>,
+    /// Whole object database
+    pub bytes: u64,
+    /// Objects not yet packed (and compressed against each other)
+    pub loose_bytes: u64,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Recommendation {
+    /// Squash old history with `tether repo prune`
+    Prune { grown: u64, days: i64, size: u64 },
+    /// Pack loose objects with `tether repo compact`
+    Compact { loose: u64 },
+}
+
+impl Recommendation {
+    pub fn message(&self) -> String {
+        match self {
+            Self::Prune { grown, days, size } => format!(
+                "Sync repo grew {} in {} days (now {}); 'tether repo prune' trims old history",
+                format_size(*grown),
+                days,
+                format_size(*size)
+            ),
+            Self::Compact { loose } => format!(
+                "{} of the sync repo is uncompressed; 'tether repo compact' packs it",
+                format_size(*loose)
+            ),
+        }
+    }
+}
+
+/// Record the repo's size in `state`, unless it was recorded in the last day
+pub fn record(state: &mut SyncState, git: &GitBackend, now: DateTime<Utc>) {
+    if state
+        .repo_sizes
+        .last()
+        .is_some_and(|last| now - last.at < Duration::days(1))
+    {
+        return;
+    }
+    match git.object_store_sizes() {
+        Ok((loose_bytes, packed)) => push(
+            &mut state.repo_sizes,
+            RepoSize {
+                at: now,
+                bytes: loose_bytes + packed,
+                loose_bytes,
+            },
+        ),
+        Err(e) => log::debug!("Failed to measure the sync repo: {}", e),
+    }
+}
+
+/// Record the repo's size now, replacing a sample from the last day: after it was
+/// pruned or compacted, the old size would keep the suggestion up
+pub fn remeasure(state: &mut SyncState, git: &GitBackend, now: DateTime<Utc>) {
+    if state
+        .repo_sizes
+        .last()
+        .is_some_and(|last| now - last.at < Duration::days(1))
+    {
+        state.repo_sizes.pop();
+    }
+    record(state, git, now);
+}
+
+fn push(samples: &mut Vec<RepoSize>, sample: RepoSize) {
+    samples.push(sample);
+    let excess = samples.len().saturating_sub(MAX_SAMPLES);
+    samples.drain(..excess);
+}
+
+/// How much the repo grew over [`WINDOW_DAYS`] up to the latest sample, and over how
+/// many days that was measured. `None` without two samples to compare.
+pub fn growth(samples: &[RepoSize]) -> Option<(u64, i64)> {
+    let latest = samples.last()?;
+    let since = latest.at - Duration::days(WINDOW_DAYS);
+    let first = samples.iter().find(|sample| sample.at >= since)?;
+    if first == latest {
+        return None;
+    }
+    Some((
+        latest.bytes.saturating_sub(first.bytes),
+        (latest.at - first.at).num_days().max(1),
+    ))
+}
+
+/// What to do about the repo's size, if anything
+pub fn recommendation(samples: &[RepoSize]) -> Option<Recommendation> {
+    let latest = samples.last()?;
+    if latest.loose_bytes >= COMPACT_LOOSE_BYTES {
+        return Some(Recommendation::Compact {
+            loose: latest.loose_bytes,
+        });
+    }
+    let (grown, days) = growth(samples)?;
+    (grown >= PRUNE_GROWTH_BYTES).then_some(Recommendation::Prune {
+        grown,
+        days,
+        size: latest.bytes,
+    })
+}
+
+pub fn format_size(bytes: u64) -> String {
+    let mib = bytes as f64 / (1024.0 * 1024.0);
+    if mib >= 1.0 {
+        format!("{:.1} MiB", mib)
+    } else {
+        format!("{:.1} KiB", bytes as f64 / 1024.0)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const MIB: u64 = 1024 * 1024;
+
+    fn sample(days_ago: i64, mib: u64, loose_mib: u64) -> RepoSize {
+        RepoSize {
+            at: Utc::now() - Duration::days(days_ago),
+            bytes: mib * MIB,
+            loose_bytes: loose_mib * MIB,
+        }
+    }
+
+    #[test]
+    fn test_recommendation() {
+        assert_eq!(recommendation(&[]), None);
+        assert_eq!(recommendation(&[sample(0, 400, 0)]), None);
+
+        // Growth before the window doesn't count
+        let slow = vec![sample(60, 10, 0), sample(20, 100, 0), sample(0, 120, 0)];
+        assert_eq!(growth(&slow), Some((20 * MIB, 20)));
+        assert_eq!(recommendation(&slow), None);
+
+        let fast = vec![sample(20, 100, 0), sample(0, 160, 0)];
+        assert_eq!(
+            recommendation(&fast),
+            Some(Recommendation::Prune {
+                grown: 60 * MIB,
+                days: 20,
+                size: 160 * MIB
+            })
+        );
+        assert!(recommendation(&fast)
+            .unwrap()
+            .message()
+            .starts_with("Sync repo grew 60.0 MiB in 20 days (now 160.0 MiB)"));
+
+        // Packing comes first: it's cheap and doesn't rewrite history
+        let loose = vec![sample(20, 100, 0), sample(0, 160, 40)];
+        assert_eq!(
+            recommendation(&loose),
+            Some(Recommendation::Compact { loose: 40 * MIB })
+        );
+
+        let mut samples = Vec::new();
+        for day in 0..100 {
+            push(&mut samples, sample(100 - day, 1, 0));
+        }
+        assert_eq!(samples.len(), MAX_SAMPLES);
+    }
+}
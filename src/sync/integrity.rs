@@ -0,0 +1,356 @@
+//! Signed manifest of the encrypted files in the sync repo.
+//!
+//! AES-GCM stops anyone without the key from forging a file's contents, but someone
+//! with write access to the repo can still delete `.enc` files, add old ones back or
+//! swap two of them. Every sync that commits re-signs `integrity.json`, which lists
+//! each encrypted file's ciphertext hash under an HMAC keyed from the encryption key.
+//! After a pull, files that don't match it changed outside a tether sync.
+//!
+//! Machines that sync at the same time both re-sign it, which would make every such
+//! pull a conflict. The pull keeps the remote's copy instead, the check skips files
+//! this machine's own unpushed commits changed, and the next commit re-signs it.
+//!
+//! Deleting the manifest along with the tampered files would otherwise pass as a
+//! repo that was never signed, so the clone remembers, in its `.git` directory, that
+//! it has seen one.
+
+use crate::config::Config;
+use anyhow::Result;
+use chrono::{DateTime, Utc};
+use hmac::{Hmac, Mac};
+use serde::{Deserialize, Serialize};
+use sha2::Sha256;
+use std::collections::BTreeMap;
+use std::path::Path;
+
+pub const INTEGRITY_FILE: &str = "integrity.json";
+
+/// Marks a clone that has seen a signed manifest; local to the clone
+const SEEN_MARKER: &str = ".git/tether-integrity-seen";
+
+type HmacSha256 = Hmac<Sha256>;
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct IntegrityManifest {
+    /// Machine whose sync last signed the manifest
+    pub signed_by: String,
+    pub signed_at: DateTime<Utc>,
+    /// Repo-relative path of every `.enc` file -> SHA-256 of its ciphertext
+    pub files: BTreeMap<String, String>,
+    /// HMAC-SHA256 over the fields above
+    pub mac: String,
+}
+
+/// How the repo differs from its manifest
+#[derive(Debug, Default, PartialEq, Eq)]
+pub struct IntegrityReport {
+    /// The manifest itself was changed without the key; nothing else is checked
+    pub forged: bool,
+    /// The manifest itself is gone, though this clone had seen one
+    pub deleted: bool,
+    pub signed_by: String,
+    /// Signed files that are gone
+    pub missing: Vec<String>,
+    /// Files the manifest doesn't list
+    pub unexpected: Vec<String>,
+    /// Files whose contents differ from the signed ones (replaced or swapped)
+    pub modified: Vec<String>,
+}
+
+impl IntegrityReport {
+    pub fn is_clean(&self) -> bool {
+        !self.forged
+            && !self.deleted
+            && self.missing.is_empty()
+            && self.unexpected.is_empty()
+            && self.modified.is_empty()
+    }
+
+    /// Leave out `files`, which this machine changed itself
+    pub fn ignore(&mut self, files: &[String]) {
+        for list in [&mut self.missing, &mut self.unexpected, &mut self.modified] {
+            list.retain(|file| !files.contains(file));
+        }
+    }
+
+    /// One line per problem, for warnings and logs
+    pub fn describe(&self) -> Vec<String> {
+        if self.forged {
+            return vec![format!(
+                "{} was modified without the encryption key",
+                INTEGRITY_FILE
+            )];
+        }
+        if self.deleted {
+            return vec![format!("{} was deleted", INTEGRITY_FILE)];
+        }
+        let missing = self.missing.iter().map(|f| format!("{} was deleted", f));
+        let unexpected = self.unexpected.iter().map(|f| format!("{} was added", f));
+        let modified = self.modified.iter().map(|f| format!("{} was replaced", f));
+        missing.chain(unexpected).chain(modified).collect()
+    }
+}
+
+impl IntegrityManifest {
+    pub fn load(sync_path: &Path) -> Result<Option<Self>> {
+        let path = sync_path.join(INTEGRITY_FILE);
+        if !path.exists() {
+            return Ok(None);
+        }
+        let content = std::fs::read_to_string(path)?;
+        Ok(Some(serde_json::from_str(&content)?))
+    }
+
+    /// HMAC over the signed fields, keyed by a MAC key derived from the encryption
+    /// key so that key is never used for two purposes
+    fn mac(&self, key: &[u8]) -> Result<HmacSha256> {
+        let mut derive = HmacSha256::new_from_slice(key)?;
+        derive.update(b"tether-integrity-key v1");
+        let mac_key = derive.finalize().into_bytes();
+
+        let mut mac = HmacSha256::new_from_slice(&mac_key)?;
+        mac.update(&serde_json::to_vec(&(
+            &self.signed_by,
+            self.signed_at,
+            &self.files,
+        ))?);
+        Ok(mac)
+    }
+
+    fn is_authentic(&self, key: &[u8]) -> bool {
+        let (Ok(expected), Ok(mac)) = (hex::decode(&self.mac), self.mac(key)) else {
+            return false;
+        };
+        mac.verify_slice(&expected).is_ok()
+    }
+}
+
+/// Ciphertext hash of every `.enc` file in the repo, by repo-relative path
+pub fn scan(sync_path: &Path) -> Result<BTreeMap<String, String>> {
+    let mut files = BTreeMap::new();
+    for entry in walkdir::WalkDir::new(sync_path)
+        .into_iter()
+        .filter_entry(|e| e.file_name() != ".git")
+    {
+        let entry = entry?;
+        if !entry.file_type().is_file() || entry.path().extension() != Some("enc".as_ref()) {
+            continue;
+        }
+        let rel = entry.path().strip_prefix(sync_path)?;
+        let rel = rel.to_string_lossy().replace('\\', "/");
+        files.insert(rel, crate::sha256_hex(&std::fs::read(entry.path())?));
+    }
+    Ok(files)
+}
+
+/// Re-sign the manifest from the repo's current encrypted files. Leaves it untouched
+/// when it already matches, so syncs that change nothing don't commit.
+pub fn sign(sync_path: &Path, key: &[u8], machine_id: &str) -> Result<()> {
+    let files = scan(sync_path)?;
+    if let Some(existing) = IntegrityManifest::load(sync_path).ok().flatten() {
+        if existing.files == files && existing.is_authentic(key) {
+            return Ok(());
+        }
+    }
+    let mut manifest = IntegrityManifest {
+        signed_by: machine_id.to_string(),
+        signed_at: Utc::now(),
+        files,
+        mac: String::new(),
+    };
+    manifest.mac = hex::encode(manifest.mac(key)?.finalize().into_bytes());
+    let content = serde_json::to_string_pretty(&manifest)? + "\n";
+    crate::sync::atomic_write(&sync_path.join(INTEGRITY_FILE), content.as_bytes())?;
+    mark_seen(sync_path)
+}
+
+/// Remember that this clone has a signed manifest, so losing it is reported. A no-op
+/// outside a git clone.
+fn mark_seen(sync_path: &Path) -> Result<()> {
+    if !sync_path.join(".git").is_dir() {
+        return Ok(());
+    }
+    let marker = sync_path.join(SEEN_MARKER);
+    if !marker.exists() {
+        std::fs::write(marker, b"")?;
+    }
+    Ok(())
+}
+
+/// Compare the repo's encrypted files with the signed manifest. `None` when no
+/// manifest has been signed yet; a manifest this clone has seen that is now gone is
+/// reported as `deleted`.
+pub fn verify(sync_path: &Path, key: &[u8]) -> Result<Option<IntegrityReport>> {
+    let Some(manifest) = IntegrityManifest::load(sync_path)? else {
+        if sync_path.join(SEEN_MARKER).exists() {
+            return Ok(Some(IntegrityReport {
+                deleted: true,
+                ..IntegrityReport::default()
+            }));
+        }
+        return Ok(None);
+    };
+    let mut report = IntegrityReport {
+        signed_by: manifest.signed_by.clone(),
+        ..IntegrityReport::default()
+    };
+    if !manifest.is_authentic(key) {
+        report.forged = true;
+        return Ok(Some(report));
+    }
+    mark_seen(sync_path)?;
+
+    let actual = scan(sync_path)?;
+    for (file, hash) in &manifest.files {
+        match actual.get(file) {
+            None => report.missing.push(file.clone()),
+            Some(actual_hash) if actual_hash != hash => report.modified.push(file.clone()),
+            Some(_) => {}
+        }
+    }
+    report.unexpected = actual
+        .keys()
+        .filter(|file| !manifest.files.contains_key(*file))
+        .cloned()
+        .collect();
+    Ok(Some(report))
+}
+
+/// Problems with the repo's encrypted files after a pull, one line each. Empty when
+/// dotfiles aren't encrypted or nothing has been signed yet. Files this machine's
+/// unpushed commits changed don't count: when a pull rebases them onto another
+/// machine's commit, the manifest is that machine's until this sync re-signs it.
+pub fn check(config: &Config, sync_path: &Path) -> Result<Vec<String>> {
+    if !config.security.encrypt_dotfiles {
+        return Ok(Vec::new());
+    }
+    let key = crate::security::get_encryption_key()?;
+    let Some(mut report) = verify(sync_path, &key)? else {
+        return Ok(Vec::new());
+    };
+    let unpushed = crate::sync::GitBackend::open(sync_path)
+        .and_then(|git| git.unpushed_files())
+        .unwrap_or_default();
+    report.ignore(&unpushed);
+    Ok(report.describe())
+}
+
+/// Re-sign the manifest before committing a sync, when dotfiles are encrypted
+pub fn sign_repo(config: &Config, sync_path: &Path, machine_id: &str) -> Result<()> {
+    if !config.security.encrypt_dotfiles {
+        return Ok(());
+    }
+    let key = crate::security::get_encryption_key()?;
+    sign(sync_path, &key, machine_id)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::TempDir;
+
+    fn write(root: &Path, rel: &str, content: &[u8]) {
+        let path = root.join(rel);
+        std::fs::create_dir_all(path.parent().unwrap()).unwrap();
+        std::fs::write(path, content).unwrap();
+    }
+
+    #[test]
+    fn test_sign_then_detect_tampering() {
+        let temp = TempDir::new().unwrap();
+        let root = temp.path();
+        let key = [7u8; 32];
+        write(root, "profiles/shared/zshrc.enc", b"zshrc-v1");
+        write(root, "profiles/shared/gitconfig.enc", b"gitconfig-v1");
+        write(root, "manifests/Brewfile", b"not encrypted");
+
+        assert!(verify(root, &key).unwrap().is_none());
+        sign(root, &key, "laptop").unwrap();
+        let report = verify(root, &key).unwrap().unwrap();
+        assert!(report.is_clean());
+        assert_eq!(report.signed_by, "laptop");
+
+        // Swap two files, drop one, add one; plain files aren't covered
+        write(root, "profiles/shared/zshrc.enc", b"gitconfig-v1");
+        std::fs::remove_file(root.join("profiles/shared/gitconfig.enc")).unwrap();
+        write(root, "profiles/shared/ssh/config.enc", b"old");
+        write(root, "manifests/Brewfile", b"changed");
+        let report = verify(root, &key).unwrap().unwrap();
+        assert_eq!(report.modified, vec!["profiles/shared/zshrc.enc"]);
+        assert_eq!(report.missing, vec!["profiles/shared/gitconfig.enc"]);
+        assert_eq!(report.unexpected, vec!["profiles/shared/ssh/config.enc"]);
+        assert_eq!(report.describe().len(), 3);
+        let mut own = verify(root, &key).unwrap().unwrap();
+        own.ignore(&["profiles/shared/ssh/config.enc".to_string()]);
+        assert!(own.unexpected.is_empty());
+        assert_eq!(own.describe().len(), 2);
+
+        // A legitimate sync re-signs the new state
+        sign(root, &key, "desktop").unwrap();
+        assert!(verify(root, &key).unwrap().unwrap().is_clean());
+    }
+
+    #[test]
+    fn test_manifest_edits_without_key_are_detected() {
+        let temp = TempDir::new().unwrap();
+        let root = temp.path();
+        let key = [7u8; 32];
+        write(root, "dotfiles/zshrc.enc", b"v1");
+        sign(root, &key, "laptop").unwrap();
+
+        // Re-hashing a replaced file into the manifest breaks the MAC
+        let mut manifest = IntegrityManifest::load(root).unwrap().unwrap();
+        write(root, "dotfiles/zshrc.enc", b"attacker");
+        manifest.files = scan(root).unwrap();
+        std::fs::write(
+            root.join(INTEGRITY_FILE),
+            serde_json::to_string(&manifest).unwrap(),
+        )
+        .unwrap();
+        let report = verify(root, &key).unwrap().unwrap();
+        assert!(report.forged);
+        assert!(!report.is_clean());
+
+        // So does signing with another key
+        sign(root, &[9u8; 32], "laptop").unwrap();
+        assert!(verify(root, &key).unwrap().unwrap().forged);
+    }
+
+    #[test]
+    fn test_deleted_manifest_is_detected() {
+        let temp = TempDir::new().unwrap();
+        let root = temp.path();
+        let key = [7u8; 32];
+        std::fs::create_dir(root.join(".git")).unwrap();
+        write(root, "dotfiles/zshrc.enc", b"v1");
+        assert!(verify(root, &key).unwrap().is_none());
+        sign(root, &key, "laptop").unwrap();
+
+        // Swapping a file and deleting the manifest with it doesn't pass as unsigned
+        write(root, "dotfiles/zshrc.enc", b"attacker");
+        std::fs::remove_file(root.join(INTEGRITY_FILE)).unwrap();
+        let report = verify(root, &key).unwrap().unwrap();
+        assert!(report.deleted);
+        assert!(!report.is_clean());
+        assert_eq!(report.describe(), vec!["integrity.json was deleted"]);
+
+        // Re-signing after the user accepts it clears the report
+        sign(root, &key, "laptop").unwrap();
+        assert!(verify(root, &key).unwrap().unwrap().is_clean());
+    }
+
+    #[test]
+    fn test_manifest_seen_on_pull_is_remembered() {
+        let temp = TempDir::new().unwrap();
+        let root = temp.path();
+        let key = [7u8; 32];
+        write(root, "dotfiles/zshrc.enc", b"v1");
+        sign(root, &key, "laptop").unwrap();
+
+        // A clone that only ever verified another machine's manifest
+        std::fs::create_dir(root.join(".git")).unwrap();
+        assert!(verify(root, &key).unwrap().unwrap().is_clean());
+        std::fs::remove_file(root.join(INTEGRITY_FILE)).unwrap();
+        assert!(verify(root, &key).unwrap().unwrap().deleted);
+    }
+}
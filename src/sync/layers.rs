@@ -6,8 +6,7 @@ use crate::sync::merge::{detect_file_type, merge_files, FileType};
 
 /// Get the layers directory (~/.tether/layers)
 pub fn layers_dir() -> Result<PathBuf> {
-    let home = crate::home_dir()?;
-    Ok(home.join(".tether").join("layers"))
+    Ok(crate::config::Config::config_dir()?.join("layers"))
 }
 
 /// Get the personal layer directory
@@ -22,8 +21,7 @@ pub fn team_layer_dir(team_name: &str) -> Result<PathBuf> {
 
 /// Get the merged output directory
 pub fn merged_dir() -> Result<PathBuf> {
-    let home = crate::home_dir()?;
-    Ok(home.join(".tether").join("merged"))
+    Ok(crate::config::Config::config_dir()?.join("merged"))
 }
 
 /// Initialize layer directories
@@ -0,0 +1,124 @@
+//! Syntax checks on changed dotfiles before they are pushed. Shell rc files go through
+//! `zsh -n`/`bash -n`/`sh -n` (when that shell is installed); JSON, TOML and YAML are
+//! parsed. Problems are reported as warnings only; the file is pushed regardless.
+
+use std::path::Path;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Format {
+    Shell(&'static str),
+    Json,
+    Toml,
+    Yaml,
+}
+
+fn format_of(file: &str) -> Option<Format> {
+    let name = file.rsplit('/').next().unwrap_or(file);
+    match name {
+        ".zshrc" | ".zprofile" | ".zshenv" | ".zlogin" | ".zlogout" => {
+            return Some(Format::Shell("zsh"))
+        }
+        ".bashrc" | ".bash_profile" | ".bash_login" | ".bash_logout" | ".bash_aliases" => {
+            return Some(Format::Shell("bash"))
+        }
+        ".profile" => return Some(Format::Shell("sh")),
+        _ => {}
+    }
+    match name.rsplit_once('.')?.1 {
+        "zsh" => Some(Format::Shell("zsh")),
+        "bash" => Some(Format::Shell("bash")),
+        "sh" => Some(Format::Shell("sh")),
+        "json" => Some(Format::Json),
+        "toml" => Some(Format::Toml),
+        "yaml" | "yml" => Some(Format::Yaml),
+        _ => None,
+    }
+}
+
+/// Why the dotfile `file` (home-relative, read from `path` as `content`) looks broken.
+/// `None` if it parses, or its format isn't one tether checks.
+pub fn check(file: &str, path: &Path, content: &[u8]) -> Option<String> {
+    let format = format_of(file)?;
+    if let Format::Shell(shell) = format {
+        which::which(shell).ok()?;
+        let args = [
+            shell.to_string(),
+            "-n".to_string(),
+            path.display().to_string(),
+        ];
+        return crate::sync::verify::run_check(&args)
+            .err()
+            .map(|e| format!("{} -n: {}", shell, e));
+    }
+
+    let text = std::str::from_utf8(content).ok()?;
+    match format {
+        Format::Json => match serde_json::from_str::<serde_json::Value>(text) {
+            // Editors such as VS Code accept comments in their JSON settings
+            Err(_) if text.contains("//") || text.contains("/*") => None,
+            Err(e) => Some(format!("invalid JSON: {}", e)),
+            Ok(_) => None,
+        },
+        Format::Toml => toml::from_str::<toml::Table>(text)
+            .err()
+            .map(|e| format!("invalid TOML: {}", e.message())),
+        Format::Yaml => yaml_rust2::YamlLoader::load_from_str(text)
+            .err()
+            .map(|e| format!("invalid YAML: {}", e)),
+        Format::Shell(_) => None,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn check_str(file: &str, content: &str) -> Option<String> {
+        check(file, Path::new(file), content.as_bytes())
+    }
+
+    #[test]
+    fn test_format_of() {
+        assert_eq!(format_of(".zshrc"), Some(Format::Shell("zsh")));
+        assert_eq!(
+            format_of(".config/fish/x.bash"),
+            Some(Format::Shell("bash"))
+        );
+        assert_eq!(format_of(".config/starship.toml"), Some(Format::Toml));
+        assert_eq!(format_of(".config/gh/config.yml"), Some(Format::Yaml));
+        assert_eq!(format_of(".gitconfig"), None);
+    }
+
+    #[test]
+    fn test_check_structured_formats() {
+        assert!(check_str("a.json", r#"{"a": 1}"#).is_none());
+        assert!(check_str("a.json", r#"{"a": 1,"#)
+            .unwrap()
+            .starts_with("invalid JSON"));
+        // JSONC is left alone
+        assert!(check_str("settings.json", "{\n  // comment\n  \"a\": 1,\n}").is_none());
+
+        assert!(check_str("a.toml", "a = 1\n[b]\nc = \"d\"\n").is_none());
+        assert!(check_str("a.toml", "a = \n")
+            .unwrap()
+            .starts_with("invalid TOML"));
+
+        assert!(check_str("a.yaml", "a:\n  - 1\n  - 2\n").is_none());
+        assert!(check_str("a.yml", "a: [1, 2\n")
+            .unwrap()
+            .starts_with("invalid YAML"));
+    }
+
+    #[test]
+    fn test_check_shell() {
+        let temp = tempfile::TempDir::new().unwrap();
+        let path = temp.path().join("rc.sh");
+        std::fs::write(&path, "if true; then\n  echo hi\n").unwrap();
+        assert!(check("rc.sh", &path, b"")
+            .unwrap()
+            .starts_with("sh -n: exit"));
+
+        std::fs::write(&path, "if true; then\n  echo hi\nfi\n").unwrap();
+        assert!(check("rc.sh", &path, b"").is_none());
+    }
+}
@@ -1,5 +1,6 @@
 use anyhow::{Context, Result};
 use std::fs;
+use std::ops::Range;
 use std::path::Path;
 
 /// File types and how to handle them
@@ -134,6 +135,102 @@ fn deep_merge_json(base: serde_json::Value, overlay: serde_json::Value) -> serde
     }
 }
 
+/// A run of changed lines on one side, as base and side line ranges
+struct Hunk {
+    base: Range<usize>,
+    side: Range<usize>,
+}
+
+fn diff_hunks(base: &[&str], side: &[&str]) -> Vec<Hunk> {
+    let mut hunks: Vec<Hunk> = Vec::new();
+    for op in similar::capture_diff_slices(similar::Algorithm::Myers, base, side) {
+        let (tag, old, new) = op.as_tag_tuple();
+        if tag == similar::DiffTag::Equal {
+            continue;
+        }
+        match hunks.last_mut() {
+            Some(last) if last.base.end == old.start && last.side.end == new.start => {
+                last.base.end = old.end;
+                last.side.end = new.end;
+            }
+            _ => hunks.push(Hunk {
+                base: old,
+                side: new,
+            }),
+        }
+    }
+    hunks
+}
+
+/// One side's lines for the base region `start..end`, given its hunks inside it
+fn side_region<'a>(
+    base: &[&'a str],
+    side: &[&'a str],
+    hunks: &[&Hunk],
+    start: usize,
+    end: usize,
+) -> Vec<&'a str> {
+    match (hunks.first(), hunks.last()) {
+        (Some(first), Some(last)) => {
+            let side_start = first.side.start - (first.base.start - start);
+            let side_end = last.side.end + (end - last.base.end);
+            side[side_start..side_end].to_vec()
+        }
+        _ => base[start..end].to_vec(),
+    }
+}
+
+/// Line-based three-way merge (like `git merge-file`) of two edits of `base`.
+/// `None` when both sides changed the same or adjacent lines differently.
+pub fn three_way_merge(base: &str, local: &str, remote: &str) -> Option<String> {
+    let base_lines: Vec<&str> = base.split_inclusive('\n').collect();
+    let local_lines: Vec<&str> = local.split_inclusive('\n').collect();
+    let remote_lines: Vec<&str> = remote.split_inclusive('\n').collect();
+
+    let local_hunks = diff_hunks(&base_lines, &local_lines);
+    let remote_hunks = diff_hunks(&base_lines, &remote_lines);
+    let mut all: Vec<(bool, &Hunk)> = local_hunks
+        .iter()
+        .map(|h| (true, h))
+        .chain(remote_hunks.iter().map(|h| (false, h)))
+        .collect();
+    all.sort_by_key(|(_, h)| (h.base.start, h.base.end));
+
+    let mut merged = String::with_capacity(local.len().max(remote.len()));
+    let mut pos = 0;
+    let mut i = 0;
+    while i < all.len() {
+        // Group hunks whose base ranges overlap or touch
+        let start = all[i].1.base.start;
+        let mut end = all[i].1.base.end;
+        let mut j = i + 1;
+        while j < all.len() && all[j].1.base.start <= end {
+            end = end.max(all[j].1.base.end);
+            j += 1;
+        }
+        let group = &all[i..j];
+        let local_group: Vec<&Hunk> = group.iter().filter(|(l, _)| *l).map(|(_, h)| *h).collect();
+        let remote_group: Vec<&Hunk> = group.iter().filter(|(l, _)| !*l).map(|(_, h)| *h).collect();
+
+        let local_region = side_region(&base_lines, &local_lines, &local_group, start, end);
+        let remote_region = side_region(&base_lines, &remote_lines, &remote_group, start, end);
+        let region = if remote_group.is_empty() {
+            local_region
+        } else if local_group.is_empty() || local_region == remote_region {
+            remote_region
+        } else {
+            return None;
+        };
+
+        merged.extend(base_lines[pos..start].iter().copied());
+        merged.extend(region);
+        pos = end;
+        i = j;
+    }
+    merged.extend(base_lines[pos..].iter().copied());
+    Some(merged)
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -208,4 +305,34 @@ t = "test --release"
         assert_eq!(val["b"]["y"], 20); // team preserved
         assert_eq!(val["c"], 3); // personal addition
     }
+
+    #[test]
+    fn test_three_way_merge_disjoint_edits() {
+        let base = "a\nb\nc\nd\ne\n";
+        let local = "a\nB\nc\nd\ne\nf\n";
+        let remote = "z\na\nb\nc\nD\ne\n";
+        assert_eq!(
+            three_way_merge(base, local, remote).as_deref(),
+            Some("z\na\nB\nc\nD\ne\nf\n")
+        );
+
+        // Identical edits on both sides merge to that edit
+        assert_eq!(three_way_merge(base, local, local).as_deref(), Some(local));
+
+        // Missing trailing newline survives
+        assert_eq!(
+            three_way_merge("a\nb\nc", "A\nb\nc", "a\nb\nc\nd").as_deref(),
+            Some("A\nb\nc\nd")
+        );
+    }
+
+    #[test]
+    fn test_three_way_merge_overlapping_edits_conflict() {
+        let base = "a\nb\nc\n";
+        assert_eq!(three_way_merge(base, "a\nX\nc\n", "a\nY\nc\n"), None);
+        // Adjacent lines count as overlapping, as with git
+        assert_eq!(three_way_merge(base, "A\nb\nc\n", "a\nB\nc\n"), None);
+        // Both appending different lines at the end
+        assert_eq!(three_way_merge(base, "a\nb\nc\nx\n", "a\nb\nc\ny\n"), None);
+    }
 }
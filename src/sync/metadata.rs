@@ -0,0 +1,137 @@
+//! Package manifests (`manifests/`) and machine states (`machines/`) in the sync repo.
+//!
+//! They're plaintext by default, which shows anyone who can read the repo what each
+//! machine has installed. With `security.encrypt_manifests` every write stores the
+//! file encrypted with the dotfile key as `<name>.enc` and drops the plaintext copy.
+//! Reads accept either form, so machines that haven't written under the new setting
+//! yet keep working.
+
+use anyhow::Result;
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicBool, Ordering};
+
+static ENCRYPT: AtomicBool = AtomicBool::new(false);
+
+/// Apply `security.encrypt_manifests` to writes in this process
+pub fn set_encrypt(enabled: bool) {
+    ENCRYPT.store(enabled, Ordering::Relaxed);
+}
+
+fn encrypted_path(path: &Path) -> PathBuf {
+    let mut name = path.as_os_str().to_owned();
+    name.push(".enc");
+    PathBuf::from(name)
+}
+
+/// `name` without the `.enc` suffix of an encrypted copy
+pub fn plain_name(name: &str) -> &str {
+    name.strip_suffix(".enc").unwrap_or(name)
+}
+
+/// Whether `path` is stored in either form
+pub fn exists(path: &Path) -> bool {
+    path.exists() || encrypted_path(path).exists()
+}
+
+/// Contents of `path`, decrypted from `path.enc` if that's how it's stored
+pub fn read(path: &Path) -> Result<Option<Vec<u8>>> {
+    read_as(path, crate::security::get_encryption_key)
+}
+
+/// `read`, decrypting with the key from `key`. It's only asked for when the file is
+/// encrypted, and each time, so a locked or rotated key is noticed.
+fn read_as(path: &Path, key: impl FnOnce() -> Result<Vec<u8>>) -> Result<Option<Vec<u8>>> {
+    let encrypted = encrypted_path(path);
+    if encrypted.exists() {
+        let content = std::fs::read(&encrypted)?;
+        return Ok(Some(crate::security::decrypt(&content, &key()?)?));
+    }
+    if path.exists() {
+        return Ok(Some(std::fs::read(path)?));
+    }
+    Ok(None)
+}
+
+pub fn read_to_string(path: &Path) -> Result<Option<String>> {
+    read(path)?
+        .map(|content| Ok(String::from_utf8(content)?))
+        .transpose()
+}
+
+/// Store `content` at `path`, or encrypted at `path.enc` with
+/// `security.encrypt_manifests`, and remove the copy in the other form. An encrypted
+/// copy that already holds `content` is left alone, so unchanged manifests don't get
+/// new ciphertext (and a commit) every sync.
+pub fn write(path: &Path, content: &[u8]) -> Result<()> {
+    let key = if ENCRYPT.load(Ordering::Relaxed) {
+        Some(crate::security::get_encryption_key()?)
+    } else {
+        None
+    };
+    write_as(path, content, key.as_deref())
+}
+
+/// `write`, encrypted with `key` or as plaintext without one
+fn write_as(path: &Path, content: &[u8], key: Option<&[u8]>) -> Result<()> {
+    let encrypted = encrypted_path(path);
+    if let Some(key) = key {
+        let unchanged = std::fs::read(&encrypted)
+            .ok()
+            .and_then(|existing| crate::security::decrypt(&existing, key).ok())
+            .is_some_and(|existing| existing == content);
+        if !unchanged {
+            let ciphertext = crate::security::encrypt(content, key)?;
+            crate::sync::atomic_write(&encrypted, &ciphertext)?;
+        }
+        if path.exists() {
+            std::fs::remove_file(path)?;
+        }
+    } else {
+        crate::sync::atomic_write(path, content)?;
+        if encrypted.exists() {
+            std::fs::remove_file(&encrypted)?;
+        }
+    }
+    Ok(())
+}
+
+/// Remove `path` in whichever form it's stored
+pub fn remove(path: &Path) -> Result<()> {
+    for path in [path.to_path_buf(), encrypted_path(path)] {
+        if path.exists() {
+            std::fs::remove_file(path)?;
+        }
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::TempDir;
+
+    #[test]
+    fn test_write_switches_form() {
+        let temp = TempDir::new().unwrap();
+        let path = temp.path().join("Brewfile");
+        let key = [3u8; 32];
+        let read = |path: &Path| read_as(path, || Ok(key.to_vec()));
+
+        write_as(&path, b"brew \"git\"\n", Some(&key)).unwrap();
+        assert!(!path.exists());
+        let ciphertext = std::fs::read(encrypted_path(&path)).unwrap();
+        assert!(!String::from_utf8_lossy(&ciphertext).contains("git"));
+        assert_eq!(read(&path).unwrap().unwrap(), b"brew \"git\"\n");
+
+        // Same content keeps the same ciphertext
+        write_as(&path, b"brew \"git\"\n", Some(&key)).unwrap();
+        assert_eq!(std::fs::read(encrypted_path(&path)).unwrap(), ciphertext);
+
+        write_as(&path, b"brew \"jq\"\n", None).unwrap();
+        assert!(!encrypted_path(&path).exists());
+        assert_eq!(read_to_string(&path).unwrap().unwrap(), "brew \"jq\"\n");
+
+        remove(&path).unwrap();
+        assert!(!exists(&path));
+    }
+}
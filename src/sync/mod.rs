@@ -1,32 +1,54 @@
 pub mod backup;
+pub mod chunks;
 pub mod conflict;
+pub mod deletions;
+pub mod dir_manifest;
 pub mod discovery;
 pub mod engine;
+pub mod freeze;
 pub mod git;
+pub mod growth;
+pub mod integrity;
 pub mod layers;
+pub mod lint;
 pub mod merge;
+pub mod metadata;
+pub mod orphans;
 pub mod packages;
+pub mod personal_secrets;
+pub mod project_index;
+pub mod rekey;
+pub mod repo_check;
+pub mod report;
+pub mod stale;
 pub mod state;
 pub mod team;
+pub mod verify;
+pub mod visibility;
 
 pub use backup::{
-    backup_file, backups_dir, create_backup_dir, list_backup_files, list_backups,
-    prune_old_backups, restore_file,
+    backup_file, backups_dir, create_backup_dir, create_conflict_backup_dir, list_backup_files,
+    list_backups, prune_old_backups, restore_file, restore_hint, snapshot_before_resolution,
 };
 pub use conflict::{
-    detect_conflict, notify_conflict, notify_conflicts, notify_deferred_casks, ConflictResolution,
-    ConflictState, FileConflict, PendingConflict,
+    binary_summary, detect_conflict, is_binary, last_write_wins, notify_conflict, notify_conflicts,
+    notify_deferred_casks, notify_drift_report, notify_integrity_warning, notify_pending_installs,
+    notify_public_repo, notify_stale_files, AutoResolution, ConflictResolution, ConflictState,
+    FileConflict, PendingConflict, ResolvedSide,
 };
 pub use discovery::discover_sourced_dirs;
-pub use engine::SyncEngine;
+pub use engine::{SyncEngine, SyncMode};
 pub use git::{checkout_id_from_path, extract_org_from_normalized_url, FileLogEntry, GitBackend};
 pub use layers::{
     init_layers, list_team_layer_files, map_team_to_personal_name, merge_layers, remerge_all,
     sync_dotfile_with_layers, sync_team_to_layer, LayerSyncResult,
 };
 pub use merge::{detect_file_type, merge_files, FileType};
-pub use packages::{import_packages, sync_packages};
-pub use state::{CheckoutInfo, FileState, MachineState, SyncState};
+pub use packages::{append_to_manifest, import_packages, sync_packages};
+pub use state::{
+    CheckoutInfo, FileChanges, FileState, MachineDiff, MachineState, MachineSummary,
+    PackageChanges, SyncState, Tombstone,
+};
 pub use team::{
     default_local_patterns, discover_symlinkable_dirs, extract_org_from_url,
     extract_team_name_from_url, find_team_for_project, get_project_org, glob_match, is_local_file,
@@ -123,7 +145,6 @@ pub fn migrate_repo_to_profiled(
     }
 
     let encrypted = config.security.encrypt_dotfiles;
-    let profile_name = config.profile_name(machine_id);
     let mut migrated_any = false;
 
     if let Some(entries) = config.profile_dotfiles(machine_id) {
@@ -131,6 +152,7 @@ pub fn migrate_repo_to_profiled(
         for entry in entries {
             let pattern = entry.path();
             let shared = entry.shared();
+            let profile_name = config.dotfile_profile(machine_id, pattern);
 
             // Expand glob patterns by scanning flat layout
             let expanded = if is_glob_pattern(pattern) && encrypted {
@@ -211,6 +233,34 @@ fn version_gte(version: &str, min: &str) -> bool {
     parse(version) >= parse(min)
 }
 
+/// Whether a peer running tether `peer_version` may write to the sync repo in ways
+/// this version doesn't understand: a different major version, or a newer minor one.
+/// Patch releases and unknown (pre-1.11) versions don't count.
+pub fn version_incompatible(peer_version: &str) -> bool {
+    let major_minor = |s: &str| -> Option<(u32, u32)> {
+        let mut parts = s.split('-').next()?.split('.');
+        Some((parts.next()?.parse().ok()?, parts.next()?.parse().ok()?))
+    };
+    let (Some((major, minor)), Some((peer_major, peer_minor))) = (
+        major_minor(env!("CARGO_PKG_VERSION")),
+        major_minor(peer_version),
+    ) else {
+        return false;
+    };
+    peer_major != major || peer_minor > minor
+}
+
+/// Peers that run a tether version incompatible with this one
+pub fn incompatible_peers<'a>(
+    machines: &'a [crate::sync::state::MachineState],
+    machine_id: &str,
+) -> Vec<&'a crate::sync::state::MachineState> {
+    machines
+        .iter()
+        .filter(|m| m.machine_id != machine_id && version_incompatible(&m.cli_version))
+        .collect()
+}
+
 /// Check sync repo format version. Creates file if missing, errors if newer than supported.
 pub fn check_sync_format_version(sync_path: &Path) -> Result<()> {
     let version_file = sync_path.join("format_version");
@@ -240,7 +290,7 @@ pub fn check_sync_format_version(sync_path: &Path) -> Result<()> {
 pub fn acquire_sync_lock(wait: bool) -> Result<File> {
     use fs2::FileExt;
 
-    let lock_path = crate::home_dir()?.join(".tether/sync.lock");
+    let lock_path = crate::config::Config::config_dir()?.join("sync.lock");
     std::fs::create_dir_all(lock_path.parent().unwrap())?;
     let file = std::fs::OpenOptions::new()
         .create(true)
@@ -274,13 +324,78 @@ pub fn canonical_project_file_path(normalized_url: &str, rel_path: &str) -> Resu
         anyhow::bail!("Absolute paths not allowed in project path");
     }
 
-    let home = crate::home_dir()?;
-    Ok(home
-        .join(".tether/projects")
+    Ok(crate::config::Config::config_dir()?
+        .join("projects")
         .join(normalized_url)
         .join(rel_path))
 }
 
+/// Whether a project config pattern matches a file at `rel_path` inside a checkout.
+/// `**` patterns (e.g. `.idea/**`) match the whole relative path, others the file name.
+pub fn project_pattern_matches(pattern: &str, rel_path: &str) -> bool {
+    if pattern.contains("**") {
+        glob_match(&pattern.replace("**", "*"), rel_path)
+    } else {
+        let file_name = rel_path.rsplit('/').next().unwrap_or(rel_path);
+        glob_match(pattern, file_name)
+    }
+}
+
+/// Cache and state files that churn on every run, skipped in synced dirs unless
+/// `dotfiles.default_excludes = false`
+pub const DEFAULT_DIR_EXCLUDES: &[&str] = &[
+    ".DS_Store",
+    "*.swp",
+    "*.swo",
+    "*.un~",
+    ".zcompdump*",
+    "fish_variables",
+    "fish_history",
+    "nvim/undo/**",
+    "nvim/swap/**",
+    "nvim/shada/**",
+];
+
+/// Whether a file inside a synced dir (`rel_path`, relative to home) is excluded.
+/// Patterns with a `/` match any trailing part of the path, others the file name.
+/// Later patterns win, so a user's `!pattern` overrides a built-in exclusion.
+pub fn is_dir_file_excluded(dotfiles: &crate::config::DotfilesConfig, rel_path: &str) -> bool {
+    let defaults = DEFAULT_DIR_EXCLUDES
+        .iter()
+        .copied()
+        .filter(|_| dotfiles.default_excludes);
+    let mut excluded = false;
+    for pattern in defaults.chain(dotfiles.exclude.iter().map(String::as_str)) {
+        let (negated, pattern) = match pattern.strip_prefix('!') {
+            Some(p) => (true, p),
+            None => (false, pattern),
+        };
+        if exclude_pattern_matches(pattern, rel_path) {
+            excluded = !negated;
+        }
+    }
+    excluded
+}
+
+/// Whether a directory (`rel_path`, relative to a project search path or repo root)
+/// matches `project_configs.exclude_dirs` and shouldn't be scanned
+pub fn is_project_dir_excluded(exclude_dirs: &[String], rel_path: &str) -> bool {
+    exclude_dirs
+        .iter()
+        .any(|pattern| exclude_pattern_matches(pattern.trim_end_matches('/'), rel_path))
+}
+
+fn exclude_pattern_matches(pattern: &str, rel_path: &str) -> bool {
+    if !pattern.contains('/') {
+        let file_name = rel_path.rsplit('/').next().unwrap_or(rel_path);
+        return glob_match(pattern, file_name);
+    }
+    let pattern = pattern.replace("**", "*");
+    std::iter::once(rel_path)
+        .chain(rel_path.match_indices('/').map(|(i, _)| &rel_path[i + 1..]))
+        .any(|suffix| glob_match(&pattern, suffix))
+}
+
 /// Check if a pattern contains glob metacharacters
 pub fn is_glob_pattern(pattern: &str) -> bool {
     pattern.contains('*') || pattern.contains('?') || pattern.contains('[')
@@ -498,6 +613,32 @@ mod tests {
         assert_eq!(result, "profiles/dev/zshrc.enc");
     }
 
+    #[test]
+    fn test_is_dir_file_excluded() {
+        let mut dotfiles = crate::config::Config::default().dotfiles;
+        assert!(is_dir_file_excluded(&dotfiles, ".config/nvim/.DS_Store"));
+        assert!(is_dir_file_excluded(
+            &dotfiles,
+            ".local/state/nvim/undo/%foo"
+        ));
+        assert!(is_dir_file_excluded(
+            &dotfiles,
+            ".config/fish/fish_variables"
+        ));
+        assert!(!is_dir_file_excluded(&dotfiles, ".config/nvim/init.lua"));
+        assert!(!is_dir_file_excluded(&dotfiles, ".config/undo/notes"));
+
+        dotfiles.exclude = vec!["*.log".into(), "!fish_variables".into()];
+        assert!(is_dir_file_excluded(&dotfiles, ".config/app/debug.log"));
+        assert!(!is_dir_file_excluded(
+            &dotfiles,
+            ".config/fish/fish_variables"
+        ));
+
+        dotfiles.default_excludes = false;
+        assert!(!is_dir_file_excluded(&dotfiles, ".config/nvim/.DS_Store"));
+    }
+
     #[test]
     fn test_file_log_entry_parse() {
         let line =
@@ -644,7 +785,7 @@ mod tests {
         };
         config.profiles.clear();
         config.dotfiles.files = dotfiles;
-        config.dotfiles.dirs = dirs;
+        config.dotfiles.dirs = dirs.into_iter().map(Into::into).collect();
         if let Some(pkg) = packages_override {
             config.packages = pkg;
         }
@@ -709,6 +850,9 @@ mod tests {
                 }],
                 dirs: vec![],
                 packages: vec![],
+                extends: None,
+                role: None,
+                server: None,
             },
         );
 
@@ -774,6 +918,9 @@ mod tests {
                 ],
                 dirs: vec![],
                 packages: vec![],
+                extends: None,
+                role: None,
+                server: None,
             },
         );
         config.profiles.insert(
@@ -784,6 +931,9 @@ mod tests {
                 )],
                 dirs: vec![],
                 packages: vec![],
+                extends: None,
+                role: None,
+                server: None,
             },
         );
         config
@@ -934,6 +1084,22 @@ mod tests {
         assert!(!version_gte("", "1.11.0"));
     }
 
+    #[test]
+    fn test_version_incompatible() {
+        let (major, minor) = {
+            let mut parts = env!("CARGO_PKG_VERSION").split('.');
+            let major: u32 = parts.next().unwrap().parse().unwrap();
+            let minor: u32 = parts.next().unwrap().parse().unwrap();
+            (major, minor)
+        };
+        assert!(!version_incompatible(env!("CARGO_PKG_VERSION")));
+        assert!(!version_incompatible(&format!("{}.{}.999", major, minor)));
+        assert!(version_incompatible(&format!("{}.{}.0", major, minor + 1)));
+        assert!(version_incompatible(&format!("{}.0.0", major + 1)));
+        assert!(!version_incompatible(&format!("{}.0.0", major)));
+        assert!(!version_incompatible(""));
+    }
+
     #[test]
     fn test_cleanup_legacy_skips_when_old_machine() {
         let tmp = TempDir::new().unwrap();
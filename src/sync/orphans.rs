@@ -0,0 +1,151 @@
+//! Detection of sync repo files that no machine's config references anymore.
+
+use crate::config::Config;
+use std::collections::HashSet;
+
+/// Repo paths under `profiles/` and `configs/` that no machine would sync.
+///
+/// A path is kept if, for any of `machine_ids`, it's the repo location of one of that
+/// machine's effective dotfiles (glob patterns match against the stored name) or lives
+/// under one of its effective dirs. The synced tether config is always kept.
+pub fn orphaned_repo_files(
+    config: &Config,
+    machine_ids: &[String],
+    tracked: &[String],
+) -> Vec<String> {
+    let encrypted = config.security.encrypt_dotfiles;
+
+    // Exact repo paths, (profile subdir, glob) pairs and config dir prefixes
+    // across all machines
+    let mut expected: HashSet<String> = HashSet::new();
+    let mut globs: Vec<(String, glob::Pattern)> = Vec::new();
+    let mut dirs: Vec<String> = Vec::new();
+    for machine_id in machine_ids {
+        for entry in config.effective_dotfiles(machine_id) {
+            let pattern = entry.path();
+            let shared = config.is_dotfile_shared(machine_id, pattern);
+            let profile = config.dotfile_profile(machine_id, pattern);
+            if crate::sync::is_glob_pattern(pattern) {
+                if let Ok(compiled) = glob::Pattern::new(pattern) {
+                    let subdir = if shared { "shared" } else { profile };
+                    globs.push((subdir.to_string(), compiled));
+                }
+            } else {
+                expected.insert(crate::sync::dotfile_to_repo_path_profiled(
+                    pattern, encrypted, profile, shared,
+                ));
+            }
+        }
+        for dir in config.effective_dirs(machine_id) {
+            let dir = dir.strip_prefix("~/").unwrap_or(&dir).trim_end_matches('/');
+            dirs.push(format!("configs/{}", dir));
+        }
+    }
+
+    tracked
+        .iter()
+        .filter(|path| {
+            if path.starts_with("configs/") {
+                if path.starts_with("configs/tether/") {
+                    return false;
+                }
+                let no_enc = path.strip_suffix(".enc").unwrap_or(path);
+                return !dirs
+                    .iter()
+                    .any(|d| no_enc == d || path.starts_with(&format!("{}/", d)));
+            }
+            let Some(rest) = path.strip_prefix("profiles/") else {
+                return false;
+            };
+            if expected.contains(path.as_str()) {
+                return false;
+            }
+            let Some((subdir, name)) = rest.split_once('/') else {
+                return false;
+            };
+            // Stored name -> dotfile path (encrypted names drop the leading dot)
+            let dotfile = if encrypted {
+                match name.strip_suffix(".enc") {
+                    Some(n) => format!(".{}", n),
+                    None => return true,
+                }
+            } else {
+                name.to_string()
+            };
+            !globs
+                .iter()
+                .any(|(dir, glob)| dir == subdir && glob.matches(&dotfile))
+        })
+        .cloned()
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::config::{ProfileConfig, ProfileDotfileEntry};
+
+    fn tracked(paths: &[&str]) -> Vec<String> {
+        paths.iter().map(|p| p.to_string()).collect()
+    }
+
+    #[test]
+    fn test_orphans_across_machines_and_profiles() {
+        let mut config = Config::default();
+        config.dotfiles.files.clear();
+        config.profiles.insert(
+            "dev".to_string(),
+            ProfileConfig {
+                dotfiles: vec![
+                    ProfileDotfileEntry::Simple(".zshrc".to_string()),
+                    ProfileDotfileEntry::WithOptions {
+                        path: ".gitconfig".to_string(),
+                        shared: true,
+                        create_if_missing: false,
+                    },
+                    ProfileDotfileEntry::Simple(".claude/*.json".to_string()),
+                ],
+                dirs: vec!["~/.config/nvim".into()],
+                ..Default::default()
+            },
+        );
+        config.profiles.insert(
+            "server".to_string(),
+            ProfileConfig {
+                dotfiles: vec![ProfileDotfileEntry::Simple(".tmux.conf".to_string())],
+                ..Default::default()
+            },
+        );
+        config
+            .machine_profiles
+            .insert("box".to_string(), "server".to_string());
+
+        let files = tracked(&[
+            "profiles/dev/zshrc.enc",
+            "profiles/dev/vimrc.enc",
+            "profiles/dev/claude/settings.json.enc",
+            "profiles/shared/gitconfig.enc",
+            "profiles/server/tmux.conf.enc",
+            "profiles/server/zshrc.enc",
+            "profiles/old/zshrc.enc",
+            "configs/.config/nvim/init.lua.enc",
+            "configs/.config/htop/htoprc.enc",
+            "configs/tether/config.toml.enc",
+        ]);
+        let machines = vec!["laptop".to_string(), "box".to_string()];
+        let orphans = orphaned_repo_files(&config, &machines, &files);
+        assert_eq!(
+            orphans,
+            tracked(&[
+                "profiles/dev/vimrc.enc",
+                "profiles/server/zshrc.enc",
+                "profiles/old/zshrc.enc",
+                "configs/.config/htop/htoprc.enc",
+            ])
+        );
+
+        // Without the server machine, its profile's files are orphaned too
+        let orphans = orphaned_repo_files(&config, &machines[..1], &files);
+        assert!(orphans.contains(&"profiles/server/tmux.conf.enc".to_string()));
+    }
+}
@@ -1,13 +1,13 @@
 use crate::cli::Output;
-use crate::config::Config;
+use crate::config::{Config, PackageTrust, PackagesMode};
 use crate::packages::{
-    normalize_formula_name, BrewManager, BrewfilePackages, BunManager, GemManager, NpmManager,
-    PackageManager, PnpmManager, UvManager,
+    brewfile_entry, manifest_entry, manifest_packages, normalize_formula_name, BrewManager,
+    BrewfilePackages, BunManager, GemManager, NpmManager, PackageManager, PnpmManager, UvManager,
 };
 use crate::sync::state::PackageState;
-use crate::sync::{MachineState, SyncState};
+use crate::sync::{metadata, MachineState, SyncState};
 use anyhow::Result;
-use std::collections::{HashMap, HashSet};
+use std::collections::{BTreeMap, HashMap, HashSet};
 use std::path::Path;
 
 /// Definition of a package manager for sync purposes
@@ -49,7 +49,11 @@ const SIMPLE_MANAGERS: &[PackageManagerDef] = &[
 ];
 
 /// Import packages from manifests, installing only missing packages.
-/// In daemon mode, casks are deferred (require password).
+/// In daemon mode, casks are deferred (require password). Server-mode machines
+/// skip casks entirely. With `packages.mode = "manifest-authoritative"` packages
+/// uninstalled on this machine are reinstalled and unlisted ones are removed. With
+/// `packages.approve_installs` missing packages are queued in `state.pending_installs`
+/// instead of installed. Packages `packages.trust` doesn't allow are skipped.
 /// Returns list of deferred casks (empty if not in daemon mode).
 pub async fn import_packages(
     config: &Config,
@@ -66,6 +70,22 @@ pub async fn import_packages(
 
     let mid = &machine_state.machine_id;
     let mut deferred_casks = Vec::new();
+    let mut queue = config
+        .packages
+        .approve_installs
+        .then(BTreeMap::<String, Vec<String>>::new);
+    let authoritative = config.packages.mode == PackagesMode::ManifestAuthoritative;
+    // The manifest decides, not what was uninstalled here
+    let authoritative_state;
+    let machine_state = if authoritative {
+        authoritative_state = MachineState {
+            removed_packages: HashMap::new(),
+            ..machine_state.clone()
+        };
+        &authoritative_state
+    } else {
+        machine_state
+    };
 
     // Homebrew - special handling for formulae/casks/taps
     if config.is_manager_enabled(mid, "brew") {
@@ -73,7 +93,10 @@ pub async fn import_packages(
             &manifests_dir,
             machine_state,
             daemon_mode,
+            config.is_server(mid),
             previously_deferred,
+            &config.packages.trust,
+            queue.as_mut(),
         )
         .await;
         deferred_casks = casks;
@@ -86,16 +109,95 @@ pub async fn import_packages(
     // Simple package managers (npm, pnpm, bun, gem)
     for def in SIMPLE_MANAGERS {
         if config.is_manager_enabled(mid, def.state_key) {
-            let installed = import_simple_manager(def, &manifests_dir, machine_state).await;
+            let installed = import_simple_manager(
+                def,
+                &manifests_dir,
+                machine_state,
+                &config.packages.trust,
+                queue.as_mut(),
+            )
+            .await;
             if installed {
                 update_last_upgrade(state, def.state_key);
             }
         }
     }
 
+    if authoritative {
+        remove_unlisted_packages(config, mid, &manifests_dir).await;
+    }
+    state.pending_installs = queue.unwrap_or_default();
+
     Ok(deferred_casks)
 }
 
+fn simple_manager(state_key: &str) -> Option<Box<dyn PackageManager>> {
+    match state_key {
+        "npm" => Some(Box::new(NpmManager::new())),
+        "pnpm" => Some(Box::new(PnpmManager::new())),
+        "bun" => Some(Box::new(BunManager::new())),
+        "gem" => Some(Box::new(GemManager::new())),
+        "uv" => Some(Box::new(UvManager::new())),
+        _ => None,
+    }
+}
+
+/// Uninstall packages the enabled managers' manifests don't list. Managers without a
+/// manifest, or with an empty one, are left alone.
+async fn remove_unlisted_packages(config: &Config, machine_id: &str, manifests_dir: &Path) {
+    let mut managers: Vec<(&str, &str, Box<dyn PackageManager>)> = Vec::new();
+    if config.is_manager_enabled(machine_id, "brew") {
+        managers.push(("brew", "Brewfile", Box::new(BrewManager::new())));
+    }
+    for def in SIMPLE_MANAGERS {
+        if config.is_manager_enabled(machine_id, def.state_key) {
+            if let Some(manager) = simple_manager(def.state_key) {
+                managers.push((def.display_name, def.manifest_file, manager));
+            }
+        }
+    }
+
+    for (name, manifest_file, manager) in managers {
+        let Ok(Some(manifest)) = metadata::read_to_string(&manifests_dir.join(manifest_file))
+        else {
+            continue;
+        };
+        if !manager.is_available().await {
+            continue;
+        }
+        match manager.remove_unlisted(&manifest).await {
+            Ok(removed) if !removed.is_empty() => Output::info(&format!(
+                "Removed {} {} package{} not in {}: {}",
+                removed.len(),
+                name,
+                if removed.len() == 1 { "" } else { "s" },
+                manifest_file,
+                removed.join(", ")
+            )),
+            Ok(_) => {}
+            Err(e) => Output::warning(&format!(
+                "Failed to remove unlisted {} packages: {}",
+                name, e
+            )),
+        }
+    }
+}
+
+/// Drop packages `packages.trust` doesn't allow, with a warning for each, so a peer
+/// machine can't get arbitrary packages installed here
+fn retain_trusted(packages: &mut Vec<String>, kind: &str, allowed: impl Fn(&str) -> bool) {
+    packages.retain(|p| {
+        let trusted = allowed(p);
+        if !trusted {
+            Output::warning(&format!(
+                "Not installing {} {}: not allowed by packages.trust",
+                kind, p
+            ));
+        }
+        trusted
+    });
+}
+
 /// Update last_upgrade timestamp for a package manager
 fn update_last_upgrade(state: &mut SyncState, manager: &str) {
     let now = chrono::Utc::now();
@@ -113,15 +215,19 @@ fn update_last_upgrade(state: &mut SyncState, manager: &str) {
 
 /// Import brew packages (formulae, casks, taps).
 /// Casks are installed individually to detect which need password.
+/// With a `queue`, missing formulae and casks are added to it instead of installed.
 /// Returns (deferred_casks, installed_any) - list of casks needing password and whether any packages were installed.
 async fn import_brew(
     manifests_dir: &Path,
     machine_state: &MachineState,
     daemon_mode: bool,
+    skip_casks: bool,
     previously_deferred: &[String],
+    trust: &PackageTrust,
+    queue: Option<&mut BTreeMap<String, Vec<String>>>,
 ) -> (Vec<String>, bool) {
     let brewfile = manifests_dir.join("Brewfile");
-    if !brewfile.exists() {
+    if !metadata::exists(&brewfile) {
         return (Vec::new(), false);
     }
 
@@ -130,9 +236,9 @@ async fn import_brew(
         return (Vec::new(), false);
     }
 
-    let manifest = match std::fs::read_to_string(&brewfile) {
-        Ok(m) => m,
-        Err(_) => return (Vec::new(), false),
+    let manifest = match metadata::read_to_string(&brewfile) {
+        Ok(Some(m)) => m,
+        _ => return (Vec::new(), false),
     };
 
     // Parse the Brewfile
@@ -159,6 +265,13 @@ async fn import_brew(
         .formulae
         .retain(|p| !removed_formulae.contains(p));
     brew_packages.casks.retain(|p| !removed_casks.contains(p));
+    // GUI apps are no use on a headless server
+    let previously_deferred = if skip_casks {
+        brew_packages.casks.clear();
+        &[][..]
+    } else {
+        previously_deferred
+    };
     brew_packages.taps.retain(|p| !removed_taps.contains(p));
 
     // Calculate missing packages (normalize formula names for comparison)
@@ -174,7 +287,7 @@ async fn import_brew(
         .unwrap_or_default();
 
     // Compare using normalized names (strip tap prefix like "oven-sh/bun/bun" -> "bun")
-    let missing_formulae: Vec<_> = brew_packages
+    let mut missing_formulae: Vec<_> = brew_packages
         .formulae
         .iter()
         .filter(|p| !local_formulae.contains(normalize_formula_name(p)))
@@ -198,6 +311,23 @@ async fn import_brew(
         }
     }
 
+    retain_trusted(&mut missing_formulae, "formula", |f| {
+        trust.allows_brew(f, false)
+    });
+    retain_trusted(&mut casks_to_try, "cask", |c| trust.allows_brew(c, true));
+
+    if let Some(queue) = queue {
+        for (key, packages) in [
+            ("brew_formulae", missing_formulae),
+            ("brew_casks", casks_to_try),
+        ] {
+            if !packages.is_empty() {
+                queue.insert(key.to_string(), packages);
+            }
+        }
+        return (Vec::new(), false);
+    }
+
     let mut installed_any = false;
 
     // Install formulae via bundle (no password needed)
@@ -213,17 +343,26 @@ async fn import_brew(
         // (brew bundle sometimes fails to tap before installing)
         if let Ok(local_taps) = brew.list_taps().await {
             let local_taps_set: HashSet<_> = local_taps.iter().map(|s| s.as_str()).collect();
-            for tap in &brew_packages.taps {
-                if !local_taps_set.contains(tap.as_str()) {
-                    if let Err(e) = brew.tap(tap).await {
-                        Output::warning(&format!("Failed to tap {}: {}", tap, e));
-                    }
+            let mut missing_taps: Vec<_> = brew_packages
+                .taps
+                .iter()
+                .filter(|t| !local_taps_set.contains(t.as_str()))
+                .cloned()
+                .collect();
+            retain_trusted(&mut missing_taps, "tap", |t| trust.allows_tap(t));
+            for tap in &missing_taps {
+                if let Err(e) = brew.tap(tap).await {
+                    Output::warning(&format!("Failed to tap {}: {}", tap, e));
                 }
             }
         }
 
         let formulae_manifest = BrewfilePackages {
-            taps: brew_packages.taps,
+            taps: brew_packages
+                .taps
+                .into_iter()
+                .filter(|t| trust.allows_tap(t))
+                .collect(),
             formulae: missing_formulae,
             casks: Vec::new(),
         };
@@ -278,35 +417,32 @@ async fn import_brew(
     (flagged_casks, installed_any)
 }
 
-/// Import a simple package manager (one package per line manifest)
+/// Import a simple package manager (one package per line manifest), or with a `queue`
+/// add its missing packages to it.
 /// Returns true if any packages were installed.
 async fn import_simple_manager(
     def: &PackageManagerDef,
     manifests_dir: &Path,
     machine_state: &MachineState,
+    trust: &PackageTrust,
+    queue: Option<&mut BTreeMap<String, Vec<String>>>,
 ) -> bool {
     let manifest_path = manifests_dir.join(def.manifest_file);
-    if !manifest_path.exists() {
+    if !metadata::exists(&manifest_path) {
         return false;
     }
 
-    // Get the appropriate manager
-    let manager: Box<dyn PackageManager> = match def.state_key {
-        "npm" => Box::new(NpmManager::new()),
-        "pnpm" => Box::new(PnpmManager::new()),
-        "bun" => Box::new(BunManager::new()),
-        "gem" => Box::new(GemManager::new()),
-        "uv" => Box::new(UvManager::new()),
-        _ => return false,
+    let Some(manager) = simple_manager(def.state_key) else {
+        return false;
     };
 
     if !manager.is_available().await {
         return false;
     }
 
-    let manifest = match std::fs::read_to_string(&manifest_path) {
-        Ok(m) => m,
-        Err(_) => return false,
+    let manifest = match metadata::read_to_string(&manifest_path) {
+        Ok(Some(m)) => m,
+        _ => return false,
     };
 
     let local_packages: HashSet<_> = machine_state
@@ -322,18 +458,23 @@ async fn import_simple_manager(
         .unwrap_or_default();
 
     // Filter to only missing packages
-    let missing: Vec<_> = manifest
-        .lines()
-        .filter(|line| {
-            let pkg = line.trim();
-            !pkg.is_empty() && !removed_packages.contains(pkg) && !local_packages.contains(pkg)
-        })
+    let mut missing: Vec<_> = manifest_packages(&manifest)
+        .into_iter()
+        .filter(|pkg| !removed_packages.contains(*pkg) && !local_packages.contains(*pkg))
         .map(|s| s.to_string())
         .collect();
+    // pnpm and bun install from the npm registry too
+    if matches!(def.state_key, "npm" | "pnpm" | "bun") {
+        retain_trusted(&mut missing, def.display_name, |p| trust.allows_npm(p));
+    }
 
     if missing.is_empty() {
         return false;
     }
+    if let Some(queue) = queue {
+        queue.insert(def.state_key.to_string(), missing);
+        return false;
+    }
 
     Output::info(&format!(
         "Installing {} {} package{}...",
@@ -357,7 +498,8 @@ async fn import_simple_manager(
     }
 }
 
-/// Export package manifests using union of all machine states
+/// Export package manifests using union of all machine states. Hand-edited
+/// manifests (`packages.mode = "manifest-authoritative"`) are never exported.
 pub async fn sync_packages(
     config: &Config,
     state: &mut SyncState,
@@ -365,6 +507,10 @@ pub async fn sync_packages(
     machine_state: &MachineState,
     dry_run: bool,
 ) -> Result<()> {
+    if config.packages.mode == PackagesMode::ManifestAuthoritative {
+        return Ok(());
+    }
+
     let manifests_dir = sync_path.join("manifests");
     std::fs::create_dir_all(&manifests_dir)?;
 
@@ -426,22 +572,22 @@ fn sync_brew(
             .unwrap_or_default(),
     };
 
-    let manifest = brew_packages.generate();
-    let hash = crate::sha256_hex(manifest.as_bytes());
     let manifest_path = manifests_dir.join("Brewfile");
-
-    let file_hash = std::fs::read(&manifest_path)
-        .ok()
-        .map(|c| crate::sha256_hex(&c));
-    let changed = file_hash.as_ref() != Some(&hash);
+    let existing = metadata::read_to_string(&manifest_path)?;
+    let manifest = preserve_layout(
+        existing.as_deref().unwrap_or_default(),
+        &brew_packages.generate(),
+        |line| brewfile_entry(line).map(|(kind, name)| format!("{} {}", kind, name)),
+    );
+    let hash = crate::sha256_hex(manifest.as_bytes());
+    let changed = existing.as_deref() != Some(manifest.as_str());
 
     if !dry_run {
         let now = chrono::Utc::now();
         let existing = state.packages.get("brew");
 
-        if changed {
-            std::fs::write(&manifest_path, &manifest)?;
-        }
+        // Also moves an unchanged manifest to the form `security.encrypt_manifests` asks for
+        metadata::write(&manifest_path, manifest.as_bytes())?;
 
         state.packages.insert(
             "brew".to_string(),
@@ -461,6 +607,73 @@ fn sync_brew(
     Ok(())
 }
 
+/// Add a package to its hand-edited manifest (`packages.mode = "manifest-authoritative"`),
+/// after the packages already listed. `manager_key` is a machine state key.
+pub fn append_to_manifest(sync_path: &Path, manager_key: &str, name: &str) -> Result<()> {
+    let (file, line) = match manager_key {
+        "brew_formulae" => ("Brewfile", format!("brew \"{}\"", name)),
+        "brew_casks" => ("Brewfile", format!("cask \"{}\"", name)),
+        "brew_taps" => ("Brewfile", format!("tap \"{}\"", name)),
+        key => match SIMPLE_MANAGERS.iter().find(|def| def.state_key == key) {
+            Some(def) => (def.manifest_file, name.to_string()),
+            None => anyhow::bail!("Unknown package manager: {}", key),
+        },
+    };
+    let path = sync_path.join("manifests").join(file);
+    let mut manifest = metadata::read_to_string(&path)?.unwrap_or_default();
+    if manifest.lines().any(|existing| existing.trim() == line) {
+        return Ok(());
+    }
+    if !manifest.is_empty() && !manifest.ends_with('\n') {
+        manifest.push('\n');
+    }
+    manifest.push_str(&line);
+    manifest.push('\n');
+    if let Some(parent) = path.parent() {
+        std::fs::create_dir_all(parent)?;
+    }
+    metadata::write(&path, manifest.as_bytes())?;
+    Ok(())
+}
+
+/// `generated` laid out like the hand-edited `existing` manifest: comments, blank-line
+/// groups and the order of packages still listed are kept, packages no longer listed
+/// are dropped and new ones are appended. `entry` names the package a line declares.
+/// A manifest without comments or blank lines is simply replaced.
+fn preserve_layout(
+    existing: &str,
+    generated: &str,
+    entry: impl Fn(&str) -> Option<String>,
+) -> String {
+    if existing.lines().all(|line| entry(line).is_some()) {
+        return generated.to_string();
+    }
+
+    let wanted: Vec<(String, &str)> = generated
+        .lines()
+        .filter_map(|line| entry(line).map(|key| (key, line)))
+        .collect();
+    let keys: HashSet<&str> = wanted.iter().map(|(key, _)| key.as_str()).collect();
+    let mut kept = HashSet::new();
+    let mut lines = Vec::new();
+    for line in existing.lines() {
+        let keep = match entry(line) {
+            None => true,
+            Some(key) => keys.contains(key.as_str()) && kept.insert(key),
+        };
+        if keep {
+            lines.push(line);
+        }
+    }
+    lines.extend(
+        wanted
+            .iter()
+            .filter(|(key, _)| !kept.contains(key))
+            .map(|(_, line)| *line),
+    );
+    lines.join("\n") + "\n"
+}
+
 /// Sync a simple package manager manifest from union
 fn sync_simple_manager(
     def: &PackageManagerDef,
@@ -473,26 +686,27 @@ fn sync_simple_manager(
         .get(def.state_key)
         .cloned()
         .unwrap_or_default();
-    let manifest = if packages.is_empty() {
+    let generated = if packages.is_empty() {
         String::new()
     } else {
         packages.join("\n") + "\n"
     };
-    let hash = crate::sha256_hex(manifest.as_bytes());
     let manifest_path = manifests_dir.join(def.manifest_file);
-
-    let file_hash = std::fs::read(&manifest_path)
-        .ok()
-        .map(|c| crate::sha256_hex(&c));
-    let changed = file_hash.as_ref() != Some(&hash);
+    let existing = metadata::read_to_string(&manifest_path)?;
+    let manifest = preserve_layout(
+        existing.as_deref().unwrap_or_default(),
+        &generated,
+        |line| manifest_entry(line).map(str::to_string),
+    );
+    let hash = crate::sha256_hex(manifest.as_bytes());
+    let changed = existing.as_deref() != Some(manifest.as_str());
 
     if !dry_run {
         let now = chrono::Utc::now();
         let existing = state.packages.get(def.state_key);
 
-        if changed {
-            std::fs::write(&manifest_path, &manifest)?;
-        }
+        // Also moves an unchanged manifest to the form `security.encrypt_manifests` asks for
+        metadata::write(&manifest_path, manifest.as_bytes())?;
 
         state.packages.insert(
             def.state_key.to_string(),
@@ -516,6 +730,49 @@ fn sync_simple_manager(
 mod tests {
     use super::*;
 
+    #[test]
+    fn test_append_to_manifest() {
+        let temp = tempfile::TempDir::new().unwrap();
+        let brewfile = temp.path().join("manifests/Brewfile");
+        std::fs::create_dir_all(brewfile.parent().unwrap()).unwrap();
+        std::fs::write(&brewfile, "# shell\nbrew \"zsh\"").unwrap();
+
+        append_to_manifest(temp.path(), "brew_casks", "ghostty").unwrap();
+        append_to_manifest(temp.path(), "brew_formulae", "zsh").unwrap();
+        append_to_manifest(temp.path(), "npm", "typescript").unwrap();
+        assert_eq!(
+            std::fs::read_to_string(&brewfile).unwrap(),
+            "# shell\nbrew \"zsh\"\ncask \"ghostty\"\n"
+        );
+        assert_eq!(
+            std::fs::read_to_string(temp.path().join("manifests/npm.txt")).unwrap(),
+            "typescript\n"
+        );
+        assert!(append_to_manifest(temp.path(), "cargo", "ripgrep").is_err());
+    }
+
+    #[test]
+    fn test_preserve_layout() {
+        let key = |line: &str| manifest_entry(line).map(str::to_string);
+        let existing = "# work tools\ntypescript\nprettier  # formatting\n\n# personal\nzx\n";
+        let generated = "eslint\nprettier\ntypescript\n";
+        assert_eq!(
+            preserve_layout(existing, generated, key),
+            "# work tools\ntypescript\nprettier  # formatting\n\n# personal\neslint\n"
+        );
+        // Without comments or grouping the manifest is regenerated as before
+        assert_eq!(preserve_layout("zx\neslint\n", generated, key), generated);
+
+        let brew =
+            |line: &str| brewfile_entry(line).map(|(kind, name)| format!("{} {}", kind, name));
+        let existing = "# shell\nbrew \"zsh\"\nbrew \"git\", args: [\"HEAD\"]\n";
+        let generated = "brew \"git\"\nbrew \"jq\"\nbrew \"zsh\"\n";
+        assert_eq!(
+            preserve_layout(existing, generated, brew),
+            "# shell\nbrew \"zsh\"\nbrew \"git\", args: [\"HEAD\"]\nbrew \"jq\"\n"
+        );
+    }
+
     #[test]
     fn test_update_last_upgrade_creates_entry() {
         let mut state = SyncState {
@@ -527,7 +784,12 @@ mod tests {
             last_upgrade_with_updates: None,
             deferred_casks: Vec::new(),
             deferred_casks_hash: None,
+            pending_installs: BTreeMap::new(),
             dismissed_imports: std::collections::HashSet::new(),
+            deleted_files: HashMap::new(),
+            repo_visibility: None,
+            last_report: None,
+            repo_sizes: Vec::new(),
         };
 
         assert!(!state.packages.contains_key("brew"));
@@ -553,7 +815,12 @@ mod tests {
             last_upgrade_with_updates: None,
             deferred_casks: Vec::new(),
             deferred_casks_hash: None,
+            pending_installs: BTreeMap::new(),
             dismissed_imports: std::collections::HashSet::new(),
+            deleted_files: HashMap::new(),
+            repo_visibility: None,
+            last_report: None,
+            repo_sizes: Vec::new(),
         };
 
         state.packages.insert(
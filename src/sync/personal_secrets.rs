@@ -0,0 +1,129 @@
+//! Personal secrets (`tether secrets`): single values such as API tokens, stored as
+//! `secrets/<name>.enc` in the personal sync repo and encrypted with the same key as
+//! dotfiles, so they unlock wherever `tether unlock` does.
+
+use anyhow::Result;
+use std::path::{Path, PathBuf};
+
+pub const SECRETS_DIR: &str = "secrets";
+
+/// Secret names become file names: no separators, traversal or leading dots
+pub fn is_valid_name(name: &str) -> bool {
+    !name.is_empty()
+        && !name.starts_with('.')
+        && name
+            .chars()
+            .all(|c| c.is_ascii_alphanumeric() || matches!(c, '-' | '_' | '.'))
+}
+
+/// Repo-relative path of a secret
+pub fn repo_path(name: &str) -> String {
+    format!("{}/{}.enc", SECRETS_DIR, name)
+}
+
+fn path(sync_path: &Path, name: &str) -> Result<PathBuf> {
+    if !is_valid_name(name) {
+        anyhow::bail!(
+            "Invalid secret name '{}': use letters, digits, '-', '_' or '.'",
+            name
+        );
+    }
+    Ok(sync_path.join(repo_path(name)))
+}
+
+pub fn exists(sync_path: &Path, name: &str) -> bool {
+    path(sync_path, name).is_ok_and(|p| p.exists())
+}
+
+/// Encrypt and write a secret, replacing any existing value
+pub fn set(sync_path: &Path, key: &[u8], name: &str, value: &[u8]) -> Result<()> {
+    let path = path(sync_path, name)?;
+    if let Some(parent) = path.parent() {
+        std::fs::create_dir_all(parent)?;
+    }
+    std::fs::write(&path, crate::security::encrypt(value, key)?)?;
+    Ok(())
+}
+
+/// Decrypted value of a secret, `None` if there is no such secret
+pub fn get(sync_path: &Path, key: &[u8], name: &str) -> Result<Option<Vec<u8>>> {
+    let path = path(sync_path, name)?;
+    if !path.exists() {
+        return Ok(None);
+    }
+    let encrypted = std::fs::read(&path)?;
+    Ok(Some(crate::security::decrypt(&encrypted, key)?))
+}
+
+/// Names of all secrets, sorted
+pub fn list(sync_path: &Path) -> Result<Vec<String>> {
+    let dir = sync_path.join(SECRETS_DIR);
+    if !dir.exists() {
+        return Ok(Vec::new());
+    }
+    let mut names: Vec<String> = std::fs::read_dir(&dir)?
+        .filter_map(|e| e.ok())
+        .filter_map(|e| {
+            let name = e.file_name().to_string_lossy().into_owned();
+            name.strip_suffix(".enc")
+                .filter(|n| is_valid_name(n))
+                .map(str::to_string)
+        })
+        .collect();
+    names.sort();
+    Ok(names)
+}
+
+/// Delete a secret. Returns whether it existed.
+pub fn remove(sync_path: &Path, name: &str) -> Result<bool> {
+    let path = path(sync_path, name)?;
+    if !path.exists() {
+        return Ok(false);
+    }
+    std::fs::remove_file(path)?;
+    Ok(true)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::TempDir;
+
+    #[test]
+    fn test_secret_roundtrip() {
+        let temp = TempDir::new().unwrap();
+        let key = [7u8; 32];
+
+        set(temp.path(), &key, "GITHUB_TOKEN", b"ghp_abc").unwrap();
+        set(temp.path(), &key, "npm-token", b"npm_123").unwrap();
+
+        assert_eq!(
+            get(temp.path(), &key, "GITHUB_TOKEN").unwrap().unwrap(),
+            b"ghp_abc"
+        );
+        assert!(get(temp.path(), &key, "MISSING").unwrap().is_none());
+        assert!(get(temp.path(), &[8u8; 32], "GITHUB_TOKEN").is_err());
+        assert_eq!(
+            list(temp.path()).unwrap(),
+            vec!["GITHUB_TOKEN", "npm-token"]
+        );
+
+        let stored = std::fs::read(temp.path().join("secrets/GITHUB_TOKEN.enc")).unwrap();
+        assert!(!stored.windows(7).any(|w| w == b"ghp_abc"));
+
+        assert!(remove(temp.path(), "npm-token").unwrap());
+        assert!(!remove(temp.path(), "npm-token").unwrap());
+        assert_eq!(list(temp.path()).unwrap(), vec!["GITHUB_TOKEN"]);
+    }
+
+    #[test]
+    fn test_secret_names() {
+        assert!(is_valid_name("AWS_ACCESS_KEY_ID"));
+        assert!(is_valid_name("work.api-key"));
+        assert!(!is_valid_name(""));
+        assert!(!is_valid_name(".hidden"));
+        assert!(!is_valid_name("../escape"));
+        assert!(!is_valid_name("a/b"));
+        assert!(set(Path::new("/tmp"), &[0u8; 32], "../x", b"v").is_err());
+    }
+}
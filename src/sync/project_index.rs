@@ -0,0 +1,238 @@
+//! Filesystem-event index so project config scans skip repos that haven't changed.
+//!
+//! With `project_configs.watch`, the daemon watches the search paths and records in
+//! `~/.tether/project_events.json` which repos saw changes and when. Each project scan
+//! records the repos it found in `~/.tether/project_index.json`. While the watcher that
+//! started before the last scan is still running, the next scan reuses the index and
+//! only revisits repos with events since then. Each file has a single writer.
+
+use anyhow::Result;
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::path::{Path, PathBuf};
+
+use crate::config::Config;
+
+/// Changes seen by the daemon's watcher, written only by the watcher
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ProjectEvents {
+    pub pid: u32,
+    /// When every search path was being watched; earlier changes weren't seen
+    pub watching_since: DateTime<Utc>,
+    /// Search paths as configured when the watcher started
+    pub roots: Vec<PathBuf>,
+    /// Last change outside any repo, where a repo may have appeared or disappeared
+    #[serde(default)]
+    pub layout_changed: Option<DateTime<Utc>>,
+    /// Last change inside each repo, by repo root
+    #[serde(default)]
+    pub repos: BTreeMap<PathBuf, DateTime<Utc>>,
+}
+
+impl ProjectEvents {
+    pub fn path() -> Result<PathBuf> {
+        Ok(Config::config_dir()?.join("project_events.json"))
+    }
+
+    pub fn new(roots: Vec<PathBuf>) -> Self {
+        Self {
+            pid: std::process::id(),
+            watching_since: Utc::now(),
+            roots,
+            layout_changed: None,
+            repos: BTreeMap::new(),
+        }
+    }
+
+    pub fn save(&self) -> Result<()> {
+        self.save_to(&Self::path()?)
+    }
+
+    pub fn save_to(&self, path: &Path) -> Result<()> {
+        let content = serde_json::to_string_pretty(self)?;
+        crate::sync::atomic_write(path, content.as_bytes())
+    }
+
+    /// Events from a watcher that is still running over exactly `roots`
+    pub fn live(roots: &[PathBuf]) -> Option<Self> {
+        let content = std::fs::read_to_string(Self::path().ok()?).ok()?;
+        let events: Self = serde_json::from_str(&content).ok()?;
+        (events.roots == roots && crate::daemon::pid::is_process_running(events.pid))
+            .then_some(events)
+    }
+
+    /// Record a change at `path`: against the repo containing it, or as a layout
+    /// change when it's outside every repo. Changes in skipped dirs (`.git`,
+    /// `node_modules`, ...) are ignored, except the repo's `.git/config`.
+    pub fn record(&mut self, path: &Path, at: DateTime<Utc>) {
+        let Some(root) = self.roots.iter().find(|r| path.starts_with(r)) else {
+            return;
+        };
+        let repo = path
+            .ancestors()
+            .take_while(|dir| dir.starts_with(root))
+            .find(|dir| dir.join(".git").exists());
+
+        let rel = match repo {
+            Some(repo) => path.strip_prefix(repo),
+            None => path.strip_prefix(root),
+        };
+        let rel = rel.unwrap_or(Path::new(""));
+        if rel != Path::new(".git/config") && in_skipped_dir(rel) {
+            return;
+        }
+
+        match repo {
+            Some(repo) => {
+                self.repos.insert(repo.to_path_buf(), at);
+            }
+            None => self.layout_changed = Some(at),
+        }
+    }
+
+    pub fn repo_changed_since(&self, repo: &Path, since: DateTime<Utc>) -> bool {
+        self.repos.get(repo).is_some_and(|at| *at >= since)
+    }
+
+    /// Whether repos may have been added or removed since `since`. Changes in repos
+    /// the index doesn't know count, as those are new repos.
+    pub fn layout_changed_since(&self, index: &ProjectIndex, since: DateTime<Utc>) -> bool {
+        self.layout_changed.is_some_and(|at| at >= since)
+            || self
+                .repos
+                .iter()
+                .any(|(repo, at)| *at >= since && !index.repos.contains_key(repo))
+    }
+}
+
+/// Whether any directory component of `rel` is one project scans skip
+fn in_skipped_dir(rel: &Path) -> bool {
+    let mut dirs = rel.components();
+    dirs.next_back();
+    dirs.filter_map(|c| c.as_os_str().to_str())
+        .any(crate::sync::git::should_skip_dir_for_project_configs)
+}
+
+/// Repos found by the last project scan, written only by the scanner
+#[derive(Debug, Default, Clone, Serialize, Deserialize)]
+pub struct ProjectIndex {
+    /// Hash of the settings the scan ran with; any change forces a full scan
+    pub settings_hash: String,
+    #[serde(default)]
+    pub scanned_at: Option<DateTime<Utc>>,
+    /// Normalized remote URL of each repo, by repo root
+    #[serde(default)]
+    pub repos: BTreeMap<PathBuf, String>,
+}
+
+impl ProjectIndex {
+    fn path() -> Result<PathBuf> {
+        Ok(Config::config_dir()?.join("project_index.json"))
+    }
+
+    /// Hash of everything that decides which repos and files a scan picks up
+    pub fn settings_hash(config: &Config) -> String {
+        let settings =
+            serde_json::to_string(&(&config.project_configs, &config.teams)).unwrap_or_default();
+        crate::sha256_hex(settings.as_bytes())
+    }
+
+    /// The saved index, or an empty one when missing or scanned with other settings
+    pub fn load(settings_hash: &str) -> Self {
+        let index = Self::path()
+            .ok()
+            .and_then(|path| std::fs::read_to_string(path).ok())
+            .and_then(|content| serde_json::from_str::<Self>(&content).ok())
+            .filter(|index| index.settings_hash == settings_hash);
+        index.unwrap_or_else(|| Self {
+            settings_hash: settings_hash.to_string(),
+            ..Self::default()
+        })
+    }
+
+    pub fn save(&self) -> Result<()> {
+        self.save_to(&Self::path()?)
+    }
+
+    pub fn save_to(&self, path: &Path) -> Result<()> {
+        let content = serde_json::to_string_pretty(self)?;
+        crate::sync::atomic_write(path, content.as_bytes())
+    }
+
+    /// When the last scan ran, if `events` covers every change since then
+    pub fn covered_since(&self, events: &ProjectEvents) -> Option<DateTime<Utc>> {
+        self.scanned_at.filter(|at| *at >= events.watching_since)
+    }
+
+    /// Indexed repos under `root`
+    pub fn repos_under(&self, root: &Path) -> Vec<PathBuf> {
+        self.repos
+            .keys()
+            .filter(|repo| repo.starts_with(root))
+            .cloned()
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::Duration;
+    use tempfile::TempDir;
+
+    #[test]
+    fn test_events_track_repos_and_layout() {
+        let temp = TempDir::new().unwrap();
+        let root = temp.path().to_path_buf();
+        let repo = root.join("app");
+        std::fs::create_dir_all(repo.join(".git")).unwrap();
+        std::fs::create_dir_all(repo.join("node_modules/pkg")).unwrap();
+
+        let start = Utc::now();
+        let mut events = ProjectEvents::new(vec![root.clone()]);
+        let mut index = ProjectIndex::default();
+        index.repos.insert(repo.clone(), "github.com/u/app".into());
+
+        // Noise inside the repo is ignored, except the remote config
+        events.record(&repo.join(".git/index"), start);
+        events.record(&repo.join("node_modules/pkg/index.js"), start);
+        assert!(!events.repo_changed_since(&repo, start));
+        events.record(&repo.join(".git/config"), start);
+        assert!(events.repo_changed_since(&repo, start));
+
+        let later = start + Duration::seconds(10);
+        events.record(&repo.join(".env"), later);
+        assert!(events.repo_changed_since(&repo, later));
+        assert!(!events.layout_changed_since(&index, start));
+
+        // A change outside any repo, or in a repo the index lacks, is a layout change
+        events.record(&root.join("notes.txt"), later);
+        assert!(events.layout_changed_since(&index, later));
+        events.layout_changed = None;
+        let other = root.join("other");
+        std::fs::create_dir_all(other.join(".git")).unwrap();
+        events.record(&other.join("README.md"), later);
+        assert!(events.layout_changed_since(&index, later));
+        index.repos.insert(other, "github.com/u/other".into());
+        assert!(!events.layout_changed_since(&index, later));
+
+        // Paths outside the watched roots are ignored
+        events.record(Path::new("/elsewhere/file"), later);
+        assert!(events.layout_changed.is_none());
+    }
+
+    #[test]
+    fn test_index_covered_only_after_watch_start() {
+        let events = ProjectEvents::new(Vec::new());
+        let mut index = ProjectIndex::default();
+        assert_eq!(index.covered_since(&events), None);
+
+        index.scanned_at = Some(events.watching_since - Duration::seconds(1));
+        assert_eq!(index.covered_since(&events), None);
+
+        let after = events.watching_since + Duration::seconds(1);
+        index.scanned_at = Some(after);
+        assert_eq!(index.covered_since(&events), Some(after));
+    }
+}
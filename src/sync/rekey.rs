@@ -0,0 +1,242 @@
+//! Re-encrypting the sync repo under a new key, for `tether key rotate`.
+//!
+//! Every `.enc` file is decrypted with the old key and encrypted again with the new
+//! one. Chunk IDs are keyed, so chunked files are split and stored again under new
+//! IDs and the old chunks are dropped.
+//!
+//! Until the rotation is pushed, the new key exists only in memory. `rotate` resets
+//! the repo to where it started if anything fails, so no file is ever left under a
+//! key that was never published.
+
+use super::{chunks, GitBackend};
+use anyhow::{Context, Result};
+use std::path::{Path, PathBuf};
+
+/// A decrypted `.enc` file waiting to be written back
+struct Plain {
+    path: PathBuf,
+    content: Vec<u8>,
+    /// Under `configs/`, where blobs may be manifests or escaped
+    chunkable: bool,
+    chunked: bool,
+}
+
+/// Re-encrypt every `.enc` file in the repo from `old_key` to `new_key` and return how
+/// many there were. Everything is decrypted before anything is written, so a file the
+/// old key can't read leaves the repo untouched.
+pub fn reencrypt_repo(sync_path: &Path, old_key: &[u8], new_key: &[u8]) -> Result<usize> {
+    let mut files = Vec::new();
+    for entry in walkdir::WalkDir::new(sync_path)
+        .follow_links(false)
+        .sort_by_file_name()
+        .into_iter()
+        .filter_entry(|e| e.file_name() != ".git" && e.file_name() != chunks::CHUNKS_DIR)
+    {
+        let entry = entry?;
+        if !entry.file_type().is_file() || entry.path().extension() != Some("enc".as_ref()) {
+            continue;
+        }
+        let rel = entry.path().strip_prefix(sync_path)?.display().to_string();
+        let plaintext = crate::security::decrypt(&std::fs::read(entry.path())?, old_key)
+            .with_context(|| format!("Failed to decrypt {}", rel))?;
+        let chunkable = chunks::may_be_chunked(&rel);
+        let chunked = chunkable && chunks::is_manifest(&plaintext);
+        let content = chunks::read_from_repo(sync_path, entry.path(), plaintext, old_key)
+            .with_context(|| format!("Failed to read chunks of {}", rel))?;
+        files.push(Plain {
+            path: entry.into_path(),
+            content,
+            chunkable,
+            chunked,
+        });
+    }
+
+    for file in &files {
+        if file.chunked {
+            chunks::write_chunked(sync_path, &file.path, &file.content, new_key)?;
+        } else if file.chunkable {
+            let encrypted =
+                crate::security::encrypt(&chunks::encode_whole(&file.content), new_key)?;
+            super::atomic_write(&file.path, &encrypted)?;
+        } else {
+            let encrypted = crate::security::encrypt(&file.content, new_key)?;
+            super::atomic_write(&file.path, &encrypted)?;
+        }
+    }
+    chunks::gc_unreferenced(sync_path, new_key)?;
+    Ok(files.len())
+}
+
+/// Re-encrypt the repo from `old_key` to `new_key`, then `publish` it (wrap the new
+/// key, commit and push), passing the number of files. If either step fails, the repo
+/// is reset to the commit it started from, where everything still decrypts with
+/// `old_key`.
+pub fn rotate(
+    git: &GitBackend,
+    old_key: &[u8],
+    new_key: &[u8],
+    publish: impl FnOnce(usize) -> Result<()>,
+) -> Result<usize> {
+    let start = git.head_commit()?;
+    match reencrypt_repo(git.sync_path(), old_key, new_key)
+        .and_then(|files| publish(files).map(|()| files))
+    {
+        Ok(files) => Ok(files),
+        Err(e) => {
+            git.reset_to(&start).with_context(|| {
+                format!(
+                    "Key rotation failed ({:#}) and the sync repo couldn't be restored",
+                    e
+                )
+            })?;
+            Err(e)
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::TempDir;
+
+    #[test]
+    fn test_reencrypt_repo() {
+        let temp = TempDir::new().unwrap();
+        let root = temp.path();
+        let old = crate::security::generate_key();
+        let new = crate::security::generate_key();
+
+        let zshrc = root.join("profiles/shared/zshrc.enc");
+        std::fs::create_dir_all(zshrc.parent().unwrap()).unwrap();
+        std::fs::write(
+            &zshrc,
+            crate::security::encrypt(b"alias g=git", &old).unwrap(),
+        )
+        .unwrap();
+        let big = crate::bench::synthetic_content(3, 64 * 1024);
+        let settings = root.join("configs/.config/app/settings.json.enc");
+        std::fs::create_dir_all(settings.parent().unwrap()).unwrap();
+        chunks::write_chunked(root, &settings, &big, &old).unwrap();
+        let old_chunk = chunks::chunk_repo_path(&chunks::chunk_id(&old, chunks::split(&big)[0]));
+        let lookalike = b"tether-chunks v1\nnot a manifest";
+        let notes = root.join("configs/notes.txt.enc");
+        let escaped = chunks::encode_whole(lookalike);
+        std::fs::write(&notes, crate::security::encrypt(&escaped, &old).unwrap()).unwrap();
+        std::fs::write(root.join("format_version"), "1").unwrap();
+
+        assert_eq!(reencrypt_repo(root, &old, &new).unwrap(), 3);
+
+        let plain = crate::security::decrypt(&std::fs::read(&zshrc).unwrap(), &new).unwrap();
+        assert_eq!(plain, b"alias g=git");
+        let manifest = crate::security::decrypt(&std::fs::read(&settings).unwrap(), &new).unwrap();
+        assert_eq!(
+            chunks::read_from_repo(root, &settings, manifest, &new).unwrap(),
+            big
+        );
+        let notes_plain = crate::security::decrypt(&std::fs::read(&notes).unwrap(), &new).unwrap();
+        assert_eq!(
+            chunks::read_from_repo(root, &notes, notes_plain, &new).unwrap(),
+            lookalike
+        );
+        assert!(!root.join(old_chunk).exists());
+        assert!(crate::security::decrypt(&std::fs::read(&zshrc).unwrap(), &old).is_err());
+    }
+
+    #[test]
+    fn test_reencrypt_leaves_repo_untouched_on_wrong_key() {
+        let temp = TempDir::new().unwrap();
+        let root = temp.path();
+        let key = crate::security::generate_key();
+        let path = root.join("secrets/TOKEN.enc");
+        std::fs::create_dir_all(path.parent().unwrap()).unwrap();
+        let encrypted = crate::security::encrypt(b"secret", &key).unwrap();
+        std::fs::write(&path, &encrypted).unwrap();
+
+        let wrong = crate::security::generate_key();
+        assert!(reencrypt_repo(root, &wrong, &key).is_err());
+        assert_eq!(std::fs::read(&path).unwrap(), encrypted);
+    }
+
+    /// A git repo holding a plain and a chunked file encrypted with `key`, committed
+    fn committed_repo(key: &[u8]) -> (TempDir, GitBackend, Vec<u8>) {
+        let temp = TempDir::new().unwrap();
+        let root = temp.path();
+        let git = |args: &[&str]| {
+            let status = std::process::Command::new("git")
+                .args(args)
+                .current_dir(root)
+                .output()
+                .unwrap()
+                .status;
+            assert!(status.success(), "git {:?}", args);
+        };
+        git(&["init", "-q", "-b", "main"]);
+        git(&["config", "user.name", "test"]);
+        git(&["config", "user.email", "test@local"]);
+
+        let plain = root.join("configs/a.enc");
+        std::fs::create_dir_all(plain.parent().unwrap()).unwrap();
+        std::fs::write(&plain, crate::security::encrypt(b"plain", key).unwrap()).unwrap();
+        let big = crate::bench::synthetic_content(3, 64 * 1024);
+        chunks::write_chunked(root, &root.join("configs/z/settings.json.enc"), &big, key).unwrap();
+        git(&["add", "-A"]);
+        git(&["commit", "-q", "-m", "init"]);
+        let backend = GitBackend::open(root).unwrap();
+        (temp, backend, big)
+    }
+
+    fn assert_decrypts_with(root: &Path, key: &[u8], big: &[u8]) {
+        let plain = std::fs::read(root.join("configs/a.enc")).unwrap();
+        assert_eq!(crate::security::decrypt(&plain, key).unwrap(), b"plain");
+        let settings = root.join("configs/z/settings.json.enc");
+        let manifest = crate::security::decrypt(&std::fs::read(&settings).unwrap(), key).unwrap();
+        assert_eq!(
+            chunks::read_from_repo(root, &settings, manifest, key).unwrap(),
+            big
+        );
+    }
+
+    #[test]
+    fn test_rotate_restores_repo_when_publishing_fails() {
+        let old = crate::security::generate_key();
+        let new = crate::security::generate_key();
+        let (temp, git, big) = committed_repo(&old);
+        let start = git.head_commit().unwrap();
+
+        // The commit is made, then the push fails
+        let result = rotate(&git, &old, &new, |files| {
+            assert_eq!(files, 2);
+            git.commit("Rotate encryption key", "test")?;
+            anyhow::bail!("push rejected")
+        });
+        assert!(result.is_err());
+        assert_eq!(git.head_commit().unwrap(), start);
+        assert!(!git.has_changes().unwrap());
+        assert_decrypts_with(temp.path(), &old, &big);
+    }
+
+    #[test]
+    fn test_rotate_restores_repo_when_reencryption_fails_midway() {
+        let old = [1u8; 32];
+        let new = [2u8; 32];
+        let (temp, git, big) = committed_repo(&old);
+        let root = temp.path();
+
+        // configs/a.enc is written first; a file where the first new chunk's directory
+        // should go makes the chunked one fail after it
+        let id = chunks::chunk_id(&new, chunks::split(&big)[0]);
+        let shard = root.join(chunks::CHUNKS_DIR).join(&id[..2]);
+        assert!(!shard.exists());
+        std::fs::write(&shard, b"in the way").unwrap();
+
+        let result = rotate(&git, &old, &new, |_| panic!("published a failed rotation"));
+        assert!(result.is_err());
+        assert!(!shard.exists());
+        assert_decrypts_with(root, &old, &big);
+
+        // Once the obstacle is gone, the rotation goes through
+        let files = rotate(&git, &old, &new, |_| Ok(())).unwrap();
+        assert_eq!(files, 2);
+        assert_decrypts_with(root, &new, &big);
+    }
+}
@@ -0,0 +1,217 @@
+//! Full check of the sync repo's contents, for `tether verify`.
+//!
+//! The signed manifest checked after every pull only compares ciphertext hashes. This
+//! reads everything: each `.enc` file must decrypt (chunked files must reassemble),
+//! machine states, locks and package manifests must parse, and the files this machine
+//! synced are compared with the hashes it recorded, so corruption is caught before
+//! another machine applies it.
+
+use crate::config::Config;
+use crate::sync::state::{MachineState, SyncState};
+use anyhow::Result;
+use std::path::Path;
+
+#[derive(Debug, Default)]
+pub struct RepoCheck {
+    /// Files read
+    pub checked: usize,
+    /// Repo-relative path and what's wrong with it
+    pub corrupt: Vec<(String, String)>,
+    /// Files this machine synced whose repo copy no longer matches its last sync.
+    /// Usually another machine's push rather than corruption.
+    pub changed: Vec<String>,
+}
+
+impl RepoCheck {
+    pub fn is_clean(&self) -> bool {
+        self.corrupt.is_empty()
+    }
+}
+
+/// Read every file in the repo. `key` is `None` for unencrypted repos, in which case
+/// `.enc` files aren't decrypted.
+pub fn check_files(sync_path: &Path, key: Option<&[u8]>) -> Result<RepoCheck> {
+    let mut report = RepoCheck::default();
+    for entry in walkdir::WalkDir::new(sync_path)
+        .into_iter()
+        .filter_entry(|e| e.file_name() != ".git")
+    {
+        let entry = entry?;
+        if !entry.file_type().is_file() {
+            continue;
+        }
+        let rel = entry.path().strip_prefix(sync_path)?;
+        let rel = rel.to_string_lossy().replace('\\', "/");
+        if let Err(e) = check_file(sync_path, entry.path(), &rel, key) {
+            report.corrupt.push((rel, format!("{:#}", e)));
+        }
+        report.checked += 1;
+    }
+
+    if let Some(key) = key {
+        match crate::sync::integrity::verify(sync_path, key) {
+            Ok(Some(integrity)) => {
+                for problem in integrity.describe() {
+                    report
+                        .corrupt
+                        .push((crate::sync::integrity::INTEGRITY_FILE.to_string(), problem));
+                }
+            }
+            Ok(None) => {}
+            Err(e) => report.corrupt.push((
+                crate::sync::integrity::INTEGRITY_FILE.to_string(),
+                format!("{:#}", e),
+            )),
+        }
+    }
+    Ok(report)
+}
+
+fn check_file(sync_path: &Path, path: &Path, rel: &str, key: Option<&[u8]>) -> Result<()> {
+    let content = std::fs::read(path)?;
+    let (dir, name) = rel.rsplit_once('/').unwrap_or(("", rel));
+
+    if let (Some(key), true) = (key, rel.ends_with(".enc")) {
+        let plaintext = crate::security::decrypt(&content, key)
+            .map_err(|_| anyhow::anyhow!("does not decrypt"))?;
+        // Encrypted package manifests and machine states get the same checks as plaintext
+        let name = crate::sync::metadata::plain_name(name);
+        match (dir, name.strip_suffix(".json")) {
+            ("machines", Some(_)) => {
+                MachineState::parse(&plaintext)?;
+            }
+            ("manifests", _) => check_manifest(name, &plaintext)?,
+            // Chunks are decrypted on their own; manifests pointing at them reassemble
+            _ if !rel.starts_with(&format!("{}/", crate::sync::chunks::CHUNKS_DIR)) => {
+                crate::sync::chunks::read_from_repo(sync_path, path, plaintext, key)?;
+            }
+            _ => {}
+        }
+        return Ok(());
+    }
+
+    if rel == crate::sync::integrity::INTEGRITY_FILE {
+        crate::sync::integrity::IntegrityManifest::load(sync_path)?;
+        return Ok(());
+    }
+    match (dir, name.strip_suffix(".json")) {
+        ("machines", Some(id)) => {
+            MachineState::load_from_repo(sync_path, id)?;
+        }
+        (crate::sync::freeze::LOCKS_DIR, Some(lock)) => {
+            crate::sync::freeze::EnvLock::load(sync_path, lock)?;
+        }
+        ("manifests", _) => check_manifest(name, &content)?,
+        _ => {}
+    }
+    Ok(())
+}
+
+fn check_manifest(name: &str, content: &[u8]) -> Result<()> {
+    let content =
+        std::str::from_utf8(content).map_err(|_| anyhow::anyhow!("is not valid UTF-8"))?;
+    let names: Vec<&str> = if name == "Brewfile" {
+        content
+            .lines()
+            .filter_map(crate::packages::brewfile_entry)
+            .map(|(_, name)| name)
+            .collect()
+    } else {
+        crate::packages::manifest_packages(content)
+    };
+    if let Some(bad) = names
+        .into_iter()
+        .find(|name| !MachineState::is_safe_package_name(name))
+    {
+        anyhow::bail!("lists an invalid package name: {}", bad);
+    }
+    Ok(())
+}
+
+/// Files synced from this machine whose repo content differs from the hash recorded
+/// at its last sync. Files that are corrupt or gone are left to [`check_files`].
+pub fn changed_since_sync(
+    config: &Config,
+    state: &SyncState,
+    sync_path: &Path,
+    key: Option<&[u8]>,
+) -> Vec<String> {
+    let mut changed = Vec::new();
+    for (file, file_state) in &state.files {
+        if !file_state.synced {
+            continue;
+        }
+        let Some(path) =
+            crate::sync::deletions::candidate_repo_paths(config, &state.machine_id, file)
+                .into_iter()
+                .map(|rel| sync_path.join(rel))
+                .find(|path| path.exists())
+        else {
+            continue;
+        };
+        let Ok(content) = std::fs::read(&path) else {
+            continue;
+        };
+        let plaintext = match key {
+            Some(key) if path.extension() == Some("enc".as_ref()) => {
+                match crate::security::decrypt(&content, key)
+                    .and_then(|p| crate::sync::chunks::read_from_repo(sync_path, &path, p, key))
+                {
+                    Ok(plaintext) => plaintext,
+                    Err(_) => continue,
+                }
+            }
+            _ => content,
+        };
+        if crate::sha256_hex(&plaintext) != file_state.hash {
+            changed.push(file.clone());
+        }
+    }
+    changed
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::TempDir;
+
+    fn write(root: &Path, rel: &str, content: &[u8]) {
+        let path = root.join(rel);
+        std::fs::create_dir_all(path.parent().unwrap()).unwrap();
+        std::fs::write(path, content).unwrap();
+    }
+
+    #[test]
+    fn test_check_files_reports_corruption() {
+        let temp = TempDir::new().unwrap();
+        let root = temp.path();
+        let key = [7u8; 32];
+        let good = crate::security::encrypt(b"export A=1\n", &key).unwrap();
+        let mut flipped = good.clone();
+        *flipped.last_mut().unwrap() ^= 1;
+
+        write(root, "configs/.zshrc.enc", &good);
+        write(root, "configs/.bashrc.enc", &flipped);
+        write(root, "machines/mac.json", b"{\"machine_id\": ");
+        write(root, "manifests/npm.txt", b"typescript\n");
+        write(root, "manifests/pnpm.txt", b"ok\nevil;rm -rf /\n");
+        write(root, ".git/HEAD", b"not checked");
+
+        let report = check_files(root, Some(&key)).unwrap();
+        assert_eq!(report.checked, 5);
+        let mut corrupt: Vec<&str> = report.corrupt.iter().map(|(f, _)| f.as_str()).collect();
+        corrupt.sort();
+        assert_eq!(
+            corrupt,
+            [
+                "configs/.bashrc.enc",
+                "machines/mac.json",
+                "manifests/pnpm.txt"
+            ]
+        );
+
+        // Without the key, encrypted files can't be checked
+        let report = check_files(root, None).unwrap();
+        assert_eq!(report.corrupt.len(), 2);
+    }
+}
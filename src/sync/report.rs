@@ -0,0 +1,366 @@
+//! The drift report: for people with several machines, a summary of how far they've
+//! drifted apart. `tether machines report` prints one; with `report.enabled` the daemon
+//! makes one every `report.every_days` days and delivers it as a notification, a
+//! Markdown file and/or a JSON webhook.
+//!
+//! Everything comes from this machine's view of the sync repo: the machine states the
+//! other machines pushed, this machine's pending conflicts and its security checks.
+
+use crate::config::Config;
+use crate::sync::{ConflictState, MachineDiff, MachineState, PackageChanges, SyncState};
+use anyhow::{Context, Result};
+use chrono::{DateTime, Duration, Local, Utc};
+use serde::Serialize;
+use std::collections::BTreeMap;
+use std::path::Path;
+
+/// A machine that hasn't synced for this long counts as behind
+pub const BEHIND_AFTER_DAYS: i64 = 3;
+
+/// Serialized as the output of `tether machines report --json` and the webhook body,
+/// so fields are only ever added
+#[derive(Debug, Clone, Serialize)]
+pub struct DriftReport {
+    pub generated_at: DateTime<Utc>,
+    /// The machine that made the report
+    pub machine_id: String,
+    /// Every other machine
+    pub machines: Vec<MachineDrift>,
+    /// Files in conflict on this machine
+    pub conflicts: Vec<String>,
+    pub warnings: Vec<String>,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct MachineDrift {
+    pub machine_id: String,
+    pub hostname: String,
+    pub profile: Option<String>,
+    pub last_sync: DateTime<Utc>,
+    pub cli_version: String,
+    /// Hasn't synced for [`BEHIND_AFTER_DAYS`]
+    pub behind: bool,
+    /// Runs a tether too far from this one to understand its syncs
+    pub incompatible: bool,
+    /// How its packages differ from this machine's, when both share a profile (other
+    /// profiles install different packages on purpose)
+    pub packages: BTreeMap<String, PackageChanges>,
+}
+
+impl MachineDrift {
+    fn package_differences(&self) -> usize {
+        self.packages
+            .values()
+            .map(|changes| changes.added.len() + changes.removed.len())
+            .sum()
+    }
+}
+
+impl DriftReport {
+    /// Build the report from the sync repo and this machine's state
+    pub fn build(config: &Config, state: &SyncState, sync_path: &Path) -> Result<Self> {
+        let machines = MachineState::list_all(sync_path)?;
+        let conflicts = ConflictState::load()?
+            .conflicts
+            .into_iter()
+            .map(|conflict| conflict.file_path)
+            .collect();
+
+        let mut warnings = Vec::new();
+        if state
+            .repo_visibility
+            .as_ref()
+            .is_some_and(|check| check.public)
+        {
+            warnings.push("The sync repo is public".to_string());
+        }
+        if !config.security.encrypt_dotfiles {
+            warnings.push("Dotfiles are synced unencrypted".to_string());
+        }
+        // Needs the unlocked key; a locked machine just skips it
+        if let Ok(problems) = crate::sync::integrity::check(config, sync_path) {
+            warnings.extend(
+                problems
+                    .into_iter()
+                    .map(|problem| format!("Changed outside a sync: {}", problem)),
+            );
+        }
+
+        Ok(Self::from_parts(
+            &state.machine_id,
+            &machines,
+            conflicts,
+            warnings,
+            Utc::now(),
+        ))
+    }
+
+    fn from_parts(
+        machine_id: &str,
+        machines: &[MachineState],
+        conflicts: Vec<String>,
+        warnings: Vec<String>,
+        now: DateTime<Utc>,
+    ) -> Self {
+        let this = machines.iter().find(|m| m.machine_id == machine_id);
+        let mut drift: Vec<MachineDrift> = machines
+            .iter()
+            .filter(|m| m.machine_id != machine_id)
+            .map(|other| {
+                let packages = match this {
+                    Some(this) if this.profile == other.profile => {
+                        MachineDiff::between(this, other).packages
+                    }
+                    _ => BTreeMap::new(),
+                };
+                MachineDrift {
+                    machine_id: other.machine_id.clone(),
+                    hostname: other.hostname.clone(),
+                    profile: other.profile.clone(),
+                    last_sync: other.last_sync,
+                    cli_version: other.cli_version.clone(),
+                    behind: now - other.last_sync >= Duration::days(BEHIND_AFTER_DAYS),
+                    incompatible: !other.cli_version.is_empty()
+                        && crate::sync::version_incompatible(&other.cli_version),
+                    packages,
+                }
+            })
+            .collect();
+        drift.sort_by(|a, b| a.machine_id.cmp(&b.machine_id));
+        Self {
+            generated_at: now,
+            machine_id: machine_id.to_string(),
+            machines: drift,
+            conflicts,
+            warnings,
+        }
+    }
+
+    pub fn behind(&self) -> impl Iterator<Item = &MachineDrift> {
+        self.machines.iter().filter(|m| m.behind)
+    }
+
+    /// One line for a notification: what needs attention, or that nothing does
+    pub fn summary(&self) -> String {
+        let behind = self.behind().count();
+        let packages: usize = self
+            .machines
+            .iter()
+            .map(MachineDrift::package_differences)
+            .sum();
+        let incompatible = self.machines.iter().filter(|m| m.incompatible).count();
+        let counts = [
+            (behind, "machine behind on sync", "machines behind on sync"),
+            (packages, "package difference", "package differences"),
+            (
+                incompatible,
+                "machine on an incompatible tether",
+                "machines on an incompatible tether",
+            ),
+            (self.conflicts.len(), "conflict", "conflicts"),
+            (self.warnings.len(), "security warning", "security warnings"),
+        ];
+        let parts: Vec<String> = counts
+            .iter()
+            .filter(|(count, _, _)| *count > 0)
+            .map(|(count, one, many)| format!("{} {}", count, if *count == 1 { one } else { many }))
+            .collect();
+        if self.machines.is_empty() && parts.is_empty() {
+            "No other machines to compare".to_string()
+        } else if parts.is_empty() {
+            format!("All {} machines in step", self.machines.len() + 1)
+        } else {
+            parts.join(", ")
+        }
+    }
+
+    pub fn to_markdown(&self) -> String {
+        let mut out = format!(
+            "# Tether drift report\n\nMade on {} at {}: {}.\n",
+            self.machine_id,
+            self.generated_at
+                .with_timezone(&Local)
+                .format("%Y-%m-%d %H:%M"),
+            self.summary()
+        );
+
+        out.push_str("\n## Machines\n\n");
+        if self.machines.is_empty() {
+            out.push_str("No other machines have synced.\n");
+        }
+        for machine in &self.machines {
+            let mut notes = Vec::new();
+            if machine.behind {
+                notes.push("behind".to_string());
+            }
+            if machine.incompatible {
+                notes.push(format!("incompatible tether {}", machine.cli_version));
+            }
+            let differences = machine.package_differences();
+            if differences > 0 {
+                notes.push(format!(
+                    "{} package difference{}",
+                    differences,
+                    if differences == 1 { "" } else { "s" }
+                ));
+            }
+            out.push_str(&format!(
+                "- **{}** ({}), last synced {}{}\n",
+                machine.machine_id,
+                machine.profile.as_deref().unwrap_or("no profile"),
+                machine
+                    .last_sync
+                    .with_timezone(&Local)
+                    .format("%Y-%m-%d %H:%M"),
+                if notes.is_empty() {
+                    String::new()
+                } else {
+                    format!(": {}", notes.join(", "))
+                }
+            ));
+            for (manager, changes) in &machine.packages {
+                if !changes.removed.is_empty() {
+                    out.push_str(&format!(
+                        "  - {} missing: {}\n",
+                        manager,
+                        changes.removed.join(", ")
+                    ));
+                }
+                if !changes.added.is_empty() {
+                    out.push_str(&format!(
+                        "  - {} only there: {}\n",
+                        manager,
+                        changes.added.join(", ")
+                    ));
+                }
+            }
+        }
+
+        if !self.conflicts.is_empty() {
+            out.push_str("\n## Conflicts\n\nRun `tether resolve` on this machine:\n\n");
+            for file in &self.conflicts {
+                out.push_str(&format!("- {}\n", file));
+            }
+        }
+        if !self.warnings.is_empty() {
+            out.push_str("\n## Security\n\n");
+            for warning in &self.warnings {
+                out.push_str(&format!("- {}\n", warning));
+            }
+        }
+        out
+    }
+
+    /// Deliver the report everywhere `report` config says: a notification, a file and
+    /// a webhook. Every channel is tried; the first failure is returned.
+    pub fn deliver(&self, config: &Config) -> Result<()> {
+        let mut result = Ok(());
+        if config.report.notify {
+            crate::sync::notify_drift_report(&self.summary()).ok();
+        }
+        if let Some(file) = &config.report.file {
+            let path = match file.strip_prefix("~/") {
+                Some(rest) => crate::home_dir()?.join(rest),
+                None => file.into(),
+            };
+            if let Err(e) = crate::sync::atomic_write(&path, self.to_markdown().as_bytes()) {
+                result = result.and(Err(e.context(format!("Failed to write {}", path.display()))));
+            }
+        }
+        if let Some(url) = &config.report.webhook {
+            result = result.and(post_webhook(url, self));
+        }
+        result
+    }
+}
+
+/// POST the report as JSON with curl, which every supported OS has
+fn post_webhook(url: &str, report: &DriftReport) -> Result<()> {
+    use std::io::Write;
+    use std::process::{Command, Stdio};
+
+    let mut child = Command::new("curl")
+        .args(["-fsS", "--max-time", "30", "-X", "POST"])
+        .args([
+            "-H",
+            "Content-Type: application/json",
+            "--data-binary",
+            "@-",
+        ])
+        .arg(url)
+        .stdin(Stdio::piped())
+        .stdout(Stdio::null())
+        .stderr(Stdio::piped())
+        .spawn()
+        .context("Failed to run curl for the report webhook")?;
+    if let Some(mut stdin) = child.stdin.take() {
+        stdin.write_all(&serde_json::to_vec(report)?)?;
+    }
+    let output = child.wait_with_output()?;
+    if !output.status.success() {
+        anyhow::bail!(
+            "Report webhook failed: {}",
+            String::from_utf8_lossy(&output.stderr).trim()
+        );
+    }
+    Ok(())
+}
+
+/// Whether the daemon should make a report
+pub fn is_due(config: &Config, state: &SyncState) -> bool {
+    config.report.enabled
+        && config.has_personal_repo()
+        && state.last_report.is_none_or(|last| {
+            Utc::now() - last >= Duration::days(config.report.every_days.max(1) as i64)
+        })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn machine(id: &str, profile: &str, days_ago: i64, brew: &[&str]) -> MachineState {
+        let mut machine = MachineState::new(id);
+        machine.profile = Some(profile.to_string());
+        machine.last_sync = Utc::now() - Duration::days(days_ago);
+        machine.packages.insert(
+            "brew_formulae".to_string(),
+            brew.iter().map(|p| p.to_string()).collect(),
+        );
+        machine
+    }
+
+    #[test]
+    fn test_drift_report() {
+        let machines = vec![
+            machine("laptop", "dev", 0, &["git", "jq", "ripgrep"]),
+            machine("desktop", "dev", 5, &["git", "htop"]),
+            machine("server", "ops", 0, &["nginx"]),
+        ];
+        let report = DriftReport::from_parts("laptop", &machines, vec![], vec![], Utc::now());
+
+        assert_eq!(report.machines.len(), 2);
+        let desktop = &report.machines[0];
+        assert_eq!(desktop.machine_id, "desktop");
+        assert!(desktop.behind);
+        assert_eq!(desktop.packages["brew_formulae"].added, vec!["htop"]);
+        assert_eq!(
+            desktop.packages["brew_formulae"].removed,
+            vec!["jq", "ripgrep"]
+        );
+        // Another profile's packages aren't drift
+        let server = &report.machines[1];
+        assert!(!server.behind && server.packages.is_empty());
+
+        assert_eq!(
+            report.summary(),
+            "1 machine behind on sync, 3 package differences"
+        );
+        let markdown = report.to_markdown();
+        assert!(markdown.contains("**desktop** (dev)"));
+        assert!(markdown.contains("brew_formulae missing: jq, ripgrep"));
+
+        let quiet = DriftReport::from_parts("laptop", &machines[..1], vec![], vec![], Utc::now());
+        assert_eq!(quiet.summary(), "No other machines to compare");
+    }
+}
@@ -0,0 +1,138 @@
+//! Dotfiles that are behind the sync remote, found before an edit turns them into
+//! a conflict (`tether check` and the daemon's stale-file warning).
+//!
+//! Works against `origin/main` as of the last fetch; nothing is merged or written.
+
+use crate::config::Config;
+use crate::sync::{GitBackend, SyncState};
+use anyhow::Result;
+use std::collections::HashSet;
+use std::path::Path;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Staleness {
+    /// Remote changed, local didn't: a sync pulls it cleanly
+    Behind,
+    /// Both changed: the next sync raises a conflict
+    Diverged,
+}
+
+#[derive(Debug, Clone)]
+pub struct StaleFile {
+    /// Home-relative path
+    pub file: String,
+    pub staleness: Staleness,
+    pub remote_hash: String,
+}
+
+/// How a file stands against the remote, from its local hash (`None` if missing),
+/// the hash it had at the last sync and the remote hash. `None` when it isn't stale.
+pub fn classify(
+    local_hash: Option<&str>,
+    last_synced_hash: Option<&str>,
+    remote_hash: &str,
+) -> Option<Staleness> {
+    // Same content, or the remote copy is what this machine last synced
+    if local_hash == Some(remote_hash) || last_synced_hash == Some(remote_hash) {
+        return None;
+    }
+    if local_hash == last_synced_hash {
+        Some(Staleness::Behind)
+    } else {
+        Some(Staleness::Diverged)
+    }
+}
+
+/// Configured dotfiles whose copy on `origin/main` is newer than what this machine
+/// last synced. `only` restricts the check to one home-relative path. Call after
+/// [`GitBackend::fetch`].
+pub fn find_stale(
+    config: &Config,
+    state: &SyncState,
+    git: &GitBackend,
+    home: &Path,
+    only: Option<&str>,
+) -> Result<Vec<StaleFile>> {
+    let changed: HashSet<String> = git.remote_changed_files()?.into_iter().collect();
+    if changed.is_empty() {
+        return Ok(Vec::new());
+    }
+
+    let sync_path = git.sync_path();
+    let machine_id = &state.machine_id;
+    let encrypted = config.security.encrypt_dotfiles;
+    let key = if encrypted {
+        Some(crate::security::get_encryption_key()?)
+    } else {
+        None
+    };
+
+    let mut stale = Vec::new();
+    for entry in config.effective_dotfiles(machine_id) {
+        if !entry.is_safe_path() {
+            continue;
+        }
+        let pattern = entry.path();
+        let profile = config.dotfile_profile(machine_id, pattern);
+        let shared = config.is_dotfile_shared(machine_id, pattern);
+        for file in crate::sync::expand_dotfile_glob(pattern, home) {
+            if only.is_some_and(|o| o != file) {
+                continue;
+            }
+            let repo_path = crate::sync::resolve_dotfile_repo_path(
+                sync_path, &file, encrypted, profile, shared,
+            );
+            if !changed.contains(&repo_path) {
+                continue;
+            }
+            // Deleted on the remote: deletion propagation handles it
+            let Ok(content) = git.show_remote(&repo_path) else {
+                continue;
+            };
+            let remote = match &key {
+                Some(key) => match crate::security::decrypt(&content, key) {
+                    Ok(plaintext) => plaintext,
+                    Err(e) => {
+                        log::warn!("Failed to decrypt remote {}: {}", file, e);
+                        continue;
+                    }
+                },
+                None => content,
+            };
+            let remote_hash = crate::sha256_hex(&remote);
+            let local_hash = std::fs::read(home.join(&file))
+                .ok()
+                .map(|c| crate::sha256_hex(&c));
+            let last_synced = state.files.get(&file).map(|f| f.hash.as_str());
+            if let Some(staleness) = classify(local_hash.as_deref(), last_synced, &remote_hash) {
+                stale.push(StaleFile {
+                    file,
+                    staleness,
+                    remote_hash,
+                });
+            }
+        }
+    }
+    Ok(stale)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_classify() {
+        // Local untouched since the last sync, remote moved on
+        assert_eq!(classify(Some("a"), Some("a"), "b"), Some(Staleness::Behind));
+        // Both moved on
+        assert_eq!(
+            classify(Some("c"), Some("a"), "b"),
+            Some(Staleness::Diverged)
+        );
+        // Only local moved on, or both already agree
+        assert_eq!(classify(Some("c"), Some("a"), "a"), None);
+        assert_eq!(classify(Some("b"), Some("a"), "b"), None);
+        // New on the remote and absent here
+        assert_eq!(classify(None, None, "b"), Some(Staleness::Behind));
+    }
+}
@@ -1,7 +1,7 @@
 use anyhow::Result;
 use chrono::{DateTime, Utc};
 use serde::{Deserialize, Serialize};
-use std::collections::HashMap;
+use std::collections::{BTreeMap, BTreeSet, HashMap};
 use std::path::PathBuf;
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -20,9 +20,25 @@ pub struct SyncState {
     /// Hash of deferred_casks for change detection (notify once)
     #[serde(default)]
     pub deferred_casks_hash: Option<String>,
+    /// Installs waiting for `tether packages approve` (machine state key -> packages),
+    /// with `packages.approve_installs`
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    pub pending_installs: BTreeMap<String, Vec<String>>,
     /// Dotfile paths dismissed when prompted to import from other profiles
     #[serde(default, skip_serializing_if = "std::collections::HashSet::is_empty")]
     pub dismissed_imports: std::collections::HashSet<String>,
+    /// Files deleted on this machine whose deletion was propagated (state key -> tombstone)
+    #[serde(default, skip_serializing_if = "HashMap::is_empty")]
+    pub deleted_files: HashMap<String, Tombstone>,
+    /// Result of the last check for a public sync repo
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub repo_visibility: Option<crate::sync::visibility::VisibilityCheck>,
+    /// When the daemon last made a drift report
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub last_report: Option<DateTime<Utc>>,
+    /// Sync repo size, sampled at most daily
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub repo_sizes: Vec<crate::sync::growth::RepoSize>,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -30,6 +46,30 @@ pub struct FileState {
     pub hash: String,
     pub last_modified: DateTime<Utc>,
     pub synced: bool,
+    /// Size and mtime of the local file when `hash` was last confirmed, so unchanged
+    /// files can skip re-hashing
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub disk: Option<DiskFingerprint>,
+    /// Size and mtime of the repo copy when it last matched the local file, so an
+    /// untouched pair can skip decryption entirely
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub repo: Option<DiskFingerprint>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct DiskFingerprint {
+    pub size: u64,
+    pub mtime: DateTime<Utc>,
+}
+
+impl DiskFingerprint {
+    pub fn of(path: &std::path::Path) -> Option<Self> {
+        let meta = std::fs::metadata(path).ok()?;
+        Some(Self {
+            size: meta.len(),
+            mtime: meta.modified().ok()?.into(),
+        })
+    }
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -45,6 +85,14 @@ pub struct PackageState {
     pub hash: String,
 }
 
+/// Record of a synced file deleted on a machine, so other machines remove it too
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Tombstone {
+    /// Repo path the file was stored under before deletion
+    pub repo_path: String,
+    pub deleted_at: DateTime<Utc>,
+}
+
 /// Tracks a checkout of a project on this machine
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct CheckoutInfo {
@@ -61,6 +109,9 @@ pub struct MachineState {
     pub last_sync: DateTime<Utc>,
     #[serde(default)]
     pub os_version: String,
+    /// CPU architecture (e.g., "aarch64", "x86_64")
+    #[serde(default)]
+    pub arch: String,
     #[serde(default)]
     pub cli_version: String,
     /// File paths and their hashes
@@ -91,6 +142,38 @@ pub struct MachineState {
     /// Profile assigned to this machine (if any)
     #[serde(default, skip_serializing_if = "Option::is_none")]
     pub profile: Option<String>,
+    /// Files deleted on this machine (state key -> tombstone); other machines
+    /// delete their unchanged copies
+    #[serde(default)]
+    pub deleted_files: HashMap<String, Tombstone>,
+}
+
+/// Human-readable OS name and version, e.g. "macOS 14.5" or "Ubuntu 24.04 LTS"
+fn os_version() -> String {
+    if cfg!(target_os = "macos") {
+        let version = std::process::Command::new("sw_vers")
+            .arg("-productVersion")
+            .output()
+            .ok()
+            .filter(|output| output.status.success())
+            .map(|output| String::from_utf8_lossy(&output.stdout).trim().to_string());
+        return match version {
+            Some(version) if !version.is_empty() => format!("macOS {}", version),
+            _ => "macOS".to_string(),
+        };
+    }
+    if cfg!(target_os = "linux") {
+        let pretty_name = std::fs::read_to_string("/etc/os-release")
+            .ok()
+            .and_then(|release| {
+                release.lines().find_map(|line| {
+                    line.strip_prefix("PRETTY_NAME=")
+                        .map(|name| name.trim_matches('"').to_string())
+                })
+            });
+        return pretty_name.unwrap_or_else(|| "Linux".to_string());
+    }
+    std::env::consts::OS.to_string()
 }
 
 impl Default for MachineState {
@@ -101,17 +184,13 @@ impl Default for MachineState {
 
 impl MachineState {
     pub fn new(machine_id: &str) -> Self {
-        let hostname = hostname::get()
-            .ok()
-            .and_then(|h| h.into_string().ok())
-            .unwrap_or_else(|| "unknown".to_string());
-
-        Self {
+        let mut state = Self {
             machine_id: machine_id.to_string(),
-            hostname,
+            hostname: String::new(),
             last_sync: Utc::now(),
             os_version: String::new(),
-            cli_version: env!("CARGO_PKG_VERSION").to_string(),
+            arch: String::new(),
+            cli_version: String::new(),
             files: HashMap::new(),
             packages: HashMap::new(),
             removed_packages: HashMap::new(),
@@ -121,7 +200,21 @@ impl MachineState {
             ignored_project_configs: HashMap::new(),
             checkouts: HashMap::new(),
             profile: None,
-        }
+            deleted_files: HashMap::new(),
+        };
+        state.refresh_system_info();
+        state
+    }
+
+    /// Record this machine's hostname, OS, architecture and tether version
+    pub fn refresh_system_info(&mut self) {
+        self.hostname = hostname::get()
+            .ok()
+            .and_then(|h| h.into_string().ok())
+            .unwrap_or_else(|| "unknown".to_string());
+        self.os_version = os_version();
+        self.arch = std::env::consts::ARCH.to_string();
+        self.cli_version = env!("CARGO_PKG_VERSION").to_string();
     }
 
     /// Maximum allowed items in deserialized collections (DoS protection)
@@ -129,7 +222,7 @@ impl MachineState {
     const MAX_FILES: usize = 50_000;
 
     /// Validate package name is safe for shell usage
-    fn is_safe_package_name(name: &str) -> bool {
+    pub fn is_safe_package_name(name: &str) -> bool {
         // Reject empty, too long, or names with shell metacharacters
         !name.is_empty()
             && name.len() <= 256
@@ -139,10 +232,10 @@ impl MachineState {
     /// Validate and sanitize machine state after deserialization
     fn validate(&mut self) -> Result<()> {
         // Limit files
-        if self.files.len() > Self::MAX_FILES {
+        if self.files.len() > Self::MAX_FILES || self.deleted_files.len() > Self::MAX_FILES {
             anyhow::bail!(
                 "Machine state contains too many files ({})",
-                self.files.len()
+                self.files.len().max(self.deleted_files.len())
             );
         }
 
@@ -167,26 +260,35 @@ impl MachineState {
         Ok(())
     }
 
+    /// Where `machine_id`'s state lives in the sync repo (plaintext form; see
+    /// [`crate::sync::metadata`])
+    pub fn repo_file(sync_path: &std::path::Path, machine_id: &str) -> std::path::PathBuf {
+        sync_path
+            .join("machines")
+            .join(format!("{}.json", machine_id))
+    }
+
     /// Load machine state from sync repo
     pub fn load_from_repo(sync_path: &std::path::Path, machine_id: &str) -> Result<Option<Self>> {
-        let path = sync_path
-            .join("machines")
-            .join(format!("{}.json", machine_id));
-        if !path.exists() {
+        let path = Self::repo_file(sync_path, machine_id);
+        let Some(content) = crate::sync::metadata::read(&path)? else {
             return Ok(None);
-        }
-        let content = std::fs::read_to_string(path)?;
-        let mut state: Self = serde_json::from_str(&content)?;
+        };
+        Self::parse(&content).map(Some)
+    }
+
+    /// Parse and validate a machine state file's contents
+    pub fn parse(content: &[u8]) -> Result<Self> {
+        let mut state: Self = serde_json::from_slice(content)?;
         state.validate()?;
-        Ok(Some(state))
+        Ok(state)
     }
 
     /// Save machine state to sync repo
     pub fn save_to_repo(&self, sync_path: &std::path::Path) -> Result<()> {
-        let machines_dir = sync_path.join("machines");
-        let path = machines_dir.join(format!("{}.json", self.machine_id));
+        let path = Self::repo_file(sync_path, &self.machine_id);
         let content = serde_json::to_string_pretty(self)?;
-        crate::sync::atomic_write(&path, content.as_bytes())
+        crate::sync::metadata::write(&path, content.as_bytes())
     }
 
     /// List all machines in sync repo
@@ -196,19 +298,22 @@ impl MachineState {
             return Ok(Vec::new());
         }
 
-        let mut machines = Vec::new();
+        let mut ids = std::collections::BTreeSet::new();
         for entry in std::fs::read_dir(&machines_dir)? {
-            let entry = entry?;
-            let path = entry.path();
-            if path.extension().map(|e| e == "json").unwrap_or(false) {
-                if let Ok(content) = std::fs::read_to_string(&path) {
-                    if let Ok(mut state) = serde_json::from_str::<MachineState>(&content) {
-                        // Skip invalid machine states
-                        if state.validate().is_ok() {
-                            machines.push(state);
-                        }
-                    }
-                }
+            let name = entry?.file_name();
+            let name = name.to_string_lossy();
+            if let Some(id) = crate::sync::metadata::plain_name(&name).strip_suffix(".json") {
+                ids.insert(id.to_string());
+            }
+        }
+
+        let mut machines = Vec::new();
+        for id in ids {
+            // Skip invalid machine states
+            match Self::load_from_repo(sync_path, &id) {
+                Ok(Some(state)) => machines.push(state),
+                Ok(None) => {}
+                Err(e) => log::warn!("Skipping machine state {}: {}", id, e),
             }
         }
         Ok(machines)
@@ -243,10 +348,111 @@ impl MachineState {
     }
 }
 
+/// Identifies one side of a `MachineDiff`
+#[derive(Debug, Clone, Serialize)]
+pub struct MachineSummary {
+    pub machine_id: String,
+    pub hostname: String,
+    pub profile: Option<String>,
+    pub cli_version: String,
+    pub last_sync: DateTime<Utc>,
+}
+
+/// Synced files (dotfiles and project configs) that differ between two machines
+#[derive(Debug, Clone, Default, PartialEq, Serialize)]
+pub struct FileChanges {
+    /// Only on the `to` machine
+    pub added: Vec<String>,
+    /// Only on the `from` machine
+    pub removed: Vec<String>,
+    /// On both, with different content
+    pub changed: Vec<String>,
+}
+
+/// One package manager's packages that differ between two machines
+#[derive(Debug, Clone, Default, PartialEq, Serialize)]
+pub struct PackageChanges {
+    /// Only installed on the `to` machine
+    pub added: Vec<String>,
+    /// Only installed on the `from` machine
+    pub removed: Vec<String>,
+}
+
+/// How the `to` machine differs from `from`. Serialized as the output of
+/// `tether machines diff --json`, so fields are only ever added.
+#[derive(Debug, Clone, Serialize)]
+pub struct MachineDiff {
+    pub from: MachineSummary,
+    pub to: MachineSummary,
+    pub files: FileChanges,
+    /// Package manager -> changes, for managers with any
+    pub packages: BTreeMap<String, PackageChanges>,
+}
+
+impl MachineSummary {
+    fn of(machine: &MachineState) -> Self {
+        Self {
+            machine_id: machine.machine_id.clone(),
+            hostname: machine.hostname.clone(),
+            profile: machine.profile.clone(),
+            cli_version: machine.cli_version.clone(),
+            last_sync: machine.last_sync,
+        }
+    }
+}
+
+impl MachineDiff {
+    pub fn between(from: &MachineState, to: &MachineState) -> Self {
+        let mut files = FileChanges::default();
+        for (file, hash) in &to.files {
+            match from.files.get(file) {
+                None => files.added.push(file.clone()),
+                Some(other) if other != hash => files.changed.push(file.clone()),
+                Some(_) => {}
+            }
+        }
+        files.removed = from
+            .files
+            .keys()
+            .filter(|file| !to.files.contains_key(*file))
+            .cloned()
+            .collect();
+        files.added.sort();
+        files.removed.sort();
+        files.changed.sort();
+
+        let mut packages = BTreeMap::new();
+        let managers: BTreeSet<_> = from.packages.keys().chain(to.packages.keys()).collect();
+        for manager in managers {
+            let installed = |machine: &MachineState| -> BTreeSet<String> {
+                machine
+                    .packages
+                    .get(manager)
+                    .map(|list| list.iter().cloned().collect())
+                    .unwrap_or_default()
+            };
+            let (before, after) = (installed(from), installed(to));
+            let changes = PackageChanges {
+                added: after.difference(&before).cloned().collect(),
+                removed: before.difference(&after).cloned().collect(),
+            };
+            if !changes.added.is_empty() || !changes.removed.is_empty() {
+                packages.insert(manager.clone(), changes);
+            }
+        }
+
+        Self {
+            from: MachineSummary::of(from),
+            to: MachineSummary::of(to),
+            files,
+            packages,
+        }
+    }
+}
+
 impl SyncState {
     pub fn state_path() -> Result<PathBuf> {
-        let home = crate::home_dir()?;
-        Ok(home.join(".tether").join("state.json"))
+        Ok(crate::config::Config::config_dir()?.join("state.json"))
     }
 
     pub fn load() -> Result<Self> {
@@ -274,7 +480,12 @@ impl SyncState {
             last_upgrade_with_updates: None,
             deferred_casks: Vec::new(),
             deferred_casks_hash: None,
+            pending_installs: BTreeMap::new(),
             dismissed_imports: std::collections::HashSet::new(),
+            deleted_files: HashMap::new(),
+            repo_visibility: None,
+            last_report: None,
+            repo_sizes: Vec::new(),
         }
     }
 
@@ -285,6 +496,25 @@ impl SyncState {
             .unwrap_or_else(|| "unknown".to_string())
     }
 
+    /// Number of package installs waiting for approval
+    pub fn pending_install_count(&self) -> usize {
+        self.pending_installs.values().map(Vec::len).sum()
+    }
+
+    /// Drop `name` from the approval queue, returning whether it was queued
+    pub fn take_pending_install(&mut self, manager_key: &str, name: &str) -> bool {
+        let Some(packages) = self.pending_installs.get_mut(manager_key) else {
+            return false;
+        };
+        let before = packages.len();
+        packages.retain(|p| p != name);
+        let taken = packages.len() != before;
+        if packages.is_empty() {
+            self.pending_installs.remove(manager_key);
+        }
+        taken
+    }
+
     pub fn update_file(&mut self, path: &str, hash: String) {
         self.files.insert(
             path.to_string(),
@@ -292,10 +522,61 @@ impl SyncState {
                 hash,
                 last_modified: Utc::now(),
                 synced: false,
+                disk: None,
+                repo: None,
             },
         );
     }
 
+    /// Whether `path` still has the size and mtime recorded for `key`. A match means
+    /// its content can be assumed unchanged without reading it.
+    pub fn disk_unchanged(&self, key: &str, path: &std::path::Path) -> bool {
+        self.files
+            .get(key)
+            .and_then(|f| f.disk.as_ref())
+            .is_some_and(|disk| DiskFingerprint::of(path).as_ref() == Some(disk))
+    }
+
+    /// Record `path`'s current size and mtime for `key` after confirming its hash
+    pub fn record_disk(&mut self, key: &str, path: &std::path::Path) {
+        if let Some(file) = self.files.get_mut(key) {
+            file.disk = DiskFingerprint::of(path);
+        }
+    }
+
+    /// Hash of the local file at `path`, reusing the hash recorded for `key` when its
+    /// size and mtime haven't changed. `None` if the file can't be read.
+    pub fn local_hash(&self, key: &str, path: &std::path::Path) -> Option<String> {
+        if self.disk_unchanged(key, path) {
+            return self.files.get(key).map(|f| f.hash.clone());
+        }
+        std::fs::read(path).ok().map(|c| crate::sha256_hex(&c))
+    }
+
+    /// Whether neither the local file nor the repo copy has changed since they were
+    /// last recorded as matching via [`record_in_sync`](Self::record_in_sync)
+    pub fn in_sync_on_disk(
+        &self,
+        key: &str,
+        local: &std::path::Path,
+        repo: &std::path::Path,
+    ) -> bool {
+        self.disk_unchanged(key, local)
+            && self
+                .files
+                .get(key)
+                .and_then(|f| f.repo.as_ref())
+                .is_some_and(|fp| DiskFingerprint::of(repo).as_ref() == Some(fp))
+    }
+
+    /// Record that the local file and repo copy for `key` hold the content of its hash
+    pub fn record_in_sync(&mut self, key: &str, local: &std::path::Path, repo: &std::path::Path) {
+        if let Some(file) = self.files.get_mut(key) {
+            file.disk = DiskFingerprint::of(local);
+            file.repo = DiskFingerprint::of(repo);
+        }
+    }
+
     pub fn mark_synced(&mut self) {
         self.last_sync = Utc::now();
         for file in self.files.values_mut() {
@@ -309,6 +590,54 @@ mod tests {
     use super::*;
     use tempfile::TempDir;
 
+    #[test]
+    fn test_disk_fingerprint_prescreen() {
+        let tmp = TempDir::new().unwrap();
+        let path = tmp.path().join(".zshrc");
+        std::fs::write(&path, "export A=1").unwrap();
+
+        let mut state = SyncState::new();
+        state.update_file(".zshrc", crate::sha256_hex(b"export A=1"));
+        // Nothing recorded yet: content must be checked
+        assert!(!state.disk_unchanged(".zshrc", &path));
+
+        state.record_disk(".zshrc", &path);
+        assert!(state.disk_unchanged(".zshrc", &path));
+
+        std::fs::write(&path, "export A=22").unwrap();
+        assert!(!state.disk_unchanged(".zshrc", &path));
+        assert!(!state.disk_unchanged(".bashrc", &path));
+    }
+
+    #[test]
+    fn test_in_sync_on_disk_cache() {
+        let tmp = TempDir::new().unwrap();
+        let local = tmp.path().join(".zshrc");
+        let repo = tmp.path().join(".zshrc.enc");
+        std::fs::write(&local, "export A=1").unwrap();
+        std::fs::write(&repo, "ciphertext").unwrap();
+
+        let mut state = SyncState::new();
+        let hash = crate::sha256_hex(b"export A=1");
+        state.update_file(".zshrc", hash.clone());
+        assert!(!state.in_sync_on_disk(".zshrc", &local, &repo));
+
+        state.record_in_sync(".zshrc", &local, &repo);
+        assert!(state.in_sync_on_disk(".zshrc", &local, &repo));
+        assert_eq!(state.local_hash(".zshrc", &local), Some(hash));
+
+        // A pulled repo copy invalidates the pair but not the local hash
+        std::fs::write(&repo, "new ciphertext").unwrap();
+        assert!(!state.in_sync_on_disk(".zshrc", &local, &repo));
+        assert!(state.disk_unchanged(".zshrc", &local));
+
+        std::fs::write(&local, "export A=22").unwrap();
+        assert_eq!(
+            state.local_hash(".zshrc", &local),
+            Some(crate::sha256_hex(b"export A=22"))
+        );
+    }
+
     #[test]
     fn test_safe_package_names() {
         assert!(MachineState::is_safe_package_name("git"));
@@ -405,6 +734,40 @@ mod tests {
         assert!(npm.contains(&"c".to_string()));
     }
 
+    #[test]
+    fn test_machine_diff_between() {
+        let mut laptop = MachineState::new("laptop");
+        laptop.files.insert(".zshrc".to_string(), "a".to_string());
+        laptop.files.insert(".vimrc".to_string(), "v".to_string());
+        laptop
+            .packages
+            .insert("npm".to_string(), vec!["a".to_string(), "b".to_string()]);
+        laptop
+            .packages
+            .insert("gem".to_string(), vec!["rails".to_string()]);
+
+        let mut desktop = MachineState::new("desktop");
+        desktop.files.insert(".zshrc".to_string(), "b".to_string());
+        desktop
+            .files
+            .insert(".gitconfig".to_string(), "g".to_string());
+        desktop
+            .packages
+            .insert("npm".to_string(), vec!["b".to_string(), "c".to_string()]);
+        desktop
+            .packages
+            .insert("gem".to_string(), vec!["rails".to_string()]);
+
+        let diff = MachineDiff::between(&laptop, &desktop);
+        assert_eq!(diff.from.machine_id, "laptop");
+        assert_eq!(diff.files.added, vec![".gitconfig"]);
+        assert_eq!(diff.files.removed, vec![".vimrc"]);
+        assert_eq!(diff.files.changed, vec![".zshrc"]);
+        assert_eq!(diff.packages.len(), 1);
+        assert_eq!(diff.packages["npm"].added, vec!["c"]);
+        assert_eq!(diff.packages["npm"].removed, vec!["a"]);
+    }
+
     #[test]
     fn test_compute_union_packages_empty() {
         let union = MachineState::compute_union_packages(&[]);
@@ -532,6 +895,7 @@ mod tests {
         let loaded: MachineState = serde_json::from_str(old_json).unwrap();
         assert_eq!(loaded.cli_version, "");
         assert_eq!(loaded.os_version, "");
+        assert_eq!(loaded.arch, "");
         assert!(loaded.checkouts.is_empty());
         assert!(loaded.removed_packages.is_empty());
         assert!(loaded.dotfiles.is_empty());
@@ -576,4 +940,23 @@ mod tests {
         assert!(loaded.last_upgrade.is_none());
         assert_eq!(loaded.hash, "abc123");
     }
+
+    #[test]
+    fn test_take_pending_install() {
+        let mut state = SyncState::new();
+        state.pending_installs.insert(
+            "npm".to_string(),
+            vec!["prettier".to_string(), "typescript".to_string()],
+        );
+        state
+            .pending_installs
+            .insert("brew_formulae".to_string(), vec!["jq".to_string()]);
+        assert_eq!(state.pending_install_count(), 3);
+
+        assert!(state.take_pending_install("brew_formulae", "jq"));
+        assert!(!state.pending_installs.contains_key("brew_formulae"));
+        assert!(!state.take_pending_install("npm", "jq"));
+        assert!(state.take_pending_install("npm", "prettier"));
+        assert_eq!(state.pending_installs["npm"], vec!["typescript"]);
+    }
 }
@@ -0,0 +1,139 @@
+//! `dotfiles.verify` checks: after a remote version of a dotfile is written, run its
+//! check and put the previous copy back if it fails, so a broken rc file pulled from
+//! another machine never stays in place.
+
+use crate::cli::Output;
+use crate::config::Config;
+use anyhow::Result;
+use std::path::Path;
+use std::process::{Command, Stdio};
+use std::time::{Duration, Instant};
+
+/// A check that hasn't finished by then is treated as failed
+const VERIFY_TIMEOUT: Duration = Duration::from_secs(10);
+
+/// Run a check command. The error says why it failed (exit status and the first line
+/// of its stderr, or the timeout).
+pub fn run_check(args: &[String]) -> std::result::Result<(), String> {
+    let (program, rest) = args.split_first().ok_or("empty command")?;
+    let mut child = Command::new(program)
+        .args(rest)
+        .stdin(Stdio::null())
+        .stdout(Stdio::null())
+        .stderr(Stdio::piped())
+        .spawn()
+        .map_err(|e| format!("could not run {}: {}", program, e))?;
+
+    let started = Instant::now();
+    let status = loop {
+        match child.try_wait().map_err(|e| e.to_string())? {
+            Some(status) => break status,
+            None if started.elapsed() > VERIFY_TIMEOUT => {
+                let _ = child.kill();
+                let _ = child.wait();
+                return Err(format!("timed out after {}s", VERIFY_TIMEOUT.as_secs()));
+            }
+            None => std::thread::sleep(Duration::from_millis(50)),
+        }
+    };
+    if status.success() {
+        return Ok(());
+    }
+
+    let mut stderr = String::new();
+    if let Some(mut pipe) = child.stderr.take() {
+        let _ = std::io::Read::read_to_string(&mut pipe, &mut stderr);
+    }
+    let code = status
+        .code()
+        .map(|c| format!("exit {}", c))
+        .unwrap_or_else(|| "killed".to_string());
+    match stderr.lines().find(|l| !l.trim().is_empty()) {
+        Some(line) => Err(format!("{}: {}", code, line.trim())),
+        None => Err(code),
+    }
+}
+
+/// Check a dotfile that was just written from the remote. On failure the copy saved in
+/// `backup_dir` is restored (or the file removed, if it didn't exist before) and false
+/// is returned, so the caller leaves the file's synced state alone.
+pub fn verify_applied(
+    config: &Config,
+    home: &Path,
+    file: &str,
+    backup_dir: Option<&Path>,
+) -> Result<bool> {
+    let args = match config.dotfiles.verify_command(file, home) {
+        Ok(Some(args)) => args,
+        Ok(None) => return Ok(true),
+        Err(e) => {
+            Output::warning(&format!("  {} (verify skipped: {})", file, e));
+            return Ok(true);
+        }
+    };
+
+    let Err(reason) = run_check(&args) else {
+        return Ok(true);
+    };
+
+    let local_file = home.join(file);
+    let previous = backup_dir.map(|d| d.join("dotfiles").join(file));
+    match previous.filter(|p| p.exists()) {
+        Some(previous) => {
+            std::fs::copy(&previous, &local_file)?;
+        }
+        None => {
+            std::fs::remove_file(&local_file)?;
+        }
+    }
+    Output::warning(&format!(
+        "  {} (remote version failed `{}` ({}), kept previous copy)",
+        file,
+        config.dotfiles.verify.get(file).map_or("", |c| c.as_str()),
+        reason
+    ));
+    Ok(false)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_run_check_reports_failure() {
+        let ok = run_check(&["sh".to_string(), "-c".to_string(), "exit 0".to_string()]);
+        assert!(ok.is_ok());
+
+        let err = run_check(&[
+            "sh".to_string(),
+            "-c".to_string(),
+            "echo 'syntax error near fi' >&2; exit 2".to_string(),
+        ])
+        .unwrap_err();
+        assert_eq!(err, "exit 2: syntax error near fi");
+    }
+
+    #[test]
+    fn test_verify_applied_restores_backup() {
+        let temp = tempfile::TempDir::new().unwrap();
+        let home = temp.path().join("home");
+        let backup = temp.path().join("backup");
+        std::fs::create_dir_all(&home).unwrap();
+        std::fs::create_dir_all(backup.join("dotfiles")).unwrap();
+        std::fs::write(home.join(".zshrc"), "if true; then\n").unwrap();
+        std::fs::write(backup.join("dotfiles/.zshrc"), "echo ok\n").unwrap();
+
+        let mut config = Config::default();
+        config
+            .dotfiles
+            .verify
+            .insert(".zshrc".to_string(), "sh -n {file}".to_string());
+
+        assert!(!verify_applied(&config, &home, ".zshrc", Some(&backup)).unwrap());
+        assert_eq!(
+            std::fs::read_to_string(home.join(".zshrc")).unwrap(),
+            "echo ok\n"
+        );
+        assert!(verify_applied(&config, &home, ".zshrc", Some(&backup)).unwrap());
+    }
+}